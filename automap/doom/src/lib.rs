@@ -0,0 +1,699 @@
+//! The automap. A top-down drawing of the lines of the level the player has
+//! seen, shown full screen or over the player's view.
+//!
+//! Keys while the map is open:
+//! - `Tab`: close the map
+//! - arrows: pan, when not following the player
+//! - `-` and `=`: zoom out and in
+//! - `0`: toggle between the whole map and the previous zoom
+//! - `f`: follow the player
+//! - `g`: grid
+//! - `m`: mark the centre of the view, `c` clears the marks
+//! - `r`: rotate the map so the player always faces up
+//! - `o`: switch between full screen and overlay
+//!
+//! Doom source name `am_map`
+
+use gamestate_traits::glam::Vec2;
+use gamestate_traits::util::get_num_sprites;
+use gamestate_traits::{
+    AutomapState, GameTraits, LineDefFlags, PixelBuffer, Player, PlayerState, PowerType, Scancode, Sector, SubsystemTrait, TICRATE
+};
+use hud_util::{HUDFont, HUDString, HUD_STRING};
+use std::f32::consts::FRAC_PI_2;
+use wad::types::{WadPalette, WadPatch};
+use wad::WadData;
+
+// Palette indexes
+const WALL: usize = 176;
+const TELEPORTER: usize = 184;
+const FLOOR_CHANGE: usize = 64;
+const CEILING_CHANGE: usize = 231;
+const TWO_SIDED: usize = 96;
+const SECRET: usize = 252;
+/// Lines shown by the computer map that haven't been seen yet
+const UNSEEN: usize = 99;
+const GRID: usize = 104;
+const THING: usize = 112;
+const PLAYER: usize = 209;
+/// Players in a netgame: green, grey, brown, red
+const NET_PLAYERS: [usize; 4] = [112, 96, 64, 184];
+/// Players with partial invisibility in a netgame
+const INVISIBLE: usize = 246;
+
+/// Locked door line specials and the colour of their key
+const LOCKED_DOORS: [([i16; 4], usize); 3] = [
+    ([26, 32, 99, 133], 204),
+    ([27, 34, 136, 137], 231),
+    ([28, 33, 134, 135], 175),
+];
+const TELEPORT_SPECIAL: i16 = 39;
+
+const PLAYER_RADIUS: f32 = 16.0;
+const GRID_SIZE: f32 = 128.0;
+/// Largest scale in screen pixels per map unit, at 320x200
+const MAX_SCALE: f32 = 200.0 / (2.0 * PLAYER_RADIUS);
+/// Zoom step per key press
+const ZOOM: f32 = 1.04;
+/// Pan step per key press in screen pixels, at 320x200
+const PAN: f32 = 4.0;
+const MAX_MARKS: usize = 10;
+/// Typed while the map is open to show all lines, then things, then neither
+const CHEAT: [Scancode; 4] = [Scancode::I, Scancode::D, Scancode::D, Scancode::T];
+
+/// Player arrow pointing along +x, the unit is `8 * PLAYER_RADIUS / 7`
+const PLAYER_ARROW: [[(f32, f32); 2]; 7] = [
+    [(-0.875, 0.0), (1.0, 0.0)],
+    [(1.0, 0.0), (0.5, 0.25)],
+    [(1.0, 0.0), (0.5, -0.25)],
+    [(-0.875, 0.0), (-1.125, 0.25)],
+    [(-0.875, 0.0), (-1.125, -0.25)],
+    [(-0.625, 0.0), (-0.875, 0.25)],
+    [(-0.625, 0.0), (-0.875, -0.25)],
+];
+/// Triangle pointing along +x drawn for things
+const THING_TRIANGLE: [[(f32, f32); 2]; 3] = [
+    [(-0.5, -0.7), (1.0, 0.0)],
+    [(1.0, 0.0), (-0.5, 0.7)],
+    [(-0.5, 0.7), (-0.5, -0.7)],
+];
+
+/// A line of the level as it is drawn, in map space
+struct MapLine {
+    a: Vec2,
+    b: Vec2,
+    colour: usize,
+}
+
+/// A thing or player as it is drawn, in map space
+struct Marker {
+    pos: Vec2,
+    angle: f32,
+    colour: usize,
+}
+
+/// Map space to screen space for one frame
+struct View {
+    centre: Vec2,
+    rotation: Vec2,
+    /// Screen pixels per map unit
+    scale: f32,
+    half_width: f32,
+    half_height: f32,
+}
+
+impl View {
+    fn to_screen(&self, point: Vec2) -> Vec2 {
+        let d = self.rotation.rotate(point - self.centre) * self.scale;
+        Vec2::new(self.half_width + d.x, self.half_height - d.y)
+    }
+}
+
+pub struct AutoMap {
+    palette: WadPalette,
+    font: HUDFont,
+    mark_nums: [WadPatch; 10],
+    screen_width: i32,
+    screen_height: i32,
+    state: AutomapState,
+    /// Open over the player's view instead of full screen
+    overlay: bool,
+    follow: bool,
+    rotate: bool,
+    grid: bool,
+    /// 0 shows what the player has seen, 1 adds all lines, 2 adds things
+    cheating: u8,
+    /// How much of `CHEAT` has been typed
+    cheat_pos: usize,
+    marks: [Option<Vec2>; MAX_MARKS],
+    next_mark: usize,
+    /// Screen pixels per map unit, at 320x200
+    scale: f32,
+    /// Scale and centre to go back to from the whole map view
+    saved: Option<(f32, Vec2)>,
+    /// Map space centre of the view
+    centre: Vec2,
+    /// Angle the view is rotated to, the player's while rotating
+    angle: f32,
+    /// Bottom left and top right of the level
+    bounds: (Vec2, Vec2),
+    /// Used to close the map when the console player dies
+    player_dead: bool,
+    lines: Vec<MapLine>,
+    things: Vec<Marker>,
+    players: Vec<Marker>,
+    /// Kills, items, secrets and level time
+    stats: [HUDString; 4],
+}
+
+impl AutoMap {
+    pub fn new(wad: &WadData) -> Self {
+        let palette = wad.playpal_iter().next().unwrap();
+
+        Self {
+            palette,
+            font: HUDFont::new(wad),
+            mark_nums: get_num_sprites("AMMNUM", 0, wad),
+            screen_width: 320,
+            screen_height: 200,
+            state: AutomapState::Off,
+            overlay: false,
+            follow: true,
+            rotate: false,
+            grid: false,
+            cheating: 0,
+            cheat_pos: 0,
+            marks: [None; MAX_MARKS],
+            next_mark: 0,
+            scale: 0.2,
+            saved: None,
+            centre: Vec2::ZERO,
+            angle: FRAC_PI_2,
+            bounds: (Vec2::ZERO, Vec2::ZERO),
+            player_dead: false,
+            lines: Vec::new(),
+            things: Vec::new(),
+            players: Vec::new(),
+            stats: [HUD_STRING; 4],
+        }
+    }
+
+    /// The scale that fits the whole level on screen
+    fn min_scale(&self) -> f32 {
+        let size = (self.bounds.1 - self.bounds.0).max(Vec2::ONE);
+        let width = self.screen_width as f32 * 200.0 / self.screen_height as f32;
+        (width / size.x).min(200.0 / size.y).min(MAX_SCALE)
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(self.min_scale(), MAX_SCALE);
+    }
+
+    fn open(&mut self, game: &mut impl GameTraits) {
+        self.state = if self.overlay {
+            AutomapState::Overlay
+        } else {
+            AutomapState::FullScreen
+        };
+        game.set_automap(self.state);
+        self.update(game);
+    }
+
+    fn close(&mut self, game: &mut impl GameTraits) {
+        self.state = AutomapState::Off;
+        game.set_automap(self.state);
+    }
+
+    fn add_mark(&mut self) {
+        self.marks[self.next_mark] = Some(self.centre);
+        self.next_mark = (self.next_mark + 1) % MAX_MARKS;
+    }
+
+    /// Track `CHEAT` being typed, cycling the cheat level when it completes
+    fn check_cheat(&mut self, sc: Scancode) {
+        if CHEAT[self.cheat_pos] != sc {
+            self.cheat_pos = (CHEAT[0] == sc) as usize;
+            return;
+        }
+        self.cheat_pos += 1;
+        if self.cheat_pos == CHEAT.len() {
+            self.cheat_pos = 0;
+            self.cheating = (self.cheating + 1) % 3;
+        }
+    }
+
+    /// How a line is drawn, `None` if it isn't shown. `cheating` shows every
+    /// line as if it was seen.
+    fn line_colour(
+        cheating: bool,
+        flags: u32,
+        special: i16,
+        front: &Sector,
+        back: Option<&Sector>,
+        allmap: bool,
+    ) -> Option<usize> {
+        if flags & LineDefFlags::UnMapped as u32 != 0 && !cheating {
+            return None;
+        }
+        if flags & LineDefFlags::Mapped as u32 == 0 && !cheating {
+            return allmap.then_some(UNSEEN);
+        }
+        let Some(back) = back else {
+            return Some(WALL);
+        };
+        if special == TELEPORT_SPECIAL {
+            return Some(TELEPORTER);
+        }
+        // A secret door must not give itself away by its key colour
+        if flags & LineDefFlags::Secret as u32 != 0 {
+            return Some(if cheating { SECRET } else { WALL });
+        }
+        if let Some((_, colour)) = LOCKED_DOORS.iter().find(|(s, _)| s.contains(&special)) {
+            return Some(*colour);
+        }
+        if front.floorheight != back.floorheight {
+            Some(FLOOR_CHANGE)
+        } else if front.ceilingheight != back.ceilingheight {
+            Some(CEILING_CHANGE)
+        } else {
+            cheating.then_some(TWO_SIDED)
+        }
+    }
+
+    /// Take what is drawn from the level, as drawing has no access to it.
+    ///
+    /// Doom function name `AM_Ticker`
+    fn update(&mut self, game: &impl GameTraits) {
+        let Some(level) = game.level() else {
+            return;
+        };
+        let console = game.console_player();
+        let Some(player) = game.player(console) else {
+            return;
+        };
+        self.player_dead = player.player_state == PlayerState::Dead;
+
+        let allmap = player.status.powers[PowerType::Allmap as usize] != 0;
+        self.lines.clear();
+        let cheating = self.cheating > 0;
+        for line in level.map_data.linedefs() {
+            let back = line.backsector.as_deref();
+            if let Some(colour) = Self::line_colour(
+                cheating,
+                line.flags,
+                line.special,
+                &line.frontsector,
+                back,
+                allmap,
+            ) {
+                self.lines.push(MapLine {
+                    a: line.v1.truncate(),
+                    b: line.v2.truncate(),
+                    colour,
+                });
+            }
+        }
+
+        self.things.clear();
+        if self.cheating == 2 {
+            for thinker in level.thinkers.iter().filter(|t| t.is_mobj()) {
+                let mobj = thinker.mobj();
+                self.things.push(Marker {
+                    pos: mobj.xyz.truncate(),
+                    angle: mobj.angle.rad(),
+                    colour: THING,
+                });
+            }
+        }
+
+        let marker = |player: &Player, colour: usize| {
+            player.mobj().map(|mobj| Marker {
+                pos: mobj.xyz.truncate(),
+                angle: mobj.angle.rad(),
+                colour,
+            })
+        };
+        self.players.clear();
+        if !level.options.netgame {
+            self.players.extend(marker(player, PLAYER));
+        } else {
+            for (i, colour) in NET_PLAYERS.iter().enumerate() {
+                // Opponents aren't shown in deathmatch
+                if level.options.deathmatch != 0 && i != console {
+                    continue;
+                }
+                let Some(other) = game.player(i) else {
+                    continue;
+                };
+                let colour = if other.status.powers[PowerType::Invisibility as usize] != 0 {
+                    INVISIBLE
+                } else {
+                    *colour
+                };
+                self.players.extend(marker(other, colour));
+            }
+        }
+
+        if let Some(mobj) = player.mobj() {
+            if self.follow {
+                self.centre = mobj.xyz.truncate();
+            }
+            self.angle = if self.rotate {
+                mobj.angle.rad()
+            } else {
+                FRAC_PI_2
+            };
+        }
+
+        let secs = level.level_time / TICRATE as u32;
+        let stats = [
+            format!("KILLS {}/{}", player.total_kills, level.total_level_kills),
+            format!(
+                "ITEMS {}/{}",
+                player.items_collected, level.total_level_items
+            ),
+            format!(
+                "SECRETS {}/{}",
+                player.secrets_found, level.total_level_secrets
+            ),
+            format!("TIME {}:{:0>2}", secs / 60, secs % 60),
+        ];
+        for (line, text) in self.stats.iter_mut().zip(stats) {
+            line.replace(text);
+            line.set_draw_all();
+        }
+    }
+
+    fn view(&self, buffer: &dyn PixelBuffer) -> View {
+        View {
+            centre: self.centre,
+            rotation: Vec2::from_angle(FRAC_PI_2 - self.angle),
+            scale: self.scale * buffer.size().height_f32() / 200.0,
+            half_width: buffer.size().width_f32() / 2.0,
+            half_height: buffer.size().height_f32() / 2.0,
+        }
+    }
+
+    /// Draw a line in screen space, clipped to the screen.
+    ///
+    /// Doom function name `AM_drawFline`
+    fn draw_line(&self, a: Vec2, b: Vec2, colour: usize, buffer: &mut dyn PixelBuffer) {
+        let max = Vec2::new(
+            buffer.size().width_f32() - 1.0,
+            buffer.size().height_f32() - 1.0,
+        );
+        // Liang-Barsky clip of the line to the screen
+        let d = b - a;
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for (p, q) in [
+            (-d.x, a.x),
+            (d.x, max.x - a.x),
+            (-d.y, a.y),
+            (d.y, max.y - a.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return;
+                }
+                continue;
+            }
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+            if t0 > t1 {
+                return;
+            }
+        }
+        let (a, b) = (a + d * t0, a + d * t1);
+
+        let colour = &self.palette.0[colour].0;
+        let (mut x, mut y) = (a.x.round() as i32, a.y.round() as i32);
+        let (x1, y1) = (b.x.round() as i32, b.y.round() as i32);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            buffer.set_pixel(x as usize, y as usize, colour);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Draw one of the shape line lists at `pos`, turned to `angle`.
+    ///
+    /// Doom function name `AM_drawLineCharacter`
+    fn draw_shape(
+        &self,
+        shape: &[[(f32, f32); 2]],
+        size: f32,
+        marker: &Marker,
+        view: &View,
+        buffer: &mut dyn PixelBuffer,
+    ) {
+        let turn = Vec2::from_angle(marker.angle);
+        let point =
+            |(x, y): (f32, f32)| view.to_screen(marker.pos + turn.rotate(Vec2::new(x, y) * size));
+        for [a, b] in shape {
+            self.draw_line(point(*a), point(*b), marker.colour, buffer);
+        }
+    }
+
+    /// Doom function name `AM_drawGrid`
+    fn draw_grid(&self, view: &View, buffer: &mut dyn PixelBuffer) {
+        // Cover the screen whichever way it is rotated
+        let reach = Vec2::new(view.half_width, view.half_height).length() / view.scale;
+        let origin = self.bounds.0;
+        let start = ((self.centre - reach - origin) / GRID_SIZE).floor() * GRID_SIZE + origin;
+        let end = self.centre + reach;
+        let mut x = start.x;
+        while x < end.x {
+            let a = view.to_screen(Vec2::new(x, start.y));
+            let b = view.to_screen(Vec2::new(x, end.y));
+            self.draw_line(a, b, GRID, buffer);
+            x += GRID_SIZE;
+        }
+        let mut y = start.y;
+        while y < end.y {
+            let a = view.to_screen(Vec2::new(start.x, y));
+            let b = view.to_screen(Vec2::new(end.x, y));
+            self.draw_line(a, b, GRID, buffer);
+            y += GRID_SIZE;
+        }
+    }
+
+    /// Doom function name `AM_drawMarks`
+    fn draw_marks(&self, view: &View, buffer: &mut dyn PixelBuffer) {
+        let f = buffer.size().height() / 200;
+        for (i, mark) in self.marks.iter().enumerate() {
+            let Some(mark) = mark else {
+                continue;
+            };
+            let patch = &self.mark_nums[i];
+            let pos = view.to_screen(*mark);
+            let x = pos.x as i32 - patch.width as i32 * f / 2;
+            let y = pos.y as i32 - patch.height as i32 * f / 2;
+            let (w, h) = (patch.width as i32 * f, patch.height as i32 * f);
+            if x < 0 || y < 0 || x + w >= self.screen_width || y + h >= self.screen_height {
+                continue;
+            }
+            self.draw_patch_pixels(patch, x, y, buffer);
+        }
+    }
+}
+
+impl SubsystemTrait for AutoMap {
+    /// Doom function name `AM_LevelInit`
+    fn init(&mut self, game: &impl GameTraits) {
+        self.state = AutomapState::Off;
+        self.marks = [None; MAX_MARKS];
+        self.next_mark = 0;
+        self.saved = None;
+        self.lines.clear();
+        self.things.clear();
+        self.players.clear();
+        if let Some(level) = game.level() {
+            let extents = level.map_data.get_map_extents();
+            self.bounds = (extents.min_vertex.truncate(), extents.max_vertex.truncate());
+            self.centre = (self.bounds.0 + self.bounds.1) / 2.0;
+        }
+        self.set_scale(self.min_scale() / 0.7);
+    }
+
+    /// Doom function name `AM_Responder`
+    fn responder(&mut self, sc: Scancode, game: &mut impl GameTraits) -> bool {
+        if self.state == AutomapState::Off {
+            if sc == Scancode::Tab && game.level().is_some() {
+                self.open(game);
+                return true;
+            }
+            return false;
+        }
+        // The cheat keys still go on to move the player
+        self.check_cheat(sc);
+
+        let pan = PAN / self.scale;
+        let back = Vec2::from_angle(self.angle - FRAC_PI_2);
+        match sc {
+            Scancode::Tab => self.close(game),
+            Scancode::Left if !self.follow => self.centre += back.rotate(Vec2::new(-pan, 0.0)),
+            Scancode::Right if !self.follow => self.centre += back.rotate(Vec2::new(pan, 0.0)),
+            Scancode::Up if !self.follow => self.centre += back.rotate(Vec2::new(0.0, pan)),
+            Scancode::Down if !self.follow => self.centre += back.rotate(Vec2::new(0.0, -pan)),
+            Scancode::Minus | Scancode::KpMinus => self.set_scale(self.scale / ZOOM),
+            Scancode::Equals | Scancode::KpPlus => self.set_scale(self.scale * ZOOM),
+            Scancode::Num0 | Scancode::Kp0 => {
+                if let Some((scale, centre)) = self.saved.take() {
+                    self.scale = scale;
+                    self.centre = centre;
+                } else {
+                    self.saved = Some((self.scale, self.centre));
+                    self.scale = self.min_scale();
+                    self.centre = (self.bounds.0 + self.bounds.1) / 2.0;
+                }
+            }
+            Scancode::F => self.follow = !self.follow,
+            Scancode::G => self.grid = !self.grid,
+            Scancode::M => self.add_mark(),
+            Scancode::C => {
+                self.marks = [None; MAX_MARKS];
+                self.next_mark = 0;
+            }
+            Scancode::R => self.rotate = !self.rotate,
+            Scancode::O => {
+                self.overlay = !self.overlay;
+                self.open(game);
+            }
+            _ => return false,
+        }
+        self.update(game);
+        true
+    }
+
+    fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
+        if self.state == AutomapState::Off {
+            return false;
+        }
+        if game.level().is_none() {
+            self.close(game);
+            return false;
+        }
+        let was_dead = self.player_dead;
+        self.update(game);
+        // The map is closed when the console player dies
+        if self.player_dead && !was_dead {
+            self.close(game);
+        }
+        false
+    }
+
+    fn get_palette(&self) -> &WadPalette {
+        &self.palette
+    }
+
+    /// Doom function name `AM_Drawer`
+    fn draw(&mut self, buffer: &mut dyn PixelBuffer) {
+        self.screen_width = buffer.size().width();
+        self.screen_height = buffer.size().height();
+        if self.state == AutomapState::Off {
+            return;
+        }
+        if self.state == AutomapState::FullScreen {
+            buffer.clear_with_colour(&[0, 0, 0, 255]);
+        }
+
+        let view = self.view(buffer);
+        if self.grid {
+            self.draw_grid(&view, buffer);
+        }
+        for line in self.lines.iter() {
+            let (a, b) = (view.to_screen(line.a), view.to_screen(line.b));
+            self.draw_line(a, b, line.colour, buffer);
+        }
+        for thing in self.things.iter() {
+            self.draw_shape(&THING_TRIANGLE, 16.0, thing, &view, buffer);
+        }
+        for player in self.players.iter() {
+            let size = 8.0 * PLAYER_RADIUS / 7.0;
+            self.draw_shape(&PLAYER_ARROW, size, player, &view, buffer);
+        }
+        self.draw_marks(&view, buffer);
+
+        // Below the four lines of HUD messages
+        let f = buffer.size().height() / 200;
+        let step = self.stats[0].line_height() * f + 1;
+        let mut y = 2 + step * 4;
+        for line in self.stats.iter() {
+            line.draw(10, y, &self.font, self, buffer);
+            y += step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPED: u32 = LineDefFlags::Mapped as u32;
+
+    fn sector(floor: f32, ceiling: f32) -> Sector {
+        Sector::new(0, floor, ceiling, 0, 0, 160, 0, 0)
+    }
+
+    fn colour(flags: u32, special: i16, back: Option<&Sector>) -> Option<usize> {
+        AutoMap::line_colour(false, flags, special, &sector(0.0, 128.0), back, false)
+    }
+
+    #[test]
+    fn seen_lines() {
+        let same = sector(0.0, 128.0);
+        assert_eq!(colour(MAPPED, 0, None), Some(WALL));
+        assert_eq!(
+            colour(MAPPED, 0, Some(&sector(24.0, 128.0))),
+            Some(FLOOR_CHANGE)
+        );
+        assert_eq!(
+            colour(MAPPED, 0, Some(&sector(0.0, 96.0))),
+            Some(CEILING_CHANGE)
+        );
+        // two sided lines with no height change aren't drawn
+        assert_eq!(colour(MAPPED, 0, Some(&same)), None);
+        assert_eq!(
+            colour(MAPPED, TELEPORT_SPECIAL, Some(&same)),
+            Some(TELEPORTER)
+        );
+        // blue, red and yellow locked doors
+        assert_eq!(colour(MAPPED, 26, Some(&same)), Some(204));
+        assert_eq!(colour(MAPPED, 134, Some(&same)), Some(175));
+        assert_eq!(colour(MAPPED, 137, Some(&same)), Some(231));
+    }
+
+    #[test]
+    fn secret_and_hidden_lines() {
+        let same = sector(0.0, 128.0);
+        let secret = MAPPED | LineDefFlags::Secret as u32;
+        // a secret locked door is drawn as a wall
+        assert_eq!(colour(secret, 26, Some(&same)), Some(WALL));
+        assert_eq!(
+            colour(MAPPED | LineDefFlags::UnMapped as u32, 0, None),
+            None
+        );
+        // unseen lines only show with the computer map
+        assert_eq!(colour(0, 0, None), None);
+        let front = sector(0.0, 128.0);
+        assert_eq!(
+            AutoMap::line_colour(false, 0, 0, &front, None, true),
+            Some(UNSEEN)
+        );
+    }
+
+    #[test]
+    fn cheat_lines() {
+        let same = sector(0.0, 128.0);
+        let cheat = |flags, special, back| {
+            AutoMap::line_colour(true, flags, special, &sector(0.0, 128.0), back, false)
+        };
+        // everything is drawn as if seen
+        assert_eq!(cheat(0, 0, None), Some(WALL));
+        assert_eq!(cheat(LineDefFlags::UnMapped as u32, 0, None), Some(WALL));
+        assert_eq!(cheat(0, 0, Some(&same)), Some(TWO_SIDED));
+        assert_eq!(
+            cheat(LineDefFlags::Secret as u32, 26, Some(&same)),
+            Some(SECRET)
+        );
+    }
+}
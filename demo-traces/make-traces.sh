@@ -0,0 +1,68 @@
+#!/bin/sh
+# Make the per-tic traces read by the headless
+# `demos_fixed_point_match_vanilla_traces` test.
+#
+# Builds Chocolate Doom with a hook at the end of each level tic of
+# `G_Ticker` that writes `tic x y z prndindex` for the console player while a
+# demo plays, then runs each demo in the IWAD with `-timedemo`. The traces are
+# written beside this script as `demoN.txt`.
+#
+# Usage: demo-traces/make-traces.sh [path/to/doom1.wad]
+#
+# Needs git, cmake, a C compiler and the SDL2, SDL2_mixer and SDL2_net
+# development packages.
+set -eu
+
+HERE=$(cd "$(dirname "$0")" && pwd)
+IWAD=$(realpath "${1:-$HERE/../doom1.wad}")
+TAG=chocolate-doom-3.1.0
+BUILD=${BUILD:-$HERE/build}
+
+if [ ! -d "$BUILD/src" ]; then
+    git clone --depth 1 --branch "$TAG" https://github.com/chocolate-doom/chocolate-doom "$BUILD/src"
+fi
+
+GAME="$BUILD/src/src/doom/g_game.c"
+if ! grep -q G_TraceTic "$GAME"; then
+    # Define the hook before G_Ticker, and call it once the level has ticked
+    sed -i '/^void G_Ticker/i\
+extern int prndindex;\
+static FILE *trace_file;\
+static int trace_tic;\
+\
+static void G_TraceTic (void)\
+{\
+    mobj_t *mo = players[consoleplayer].mo;\
+\
+    if (!demoplayback || mo == NULL)\
+        return;\
+    if (trace_file == NULL)\
+    {\
+        const char *path = getenv("DEMO_TRACE");\
+        if (path == NULL || (trace_file = fopen(path, "w")) == NULL)\
+            return;\
+    }\
+    fprintf(trace_file, "%d %d %d %d %d\\n", trace_tic++, mo->x, mo->y, mo->z, prndindex);\
+    fflush(trace_file);\
+}\
+' "$GAME"
+    sed -i 's/^\([[:space:]]*\)HU_Ticker ();/&\n\1G_TraceTic ();/' "$GAME"
+fi
+
+cmake -S "$BUILD/src" -B "$BUILD/out" -DCMAKE_BUILD_TYPE=Release
+cmake --build "$BUILD/out" --target chocolate-doom
+
+for demo in demo1 demo2 demo3 demo4; do
+    out="$HERE/$demo.txt"
+    rm -f "$out"
+    # `-timedemo` quits with an error holding the timing when the demo ends
+    DEMO_TRACE="$out" SDL_VIDEODRIVER=dummy SDL_AUDIODRIVER=dummy \
+        "$BUILD/out/src/chocolate-doom" -iwad "$IWAD" -nosound -nodraw \
+        -timedemo "$demo" >/dev/null 2>&1 || true
+    if [ -s "$out" ]; then
+        echo "$demo: $(wc -l <"$out") tics"
+    else
+        rm -f "$out"
+        echo "$demo: not in $IWAD"
+    fi
+done
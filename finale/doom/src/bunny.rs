@@ -0,0 +1,129 @@
+//! The end of Doom episode 3: the bunny picture scrolls across from `PFUB1`
+//! to `PFUB2`, then "THE END" is shot on to it a letter at a time.
+//!
+//! Doom source name `f_finale`
+
+use crate::draw_patch;
+use gamestate_traits::{GameTraits, PixelBuffer, SfxName, SubsystemTrait};
+use wad::types::WadPatch;
+use wad::WadData;
+
+/// Tics before the scroll starts
+const SCROLL_START: i32 = 230;
+/// Tics until "THE END" shows
+const END_START: i32 = 1130;
+/// Tics until the first letter is shot
+const SHOTS_START: i32 = 1180;
+/// Tics between shots
+const SHOT_TICS: i32 = 5;
+
+pub(crate) struct Bunny {
+    /// Scrolls in from the left
+    left: WadPatch,
+    /// Shown first
+    right: WadPatch,
+    /// `END0` to `END6`, each with one more letter shot
+    end: Vec<WadPatch>,
+    count: i32,
+    stage: usize,
+}
+
+impl Bunny {
+    pub fn new(wad: &WadData) -> Self {
+        let patch = |name: &str| {
+            wad.get_lump(name)
+                .map(WadPatch::from_lump)
+                .unwrap_or_else(WadPatch::default)
+        };
+        Self {
+            left: patch("PFUB2"),
+            right: patch("PFUB1"),
+            end: (0..7).map(|i| patch(&format!("END{i}"))).collect(),
+            count: 0,
+            stage: 0,
+        }
+    }
+
+    pub fn ticker(&mut self, game: &mut impl GameTraits) {
+        self.count += 1;
+        let stage = self.shot_stage();
+        if stage > self.stage {
+            game.start_sound(SfxName::Pistol);
+            self.stage = stage;
+        }
+    }
+
+    /// How many letters of "THE END" have been shot
+    fn shot_stage(&self) -> usize {
+        if self.count < SHOTS_START {
+            return 0;
+        }
+        (((self.count - SHOTS_START) / SHOT_TICS) as usize).min(self.end.len() - 1)
+    }
+
+    /// How far `PFUB1` is from being scrolled off to the right. Starts at 320
+    fn scrolled(&self) -> i32 {
+        (320 - (self.count - SCROLL_START) / 2).clamp(0, 320)
+    }
+
+    /// "THE END" is drawn over the finished scroll
+    fn shows_end(&self) -> bool {
+        self.count >= END_START
+    }
+
+    /// Doom function name `F_BunnyScroll`
+    pub fn draw(&self, machination: &impl SubsystemTrait, pixels: &mut dyn PixelBuffer) {
+        let scrolled = self.scrolled();
+        draw_patch(machination, &self.left, -scrolled, 0, pixels);
+        draw_patch(machination, &self.right, 320 - scrolled, 0, pixels);
+
+        if !self.shows_end() {
+            return;
+        }
+        let x = (320 - 13 * 8) / 2;
+        let y = (200 - 8 * 8) / 2;
+        draw_patch(machination, &self.end[self.stage], x, y, pixels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wad::types::WadPatch;
+
+    use super::Bunny;
+
+    fn bunny(count: i32) -> Bunny {
+        Bunny {
+            left: WadPatch::default(),
+            right: WadPatch::default(),
+            end: vec![WadPatch::default(); 7],
+            count,
+            stage: 0,
+        }
+    }
+
+    #[test]
+    fn scroll_timing() {
+        assert_eq!(bunny(0).scrolled(), 320);
+        assert_eq!(bunny(230).scrolled(), 320);
+        assert_eq!(bunny(232).scrolled(), 319);
+        // half way across
+        assert_eq!(bunny(550).scrolled(), 160);
+        assert_eq!(bunny(870).scrolled(), 0);
+        assert_eq!(bunny(1130).scrolled(), 0);
+    }
+
+    #[test]
+    fn end_stages() {
+        assert!(!bunny(1129).shows_end());
+        assert!(bunny(1130).shows_end());
+        assert_eq!(bunny(1130).shot_stage(), 0);
+        assert_eq!(bunny(1180).shot_stage(), 0);
+        assert_eq!(bunny(1184).shot_stage(), 0);
+        assert_eq!(bunny(1185).shot_stage(), 1);
+        // the last letter is shot at 1210 and stays
+        assert_eq!(bunny(1209).shot_stage(), 5);
+        assert_eq!(bunny(1210).shot_stage(), 6);
+        assert_eq!(bunny(2000).shot_stage(), 6);
+    }
+}
@@ -0,0 +1,273 @@
+//! The Doom II cast call shown after the MAP30 text. Each monster walks,
+//! attacks and dies in turn in front of `BOSSBACK`, with its name below.
+//!
+//! Doom source name `f_finale`
+
+use crate::draw_patch;
+use crate::text::*;
+use gamestate_traits::{
+    GameTraits, MapObjInfo, MapObjKind, PixelBuffer, SfxName, StateNum, SubsystemTrait, MOBJINFO, SPRNAMES, STATES
+};
+use hud_util::{HUDString, HUD_STRING};
+use std::collections::HashMap;
+use wad::types::{WadPatch, WadPatchCol};
+use wad::WadData;
+
+const FF_FRAMEMASK: u32 = 0x7FFF;
+
+const CAST_ORDER: [(&str, MapObjKind); 17] = [
+    (CC_ZOMBIE, MapObjKind::MT_POSSESSED),
+    (CC_SHOTGUN, MapObjKind::MT_SHOTGUY),
+    (CC_HEAVY, MapObjKind::MT_CHAINGUY),
+    (CC_IMP, MapObjKind::MT_TROOP),
+    (CC_DEMON, MapObjKind::MT_SERGEANT),
+    (CC_LOST, MapObjKind::MT_SKULL),
+    (CC_CACO, MapObjKind::MT_HEAD),
+    (CC_HELL, MapObjKind::MT_KNIGHT),
+    (CC_BARON, MapObjKind::MT_BRUISER),
+    (CC_ARACH, MapObjKind::MT_BABY),
+    (CC_PAIN, MapObjKind::MT_PAIN),
+    (CC_REVEN, MapObjKind::MT_UNDEAD),
+    (CC_MANCU, MapObjKind::MT_FATSO),
+    (CC_ARCH, MapObjKind::MT_VILE),
+    (CC_SPIDER, MapObjKind::MT_SPIDER),
+    (CC_CYBER, MapObjKind::MT_CYBORG),
+    (CC_HERO, MapObjKind::MT_PLAYER),
+];
+
+/// The attack sounds, as the states that play them are run without a map
+/// object their actions can't be
+fn attack_sound(state: StateNum) -> Option<SfxName> {
+    Some(match state {
+        StateNum::PLAY_ATK1 => SfxName::Dshtgn,
+        StateNum::POSS_ATK2 => SfxName::Pistol,
+        StateNum::SPOS_ATK2 => SfxName::Shotgn,
+        StateNum::VILE_ATK2 => SfxName::Vilatk,
+        StateNum::SKEL_FIST2 => SfxName::Skeswg,
+        StateNum::SKEL_FIST4 => SfxName::Skepch,
+        StateNum::SKEL_MISS2 => SfxName::Skeatk,
+        StateNum::FATT_ATK8 | StateNum::FATT_ATK5 | StateNum::FATT_ATK2 => SfxName::Firsht,
+        StateNum::CPOS_ATK2 | StateNum::CPOS_ATK3 | StateNum::CPOS_ATK4 => SfxName::Shotgn,
+        StateNum::TROO_ATK3 => SfxName::Claw,
+        StateNum::SARG_ATK2 => SfxName::Sgtatk,
+        StateNum::BOSS_ATK2 | StateNum::BOS2_ATK2 | StateNum::HEAD_ATK2 => SfxName::Firsht,
+        StateNum::SKULL_ATK2 => SfxName::Sklatk,
+        StateNum::SPID_ATK2 | StateNum::SPID_ATK3 => SfxName::Shotgn,
+        StateNum::BSPI_ATK2 => SfxName::Plasma,
+        StateNum::CYBER_ATK2 | StateNum::CYBER_ATK4 | StateNum::CYBER_ATK6 => SfxName::Rlaunc,
+        StateNum::PAIN_ATK3 => SfxName::Sklatk,
+        _ => return None,
+    })
+}
+
+/// Mirror a patch left to right
+fn flip_patch(patch: &WadPatch) -> WadPatch {
+    // Each column is a run of posts ended by a post with `y_offset` 255
+    let mut columns: Vec<&[WadPatchCol]> = patch
+        .columns
+        .split_inclusive(|c| c.y_offset == 255)
+        .collect();
+    columns.reverse();
+    WadPatch {
+        columns: columns.concat(),
+        ..patch.clone()
+    }
+}
+
+pub(crate) struct Cast {
+    background: WadPatch,
+    /// Names of all sprite lumps, to find the frames in
+    sprite_lumps: Vec<String>,
+    /// The front facing patch of each sprite and frame shown so far
+    frames: HashMap<(usize, u32), WadPatch>,
+    name: HUDString,
+    /// Index in to `CAST_ORDER`
+    num: usize,
+    state: StateNum,
+    tics: i32,
+    /// Dying after a key press
+    death: bool,
+    /// Frames since the monster started walking or attacking
+    frame_count: i32,
+    /// Alternates between melee and missile attacks
+    on_melee: bool,
+    attacking: bool,
+}
+
+impl Cast {
+    /// Doom function name `F_StartCast`
+    pub fn new(wad: &WadData) -> Self {
+        let background = wad
+            .get_lump("BOSSBACK")
+            .map(WadPatch::from_lump)
+            .unwrap_or_else(WadPatch::default);
+        let sprite_lumps = wad
+            .lump_names_between("S_START", "S_END")
+            .into_iter()
+            .map(|n| n.to_string())
+            .collect();
+        let mut cast = Self {
+            background,
+            sprite_lumps,
+            frames: HashMap::new(),
+            name: HUD_STRING,
+            num: 0,
+            state: StateNum::None,
+            tics: 0,
+            death: false,
+            frame_count: 0,
+            on_melee: false,
+            attacking: false,
+        };
+        cast.set_monster(0, wad);
+        cast
+    }
+
+    fn info(&self) -> MapObjInfo {
+        MOBJINFO[CAST_ORDER[self.num].1 as usize]
+    }
+
+    fn set_monster(&mut self, num: usize, wad: &WadData) {
+        self.num = num;
+        self.death = false;
+        self.frame_count = 0;
+        self.name.replace(CAST_ORDER[num].0.to_string());
+        self.name.set_draw_all();
+        self.set_state(self.info().seestate, wad);
+    }
+
+    fn set_state(&mut self, state: StateNum, wad: &WadData) {
+        self.state = state;
+        self.tics = STATES[state as usize].tics;
+        self.load_frame(wad);
+    }
+
+    /// Find the patch to show for the current state. Doom shows rotation 0,
+    /// or 1 for frames drawn from eight angles
+    fn load_frame(&mut self, wad: &WadData) {
+        let state = &STATES[self.state as usize];
+        let key = (state.sprite as usize, state.frame & FF_FRAMEMASK);
+        if self.frames.contains_key(&key) {
+            return;
+        }
+        let sprite = SPRNAMES[key.0].as_bytes();
+        let frame = b'A' + key.1 as u8;
+        let front = |f: &[u8]| f[0] == frame && (f[1] == b'0' || f[1] == b'1');
+        let found = self.sprite_lumps.iter().find_map(|name| {
+            let n = name.as_bytes();
+            if n.len() < 6 || &n[..4] != sprite {
+                return None;
+            }
+            if front(&n[4..6]) {
+                Some((name, false))
+            } else if n.len() == 8 && front(&n[6..8]) {
+                Some((name, true))
+            } else {
+                None
+            }
+        });
+        let patch = found
+            .and_then(|(name, flip)| {
+                let patch = WadPatch::from_lump(wad.get_lump(name)?);
+                Some(if flip { flip_patch(&patch) } else { patch })
+            })
+            .unwrap_or_else(WadPatch::default);
+        self.frames.insert(key, patch);
+    }
+
+    /// Doom function name `F_CastTicker`
+    pub fn ticker(&mut self, game: &mut impl GameTraits) {
+        self.tics -= 1;
+        if self.tics > 0 {
+            return;
+        }
+        let current = &STATES[self.state as usize];
+        if current.tics == -1 || current.next_state == StateNum::None {
+            // Done dying, on to the next monster
+            let num = (self.num + 1) % CAST_ORDER.len();
+            self.set_monster(num, game.get_wad_data());
+            let sound = self.info().seesound;
+            if sound != SfxName::None {
+                game.start_sound(sound);
+            }
+        } else if self.state == StateNum::PLAY_ATK1 {
+            // The player only fires once
+            self.stop_attack(game.get_wad_data());
+        } else {
+            let next = current.next_state;
+            self.set_state(next, game.get_wad_data());
+            self.frame_count += 1;
+            if let Some(sfx) = attack_sound(next) {
+                game.start_sound(sfx);
+            }
+        }
+
+        let info = self.info();
+        if self.frame_count == 12 {
+            self.attacking = true;
+            let (first, second) = if self.on_melee {
+                (info.meleestate, info.missilestate)
+            } else {
+                (info.missilestate, info.meleestate)
+            };
+            self.on_melee = !self.on_melee;
+            let state = if first == StateNum::None {
+                second
+            } else {
+                first
+            };
+            self.set_state(state, game.get_wad_data());
+        }
+
+        if self.attacking && (self.frame_count == 24 || self.state == info.seestate) {
+            self.stop_attack(game.get_wad_data());
+        }
+
+        if self.tics == -1 {
+            self.tics = 15;
+        }
+    }
+
+    fn stop_attack(&mut self, wad: &WadData) {
+        self.attacking = false;
+        self.frame_count = 0;
+        self.set_state(self.info().seestate, wad);
+    }
+
+    /// Kill the current monster.
+    ///
+    /// Doom function name `F_CastResponder`
+    pub fn responder(&mut self, game: &mut impl GameTraits) {
+        if self.death {
+            return;
+        }
+        self.death = true;
+        self.attacking = false;
+        self.frame_count = 0;
+        let info = self.info();
+        self.set_state(info.deathstate, game.get_wad_data());
+        if info.deathsound != SfxName::None {
+            game.start_sound(info.deathsound);
+        }
+    }
+
+    pub fn background(&self) -> &WadPatch {
+        &self.background
+    }
+
+    /// The name of the current monster
+    pub fn name(&self) -> &HUDString {
+        &self.name
+    }
+
+    /// Draw the monster, flipped if its frame is.
+    ///
+    /// Doom function name `F_CastDrawer`
+    pub fn draw(&self, machination: &impl SubsystemTrait, pixels: &mut dyn PixelBuffer) {
+        let state = &STATES[self.state as usize];
+        let key = (state.sprite as usize, state.frame & FF_FRAMEMASK);
+        if let Some(patch) = self.frames.get(&key) {
+            draw_patch(machination, patch, 160, 170, pixels);
+        }
+    }
+}
@@ -1,26 +1,69 @@
+mod bunny;
+mod cast;
 mod text;
 
+use crate::bunny::Bunny;
+use crate::cast::Cast;
 use crate::text::*;
 use gamestate_traits::{
     GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, TICRATE
 };
-use hud_util::{load_char_patches, HUDString, HUD_STRING};
-use wad::types::{WadFlat, WadPalette};
+use hud_util::{HUDFont, HUDString, HUD_STRING};
+use log::warn;
+use wad::types::{WadFlat, WadPalette, WadPatch};
 use wad::WadData;
 
+/// Size of a flat lump, used to tell flats and patches apart for backdrops
+const FLAT_SIZE: usize = 64 * 64;
+/// Tics per character of text before an episode's end screen is shown
+const TEXT_SPEED: i32 = 3;
+/// Tics to wait after the text before an episode's end screen is shown
+const TEXT_WAIT: i32 = 250;
+
+/// Draw a patch placed by its offsets at `x`, `y` on the 320x200 screen,
+/// scaled to the screen height. `draw_patch_pixels()` takes the position in
+/// pixels, ends each scaled column at it and takes off the left offset
+/// unscaled, so that is undone here.
+///
+/// Doom function name `V_DrawPatch`
+pub(crate) fn draw_patch(
+    machination: &impl SubsystemTrait,
+    patch: &WadPatch,
+    x: i32,
+    y: i32,
+    pixels: &mut dyn PixelBuffer,
+) {
+    let f = pixels.size().height() / 200;
+    let left = patch.left_offset as i32;
+    let x = (x - left) * f + left + f - 1;
+    let y = (y - patch.top_offset as i32) * f;
+    machination.draw_patch_pixels(patch, x, y, pixels);
+}
+
 pub struct Finale {
     palette: WadPalette,
     screen_width: i32,
     screen_height: i32,
+    font: HUDFont,
     text: HUDString,
     bg_flat: WadFlat,
+    /// A `UMAPINFO` backdrop that isn't a flat
+    bg_patch: Option<WadPatch>,
+    /// The end screen shown after the text
+    end_pic: Option<WadPatch>,
+    /// The episode 3 end, shown after the text instead of `end_pic`
+    bunny: Option<Bunny>,
+    show_end_pic: bool,
+    /// If false the next level is started after the text
+    ends_game: bool,
+    /// Show the Doom II cast after the text
+    ends_with_cast: bool,
+    cast: Option<Cast>,
     count: i32,
 }
 
 impl Finale {
     pub fn new(wad: &WadData) -> Self {
-        // initialise
-        load_char_patches(wad);
         let palette = wad.playpal_iter().next().unwrap();
 
         let lump = wad.get_lump("FLOOR4_8").unwrap();
@@ -33,8 +76,16 @@ impl Finale {
             palette,
             screen_width: 0,
             screen_height: 0,
+            font: HUDFont::new(wad),
             text: HUD_STRING,
             bg_flat,
+            bg_patch: None,
+            end_pic: None,
+            bunny: None,
+            show_end_pic: false,
+            ends_game: true,
+            ends_with_cast: false,
+            cast: None,
             count: 0,
         }
     }
@@ -44,6 +95,29 @@ impl Finale {
         self.screen_width = pixels.size().width();
         self.screen_height = pixels.size().height();
 
+        if let Some(cast) = &self.cast {
+            self.draw_patch_pixels(cast.background(), 0, 0, pixels);
+            let name = cast.name();
+            let x = (160 - name.width(&self.font) / 2) * f;
+            name.draw_pixels(x, 180 * f, &self.font, self, pixels);
+            cast.draw(self, pixels);
+            return;
+        }
+        if self.show_end_pic {
+            if let Some(bunny) = &self.bunny {
+                bunny.draw(self, pixels);
+            } else if let Some(end_pic) = &self.end_pic {
+                self.draw_patch_pixels(end_pic, 0, 0, pixels);
+            }
+            return;
+        }
+        if let Some(bg) = &self.bg_patch {
+            self.draw_patch_pixels(bg, 0, 0, pixels);
+            self.text
+                .draw_pixels(6 * f, 6 * f, &self.font, self, pixels);
+            return;
+        }
+
         let pal = &self.palette;
         for sx in (0..self.screen_width).step_by(64) {
             for sy in (0..self.screen_height).step_by(64) {
@@ -55,7 +129,8 @@ impl Finale {
                 }
             }
         }
-        self.text.draw_pixels(6 * f, 6 * f, self, pixels);
+        self.text
+            .draw_pixels(6 * f, 6 * f, &self.font, self, pixels);
     }
 }
 
@@ -63,9 +138,26 @@ impl SubsystemTrait for Finale {
     fn init(&mut self, game: &impl GameTraits) {
         let mut name = "FLOOR4_8";
         self.count = 20 * TICRATE;
+        self.text.clear();
+        self.bg_patch = None;
+        self.end_pic = None;
+        self.bunny = None;
+        self.show_end_pic = false;
+        self.cast = None;
+        self.ends_game =
+            game.get_mode() != GameMode::Commercial || game.level_end_info().last == 30;
+        self.ends_with_cast =
+            game.get_mode() == GameMode::Commercial && game.level_end_info().last == 30;
+
+        let info = game.level_end_info();
+        let map_info = info.last_info.as_ref();
+        let inter_music = map_info.and_then(|i| i.inter_music.as_deref());
+        let music_changed = inter_music.is_some_and(|m| game.change_music_lump(m));
 
         if game.get_mode() != GameMode::Commercial {
-            game.change_music(MusTrack::Victor);
+            if !music_changed {
+                game.change_music(MusTrack::Victor);
+            }
             match game.level_end_info().episode + 1 {
                 1 => {
                     name = "FLOOR4_8";
@@ -86,7 +178,9 @@ impl SubsystemTrait for Finale {
                 _ => {}
             }
         } else {
-            game.change_music(MusTrack::Read_M);
+            if !music_changed {
+                game.change_music(MusTrack::Read_M);
+            }
             match game.level_end_info().last {
                 6 => {
                     name = "SLIME16";
@@ -116,14 +210,69 @@ impl SubsystemTrait for Finale {
             }
         };
 
-        let lump = game.get_wad_data().get_lump(name).unwrap();
+        let wad = game.get_wad_data();
+        if let Some(map_info) = map_info {
+            if let Some(text) = map_info.inter_text(info.secret_exit) {
+                self.text.replace(text.to_ascii_uppercase());
+            }
+            if let Some(ends_game) = map_info.ends_game() {
+                self.ends_game = ends_game;
+                self.ends_with_cast = map_info.end_cast;
+            }
+            if let Some(backdrop) = map_info.inter_backdrop.as_deref() {
+                match wad.get_lump(backdrop) {
+                    Some(lump) if lump.data.len() == FLAT_SIZE => name = backdrop,
+                    Some(lump) => self.bg_patch = Some(WadPatch::from_lump(lump)),
+                    None => warn!("Missing backdrop {backdrop}"),
+                }
+            }
+            if let Some(end_pic) = map_info.end_pic.as_deref() {
+                self.end_pic = wad.get_lump(end_pic).map(WadPatch::from_lump);
+                if self.end_pic.is_none() {
+                    warn!("Missing end pic {end_pic}");
+                }
+            }
+        }
+        if map_info.is_some_and(|i| i.end_bunny) {
+            self.bunny = Some(Bunny::new(wad));
+        } else if map_info.and_then(|i| i.ends_game()).is_none()
+            && game.get_mode() != GameMode::Commercial
+        {
+            let end_pic = match info.episode + 1 {
+                1 if game.get_mode() == GameMode::Retail => "CREDIT",
+                1 => "HELP2",
+                2 => "VICTORY2",
+                4 => "ENDPIC",
+                _ => "",
+            };
+            if info.episode + 1 == 3 {
+                self.bunny = Some(Bunny::new(wad));
+            } else {
+                self.end_pic = wad.get_lump(end_pic).map(WadPatch::from_lump);
+            }
+        }
+        if self.end_pic.is_some() || self.bunny.is_some() {
+            self.count = self.text.line().len() as i32 * TEXT_SPEED + TEXT_WAIT;
+        }
+        // Nothing to read, e.g, a `UMAPINFO` end pic with no text
+        if self.text.line().is_empty() {
+            self.text.set_draw_all();
+            self.count = 0;
+        }
+
+        let lump = wad.get_lump(name).unwrap();
         self.bg_flat = WadFlat {
             name: name.to_string(),
             data: lump.data.clone(),
         };
     }
 
-    fn responder(&mut self, sc: Scancode, _game: &mut impl GameTraits) -> bool {
+    fn responder(&mut self, sc: Scancode, game: &mut impl GameTraits) -> bool {
+        if let Some(cast) = self.cast.as_mut() {
+            // Any key kills the current monster
+            cast.responder(game);
+            return true;
+        }
         if sc == Scancode::Return || sc == Scancode::Space {
             if !self.text.is_at_end() {
                 self.text.set_draw_all();
@@ -136,13 +285,29 @@ impl SubsystemTrait for Finale {
     }
 
     fn ticker(&mut self, game: &mut impl GameTraits) -> bool {
+        if let Some(cast) = self.cast.as_mut() {
+            cast.ticker(game);
+            return false;
+        }
         self.text.inc_current_char();
         self.count -= 1;
-        if self.count <= 0
-            && game.get_mode() == GameMode::Commercial
-            && game.level_end_info().last != 30
-        {
-            game.finale_done();
+        if self.count <= 0 {
+            if !self.ends_game {
+                game.finale_done();
+            } else if self.ends_with_cast {
+                self.cast = Some(Cast::new(game.get_wad_data()));
+                game.change_music(MusTrack::Evil);
+            } else if !self.show_end_pic && (self.end_pic.is_some() || self.bunny.is_some()) {
+                self.show_end_pic = true;
+                if self.bunny.is_some() {
+                    game.change_music(MusTrack::Bunny);
+                }
+            }
+        }
+        if self.show_end_pic {
+            if let Some(bunny) = self.bunny.as_mut() {
+                bunny.ticker(game);
+            }
         }
         false
     }
@@ -151,6 +316,12 @@ impl SubsystemTrait for Finale {
         &self.palette
     }
 
+    /// The bunny scroll slides its pictures in from the right of the 320
+    /// wide area
+    fn clip_patches_to_320(&self) -> bool {
+        true
+    }
+
     fn draw(&mut self, buffer: &mut dyn PixelBuffer) {
         self.draw_pixels(buffer);
     }
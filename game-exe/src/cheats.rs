@@ -3,8 +3,8 @@
 use gameplay::log::debug;
 use gameplay::{english, GameMission, PlayerCheat, PowerType, Skill, WeaponType};
 use gamestate::Game;
-use gamestate_traits::sdl2::keyboard::{Keycode, Scancode};
 use gamestate_traits::GameTraits;
+use sdl2::keyboard::{Keycode, Scancode};
 use sound_traits::MusTrack;
 
 pub struct Cheats {
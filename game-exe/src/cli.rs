@@ -1,5 +1,5 @@
 use argh::FromArgs;
-use gameplay::{log, GameOptions, Skill};
+use gameplay::{log, GameOptions, ModernControls, Skill, MAXPLAYERS};
 use render_target::shaders::Shaders;
 
 use crate::config::{self, MusicType};
@@ -35,17 +35,36 @@ pub struct CLIOptions {
     /// disable monsters
     #[argh(switch, short = 'n')]
     pub no_monsters: bool,
-    // /// Monsters respawn after being killed
-    // pub respawn_parm: bool,
+    /// monsters respawn after being killed
+    #[argh(switch)]
+    pub respawn: bool,
     // /// Monsters move faster
     // pub fast_parm: bool,
     /// developer mode. Screen is cleared with green colour for seg/flat drawing
     /// leak checks
     #[argh(switch)]
     pub dev_parm: bool,
-    //     help = "Start a deathmatch game-exe: 1 = classic, 2 = Start a deathmatch 2.0 game-exe.
-    // Weapons do not stay in place and all items respawn after 30 seconds" pub deathmatch: u8,
-    // pub autostart: bool,
+    /// start a deathmatch game, weapons stay in place
+    #[argh(switch)]
+    pub deathmatch: bool,
+    /// start a deathmatch 2.0 game. Weapons do not stay in place and all
+    /// items respawn after 30 seconds. Takes precedence over --deathmatch
+    #[argh(switch)]
+    pub altdeath: bool,
+    /// exit deathmatch levels after this many minutes
+    #[argh(option)]
+    pub timer: Option<u32>,
+    /// exit deathmatch levels when a player reaches this many frags
+    #[argh(option)]
+    pub fraglimit: Option<i32>,
+    /// fill this many other player slots with computer controlled players,
+    /// 1-3. Combine with --deathmatch or --altdeath to fight them
+    #[argh(option)]
+    pub bots: Option<usize>,
+    /// set how well bots play, 0-4 (0: easiest, 4: hardest). Defaults to the
+    /// game skill
+    #[argh(option)]
+    pub bot_skill: Option<Skill>,
     /// set the game-exe skill, 0-4 (0: easiest, 4: hardest)
     #[argh(option, short = 's')]
     pub skill: Option<Skill>,
@@ -65,9 +84,57 @@ pub struct CLIOptions {
     /// music type <fluidsynth, timidity(default)>. Unfinished
     #[argh(option, short = 'M')]
     pub music_type: Option<MusicType>,
-    /// enable demo playback (currently bad due to f32 used in movements)
+    /// enable demo playback (vanilla demos desync, see --fixed-point)
     #[argh(switch, short = 'E')]
     pub enable_demos: bool,
+    /// move things with vanilla fixed-point math and BAM angles
+    #[argh(switch)]
+    pub fixed_point: bool,
+    /// draw fireballs, plasma, fog and some powerups see-through, as BOOM
+    #[argh(switch)]
+    pub translucency: bool,
+    /// look up and down with the mouse. Not used in demos
+    #[argh(switch)]
+    pub free_look: bool,
+    /// with --free-look, shoot where you look instead of auto-aiming up and
+    /// down
+    #[argh(switch)]
+    pub no_autoaim: bool,
+    /// enable the jump and crouch keys. Not used in demos
+    #[argh(switch)]
+    pub jump_crouch: bool,
+    /// record a demo to <name>.lmp, starting a new game on the selected
+    /// skill/episode/map. Written when the game quits
+    #[argh(option)]
+    pub record: Option<String>,
+    /// record demo turning at full resolution (longtics, v1.91 demo format)
+    #[argh(switch)]
+    pub longtics: bool,
+    /// play a single demo from a .lmp file or a lump, then quit
+    #[argh(option)]
+    pub playdemo: Option<String>,
+    /// play a single demo as fast as possible, rendering every tic, then
+    /// print frame timings and quit
+    #[argh(option)]
+    pub timedemo: Option<String>,
+    /// also write the timedemo results to this file as JSON
+    #[argh(option)]
+    pub timedemo_json: Option<String>,
+    /// write the consistency hash and compared state of every tic to this
+    /// file, for finding desyncs between runs or builds
+    #[argh(option)]
+    pub consistency_dump: Option<String>,
+    /// check every tic against a file written by --consistency-dump and
+    /// report the first tic that differs
+    #[argh(option)]
+    pub consistency_check: Option<String>,
+    /// start a network game. Comma separated <host:port> of every player in
+    /// player order, including this one. Player 1's options are used
+    #[argh(option)]
+    pub net: Option<String>,
+    /// this player's number in --net, 1-4
+    #[argh(option, default = "1")]
+    pub net_player: usize,
 }
 
 impl From<CLIOptions> for GameOptions {
@@ -80,16 +147,27 @@ impl From<CLIOptions> for GameOptions {
             skill: g.skill.unwrap_or_default(),
             episode: g.episode.unwrap_or_default(),
             map: g.map.unwrap_or_default(),
-            warp: g.map.is_some() || g.episode.is_some(),
+            warp: g.map.is_some() || g.episode.is_some() || g.record.is_some(),
             hi_res: g.hi_res && !g.lo_res,
             verbose: g.verbose.unwrap_or(log::LevelFilter::Warn),
-            respawn_parm: false,
+            respawn_parm: g.respawn,
             respawn_monsters: false,
             fast_parm: false,
-            deathmatch: 0,
+            deathmatch: if g.altdeath { 2 } else { g.deathmatch as u8 },
+            time_limit: g.timer.filter(|t| *t > 0),
+            frag_limit: g.fraglimit.filter(|f| *f > 0),
             autostart: false,
             enable_demos: g.enable_demos,
             netgame: false,
+            bots: g.bots.unwrap_or_default().min(MAXPLAYERS - 1),
+            bot_skill: g.bot_skill.or(g.skill).unwrap_or_default(),
+            fixed_point: g.fixed_point,
+            translucency: g.translucency,
+            controls: ModernControls {
+                free_look: g.free_look,
+                autoaim: !g.no_autoaim,
+                jump_crouch: g.jump_crouch,
+            },
         }
     }
 }
@@ -7,14 +7,16 @@ use input::config::InputConfig;
 use nanoserde::{DeRon, SerRon};
 use render_target::shaders::Shaders;
 use sound_sdl2::timidity::GusMemSize;
-use std::fs::{create_dir, File, OpenOptions};
+use std::fs::{create_dir, create_dir_all, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::str::FromStr;
 
 const LOG_TAG: &str = "UserConfig";
 
-fn get_cfg_file() -> PathBuf {
+/// The directory the user config and cached data are kept in, created if it
+/// doesn't exist
+pub fn get_config_dir() -> PathBuf {
     let mut dir =
         config_dir().unwrap_or_else(|| panic!("{}: Couldn't open user config dir", LOG_TAG));
     dir.push(BASE_DIR);
@@ -22,10 +24,28 @@ fn get_cfg_file() -> PathBuf {
         create_dir(&dir)
             .unwrap_or_else(|e| panic!("{}: Couldn't create {:?}: {}", LOG_TAG, dir, e));
     }
+    dir
+}
+
+fn get_cfg_file() -> PathBuf {
+    let mut dir = get_config_dir();
     dir.push("user.toml");
     dir
 }
 
+/// The directory save slots are kept in, created if it doesn't exist
+pub fn get_save_dir() -> PathBuf {
+    let mut dir =
+        config_dir().unwrap_or_else(|| panic!("{}: Couldn't open user config dir", LOG_TAG));
+    dir.push(BASE_DIR);
+    dir.push("saves");
+    if !dir.exists() {
+        create_dir_all(&dir)
+            .unwrap_or_else(|e| panic!("{}: Couldn't create {:?}: {}", LOG_TAG, dir, e));
+    }
+    dir
+}
+
 #[derive(Debug, Default, PartialEq, PartialOrd, Clone, Copy, DeRon, SerRon)]
 pub enum RenderType {
     /// Purely software. Typically used with blitting a framebuffer maintained
@@ -10,7 +10,7 @@
 //! - `Intermission`
 //! - `Messages`
 //! - `Finale`
-//! - others may be added such as automap
+//! - `AutoMap`
 //!
 //! Note that the sound system runs on its own thread.
 //!
@@ -19,32 +19,40 @@
 //! and the overall gamestate.
 
 use std::error::Error;
-use std::mem;
+use std::time::{Duration, Instant};
+use std::{fs, mem};
 
+use automap_doom::AutoMap;
 use finale_doom::Finale;
 use gameplay::log::{self, error, info};
-use gameplay::MapObject;
+use gameplay::{MapObject, TICRATE};
 use gamestate::subsystems::GameSubsystem;
 use gamestate::Game;
-use gamestate_traits::sdl2::keyboard::Scancode;
-use gamestate_traits::sdl2::render::Canvas;
-use gamestate_traits::sdl2::video::Window;
-use gamestate_traits::{GameState, SubsystemTrait};
+use gamestate_traits::{AutomapState, GameState, GameTraits, Scancode, SubsystemTrait};
 use hud_doom::Messages;
 use input::Input;
 use intermission_doom::Intermission;
 use menu_doom::MenuDoom;
 use render_soft::SoftwareRenderer;
 use render_target::{PixelBuffer, PlayRenderer, RenderTarget, RenderType};
+use sdl2::keyboard::Scancode as SdlScancode;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
 use sound_traits::SoundAction;
 use statusbar_doom::Statusbar;
 use wad::types::WadPatch;
 
 use crate::cheats::Cheats;
+use crate::demo_controls::demo_responder;
+use crate::timedemo::{FrameTimes, TimeDemo};
 use crate::timestep::TimeStep;
 use crate::wipe::Wipe;
 use crate::CLIOptions;
 
+/// Most tics run per frame while skipping through a demo, so that input is
+/// still read during long skips
+const SKIP_TICS_PER_FRAME: u32 = 60 * TICRATE as u32;
+
 /// Used to set correct buffer width for screen dimensions matching the OD Doom
 /// height
 fn buffer_dimensions(width: f32, height: f32, double: bool) -> (usize, usize) {
@@ -126,34 +134,64 @@ pub fn d_doom_loop(
 
     let mut machines = GameSubsystem {
         statusbar: Statusbar::new(game.game_type.mode, &game.wad_data),
+        automap: AutoMap::new(&game.wad_data),
         intermission: Intermission::new(game.game_type.mode, &game.wad_data),
         hud_msgs: Messages::new(&game.wad_data),
         finale: Finale::new(&game.wad_data),
     };
 
     // Start demo playback and titlescreens +
-    if options.episode.is_none() && options.map.is_none() {
+    if options.episode.is_none()
+        && options.map.is_none()
+        && options.record.is_none()
+        && options.playdemo.is_none()
+        && options.timedemo.is_none()
+        && options.net.is_none()
+    {
         game.start_title();
     }
 
+    let mut timedemo = options.timedemo.as_deref().map(TimeDemo::new);
+
     loop {
         if !game.running() {
             break;
         }
+        let frame_start = Instant::now();
         // The game-exe is split in to two parts:
         // - tickers, these update all states (game-exe, menu, hud, automap etc)
         // - drawers, these take a state from above and display it to the user
 
+        timestep.set_speed(if game.demo_playing() {
+            game.demo_speed()
+        } else {
+            1.0
+        });
         // Update the game-exe state
-        try_run_tics(
+        let thinkers = try_run_tics(
             &mut game,
             &mut input,
             &mut menu,
             &mut machines,
             &mut cheats,
             &mut timestep,
+            timedemo.is_some(),
         );
 
+        // Skipping through a demo runs tics without drawing anything
+        if game.demo_skipping() {
+            for _ in 0..SKIP_TICS_PER_FRAME {
+                if !game.demo_skipping() || !game.running() {
+                    break;
+                }
+                run_tic(&mut game, &mut menu, &mut machines);
+            }
+            // Don't count the time spent skipping towards the next tics
+            timestep.delta();
+            continue;
+        }
+        machines.hud_msgs.set_demo_status(game.demo_status());
+
         // Update the positional sounds
         // Update the listener of the sound server. Will always be consoleplayer.
         if let Some(mobj) = game.players[game.consoleplayer].mobj() {
@@ -185,7 +223,26 @@ pub fn d_doom_loop(
             info!("{:?}", fps);
         }
 
+        let blit_start = Instant::now();
         render_buffer.blit(&mut canvas);
+
+        if let Some(timedemo) = timedemo.as_mut() {
+            let render = renderer.take_timings();
+            timedemo.add_frame(FrameTimes {
+                frame: frame_start.elapsed(),
+                thinkers,
+                bsp: render.bsp,
+                masked: render.masked,
+                blit: blit_start.elapsed(),
+            });
+        }
+    }
+
+    if let Some(timedemo) = timedemo {
+        print!("{timedemo}");
+        if let Some(path) = options.timedemo_json.as_deref() {
+            fs::write(path, timedemo.to_json())?;
+        }
     }
 
     // Explicit drop to ensure shutdown happens
@@ -242,6 +299,7 @@ fn d_display(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     game: &mut Game,
     disp_buf: &mut RenderTarget, // Display from this buffer
@@ -249,16 +307,13 @@ fn d_display(
     canvas: &mut Canvas<Window>,
     timestep: &mut TimeStep,
 ) {
-    let automap_active = false;
-    //if (gamestate == GS_LEVEL && !automapactive && gametic)
-
     let wipe = game.gamestate != game.wipe_game_state;
 
     // Drawing order is different for RUST4DOOM as the screensize-statusbar is
     // never taken in to account. A full Doom-style statusbar will never be added
     // instead an "overlay" style bar will be done.
     if game.gamestate == GameState::Level && game.game_tic != 0 {
-        if !automap_active {
+        if game.automap() != AutomapState::FullScreen {
             if let Some(ref level) = game.level {
                 if !game.players_in_game[game.consoleplayer] {
                     return;
@@ -287,7 +342,7 @@ fn d_display(
 
     match game.gamestate {
         GameState::Level => {
-            // TODO: Automap draw
+            machines.automap.draw(draw_buf.pixel_buffer());
             machines.statusbar.draw(draw_buf.pixel_buffer());
             machines.hud_msgs.draw(draw_buf.pixel_buffer());
         }
@@ -320,7 +375,11 @@ fn d_display(
     // Doom uses a loop here. The thing about it is that while the loop is running
     // there can be no input, so the menu can't be activated. I think with Doom the
     // input event queue was still filled via interrupt.
-    let mut wipe = Wipe::new(disp_buf.width() as i32, disp_buf.height() as i32);
+    let mut wipe = Wipe::new(
+        disp_buf.width() as i32,
+        disp_buf.height() as i32,
+        game.random_mut(),
+    );
     loop {
         let mut done = false;
         timestep.run_this(|_| {
@@ -337,6 +396,9 @@ fn d_display(
     //menu.draw(disp_buf); // menu is drawn on top of wipes too
 }
 
+/// Runs the tickers for as many tics as are due, or exactly one if
+/// `uncapped` (timedemo). Returns the time spent in the tickers.
+#[allow(clippy::too_many_arguments)]
 fn try_run_tics(
     game: &mut Game,
     input: &mut Input,
@@ -346,24 +408,60 @@ fn try_run_tics(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     cheats: &mut Cheats,
     timestep: &mut TimeStep,
-) {
+    uncapped: bool,
+) -> Duration {
     // TODO: net.c starts here
     process_events(game, input, menu, machinations, cheats); // D_ProcessEvents
 
-    // Build tics here?
-    timestep.run_this(|_| {
-        if game.demo.advance {
-            game.do_advance_demo();
-        }
-        // Did menu take control?
-        if !menu.ticker(game) {
-            game.ticker(machinations); // G_Ticker
+    let mut ticker_time = Duration::ZERO;
+    if game.net_playing() {
+        // Cmds are sent at the tic rate, and tics are run once the cmds of
+        // every player have arrived
+        timestep.run_this(|_| game.net_build_tic());
+        while game.net_tic_ready() {
+            let start = Instant::now();
+            run_tic(game, menu, machinations);
+            ticker_time += start.elapsed();
         }
-        game.game_tic += 1;
-    });
+        return ticker_time;
+    }
+    let tic = |_| {
+        let start = Instant::now();
+        run_tic(game, menu, machinations);
+        ticker_time += start.elapsed();
+    };
+    if uncapped {
+        timestep.run_uncapped(tic);
+    } else {
+        timestep.run_this(tic);
+    }
+    ticker_time
+}
+
+/// Run the tickers for a single tic
+fn run_tic(
+    game: &mut Game,
+    menu: &mut impl SubsystemTrait,
+    machinations: &mut GameSubsystem<
+        impl SubsystemTrait,
+        impl SubsystemTrait,
+        impl SubsystemTrait,
+        impl SubsystemTrait,
+        impl SubsystemTrait,
+    >,
+) {
+    if game.demo.advance {
+        game.do_advance_demo();
+    }
+    // Did menu take control? A network game keeps running regardless
+    if !menu.ticker(game) || game.net_playing() {
+        game.ticker(machinations); // G_Ticker
+    }
+    game.game_tic += 1;
 }
 
 fn process_events(
@@ -375,15 +473,19 @@ fn process_events(
         impl SubsystemTrait,
         impl SubsystemTrait,
         impl SubsystemTrait,
+        impl SubsystemTrait,
     >,
     cheats: &mut Cheats,
 ) {
     // required for cheats and menu so they don't receive multiple key-press fo same
     // key
-    let callback = |sc: Scancode| {
+    let callback = |sdl_sc: SdlScancode| {
         if game.level.is_some() {
-            cheats.check_input(sc, game);
+            cheats.check_input(sdl_sc, game);
         }
+        let Some(sc) = Scancode::from_i32(sdl_sc as i32) else {
+            return false;
+        };
 
         // Menu also has hotkeys like F1, so check at all times
         if menu.responder(sc, game) {
@@ -394,6 +496,14 @@ fn process_events(
             return true; // Menu took event
         }
 
+        if game.gamestate == GameState::Level && machinations.automap.responder(sc, game) {
+            return true;
+        }
+
+        if demo_responder(sc, game) {
+            return true;
+        }
+
         // We want intermission to check checks only if the level isn't loaded
         if game.level.is_none() {
             match game.gamestate {
@@ -416,10 +526,8 @@ fn process_events(
 
     if !input.update(callback) {
         let console_player = game.consoleplayer;
-        // net update does i/o and buildcmds...
-        // TODO: NetUpdate(); // send out any new accumulation
-
-        // TODO: Network code would update each player slot with incoming TicCmds...
+        // In a network game this is sent by `net_build_tic`
+        input.events.set_free_look(game.options.controls.free_look);
         let cmd = input.events.build_tic_cmd(&input.config);
         game.netcmds[console_player][0] = cmd;
     }
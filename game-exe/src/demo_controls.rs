@@ -0,0 +1,36 @@
+//! Keyboard controls for demo playback:
+//!
+//! - `Pause`/`Space`: pause or resume
+//! - `.`: run a single tic, pausing if not paused
+//! - `=`/`-`: double or halve the playback speed, `Backspace` resets it
+//! - `Right`/`Left`: skip forward or back ten seconds
+//! - `Home`: back to the start
+//! - `PageDown`: skip to the next level
+
+use gameplay::TICRATE;
+use gamestate::Game;
+use gamestate_traits::Scancode;
+
+/// How far `Left` and `Right` skip
+const SKIP_TICS: u32 = 10 * TICRATE as u32;
+
+/// Returns true if the key was a demo control. Does nothing unless a demo
+/// from `-playdemo` is playing, attract demos leave keys to the menu.
+pub fn demo_responder(sc: Scancode, game: &mut Game) -> bool {
+    if !game.single_demo_playing() {
+        return false;
+    }
+    match sc {
+        Scancode::Pause | Scancode::Space => game.toggle_demo_pause(),
+        Scancode::Period => game.demo_step(),
+        Scancode::Equals | Scancode::KpPlus => game.set_demo_speed(game.demo_speed() * 2.0),
+        Scancode::Minus | Scancode::KpMinus => game.set_demo_speed(game.demo_speed() / 2.0),
+        Scancode::Backspace => game.set_demo_speed(1.0),
+        Scancode::Right => game.demo_skip_to_tic(game.demo_tic() + SKIP_TICS),
+        Scancode::Left => game.demo_skip_to_tic(game.demo_tic().saturating_sub(SKIP_TICS)),
+        Scancode::Home => game.demo_skip_to_tic(0),
+        Scancode::PageDown => game.demo_skip_to_next_level(),
+        _ => return false,
+    }
+    true
+}
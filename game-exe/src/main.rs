@@ -2,26 +2,30 @@ mod cheats;
 mod cli;
 mod config;
 mod d_main;
+mod demo_controls;
+mod timedemo;
 mod timestep;
 mod wipe;
 
 use cli::*;
 use config::MusicType;
 use dirs::{cache_dir, data_dir};
-use gamestate_traits::sdl2;
 use std::env::set_var;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use d_main::d_doom_loop;
 use env_logger::fmt::Color;
-use gamestate::Game;
+use gamestate::{spawn_sound_server, Game};
 
 use crate::config::UserConfig;
 use gameplay::log;
 use input::Input;
+use net::{checksum, NetConfig, NetGame, NetSetup};
 use sound_sdl2::timidity::{make_timidity_cfg, GusMemSize};
 
 use crate::log::{info, warn};
@@ -30,6 +34,8 @@ use wad::WadData;
 const SOUND_DIR: &str = "room4doom/sound/";
 const TIMIDITY_CFG: &str = "timidity.cfg";
 const BASE_DIR: &str = "room4doom/";
+/// How long to wait for every player of a network game to start
+const NET_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
 
 fn setup_timidity(music_type: MusicType, gus_mem: GusMemSize, wad: &WadData) {
     if music_type == MusicType::FluidSynth {
@@ -58,6 +64,53 @@ fn setup_timidity(music_type: MusicType, gus_mem: GusMemSize, wad: &WadData) {
     }
 }
 
+/// Connect to the other players of a network game. Blocks until every player
+/// has started, using this game's options if this is player 1.
+fn connect_netgame(addrs: &str, player: usize, game: &Game) -> Result<NetGame, Box<dyn Error>> {
+    let addrs = addrs
+        .split(',')
+        .map(|addr| {
+            addr.trim()
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| format!("Could not resolve {addr}").into())
+        })
+        .collect::<Result<Vec<SocketAddr>, Box<dyn Error>>>()?;
+    if player == 0 || player > addrs.len() {
+        return Err(format!("--net-player must be 1 to {}", addrs.len()).into());
+    }
+    let player = player - 1;
+
+    let mut wads = fs::read(&game.options.iwad)?;
+    for pwad in game.options.pwad.iter() {
+        wads.extend(fs::read(pwad)?);
+    }
+    let options = &game.options;
+    let config = NetConfig {
+        player,
+        wad_checksum: checksum(&wads),
+        setup: NetSetup {
+            skill: options.skill as i8 as u8,
+            episode: options.episode as u8,
+            map: options.map as u8,
+            deathmatch: options.deathmatch,
+            no_monsters: options.no_monsters,
+            respawn: options.respawn_parm,
+            fast: options.fast_parm,
+            players: addrs.len() as u8,
+            time_limit: options.time_limit.unwrap_or(0).min(u8::MAX as u32) as u8,
+            frag_limit: options.frag_limit.unwrap_or(0).clamp(0, u8::MAX as i32) as u8,
+            free_look: options.controls.free_look,
+            autoaim: options.controls.autoaim,
+            jump_crouch: options.controls.jump_crouch,
+            fixed_point: options.fixed_point,
+        },
+        addrs,
+    };
+    let socket = UdpSocket::bind(("0.0.0.0", config.addrs[player].port()))?;
+    Ok(NetGame::connect(socket, config, NET_CONNECT_TIMEOUT)?)
+}
+
 /// The main `game-exe` crate should take care of initialising a few things
 fn main() -> Result<(), Box<dyn Error>> {
     let mut options: CLIOptions = argh::from_env();
@@ -126,14 +179,39 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let wad = WadData::new(user_config.iwad.clone().into());
     setup_timidity(user_config.music_type, user_config.gus_mem_size, &wad);
+    let snd_server = match sound_sdl2::Snd::new(snd_ctx, &wad) {
+        Ok(snd) => Some(spawn_sound_server(snd)?),
+        Err(e) => {
+            warn!("Could not set up sound server: {e}");
+            None
+        }
+    };
 
-    let game = Game::new(
+    let mut game = Game::new(
         options.clone().into(),
         wad,
-        snd_ctx,
+        snd_server,
         user_config.sfx_vol,
         user_config.mus_vol,
+        config::get_save_dir(),
+        Some(&config::get_config_dir()),
     );
+    if let Some(name) = options.record.as_deref() {
+        game.record_demo(name, options.longtics);
+    }
+    if let Some(path) = options.consistency_dump.as_deref() {
+        game.dump_consistency(path)?;
+    }
+    if let Some(path) = options.consistency_check.as_deref() {
+        game.check_consistency(path)?;
+    }
+    if let Some(name) = options.playdemo.as_deref().or(options.timedemo.as_deref()) {
+        game.play_demo(name);
+    }
+    if let Some(addrs) = options.net.as_deref() {
+        let net = connect_netgame(addrs, options.net_player, &game)?;
+        game.start_netgame(net);
+    }
 
     if let Some(fullscreen) = options.fullscreen {
         if fullscreen {
@@ -0,0 +1,269 @@
+//! Timedemo benchmarking. The demo is run one tic per frame with no pacing,
+//! every tic is rendered, and the time spent in each part of the frame is
+//! collected for a report when the demo ends.
+//!
+//! Doom source name `g_game` (timedemo section)
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use gameplay::TICRATE;
+use nanoserde::SerJson;
+
+/// Time spent in each part of a single frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrameTimes {
+    /// The whole frame, including input and sound updates
+    pub frame: Duration,
+    /// Game, menu and subsystem tickers
+    pub thinkers: Duration,
+    /// BSP traversal, walls and flats
+    pub bsp: Duration,
+    /// Sprites and masked mid-textures
+    pub masked: Duration,
+    /// Copying the finished buffer to the window
+    pub blit: Duration,
+}
+
+/// Running total, minimum and maximum of one part of the frame
+#[derive(Debug, Default, Clone, Copy)]
+struct Span {
+    total: Duration,
+    min: Option<Duration>,
+    max: Duration,
+}
+
+impl Span {
+    fn add(&mut self, time: Duration) {
+        self.total += time;
+        self.min = Some(self.min.map_or(time, |min| min.min(time)));
+        self.max = self.max.max(time);
+    }
+
+    fn avg_ms(&self, frames: u32) -> f64 {
+        if frames == 0 {
+            return 0.0;
+        }
+        self.total.as_secs_f64() * 1000.0 / frames as f64
+    }
+
+    fn min_ms(&self) -> f64 {
+        self.min.unwrap_or_default().as_secs_f64() * 1000.0
+    }
+
+    fn max_ms(&self) -> f64 {
+        self.max.as_secs_f64() * 1000.0
+    }
+
+    fn report(&self, frames: u32) -> SpanReport {
+        SpanReport {
+            avg_ms: self.avg_ms(frames),
+            min_ms: self.min_ms(),
+            max_ms: self.max_ms(),
+            total_ms: self.total.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// A `Span` as written to the JSON report
+#[derive(SerJson)]
+struct SpanReport {
+    avg_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+    total_ms: f64,
+}
+
+/// The JSON report written at the end of a timedemo
+#[derive(SerJson)]
+struct Report {
+    demo: String,
+    gametics: u32,
+    realtics: u32,
+    seconds: f64,
+    fps: f64,
+    frame: SpanReport,
+    thinkers: SpanReport,
+    bsp: SpanReport,
+    masked: SpanReport,
+    blit: SpanReport,
+}
+
+/// Collects frame timings while a timedemo runs
+#[derive(Debug)]
+pub struct TimeDemo {
+    demo: String,
+    start: Instant,
+    /// Time from the start to the end of the last frame
+    elapsed: Duration,
+    game_tics: u32,
+    frame: Span,
+    thinkers: Span,
+    bsp: Span,
+    masked: Span,
+    blit: Span,
+}
+
+impl TimeDemo {
+    pub fn new(demo: &str) -> Self {
+        Self {
+            demo: demo.to_string(),
+            start: Instant::now(),
+            elapsed: Duration::ZERO,
+            game_tics: 0,
+            frame: Span::default(),
+            thinkers: Span::default(),
+            bsp: Span::default(),
+            masked: Span::default(),
+            blit: Span::default(),
+        }
+    }
+
+    /// Add a frame. Timedemo runs a single tic per frame so this is also a
+    /// gametic.
+    pub fn add_frame(&mut self, times: FrameTimes) {
+        self.game_tics += 1;
+        self.frame.add(times.frame);
+        self.thinkers.add(times.thinkers);
+        self.bsp.add(times.bsp);
+        self.masked.add(times.masked);
+        self.blit.add(times.blit);
+        self.elapsed = self.start.elapsed();
+    }
+
+    /// The realtics a normal game would have taken for the elapsed time
+    fn real_tics(&self) -> u32 {
+        (self.elapsed.as_secs_f64() * TICRATE as f64) as u32
+    }
+
+    fn fps(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.game_tics as f64 / secs
+    }
+
+    /// The report as a JSON object
+    pub fn to_json(&self) -> String {
+        let n = self.game_tics;
+        let report = Report {
+            demo: self.demo.clone(),
+            gametics: n,
+            realtics: self.real_tics(),
+            seconds: self.elapsed.as_secs_f64(),
+            fps: self.fps(),
+            frame: self.frame.report(n),
+            thinkers: self.thinkers.report(n),
+            bsp: self.bsp.report(n),
+            masked: self.masked.report(n),
+            blit: self.blit.report(n),
+        };
+        report.serialize_json() + "\n"
+    }
+}
+
+impl fmt::Display for TimeDemo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.game_tics;
+        writeln!(
+            f,
+            "timed {} gametics in {} realtics ({:.1} fps)",
+            n,
+            self.real_tics(),
+            self.fps()
+        )?;
+        writeln!(
+            f,
+            "  {:<9}{:>10}{:>10}{:>10}",
+            "", "avg ms", "min ms", "max ms"
+        )?;
+        for (name, span) in [
+            ("frame", &self.frame),
+            ("thinkers", &self.thinkers),
+            ("bsp", &self.bsp),
+            ("masked", &self.masked),
+            ("blit", &self.blit),
+        ] {
+            writeln!(
+                f,
+                "  {:<9}{:>10.3}{:>10.3}{:>10.3}",
+                name,
+                span.avg_ms(n),
+                span.min_ms(),
+                span.max_ms()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{FrameTimes, Span, TimeDemo};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn span_avg_min_max() {
+        let mut span = Span::default();
+        assert_eq!(
+            (span.avg_ms(0), span.min_ms(), span.max_ms()),
+            (0.0, 0.0, 0.0)
+        );
+        for time in [4, 2, 9] {
+            span.add(ms(time));
+        }
+        assert_eq!(span.avg_ms(3), 5.0);
+        assert_eq!(span.min_ms(), 2.0);
+        assert_eq!(span.max_ms(), 9.0);
+    }
+
+    fn timed(frames: u32, elapsed: Duration) -> TimeDemo {
+        let mut timedemo = TimeDemo::new("demo1");
+        for i in 0..frames {
+            timedemo.add_frame(FrameTimes {
+                frame: ms(10 + i as u64 % 3),
+                thinkers: ms(2),
+                bsp: ms(5),
+                masked: ms(1),
+                blit: ms(1),
+            });
+        }
+        timedemo.elapsed = elapsed;
+        timedemo
+    }
+
+    #[test]
+    fn realtics_and_fps() {
+        let timedemo = timed(70, ms(2000));
+        assert_eq!(timedemo.real_tics(), 70);
+        assert_eq!(timedemo.fps(), 35.0);
+
+        // partial tics are dropped
+        let timedemo = timed(70, ms(1500));
+        assert_eq!(timedemo.real_tics(), 52);
+
+        let timedemo = timed(0, Duration::ZERO);
+        assert_eq!((timedemo.real_tics(), timedemo.fps()), (0, 0.0));
+    }
+
+    #[test]
+    fn json_report() {
+        let json = timed(3, ms(500)).to_json();
+        assert!(json.ends_with("}\n"));
+        for field in [
+            r#""demo":"demo1""#,
+            r#""gametics":3"#,
+            r#""realtics":17"#,
+            r#""frame":{"avg_ms":11.0,"min_ms":10.0,"max_ms":12.0,"total_ms":33.0}"#,
+            r#""bsp":{"avg_ms":5.0,"min_ms":5.0,"max_ms":5.0,"total_ms":15.0}"#,
+        ] {
+            assert!(json.contains(field), "{field} missing from {json}");
+        }
+    }
+}
@@ -15,6 +15,8 @@ pub struct TimeStep {
     run_tics: u32,
     last_tics: u32,
     lag: f32,
+    /// Tics run per 35Hz tic, used to fast-forward or slow demos
+    speed: f32,
 }
 
 #[derive(Debug)]
@@ -42,9 +44,15 @@ impl TimeStep {
             run_tics: 0,
             last_tics: 0,
             lag: 0.0,
+            speed: 1.0,
         }
     }
 
+    /// Run tics at `speed` times the normal rate
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
     pub fn delta(&mut self) -> f32 {
         let current_time = Instant::now();
         let delta = current_time.duration_since(self.last_time).as_micros() as f32 * 0.001;
@@ -58,13 +66,23 @@ impl TimeStep {
     pub fn run_this(&mut self, mut run_this: impl FnMut(f32)) {
         let dt = self.delta();
         self.lag += dt;
-        while self.lag >= MS_PER_UPDATE {
+        let ms_per_update = MS_PER_UPDATE / self.speed;
+        while self.lag >= ms_per_update {
             run_this(dt);
-            self.lag -= MS_PER_UPDATE;
+            self.lag -= ms_per_update;
             self.run_tics += 1;
         }
     }
 
+    /// Run exactly one tic regardless of how much time has passed. Used by
+    /// timedemo to run as fast as the machine allows.
+    pub fn run_uncapped(&mut self, mut run_this: impl FnMut(f32)) {
+        let dt = self.delta();
+        run_this(dt);
+        self.lag = 0.0;
+        self.run_tics += 1;
+    }
+
     pub fn frame_rate(&mut self) -> Option<FrameData> {
         self.frame_count += 1;
         self.frame_time += self.delta_time;
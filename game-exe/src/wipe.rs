@@ -1,4 +1,4 @@
-use gameplay::m_random;
+use gameplay::Random;
 use render_target::PixelBuffer;
 
 #[derive(Debug)]
@@ -9,12 +9,12 @@ pub(crate) struct Wipe {
 }
 
 impl Wipe {
-    pub(crate) fn new(width: i32, height: i32) -> Self {
+    pub(crate) fn new(width: i32, height: i32, random: &mut Random) -> Self {
         let mut y = Vec::with_capacity(width as usize);
-        y.push(-(m_random() % 16));
+        y.push(-(random.m_random() % 16));
 
         for i in 1..width as usize {
-            let r = (m_random() % 3) - 1;
+            let r = (random.m_random() % 3) - 1;
             y.push(y[i - 1] + r);
             if y[i] > 0 {
                 y[i] = 0;
@@ -0,0 +1,584 @@
+//! Computer controlled players. A bot takes a player slot and, like a person
+//! at the keyboard, only makes the `TicCmd` for its player each tic. Games with
+//! bots are recorded and played back as ordinary demos.
+//!
+//! Bots find their way with a `PathGraph` of the level's sectors, open doors
+//! and ride lifts by pressing use, collect the items they need and shoot what
+//! they can see.
+
+mod path;
+
+use std::f32::consts::{PI, TAU};
+
+use glam::Vec2;
+
+use crate::doom_def::{
+    AmmoType, PowerType, WeaponType, MAXPLAYERS, MISSILERANGE, USERANGE, WEAPON_INFO,
+};
+use crate::info::SpriteNum;
+use crate::level::Level;
+use crate::player::{Player, PlayerState};
+use crate::thing::{MapObjFlag, MapObject};
+use crate::tic_cmd::{TicCmd, FORWARDMOVE, SIDEMOVE, TIC_CMD_BUTTONS};
+use crate::utilities::{point_to_angle_2, Random};
+use crate::{Angle, GameOptions, Skill};
+
+use self::path::{Crossing, PathGraph, Waypoint};
+
+/// Tics between choosing a new goal and path to it
+const REPLAN_TICS: u32 = 35;
+/// How close a waypoint must be to count as reached
+const WAYPOINT_RADIUS: f32 = 24.0;
+/// Items further than this aren't considered
+const ITEM_RANGE: f32 = 2048.0;
+/// Tics without moving before a bot tries to get unstuck
+const STUCK_TICS: u32 = 12;
+/// Tics spent sidestepping to get unstuck
+const DODGE_TICS: u32 = 16;
+/// Nearest enemies checked for line of sight each tic
+const SIGHT_CHECKS: usize = 4;
+
+/// Weapons in the order they are preferred, with the ammo used per shot
+const WEAPON_PREFERENCE: [(WeaponType, u32); 9] = [
+    (WeaponType::Plasma, 1),
+    (WeaponType::SuperShotgun, 2),
+    (WeaponType::Chaingun, 1),
+    (WeaponType::BFG, 40),
+    (WeaponType::Shotgun, 1),
+    (WeaponType::Missile, 1),
+    (WeaponType::Pistol, 1),
+    (WeaponType::Chainsaw, 0),
+    (WeaponType::Fist, 0),
+];
+
+/// How well a bot plays, set by the `Skill` it is given
+#[derive(Debug, Clone, Copy)]
+struct Traits {
+    /// Tics an enemy must be in sight before firing at it
+    reaction: u32,
+    /// Largest aiming error, in radians
+    aim_error: f32,
+    /// Largest turn in a tic, as `TicCmd::angleturn`
+    turn: i16,
+    /// Runs instead of walking
+    run: bool,
+}
+
+impl Traits {
+    fn new(skill: Skill) -> Self {
+        match skill {
+            Skill::NoItems | Skill::Baby => Self {
+                reaction: 35,
+                aim_error: 0.25,
+                turn: 320,
+                run: false,
+            },
+            Skill::Easy => Self {
+                reaction: 24,
+                aim_error: 0.16,
+                turn: 480,
+                run: false,
+            },
+            Skill::Medium => Self {
+                reaction: 14,
+                aim_error: 0.09,
+                turn: 640,
+                run: true,
+            },
+            Skill::Hard => Self {
+                reaction: 8,
+                aim_error: 0.05,
+                turn: 1024,
+                run: true,
+            },
+            Skill::Nightmare => Self {
+                reaction: 4,
+                aim_error: 0.02,
+                turn: 1280,
+                run: true,
+            },
+        }
+    }
+}
+
+/// Somewhere a bot is heading
+#[derive(Debug, Clone, Copy)]
+struct Goal {
+    point: Vec2,
+    sector: usize,
+}
+
+/// A computer controlled player
+#[derive(Debug)]
+pub struct Bot {
+    player: usize,
+    traits: Traits,
+    /// Bots have their own random numbers so they don't change the game's
+    random: Random,
+    path: Vec<Waypoint>,
+    /// Level tic to choose a goal again
+    replan: u32,
+    /// Items that couldn't be reached on this level
+    unreachable: Vec<Vec2>,
+    /// The enemy being fired at. Only compared, never followed
+    target: Option<*const MapObject>,
+    /// Tics the target has been in sight
+    seen: u32,
+    aim_offset: f32,
+    last_xy: Vec2,
+    still: u32,
+    /// Tics left sidestepping, and which way
+    dodge: (u32, i8),
+    /// Strafe direction while fighting
+    strafe: i8,
+}
+
+impl Bot {
+    fn new(player: usize, skill: Skill) -> Self {
+        let mut random = Random::new();
+        // Start each bot at a different point in the table
+        for _ in 0..player * 64 {
+            random.m_random();
+        }
+        Self {
+            player,
+            traits: Traits::new(skill),
+            random,
+            path: Vec::new(),
+            replan: 0,
+            unreachable: Vec::new(),
+            target: None,
+            seen: 0,
+            aim_offset: 0.0,
+            last_xy: Vec2::ZERO,
+            still: 0,
+            dodge: (0, 0),
+            strafe: 1,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.path.clear();
+        self.replan = 0;
+        self.unreachable.clear();
+        self.target = None;
+        self.seen = 0;
+        self.still = 0;
+        self.dodge = (0, 0);
+    }
+
+    /// Decide what the bot's player does this tic
+    fn think(&mut self, graph: &PathGraph, level: &mut Level) -> TicCmd {
+        let mut cmd = TicCmd::new();
+        let player = unsafe { &mut *(&mut level.players_mut()[self.player] as *mut Player) };
+        if player.player_state == PlayerState::Dead {
+            self.reset();
+            // Let go of use between presses so the player respawns
+            if self.random.m_random() < 32 {
+                cmd.buttons |= TIC_CMD_BUTTONS.bt_use;
+            }
+            return cmd;
+        }
+        let Some(mobj) = player.mobj_raw() else {
+            return cmd;
+        };
+        let mobj = unsafe { &mut *mobj };
+        let xy = mobj.xyz.truncate();
+
+        let target = self.find_target(mobj, level);
+        let mut facing = None;
+        if let Some(target) = target {
+            let target = unsafe { &*target };
+            if self.target != Some(target as *const _) {
+                self.target = Some(target);
+                self.seen = 0;
+            }
+            self.seen += 1;
+            if self.seen % 8 == 1 {
+                let error = self.random.m_random() as f32 / 255.0 * 2.0 - 1.0;
+                self.aim_offset = error * self.traits.aim_error;
+            }
+            let angle = point_to_angle_2(target.xyz, mobj.xyz) + self.aim_offset;
+            facing = Some(angle);
+            if level.options.controls.free_aim() {
+                // Shots go where the bot looks, so look at the target
+                let dz =
+                    target.xyz.z + target.height / 2.0 - (mobj.xyz.z + mobj.height / 2.0 + 8.0);
+                let pitch = dz.atan2(target.xyz.truncate().distance(xy));
+                let turn = (pitch - player.pitch) * 32768.0 / PI;
+                cmd.pitch = turn.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            }
+            if self.seen > self.traits.reaction && angle_diff(angle, mobj.angle).abs() < 0.2 {
+                let mut bsp_trace = mobj.get_shoot_bsp_trace(MISSILERANGE);
+                let fire = match mobj.aim_line_attack(MISSILERANGE, &mut bsp_trace) {
+                    // Don't shoot other players in co-op
+                    Some(aim) => level.options.deathmatch != 0 || aim.line_target.player.is_none(),
+                    // Nothing for the auto-aim to lock on to, so only fire if
+                    // the aim is close
+                    None => self.aim_offset.abs() < 0.05,
+                };
+                if fire {
+                    cmd.buttons |= TIC_CMD_BUTTONS.bt_attack;
+                }
+            }
+            if self.level_tic(level).is_multiple_of(24) {
+                self.strafe = if self.random.m_random() < 128 { -1 } else { 1 };
+            }
+        } else {
+            self.target = None;
+            self.seen = 0;
+        }
+
+        if level.level_time >= self.replan {
+            self.plan(graph, mobj, target, level);
+        }
+        while self
+            .path
+            .first()
+            .is_some_and(|w| w.point.distance(xy) < WAYPOINT_RADIUS && self.path.len() > 1)
+        {
+            self.path.remove(0);
+        }
+
+        let mut heading = None;
+        let mut opening = false;
+        if let Some(waypoint) = self.path.first().copied() {
+            let to = waypoint.point - xy;
+            if to.length() > WAYPOINT_RADIUS || self.path.len() > 1 {
+                heading = Some(Angle::new(to.y.atan2(to.x)));
+            }
+            if waypoint.crossing == Crossing::Use && to.length() < USERANGE + mobj.radius {
+                // Face the line to press use on it, and wait for it to open
+                opening = true;
+                if facing.is_none() {
+                    facing = heading;
+                }
+                if level.level_time.is_multiple_of(2) {
+                    cmd.buttons |= TIC_CMD_BUTTONS.bt_use;
+                }
+            }
+        }
+
+        // Get unstuck by pressing use and sidestepping for a while
+        let moved = xy.distance(self.last_xy);
+        self.last_xy = xy;
+        if heading.is_some() && !opening && moved < 1.0 {
+            self.still += 1;
+        } else {
+            self.still = 0;
+        }
+        if self.still > STUCK_TICS {
+            self.still = 0;
+            let side = if self.random.m_random() < 128 { -1 } else { 1 };
+            self.dodge = (DODGE_TICS, side);
+            self.replan = level.level_time + DODGE_TICS;
+            cmd.buttons |= TIC_CMD_BUTTONS.bt_use;
+        }
+
+        let facing = facing.or(heading).unwrap_or(mobj.angle);
+        let turn = angle_diff(facing, mobj.angle) / TAU * 65536.0;
+        cmd.angleturn =
+            (turn as i32).clamp(-(self.traits.turn as i32), self.traits.turn as i32) as i16;
+
+        let run = self.traits.run as usize;
+        let mut forward = 0.0;
+        let mut side = 0.0;
+        if let Some(heading) = heading {
+            // Move towards the heading whichever way the bot is facing
+            let delta = angle_diff(heading, mobj.angle);
+            forward = delta.cos();
+            side = -delta.sin();
+        }
+        if target.is_some() && !is_melee(player.status.readyweapon) {
+            side += self.strafe as f32 * 0.5;
+        }
+        if self.dodge.0 > 0 {
+            self.dodge.0 -= 1;
+            side = self.dodge.1 as f32;
+        }
+        cmd.forwardmove = (forward.clamp(-1.0, 1.0) * FORWARDMOVE[run] as f32) as i8;
+        cmd.sidemove = (side.clamp(-1.0, 1.0) * SIDEMOVE[run] as f32) as i8;
+
+        if cmd.buttons & TIC_CMD_BUTTONS.bt_attack == 0 {
+            self.choose_weapon(
+                player,
+                target.map(|t| unsafe { (*t).xyz.distance(mobj.xyz) }),
+                &mut cmd,
+            );
+        }
+        cmd
+    }
+
+    fn level_tic(&self, level: &Level) -> u32 {
+        level.level_time + self.player as u32
+    }
+
+    /// Find the nearest enemy in sight
+    fn find_target(&mut self, mobj: &mut MapObject, level: &Level) -> Option<*mut MapObject> {
+        let deathmatch = level.options.deathmatch != 0;
+        let mut enemies: Vec<(f32, *mut MapObject)> = level
+            .thinkers
+            .iter()
+            .filter(|t| t.is_mobj())
+            .filter_map(|t| {
+                let other = t.mobj();
+                if std::ptr::eq(other, mobj)
+                    || other.health <= 0
+                    || other.flags & MapObjFlag::Shootable as u32 == 0
+                {
+                    return None;
+                }
+                let enemy = if other.player.is_some() {
+                    deathmatch
+                } else {
+                    other.flags & MapObjFlag::Countkill as u32 != 0
+                };
+                let dist = other.xyz.distance(mobj.xyz);
+                (enemy && dist < MISSILERANGE).then_some((dist, other as *const _ as *mut _))
+            })
+            .collect();
+        enemies.sort_by(|a, b| a.0.total_cmp(&b.0));
+        enemies
+            .into_iter()
+            .take(SIGHT_CHECKS)
+            .find(|(_, other)| mobj.check_sight_target(unsafe { &**other }))
+            .map(|(_, other)| other)
+    }
+
+    /// Choose where to go next and find the path there
+    fn plan(
+        &mut self,
+        graph: &PathGraph,
+        mobj: &MapObject,
+        target: Option<*mut MapObject>,
+        level: &Level,
+    ) {
+        self.replan = level.level_time + REPLAN_TICS;
+        let from = mobj.subsector.sector.num as usize;
+        let xy = mobj.xyz.truncate();
+        let player = unsafe { &*mobj.player.unwrap() };
+
+        let goal = if let Some(target) = target.filter(|_| is_melee(player.status.readyweapon)) {
+            Some(goal_of(unsafe { &*target }))
+        } else {
+            self.choose_item(mobj, player, level)
+                .or_else(|| self.choose_quarry(mobj, level))
+        };
+        let Some(goal) = goal else {
+            self.path.clear();
+            return;
+        };
+        match graph.find(level, from, xy, goal.sector, goal.point) {
+            Some(path) => self.path = path,
+            None => {
+                self.unreachable.push(goal.point);
+                self.path.clear();
+            }
+        }
+    }
+
+    /// The item most worth going for, weighing how much it's needed against
+    /// how far away it is
+    fn choose_item(&self, mobj: &MapObject, player: &Player, level: &Level) -> Option<Goal> {
+        let xy = mobj.xyz.truncate();
+        level
+            .thinkers
+            .iter()
+            .filter(|t| t.is_mobj())
+            .map(|t| t.mobj())
+            .filter(|item| item.flags & MapObjFlag::Special as u32 != 0)
+            .filter_map(|item| {
+                let point = item.xyz.truncate();
+                let dist = point.distance(xy);
+                if dist > ITEM_RANGE || self.unreachable.iter().any(|p| p.distance(point) < 1.0) {
+                    return None;
+                }
+                let value = item_value(item.sprite, player);
+                (value > 0).then_some((value as f32 / (dist + 128.0), item))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, item)| goal_of(item))
+    }
+
+    /// Someone to hunt down: other players in deathmatch, otherwise monsters,
+    /// or the other players to keep up with when there are none
+    fn choose_quarry(&self, mobj: &MapObject, level: &Level) -> Option<Goal> {
+        let deathmatch = level.options.deathmatch != 0;
+        let nearest = |want: &dyn Fn(&MapObject) -> bool| {
+            level
+                .thinkers
+                .iter()
+                .filter(|t| t.is_mobj())
+                .map(|t| t.mobj())
+                .filter(|other| !std::ptr::eq(*other, mobj) && other.health > 0 && want(other))
+                .filter(|other| {
+                    let point = other.xyz.truncate();
+                    !self.unreachable.iter().any(|p| p.distance(point) < 1.0)
+                })
+                .min_by(|a, b| {
+                    a.xyz
+                        .distance(mobj.xyz)
+                        .total_cmp(&b.xyz.distance(mobj.xyz))
+                })
+                .map(goal_of)
+        };
+        if deathmatch {
+            return nearest(&|other| other.player.is_some());
+        }
+        nearest(&|other| other.player.is_none() && other.flags & MapObjFlag::Countkill as u32 != 0)
+            .or_else(|| {
+                nearest(&|other| other.player.is_some() && other.xyz.distance(mobj.xyz) > 192.0)
+            })
+    }
+
+    /// Switch to the best weapon there is ammo for
+    fn choose_weapon(&self, player: &Player, range: Option<f32>, cmd: &mut TicCmd) {
+        if player.pendingweapon != WeaponType::NoChange {
+            return;
+        }
+        let status = &player.status;
+        let best = WEAPON_PREFERENCE.iter().find(|(weapon, per_shot)| {
+            let ammo = WEAPON_INFO[*weapon as usize].ammo;
+            status.weaponowned[*weapon as usize]
+                && (ammo == AmmoType::NoAmmo || status.ammo[ammo as usize] >= *per_shot)
+                // Rockets are only safe at a distance
+                && !(*weapon == WeaponType::Missile && range.is_some_and(|r| r < 256.0))
+                && !(*weapon == WeaponType::Fist && status.powers[PowerType::Strength as usize] == 0
+                    && status.weaponowned[WeaponType::Chainsaw as usize])
+        });
+        let Some((weapon, _)) = best else {
+            return;
+        };
+        if *weapon == status.readyweapon {
+            return;
+        }
+        // The shotgun and chainsaw share slots with the super shotgun and fist
+        let slot = match weapon {
+            WeaponType::SuperShotgun => WeaponType::Shotgun,
+            WeaponType::Chainsaw => WeaponType::Fist,
+            w => *w,
+        };
+        cmd.buttons |= TIC_CMD_BUTTONS.bt_change | ((slot as u8) << TIC_CMD_BUTTONS.bt_weaponshift);
+    }
+}
+
+/// The bots of a game, and the path graph of the level they are playing
+#[derive(Debug, Default)]
+pub struct Bots {
+    bots: [Option<Bot>; MAXPLAYERS],
+    graph: PathGraph,
+}
+
+impl Bots {
+    /// Bots for `options.bots` player slots after `consoleplayer`
+    pub fn new(options: &GameOptions, consoleplayer: usize) -> Self {
+        let mut bots: [Option<Bot>; MAXPLAYERS] = Default::default();
+        for slot in (0..MAXPLAYERS)
+            .filter(|i| *i != consoleplayer)
+            .take(options.bots)
+        {
+            bots[slot] = Some(Bot::new(slot, options.bot_skill));
+        }
+        Self {
+            bots,
+            graph: PathGraph::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bots.iter().all(Option::is_none)
+    }
+
+    /// The player is controlled by a bot
+    pub fn controls(&self, player: usize) -> bool {
+        self.bots[player].is_some()
+    }
+
+    /// Build the path graph of a newly loaded level
+    pub fn load_level(&mut self, level: &Level) {
+        self.graph = PathGraph::new(level);
+        for bot in self.bots.iter_mut().flatten() {
+            bot.reset();
+        }
+    }
+
+    /// The cmd of a bot's player for the next tic
+    pub fn tic_cmd(&mut self, player: usize, level: &mut Level) -> TicCmd {
+        match self.bots[player].as_mut() {
+            Some(bot) => bot.think(&self.graph, level),
+            None => TicCmd::new(),
+        }
+    }
+}
+
+fn goal_of(mobj: &MapObject) -> Goal {
+    Goal {
+        point: mobj.xyz.truncate(),
+        sector: mobj.subsector.sector.num as usize,
+    }
+}
+
+/// The signed difference between two angles, in radians
+fn angle_diff(a: Angle, b: Angle) -> f32 {
+    let diff = (a - b).rad();
+    if diff > PI {
+        diff - TAU
+    } else {
+        diff
+    }
+}
+
+fn is_melee(weapon: WeaponType) -> bool {
+    matches!(weapon, WeaponType::Fist | WeaponType::Chainsaw)
+}
+
+/// How much a player needs an item, 0 if not at all
+fn item_value(sprite: SpriteNum, player: &Player) -> i32 {
+    let status = &player.status;
+    let health = player.mobj().map_or(0, |m| m.health);
+    let weapon = |weapon: WeaponType| {
+        if status.weaponowned[weapon as usize] {
+            0
+        } else {
+            100
+        }
+    };
+    let ammo = |ammo: AmmoType| {
+        if status.ammo[ammo as usize] < status.maxammo[ammo as usize] {
+            20
+        } else {
+            0
+        }
+    };
+    match sprite {
+        SpriteNum::SHOT => weapon(WeaponType::Shotgun),
+        SpriteNum::SGN2 => weapon(WeaponType::SuperShotgun),
+        SpriteNum::MGUN => weapon(WeaponType::Chaingun),
+        SpriteNum::LAUN => weapon(WeaponType::Missile),
+        SpriteNum::PLAS => weapon(WeaponType::Plasma),
+        SpriteNum::BFUG => weapon(WeaponType::BFG),
+        SpriteNum::CSAW => weapon(WeaponType::Chainsaw),
+        SpriteNum::STIM if health < 100 => 25,
+        SpriteNum::MEDI if health < 100 => 40,
+        SpriteNum::SOUL | SpriteNum::MEGA => 90,
+        SpriteNum::BON1 if health < 200 => 5,
+        SpriteNum::ARM1 if status.armorpoints < 100 => 30,
+        SpriteNum::ARM2 if status.armorpoints < 200 => 60,
+        SpriteNum::BON2 if status.armorpoints < 200 => 5,
+        SpriteNum::CLIP | SpriteNum::AMMO => ammo(AmmoType::Clip),
+        SpriteNum::SHEL | SpriteNum::SBOX => ammo(AmmoType::Shell),
+        SpriteNum::ROCK | SpriteNum::BROK => ammo(AmmoType::Missile),
+        SpriteNum::CELL | SpriteNum::CELP => ammo(AmmoType::Cell),
+        SpriteNum::BPAK => 40,
+        SpriteNum::BKEY
+        | SpriteNum::YKEY
+        | SpriteNum::RKEY
+        | SpriteNum::BSKU
+        | SpriteNum::YSKU
+        | SpriteNum::RSKU => 50,
+        SpriteNum::PINV | SpriteNum::PSTR | SpriteNum::PINS | SpriteNum::PVIS | SpriteNum::PMAP => {
+            30
+        }
+        _ => 0,
+    }
+}
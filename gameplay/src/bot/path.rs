@@ -0,0 +1,405 @@
+//! The path graph bots navigate by. Each two-sided line is a portal between
+//! the sectors on either side of it, and a path is a run of portals from the
+//! sector the bot is in to the sector of its goal.
+//!
+//! Sector heights are checked while searching, so doors and lifts that have
+//! moved since the graph was built are followed as they are now.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use glam::Vec2;
+
+use crate::level::map_defs::{LineDef, Sector};
+use crate::level::Level;
+use crate::LineDefFlags;
+
+/// Highest step up a player can walk
+const MAX_STEP: f32 = 24.0;
+/// Height of a player
+const PLAYER_HEIGHT: f32 = 56.0;
+/// Extra cost of crossing a sector that damages the player
+const DAMAGE_COST: u32 = 512;
+
+/// Doors a player opens by pressing use on the line
+const USE_DOORS: [i16; 10] = [1, 26, 27, 28, 31, 32, 33, 34, 117, 118];
+/// Lifts lowered by pressing use on the line
+const USE_LIFTS: [i16; 4] = [21, 62, 122, 123];
+/// Lifts lowered by walking over the line
+const WALK_LIFTS: [i16; 4] = [10, 88, 120, 121];
+
+/// How a portal is crossed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Crossing {
+    Walk,
+    /// Press use on the line, then walk through once it opens
+    Use,
+}
+
+#[derive(Debug, Clone)]
+struct Portal {
+    line: usize,
+    /// Middle of the line
+    point: Vec2,
+    /// Front and back sector numbers
+    sectors: [usize; 2],
+}
+
+/// A step of a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Waypoint {
+    pub point: Vec2,
+    pub crossing: Crossing,
+}
+
+#[derive(PartialEq, Eq)]
+struct Open {
+    cost: u32,
+    node: usize,
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Lowest cost first out of the max-heap
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sector to sector connectivity of a level
+#[derive(Debug, Default)]
+pub(crate) struct PathGraph {
+    portals: Vec<Portal>,
+    /// The portals bordering each sector
+    sector_portals: Vec<Vec<usize>>,
+}
+
+impl PathGraph {
+    pub fn new(level: &Level) -> Self {
+        let map = &level.map_data;
+        Self::from_lines(map.linedefs(), map.sectors().len())
+    }
+
+    fn from_lines(lines: &[LineDef], sector_count: usize) -> Self {
+        let mut sector_portals = vec![Vec::new(); sector_count];
+        let mut portals = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let Some(back) = line.backsector.as_ref() else {
+                continue;
+            };
+            let sectors = [line.frontsector.num as usize, back.num as usize];
+            if sectors[0] == sectors[1] {
+                continue;
+            }
+            let point = ((line.v1 + line.v2) * 0.5).truncate();
+            for s in sectors {
+                sector_portals[s].push(portals.len());
+            }
+            portals.push(Portal {
+                line: i,
+                point,
+                sectors,
+            });
+        }
+        Self {
+            portals,
+            sector_portals,
+        }
+    }
+
+    /// Find the cheapest path from `start` in sector `from` to `goal` in
+    /// sector `to`. The last waypoint is the goal.
+    pub fn find(
+        &self,
+        level: &Level,
+        from: usize,
+        start: Vec2,
+        to: usize,
+        goal: Vec2,
+    ) -> Option<Vec<Waypoint>> {
+        let map = &level.map_data;
+        self.find_in(map.sectors(), map.linedefs(), from, start, to, goal)
+    }
+
+    fn find_in(
+        &self,
+        sectors: &[Sector],
+        lines: &[LineDef],
+        from: usize,
+        start: Vec2,
+        to: usize,
+        goal: Vec2,
+    ) -> Option<Vec<Waypoint>> {
+        let goal_point = Waypoint {
+            point: goal,
+            crossing: Crossing::Walk,
+        };
+        if from == to {
+            return Some(vec![goal_point]);
+        }
+
+        // A node is a portal crossed in to one of its sectors: `portal * 2 +
+        // side`
+        let nodes = self.portals.len() * 2;
+        let mut cost = vec![u32::MAX; nodes];
+        let mut came_from = vec![usize::MAX; nodes];
+        let mut open = BinaryHeap::new();
+
+        // The nodes reached by crossing out of `in_sector` from `point`, and
+        // the cost of getting to them
+        let exits = |in_sector: usize, point: Vec2| {
+            self.sector_portals[in_sector].iter().filter_map(move |&p| {
+                let portal = &self.portals[p];
+                let side = (portal.sectors[0] == in_sector) as usize;
+                let into = &sectors[portal.sectors[side]];
+                crossing(&lines[portal.line], &sectors[in_sector], into)?;
+                let mut step = point.distance(portal.point) as u32;
+                if is_damaging(into) {
+                    step += DAMAGE_COST;
+                }
+                Some((p * 2 + side, step))
+            })
+        };
+
+        let mut best: Option<(u32, usize)> = None;
+        let mut next = Some(Open {
+            cost: 0,
+            node: usize::MAX,
+        });
+        while let Some(Open { cost: c, node }) = next {
+            let (in_sector, point) = if node == usize::MAX {
+                (from, start)
+            } else {
+                let portal = &self.portals[node / 2];
+                (portal.sectors[node % 2], portal.point)
+            };
+            if node != usize::MAX && in_sector == to {
+                let total = c + point.distance(goal) as u32;
+                if best.is_none_or(|(b, _)| total < b) {
+                    best = Some((total, node));
+                }
+            } else {
+                for (exit, step) in exits(in_sector, point) {
+                    if c + step < cost[exit] {
+                        cost[exit] = c + step;
+                        came_from[exit] = node;
+                        open.push(Open {
+                            cost: c + step,
+                            node: exit,
+                        });
+                    }
+                }
+            }
+            // Skip nodes since reached more cheaply, and stop once nothing
+            // left can beat the best path
+            next = std::iter::from_fn(|| open.pop())
+                .find(|o| o.cost == cost[o.node])
+                .filter(|o| !matches!(best, Some((b, _)) if o.cost >= b));
+        }
+
+        let (_, mut node) = best?;
+        let mut path = vec![goal_point];
+        while node != usize::MAX {
+            let portal = &self.portals[node / 2];
+            let side = node % 2;
+            let line = &lines[portal.line];
+            let behind = &sectors[portal.sectors[1 - side]];
+            let ahead = &sectors[portal.sectors[side]];
+            path.push(Waypoint {
+                point: portal.point,
+                crossing: crossing(line, behind, ahead).unwrap_or(Crossing::Walk),
+            });
+            node = came_from[node];
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// How a player can cross `line` from sector `from` in to `to`, if they can
+fn crossing(line: &LineDef, from: &Sector, to: &Sector) -> Option<Crossing> {
+    if line.flags & LineDefFlags::Blocking as u32 != 0 {
+        return None;
+    }
+    let special = line.special;
+    if USE_DOORS.contains(&special) {
+        return Some(Crossing::Use);
+    }
+    let lift = to.floorheight - from.floorheight > MAX_STEP;
+    if lift && USE_LIFTS.contains(&special) {
+        return Some(Crossing::Use);
+    }
+    if lift && !WALK_LIFTS.contains(&special) {
+        return None;
+    }
+    let gap = from.ceilingheight.min(to.ceilingheight) - from.floorheight.max(to.floorheight);
+    if gap < PLAYER_HEIGHT && !lift {
+        return None;
+    }
+    Some(Crossing::Walk)
+}
+
+/// Sectors with nukage, lava and the like
+fn is_damaging(sector: &Sector) -> bool {
+    matches!(sector.special, 4 | 5 | 7 | 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{crossing, Crossing, PathGraph, Waypoint};
+    use crate::level::map_defs::{BBox, LineDef, Sector, SlopeType};
+    use crate::{LineDefFlags, MapPtr};
+
+    fn sector(num: u32, floor: f32, ceiling: f32) -> Sector {
+        Sector::new(num, floor, ceiling, 0, 0, 160, 0, 0)
+    }
+
+    fn line(
+        v1: Vec2,
+        v2: Vec2,
+        front: &mut Sector,
+        back: &mut Sector,
+        special: i16,
+        flags: u32,
+    ) -> LineDef {
+        LineDef {
+            v1: v1.extend(0.0),
+            v2: v2.extend(0.0),
+            delta: (v2 - v1).extend(0.0),
+            flags,
+            special,
+            tag: 0,
+            bbox: BBox::default(),
+            slopetype: SlopeType::Vertical,
+            sides: [0, 1],
+            front_sidedef: unsafe { MapPtr::new_null() },
+            back_sidedef: None,
+            frontsector: MapPtr::new(front),
+            backsector: Some(MapPtr::new(back)),
+            valid_count: 0,
+            translucent: false,
+        }
+    }
+
+    /// A row of rooms 128 wide, 0 to 3 west to east, with a ledge 4 off the
+    /// north of room 0 too high to step up on to:
+    ///
+    /// ```text
+    ///  [4]
+    ///  [0] [1] [2] [3]
+    /// ```
+    ///
+    /// 0 to 1 is open, 1 to 2 is a door, and 2 to 3 is blocked by an
+    /// impassable line.
+    fn rooms() -> (Vec<Sector>, Vec<LineDef>) {
+        let mut sectors = vec![
+            sector(0, 0.0, 128.0),
+            sector(1, 0.0, 128.0),
+            sector(2, 0.0, 128.0),
+            sector(3, 0.0, 128.0),
+            sector(4, 64.0, 192.0),
+        ];
+        // The lines point in to the Vec's buffer, which stays put when returned
+        let [s0, s1, s2, s3, s4] = &mut sectors[..] else {
+            unreachable!()
+        };
+        let lines = vec![
+            line(Vec2::new(128.0, 0.0), Vec2::new(128.0, 128.0), s0, s1, 0, 0),
+            line(Vec2::new(256.0, 0.0), Vec2::new(256.0, 128.0), s1, s2, 1, 0),
+            line(
+                Vec2::new(384.0, 0.0),
+                Vec2::new(384.0, 128.0),
+                s2,
+                s3,
+                0,
+                LineDefFlags::Blocking as u32,
+            ),
+            line(Vec2::new(0.0, 128.0), Vec2::new(128.0, 128.0), s0, s4, 0, 0),
+        ];
+        (sectors, lines)
+    }
+
+    #[test]
+    fn find_through_door() {
+        let (sectors, lines) = rooms();
+        let graph = PathGraph::from_lines(&lines, sectors.len());
+        let start = Vec2::new(64.0, 64.0);
+        let goal = Vec2::new(320.0, 64.0);
+
+        let path = graph.find_in(&sectors, &lines, 0, start, 2, goal).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                Waypoint {
+                    point: Vec2::new(128.0, 64.0),
+                    crossing: Crossing::Walk
+                },
+                Waypoint {
+                    point: Vec2::new(256.0, 64.0),
+                    crossing: Crossing::Use
+                },
+                Waypoint {
+                    point: goal,
+                    crossing: Crossing::Walk
+                },
+            ]
+        );
+
+        // Already there
+        let path = graph.find_in(&sectors, &lines, 0, start, 0, start).unwrap();
+        assert_eq!(path.len(), 1);
+    }
+
+    #[test]
+    fn find_no_path() {
+        let (sectors, lines) = rooms();
+        let graph = PathGraph::from_lines(&lines, sectors.len());
+        let start = Vec2::new(64.0, 64.0);
+        // Behind the impassable line
+        assert!(graph
+            .find_in(&sectors, &lines, 0, start, 3, Vec2::new(448.0, 64.0))
+            .is_none());
+        // Up the ledge
+        assert!(graph
+            .find_in(&sectors, &lines, 0, start, 4, Vec2::new(64.0, 192.0))
+            .is_none());
+    }
+
+    #[test]
+    fn portal_crossing() {
+        let mut low = sector(0, 0.0, 128.0);
+        let mut high = sector(1, 64.0, 192.0);
+        let mut step = sector(2, 24.0, 128.0);
+        let mut crawl = sector(3, 0.0, 48.0);
+        let (a, b) = (Vec2::ZERO, Vec2::new(0.0, 64.0));
+
+        let open = line(a, b, &mut low, &mut step, 0, 0);
+        assert_eq!(crossing(&open, &low, &step), Some(Crossing::Walk));
+        let blocking = line(a, b, &mut low, &mut step, 0, LineDefFlags::Blocking as u32);
+        assert_eq!(crossing(&blocking, &low, &step), None);
+        let door = line(a, b, &mut low, &mut crawl, 1, 0);
+        assert_eq!(crossing(&door, &low, &crawl), Some(Crossing::Use));
+        // Too low to fit under unless it is a door
+        let vent = line(a, b, &mut low, &mut crawl, 0, 0);
+        assert_eq!(crossing(&vent, &low, &crawl), None);
+
+        // Lifts are only followed up, down is a drop
+        let ledge = line(a, b, &mut low, &mut high, 0, 0);
+        assert_eq!(crossing(&ledge, &low, &high), None);
+        assert_eq!(crossing(&ledge, &high, &low), Some(Crossing::Walk));
+        let use_lift = line(a, b, &mut low, &mut high, 62, 0);
+        assert_eq!(crossing(&use_lift, &low, &high), Some(Crossing::Use));
+        let walk_lift = line(a, b, &mut low, &mut high, 88, 0);
+        assert_eq!(crossing(&walk_lift, &low, &high), Some(Crossing::Walk));
+    }
+}
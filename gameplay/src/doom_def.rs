@@ -84,6 +84,15 @@ pub static MTF_AMBUSH: i16 = 8;
 /// A single flag used to determine if the thing options are multiplayer of
 /// singleplayer enabled.
 pub const MTF_SINGLE_PLAYER: i16 = 16;
+/// BOOM: not spawned in deathmatch
+pub const MTF_NOT_DEATHMATCH: i16 = 32;
+/// BOOM: not spawned in co-op
+pub const MTF_NOT_COOP: i16 = 64;
+/// Never set by vanilla editors. If it is the thing was written by a broken
+/// editor and only the vanilla options bits can be trusted
+pub const MTF_RESERVED: i16 = 256;
+/// The skill, ambush and multiplayer options bits vanilla Doom reads
+pub const MTF_VANILLA: i16 = 31;
 
 /// Key cards.
 #[derive(Copy, Clone)]
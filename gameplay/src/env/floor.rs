@@ -236,6 +236,7 @@ impl Think for FloorMove {
             false,
             0,
             floor.direction,
+            level,
         );
 
         if level.level_time & 7 == 0 {
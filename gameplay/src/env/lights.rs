@@ -11,7 +11,6 @@ use crate::MapPtr;
 use crate::env::specials::{
     find_max_light_surrounding, find_min_light_surrounding, get_next_sector
 };
-use crate::utilities::p_random;
 
 const STROBEBRIGHT: i32 = 5;
 pub const FASTDARK: i32 = 15;
@@ -62,7 +61,7 @@ impl FireFlicker {
 }
 
 impl Think for FireFlicker {
-    fn think(object: &mut Thinker, _level: &mut Level) -> bool {
+    fn think(object: &mut Thinker, level: &mut Level) -> bool {
         let light = object.fire_flick_mut();
         #[cfg(feature = "null_check")]
         if light.thinker.is_null() {
@@ -74,7 +73,7 @@ impl Think for FireFlicker {
             return false;
         }
 
-        let amount = ((p_random() & 3) * 16) as usize;
+        let amount = ((level.random.p_random() & 3) * 16) as usize;
         if light.sector.lightlevel >= amount {
             light.sector.lightlevel = light.max_light - amount;
         }
@@ -124,7 +123,7 @@ impl LightFlash {
         let light = LightFlash {
             thinker: null_mut(),
             sector: MapPtr::new(sector),
-            count: (p_random() & 64) + 1,
+            count: (level.random.p_random() & 64) + 1,
             max_light: sector.lightlevel,
             min_light: find_min_light_surrounding(MapPtr::new(sector), sector.lightlevel),
             max_time: 64,
@@ -140,7 +139,7 @@ impl LightFlash {
 }
 
 impl Think for LightFlash {
-    fn think(object: &mut Thinker, _level: &mut Level) -> bool {
+    fn think(object: &mut Thinker, level: &mut Level) -> bool {
         let light = object.light_flash_mut();
         #[cfg(feature = "null_check")]
         if light.thinker.is_null() {
@@ -154,10 +153,10 @@ impl Think for LightFlash {
 
         if light.sector.lightlevel == light.max_light {
             light.sector.lightlevel = light.min_light;
-            light.count = (p_random() & light.min_time) + 1
+            light.count = (level.random.p_random() & light.min_time) + 1
         } else {
             light.sector.lightlevel = light.max_light;
-            light.count = (p_random() & light.max_time) + 1
+            light.count = (level.random.p_random() & light.max_time) + 1
         }
 
         false
@@ -201,7 +200,11 @@ impl StrobeFlash {
         let mut light = StrobeFlash {
             thinker: null_mut(),
             sector: MapPtr::new(sector),
-            count: if in_sync { (p_random() & 7) + 1 } else { 1 },
+            count: if in_sync {
+                (level.random.p_random() & 7) + 1
+            } else {
+                1
+            },
             min_light: find_min_light_surrounding(MapPtr::new(sector), sector.lightlevel),
             max_light: sector.lightlevel,
             dark_time: fast_or_slow,
@@ -6,6 +6,8 @@ pub(crate) mod doors;
 pub(crate) mod floor;
 pub(crate) mod lights;
 pub(crate) mod platforms;
+pub(crate) mod pushers;
+pub(crate) mod scroll;
 pub(crate) mod specials;
 pub(crate) mod switch;
 pub(crate) mod teleport;
@@ -16,7 +16,6 @@ use crate::level::map_defs::{LineDef, Sector};
 use crate::level::Level;
 use crate::thing::MapObject;
 use crate::thinker::{Think, Thinker, ThinkerData};
-use crate::utilities::p_random;
 use crate::MapPtr;
 
 const PLATSPEED: f32 = 1.0;
@@ -135,7 +134,7 @@ pub fn ev_do_platform(
 
                 platform.wait = TICRATE * PLATWAIT;
 
-                platform.status = if (p_random() & 1) == 0 {
+                platform.status = if (level.random.p_random() & 1) == 0 {
                     PlatStatus::Up
                 } else {
                     PlatStatus::Down
@@ -204,6 +203,7 @@ impl Think for Platform {
                     platform.crush,
                     0,
                     1,
+                    level,
                 );
 
                 if matches!(
@@ -248,6 +248,7 @@ impl Think for Platform {
                     false,
                     0,
                     -1,
+                    level,
                 );
 
                 if matches!(res, PlaneResult::PastDest) {
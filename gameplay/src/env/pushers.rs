@@ -0,0 +1,512 @@
+//! BOOM sector friction (ice and mud), wind, currents, and point
+//! pushers/pullers.
+//!
+//! Doom source name `p_spec` (BOOM `T_Friction`, `T_Pusher`)
+
+use std::f32::consts::PI;
+use std::ptr::null_mut;
+
+use glam::Vec3;
+use log::debug;
+
+use crate::level::map_defs::Sector;
+use crate::level::Level;
+use crate::thing::{MapObject, FRICTION};
+use crate::thinker::{Think, Thinker, ThinkerData};
+use crate::utilities::{p_aprox_distance, point_to_angle_2};
+use crate::{MapObjFlag, MapPtr};
+
+/// Sector special bit enabling friction effects
+pub const FRICTION_MASK: i16 = 0x100;
+/// Sector special bit enabling wind, currents and point pushers
+pub const PUSH_MASK: i16 = 0x200;
+/// The default movement factor for players: `forwardmove * 2048`
+pub const ORIG_FRICTION_FACTOR: i32 = 2048;
+/// Doom editor thing numbers for `MT_PUSH` and `MT_PULL`
+pub const PUSH_DOOMEDNUM: i16 = 5001;
+pub const PULL_DOOMEDNUM: i16 = 5002;
+/// Amount to shift the pusher magnitude down by
+const PUSH_FACTOR: f32 = 128.0;
+
+pub struct Friction {
+    pub thinker: *mut Thinker,
+    pub sector: MapPtr<Sector>,
+    /// Friction value (0xE800 = normal)
+    pub friction: f32,
+    /// Inertia factor when adding to momentum
+    pub movefactor: i32,
+}
+
+impl Friction {
+    /// Doom function name `Add_Friction`
+    pub fn add(friction: f32, movefactor: i32, sector: &mut Sector, level: &mut Level) {
+        let friction = Friction {
+            thinker: null_mut(),
+            sector: MapPtr::new(sector),
+            friction,
+            movefactor,
+        };
+
+        let thinker = MapObject::create_thinker(ThinkerData::Friction(friction), Friction::think);
+
+        if let Some(ptr) = level.thinkers.push::<Friction>(thinker) {
+            ptr.set_obj_thinker_ptr();
+        }
+    }
+}
+
+impl Think for Friction {
+    fn think(object: &mut Thinker, _level: &mut Level) -> bool {
+        let friction = object.friction_mut();
+        #[cfg(feature = "null_check")]
+        if friction.thinker.is_null() {
+            std::panic!("friction thinker was null");
+        }
+
+        // Be sure the special sector type is still turned on. If so, proceed.
+        // Else, bail out; the sector type has been changed on us.
+        if friction.sector.special & FRICTION_MASK == 0 {
+            return false;
+        }
+
+        // Assign the friction value to players on the floor, non-floating,
+        // and clipped. Normally the object's friction value is kept at
+        // `FRICTION` and this thinker changes it for icy or muddy floors.
+        //
+        // When the object is straddling sectors with the same floorheight
+        // that have different frictions, use the lowest friction value
+        // (muddy has precedence over icy).
+        let floorheight = friction.sector.floorheight;
+        let (value, movefactor) = (friction.friction, friction.movefactor);
+        friction.sector.run_mut_func_on_thinglist(|thing| {
+            if thing.player().is_some()
+                && thing.flags & (MapObjFlag::Nogravity as u32 | MapObjFlag::Noclip as u32) == 0
+                && thing.xyz.z <= floorheight
+                && (thing.friction == FRICTION || value < thing.friction)
+            {
+                thing.friction = value;
+                thing.movefactor = movefactor;
+            }
+            true
+        });
+
+        false
+    }
+
+    fn set_thinker_ptr(&mut self, ptr: *mut Thinker) {
+        self.thinker = ptr;
+    }
+
+    fn thinker_mut(&mut self) -> &mut Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("friction thinker was null");
+        }
+        unsafe { &mut *self.thinker }
+    }
+
+    fn thinker(&self) -> &Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("friction thinker was null");
+        }
+        unsafe { &*self.thinker }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushKind {
+    /// Point source, pushes away from the source
+    Push,
+    /// Point source, pulls towards the source
+    Pull,
+    Wind,
+    Current,
+}
+
+pub struct Pusher {
+    pub thinker: *mut Thinker,
+    pub kind: PushKind,
+    pub sector: MapPtr<Sector>,
+    /// Magnitude of the push on each axis
+    pub x_mag: f32,
+    pub y_mag: f32,
+    pub magnitude: f32,
+    /// Effective radius of a point source
+    pub radius: f32,
+    /// Location of a point source
+    pub source: Vec3,
+}
+
+impl Pusher {
+    /// Doom function name `Add_Pusher`
+    pub fn add(
+        kind: PushKind,
+        x_mag: f32,
+        y_mag: f32,
+        source: Option<Vec3>,
+        sector: &mut Sector,
+        level: &mut Level,
+    ) {
+        let magnitude = p_aprox_distance(x_mag, y_mag);
+        let pusher = Pusher {
+            thinker: null_mut(),
+            kind,
+            sector: MapPtr::new(sector),
+            x_mag,
+            y_mag,
+            magnitude,
+            // where force goes to zero
+            radius: magnitude * 2.0,
+            source: source.unwrap_or_default(),
+        };
+
+        let thinker = MapObject::create_thinker(ThinkerData::Pusher(pusher), Pusher::think);
+
+        if let Some(ptr) = level.thinkers.push::<Pusher>(thinker) {
+            ptr.set_obj_thinker_ptr();
+        }
+    }
+
+    /// Push or pull a shootable thing towards the point source if there is a
+    /// line of sight. The force drops off linearly to nothing at `radius`.
+    ///
+    /// Doom function name `PIT_PushThing`
+    fn push_thing(&self, thing: &mut MapObject) {
+        if thing.flags & MapObjFlag::Noclip as u32 != 0
+            || (thing.player().is_none() && thing.flags & MapObjFlag::Shootable as u32 == 0)
+        {
+            return;
+        }
+
+        let Some((momx, momy)) = self.point_push(thing.xyz) else {
+            // outside the effective radius
+            return;
+        };
+
+        let mut bsp_trace = thing.get_sight_bsp_trace(self.source);
+        if thing.check_sight(self.source, self.source.z, 0.0, &mut bsp_trace) {
+            thing.momxyz.x += momx;
+            thing.momxyz.y += momy;
+        }
+    }
+
+    /// The momentum a point source adds to a thing at `xyz`, or `None` if the
+    /// thing is outside the effective radius.
+    fn point_push(&self, xyz: Vec3) -> Option<(f32, f32)> {
+        let dist = p_aprox_distance(xyz.x - self.source.x, xyz.y - self.source.y);
+        let speed = (self.magnitude - dist / 2.0) / 256.0;
+        if speed <= 0.0 {
+            return None;
+        }
+        let mut angle = point_to_angle_2(self.source, xyz);
+        if self.kind == PushKind::Push {
+            // away
+            angle += PI;
+        }
+        Some((speed * angle.cos(), speed * angle.sin()))
+    }
+
+    /// The momentum wind or a current adds to a player standing at height `z`
+    /// over a floor at `floorz`, with their view at `viewz`.
+    fn constant_push(&self, z: f32, floorz: f32, viewz: f32) -> (f32, f32) {
+        let (x_mag, y_mag) = (self.x_mag, self.y_mag);
+        // special water sector?
+        let water = self.sector.heightsec.as_ref().map(|s| s.floorheight);
+        let (xspeed, yspeed) = match (self.kind, water) {
+            (PushKind::Wind, None) if z > floorz => (x_mag, y_mag),
+            (PushKind::Wind, None) => (x_mag / 2.0, y_mag / 2.0),
+            (PushKind::Wind, Some(ht)) if z > ht => (x_mag, y_mag),
+            // underwater
+            (PushKind::Wind, Some(ht)) if viewz < ht => (0.0, 0.0),
+            // wading in water
+            (PushKind::Wind, Some(_)) => (x_mag / 2.0, y_mag / 2.0),
+            (_, None) if z > self.sector.floorheight => (0.0, 0.0),
+            (_, Some(ht)) if z > ht => (0.0, 0.0),
+            _ => (x_mag, y_mag),
+        };
+        (xspeed / PUSH_FACTOR, yspeed / PUSH_FACTOR)
+    }
+}
+
+impl Think for Pusher {
+    fn think(object: &mut Thinker, level: &mut Level) -> bool {
+        let pusher = object.pusher_mut();
+        #[cfg(feature = "null_check")]
+        if pusher.thinker.is_null() {
+            std::panic!("pusher thinker was null");
+        }
+
+        // Be sure the special sector type is still turned on. If so, proceed.
+        // Else, bail out; the sector type has been changed on us.
+        if pusher.sector.special & PUSH_MASK == 0 {
+            return false;
+        }
+
+        if matches!(pusher.kind, PushKind::Push | PushKind::Pull) {
+            // Seek out all pushable things within the force radius of this
+            // point pusher. Crosses sectors.
+            let radius = pusher.radius + 32.0;
+            level.thinkers.run_fn_on_things(|thinker| {
+                if thinker.is_mobj() {
+                    let thing = thinker.mobj_mut();
+                    if (thing.xyz.x - pusher.source.x).abs() < radius
+                        && (thing.xyz.y - pusher.source.y).abs() < radius
+                    {
+                        pusher.push_thing(thing);
+                    }
+                }
+                true
+            });
+            return false;
+        }
+
+        // For constant pushers (wind/current) there are 3 situations:
+        //
+        // 1) Affected Thing is above the floor.
+        //
+        //    Apply the full force if wind, no force if current.
+        //
+        // 2) Affected Thing is on the ground.
+        //
+        //    Apply half force if wind, full force if current.
+        //
+        // 3) Affected Thing is below the ground (underwater effect).
+        //
+        //    Apply no force if wind, full force if current.
+        let pusher = &*pusher;
+        let mut sector = pusher.sector.clone();
+        sector.run_mut_func_on_thinglist(|thing| {
+            let Some(player) = thing.player() else {
+                return true;
+            };
+            if thing.flags & (MapObjFlag::Nogravity as u32 | MapObjFlag::Noclip as u32) != 0 {
+                return true;
+            }
+            let (momx, momy) = pusher.constant_push(thing.xyz.z, thing.floorz, player.viewz);
+            thing.momxyz.x += momx;
+            thing.momxyz.y += momy;
+            true
+        });
+
+        false
+    }
+
+    fn set_thinker_ptr(&mut self, ptr: *mut Thinker) {
+        self.thinker = ptr;
+    }
+
+    fn thinker_mut(&mut self) -> &mut Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("pusher thinker was null");
+        }
+        unsafe { &mut *self.thinker }
+    }
+
+    fn thinker(&self) -> &Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("pusher thinker was null");
+        }
+        unsafe { &*self.thinker }
+    }
+}
+
+/// Initialize the sectors where friction is increased or decreased. The
+/// linedef length sets the amount of friction; lines longer than 100 units
+/// give ice, shorter give mud.
+///
+/// Doom function name `P_SpawnFriction`
+pub fn spawn_friction(level: &mut Level) {
+    let level_lines = unsafe { &*(level as *const Level) };
+    let level_iter = unsafe { &mut *(level as *mut Level) };
+    for line in level_lines
+        .map_data
+        .linedefs
+        .iter()
+        .filter(|l| l.special == 223)
+    {
+        debug!("line-special #{}: sector friction", line.special);
+        let length = p_aprox_distance(line.delta.x, line.delta.y) as i32;
+        let (friction, movefactor) = friction_for_length(length);
+        for sector in level_iter
+            .map_data
+            .sectors_mut()
+            .iter_mut()
+            .filter(|s| s.tag == line.tag)
+        {
+            Friction::add(friction, movefactor, sector, level);
+        }
+    }
+}
+
+/// The friction and player movement factor for a friction linedef of
+/// `length`. Works in fixed-point to match the BOOM values exactly.
+fn friction_for_length(length: i32) -> (f32, i32) {
+    let friction = ((0x1EB8 * length) / 0x80 + 0xD000).clamp(0, 0x10000);
+
+    let movefactor = if friction > 0xE800 {
+        // ice
+        ((0x10092 - friction) * 0x70) / 0x158
+    } else {
+        ((friction - 0xDB34) * 0xA) / 0x80
+    }
+    .max(32);
+
+    (friction as f32 / 65536.0, movefactor)
+}
+
+/// Initialize the sectors with wind, currents, and point pushers. Point
+/// pushers require a push (5001) or pull (5002) thing inside the tagged
+/// sector.
+///
+/// Doom function name `P_SpawnPushers`
+pub fn spawn_pushers(level: &mut Level) {
+    let level_lines = unsafe { &*(level as *const Level) };
+    let level_iter = unsafe { &mut *(level as *mut Level) };
+    let sources: Vec<(Vec3, PushKind)> = level
+        .map_data
+        .things()
+        .iter()
+        .filter(|t| t.kind == PUSH_DOOMEDNUM || t.kind == PULL_DOOMEDNUM)
+        .map(|t| {
+            let kind = if t.kind == PUSH_DOOMEDNUM {
+                PushKind::Push
+            } else {
+                PushKind::Pull
+            };
+            (Vec3::new(t.x as f32, t.y as f32, 0.0), kind)
+        })
+        .collect();
+
+    for line in level_lines.map_data.linedefs.iter() {
+        let kind = match line.special {
+            224 => PushKind::Wind,
+            225 => PushKind::Current,
+            226 => PushKind::Push,
+            _ => continue,
+        };
+        debug!("line-special #{}: pusher {kind:?}", line.special);
+
+        for sector in level_iter
+            .map_data
+            .sectors_mut()
+            .iter_mut()
+            .filter(|s| s.tag == line.tag)
+        {
+            if kind != PushKind::Push {
+                Pusher::add(kind, line.delta.x, line.delta.y, None, sector, level);
+                continue;
+            }
+            // No push/pull thing means no effect
+            let source = sources.iter().find(|(xyz, _)| {
+                let ss = level.map_data.point_in_subsector(*xyz);
+                ss.sector.num == sector.num
+            });
+            if let Some((mut xyz, kind)) = source.copied() {
+                xyz.z = sector.floorheight;
+                Pusher::add(kind, line.delta.x, line.delta.y, Some(xyz), sector, level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr::null_mut;
+
+    use glam::Vec3;
+
+    use super::{friction_for_length, PushKind, Pusher, ORIG_FRICTION_FACTOR};
+    use crate::level::map_defs::Sector;
+    use crate::utilities::p_aprox_distance;
+    use crate::MapPtr;
+
+    fn pusher(kind: PushKind, x_mag: f32, y_mag: f32, sector: &mut Sector) -> Pusher {
+        let magnitude = p_aprox_distance(x_mag, y_mag);
+        Pusher {
+            thinker: null_mut(),
+            kind,
+            sector: MapPtr::new(sector),
+            x_mag,
+            y_mag,
+            magnitude,
+            radius: magnitude * 2.0,
+            source: Vec3::ZERO,
+        }
+    }
+
+    #[test]
+    fn friction_to_movefactor() {
+        // just under normal friction drops to the mud formula
+        assert_eq!(friction_for_length(100), (0xE7FF as f32 / 65536.0, 255));
+        // just over stays close to the normal movement factor
+        let (friction, movefactor) = friction_for_length(101);
+        assert_eq!(friction, 0xE83D as f32 / 65536.0);
+        assert!(movefactor == 2028 && movefactor < ORIG_FRICTION_FACTOR);
+        // ice
+        assert_eq!(friction_for_length(200), (0xFFFF as f32 / 65536.0, 47));
+        assert_eq!(friction_for_length(300), (1.0, 47));
+        // mud bottoms out
+        assert_eq!(friction_for_length(50), (0xDBFF as f32 / 65536.0, 32));
+        assert_eq!(friction_for_length(0), (0xD000 as f32 / 65536.0, 32));
+    }
+
+    #[test]
+    fn wind_momentum() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let wind = pusher(PushKind::Wind, 128.0, -64.0, &mut sector);
+        // in the air
+        assert_eq!(wind.constant_push(16.0, 0.0, 57.0), (1.0, -0.5));
+        // on the ground
+        assert_eq!(wind.constant_push(0.0, 0.0, 41.0), (0.5, -0.25));
+    }
+
+    #[test]
+    fn wind_and_current_with_water() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut water = Sector::new(1, 32.0, 128.0, 0, 0, 160, 0, 0);
+        sector.heightsec = Some(MapPtr::new(&mut water));
+
+        let wind = pusher(PushKind::Wind, 128.0, 0.0, &mut sector);
+        // above the water
+        assert_eq!(wind.constant_push(40.0, 0.0, 81.0), (1.0, 0.0));
+        // wading
+        assert_eq!(wind.constant_push(0.0, 0.0, 41.0), (0.5, 0.0));
+        // underwater
+        assert_eq!(wind.constant_push(-32.0, 0.0, 9.0), (0.0, 0.0));
+
+        let current = pusher(PushKind::Current, 128.0, 0.0, &mut sector);
+        assert_eq!(current.constant_push(40.0, 0.0, 81.0), (0.0, 0.0));
+        assert_eq!(current.constant_push(0.0, 0.0, 41.0), (1.0, 0.0));
+    }
+
+    #[test]
+    fn current_only_on_the_floor() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let current = pusher(PushKind::Current, 0.0, 256.0, &mut sector);
+        assert_eq!(current.constant_push(0.0, 0.0, 41.0), (0.0, 2.0));
+        assert_eq!(current.constant_push(8.0, 0.0, 49.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn point_push_and_pull() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let push = pusher(PushKind::Push, 100.0, 0.0, &mut sector);
+        let pull = pusher(PushKind::Pull, 100.0, 0.0, &mut sector);
+
+        // (100 - 100 / 2) / 256
+        let (x, y) = push.point_push(Vec3::new(100.0, 0.0, 0.0)).unwrap();
+        assert!((x - 0.1953125).abs() < 1e-6 && y.abs() < 1e-6);
+        let (x, y) = pull.point_push(Vec3::new(100.0, 0.0, 0.0)).unwrap();
+        assert!((x + 0.1953125).abs() < 1e-6 && y.abs() < 1e-6);
+        // stronger closer in
+        let (x, y) = push.point_push(Vec3::new(0.0, -20.0, 0.0)).unwrap();
+        assert!(x.abs() < 1e-6 && (y + 0.3515625).abs() < 1e-6);
+
+        // nothing at the radius or beyond
+        assert!(push.point_push(Vec3::new(200.0, 0.0, 0.0)).is_none());
+        assert!(pull.point_push(Vec3::new(0.0, 300.0, 0.0)).is_none());
+    }
+}
@@ -0,0 +1,378 @@
+//! BOOM scrolling walls, floors, ceilings and conveyors.
+//!
+//! Doom source name `p_spec` (BOOM `T_Scroll`)
+
+use std::ptr::null_mut;
+
+use log::debug;
+
+use crate::level::map_defs::{LineDef, Sector, SideDef};
+use crate::level::Level;
+use crate::thing::MapObject;
+use crate::thinker::{Think, Thinker, ThinkerData};
+use crate::{MapObjFlag, MapPtr};
+
+/// Scrolling speed is the linedef length shifted down by this
+const SCROLL_SHIFT: f32 = 32.0;
+/// Factor to scale scrolling effect into mobj-carrying properties = 3/32.
+/// (This is so scrolling floors and objects on them can move at same speed.)
+const CARRYFACTOR: f32 = 0.09375;
+
+pub enum ScrollKind {
+    Side(MapPtr<SideDef>),
+    Floor(MapPtr<Sector>),
+    Ceiling(MapPtr<Sector>),
+    /// Move things that are standing on the sector floor
+    Carry(MapPtr<Sector>),
+}
+
+pub struct Scroll {
+    pub thinker: *mut Thinker,
+    pub kind: ScrollKind,
+    /// Scroll speeds
+    pub dx: f32,
+    pub dy: f32,
+    /// Control sector where the floor and ceiling height changes drive the
+    /// scrolling, or `None`
+    pub control: Option<MapPtr<Sector>>,
+    /// Last known height of the control sector
+    pub last_height: f32,
+    /// Accumulated velocity if accelerative
+    pub vdx: f32,
+    pub vdy: f32,
+    pub accel: bool,
+}
+
+impl Scroll {
+    /// Doom function name `Add_Scroller`
+    pub fn add(
+        kind: ScrollKind,
+        dx: f32,
+        dy: f32,
+        control: Option<MapPtr<Sector>>,
+        accel: bool,
+        level: &mut Level,
+    ) {
+        let last_height = control
+            .as_ref()
+            .map(|c| c.floorheight + c.ceilingheight)
+            .unwrap_or_default();
+        let scroll = Scroll {
+            thinker: null_mut(),
+            kind,
+            dx,
+            dy,
+            control,
+            last_height,
+            vdx: 0.0,
+            vdy: 0.0,
+            accel,
+        };
+
+        let thinker = MapObject::create_thinker(ThinkerData::Scroll(scroll), Scroll::think);
+
+        if let Some(ptr) = level.thinkers.push::<Scroll>(thinker) {
+            ptr.set_obj_thinker_ptr();
+        }
+    }
+
+    /// Adds a wall scroller. Scroll amount is rotated with respect to wall's
+    /// linedef first, so that scrolling towards the wall in a perpendicular
+    /// direction is translated into vertical motion, while scrolling along
+    /// the wall in a parallel direction is translated into horizontal motion.
+    ///
+    /// Doom function name `Add_WallScroller`
+    fn add_wall(
+        dx: f32,
+        dy: f32,
+        line: &mut LineDef,
+        control: Option<MapPtr<Sector>>,
+        accel: bool,
+        level: &mut Level,
+    ) {
+        let d = line.delta.x.hypot(line.delta.y);
+        if d == 0.0 {
+            return;
+        }
+        let x = -(dy * line.delta.y + dx * line.delta.x) / d;
+        let y = -(dx * line.delta.y - dy * line.delta.x) / d;
+        Scroll::add(
+            ScrollKind::Side(line.front_sidedef.clone()),
+            x,
+            y,
+            control,
+            accel,
+            level,
+        );
+    }
+
+    /// The scroll amounts for this tic. Displacement scrollers follow the
+    /// control sector height change, and accelerative ones add to the speed
+    /// built up so far.
+    fn step(&mut self) -> (f32, f32) {
+        let mut dx = self.dx;
+        let mut dy = self.dy;
+
+        if let Some(control) = self.control.as_ref() {
+            // compute scroll amounts based on a sector's height changes
+            let height = control.floorheight + control.ceilingheight;
+            let delta = height - self.last_height;
+            self.last_height = height;
+            dx *= delta;
+            dy *= delta;
+        }
+
+        if self.accel {
+            dx += self.vdx;
+            dy += self.vdy;
+            self.vdx = dx;
+            self.vdy = dy;
+        }
+        (dx, dy)
+    }
+}
+
+impl Think for Scroll {
+    fn think(object: &mut Thinker, _level: &mut Level) -> bool {
+        let scroll = object.scroll_mut();
+        #[cfg(feature = "null_check")]
+        if scroll.thinker.is_null() {
+            std::panic!("scroll thinker was null");
+        }
+
+        let (dx, dy) = scroll.step();
+        if dx == 0.0 && dy == 0.0 {
+            return false;
+        }
+
+        match &mut scroll.kind {
+            ScrollKind::Side(side) => {
+                side.textureoffset += dx;
+                side.rowoffset += dy;
+            }
+            ScrollKind::Floor(sector) => {
+                sector.floor_xoffs += dx;
+                sector.floor_yoffs += dy;
+            }
+            ScrollKind::Ceiling(sector) => {
+                sector.ceil_xoffs += dx;
+                sector.ceil_yoffs += dy;
+            }
+            ScrollKind::Carry(sector) => {
+                let height = sector.floorheight;
+                // things under deep water are also carried
+                let waterheight = sector
+                    .heightsec
+                    .as_ref()
+                    .map(|s| s.floorheight)
+                    .filter(|h| *h > height)
+                    .unwrap_or(f32::MIN);
+                sector.run_mut_func_on_thinglist(|thing| {
+                    if thing.flags & MapObjFlag::Noclip as u32 == 0
+                        && ((thing.flags & MapObjFlag::Nogravity as u32 == 0
+                            && thing.xyz.z <= height)
+                            || thing.xyz.z < waterheight)
+                    {
+                        thing.momxyz.x += dx;
+                        thing.momxyz.y += dy;
+                    }
+                    true
+                });
+            }
+        }
+
+        false
+    }
+
+    fn set_thinker_ptr(&mut self, ptr: *mut Thinker) {
+        self.thinker = ptr;
+    }
+
+    fn thinker_mut(&mut self) -> &mut Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("scroll thinker was null");
+        }
+        unsafe { &mut *self.thinker }
+    }
+
+    fn thinker(&self) -> &Thinker {
+        #[cfg(feature = "null_check")]
+        if self.thinker.is_null() {
+            std::panic!("scroll thinker was null");
+        }
+        unsafe { &*self.thinker }
+    }
+}
+
+/// Initialize the scrollers. Types 245-249 are the same as 250-254 except
+/// that the first side's sector's heights cause scrolling when they change.
+/// Types 214-218 are the same again but accelerative.
+///
+/// Doom function name `P_SpawnScrollers`
+pub fn spawn_scrollers(level: &mut Level) {
+    let level_iter = unsafe { &mut *(level as *mut Level) };
+    let lines = level_iter.map_data.linedefs.as_mut_ptr();
+    let line_count = level_iter.map_data.linedefs.len();
+
+    for i in 0..line_count {
+        let line = unsafe { &mut *lines.add(i) };
+        // direction and speed of scrolling
+        let mut dx = line.delta.x / SCROLL_SHIFT;
+        let mut dy = line.delta.y / SCROLL_SHIFT;
+        let mut control = None;
+        let mut accel = false;
+        let mut special = line.special;
+
+        if (245..=249).contains(&special) {
+            // displacement scrollers
+            special += 250 - 245;
+            control = Some(line.frontsector.clone());
+        } else if (214..=218).contains(&special) {
+            // accelerative scrollers
+            accel = true;
+            special += 250 - 214;
+            control = Some(line.frontsector.clone());
+        }
+
+        match special {
+            250 => {
+                debug!("line-special #{}: scroll ceiling", line.special);
+                for sector in level_iter
+                    .map_data
+                    .sectors_mut()
+                    .iter_mut()
+                    .filter(|s| s.tag == line.tag)
+                {
+                    let kind = ScrollKind::Ceiling(MapPtr::new(sector));
+                    Scroll::add(kind, -dx, dy, control.clone(), accel, level);
+                }
+            }
+            251..=253 => {
+                debug!("line-special #{}: scroll floor/carry", line.special);
+                if special != 252 {
+                    for sector in level_iter
+                        .map_data
+                        .sectors_mut()
+                        .iter_mut()
+                        .filter(|s| s.tag == line.tag)
+                    {
+                        let kind = ScrollKind::Floor(MapPtr::new(sector));
+                        Scroll::add(kind, -dx, dy, control.clone(), accel, level);
+                    }
+                }
+                if special != 251 {
+                    dx *= CARRYFACTOR;
+                    dy *= CARRYFACTOR;
+                    for sector in level_iter
+                        .map_data
+                        .sectors_mut()
+                        .iter_mut()
+                        .filter(|s| s.tag == line.tag)
+                    {
+                        let kind = ScrollKind::Carry(MapPtr::new(sector));
+                        Scroll::add(kind, dx, dy, control.clone(), accel, level);
+                    }
+                }
+            }
+            254 => {
+                debug!("line-special #{}: scroll tagged walls", line.special);
+                // scroll wall according to linedef (same direction and speed as
+                // scrolling floors)
+                for s in 0..line_count {
+                    let other = unsafe { &mut *lines.add(s) };
+                    if s != i && other.tag == line.tag {
+                        Scroll::add_wall(dx, dy, other, control.clone(), accel, level);
+                    }
+                }
+            }
+            255 => {
+                debug!("line-special #{}: scroll by sidedef offsets", line.special);
+                let side = line.front_sidedef.clone();
+                let (x, y) = (-side.textureoffset, side.rowoffset);
+                Scroll::add(ScrollKind::Side(side), x, y, None, accel, level);
+            }
+            85 => {
+                debug!("line-special #{}: scroll texture right", line.special);
+                let side = ScrollKind::Side(line.front_sidedef.clone());
+                Scroll::add(side, -1.0, 0.0, None, accel, level);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr::null_mut;
+
+    use super::{Scroll, ScrollKind, CARRYFACTOR, SCROLL_SHIFT};
+    use crate::level::map_defs::Sector;
+    use crate::MapPtr;
+
+    fn scroll(kind: ScrollKind, dx: f32, control: Option<&mut Sector>, accel: bool) -> Scroll {
+        let control = control.map(MapPtr::new);
+        Scroll {
+            thinker: null_mut(),
+            kind,
+            dx,
+            dy: 0.0,
+            last_height: control
+                .as_ref()
+                .map(|c| c.floorheight + c.ceilingheight)
+                .unwrap_or_default(),
+            control,
+            vdx: 0.0,
+            vdy: 0.0,
+            accel,
+        }
+    }
+
+    #[test]
+    fn carry_moves_at_a_constant_rate() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        // a 64 unit linedef as spawned for special 252
+        let dx = 64.0 / SCROLL_SHIFT * CARRYFACTOR;
+        let mut carry = scroll(ScrollKind::Carry(MapPtr::new(&mut sector)), dx, None, false);
+        for _ in 0..3 {
+            assert_eq!(carry.step(), (0.1875, 0.0));
+        }
+    }
+
+    #[test]
+    fn displacement_carry_follows_the_control_sector() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut control = Sector::new(1, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut ptr = MapPtr::new(&mut control);
+        let kind = ScrollKind::Carry(MapPtr::new(&mut sector));
+        let mut carry = scroll(kind, 0.5, Some(&mut control), false);
+
+        assert_eq!(carry.step(), (0.0, 0.0));
+        ptr.floorheight += 8.0;
+        assert_eq!(carry.step(), (4.0, 0.0));
+        // stops as soon as the sector does
+        assert_eq!(carry.step(), (0.0, 0.0));
+        ptr.floorheight -= 2.0;
+        assert_eq!(carry.step(), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn accelerative_scroll_keeps_its_speed() {
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut control = Sector::new(1, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut ptr = MapPtr::new(&mut control);
+        let kind = ScrollKind::Floor(MapPtr::new(&mut sector));
+        let mut accel = scroll(kind, 0.5, Some(&mut control), true);
+
+        assert_eq!(accel.step(), (0.0, 0.0));
+        ptr.floorheight += 8.0;
+        assert_eq!(accel.step(), (4.0, 0.0));
+        // speed is kept after the control sector stops
+        assert_eq!(accel.step(), (4.0, 0.0));
+        ptr.ceilingheight += 4.0;
+        assert_eq!(accel.step(), (6.0, 0.0));
+        ptr.floorheight -= 12.0;
+        assert_eq!(accel.step(), (0.0, 0.0));
+        assert_eq!((accel.vdx, accel.vdy), (0.0, 0.0));
+    }
+}
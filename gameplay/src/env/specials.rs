@@ -12,15 +12,18 @@ use crate::env::lights::{
     ev_start_light_strobing, ev_turn_light_on, ev_turn_tag_lights_off, FireFlicker, Glow, LightFlash, StrobeFlash, FASTDARK, SLOWDARK
 };
 use crate::env::platforms::{ev_do_platform, ev_stop_platform, PlatKind};
+use crate::env::pushers::{spawn_friction, spawn_pushers};
+use crate::env::scroll::spawn_scrollers;
 use crate::env::switch::{change_switch_texture, start_sector_sound};
 use crate::env::teleport::teleport;
+use crate::fixed::{float_to_fixed, Fixed};
 use crate::info::{MapObjKind, MOBJINFO};
 use crate::level::flags::LineDefFlags;
 use crate::level::map_defs::{LineDef, Sector};
 use crate::level::Level;
 use crate::pic::ButtonWhere;
-use crate::thing::MapObject;
-use crate::utilities::circle_line_collide;
+use crate::thing::{fixed_change_sector, MapObject};
+use crate::utilities::{circle_line_collide, fixed_to_float};
 use crate::{Angle, MapObjFlag, MapPtr, PicData, TICRATE};
 use glam::Vec3;
 use log::{debug, error, trace};
@@ -204,7 +207,19 @@ pub fn move_plane(
     crush: bool,
     floor_or_ceiling: i32,
     direction: i32,
+    level: &mut Level,
 ) -> PlaneResult {
+    if level.options.fixed_point {
+        return move_plane_fixed(
+            sector,
+            float_to_fixed(speed),
+            float_to_fixed(dest),
+            crush,
+            floor_or_ceiling,
+            direction,
+            level,
+        );
+    }
     match floor_or_ceiling {
         0 => {
             // FLOOR
@@ -341,15 +356,95 @@ pub fn move_plane(
     PlaneResult::Ok
 }
 
+/// `move_plane()` for the fixed-point mode, stepping and crushing as the
+/// original did. Things near the sector are found through the `BlockMap`.
+///
+/// Doom function name `T_MovePlane`
+fn move_plane_fixed(
+    mut sector: MapPtr<Sector>,
+    speed: Fixed,
+    dest: Fixed,
+    crush: bool,
+    floor_or_ceiling: i32,
+    direction: i32,
+    level: &mut Level,
+) -> PlaneResult {
+    if direction != -1 && direction != 1 {
+        error!("Invalid plane direction: {}", direction);
+        return PlaneResult::Ok;
+    }
+    let floor = match floor_or_ceiling {
+        0 => true,
+        1 => false,
+        _ => {
+            error!("Invalid floor_or_ceiling: {}", floor_or_ceiling);
+            return PlaneResult::Ok;
+        }
+    };
+    // Set the plane to `height` and clip the things near it, true if any
+    // no longer fit
+    let mut change = |sector: &mut MapPtr<Sector>, height: Fixed| {
+        if floor {
+            sector.floorheight = fixed_to_float(height);
+        } else {
+            sector.ceilingheight = fixed_to_float(height);
+        }
+        fixed_change_sector(level, sector, crush)
+    };
+
+    let last_pos = float_to_fixed(if floor {
+        sector.floorheight
+    } else {
+        sector.ceilingheight
+    });
+    let down = direction == -1;
+    let past_dest = if down {
+        last_pos - speed < dest
+    } else {
+        last_pos + speed > dest
+    };
+    if past_dest {
+        if change(&mut sector, dest) {
+            change(&mut sector, last_pos);
+        }
+        return PlaneResult::PastDest;
+    }
+
+    let next = if down {
+        last_pos - speed
+    } else {
+        last_pos + speed
+    };
+    if change(&mut sector, next) {
+        // A rising ceiling never stops
+        if !floor && !down {
+            return PlaneResult::Ok;
+        }
+        // Crushers keep going, except a lowering floor which always goes back
+        if crush && !(floor && down) {
+            return PlaneResult::Crushed;
+        }
+        change(&mut sector, last_pos);
+        return PlaneResult::Crushed;
+    }
+    PlaneResult::Ok
+}
+
 /// Trigger various actions when a line is crossed which has a non-zero special
-/// attached
+/// attached. `bossaction` is set for `UMAPINFO` boss death actions, which
+/// may trigger any special.
 ///
 /// Doom function name is `P_CrossSpecialLine`
-pub fn cross_special_line(side: usize, mut line: MapPtr<LineDef>, thing: &mut MapObject) {
+pub fn cross_special_line(
+    side: usize,
+    mut line: MapPtr<LineDef>,
+    thing: &mut MapObject,
+    bossaction: bool,
+) {
     let mut ok = false;
 
     //  Triggers that other things can activate
-    if thing.player().is_none() {
+    if thing.player().is_none() && !bossaction {
         // Things that should NOT trigger specials...
         match thing.kind {
             MapObjKind::MT_ROCKET
@@ -794,7 +889,8 @@ pub fn shoot_special_line(line: MapPtr<LineDef>, thing: &mut MapObject) {
 }
 
 pub fn spawn_specials(level: &mut Level) {
-    // TODO: level timer
+    // See if -timer needs to be used
+    level.level_timer = level.options.deathmatch != 0 && level.options.time_limit.is_some();
 
     let level_iter = unsafe { &mut *(level as *mut Level) };
     for sector in level_iter
@@ -859,18 +955,60 @@ pub fn spawn_specials(level: &mut Level) {
         }
     }
 
+    // BOOM translucent lines, tagged 260 lines set all lines with the same tag
+    let tran_tags: Vec<i16> = level
+        .map_data
+        .linedefs
+        .iter()
+        .filter(|l| l.special == 260 && l.tag != 0)
+        .map(|l| l.tag)
+        .collect();
+
     for line in level_iter.map_data.linedefs.iter_mut() {
+        if (line.special == 260 && line.tag == 0) || tran_tags.contains(&line.tag) {
+            line.translucent = true;
+        }
         if line.special == 48 {
             level.line_special_list.push(MapPtr::new(line));
         }
+        if line.special == 242 {
+            debug!("line-special #{}: fake floor and ceiling", line.special);
+            for sector in level
+                .map_data
+                .sectors_mut()
+                .iter_mut()
+                .filter(|s| s.tag == line.tag)
+            {
+                sector.heightsec = Some(line.frontsector.clone());
+            }
+        }
+        if line.special == 271 || line.special == 272 {
+            debug!("line-special #{}: transfer sky texture", line.special);
+            transfer_sky(line, level.map_data.sectors_mut());
+        }
+    }
+
+    // BOOM
+    spawn_scrollers(level);
+    spawn_friction(level);
+    spawn_pushers(level);
+}
+
+/// Draw the sky of the sectors tagged by a sky transfer line (271/272) with
+/// the upper texture of its front side
+fn transfer_sky(line: &mut LineDef, sectors: &mut [Sector]) {
+    let tag = line.tag;
+    for sector in sectors.iter_mut().filter(|s| s.tag == tag) {
+        sector.sky_line = Some(MapPtr::new(line));
     }
 }
 
 /// Doom function name `P_UpdateSpecials`
 pub fn update_specials(level: &mut Level, pic_data: &mut PicData) {
-    // Used mostly for deathmatch as far as I know
-    if level.level_timer && level.level_time == 0 {
-        // exit
+    // -timer for deathmatch
+    if level.level_timer
+        && level.options.time_limit.map(|m| m * 60 * TICRATE as u32) == Some(level.level_time)
+    {
         level.do_exit_level();
     }
 
@@ -916,8 +1054,8 @@ pub fn update_specials(level: &mut Level, pic_data: &mut PicData) {
 
 /// P_RespawnSpecials
 pub fn respawn_specials(level: &mut Level) {
-    // only respawn items in deathmatch
-    if level.options.deathmatch == 0 && !level.options.respawn_monsters {
+    // only respawn items in altdeath
+    if level.options.deathmatch != 2 {
         return;
     }
 
@@ -970,3 +1108,59 @@ pub fn respawn_specials(level: &mut Level) {
         thing.spawnpoint = mthing.1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::transfer_sky;
+    use crate::level::map_defs::{BBox, LineDef, Sector, SlopeType};
+    use crate::MapPtr;
+
+    fn sky_line(special: i16, tag: i16, front: &mut Sector) -> LineDef {
+        LineDef {
+            v1: Vec3::ZERO,
+            v2: Vec3::new(64.0, 0.0, 0.0),
+            delta: Vec3::new(64.0, 0.0, 0.0),
+            flags: 0,
+            special,
+            tag,
+            bbox: BBox::default(),
+            slopetype: SlopeType::Horizontal,
+            sides: [0, 0],
+            front_sidedef: unsafe { MapPtr::new_null() },
+            back_sidedef: None,
+            frontsector: MapPtr::new(front),
+            backsector: None,
+            valid_count: 0,
+            translucent: false,
+        }
+    }
+
+    #[test]
+    fn sky_transfer_to_tagged_sectors() {
+        let mut sectors: Vec<Sector> = [0, 3, 0, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(i, tag)| Sector::new(i as u32, 0.0, 128.0, 0, 0, 160, 0, tag))
+            .collect();
+        let mut control = Sector::new(4, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut flipped = sky_line(271, 3, &mut control);
+        let mut line = sky_line(272, 0, &mut control);
+
+        transfer_sky(&mut flipped, &mut sectors);
+        transfer_sky(&mut line, &mut sectors);
+        let specials: Vec<Option<i16>> = sectors
+            .iter()
+            .map(|s| s.sky_line.as_ref().map(|l| l.special))
+            .collect();
+        // a zero tag is only matched by untagged sectors
+        assert_eq!(specials, [Some(272), Some(271), Some(272), Some(271)]);
+
+        // a later line takes over
+        let mut later = sky_line(272, 3, &mut control);
+        transfer_sky(&mut later, &mut sectors);
+        let sky = sectors[3].sky_line.as_ref().unwrap();
+        assert!(std::ptr::eq(sky.as_ref(), &later));
+    }
+}
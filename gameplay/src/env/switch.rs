@@ -1,8 +1,7 @@
 //! Doom source name `p_switch`
 
 use log::{debug, warn};
-use sound_sdl2::SndServerTx;
-use sound_traits::SfxName;
+use sound_traits::{SfxName, SndServerTx};
 
 use crate::thing::MapObject;
 
@@ -136,10 +135,16 @@ pub fn change_switch_texture(
 
 /// P_UseSpecialLine
 /// Called when a thing uses a special line.
-/// Only the front sides of lines are usable.
-pub fn p_use_special_line(_side: i32, line: MapPtr<LineDef>, thing: &mut MapObject) -> bool {
+/// Only the front sides of lines are usable. `bossaction` is set for
+/// `UMAPINFO` boss death actions, which can't use manual doors.
+pub fn p_use_special_line(
+    _side: i32,
+    line: MapPtr<LineDef>,
+    thing: &mut MapObject,
+    bossaction: bool,
+) -> bool {
     //  Switches that other things can activate
-    if thing.player().is_none() {
+    if thing.player().is_none() && !bossaction {
         // never open secret doors
         if line.flags & LineDefFlags::Secret as u32 != 0 {
             return false;
@@ -168,6 +173,9 @@ pub fn p_use_special_line(_side: i32, line: MapPtr<LineDef>, thing: &mut MapObje
         | 117 // Blazing door raise
         | 118 // Blazing door open
         => {
+            if bossaction {
+                return false;
+            }
             ev_vertical_door(line, thing, level);
         }
         11 => {
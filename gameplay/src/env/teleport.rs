@@ -2,6 +2,7 @@ use std::ptr;
 
 use glam::Vec3;
 
+use crate::fixed::{finecosine, finesine, float_to_fixed, ANGLETOFINESHIFT};
 use crate::info::MapObjKind;
 use crate::level::map_defs::LineDef;
 use crate::thinker::ThinkerData;
@@ -41,6 +42,9 @@ pub fn teleport(
                 false
             }) {
                 let level = unsafe { &mut *thing.level };
+                if level.options.fixed_point {
+                    return teleport_fixed(thing, thinker.mobj(), level);
+                }
 
                 let old_xy = thing.xyz;
                 let old_z = thing.xyz.z;
@@ -91,8 +95,57 @@ pub fn teleport(
     false
 }
 
+/// `teleport()` to `endpoint` for the fixed-point mode
+fn teleport_fixed(thing: &mut MapObject, endpoint: &MapObject, level: &mut Level) -> bool {
+    thing.sync_fixed();
+    let (oldx, oldy, oldz) = (thing.fixed.x, thing.fixed.y, thing.fixed.z);
+    let (x, y, _) = endpoint.fixed_pos();
+    if !thing.fixed_teleport_move(x, y) {
+        return false;
+    }
+    thing.fixed.z = float_to_fixed(thing.floorz);
+    thing.xyz.z = thing.floorz;
+    let z = thing.floorz;
+    if let Some(player) = thing.player_mut() {
+        player.viewz = z + player.viewheight;
+    }
+
+    let fog = MapObject::spawn_map_object_fixed(oldx, oldy, oldz, MapObjKind::MT_TFOG, level);
+    unsafe {
+        (*fog).start_sound(sound_traits::SfxName::Telept);
+    }
+
+    // spawn teleport fog at the destination, in front of the thing
+    let angle = endpoint.fixed_angle();
+    let fine = (angle >> ANGLETOFINESHIFT) as usize;
+    let fog = MapObject::spawn_map_object_fixed(
+        x + 20 * finecosine(fine),
+        y + 20 * finesine(fine),
+        thing.fixed.z,
+        MapObjKind::MT_TFOG,
+        level,
+    );
+    unsafe {
+        (*fog).start_sound(sound_traits::SfxName::Telept);
+    }
+
+    if thing.player().is_some() {
+        thing.reactiontime = 18;
+    }
+    thing.fixed.angle = angle;
+    thing.fixed.momx = 0;
+    thing.fixed.momy = 0;
+    thing.fixed.momz = 0;
+    thing.store_fixed();
+    true
+}
+
 /// Doom function nam `P_TeleportMove`
 pub fn teleport_move(xyz: Vec3, thing: &mut MapObject, level: &mut Level) -> bool {
+    if level.options.fixed_point {
+        thing.sync_fixed();
+        return thing.fixed_teleport_move(float_to_fixed(xyz.x), float_to_fixed(xyz.y));
+    }
     let new_subsect = &mut *level.map_data.point_in_subsector_raw(xyz);
     let floorz = new_subsect.sector.floorheight;
     let ceilzz = new_subsect.sector.ceilingheight;
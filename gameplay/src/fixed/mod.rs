@@ -0,0 +1,177 @@
+//! Vanilla 16.16 fixed-point and BAM (Binary Angle Measure) math, used by the
+//! optional fixed-point simulation mode (`GameOptions::fixed_point`) so that
+//! movement rounds exactly as the original engine did.
+//!
+//! Doom source name `m_fixed`, `tables`
+
+mod tables;
+
+use std::f64::consts::PI;
+
+use crate::angle::Angle;
+use crate::utilities::bam_to_radian;
+
+use self::tables::{FINESINE, TANTOANGLE};
+
+/// A 16.16 fixed-point number
+pub type Fixed = i32;
+/// A BAM angle, `u32::MAX` is just short of a full circle
+pub type Bam = u32;
+
+pub const FRACBITS: i32 = 16;
+pub const FRACUNIT: Fixed = 1 << FRACBITS;
+
+pub const ANG45: Bam = 0x20000000;
+pub const ANG90: Bam = 0x40000000;
+pub const ANG180: Bam = 0x80000000;
+pub const ANG270: Bam = 0xC0000000;
+
+pub const FINEANGLES: usize = 8192;
+pub const FINEMASK: usize = FINEANGLES - 1;
+/// Shift a `Bam` right by this to get an index in to the fine tables
+pub const ANGLETOFINESHIFT: u32 = 19;
+
+const SLOPERANGE: usize = 2048;
+
+/// Sine of a fine angle index
+pub fn finesine(fine: usize) -> Fixed {
+    FINESINE[fine & FINEMASK]
+}
+
+/// Cosine of a fine angle index
+pub fn finecosine(fine: usize) -> Fixed {
+    FINESINE[(fine & FINEMASK) + FINEANGLES / 4]
+}
+
+/// Doom function name `FixedMul`
+pub fn fixed_mul(a: Fixed, b: Fixed) -> Fixed {
+    ((a as i64 * b as i64) >> FRACBITS) as Fixed
+}
+
+/// Divide, saturating to `i32::MIN`/`i32::MAX` where the quotient would
+/// overflow as the original did
+///
+/// Doom function name `FixedDiv`
+pub fn fixed_div(a: Fixed, b: Fixed) -> Fixed {
+    if (a.unsigned_abs() >> 14) >= b.unsigned_abs() {
+        return if (a ^ b) < 0 { i32::MIN } else { i32::MAX };
+    }
+    (((a as i64) << FRACBITS) / b as i64) as Fixed
+}
+
+/// Convert a `f32` in map units to fixed-point, truncating as `(int)(f *
+/// FRACUNIT)` would
+pub fn float_to_fixed(value: f32) -> Fixed {
+    (value as f64 * FRACUNIT as f64) as Fixed
+}
+
+/// Convert an `Angle` to BAM, rounded to the nearest `1 << 16`. That is the
+/// resolution of both map thing angles and tic cmd turning, and is far coarser
+/// than the error in an `f32` radian.
+pub fn angle_to_bam(angle: Angle) -> Bam {
+    let bam = angle.rad() as f64 / (2.0 * PI) * 4294967296.0;
+    ((bam / 65536.0).round() as i64 as u32).wrapping_shl(16)
+}
+
+/// Convert a BAM to an `Angle`
+pub fn bam_to_angle(bam: Bam) -> Angle {
+    Angle::new(bam_to_radian(bam))
+}
+
+/// Doom function name `SlopeDiv`
+fn slope_div(num: u32, den: u32) -> usize {
+    if den < 512 {
+        return SLOPERANGE;
+    }
+    let ans = (num.wrapping_shl(3) / (den >> 8)) as usize;
+    ans.min(SLOPERANGE)
+}
+
+/// The BAM angle of the vector from `(x1, y1)` to `(x2, y2)`
+///
+/// Doom function name `R_PointToAngle2`
+pub fn point_to_angle2(x1: Fixed, y1: Fixed, x2: Fixed, y2: Fixed) -> Bam {
+    let tantoangle = &TANTOANGLE;
+    let x = x2.wrapping_sub(x1);
+    let y = y2.wrapping_sub(y1);
+    if x == 0 && y == 0 {
+        return 0;
+    }
+
+    let (ax, ay) = (x.unsigned_abs(), y.unsigned_abs());
+    match (x >= 0, y >= 0) {
+        (true, true) if ax > ay => tantoangle[slope_div(ay, ax)],
+        (true, true) => (ANG90 - 1).wrapping_sub(tantoangle[slope_div(ax, ay)]),
+        (true, false) if ax > ay => tantoangle[slope_div(ay, ax)].wrapping_neg(),
+        (true, false) => ANG270.wrapping_add(tantoangle[slope_div(ax, ay)]),
+        (false, true) if ax > ay => (ANG180 - 1).wrapping_sub(tantoangle[slope_div(ay, ax)]),
+        (false, true) => ANG90.wrapping_add(tantoangle[slope_div(ax, ay)]),
+        (false, false) if ax > ay => ANG180.wrapping_add(tantoangle[slope_div(ay, ax)]),
+        (false, false) => (ANG270 - 1).wrapping_sub(tantoangle[slope_div(ax, ay)]),
+    }
+}
+
+/// Gives an estimation of distance (not exact)
+///
+/// Doom function name `P_AproxDistance`
+pub fn aprox_distance(dx: Fixed, dy: Fixed) -> Fixed {
+    let dx = dx.wrapping_abs();
+    let dy = dy.wrapping_abs();
+    if dx < dy {
+        return dx.wrapping_add(dy).wrapping_sub(dx >> 1);
+    }
+    dx.wrapping_add(dy).wrapping_sub(dy >> 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fine_tables() {
+        assert_eq!(finesine(0), 25);
+        assert_eq!(finesine(1), 75);
+        assert_eq!(finesine(4), 226);
+        assert_eq!(finesine(11), 578);
+        assert_eq!(finesine(15), 779);
+        assert_eq!(finesine(2048), 65535);
+        assert_eq!(finesine(4096), -25);
+        assert_eq!(finecosine(0), 65535);
+        assert_eq!(finecosine(2048), -25);
+    }
+
+    #[test]
+    fn fixed_multiply() {
+        assert_eq!(fixed_mul(FRACUNIT * 3, FRACUNIT / 2), FRACUNIT * 3 / 2);
+        assert_eq!(fixed_mul(-FRACUNIT, 0xE800), -0xE800);
+    }
+
+    #[test]
+    fn fixed_divide() {
+        assert_eq!(fixed_div(FRACUNIT * 3, FRACUNIT * 2), FRACUNIT * 3 / 2);
+        assert_eq!(fixed_div(-FRACUNIT, FRACUNIT * 4), -FRACUNIT / 4);
+        assert_eq!(fixed_div(FRACUNIT * 100, 1), i32::MAX);
+        assert_eq!(fixed_div(-FRACUNIT * 100, 1), i32::MIN);
+    }
+
+    #[test]
+    fn point_to_angle() {
+        let f = FRACUNIT;
+        assert_eq!(TANTOANGLE[1], 333772);
+        assert_eq!(TANTOANGLE[3], 1001315);
+        assert_eq!(TANTOANGLE[15], 5006492);
+        assert_eq!(TANTOANGLE[SLOPERANGE], ANG45);
+        assert_eq!(point_to_angle2(0, 0, 10 * f, 0), 0);
+        assert_eq!(point_to_angle2(0, 0, 10 * f, 10 * f), ANG45 - 1);
+        assert_eq!(point_to_angle2(0, 0, 0, 10 * f), ANG90 - 1);
+        assert_eq!(point_to_angle2(0, 0, -10 * f, 0), ANG180 - 1);
+        assert_eq!(point_to_angle2(0, 0, 0, -10 * f), ANG270);
+    }
+
+    #[test]
+    fn angle_round_trip() {
+        for bam in [0, ANG45, ANG90, ANG180, ANG270, 0x1234 << 16, 0xffff << 16] {
+            assert_eq!(angle_to_bam(bam_to_angle(bam)), bam);
+        }
+    }
+}
@@ -1325,7 +1325,9 @@ pub struct State {
 }
 
 const NUM_STATES: usize = StateNum::Count as usize;
-pub const STATES: [State; NUM_STATES] = [
+/// A `static` so each state has a single address that can be turned back in
+/// to an index, e.g, by savegames
+pub static STATES: [State; NUM_STATES] = [
     // State::new(sprite, frame, tics, action, next_state, misc1, misc2)
     State {
         // S_NULL
@@ -11508,7 +11510,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_KNIGHT
@@ -11926,7 +11929,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_HEADSHOT
@@ -11955,7 +11959,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_ROCKET
@@ -12013,7 +12018,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_BFG
@@ -12042,7 +12048,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_ARACHPLAZ
@@ -12071,7 +12078,8 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         flags: MapObjFlag::Noblockmap as u32
             | MapObjFlag::Missile as u32
             | MapObjFlag::Dropoff as u32
-            | MapObjFlag::Nogravity as u32,
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_PUFF
@@ -12149,7 +12157,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_IFOG
@@ -12175,7 +12185,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Noblockmap as u32 | MapObjFlag::Nogravity as u32,
+        flags: MapObjFlag::Noblockmap as u32
+            | MapObjFlag::Nogravity as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_TELEPORTMAN
@@ -12565,7 +12577,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_INV
@@ -12591,7 +12605,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_MISC13
@@ -12643,7 +12659,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_MISC14
@@ -12747,7 +12765,9 @@ pub const MOBJINFO: [MapObjInfo; NUM_CATEGORIES] = [
         mass: 100,
         damage: 0,
         activesound: SfxName::None,
-        flags: MapObjFlag::Special as u32 | MapObjFlag::Countitem as u32,
+        flags: MapObjFlag::Special as u32
+            | MapObjFlag::Countitem as u32
+            | MapObjFlag::Translucent as u32,
         raisestate: StateNum::None,
     },
     // MT_CLIP
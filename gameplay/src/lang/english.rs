@@ -63,3 +63,12 @@ pub const STSTR_BEHOLDX: &str = "Power-up Toggled";
 
 pub const STSTR_CHOPPERS: &str = "... doesn't suck - GM";
 pub const STSTR_CLEV: &str = "Changing Level...";
+
+pub const GGSAVED: &str = "game saved.";
+
+pub const PLAYER_LEFT: [&str; 4] = [
+    "Player 1 left the game",
+    "Player 2 left the game",
+    "Player 3 left the game",
+    "Player 4 left the game",
+];
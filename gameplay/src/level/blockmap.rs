@@ -0,0 +1,194 @@
+//! The `BLOCKMAP`, the level cut in to 128 unit square blocks that each list
+//! the lines touching them, along with a list per block of the things whose
+//! centre is in it.
+//!
+//! Only the fixed-point mode (`GameOptions::fixed_point`) uses it, to find
+//! lines and things to check in exactly the order the original engine did.
+//!
+//! Doom source name `p_setup` (`P_LoadBlockMap`, `P_GroupLines`)
+
+use log::{info, warn};
+use wad::types::WadBlockMap;
+
+use crate::fixed::{float_to_fixed, Fixed, FRACBITS, FRACUNIT};
+use crate::level::map_defs::{LineDef, Sector};
+use crate::thinker::Thinker;
+
+pub const MAPBLOCKUNITS: i32 = 128;
+pub const MAPBLOCKSIZE: Fixed = MAPBLOCKUNITS * FRACUNIT;
+pub const MAPBLOCKSHIFT: i32 = FRACBITS + 7;
+/// Shift a block relative `Fixed` right by this to get a 16.16 fraction of a
+/// block
+pub const MAPBTOFRAC: i32 = MAPBLOCKSHIFT - FRACBITS;
+/// The radius of the largest thing, things are only linked in the block their
+/// centre is in so this much is added when looking for things touching a box
+pub const MAXRADIUS: Fixed = 32 * FRACUNIT;
+
+pub(crate) const BOXTOP: usize = 0;
+pub(crate) const BOXBOTTOM: usize = 1;
+pub(crate) const BOXLEFT: usize = 2;
+pub(crate) const BOXRIGHT: usize = 3;
+
+#[derive(Default)]
+pub struct BlockMap {
+    pub orgx: Fixed,
+    pub orgy: Fixed,
+    pub width: i32,
+    pub height: i32,
+    /// The whole lump as read, header and offsets included, so that an offset
+    /// indexes it directly. A list starts with a `0` and ends with `0xFFFF`.
+    lump: Vec<u16>,
+    /// The first thing in each block
+    pub(crate) links: Vec<Option<*mut Thinker>>,
+    /// The blocks a change of height in each sector can reach, as `top,
+    /// bottom, left, right`
+    sector_boxes: Vec<[i32; 4]>,
+}
+
+impl BlockMap {
+    /// Use the map's `BLOCKMAP`, or build one if it has none
+    pub(super) fn new(wad: Option<WadBlockMap>, lines: &[LineDef], sectors: &[Sector]) -> Self {
+        let mut blockmap = match wad {
+            Some(wad) => Self {
+                orgx: (wad.x_origin as Fixed) << FRACBITS,
+                orgy: (wad.y_origin as Fixed) << FRACBITS,
+                width: wad.width as u16 as i32,
+                height: wad.height as u16 as i32,
+                lump: wad.line_indexes.iter().map(|i| *i as u16).collect(),
+                ..Self::default()
+            },
+            None => {
+                warn!("Map has no BLOCKMAP, building one");
+                Self::build(lines)
+            }
+        };
+        info!("Blockmap is {}x{} blocks", blockmap.width, blockmap.height);
+        blockmap.links = vec![None; (blockmap.width * blockmap.height) as usize];
+        blockmap.sector_boxes = sectors
+            .iter()
+            .map(|sector| blockmap.sector_box(sector))
+            .collect();
+        blockmap
+    }
+
+    /// Build a blockmap listing each line in every block its bounding box
+    /// touches, for maps whose node builder left it out
+    fn build(lines: &[LineDef]) -> Self {
+        let mut min = (i32::MAX, i32::MAX);
+        let mut max = (i32::MIN, i32::MIN);
+        for line in lines {
+            min.0 = min.0.min(line.bbox.left as i32);
+            min.1 = min.1.min(line.bbox.bottom as i32);
+            max.0 = max.0.max(line.bbox.right as i32);
+            max.1 = max.1.max(line.bbox.top as i32);
+        }
+        if lines.is_empty() {
+            (min, max) = ((0, 0), (0, 0));
+        }
+        let (orgx, orgy) = (min.0 - 8, min.1 - 8);
+        let width = (max.0 - orgx) / MAPBLOCKUNITS + 1;
+        let height = (max.1 - orgy) / MAPBLOCKUNITS + 1;
+
+        let mut blocks = vec![Vec::new(); (width * height) as usize];
+        for (i, line) in lines.iter().enumerate() {
+            let left = (line.bbox.left as i32 - orgx) / MAPBLOCKUNITS;
+            let right = (line.bbox.right as i32 - orgx) / MAPBLOCKUNITS;
+            let bottom = (line.bbox.bottom as i32 - orgy) / MAPBLOCKUNITS;
+            let top = (line.bbox.top as i32 - orgy) / MAPBLOCKUNITS;
+            for y in bottom..=top {
+                for x in left..=right {
+                    blocks[(y * width + x) as usize].push(i as u16);
+                }
+            }
+        }
+
+        // Header, then an offset per block, then a list per block
+        let mut lump = vec![orgx as u16, orgy as u16, width as u16, height as u16];
+        let base = lump.len() + blocks.len();
+        let mut lists = Vec::new();
+        for block in blocks {
+            lump.push((base + lists.len()) as u16);
+            lists.push(0);
+            lists.extend(block);
+            lists.push(u16::MAX);
+        }
+        lump.extend(lists);
+
+        Self {
+            orgx: orgx << FRACBITS,
+            orgy: orgy << FRACBITS,
+            width,
+            height,
+            lump,
+            ..Self::default()
+        }
+    }
+
+    /// The blocks that things touching `sector` can be in, clamped to the map
+    fn sector_box(&self, sector: &Sector) -> [i32; 4] {
+        // M_ClearBox then M_AddToBox, which only grows one side per point
+        let mut bbox = [i32::MIN, i32::MAX, i32::MAX, i32::MIN];
+        for line in &sector.lines {
+            for v in [line.v1, line.v2] {
+                let (x, y) = (float_to_fixed(v.x), float_to_fixed(v.y));
+                if x < bbox[BOXLEFT] {
+                    bbox[BOXLEFT] = x;
+                } else if x > bbox[BOXRIGHT] {
+                    bbox[BOXRIGHT] = x;
+                }
+                if y < bbox[BOXBOTTOM] {
+                    bbox[BOXBOTTOM] = y;
+                } else if y > bbox[BOXTOP] {
+                    bbox[BOXTOP] = y;
+                }
+            }
+        }
+
+        let block = |v: Fixed, org: Fixed| v.wrapping_sub(org) >> MAPBLOCKSHIFT;
+        [
+            block(bbox[BOXTOP].wrapping_add(MAXRADIUS), self.orgy).min(self.height - 1),
+            block(bbox[BOXBOTTOM].wrapping_sub(MAXRADIUS), self.orgy).max(0),
+            block(bbox[BOXLEFT].wrapping_sub(MAXRADIUS), self.orgx).max(0),
+            block(bbox[BOXRIGHT].wrapping_add(MAXRADIUS), self.orgx).min(self.width - 1),
+        ]
+    }
+
+    /// The blocks to check for things when `sector` changes height, as `top,
+    /// bottom, left, right`
+    pub(crate) fn sector_blockbox(&self, sector: &Sector) -> [i32; 4] {
+        self.sector_boxes[sector.num as usize]
+    }
+
+    /// The index of block `(x, y)`, or `None` if it is outside the map
+    pub(crate) fn block(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    /// The index of the block containing the point
+    pub(crate) fn block_at(&self, x: Fixed, y: Fixed) -> Option<usize> {
+        self.block(
+            x.wrapping_sub(self.orgx) >> MAPBLOCKSHIFT,
+            y.wrapping_sub(self.orgy) >> MAPBLOCKSHIFT,
+        )
+    }
+
+    /// The line list of a block, including the leading `0` that makes the
+    /// first line of the map checked in every block as it was originally
+    pub(crate) fn block_lines(&self, block: usize) -> &[u16] {
+        let offset = self.lump[4 + block] as usize;
+        let list = &self.lump[offset.min(self.lump.len())..];
+        let end = list
+            .iter()
+            .position(|i| *i == u16::MAX)
+            .unwrap_or(list.len());
+        &list[..end]
+    }
+
+    /// Drop every link to a `Thinker`, for when all thinkers are replaced
+    pub(crate) fn clear_links(&mut self) {
+        self.links.iter_mut().for_each(|link| *link = None);
+    }
+}
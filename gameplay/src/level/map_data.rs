@@ -3,6 +3,8 @@ use std::f32::consts::FRAC_PI_2;
 use std::time::Instant;
 
 use crate::angle::Angle;
+use crate::fixed::Fixed;
+use crate::level::blockmap::BlockMap;
 use crate::level::map_defs::{BBox, LineDef, Node, Sector, Segment, SideDef, SlopeType, SubSector};
 use crate::log::info;
 use crate::utilities::{bam_to_radian, circle_line_collide};
@@ -54,6 +56,10 @@ pub struct MapData {
     extents: MapExtents,
     nodes: Vec<Node>,
     start_node: u32,
+    /// Only used by the fixed-point mode
+    pub(crate) blockmap: BlockMap,
+    /// Only used by the fixed-point mode, see `WadData::read_reject()`
+    pub(crate) reject: Vec<u8>,
 }
 
 impl MapData {
@@ -103,6 +109,10 @@ impl MapData {
         &self.sidedefs
     }
 
+    pub fn sidedefs_mut(&mut self) -> &mut [SideDef] {
+        &mut self.sidedefs
+    }
+
     pub fn subsectors(&self) -> &[SubSector] {
         &self.subsectors
     }
@@ -119,6 +129,24 @@ impl MapData {
         &mut self.segments
     }
 
+    /// A map of `sectors` without nodes, where every point is in a single
+    /// subsector of the first sector. For tests that can't load a WAD.
+    #[cfg(test)]
+    pub(crate) fn with_sectors(sectors: Vec<Sector>) -> Self {
+        let mut map = MapData {
+            sectors,
+            start_node: IS_SSECTOR_MASK,
+            ..Default::default()
+        };
+        let sector = MapPtr::new(&mut map.sectors[0]);
+        map.subsectors.push(SubSector {
+            sector,
+            seg_count: 0,
+            start_seg: 0,
+        });
+        map
+    }
+
     fn set_scale(&mut self) {
         let map_width = self.extents.width;
         let map_height = self.extents.height;
@@ -170,12 +198,15 @@ impl MapData {
         self.load_sectors(map_name, wad, pic_data);
         self.load_sidedefs(map_name, wad, &tex_order);
         self.load_linedefs(map_name, wad);
+        self.load_boom_colourmaps(map_name, wad, pic_data);
         // TODO: iterate sector lines to find max bounding box for sector
 
         // The BSP level structure for rendering, movement, collisions etc
         self.load_segments(map_name, wad, extended.as_ref());
         self.load_subsectors(map_name, wad, extended.as_ref());
         self.load_nodes(map_name, wad, node_type, extended.as_ref());
+        self.blockmap = BlockMap::new(wad.read_blockmap(map_name), &self.linedefs, &self.sectors);
+        self.reject = wad.read_reject(map_name);
 
         for sector in &mut self.sectors {
             set_sector_sound_origin(sector);
@@ -258,6 +289,28 @@ impl MapData {
         info!("{}: Loaded {} sidedefs", map_name, self.sidedefs.len());
     }
 
+    /// BOOM linedef 242 uses the texture names of its front sidedef to name
+    /// colourmaps for the control sector
+    fn load_boom_colourmaps(&mut self, map_name: &str, wad: &WadData, pic_data: &PicData) {
+        if !self.linedefs.iter().any(|l| l.special == 242) {
+            return;
+        }
+        let sides: Vec<_> = wad.sidedef_iter(map_name).collect();
+        for line in self.linedefs.iter_mut().filter(|l| l.special == 242) {
+            let side = &sides[line.sides[0] as usize];
+            let mut sector = line.front_sidedef.sector.clone();
+            if let Some(num) = pic_data.colourmap_num_for_name(&side.upper_tex) {
+                sector.topmap = num;
+            }
+            if let Some(num) = pic_data.colourmap_num_for_name(&side.middle_tex) {
+                sector.midmap = num;
+            }
+            if let Some(num) = pic_data.colourmap_num_for_name(&side.lower_tex) {
+                sector.bottommap = num;
+            }
+        }
+    }
+
     fn load_linedefs(&mut self, map_name: &str, wad: &WadData) {
         if self.vertexes.is_empty() {
             panic!("Vertexes must be loaded before linedefs");
@@ -317,6 +370,7 @@ impl MapData {
                     frontsector: front.sector.clone(),
                     backsector: back_sector,
                     valid_count: 0,
+                    translucent: false,
                     sides: l.sides,
                 }
             })
@@ -530,6 +584,19 @@ impl MapData {
         MapPtr::new(&mut self.subsectors[(node_id ^ IS_SSECTOR_MASK) as usize])
     }
 
+    /// `point_in_subsector_raw()` for a fixed-point position, deciding the
+    /// side of each node as the original engine did
+    ///
+    /// Doom function name  `R_PointInSubsector`
+    pub(crate) fn point_in_subsector_fixed(&mut self, x: Fixed, y: Fixed) -> MapPtr<SubSector> {
+        let mut node_id = self.start_node();
+        while node_id & IS_SSECTOR_MASK == 0 {
+            let node = &self.nodes[node_id as usize];
+            node_id = node.children[node.point_on_side_fixed(x, y)];
+        }
+        MapPtr::new(&mut self.subsectors[(node_id ^ IS_SSECTOR_MASK) as usize])
+    }
+
     pub fn point_in_subsector(&mut self, point: Vec3) -> &SubSector {
         let mut node_id = self.start_node();
         let mut node;
@@ -29,6 +29,23 @@ pub struct Sector {
     pub special: i16,
     pub tag: i16,
 
+    /// BOOM flat offsets, changed by floor and ceiling scrollers
+    pub floor_xoffs: f32,
+    pub floor_yoffs: f32,
+    pub ceil_xoffs: f32,
+    pub ceil_yoffs: f32,
+    /// BOOM linedef 242: the control sector supplying the fake floor and
+    /// ceiling heights that are drawn instead of this sector's own
+    pub heightsec: Option<MapPtr<Sector>>,
+    /// BOOM colourmaps used when the view is below, between, or above the fake
+    /// planes of a control sector. `0` is the normal `COLORMAP`
+    pub bottommap: usize,
+    pub midmap: usize,
+    pub topmap: usize,
+    /// MBF linedef 271/272: the line whose front upper texture is drawn as
+    /// this sector's sky
+    pub sky_line: Option<MapPtr<LineDef>>,
+
     /// 0 = untraversed, 1,2 = sndlines -1
     pub soundtraversed: i32,
 
@@ -86,6 +103,29 @@ impl Sector {
         }
     }
 
+    /// The BOOM colourmap to draw the view with while the viewer is in this
+    /// sector. Sectors with a deep water control sector use the map for the
+    /// side of the fake planes `viewz` is on, otherwise it is `0`, the default
+    /// `COLORMAP`.
+    pub fn colourmap_for_view(&self, viewz: f32) -> usize {
+        self.heightsec
+            .as_ref()
+            .map_or(0, |s| s.control_colourmap(viewz))
+    }
+
+    /// The colourmap of this deep water control sector for the side of its
+    /// planes `viewz` is on. At the water surface the view is underwater, as
+    /// `R_FakeFlat` decides.
+    pub fn control_colourmap(&self, viewz: f32) -> usize {
+        if viewz <= self.floorheight {
+            self.bottommap
+        } else if viewz >= self.ceilingheight {
+            self.topmap
+        } else {
+            self.midmap
+        }
+    }
+
     /// Returns false if `func` returns false
     pub fn run_mut_func_on_thinglist(
         &mut self,
@@ -219,6 +259,14 @@ impl Sector {
     pub fn set_sound_target(&mut self, target: *mut Thinker) {
         self.sound_target = Some(target);
     }
+
+    /// Drop every link to a `Thinker`, for when all thinkers are replaced such
+    /// as by loading a saved game
+    pub(crate) fn clear_thinker_links(&mut self) {
+        self.thinglist = None;
+        self.specialdata = None;
+        self.sound_target = None;
+    }
 }
 
 #[derive(Debug)]
@@ -304,6 +352,8 @@ pub struct LineDef {
 
     // if == validcount, already checked
     pub valid_count: usize,
+    /// BOOM linedef 260, the middle texture is drawn translucent
+    pub translucent: bool,
     // thinker_t for reversable actions
     // TODO: void*	specialdata: Option<MapPtr<Thinker>>,
 }
@@ -4,6 +4,7 @@
 //! Some of the state is mirrored from the overall game-exe state, or ref by
 //! pointer.
 
+pub mod blockmap;
 pub mod flags;
 pub mod map_data;
 pub mod map_defs;
@@ -13,9 +14,9 @@ use std::collections::VecDeque;
 use std::ptr;
 
 use log::info;
-use sound_sdl2::SndServerTx;
-use sound_traits::{SfxName, SoundAction};
+use sound_traits::{SfxName, SndServerTx, SoundAction};
 use wad::types::WadThing;
+use wad::umapinfo::{BossAction, MapInfo};
 use wad::WadData;
 
 use crate::doom_def::{GameAction, GameMode, MAXPLAYERS, MAX_DEATHMATCH_STARTS, MAX_RESPAWNS};
@@ -23,6 +24,7 @@ use crate::env::platforms::{PlatStatus, Platform};
 use crate::level::map_data::MapData;
 use crate::pic::Button;
 use crate::thinker::ThinkerAlloc;
+use crate::utilities::Random;
 use crate::{GameOptions, MapPtr, PicAnimation, PicData, Player, Switches};
 
 use self::map_defs::LineDef;
@@ -44,6 +46,12 @@ pub struct Level {
     pub level_timer: bool,
     /// Time spent in level
     pub level_time: u32,
+    /// The game's tic count, set by the game before the thinkers run. Only the
+    /// fixed-point revenant tracers go by it.
+    pub game_tic: u32,
+    /// Flipped each time a boss brain spits on the easy skills, so only every
+    /// other spit makes a cube
+    pub(super) brain_spit_easy: bool,
 
     /// The `Things` for player start locations
     pub player_starts: [Option<WadThing>; MAXPLAYERS],
@@ -62,6 +70,8 @@ pub struct Level {
     pub secret_exit: bool,
 
     pub game_action: Option<GameAction>,
+    /// The game's random number generator while this level is loaded
+    pub random: Random,
 
     /// Pre-composed textures, shared to the renderer. `doom-lib` owns and uses
     /// access to change animations + translation tables.
@@ -82,6 +92,9 @@ pub struct Level {
 
     /// Marker count for lines checked
     pub(super) valid_count: usize,
+    /// Special lines touched by the last position check of the fixed-point
+    /// mode, used up in reverse as the original did
+    pub(crate) spechit: Vec<MapPtr<LineDef>>,
     /// List of used buttons. Typically these buttons or switches are timed.
     pub(super) button_list: Vec<Button>,
     pub(super) line_special_list: Vec<MapPtr<LineDef>>,
@@ -90,6 +103,13 @@ pub struct Level {
 
     active_platforms: Vec<*mut Platform>,
     pub(crate) sky_num: usize,
+    /// Boss death actions from `UMAPINFO`. These replace the default actions
+    /// of `A_BossDeath` if set, and disable them if empty.
+    pub(crate) boss_actions: Option<Vec<BossAction>>,
+    /// Lines created for boss actions. These are boxed so they don't move, as
+    /// switches and specials may point to them.
+    #[allow(clippy::vec_box)]
+    pub(crate) boss_lines: Vec<Box<LineDef>>,
 }
 
 impl Level {
@@ -111,6 +131,7 @@ impl Level {
         snd_command: SndServerTx,
         players_in_game: &[bool; MAXPLAYERS],
         players: &mut [Player; MAXPLAYERS],
+        random: Random,
     ) -> Self {
         let map_data = MapData::default();
 
@@ -123,6 +144,8 @@ impl Level {
             options,
             respawn_queue: VecDeque::with_capacity(MAX_RESPAWNS),
             level_time: 0,
+            game_tic: 0,
+            brain_spit_easy: false,
             level_timer: false,
             player_starts: [None; MAXPLAYERS],
             deathmatch_starts: [None; MAX_DEATHMATCH_STARTS],
@@ -131,8 +154,10 @@ impl Level {
             total_level_items: 0,
             total_level_secrets: 0,
             game_action: None,
+            random,
             secret_exit: false,
             valid_count: 0,
+            spechit: Vec::new(),
             switch_list: Default::default(),
             animations: Default::default(),
             button_list: Vec::with_capacity(50),
@@ -143,6 +168,8 @@ impl Level {
             players,
             active_platforms: Vec::new(),
             sky_num: 0,
+            boss_actions: None,
+            boss_lines: Vec::new(),
         }
     }
 
@@ -186,6 +213,20 @@ impl Level {
         }
     }
 
+    /// Replace the thinkers with an empty `ThinkerAlloc` of `capacity`, and drop
+    /// everything that points in to the old one.
+    ///
+    /// # Safety
+    /// No `Player` may keep a pointer to a `MapObject` of the old thinkers.
+    pub(super) unsafe fn clear_thinkers(&mut self, capacity: usize) {
+        for sector in self.map_data.sectors_mut() {
+            sector.clear_thinker_links();
+        }
+        self.map_data.blockmap.clear_links();
+        self.active_platforms.clear();
+        self.thinkers = ThinkerAlloc::new(capacity);
+    }
+
     pub(super) fn players_in_game(&self) -> &[bool; MAXPLAYERS] {
         unsafe { &*self.players_in_game }
     }
@@ -198,18 +239,32 @@ impl Level {
         unsafe { &mut *self.players }
     }
 
+    /// The number of `player` in the players array
+    pub(super) fn player_num(&self, player: &Player) -> Option<usize> {
+        self.players().iter().position(|p| ptr::eq(p, player))
+    }
+
+    /// Load the map data. `map_info` is the `UMAPINFO` entry for the map if
+    /// there is one.
     pub fn load(
         &mut self,
         map_name: &str,
         game_mode: GameMode,
+        map_info: Option<&MapInfo>,
         pic_data: &mut PicData,
         wad_data: &WadData,
     ) {
         let animations = PicAnimation::init(pic_data);
         let switch_list = Switches::init(self.game_mode, pic_data);
 
-        pic_data.set_sky_pic(game_mode, self.options.episode, self.options.map);
+        pic_data.set_sky_pic(
+            game_mode,
+            self.options.episode,
+            self.options.map,
+            map_info.and_then(|i| i.sky_texture.as_deref()),
+        );
         self.sky_num = pic_data.sky_num();
+        self.boss_actions = map_info.and_then(|i| i.boss_actions.clone());
 
         self.map_data.load(map_name, pic_data, wad_data);
         self.animations = animations;
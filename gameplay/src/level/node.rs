@@ -1,3 +1,4 @@
+use crate::fixed::{fixed_mul, float_to_fixed, Fixed, FRACBITS};
 use crate::level::map_defs::Node;
 
 // use crate::play::utilities::ray_to_line_intersect;
@@ -18,6 +19,44 @@ impl Node {
         1
     }
 
+    /// `point_on_side()` for a fixed-point position, rounding as the original
+    /// engine did
+    ///
+    /// Doom function name `R_PointOnSide`
+    pub(crate) fn point_on_side_fixed(&self, x: Fixed, y: Fixed) -> usize {
+        let (nx, ny) = (float_to_fixed(self.xyz.x), float_to_fixed(self.xyz.y));
+        let (ndx, ndy) = (float_to_fixed(self.delta.x), float_to_fixed(self.delta.y));
+        if ndx == 0 {
+            return if x <= nx {
+                (ndy > 0) as usize
+            } else {
+                (ndy < 0) as usize
+            };
+        }
+        if ndy == 0 {
+            return if y <= ny {
+                (ndx < 0) as usize
+            } else {
+                (ndx > 0) as usize
+            };
+        }
+
+        let dx = x.wrapping_sub(nx);
+        let dy = y.wrapping_sub(ny);
+        // Try to quickly decide by looking at sign bits
+        if (ndy ^ ndx ^ dx ^ dy) < 0 {
+            // left is negative
+            return ((ndy ^ dx) < 0) as usize;
+        }
+
+        let left = fixed_mul(ndy >> FRACBITS, dx);
+        let right = fixed_mul(dy, ndx >> FRACBITS);
+        if right < left {
+            return 0; // front side
+        }
+        1 // back side
+    }
+
     pub fn point_in_bounds(&self, v: Vec3, side: usize) -> bool {
         if v.x > self.bboxes[side][0].x
             && v.x < self.bboxes[side][1].x
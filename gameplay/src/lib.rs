@@ -18,8 +18,10 @@ use std::panic;
 use std::ptr::null_mut;
 
 mod angle;
+pub mod bot;
 mod doom_def;
 pub(crate) mod env;
+mod fixed;
 #[rustfmt::skip]
 mod info;
 mod lang;
@@ -27,6 +29,7 @@ mod level;
 mod pic;
 mod player;
 mod player_sprite;
+pub mod save;
 mod thing;
 mod thinker;
 pub mod tic_cmd;
@@ -38,7 +41,7 @@ pub use doom_def::{
 };
 pub use env::specials::{respawn_specials, spawn_specials, update_specials};
 pub use env::teleport::teleport_move;
-pub use info::MapObjKind;
+pub use info::{MapObjInfo, MapObjKind, StateNum, MOBJINFO, SPRNAMES, STATES};
 pub use lang::english;
 pub use level::flags::LineDefFlags;
 pub use level::map_data::MapData;
@@ -50,7 +53,7 @@ pub use player_sprite::PspDef;
 use std::error::Error;
 use std::str::FromStr;
 pub use thing::{MapObjFlag, MapObject};
-pub use utilities::{m_clear_random, m_random, p_random, point_to_angle_2};
+pub use utilities::{point_to_angle_2, Random};
 
 // re-export
 pub use {glam, log};
@@ -79,7 +82,13 @@ pub struct GameOptions {
     pub respawn_parm: bool,
     pub fast_parm: bool,
     pub dev_parm: bool,
+    /// 0 for co-op or single player, 1 for deathmatch (weapons stay), 2 for
+    /// altdeath (items respawn)
     pub deathmatch: u8,
+    /// Deathmatch levels end after this many minutes
+    pub time_limit: Option<u32>,
+    /// Deathmatch levels end when a player has this many frags
+    pub frag_limit: Option<i32>,
     pub warp: bool,
     pub skill: Skill,
     pub episode: usize,
@@ -91,6 +100,18 @@ pub struct GameOptions {
     pub enable_demos: bool,
     /// only true if packets are broadcast
     pub netgame: bool,
+    /// Player slots taken by computer controlled players
+    pub bots: usize,
+    /// How well the bots play
+    pub bot_skill: Skill,
+    /// Move things with vanilla 16.16 fixed-point math and BAM angles for demo
+    /// compatibility
+    pub fixed_point: bool,
+    /// Draw things flagged `Translucent` through the TRANMAP, as BOOM's
+    /// `general_translucency`. Off by default so the IWADs look as vanilla
+    pub translucency: bool,
+    /// Free-look, vertical aiming, jumping and crouching
+    pub controls: ModernControls,
 }
 
 impl Default for GameOptions {
@@ -103,6 +124,8 @@ impl Default for GameOptions {
             fast_parm: Default::default(),
             dev_parm: Default::default(),
             deathmatch: Default::default(),
+            time_limit: None,
+            frag_limit: None,
             skill: Default::default(),
             episode: Default::default(),
             map: Default::default(),
@@ -113,10 +136,47 @@ impl Default for GameOptions {
             verbose: log::LevelFilter::Info,
             enable_demos: false,
             netgame: false,
+            bots: 0,
+            bot_skill: Skill::default(),
+            fixed_point: false,
+            translucency: false,
+            controls: ModernControls::VANILLA,
         }
     }
 }
 
+/// Controls vanilla Doom doesn't have. Demos are always played and recorded
+/// with `VANILLA` as they can't store them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModernControls {
+    /// Look up and down with the mouse. The software renderer shears the view
+    pub free_look: bool,
+    /// Shots lock on to things above or below the player. Only turned off
+    /// with free-look, where they go where the player looks instead
+    pub autoaim: bool,
+    /// The jump and crouch buttons work
+    pub jump_crouch: bool,
+}
+
+impl ModernControls {
+    pub const VANILLA: Self = Self {
+        free_look: false,
+        autoaim: true,
+        jump_crouch: false,
+    };
+
+    /// Players shoot where they look
+    pub const fn free_aim(&self) -> bool {
+        self.free_look && !self.autoaim
+    }
+}
+
+impl Default for ModernControls {
+    fn default() -> Self {
+        Self::VANILLA
+    }
+}
+
 #[repr(i32)]
 #[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
 pub enum Skill {
@@ -13,6 +13,7 @@ pub use switches::*;
 mod sprites;
 
 use std::mem::{size_of, size_of_val};
+use std::path::Path;
 
 use log::{debug, warn};
 use wad::types::{WadColour, WadPalette, WadPatch, WadTexture};
@@ -59,13 +60,24 @@ pub struct SpritePic {
 type Colourmap = [usize; 256];
 const PALLETE_LEN: usize = 14;
 const COLOURMAP_LEN: usize = 34;
+/// Percentage of the foreground colour used when generating a `TRANMAP`
+const TRAN_FILTER_PCT: u32 = 66;
 
 #[derive(Debug)]
 pub struct PicData {
     /// Colours for pixels
     palettes: [WadPalette; PALLETE_LEN],
-    // Usually 34 blocks of 256, each u8 being an index in to the palette
-    colourmap: [Colourmap; COLOURMAP_LEN],
+    /// Usually 34 blocks of 256, each u8 being an index in to the palette. The
+    /// first set is `COLORMAP`, any others are BOOM colourmaps from between
+    /// `C_START` and `C_END`
+    colourmaps: Vec<[Colourmap; COLOURMAP_LEN]>,
+    /// Lump names of `colourmaps`, used to match sidedef texture names
+    colourmap_names: Vec<String>,
+    /// The colourmap set currently used for drawing
+    use_colourmap: usize,
+    /// BOOM translucency table of 256x256, indexed by `(background << 8) +
+    /// foreground`. Loaded from `TRANMAP` or generated from the palette.
+    tranmap: Vec<u8>,
     // 16 groups of 48 sets of indexes to colourmap
     light_scale: [[usize; 48]; 16],
     // 16 groups of 128 sets of palette
@@ -95,7 +107,10 @@ impl Default for PicData {
     fn default() -> Self {
         Self {
             palettes: Default::default(),
-            colourmap: [[0; 256]; COLOURMAP_LEN],
+            colourmaps: vec![[[0; 256]; COLOURMAP_LEN]],
+            colourmap_names: vec!["COLORMAP".to_string()],
+            use_colourmap: 0,
+            tranmap: vec![0; 256 * 256],
             light_scale: [[0; 48]; 16],
             zlight_scale: [[0usize; 128]; 16],
             use_fixed_colourmap: Default::default(),
@@ -114,11 +129,14 @@ impl Default for PicData {
 }
 
 impl PicData {
-    pub fn init(double_res: bool, wad: &WadData) -> Self {
+    /// A `TRANMAP` generated from the palette is cached in `cache_dir` if
+    /// given, to save generating it again on the next start.
+    pub fn init(double_res: bool, wad: &WadData, cache_dir: Option<&Path>) -> Self {
         print!("Init image data  [");
 
-        let colourmap = Self::init_colourmap(wad);
+        let (colourmaps, colourmap_names) = Self::init_colourmaps(wad);
         let palettes = Self::init_palette(wad);
+        let tranmap = Self::init_tranmap(wad, &palettes[0], cache_dir);
         let light_scale = Self::init_light_scales();
         let zlight_scale = Self::init_zlight_scales();
 
@@ -176,7 +194,10 @@ impl PicData {
             palettes,
             light_scale,
             zlight_scale,
-            colourmap,
+            colourmaps,
+            colourmap_names,
+            use_colourmap: 0,
+            tranmap,
             use_fixed_colourmap: 0,
             sprite_patches,
             sprite_defs,
@@ -194,24 +215,118 @@ impl PicData {
         tmp
     }
 
-    fn init_colourmap(wad: &WadData) -> [Colourmap; COLOURMAP_LEN] {
+    /// Use the `TRANMAP` lump if the wad has one, otherwise the table cached
+    /// for this palette, or generate the table by blending each pair of
+    /// colours and finding the closest palette entry.
+    ///
+    /// Doom function name `R_InitTranMap`
+    fn init_tranmap(wad: &WadData, palette: &WadPalette, cache_dir: Option<&Path>) -> Vec<u8> {
         print!(".");
+        if let Some(lump) = wad.get_lump("TRANMAP") {
+            if lump.data.len() >= 256 * 256 {
+                debug!("Using TRANMAP lump");
+                return lump.data[..256 * 256].to_vec();
+            }
+            warn!("TRANMAP lump is too short, generating table instead");
+        }
+
+        let cache = cache_dir
+            .map(|dir| dir.join(format!("tranmap-{:016x}.dat", Self::palette_hash(palette))));
+        if let Some(data) = cache.as_ref().and_then(|path| std::fs::read(path).ok()) {
+            if data.len() == 256 * 256 {
+                debug!("Using cached TRANMAP");
+                return data;
+            }
+            warn!("Cached TRANMAP is the wrong size, generating table instead");
+        }
+
+        let tranmap = Self::generate_tranmap(palette);
+        if let Some(path) = cache {
+            if let Err(e) = std::fs::write(&path, &tranmap) {
+                warn!("Couldn't cache TRANMAP to {path:?}: {e}");
+            }
+        }
+        tranmap
+    }
+
+    /// FNV-1a of the palette and filter percentage, naming the cached table so
+    /// that a different palette doesn't use it
+    fn palette_hash(palette: &WadPalette) -> u64 {
+        palette
+            .0
+            .iter()
+            .flat_map(|c| [c.0[0], c.0[1], c.0[2]])
+            .chain([TRAN_FILTER_PCT as u8])
+            .fold(0xcbf29ce484222325, |hash, b| {
+                (hash ^ b as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    fn generate_tranmap(palette: &WadPalette) -> Vec<u8> {
+        let pal = &palette.0;
+        let mut tranmap = vec![0; 256 * 256];
+        for (bg, row) in tranmap.chunks_exact_mut(256).enumerate() {
+            let [r1, g1, b1, _] = pal[bg].0;
+            let bg = [r1, g1, b1].map(|c| c as u32 * (100 - TRAN_FILTER_PCT));
+            for (fg, out) in row.iter_mut().enumerate() {
+                let fg = pal[fg].0;
+                let blend: [i32; 3] = std::array::from_fn(|i| {
+                    ((fg[i] as u32 * TRAN_FILTER_PCT + bg[i]) / 100) as i32
+                });
+
+                let mut best = u32::MAX;
+                for (i, c) in pal.iter().enumerate() {
+                    let dr = c.0[0] as i32 - blend[0];
+                    let dg = c.0[1] as i32 - blend[1];
+                    let db = c.0[2] as i32 - blend[2];
+                    let dist = (dr * dr + dg * dg + db * db) as u32;
+                    if dist < best {
+                        best = dist;
+                        *out = i as u8;
+                        if dist == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        tranmap
+    }
+
+    fn init_colourmap(data: impl Iterator<Item = u8>) -> [Colourmap; COLOURMAP_LEN] {
         let mut tmp = [[0; 256]; COLOURMAP_LEN];
-        let maps: Vec<Colourmap> = wad
-            .colourmap_iter()
+        let maps: Vec<Colourmap> = data
             .map(|i| i as usize)
             .collect::<Vec<usize>>()
             .chunks(256)
+            .filter(|v| v.len() == 256)
             .map(|v| {
                 let mut tmp: Colourmap = [0; 256];
                 tmp.copy_from_slice(v);
                 tmp
             })
             .collect();
-        tmp.copy_from_slice(&maps);
+        let len = maps.len().min(COLOURMAP_LEN);
+        tmp[..len].copy_from_slice(&maps[..len]);
         tmp
     }
 
+    /// Load `COLORMAP` followed by any BOOM colourmaps in `C_START`/`C_END`
+    fn init_colourmaps(wad: &WadData) -> (Vec<[Colourmap; COLOURMAP_LEN]>, Vec<String>) {
+        print!(".");
+        let mut maps = vec![Self::init_colourmap(wad.colourmap_iter())];
+        let mut names = vec!["COLORMAP".to_string()];
+        for lump in wad.colourmap_lumps_iter() {
+            if names.contains(&lump.name) {
+                continue;
+            }
+            debug!("Loading colourmap {}", lump.name);
+            maps.push(Self::init_colourmap(lump.data.iter().copied()));
+            names.push(lump.name.clone());
+        }
+        (maps, names)
+    }
+
     /// Populate the indexes to colourmaps
     fn init_light_scales() -> [[usize; 48]; 16] {
         print!(".");
@@ -436,6 +551,12 @@ impl PicData {
         &self.palettes[self.use_pallette].0
     }
 
+    /// The BOOM translucency table, indexed by `(background << 8) +
+    /// foreground`
+    pub fn tranmap(&self) -> &[u8] {
+        &self.tranmap
+    }
+
     pub fn set_palette(&mut self, mut num: usize) {
         if num >= self.palettes.len() {
             num = self.palettes.len() - 1;
@@ -493,8 +614,22 @@ impl PicData {
         self.sky_pic
     }
 
-    /// Set the correct skybox for the map/episode currently playing
-    pub fn set_sky_pic(&mut self, mode: GameMode, episode: usize, map: usize) {
+    /// Set the correct skybox for the map/episode currently playing.
+    /// `sky_texture` overrides the default sky, e.g, from `UMAPINFO`.
+    pub fn set_sky_pic(
+        &mut self,
+        mode: GameMode,
+        episode: usize,
+        map: usize,
+        sky_texture: Option<&str>,
+    ) {
+        if let Some(name) = sky_texture {
+            if let Some(num) = self.wallpic_num_for_name(name) {
+                self.sky_pic = num;
+                return;
+            }
+            warn!("Sky texture {name} is missing");
+        }
         if mode == GameMode::Commercial {
             self.sky_pic = self.wallpic_num_for_name("SKY3").expect("SKY3 is missing");
             if map < 12 {
@@ -521,7 +656,21 @@ impl PicData {
     }
 
     pub fn colourmap(&self, index: usize) -> &[usize] {
-        &self.colourmap[index]
+        &self.colourmaps[self.use_colourmap][index]
+    }
+
+    /// Find a BOOM colourmap lump by name. `0` is always the default
+    /// `COLORMAP`.
+    pub fn colourmap_num_for_name(&self, name: &str) -> Option<usize> {
+        let name = name.to_ascii_uppercase();
+        self.colourmap_names.iter().position(|n| *n == name)
+    }
+
+    /// Set which colourmap set is used for unlit drawing such as the sky,
+    /// shadows and fixed colourmaps, e.g, the underwater colourmap of a deep
+    /// water sector the view is in.
+    pub fn set_view_colourmap(&mut self, num: usize) {
+        self.use_colourmap = if num < self.colourmaps.len() { num } else { 0 };
     }
 
     fn colourmap_for_scale(&self, scale: f32) -> usize {
@@ -536,11 +685,22 @@ impl PicData {
         colourmap as usize
     }
 
+    #[inline]
+    fn colourmap_set(&self, num: usize) -> &[Colourmap; COLOURMAP_LEN] {
+        self.colourmaps.get(num).unwrap_or(&self.colourmaps[0])
+    }
+
     /// Get the correct colourmapping for a light level. The colourmap is
-    /// indexed by the Y coordinate of a texture column.
-    pub fn vert_light_colourmap(&self, light_level: usize, wall_scale: f32) -> &[usize] {
+    /// indexed by the Y coordinate of a texture column. `colourmap_set` is
+    /// the sector's BOOM colourmap, `0` for the default `COLORMAP`.
+    pub fn vert_light_colourmap(
+        &self,
+        light_level: usize,
+        wall_scale: f32,
+        colourmap_set: usize,
+    ) -> &[usize] {
         if self.use_fixed_colourmap != 0 {
-            return &self.colourmap[self.use_fixed_colourmap];
+            return &self.colourmaps[self.use_colourmap][self.use_fixed_colourmap];
         }
 
         let mut light_level = light_level;
@@ -556,21 +716,31 @@ impl PicData {
                 .light_scale
                 .get_unchecked(light_level)
                 .get_unchecked(colourmap);
-            self.colourmap.get_unchecked(*i)
+            self.colourmap_set(colourmap_set).get_unchecked(*i)
         }
         #[cfg(feature = "safety_check")]
-        &self.light_scale[light_level][colourmap]
+        &self.colourmap_set(colourmap_set)[self.light_scale[light_level][colourmap]]
     }
 
+    /// Get the colourmap for a flat span at distance `scale`. `colourmap_set`
+    /// is the sector's BOOM colourmap, `0` for the default `COLORMAP`.
     #[inline(always)]
-    pub fn flat_light_colourmap(&self, mut light_level: usize, scale: usize) -> &[usize] {
+    pub fn flat_light_colourmap(
+        &self,
+        mut light_level: usize,
+        scale: usize,
+        colourmap_set: usize,
+    ) -> &[usize] {
         if self.use_fixed_colourmap != 0 {
             #[cfg(not(feature = "safety_check"))]
             unsafe {
-                return self.colourmap.get_unchecked(self.use_fixed_colourmap);
+                return self
+                    .colourmaps
+                    .get_unchecked(self.use_colourmap)
+                    .get_unchecked(self.use_fixed_colourmap);
             }
             #[cfg(feature = "safety_check")]
-            return &self.colourmap[self.use_fixed_colourmap];
+            return &self.colourmaps[self.use_colourmap][self.use_fixed_colourmap];
         }
 
         let mut dist = scale >> 4;
@@ -589,10 +759,10 @@ impl PicData {
                 .zlight_scale
                 .get_unchecked(light_level)
                 .get_unchecked(dist);
-            self.colourmap.get_unchecked(*i)
+            self.colourmap_set(colourmap_set).get_unchecked(*i)
         }
         #[cfg(feature = "safety_check")]
-        &self.zlight_scale[light_level][dist]
+        &self.colourmap_set(colourmap_set)[self.zlight_scale[light_level][dist]]
     }
 
     pub fn get_texture(&self, num: usize) -> &WallPic {
@@ -691,3 +861,58 @@ impl PicData {
         &self.sprite_patches[patch_num]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wad::types::{WadColour, WadPalette};
+
+    use super::{PicData, COLOURMAP_LEN};
+
+    /// Black, white, and the greys a 66% blend of one over the other gives
+    fn greys() -> WadPalette {
+        let mut palette = WadPalette::new();
+        palette.0[0] = WadColour::new(0, 0, 0);
+        palette.0[1] = WadColour::new(255, 255, 255);
+        palette.0[2] = WadColour::new(168, 168, 168);
+        palette.0[3] = WadColour::new(85, 85, 85);
+        palette.0[4] = WadColour::new(8, 8, 8);
+        palette
+    }
+
+    fn tran(tranmap: &[u8], bg: usize, fg: usize) -> u8 {
+        tranmap[(bg << 8) + fg]
+    }
+
+    #[test]
+    fn generated_tranmap() {
+        let tranmap = PicData::generate_tranmap(&greys());
+        assert_eq!(tranmap.len(), 256 * 256);
+        // white over black: 255 * 66 / 100
+        assert_eq!(tran(&tranmap, 0, 1), 2);
+        // black over white: 255 * 34 / 100 is closest to 85
+        assert_eq!(tran(&tranmap, 1, 0), 3);
+        // a colour over itself doesn't change
+        for i in 0..5 {
+            assert_eq!(tran(&tranmap, i, i), i as u8);
+        }
+    }
+
+    #[test]
+    fn colourmap_by_name() {
+        let mut pic_data = PicData::default();
+        for name in ["WATERMAP", "LAVAMAP"] {
+            pic_data.colourmaps.push([[0; 256]; COLOURMAP_LEN]);
+            pic_data.colourmap_names.push(name.to_string());
+        }
+        assert_eq!(pic_data.colourmap_num_for_name("COLORMAP"), Some(0));
+        // sidedef texture names may be any case
+        assert_eq!(pic_data.colourmap_num_for_name("watermap"), Some(1));
+        assert_eq!(pic_data.colourmap_num_for_name("LAVAMAP"), Some(2));
+        assert_eq!(pic_data.colourmap_num_for_name("STARTAN3"), None);
+
+        pic_data.set_view_colourmap(2);
+        assert_eq!(pic_data.use_colourmap, 2);
+        pic_data.set_view_colourmap(3);
+        assert_eq!(pic_data.use_colourmap, 0);
+    }
+}
@@ -4,7 +4,7 @@ use crate::doom_def::GameMode;
 use crate::level::map_defs::LineDef;
 use crate::{MapPtr, PicData};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum ButtonWhere {
     Top,
     Middle,
@@ -1,4 +1,4 @@
-use std::f32::consts::FRAC_PI_2;
+use std::f32::consts::{FRAC_PI_2, PI};
 
 use glam::Vec3;
 use log::{debug, error, info};
@@ -14,13 +14,20 @@ use crate::pic::INVERSECOLORMAP;
 use crate::player_sprite::{PspDef, WEAPONBOTTOM};
 use crate::thing::enemy_behaviour::noise_alert;
 use crate::thing::{MapObjFlag, MapObject, BONUSADD};
-use crate::tic_cmd::{TicCmd, TIC_CMD_BUTTONS};
-use crate::utilities::{bam_to_radian, fixed_to_float, p_random, point_to_angle_2};
+use crate::tic_cmd::{TicCmd, ACTION_CROUCH, ACTION_JUMP, TIC_CMD_BUTTONS};
+use crate::utilities::{bam_to_radian, fixed_to_float, point_to_angle_2};
 use crate::{GameMode, Skill};
 
 /// 16 pixels of bob
 const MAX_BOB: f32 = 16.0; // 0x100000;
 const ANG5: f32 = 0.08726646; //5f32.to_radians();
+/// Furthest the view can pitch up or down, about 31.5 degrees. The software
+/// renderer can't shear the view any further
+const MAX_PITCH: f32 = 0.55;
+/// Upward momentum of a jump
+const JUMP_SPEED: f32 = 8.0;
+/// How much further to crouch or stand up each tic
+const CROUCH_SPEED: f32 = 0.125;
 
 /// Overlay psprites are scaled shapes
 /// drawn directly on the view screen,
@@ -34,7 +41,7 @@ pub enum PsprNum {
 }
 
 /// Player states.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlayerState {
     /// Playing or camping.
     Live,
@@ -141,7 +148,7 @@ impl Default for PlayerStatus {
 
 /// player_t
 pub struct Player {
-    mobj: Option<*mut MapObject>,
+    pub(crate) mobj: Option<*mut MapObject>,
     pub player_state: PlayerState,
     pub cmd: TicCmd,
 
@@ -156,6 +163,11 @@ pub struct Player {
     /// bounded/scaled total momentum.
     pub(crate) bob: f32,
     pub(crate) onground: bool,
+    /// View pitch in radians, positive looks up. Only changed by free-look
+    pub pitch: f32,
+    /// How far down the player is crouched, 0.0 standing to 1.0 crouched.
+    /// Crouching halves the view and body height
+    pub crouch: f32,
 
     pub status: PlayerStatus,
 
@@ -218,6 +230,8 @@ impl Player {
             deltaviewheight: 1.0,
             bob: 1.0,
             onground: true,
+            pitch: 0.0,
+            crouch: 0.0,
             status: PlayerStatus::default(),
             refire: 0,
 
@@ -269,10 +283,40 @@ impl Player {
         self.mobj
     }
 
+    /// Get the next gameplay random number from the level the player is in.
+    /// Weapons only act while the player has a `MapObject`, without one this
+    /// is always 0.
+    pub(crate) fn p_random(&mut self) -> i32 {
+        self.mobj_mut().map_or(0, |mobj| mobj.p_random())
+    }
+
     pub fn set_mobj(&mut self, mobj: *mut MapObject) {
         self.mobj = Some(mobj);
     }
 
+    /// Forget the `MapObject`, e.g, when the level it was in is gone
+    pub fn clear_mobj(&mut self) {
+        self.mobj = None;
+    }
+
+    /// Leave the `MapObject` behind as a corpse that no longer belongs to this
+    /// player. The player keeps pointing at it until respawned.
+    pub fn disassociate_corpse(&mut self) {
+        if let Some(mobj) = self.mobj_mut() {
+            mobj.player = None;
+        }
+    }
+
+    /// Frags of other players less suicides, as shown on the status bar.
+    /// `me` is this player's number.
+    pub fn frag_count(&self, me: usize) -> i32 {
+        self.frags
+            .iter()
+            .enumerate()
+            .map(|(i, f)| if i == me { -f } else { *f })
+            .sum()
+    }
+
     /// Unchecked access to the raw `MapObject` pointer cast to ref
     ///
     /// # Safety
@@ -327,8 +371,10 @@ impl Player {
         let kill_count = self.total_kills;
         let item_count = self.items_collected;
         let secret_count = self.secrets_found;
+        let frags = self.frags;
 
         *self = Player::default();
+        self.frags = frags;
         self.total_kills = kill_count;
         self.items_collected = item_count;
         self.secrets_found = secret_count;
@@ -394,15 +440,16 @@ impl Player {
 
             // move viewheight
             if self.player_state == PlayerState::Live {
+                let standing = VIEWHEIGHT * (1.0 - self.crouch / 2.0);
                 self.viewheight += self.deltaviewheight;
 
-                if self.viewheight > VIEWHEIGHT {
-                    self.viewheight = VIEWHEIGHT;
+                if self.viewheight > standing {
+                    self.viewheight = standing;
                     self.deltaviewheight = 0.0;
                 }
 
-                if self.viewheight < VIEWHEIGHT / 2.0 {
-                    self.viewheight = VIEWHEIGHT / 2.0;
+                if self.viewheight < standing / 2.0 {
+                    self.viewheight = standing / 2.0;
                     if self.deltaviewheight <= 0.0 {
                         self.deltaviewheight = 1.0;
                     }
@@ -429,22 +476,29 @@ impl Player {
         if let Some(mobj) = self.mobj {
             let mobj = unsafe { &mut *mobj };
 
-            // TODO: Fix adjustments after fixing the tic timestep
-            if self.cmd.angleturn != 0 {
-                let a = bam_to_radian((self.cmd.angleturn as u32) << 16);
-                mobj.angle += a;
-            }
+            if mobj.level().options.fixed_point {
+                self.onground = mobj.fixed_move_player(&self.cmd);
+            } else {
+                // TODO: Fix adjustments after fixing the tic timestep
+                if self.cmd.angleturn != 0 {
+                    let a = bam_to_radian((self.cmd.angleturn as u32) << 16);
+                    mobj.angle += a;
+                }
 
-            self.onground = mobj.xyz.z <= mobj.floorz;
+                self.onground = mobj.xyz.z <= mobj.floorz;
 
-            if self.cmd.forwardmove != 0 && self.onground {
-                let angle = mobj.angle;
-                self.thrust(angle, self.cmd.forwardmove as i32 * 2048);
-            }
+                if self.cmd.forwardmove != 0 && self.onground {
+                    let angle = mobj.angle;
+                    self.thrust(angle, self.cmd.forwardmove as i32 * mobj.movefactor);
+                }
 
-            if self.cmd.sidemove != 0 && self.onground {
-                let angle = mobj.angle;
-                self.thrust(angle - FRAC_PI_2, self.cmd.sidemove as i32 * 2048);
+                if self.cmd.sidemove != 0 && self.onground {
+                    let angle = mobj.angle;
+                    self.thrust(
+                        angle - FRAC_PI_2,
+                        self.cmd.sidemove as i32 * mobj.movefactor,
+                    );
+                }
             }
 
             if (self.cmd.forwardmove != 0 || self.cmd.sidemove != 0)
@@ -452,7 +506,40 @@ impl Player {
             {
                 mobj.set_state(StateNum::PLAY_RUN1);
             }
+
+            let controls = mobj.level().options.controls;
+            if controls.free_look && self.cmd.pitch != 0 {
+                let look = self.cmd.pitch as f32 * PI / 32768.0;
+                self.pitch = (self.pitch + look).clamp(-MAX_PITCH, MAX_PITCH);
+            }
+            if controls.jump_crouch {
+                self.jump_and_crouch(mobj);
+            }
+        }
+    }
+
+    /// Jump if on the ground, and crouch or stand up. Standing up waits until
+    /// there is room above
+    fn jump_and_crouch(&mut self, mobj: &mut MapObject) {
+        if self.cmd.actions & ACTION_JUMP != 0 && self.onground {
+            mobj.momxyz.z = JUMP_SPEED;
+        }
+
+        let crouch = if self.cmd.actions & ACTION_CROUCH != 0 {
+            (self.crouch + CROUCH_SPEED).min(1.0)
+        } else {
+            (self.crouch - CROUCH_SPEED).max(0.0)
+        };
+        if crouch == self.crouch {
+            return;
         }
+        let height = mobj.info.height * (1.0 - crouch / 2.0);
+        if crouch < self.crouch && mobj.xyz.z + height > mobj.ceilingz {
+            return;
+        }
+        self.viewheight -= (crouch - self.crouch) * VIEWHEIGHT / 2.0;
+        self.crouch = crouch;
+        mobj.height = height;
     }
 
     /// Called every tic by player thinking routine to update sprites and states
@@ -543,7 +630,8 @@ impl Player {
                 }
                 // SUPER HELLSLIME DAMAGE | STROBE HURT
                 16 | 4 => {
-                    if (self.status.powers[PowerType::IronFeet as usize] == 0 || p_random() < 5)
+                    if (self.status.powers[PowerType::IronFeet as usize] == 0
+                        || level.random.p_random() < 5)
                         && level.level_time & 0x1F == 0
                     {
                         debug!("Super hell-slime damage!");
@@ -647,7 +735,25 @@ impl Player {
     pub(crate) fn give_weapon(&mut self, weapon: WeaponType, dropped: bool, skill: Skill) -> bool {
         let mut gave_ammo = false;
         let mut gave_weapon = false;
-        // TODO: if (netgame && (deathmatch != 2) && !dropped) {
+        let (netgame, deathmatch) = self
+            .mobj()
+            .map(|m| (m.level().options.netgame, m.level().options.deathmatch))
+            .unwrap_or_default();
+        if netgame && deathmatch != 2 && !dropped {
+            // leave placed weapons forever on net games
+            if self.status.weaponowned[weapon as usize] {
+                return false;
+            }
+            self.status.bonuscount += BONUSADD;
+            self.status.weaponowned[weapon as usize] = true;
+            let ammo = WEAPON_INFO[weapon as usize].ammo;
+            if ammo != AmmoType::NoAmmo {
+                self.give_ammo(ammo, if deathmatch != 0 { 5 } else { 2 }, skill);
+            }
+            self.pendingweapon = weapon;
+            self.start_sound(SfxName::Wpnup);
+            return false;
+        }
         let ammo = WEAPON_INFO[weapon as usize].ammo;
         if ammo != AmmoType::NoAmmo {
             if dropped {
@@ -677,12 +783,15 @@ impl Player {
         true
     }
 
-    pub(crate) fn give_key(&mut self, card: Card) {
-        if self.status.cards[card as usize] {
-            return;
+    /// Give a key with its pickup message. Returns false if the key should
+    /// be left in the level, as keys stay for the other players in a netgame.
+    pub(crate) fn give_key(&mut self, card: Card, message: &'static str, netgame: bool) -> bool {
+        if !self.status.cards[card as usize] {
+            self.message = Some(message);
+            self.status.bonuscount += BONUSADD;
+            self.status.cards[card as usize] = true;
         }
-        self.status.bonuscount += BONUSADD;
-        self.status.cards[card as usize] = true;
+        !netgame
     }
 
     pub(crate) fn give_body(&mut self, num: i32) -> bool {
@@ -5,11 +5,12 @@ use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
 use sound_traits::SfxName;
 
 use crate::doom_def::{PowerType, MELEERANGE, MISSILERANGE, WEAPON_INFO};
+use crate::fixed::{float_to_fixed, ANG90};
 use crate::info::{State, StateNum, STATES};
 use crate::player::{Player, PsprNum};
 use crate::thing::MapObject;
 use crate::tic_cmd::TIC_CMD_BUTTONS;
-use crate::utilities::{p_random, point_to_angle_2};
+use crate::utilities::{fixed_to_float, point_to_angle_2};
 use crate::{MapObjKind, PlayerState, WeaponType};
 
 const LOWERSPEED: f32 = 6.0;
@@ -189,16 +190,13 @@ pub(crate) fn a_fireshotgun2(player: &mut Player, _pspr: &mut PspDef) {
         let bullet_slope = mobj.bullet_slope(distance, &mut bsp_trace);
 
         for _ in 0..20 {
-            let damage = 5.0 * (p_random() % 3 + 1) as f32;
-            let mut angle = mobj.angle;
-            angle += (((p_random() - p_random()) >> 5) as f32).to_radians();
-            mobj.line_attack(
-                damage,
-                MISSILERANGE,
-                angle,
-                bullet_slope.clone(),
-                &mut bsp_trace,
-            );
+            let damage = 5.0 * (mobj.p_random() % 3 + 1) as f32;
+            let angle = mobj.spread_angle(19, 5);
+            let mut slope = mobj.shot_slope(bullet_slope.clone());
+            if mobj.level().options.fixed_point {
+                slope += fixed_to_float((mobj.p_random() - mobj.p_random()) << 5);
+            }
+            mobj.shoot_line_attack(MISSILERANGE, angle, slope, damage, &mut bsp_trace);
         }
     }
 
@@ -227,8 +225,9 @@ pub(crate) fn a_firecgun(player: &mut Player, pspr: &mut PspDef) {
 
 pub(crate) fn a_fireplasma(player: &mut Player, _pspr: &mut PspDef) {
     player.subtract_readyweapon_ammo(1);
+    let random = player.p_random() as u16;
     let state = StateNum::from(
-        (WEAPON_INFO[player.status.readyweapon as usize].flashstate as u16 + p_random() as u16) & 1,
+        (WEAPON_INFO[player.status.readyweapon as usize].flashstate as u16 + random) & 1,
     );
     player.set_psprite(PsprNum::Flash as usize, state);
     if let Some(mobj) = player.mobj_raw() {
@@ -283,6 +282,10 @@ pub(crate) fn a_bfgsound(player: &mut Player, _pspr: &mut PspDef) {
 }
 
 pub(crate) fn a_bfgspray(player: &mut MapObject) {
+    if player.level().options.fixed_point {
+        fixed_bfgspray(player);
+        return;
+    }
     for i in 0..40 {
         // From left to right
         let angle = player.angle - FRAC_PI_4 + (FRAC_PI_2 / 40.0) * i as f32;
@@ -299,13 +302,42 @@ pub(crate) fn a_bfgspray(player: &mut MapObject) {
 
             let mut damage = 0;
             for _ in 0..15 {
-                damage += (p_random() & 7) + 1;
+                damage += (player.p_random() & 7) + 1;
             }
             lt.p_take_damage(Some(player), None, false, damage);
         }
     }
 }
 
+/// `a_bfgspray()` for the fixed-point mode. The player that fired the `ball`
+/// aims each ray, from where they stand now.
+fn fixed_bfgspray(ball: &mut MapObject) {
+    let Some(source) = ball.target else {
+        return;
+    };
+    let source = unsafe { (*source).mobj_mut() };
+    let angle = ball.fixed_angle();
+    let view_slope = source.view_slope();
+    for i in 0..40 {
+        // From left to right
+        let angle = angle.wrapping_sub(ANG90 / 2).wrapping_add(ANG90 / 40 * i);
+        let Some(aim) = source.fixed_aim_result(angle, 16.0 * 64.0, view_slope) else {
+            continue;
+        };
+        let mut lt = aim.line_target;
+        let (x, y, z) = lt.fixed_pos();
+        let z = z + (float_to_fixed(lt.height) >> 2);
+        let level = unsafe { &mut *ball.level };
+        MapObject::spawn_map_object_fixed(x, y, z, MapObjKind::MT_EXTRABFG, level);
+
+        let mut damage = 0;
+        for _ in 0..15 {
+            damage += (ball.p_random() & 7) + 1;
+        }
+        lt.p_take_damage(None, Some(source), true, damage);
+    }
+}
+
 pub(crate) fn a_gunflash(player: &mut Player, _pspr: &mut PspDef) {
     player.set_mobj_state(StateNum::PLAY_ATK2);
     player.set_psprite(
@@ -315,23 +347,26 @@ pub(crate) fn a_gunflash(player: &mut Player, _pspr: &mut PspDef) {
 }
 
 pub(crate) fn a_punch(player: &mut Player, _pspr: &mut PspDef) {
-    let mut damage = (p_random() % 10 + 1) as f32;
+    let mut damage = ((player.p_random() % 10 + 1) << 1) as f32;
     if player.status.powers[PowerType::Strength as usize] != 0 {
         damage *= 10.0;
     }
 
     if let Some(mobj) = player.mobj_mut() {
-        let mut angle = mobj.angle;
-        angle += (((p_random() - p_random()) >> 5) as f32).to_radians();
+        let angle = mobj.spread_angle(18, 5);
 
         let mut bsp_trace = mobj.get_shoot_bsp_trace(MELEERANGE);
-        let slope = mobj.aim_line_attack(MELEERANGE, &mut bsp_trace);
+        let slope = mobj.aim_line_attack_to(angle, MELEERANGE, &mut bsp_trace);
         mobj.line_attack(damage, MELEERANGE, angle, slope.clone(), &mut bsp_trace);
 
         if let Some(res) = slope {
             let target = res.line_target;
             mobj.start_sound(SfxName::Punch);
-            mobj.angle = point_to_angle_2(target.xyz, mobj.xyz);
+            if mobj.level().options.fixed_point {
+                mobj.fixed_face_thing(&target);
+            } else {
+                mobj.angle = point_to_angle_2(target.xyz, mobj.xyz);
+            }
         }
     }
 }
@@ -354,21 +389,20 @@ pub(crate) fn a_closeshotgun2(player: &mut Player, pspr: &mut PspDef) {
 }
 
 pub(crate) fn a_saw(player: &mut Player, _pspr: &mut PspDef) {
-    let damage = 2.0 * (p_random() % 10 + 1) as f32;
+    let damage = 2.0 * (player.p_random() % 10 + 1) as f32;
 
     if let Some(mobj) = player.mobj_mut() {
-        let mut angle = mobj.angle;
-        angle += (((p_random() - p_random()) >> 5) as f32).to_radians();
-
-        let mut bsp_trace = mobj.get_shoot_bsp_trace(MELEERANGE + 1.0);
-        let slope = mobj.aim_line_attack(MELEERANGE + 1.0, &mut bsp_trace);
-        mobj.line_attack(
-            damage,
-            MELEERANGE + 1.0,
-            angle,
-            slope.clone(),
-            &mut bsp_trace,
-        );
+        // Just past a punch, by one fixed-point step in the original
+        let distance = if mobj.level().options.fixed_point {
+            MELEERANGE + fixed_to_float(1)
+        } else {
+            MELEERANGE + 1.0
+        };
+        let angle = mobj.spread_angle(18, 5);
+
+        let mut bsp_trace = mobj.get_shoot_bsp_trace(distance);
+        let slope = mobj.aim_line_attack_to(angle, distance, &mut bsp_trace);
+        mobj.line_attack(damage, distance, angle, slope.clone(), &mut bsp_trace);
 
         if slope.is_none() {
             mobj.start_sound(SfxName::Sawful);
@@ -379,6 +413,10 @@ pub(crate) fn a_saw(player: &mut Player, _pspr: &mut PspDef) {
         mobj.start_sound(SfxName::Sawhit);
         if let Some(res) = slope {
             let target = res.line_target;
+            if mobj.level().options.fixed_point {
+                mobj.fixed_saw_turn(&target);
+                return;
+            }
             mobj.start_sound(SfxName::Punch);
             let angle = point_to_angle_2(target.xyz, mobj.xyz);
 
@@ -0,0 +1,1459 @@
+//! Saving and loading of games. The format is native to this engine and is
+//! versioned, it is not compatible with the `.dsg` files of Doom. Those can be
+//! imported with `vanilla`.
+//!
+//! A save starts with a `SaveHeader`, followed by the level: the players, the
+//! world (sectors, lines, sides), every live thinker in the order they are run,
+//! then the switch and respawn lists and the random number indexes. Pointers
+//! between `MapObject`s are written as an index in to the list of saved
+//! `MapObject`s and are linked up again once all thinkers are loaded.
+//!
+//! Doom source name `p_saveg`
+
+pub mod vanilla;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ptr::{self, null_mut};
+
+use glam::Vec3;
+use log::warn;
+use wad::types::WadThing;
+
+use crate::angle::Angle;
+use crate::doom_def::{WeaponType, MAXPLAYERS};
+use crate::env::ceiling::{CeilKind, CeilingMove};
+use crate::env::doors::{DoorKind, VerticalDoor};
+use crate::env::floor::{FloorKind, FloorMove};
+use crate::env::lights::{FireFlicker, Glow, LightFlash, StrobeFlash};
+use crate::env::platforms::{PlatKind, PlatStatus, Platform};
+use crate::env::pushers::{Friction, PushKind, Pusher};
+use crate::env::scroll::{Scroll, ScrollKind};
+use crate::info::{MapObjKind, State, STATES};
+use crate::level::map_defs::{Sector, SideDef};
+use crate::level::Level;
+use crate::pic::{Button, ButtonWhere};
+use crate::player::{Player, PlayerState};
+use crate::player_sprite::PspDef;
+use crate::thing::enemy_movement::MoveDir;
+use crate::thing::{FixedState, MapObject};
+use crate::thinker::{Think, Thinker, ThinkerData};
+use crate::{GameOptions, MapPtr, ModernControls, Skill};
+
+/// Identifies the file as a savegame of this engine
+const SAVE_MAGIC: &[u8; 8] = b"R4DSAVE\0";
+/// Bump this whenever the layout of a save changes. Saves of any other version
+/// are refused.
+pub const SAVE_VERSION: u32 = 1;
+/// Max length of the description of a save
+pub const SAVESTRINGSIZE: usize = 24;
+/// Written at the end of a save as a check that everything was read
+const SAVE_END: u8 = 0x1d;
+
+// Thinker classes
+const TC_END: u8 = 0;
+const TC_MOBJ: u8 = 1;
+const TC_DOOR: u8 = 2;
+const TC_FLOOR: u8 = 3;
+const TC_CEILING: u8 = 4;
+const TC_PLAT: u8 = 5;
+const TC_FLASH: u8 = 6;
+const TC_STROBE: u8 = 7;
+const TC_FLICKER: u8 = 8;
+const TC_GLOW: u8 = 9;
+const TC_SCROLL: u8 = 10;
+const TC_FRICTION: u8 = 11;
+const TC_PUSHER: u8 = 12;
+
+// Tables of fieldless enum variants in declaration order, to turn a saved
+// discriminant back in to the variant.
+const PLAYER_STATES: [PlayerState; 3] = [PlayerState::Live, PlayerState::Dead, PlayerState::Reborn];
+const DOOR_KINDS: [DoorKind; 8] = [
+    DoorKind::Normal,
+    DoorKind::Close30ThenOpen,
+    DoorKind::Close,
+    DoorKind::Open,
+    DoorKind::RaiseIn5Mins,
+    DoorKind::BlazeRaise,
+    DoorKind::BlazeOpen,
+    DoorKind::BlazeClose,
+];
+const FLOOR_KINDS: [FloorKind; 13] = [
+    FloorKind::LowerFloor,
+    FloorKind::LowerFloorToLowest,
+    FloorKind::TurboLower,
+    FloorKind::RaiseFloor,
+    FloorKind::RaiseFloorToNearest,
+    FloorKind::RaiseToTexture,
+    FloorKind::LowerAndChange,
+    FloorKind::RaiseFloor24,
+    FloorKind::RaiseFloor24andChange,
+    FloorKind::RaiseFloorCrush,
+    FloorKind::RaiseFloorTurbo,
+    FloorKind::DonutRaise,
+    FloorKind::RaiseFloor512,
+];
+const CEIL_KINDS: [CeilKind; 6] = [
+    CeilKind::LowerToFloor,
+    CeilKind::RaiseToHighest,
+    CeilKind::LowerAndCrush,
+    CeilKind::CrushAndRaise,
+    CeilKind::FastCrushAndRaise,
+    CeilKind::SilentCrushAndRaise,
+];
+const PLAT_KINDS: [PlatKind; 5] = [
+    PlatKind::PerpetualRaise,
+    PlatKind::DownWaitUpStay,
+    PlatKind::RaiseAndChange,
+    PlatKind::RaiseToNearestAndChange,
+    PlatKind::BlazeDWUS,
+];
+const PLAT_STATUS: [PlatStatus; 4] = [
+    PlatStatus::Up,
+    PlatStatus::Down,
+    PlatStatus::Waiting,
+    PlatStatus::InStasis,
+];
+const PUSH_KINDS: [PushKind; 4] = [
+    PushKind::Push,
+    PushKind::Pull,
+    PushKind::Wind,
+    PushKind::Current,
+];
+const BUTTON_WHERE: [ButtonWhere; 3] = [ButtonWhere::Top, ButtonWhere::Middle, ButtonWhere::Bottom];
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    /// Not a savegame of this engine
+    NotASave,
+    /// A save of a different version, the version is included
+    Version(u32),
+    /// The data doesn't make sense for the level, or ended early
+    Corrupt(&'static str),
+    /// A vanilla save of a version other than 1.9, the version string is
+    /// included
+    VanillaVersion(String),
+}
+
+impl Error for SaveError {}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{e}"),
+            SaveError::NotASave => write!(f, "Not a savegame"),
+            SaveError::Version(v) => write!(
+                f,
+                "Savegame is version {v}, only version {SAVE_VERSION} is supported"
+            ),
+            SaveError::Corrupt(m) => write!(f, "Savegame is corrupt: {m}"),
+            SaveError::VanillaVersion(v) => write!(
+                f,
+                "Vanilla savegame is {v:?}, only \"version 109\" is supported"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+/// Writes the little-endian data of a save
+#[derive(Default)]
+pub struct SaveWriter {
+    buf: Vec<u8>,
+}
+
+impl SaveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn bytes(&mut self, b: &[u8]) {
+        self.buf.extend_from_slice(b);
+    }
+
+    fn u8(&mut self, n: u8) {
+        self.buf.push(n);
+    }
+
+    fn bool(&mut self, b: bool) {
+        self.u8(b as u8);
+    }
+
+    fn i16(&mut self, n: i16) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn u16(&mut self, n: u16) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn i32(&mut self, n: i32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn f32(&mut self, n: f32) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn vec3(&mut self, v: Vec3) {
+        self.f32(v.x);
+        self.f32(v.y);
+        self.f32(v.z);
+    }
+
+    /// An optional index is written as `-1` if `None`
+    fn index(&mut self, n: Option<u32>) {
+        self.i32(n.map_or(-1, |n| n as i32));
+    }
+
+    fn string(&mut self, s: &str) {
+        self.u16(s.len() as u16);
+        self.bytes(s.as_bytes());
+    }
+}
+
+/// Reads the little-endian data of a save
+pub struct SaveReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SaveError> {
+        let b = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or(SaveError::Corrupt("unexpected end of data"))?;
+        self.pos += len;
+        Ok(b)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SaveError> {
+        let mut a = [0; N];
+        a.copy_from_slice(self.bytes(N)?);
+        Ok(a)
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, SaveError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn i16(&mut self) -> Result<i16, SaveError> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, SaveError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, SaveError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn vec3(&mut self) -> Result<Vec3, SaveError> {
+        Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn index(&mut self) -> Result<Option<usize>, SaveError> {
+        let n = self.i32()?;
+        Ok((n >= 0).then_some(n as usize))
+    }
+
+    fn string(&mut self) -> Result<String, SaveError> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    /// Read a discriminant and fetch the variant from the table of variants
+    fn variant<T: Copy>(&mut self, variants: &[T], what: &'static str) -> Result<T, SaveError> {
+        let n = self.u8()? as usize;
+        variants.get(n).copied().ok_or(SaveError::Corrupt(what))
+    }
+}
+
+/// The description and game options of a save, which are required to set up
+/// the level before the rest can be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveHeader {
+    pub description: String,
+    pub skill: Skill,
+    pub episode: usize,
+    pub map: usize,
+    pub players_in_game: [bool; MAXPLAYERS],
+    /// `None` for vanilla saves, which don't record them
+    pub options: Option<SaveOptions>,
+}
+
+/// The `GameOptions` that change how the game plays. A save must be loaded
+/// with the ones it was made with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaveOptions {
+    pub deathmatch: u8,
+    pub netgame: bool,
+    pub fixed_point: bool,
+    pub controls: ModernControls,
+}
+
+impl SaveOptions {
+    pub fn new(options: &GameOptions) -> Self {
+        Self {
+            deathmatch: options.deathmatch,
+            netgame: options.netgame,
+            fixed_point: options.fixed_point,
+            controls: options.controls,
+        }
+    }
+
+    pub fn apply(&self, options: &mut GameOptions) {
+        options.deathmatch = self.deathmatch;
+        options.netgame = self.netgame;
+        options.fixed_point = self.fixed_point;
+        options.controls = self.controls;
+    }
+
+    fn write(&self, out: &mut SaveWriter) {
+        out.u8(self.deathmatch);
+        out.bool(self.netgame);
+        out.bool(self.fixed_point);
+        out.bool(self.controls.free_look);
+        out.bool(self.controls.autoaim);
+        out.bool(self.controls.jump_crouch);
+    }
+
+    fn read(inp: &mut SaveReader) -> Result<Self, SaveError> {
+        let deathmatch = inp.u8()?;
+        if deathmatch > 2 {
+            return Err(SaveError::Corrupt("invalid deathmatch mode"));
+        }
+        Ok(Self {
+            deathmatch,
+            netgame: inp.bool()?,
+            fixed_point: inp.bool()?,
+            controls: ModernControls {
+                free_look: inp.bool()?,
+                autoaim: inp.bool()?,
+                jump_crouch: inp.bool()?,
+            },
+        })
+    }
+}
+
+impl SaveHeader {
+    pub fn write(&self, out: &mut SaveWriter) {
+        out.bytes(SAVE_MAGIC);
+        out.u32(SAVE_VERSION);
+        let mut description = self.description.clone();
+        while description.len() > SAVESTRINGSIZE {
+            description.pop();
+        }
+        out.string(&description);
+        out.i32(self.skill as i32);
+        out.u8(self.episode as u8);
+        out.u8(self.map as u8);
+        for p in self.players_in_game {
+            out.bool(p);
+        }
+        out.bool(self.options.is_some());
+        if let Some(options) = self.options {
+            options.write(out);
+        }
+    }
+
+    pub fn read(inp: &mut SaveReader) -> Result<Self, SaveError> {
+        if inp.bytes(SAVE_MAGIC.len())? != SAVE_MAGIC {
+            return Err(SaveError::NotASave);
+        }
+        let version = inp.u32()?;
+        if version != SAVE_VERSION {
+            return Err(SaveError::Version(version));
+        }
+        let description = inp.string()?;
+        let skill = inp.i32()?;
+        if !(Skill::NoItems as i32..=Skill::Nightmare as i32).contains(&skill) {
+            return Err(SaveError::Corrupt("invalid skill"));
+        }
+        let episode = inp.u8()? as usize;
+        let map = inp.u8()? as usize;
+        let mut players_in_game = [false; MAXPLAYERS];
+        for p in players_in_game.iter_mut() {
+            *p = inp.bool()?;
+        }
+        let options = if inp.bool()? {
+            Some(SaveOptions::read(inp)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            description,
+            skill: Skill::from(skill),
+            episode,
+            map,
+            players_in_game,
+            options,
+        })
+    }
+}
+
+/// Write the complete state of the level. The `SaveHeader` must be written
+/// first.
+///
+/// Doom function names `P_ArchivePlayers`, `P_ArchiveWorld`,
+/// `P_ArchiveThinkers`, `P_ArchiveSpecials`
+pub fn archive_level(out: &mut SaveWriter, level: &Level) {
+    out.u32(level.level_time);
+    out.i32(level.total_level_kills);
+    out.i32(level.total_level_items);
+    out.i32(level.total_level_secrets);
+
+    // Index each MapObject so pointers to them can be saved
+    let mut mobjs = HashMap::new();
+    for thinker in level.thinkers.iter().filter(|t| t.is_mobj()) {
+        mobjs.insert(thinker as *const Thinker, mobjs.len() as u32);
+    }
+    let mobj_index = |t: Option<*mut Thinker>| t.and_then(|t| mobjs.get(&(t as *const _)).copied());
+
+    for (i, player) in level.players().iter().enumerate() {
+        if level.players_in_game()[i] {
+            archive_player(out, player, &mobj_index);
+        }
+    }
+
+    archive_world(out, level);
+
+    out.u32(level.thinkers.iter().count() as u32);
+    for thinker in level.thinkers.iter() {
+        match thinker.data() {
+            ThinkerData::MapObject(mobj) => {
+                out.u8(TC_MOBJ);
+                archive_mobj(out, mobj, level, &mobj_index);
+            }
+            ThinkerData::VerticalDoor(door) => {
+                out.u8(TC_DOOR);
+                out.u32(door.sector.num);
+                out.u8(door.kind as u8);
+                out.f32(door.topheight);
+                out.f32(door.speed);
+                out.i32(door.direction);
+                out.i32(door.topwait);
+                out.i32(door.topcountdown);
+            }
+            ThinkerData::FloorMove(floor) => {
+                out.u8(TC_FLOOR);
+                out.u32(floor.sector.num);
+                out.u8(floor.kind as u8);
+                out.f32(floor.speed);
+                out.bool(floor.crush);
+                out.i32(floor.direction);
+                out.i16(floor.newspecial);
+                out.u32(floor.texture as u32);
+                out.f32(floor.destheight);
+            }
+            ThinkerData::CeilingMove(ceiling) => {
+                out.u8(TC_CEILING);
+                out.u32(ceiling.sector.num);
+                out.u8(ceiling.kind as u8);
+                out.f32(ceiling.bottomheight);
+                out.f32(ceiling.topheight);
+                out.f32(ceiling.speed);
+                out.bool(ceiling.crush);
+                out.i32(ceiling.direction);
+                out.i16(ceiling.tag);
+                out.i32(ceiling.olddirection);
+            }
+            ThinkerData::Platform(plat) => {
+                out.u8(TC_PLAT);
+                out.u32(plat.sector.num);
+                out.f32(plat.speed);
+                out.f32(plat.low);
+                out.f32(plat.high);
+                out.i32(plat.wait);
+                out.i32(plat.count);
+                out.u8(plat.status as u8);
+                out.u8(plat.old_status as u8);
+                out.bool(plat.crush);
+                out.i16(plat.tag);
+                out.u8(plat.kind as u8);
+            }
+            ThinkerData::LightFlash(light) => {
+                out.u8(TC_FLASH);
+                out.u32(light.sector.num);
+                out.i32(light.count);
+                out.u32(light.max_light as u32);
+                out.u32(light.min_light as u32);
+                out.i32(light.max_time);
+                out.i32(light.min_time);
+            }
+            ThinkerData::StrobeFlash(light) => {
+                out.u8(TC_STROBE);
+                out.u32(light.sector.num);
+                out.i32(light.count);
+                out.u32(light.min_light as u32);
+                out.u32(light.max_light as u32);
+                out.i32(light.dark_time);
+                out.i32(light.bright_time);
+            }
+            ThinkerData::FireFlicker(light) => {
+                out.u8(TC_FLICKER);
+                out.u32(light.sector.num);
+                out.i32(light.count);
+                out.u32(light.max_light as u32);
+                out.u32(light.min_light as u32);
+            }
+            ThinkerData::Glow(light) => {
+                out.u8(TC_GLOW);
+                out.u32(light.sector.num);
+                out.u32(light.min_light as u32);
+                out.u32(light.max_light as u32);
+                out.i32(light.direction);
+            }
+            ThinkerData::Scroll(scroll) => {
+                out.u8(TC_SCROLL);
+                match &scroll.kind {
+                    ScrollKind::Side(side) => {
+                        out.u8(0);
+                        out.index(index_of(level.map_data.sidedefs(), side));
+                    }
+                    ScrollKind::Floor(sector) => {
+                        out.u8(1);
+                        out.u32(sector.num);
+                    }
+                    ScrollKind::Ceiling(sector) => {
+                        out.u8(2);
+                        out.u32(sector.num);
+                    }
+                    ScrollKind::Carry(sector) => {
+                        out.u8(3);
+                        out.u32(sector.num);
+                    }
+                }
+                out.f32(scroll.dx);
+                out.f32(scroll.dy);
+                out.index(scroll.control.as_ref().map(|s| s.num));
+                out.f32(scroll.last_height);
+                out.f32(scroll.vdx);
+                out.f32(scroll.vdy);
+                out.bool(scroll.accel);
+            }
+            ThinkerData::Friction(friction) => {
+                out.u8(TC_FRICTION);
+                out.u32(friction.sector.num);
+                out.f32(friction.friction);
+                out.i32(friction.movefactor);
+            }
+            ThinkerData::Pusher(pusher) => {
+                out.u8(TC_PUSHER);
+                out.u32(pusher.sector.num);
+                out.u8(pusher.kind as u8);
+                out.f32(pusher.x_mag);
+                out.f32(pusher.y_mag);
+                out.f32(pusher.magnitude);
+                out.f32(pusher.radius);
+                out.vec3(pusher.source);
+            }
+            ThinkerData::TestObject(_) | ThinkerData::Remove | ThinkerData::Free => {}
+        }
+    }
+    out.u8(TC_END);
+
+    // Switches that are timed to change back. Buttons on lines created for
+    // `UMAPINFO` boss actions can't be saved.
+    let buttons: Vec<(u32, &Button)> = level
+        .button_list
+        .iter()
+        .filter_map(|b| index_of(level.map_data.linedefs(), &b.line).map(|i| (i, b)))
+        .collect();
+    out.u32(buttons.len() as u32);
+    for (line, button) in buttons {
+        out.u32(line);
+        out.u8(button.bwhere as u8);
+        out.u32(button.texture as u32);
+        out.u32(button.timer);
+    }
+
+    out.u32(level.respawn_queue.len() as u32);
+    for (time, thing) in level.respawn_queue.iter() {
+        out.u32(*time);
+        archive_wad_thing(out, thing);
+    }
+
+    let (rnd, prnd) = level.random.indexes();
+    out.u8(rnd as u8);
+    out.u8(prnd as u8);
+    out.u8(SAVE_END);
+}
+
+/// Restore the complete state of the level. The `Level` must be freshly set up
+/// with the map, episode, and skill in the `SaveHeader`, and the players in
+/// game set. All thinkers in the level are replaced.
+///
+/// Doom function names `P_UnArchivePlayers`, `P_UnArchiveWorld`,
+/// `P_UnArchiveThinkers`, `P_UnArchiveSpecials`
+pub fn unarchive_level(inp: &mut SaveReader, level: &mut Level) -> Result<(), SaveError> {
+    level.level_time = inp.u32()?;
+    level.total_level_kills = inp.i32()?;
+    level.total_level_items = inp.i32()?;
+    level.total_level_secrets = inp.i32()?;
+
+    let mut attackers = [None; MAXPLAYERS];
+    for (i, attacker) in attackers.iter_mut().enumerate() {
+        let player = &mut level.players_mut()[i];
+        // Pointers in to the old level are dropped, the MapObject and attacker
+        // are linked once the thinkers are loaded
+        player.mobj = None;
+        player.attacker = None;
+        player.message = None;
+        if level.players_in_game()[i] {
+            *attacker = unarchive_player(inp, &mut level.players_mut()[i])?;
+        }
+    }
+
+    unarchive_world(inp, level)?;
+
+    let count = inp.u32()? as usize;
+    let capacity = (level.map_data.things().len() * 2).max(count) + 256;
+    unsafe { level.clear_thinkers(capacity) };
+
+    let mut mobjs: Vec<*mut Thinker> = Vec::new();
+    let mut links = Vec::new();
+    loop {
+        let class = inp.u8()?;
+        match class {
+            TC_END => break,
+            TC_MOBJ => {
+                let (thinker, link) = unarchive_mobj(inp, level)?;
+                mobjs.push(thinker);
+                links.push(link);
+            }
+            TC_DOOR => {
+                let mut sector = sector_ptr(inp, level)?;
+                let door = VerticalDoor {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: inp.variant(&DOOR_KINDS, "door kind")?,
+                    topheight: inp.f32()?,
+                    speed: inp.f32()?,
+                    direction: inp.i32()?,
+                    topwait: inp.i32()?,
+                    topcountdown: inp.i32()?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::VerticalDoor(door), VerticalDoor::think);
+                sector.specialdata = Some(push_thinker::<VerticalDoor>(level, thinker)?);
+            }
+            TC_FLOOR => {
+                let mut sector = sector_ptr(inp, level)?;
+                let floor = FloorMove {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: inp.variant(&FLOOR_KINDS, "floor kind")?,
+                    speed: inp.f32()?,
+                    crush: inp.bool()?,
+                    direction: inp.i32()?,
+                    newspecial: inp.i16()?,
+                    texture: inp.u32()? as usize,
+                    destheight: inp.f32()?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::FloorMove(floor), FloorMove::think);
+                sector.specialdata = Some(push_thinker::<FloorMove>(level, thinker)?);
+            }
+            TC_CEILING => {
+                let mut sector = sector_ptr(inp, level)?;
+                let ceiling = CeilingMove {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: inp.variant(&CEIL_KINDS, "ceiling kind")?,
+                    bottomheight: inp.f32()?,
+                    topheight: inp.f32()?,
+                    speed: inp.f32()?,
+                    crush: inp.bool()?,
+                    direction: inp.i32()?,
+                    tag: inp.i16()?,
+                    olddirection: inp.i32()?,
+                };
+                let thinker = MapObject::create_thinker(
+                    ThinkerData::CeilingMove(ceiling),
+                    CeilingMove::think,
+                );
+                sector.specialdata = Some(push_thinker::<CeilingMove>(level, thinker)?);
+            }
+            TC_PLAT => {
+                let mut sector = sector_ptr(inp, level)?;
+                let plat = Platform {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    speed: inp.f32()?,
+                    low: inp.f32()?,
+                    high: inp.f32()?,
+                    wait: inp.i32()?,
+                    count: inp.i32()?,
+                    status: inp.variant(&PLAT_STATUS, "platform status")?,
+                    old_status: inp.variant(&PLAT_STATUS, "platform status")?,
+                    crush: inp.bool()?,
+                    tag: inp.i16()?,
+                    kind: inp.variant(&PLAT_KINDS, "platform kind")?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::Platform(plat), Platform::think);
+                let ptr = push_thinker::<Platform>(level, thinker)?;
+                sector.specialdata = Some(ptr);
+                level.add_active_platform(unsafe { (*ptr).platform_mut() });
+            }
+            TC_FLASH => {
+                let light = LightFlash {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    count: inp.i32()?,
+                    max_light: inp.u32()? as usize,
+                    min_light: inp.u32()? as usize,
+                    max_time: inp.i32()?,
+                    min_time: inp.i32()?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::LightFlash(light), LightFlash::think);
+                push_thinker::<LightFlash>(level, thinker)?;
+            }
+            TC_STROBE => {
+                let light = StrobeFlash {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    count: inp.i32()?,
+                    min_light: inp.u32()? as usize,
+                    max_light: inp.u32()? as usize,
+                    dark_time: inp.i32()?,
+                    bright_time: inp.i32()?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::StrobeFlash(light), StrobeFlash::think);
+                push_thinker::<StrobeFlash>(level, thinker)?;
+            }
+            TC_FLICKER => {
+                let light = FireFlicker {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    count: inp.i32()?,
+                    max_light: inp.u32()? as usize,
+                    min_light: inp.u32()? as usize,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::FireFlicker(light), FireFlicker::think);
+                push_thinker::<FireFlicker>(level, thinker)?;
+            }
+            TC_GLOW => {
+                let light = Glow {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    min_light: inp.u32()? as usize,
+                    max_light: inp.u32()? as usize,
+                    direction: inp.i32()?,
+                };
+                let thinker = MapObject::create_thinker(ThinkerData::Glow(light), Glow::think);
+                push_thinker::<Glow>(level, thinker)?;
+            }
+            TC_SCROLL => {
+                let kind = match inp.u8()? {
+                    0 => ScrollKind::Side(side_ptr(inp, level)?),
+                    1 => ScrollKind::Floor(sector_ptr(inp, level)?),
+                    2 => ScrollKind::Ceiling(sector_ptr(inp, level)?),
+                    3 => ScrollKind::Carry(sector_ptr(inp, level)?),
+                    _ => return Err(SaveError::Corrupt("scroll kind")),
+                };
+                let scroll = Scroll {
+                    thinker: null_mut(),
+                    kind,
+                    dx: inp.f32()?,
+                    dy: inp.f32()?,
+                    control: match inp.index()? {
+                        Some(n) => Some(sector_at(n, level)?),
+                        None => None,
+                    },
+                    last_height: inp.f32()?,
+                    vdx: inp.f32()?,
+                    vdy: inp.f32()?,
+                    accel: inp.bool()?,
+                };
+                let thinker = MapObject::create_thinker(ThinkerData::Scroll(scroll), Scroll::think);
+                push_thinker::<Scroll>(level, thinker)?;
+            }
+            TC_FRICTION => {
+                let friction = Friction {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    friction: inp.f32()?,
+                    movefactor: inp.i32()?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::Friction(friction), Friction::think);
+                push_thinker::<Friction>(level, thinker)?;
+            }
+            TC_PUSHER => {
+                let pusher = Pusher {
+                    thinker: null_mut(),
+                    sector: sector_ptr(inp, level)?,
+                    kind: inp.variant(&PUSH_KINDS, "pusher kind")?,
+                    x_mag: inp.f32()?,
+                    y_mag: inp.f32()?,
+                    magnitude: inp.f32()?,
+                    radius: inp.f32()?,
+                    source: inp.vec3()?,
+                };
+                let thinker = MapObject::create_thinker(ThinkerData::Pusher(pusher), Pusher::think);
+                push_thinker::<Pusher>(level, thinker)?;
+            }
+            _ => return Err(SaveError::Corrupt("unknown thinker class")),
+        }
+    }
+
+    // Everything exists now so the pointers between MapObjects can be linked
+    let mobj_at = |n: Option<usize>| -> Result<Option<*mut Thinker>, SaveError> {
+        match n {
+            Some(n) => match mobjs.get(n) {
+                Some(t) => Ok(Some(*t)),
+                None => Err(SaveError::Corrupt("MapObject index out of range")),
+            },
+            None => Ok(None),
+        }
+    };
+    for (thinker, link) in mobjs.iter().zip(links) {
+        let mobj = unsafe { (**thinker).mobj_mut() };
+        mobj.target = mobj_at(link.target)?;
+        mobj.tracer = mobj_at(link.tracer)?;
+        for target in link.boss_targets {
+            if let Some(t) = mobj_at(Some(target))? {
+                mobj.boss_targets.push(t);
+            }
+        }
+    }
+    for (i, attacker) in attackers.into_iter().enumerate() {
+        let attacker = mobj_at(attacker)?;
+        level.players_mut()[i].attacker = attacker.map(|t| unsafe { (*t).mobj_mut() as *mut _ });
+    }
+
+    level.button_list.clear();
+    for _ in 0..inp.u32()? {
+        let line = inp.u32()? as usize;
+        let line = level
+            .map_data
+            .linedefs
+            .get_mut(line)
+            .ok_or(SaveError::Corrupt("button line out of range"))?;
+        level.button_list.push(Button {
+            line: MapPtr::new(line),
+            bwhere: inp.variant(&BUTTON_WHERE, "button position")?,
+            texture: inp.u32()? as usize,
+            timer: inp.u32()?,
+        });
+    }
+
+    level.respawn_queue.clear();
+    for _ in 0..inp.u32()? {
+        let time = inp.u32()?;
+        level
+            .respawn_queue
+            .push_back((time, unarchive_wad_thing(inp)?));
+    }
+
+    let rnd = inp.u8()? as usize;
+    let prnd = inp.u8()? as usize;
+    level.random.set_indexes(rnd, prnd);
+
+    if inp.u8()? != SAVE_END {
+        return Err(SaveError::Corrupt("bad end marker"));
+    }
+    Ok(())
+}
+
+/// Saved `MapObject` indexes to be linked once every thinker is loaded
+struct MobjLinks {
+    target: Option<usize>,
+    tracer: Option<usize>,
+    boss_targets: Vec<usize>,
+}
+
+/// The index of an item in the slice it belongs to, if it is in there
+fn index_of<T>(items: &[T], item: &T) -> Option<u32> {
+    let range = items.as_ptr_range();
+    let item = item as *const T;
+    range
+        .contains(&item)
+        .then(|| unsafe { item.offset_from(range.start) } as u32)
+}
+
+/// The index of `state` in `STATES`. A state from anywhere else is written out
+/// of range, so that loading fails instead of the thing getting `S_NULL`
+fn state_index(state: &State) -> u16 {
+    index_of(&STATES, state).map_or(u16::MAX, |i| i as u16)
+}
+
+fn state_at(n: usize) -> Result<&'static State, SaveError> {
+    STATES
+        .get(n)
+        .ok_or(SaveError::Corrupt("state out of range"))
+}
+
+fn sector_at(n: usize, level: &mut Level) -> Result<MapPtr<Sector>, SaveError> {
+    level
+        .map_data
+        .sectors_mut()
+        .get_mut(n)
+        .map(MapPtr::new)
+        .ok_or(SaveError::Corrupt("sector out of range"))
+}
+
+fn sector_ptr(inp: &mut SaveReader, level: &mut Level) -> Result<MapPtr<Sector>, SaveError> {
+    sector_at(inp.u32()? as usize, level)
+}
+
+fn side_ptr(inp: &mut SaveReader, level: &mut Level) -> Result<MapPtr<SideDef>, SaveError> {
+    let n = inp.index()?.ok_or(SaveError::Corrupt("scroller side"))?;
+    level
+        .map_data
+        .sidedefs_mut()
+        .get_mut(n)
+        .map(MapPtr::new)
+        .ok_or(SaveError::Corrupt("side out of range"))
+}
+
+fn push_thinker<T: Think>(level: &mut Level, thinker: Thinker) -> Result<*mut Thinker, SaveError> {
+    level
+        .thinkers
+        .push::<T>(thinker)
+        .map(|t| t as *mut Thinker)
+        .ok_or(SaveError::Corrupt("too many thinkers"))
+}
+
+fn archive_wad_thing(out: &mut SaveWriter, thing: &WadThing) {
+    out.i16(thing.x);
+    out.i16(thing.y);
+    out.i16(thing.angle);
+    out.i16(thing.kind);
+    out.i16(thing.flags);
+}
+
+fn unarchive_wad_thing(inp: &mut SaveReader) -> Result<WadThing, SaveError> {
+    Ok(WadThing::new(
+        inp.i16()?,
+        inp.i16()?,
+        inp.i16()?,
+        inp.i16()?,
+        inp.i16()?,
+    ))
+}
+
+fn archive_psprite(out: &mut SaveWriter, psp: &PspDef) {
+    out.index(psp.state.map(|s| state_index(s) as u32));
+    out.i32(psp.tics);
+    out.f32(psp.sx);
+    out.f32(psp.sy);
+}
+
+fn unarchive_psprite(inp: &mut SaveReader, psp: &mut PspDef) -> Result<(), SaveError> {
+    psp.state = match inp.index()? {
+        Some(n) => Some(state_at(n)?),
+        None => None,
+    };
+    psp.tics = inp.i32()?;
+    psp.sx = inp.f32()?;
+    psp.sy = inp.f32()?;
+    Ok(())
+}
+
+fn archive_player(
+    out: &mut SaveWriter,
+    player: &Player,
+    mobj_index: &impl Fn(Option<*mut Thinker>) -> Option<u32>,
+) {
+    out.u8(player.player_state as u8);
+    out.f32(player.viewz);
+    out.f32(player.viewheight);
+    out.f32(player.deltaviewheight);
+    out.f32(player.bob);
+    out.bool(player.onground);
+    out.f32(player.pitch);
+    out.f32(player.crouch);
+
+    let status = &player.status;
+    out.bool(status.attackdown);
+    out.bool(status.usedown);
+    out.u8(status.readyweapon as u8);
+    out.i32(status.health);
+    out.i32(status.armorpoints);
+    out.i32(status.armortype);
+    for card in status.cards {
+        out.bool(card);
+    }
+    for owned in status.weaponowned {
+        out.bool(owned);
+    }
+    for ammo in status.ammo {
+        out.u32(ammo);
+    }
+    for ammo in status.maxammo {
+        out.u32(ammo);
+    }
+    out.bool(status.backpack);
+    for power in status.powers {
+        out.i32(power);
+    }
+    out.i32(status.damagecount);
+    out.i32(status.bonuscount);
+    out.f32(status.attacked_from.rad());
+    out.f32(status.own_angle.rad());
+    out.u32(status.attacked_angle_count);
+    out.u32(status.cheats);
+
+    for frags in player.frags {
+        out.i32(frags);
+    }
+    out.u8(player.pendingweapon as u8);
+    out.i32(player.refire);
+    out.i32(player.total_kills);
+    out.i32(player.items_collected);
+    out.i32(player.secrets_found);
+    out.u32(player.extralight as u32);
+    out.i32(player.fixedcolormap);
+    for psp in player.psprites.iter() {
+        archive_psprite(out, psp);
+    }
+    out.bool(player.didsecret);
+    out.index(mobj_index(player.attacker.map(|a| unsafe { (*a).thinker })));
+}
+
+/// Returns the index of the player's attacker, to be linked once the
+/// `MapObject`s are loaded
+fn unarchive_player(inp: &mut SaveReader, player: &mut Player) -> Result<Option<usize>, SaveError> {
+    player.player_state = inp.variant(&PLAYER_STATES, "player state")?;
+    player.viewz = inp.f32()?;
+    player.viewheight = inp.f32()?;
+    player.deltaviewheight = inp.f32()?;
+    player.bob = inp.f32()?;
+    player.onground = inp.bool()?;
+    player.pitch = inp.f32()?;
+    player.crouch = inp.f32()?;
+
+    let status = &mut player.status;
+    status.attackdown = inp.bool()?;
+    status.usedown = inp.bool()?;
+    status.readyweapon = weapon(inp.u8()?)?;
+    status.health = inp.i32()?;
+    status.armorpoints = inp.i32()?;
+    status.armortype = inp.i32()?;
+    for card in status.cards.iter_mut() {
+        *card = inp.bool()?;
+    }
+    for owned in status.weaponowned.iter_mut() {
+        *owned = inp.bool()?;
+    }
+    for ammo in status.ammo.iter_mut() {
+        *ammo = inp.u32()?;
+    }
+    for ammo in status.maxammo.iter_mut() {
+        *ammo = inp.u32()?;
+    }
+    status.backpack = inp.bool()?;
+    for power in status.powers.iter_mut() {
+        *power = inp.i32()?;
+    }
+    status.damagecount = inp.i32()?;
+    status.bonuscount = inp.i32()?;
+    status.attacked_from = Angle::new(inp.f32()?);
+    status.own_angle = Angle::new(inp.f32()?);
+    status.attacked_angle_count = inp.u32()?;
+    status.cheats = inp.u32()?;
+
+    for frags in player.frags.iter_mut() {
+        *frags = inp.i32()?;
+    }
+    let pending = inp.u8()?;
+    player.pendingweapon = if pending == WeaponType::NoChange as u8 {
+        WeaponType::NoChange
+    } else {
+        weapon(pending)?
+    };
+    player.refire = inp.i32()?;
+    player.total_kills = inp.i32()?;
+    player.items_collected = inp.i32()?;
+    player.secrets_found = inp.i32()?;
+    player.extralight = inp.u32()? as usize;
+    player.fixedcolormap = inp.i32()?;
+    for psp in player.psprites.iter_mut() {
+        unarchive_psprite(inp, psp)?;
+    }
+    player.didsecret = inp.bool()?;
+    inp.index()
+}
+
+fn weapon(n: u8) -> Result<WeaponType, SaveError> {
+    if n >= WeaponType::NumWeapons as u8 {
+        return Err(SaveError::Corrupt("weapon out of range"));
+    }
+    Ok(WeaponType::from(n))
+}
+
+fn archive_world(out: &mut SaveWriter, level: &Level) {
+    let map = &level.map_data;
+    out.u32(map.sectors().len() as u32);
+    for sector in map.sectors() {
+        out.f32(sector.floorheight);
+        out.f32(sector.ceilingheight);
+        out.u32(sector.floorpic as u32);
+        out.u32(sector.ceilingpic as u32);
+        out.u32(sector.lightlevel as u32);
+        out.i16(sector.special);
+        out.i16(sector.tag);
+        out.f32(sector.floor_xoffs);
+        out.f32(sector.floor_yoffs);
+        out.f32(sector.ceil_xoffs);
+        out.f32(sector.ceil_yoffs);
+    }
+
+    out.u32(map.linedefs().len() as u32);
+    for line in map.linedefs() {
+        out.u32(line.flags);
+        out.i16(line.special);
+        out.i16(line.tag);
+    }
+
+    out.u32(map.sidedefs().len() as u32);
+    for side in map.sidedefs() {
+        out.f32(side.textureoffset);
+        out.f32(side.rowoffset);
+        for texture in [side.toptexture, side.midtexture, side.bottomtexture] {
+            out.index(texture.map(|t| t as u32));
+        }
+    }
+}
+
+fn unarchive_world(inp: &mut SaveReader, level: &mut Level) -> Result<(), SaveError> {
+    let map = &mut level.map_data;
+    if inp.u32()? as usize != map.sectors().len() {
+        return Err(SaveError::Corrupt("sector count doesn't match the map"));
+    }
+    for sector in map.sectors_mut() {
+        sector.floorheight = inp.f32()?;
+        sector.ceilingheight = inp.f32()?;
+        sector.floorpic = inp.u32()? as usize;
+        sector.ceilingpic = inp.u32()? as usize;
+        sector.lightlevel = inp.u32()? as usize;
+        sector.special = inp.i16()?;
+        sector.tag = inp.i16()?;
+        sector.floor_xoffs = inp.f32()?;
+        sector.floor_yoffs = inp.f32()?;
+        sector.ceil_xoffs = inp.f32()?;
+        sector.ceil_yoffs = inp.f32()?;
+    }
+
+    if inp.u32()? as usize != map.linedefs().len() {
+        return Err(SaveError::Corrupt("line count doesn't match the map"));
+    }
+    for line in map.linedefs.iter_mut() {
+        line.flags = inp.u32()?;
+        line.special = inp.i16()?;
+        line.tag = inp.i16()?;
+    }
+
+    if inp.u32()? as usize != map.sidedefs().len() {
+        return Err(SaveError::Corrupt("side count doesn't match the map"));
+    }
+    for side in map.sidedefs_mut() {
+        side.textureoffset = inp.f32()?;
+        side.rowoffset = inp.f32()?;
+        side.toptexture = inp.index()?;
+        side.midtexture = inp.index()?;
+        side.bottomtexture = inp.index()?;
+    }
+    Ok(())
+}
+
+fn archive_mobj(
+    out: &mut SaveWriter,
+    mobj: &MapObject,
+    level: &Level,
+    mobj_index: &impl Fn(Option<*mut Thinker>) -> Option<u32>,
+) {
+    out.u16(mobj.kind as u16);
+    out.vec3(mobj.xyz);
+    archive_fixed(out, &mobj.fixed);
+    out.f32(mobj.angle.rad());
+    out.u32(mobj.frame);
+    out.f32(mobj.floorz);
+    out.f32(mobj.ceilingz);
+    out.f32(mobj.radius);
+    out.f32(mobj.height);
+    out.vec3(mobj.momxyz);
+    out.f32(mobj.friction);
+    out.i32(mobj.movefactor);
+    out.i32(mobj.tics);
+    out.u16(state_index(mobj.state));
+    out.u32(mobj.flags);
+    out.i32(mobj.health);
+    out.u8(mobj.movedir as u8);
+    out.i32(mobj.movecount);
+    out.i32(mobj.reactiontime);
+    out.i32(mobj.threshold);
+    out.i32(mobj.lastlook);
+    archive_wad_thing(out, &mobj.spawnpoint);
+
+    out.index(mobj_index(mobj.target));
+    out.index(mobj_index(mobj.tracer));
+    let player = mobj
+        .player
+        .and_then(|p| level.players().iter().position(|player| ptr::eq(player, p)));
+    out.index(player.map(|p| p as u32));
+    let boss_targets: Vec<u32> = mobj
+        .boss_targets
+        .iter()
+        .filter_map(|t| mobj_index(Some(*t)))
+        .collect();
+    out.u32(boss_targets.len() as u32);
+    for target in boss_targets {
+        out.u32(target);
+    }
+    out.u32(mobj.boss_target_on as u32);
+}
+
+/// The exact 16.16 position and momentum and BAM angle, which the `f32` fields
+/// mirror in the fixed-point mode
+fn archive_fixed(out: &mut SaveWriter, fixed: &FixedState) {
+    out.i32(fixed.x);
+    out.i32(fixed.y);
+    out.i32(fixed.z);
+    out.i32(fixed.momx);
+    out.i32(fixed.momy);
+    out.i32(fixed.momz);
+    out.u32(fixed.angle);
+}
+
+fn unarchive_fixed(inp: &mut SaveReader) -> Result<FixedState, SaveError> {
+    Ok(FixedState {
+        x: inp.i32()?,
+        y: inp.i32()?,
+        z: inp.i32()?,
+        momx: inp.i32()?,
+        momy: inp.i32()?,
+        momz: inp.i32()?,
+        angle: inp.u32()?,
+    })
+}
+
+fn unarchive_mobj(
+    inp: &mut SaveReader,
+    level: &mut Level,
+) -> Result<(*mut Thinker, MobjLinks), SaveError> {
+    let kind = inp.u16()?;
+    if kind >= MapObjKind::Count as u16 {
+        return Err(SaveError::Corrupt("MapObject kind out of range"));
+    }
+    let xyz = inp.vec3()?;
+    let fixed = unarchive_fixed(inp)?;
+    // Spawning links the MapObject in to the sector and blockmap
+    let kind = MapObjKind::from(kind);
+    let mobj = unsafe {
+        if level.options.fixed_point {
+            &mut *MapObject::spawn_map_object_fixed(fixed.x, fixed.y, fixed.z, kind, level)
+        } else {
+            &mut *MapObject::spawn_map_object(xyz.x, xyz.y, xyz.z as i32, kind, level)
+        }
+    };
+    mobj.xyz = xyz;
+    mobj.fixed = fixed;
+    mobj.angle = Angle::new(inp.f32()?);
+    mobj.frame = inp.u32()?;
+    mobj.floorz = inp.f32()?;
+    mobj.ceilingz = inp.f32()?;
+    mobj.radius = inp.f32()?;
+    mobj.height = inp.f32()?;
+    mobj.momxyz = inp.vec3()?;
+    mobj.friction = inp.f32()?;
+    mobj.movefactor = inp.i32()?;
+    mobj.tics = inp.i32()?;
+    mobj.state = state_at(inp.u16()? as usize)?;
+    mobj.sprite = mobj.state.sprite;
+    mobj.flags = inp.u32()?;
+    mobj.health = inp.i32()?;
+    let movedir = inp.u8()? as usize;
+    if movedir >= MoveDir::NumDirs as usize {
+        return Err(SaveError::Corrupt("move direction out of range"));
+    }
+    mobj.movedir = MoveDir::from(movedir);
+    mobj.movecount = inp.i32()?;
+    mobj.reactiontime = inp.i32()?;
+    mobj.threshold = inp.i32()?;
+    mobj.lastlook = inp.i32()?;
+    mobj.spawnpoint = unarchive_wad_thing(inp)?;
+
+    let target = inp.index()?;
+    let tracer = inp.index()?;
+    if let Some(p) = inp.index()? {
+        if p >= MAXPLAYERS {
+            return Err(SaveError::Corrupt("player out of range"));
+        }
+        let player = &mut level.players_mut()[p];
+        player.set_mobj(mobj);
+        mobj.player = Some(player);
+    }
+    let mut boss_targets = Vec::new();
+    for _ in 0..inp.u32()? {
+        boss_targets.push(inp.u32()? as usize);
+    }
+    mobj.boss_target_on = inp.u32()? as usize;
+
+    if mobj.player.is_none() && mobj.kind == MapObjKind::MT_PLAYER {
+        warn!("Loaded a player MapObject without a player");
+    }
+
+    Ok((
+        mobj.thinker,
+        MobjLinks {
+            target,
+            tracer,
+            boss_targets,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        archive_fixed, state_at, state_index, unarchive_fixed, SaveError, SaveHeader, SaveOptions, SaveReader, SaveWriter, SAVE_VERSION
+    };
+    use crate::doom_def::ActFn;
+    use crate::info::{SpriteNum, State, StateNum, STATES};
+    use crate::thing::FixedState;
+    use crate::{ModernControls, Skill};
+
+    #[test]
+    fn header_round_trip() {
+        let header = SaveHeader {
+            description: "E1M1 at the exit".to_string(),
+            skill: Skill::Hard,
+            episode: 1,
+            map: 3,
+            players_in_game: [true, true, false, false],
+            options: Some(SaveOptions {
+                deathmatch: 2,
+                netgame: true,
+                fixed_point: true,
+                controls: ModernControls {
+                    free_look: true,
+                    autoaim: false,
+                    jump_crouch: true,
+                },
+            }),
+        };
+        let mut out = SaveWriter::new();
+        header.write(&mut out);
+        let data = out.into_inner();
+
+        let mut inp = SaveReader::new(&data);
+        assert_eq!(SaveHeader::read(&mut inp).unwrap(), header);
+    }
+
+    #[test]
+    fn fixed_state_round_trip() {
+        let fixed = FixedState {
+            x: -1024 * 65536 + 3,
+            y: 512 * 65536 - 7,
+            z: 24 * 65536,
+            momx: 0x8000,
+            momy: -0x1234,
+            momz: -65536,
+            angle: 0xC000_0001,
+        };
+        let mut out = SaveWriter::new();
+        archive_fixed(&mut out, &fixed);
+        let data = out.into_inner();
+
+        let mut inp = SaveReader::new(&data);
+        let read = unarchive_fixed(&mut inp).unwrap();
+        let fields = |f: &FixedState| (f.x, f.y, f.z, f.momx, f.momy, f.momz, f.angle);
+        assert_eq!(fields(&read), fields(&fixed));
+        assert!(unarchive_fixed(&mut inp).is_err());
+    }
+
+    #[test]
+    fn header_errors() {
+        let mut inp = SaveReader::new(b"DOOMSAVE and more");
+        assert!(matches!(
+            SaveHeader::read(&mut inp),
+            Err(SaveError::NotASave)
+        ));
+
+        let mut out = SaveWriter::new();
+        SaveHeader {
+            description: String::new(),
+            skill: Skill::Baby,
+            episode: 1,
+            map: 1,
+            players_in_game: [true; 4],
+            options: None,
+        }
+        .write(&mut out);
+        let mut data = out.into_inner();
+        data[8..12].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes());
+        let mut inp = SaveReader::new(&data);
+        assert!(matches!(
+            SaveHeader::read(&mut inp),
+            Err(SaveError::Version(v)) if v == SAVE_VERSION + 1
+        ));
+
+        let mut inp = SaveReader::new(&data[..10]);
+        assert!(matches!(
+            SaveHeader::read(&mut inp),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_state_fails_to_load() {
+        assert_eq!(
+            state_index(&STATES[StateNum::PLAY as usize]),
+            StateNum::PLAY as u16
+        );
+
+        let state = State {
+            sprite: SpriteNum::PLAY,
+            frame: 0,
+            tics: -1,
+            action: ActFn::N,
+            next_state: StateNum::None,
+            misc1: 0,
+            misc2: 0,
+        };
+        assert!(matches!(
+            state_at(state_index(&state) as usize),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+}
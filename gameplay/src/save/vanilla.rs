@@ -0,0 +1,992 @@
+//! Import of the `DOOMSAV*.DSG` savegames of vanilla Doom 1.9 (and the ports
+//! that kept its layout, such as Chocolate Doom).
+//!
+//! Vanilla writes its structs to the file as they are in memory on a 32-bit
+//! machine, each aligned to 4 bytes. Pointers are written as garbage other
+//! than a few that are turned in to numbers first: the state of a `MapObject`
+//! or player sprite, the sector of a special, and the player of a
+//! `MapObject`. This means vanilla loses the `target` and `tracer` of every
+//! `MapObject`, and the fire flicker lights, and an import does the same.
+//!
+//! Loading is best effort. Anything that can't be represented is skipped and
+//! logged as a warning.
+//!
+//! Doom source name `p_saveg`
+
+use std::ptr::null_mut;
+
+use log::warn;
+use wad::types::WadThing;
+use wad::WadData;
+
+use super::{
+    push_thinker, sector_at, state_at, SaveError, SaveHeader, SaveReader, CEIL_KINDS, DOOR_KINDS,
+    FLOOR_KINDS, PLAT_KINDS, PLAT_STATUS, PLAYER_STATES, SAVESTRINGSIZE,
+};
+use crate::angle::Angle;
+use crate::doom_def::{WeaponType, MAXPLAYERS};
+use crate::env::ceiling::CeilingMove;
+use crate::env::doors::VerticalDoor;
+use crate::env::floor::FloorMove;
+use crate::env::lights::{Glow, LightFlash, StrobeFlash};
+use crate::env::platforms::Platform;
+use crate::info::MapObjKind;
+use crate::level::Level;
+use crate::player::Player;
+use crate::thing::enemy_movement::MoveDir;
+use crate::thing::{FixedState, MapObject};
+use crate::thinker::{Think, Thinker, ThinkerData};
+use crate::utilities::{bam_to_radian, fixed_to_float};
+use crate::{PicData, Skill};
+
+const VERSIONSIZE: usize = 16;
+/// The only version of the layout that can be read
+const VANILLA_VERSION: &str = "version 109";
+const SAVE_END: u8 = 0x1d;
+
+/// Sizes of the structs as written by vanilla
+const PLAYER_SIZE: usize = 280;
+const MOBJ_SIZE: usize = 154;
+const CEILING_SIZE: usize = 48;
+const DOOR_SIZE: usize = 40;
+const FLOOR_SIZE: usize = 44;
+const PLAT_SIZE: usize = 56;
+const FLASH_SIZE: usize = 36;
+const STROBE_SIZE: usize = 36;
+const GLOW_SIZE: usize = 28;
+
+// Thinker classes
+const TC_END: u8 = 0;
+const TC_MOBJ: u8 = 1;
+
+// Special classes
+const TC_CEILING: u8 = 0;
+const TC_DOOR: u8 = 1;
+const TC_FLOOR: u8 = 2;
+const TC_PLAT: u8 = 3;
+const TC_FLASH: u8 = 4;
+const TC_STROBE: u8 = 5;
+const TC_GLOW: u8 = 6;
+const TC_ENDSPECIALS: u8 = 7;
+
+/// A struct as written by vanilla, read by the offset of each field
+struct Record<'a>(&'a [u8]);
+
+impl Record<'_> {
+    fn int(&self, at: usize) -> i32 {
+        i32::from_le_bytes([self.0[at], self.0[at + 1], self.0[at + 2], self.0[at + 3]])
+    }
+
+    fn short(&self, at: usize) -> i16 {
+        i16::from_le_bytes([self.0[at], self.0[at + 1]])
+    }
+
+    fn fixed(&self, at: usize) -> f32 {
+        fixed_to_float(self.int(at))
+    }
+
+    fn boolean(&self, at: usize) -> bool {
+        self.int(at) != 0
+    }
+}
+
+impl<'a> SaveReader<'a> {
+    /// Skip to the next 4 byte boundary of the save
+    ///
+    /// Doom macro name `PADSAVEP`
+    fn pad(&mut self) {
+        self.pos += (4 - (self.pos & 3)) & 3;
+    }
+
+    fn record(&mut self, size: usize) -> Result<Record<'a>, SaveError> {
+        self.pad();
+        Ok(Record(self.bytes(size)?))
+    }
+}
+
+/// Read the header of a vanilla save. The level time that follows it is read
+/// by `unarchive_level`.
+///
+/// Doom function name `G_DoLoadGame`
+pub fn read_header(inp: &mut SaveReader) -> Result<SaveHeader, SaveError> {
+    let description = inp.bytes(SAVESTRINGSIZE)?;
+    let description = String::from_utf8_lossy(description)
+        .trim_end_matches('\0')
+        .to_string();
+
+    let version = inp.bytes(VERSIONSIZE)?;
+    let version = String::from_utf8_lossy(version)
+        .trim_end_matches('\0')
+        .to_string();
+    if !version.starts_with("version ") {
+        return Err(SaveError::NotASave);
+    }
+    if version != VANILLA_VERSION {
+        return Err(SaveError::VanillaVersion(version));
+    }
+
+    let skill = inp.u8()? as i32;
+    if skill > Skill::Nightmare as i32 {
+        return Err(SaveError::Corrupt("invalid skill"));
+    }
+    let episode = inp.u8()? as usize;
+    let map = inp.u8()? as usize;
+    let mut players_in_game = [false; MAXPLAYERS];
+    for p in players_in_game.iter_mut() {
+        *p = inp.bool()?;
+    }
+    Ok(SaveHeader {
+        description,
+        skill: Skill::from(skill),
+        episode,
+        map,
+        players_in_game,
+        options: None,
+    })
+}
+
+/// Rebuild the level from a vanilla save. As with `super::unarchive_level` the
+/// `Level` must be freshly set up with the options in the header, and all
+/// thinkers in it are replaced.
+///
+/// Doom function name `P_UnArchivePlayers`
+pub fn unarchive_level(
+    inp: &mut SaveReader,
+    level: &mut Level,
+    wad: &WadData,
+    pic_data: &PicData,
+) -> Result<(), SaveError> {
+    let time = inp.bytes(3)?;
+    level.level_time = (time[0] as u32) << 16 | (time[1] as u32) << 8 | time[2] as u32;
+
+    for i in 0..MAXPLAYERS {
+        let player = &mut level.players_mut()[i];
+        player.mobj = None;
+        player.attacker = None;
+        player.message = None;
+        if level.players_in_game()[i] {
+            let record = inp.record(PLAYER_SIZE)?;
+            unarchive_player(&record, &mut level.players_mut()[i])?;
+        }
+    }
+
+    let flats = Flats::new(wad, pic_data);
+    unarchive_world(inp, level, &flats, pic_data.num_textures())?;
+
+    let capacity = level.map_data.things().len() * 2 + 256;
+    unsafe { level.clear_thinkers(capacity) };
+    unarchive_thinkers(inp, level)?;
+    unarchive_specials(inp, level, &flats)?;
+
+    level.button_list.clear();
+    level.respawn_queue.clear();
+
+    if inp.u8()? != SAVE_END {
+        return Err(SaveError::Corrupt("bad end marker"));
+    }
+    Ok(())
+}
+
+/// Doom function name `P_UnArchiveThinkers`
+fn unarchive_thinkers(inp: &mut SaveReader, level: &mut Level) -> Result<(), SaveError> {
+    loop {
+        match inp.u8()? {
+            TC_END => break,
+            TC_MOBJ => {
+                let record = inp.record(MOBJ_SIZE)?;
+                unarchive_mobj(&record, level)?;
+            }
+            _ => return Err(SaveError::Corrupt("unknown thinker class")),
+        }
+    }
+    Ok(())
+}
+
+/// Doom function name `P_UnArchiveSpecials`
+fn unarchive_specials(
+    inp: &mut SaveReader,
+    level: &mut Level,
+    flats: &Flats,
+) -> Result<(), SaveError> {
+    loop {
+        match inp.u8()? {
+            TC_ENDSPECIALS => break,
+            TC_CEILING => {
+                let r = inp.record(CEILING_SIZE)?;
+                let mut sector = sector_at(r.int(16) as usize, level)?;
+                if r.int(8) == 0 {
+                    warn!("Ceiling in stasis in sector {} will move", sector.num);
+                }
+                let ceiling = CeilingMove {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: variant(&CEIL_KINDS, r.int(12), "ceiling kind")?,
+                    bottomheight: r.fixed(20),
+                    topheight: r.fixed(24),
+                    speed: r.fixed(28),
+                    crush: r.boolean(32),
+                    direction: r.int(36),
+                    tag: r.int(40) as i16,
+                    olddirection: r.int(44),
+                };
+                let thinker = MapObject::create_thinker(
+                    ThinkerData::CeilingMove(ceiling),
+                    CeilingMove::think,
+                );
+                sector.specialdata = Some(push_thinker::<CeilingMove>(level, thinker)?);
+            }
+            TC_DOOR => {
+                let r = inp.record(DOOR_SIZE)?;
+                let mut sector = sector_at(r.int(16) as usize, level)?;
+                let door = VerticalDoor {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: variant(&DOOR_KINDS, r.int(12), "door kind")?,
+                    topheight: r.fixed(20),
+                    speed: r.fixed(24),
+                    direction: r.int(28),
+                    topwait: r.int(32),
+                    topcountdown: r.int(36),
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::VerticalDoor(door), VerticalDoor::think);
+                sector.specialdata = Some(push_thinker::<VerticalDoor>(level, thinker)?);
+            }
+            TC_FLOOR => {
+                let r = inp.record(FLOOR_SIZE)?;
+                let mut sector = sector_at(r.int(20) as usize, level)?;
+                let texture = flats.get(r.short(32)).unwrap_or(sector.floorpic);
+                let floor = FloorMove {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    kind: variant(&FLOOR_KINDS, r.int(12), "floor kind")?,
+                    crush: r.boolean(16),
+                    direction: r.int(24),
+                    newspecial: r.int(28) as i16,
+                    texture,
+                    destheight: r.fixed(36),
+                    speed: r.fixed(40),
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::FloorMove(floor), FloorMove::think);
+                sector.specialdata = Some(push_thinker::<FloorMove>(level, thinker)?);
+            }
+            TC_PLAT => {
+                let r = inp.record(PLAT_SIZE)?;
+                let mut sector = sector_at(r.int(12) as usize, level)?;
+                let plat = Platform {
+                    thinker: null_mut(),
+                    sector: sector.clone(),
+                    speed: r.fixed(16),
+                    low: r.fixed(20),
+                    high: r.fixed(24),
+                    wait: r.int(28),
+                    count: r.int(32),
+                    status: variant(&PLAT_STATUS, r.int(36), "platform status")?,
+                    old_status: variant(&PLAT_STATUS, r.int(40), "platform status")?,
+                    crush: r.boolean(44),
+                    tag: r.int(48) as i16,
+                    kind: variant(&PLAT_KINDS, r.int(52), "platform kind")?,
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::Platform(plat), Platform::think);
+                let ptr = push_thinker::<Platform>(level, thinker)?;
+                sector.specialdata = Some(ptr);
+                level.add_active_platform(unsafe { (*ptr).platform_mut() });
+            }
+            TC_FLASH => {
+                let r = inp.record(FLASH_SIZE)?;
+                let light = LightFlash {
+                    thinker: null_mut(),
+                    sector: sector_at(r.int(12) as usize, level)?,
+                    count: r.int(16),
+                    max_light: r.int(20) as usize,
+                    min_light: r.int(24) as usize,
+                    max_time: r.int(28),
+                    min_time: r.int(32),
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::LightFlash(light), LightFlash::think);
+                push_thinker::<LightFlash>(level, thinker)?;
+            }
+            TC_STROBE => {
+                let r = inp.record(STROBE_SIZE)?;
+                let light = StrobeFlash {
+                    thinker: null_mut(),
+                    sector: sector_at(r.int(12) as usize, level)?,
+                    count: r.int(16),
+                    min_light: r.int(20) as usize,
+                    max_light: r.int(24) as usize,
+                    dark_time: r.int(28),
+                    bright_time: r.int(32),
+                };
+                let thinker =
+                    MapObject::create_thinker(ThinkerData::StrobeFlash(light), StrobeFlash::think);
+                push_thinker::<StrobeFlash>(level, thinker)?;
+            }
+            TC_GLOW => {
+                let r = inp.record(GLOW_SIZE)?;
+                let light = Glow {
+                    thinker: null_mut(),
+                    sector: sector_at(r.int(12) as usize, level)?,
+                    min_light: r.int(16) as usize,
+                    max_light: r.int(20) as usize,
+                    direction: r.int(24),
+                };
+                let thinker = MapObject::create_thinker(ThinkerData::Glow(light), Glow::think);
+                push_thinker::<Glow>(level, thinker)?;
+            }
+            _ => return Err(SaveError::Corrupt("unknown special class")),
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the variant of a vanilla enum. The tables of the native format are in
+/// the same order.
+fn variant<T: Copy>(variants: &[T], n: i32, what: &'static str) -> Result<T, SaveError> {
+    usize::try_from(n)
+        .ok()
+        .and_then(|n| variants.get(n).copied())
+        .ok_or(SaveError::Corrupt(what))
+}
+
+/// Vanilla numbers flats by their lump between `F_START` and `F_END`, which
+/// includes any markers in between. This translates those numbers to the
+/// flats of `PicData`.
+struct Flats(Vec<Option<usize>>);
+
+impl Flats {
+    fn new(wad: &WadData, pic_data: &PicData) -> Self {
+        Self(
+            wad.lump_names_between("F_START", "F_END")
+                .iter()
+                .map(|name| pic_data.flat_num_for_name(name))
+                .collect(),
+        )
+    }
+
+    fn get(&self, num: i16) -> Option<usize> {
+        let flat = usize::try_from(num)
+            .ok()
+            .and_then(|n| self.0.get(n).copied().flatten());
+        if flat.is_none() {
+            warn!("Flat {num} of the save is not a flat");
+        }
+        flat
+    }
+}
+
+fn unarchive_player(r: &Record, player: &mut Player) -> Result<(), SaveError> {
+    player.player_state = variant(&PLAYER_STATES, r.int(4), "player state")?;
+    player.viewz = r.fixed(16);
+    player.viewheight = r.fixed(20);
+    player.deltaviewheight = r.fixed(24);
+    player.bob = r.fixed(28);
+    // Vanilla has no free-look or crouching
+    player.pitch = 0.0;
+    player.crouch = 0.0;
+
+    let status = &mut player.status;
+    status.health = r.int(32);
+    status.armorpoints = r.int(36);
+    status.armortype = r.int(40);
+    for (i, power) in status.powers.iter_mut().enumerate() {
+        *power = r.int(44 + i * 4);
+    }
+    for (i, card) in status.cards.iter_mut().enumerate() {
+        *card = r.boolean(68 + i * 4);
+    }
+    status.backpack = r.boolean(92);
+    status.readyweapon = weapon(r.int(112))?;
+    for (i, owned) in status.weaponowned.iter_mut().enumerate() {
+        *owned = r.boolean(120 + i * 4);
+    }
+    for (i, ammo) in status.ammo.iter_mut().enumerate() {
+        *ammo = r.int(156 + i * 4).max(0) as u32;
+    }
+    for (i, ammo) in status.maxammo.iter_mut().enumerate() {
+        *ammo = r.int(172 + i * 4).max(0) as u32;
+    }
+    status.attackdown = r.boolean(188);
+    status.usedown = r.boolean(192);
+    status.cheats = r.int(196) as u32;
+    status.damagecount = r.int(220);
+    status.bonuscount = r.int(224);
+
+    for (i, frags) in player.frags.iter_mut().enumerate() {
+        *frags = r.int(96 + i * 4);
+    }
+    let pending = r.int(116);
+    player.pendingweapon = if pending == WeaponType::NoChange as i32 {
+        WeaponType::NoChange
+    } else {
+        weapon(pending)?
+    };
+    player.refire = r.int(200);
+    player.total_kills = r.int(204);
+    player.items_collected = r.int(208);
+    player.secrets_found = r.int(212);
+    player.extralight = r.int(232).max(0) as usize;
+    player.fixedcolormap = r.int(236);
+    if r.int(240) != 0 {
+        warn!("Player colour translations are not supported");
+    }
+    for (i, psp) in player.psprites.iter_mut().enumerate() {
+        let at = 244 + i * 16;
+        // Vanilla writes a null state as 0, the same as `S_NULL`
+        psp.state = match r.int(at) {
+            0 => None,
+            n => Some(state_at(n as usize)?),
+        };
+        psp.tics = r.int(at + 4);
+        psp.sx = r.fixed(at + 8);
+        psp.sy = r.fixed(at + 12);
+    }
+    player.didsecret = r.boolean(276);
+    Ok(())
+}
+
+fn weapon(n: i32) -> Result<WeaponType, SaveError> {
+    u8::try_from(n)
+        .map_err(|_| SaveError::Corrupt("weapon out of range"))
+        .and_then(super::weapon)
+}
+
+/// Doom function name `P_UnArchiveWorld`
+fn unarchive_world(
+    inp: &mut SaveReader,
+    level: &mut Level,
+    flats: &Flats,
+    num_textures: usize,
+) -> Result<(), SaveError> {
+    let map = &mut level.map_data;
+    for sector in map.sectors_mut() {
+        sector.floorheight = inp.i16()? as f32;
+        sector.ceilingheight = inp.i16()? as f32;
+        if let Some(flat) = flats.get(inp.i16()?) {
+            sector.floorpic = flat;
+        }
+        if let Some(flat) = flats.get(inp.i16()?) {
+            sector.ceilingpic = flat;
+        }
+        sector.lightlevel = inp.i16()?.max(0) as usize;
+        sector.special = inp.i16()?;
+        sector.tag = inp.i16()?;
+    }
+
+    let texture = |n: i16| -> Option<usize> {
+        // 0 is the texture used for no texture
+        match usize::try_from(n) {
+            Ok(0) => None,
+            Ok(n) if n < num_textures => Some(n),
+            _ => {
+                warn!("Texture {n} of the save is not a texture");
+                None
+            }
+        }
+    };
+    for line in map.linedefs.iter_mut() {
+        line.flags = inp.i16()? as u16 as u32;
+        line.special = inp.i16()?;
+        line.tag = inp.i16()?;
+
+        let mut sides = vec![line.front_sidedef.clone()];
+        sides.extend(line.back_sidedef.clone());
+        for mut side in sides {
+            side.textureoffset = inp.i16()? as f32;
+            side.rowoffset = inp.i16()? as f32;
+            side.toptexture = texture(inp.i16()?);
+            side.bottomtexture = texture(inp.i16()?);
+            side.midtexture = texture(inp.i16()?);
+        }
+    }
+    Ok(())
+}
+
+fn unarchive_mobj(r: &Record, level: &mut Level) -> Result<*mut Thinker, SaveError> {
+    let kind = r.int(88);
+    if !(0..MapObjKind::Count as i32).contains(&kind) {
+        return Err(SaveError::Corrupt("MapObject kind out of range"));
+    }
+    let (x, y, z) = (r.fixed(12), r.fixed(16), r.fixed(20));
+    let mobj = unsafe {
+        &mut *MapObject::spawn_map_object(x, y, z as i32, MapObjKind::from(kind as u16), level)
+    };
+    mobj.xyz.z = z;
+    mobj.angle = Angle::new(bam_to_radian(r.int(32) as u32));
+    mobj.frame = r.int(40) as u32;
+    mobj.floorz = r.fixed(56);
+    mobj.ceilingz = r.fixed(60);
+    mobj.radius = r.fixed(64);
+    mobj.height = r.fixed(68);
+    mobj.momxyz.x = r.fixed(72);
+    mobj.momxyz.y = r.fixed(76);
+    mobj.momxyz.z = r.fixed(80);
+    // The exact values, which the f32 fields mirror in the fixed-point mode
+    mobj.fixed = FixedState {
+        x: r.int(12),
+        y: r.int(16),
+        z: r.int(20),
+        momx: r.int(72),
+        momy: r.int(76),
+        momz: r.int(80),
+        angle: r.int(32) as u32,
+    };
+    mobj.tics = r.int(96);
+    mobj.state = state_at(r.int(100) as usize)?;
+    mobj.sprite = mobj.state.sprite;
+    mobj.flags = r.int(104) as u32;
+    mobj.health = r.int(108);
+    let movedir = r.int(112);
+    if !(0..MoveDir::NumDirs as i32).contains(&movedir) {
+        return Err(SaveError::Corrupt("move direction out of range"));
+    }
+    mobj.movedir = MoveDir::from(movedir as usize);
+    mobj.movecount = r.int(116);
+    mobj.reactiontime = r.int(124);
+    mobj.threshold = r.int(128);
+    mobj.lastlook = r.int(136);
+    mobj.spawnpoint = WadThing::new(
+        r.short(140),
+        r.short(142),
+        r.short(144),
+        r.short(146),
+        r.short(148),
+    );
+
+    // Saved as the player number + 1
+    let player = r.int(132);
+    if player != 0 {
+        let p = player as usize - 1;
+        if p >= MAXPLAYERS {
+            return Err(SaveError::Corrupt("player out of range"));
+        }
+        let player = &mut level.players_mut()[p];
+        player.set_mobj(mobj);
+        mobj.player = Some(player);
+    }
+    Ok(mobj.thinker)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use std::sync::mpsc::channel;
+
+    use glam::Vec3;
+
+    use super::{
+        read_header, unarchive_specials, unarchive_thinkers, unarchive_world, Flats, CEILING_SIZE,
+        DOOR_SIZE, FLASH_SIZE, FLOOR_SIZE, GLOW_SIZE, MOBJ_SIZE, PLAT_SIZE, STROBE_SIZE,
+        TC_CEILING, TC_DOOR, TC_END, TC_ENDSPECIALS, TC_FLASH, TC_FLOOR, TC_GLOW, TC_MOBJ, TC_PLAT,
+        TC_STROBE,
+    };
+    use crate::doom_def::GameMode;
+    use crate::env::ceiling::CeilKind;
+    use crate::env::doors::DoorKind;
+    use crate::env::floor::FloorKind;
+    use crate::env::platforms::{PlatKind, PlatStatus};
+    use crate::info::{MapObjKind, StateNum, STATES};
+    use crate::level::map_data::MapData;
+    use crate::level::map_defs::{BBox, LineDef, Sector, SideDef, SlopeType};
+    use crate::level::Level;
+    use crate::save::{SaveError, SaveReader};
+    use crate::thing::enemy_movement::MoveDir;
+    use crate::thinker::ThinkerData;
+    use crate::{GameOptions, MapPtr, Player, Random, Skill, MAXPLAYERS};
+
+    fn header(version: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut description = b"E1M3 SECRET".to_vec();
+        description.resize(24, 0);
+        data.extend(description);
+        let mut version = version.as_bytes().to_vec();
+        version.resize(16, 0);
+        data.extend(version);
+        data.extend([3, 1, 3, 1, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn vanilla_header() {
+        let data = header("version 109");
+        let mut inp = SaveReader::new(&data);
+        let header = read_header(&mut inp).unwrap();
+        assert_eq!(header.description, "E1M3 SECRET");
+        assert_eq!(header.skill, Skill::Hard);
+        assert_eq!((header.episode, header.map), (1, 3));
+        assert_eq!(header.players_in_game, [true, false, false, false]);
+    }
+
+    #[test]
+    fn vanilla_header_errors() {
+        let data = header("version 105");
+        let mut inp = SaveReader::new(&data);
+        assert!(matches!(
+            read_header(&mut inp),
+            Err(SaveError::VanillaVersion(v)) if v == "version 105"
+        ));
+
+        let data = header("R4DSAVE");
+        let mut inp = SaveReader::new(&data);
+        assert!(matches!(read_header(&mut inp), Err(SaveError::NotASave)));
+    }
+
+    fn level(players: &mut [Player; MAXPLAYERS]) -> Level {
+        let (tx, _rx) = channel();
+        let mut level = unsafe {
+            Level::new_empty(
+                GameOptions::default(),
+                GameMode::Shareware,
+                tx,
+                &[true, false, false, false],
+                players,
+                Random::new(),
+            )
+        };
+        level.map_data = MapData::with_sectors(
+            (0..3)
+                .map(|i| Sector::new(i, 0.0, 128.0, 0, 0, 160, 0, 0))
+                .collect(),
+        );
+        unsafe { level.clear_thinkers(16) };
+        level
+    }
+
+    fn shorts(data: &mut Vec<u8>, values: &[i16]) {
+        for v in values {
+            data.extend(v.to_le_bytes());
+        }
+    }
+
+    /// Add a thinker of `class` as vanilla writes it, padded to 4 bytes, with
+    /// the ints at the offsets of `fields` set
+    fn record(data: &mut Vec<u8>, class: u8, size: usize, fields: &[(usize, i32)]) {
+        data.push(class);
+        data.resize((data.len() + 3) & !3, 0);
+        let at = data.len();
+        data.resize(at + size, 0);
+        for &(offset, value) in fields {
+            data[at + offset..at + offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn line(sides: &mut [SideDef], sector: &mut Sector) -> LineDef {
+        let (front, back) = sides.split_at_mut(1);
+        LineDef {
+            v1: Vec3::ZERO,
+            v2: Vec3::new(64.0, 0.0, 0.0),
+            delta: Vec3::new(64.0, 0.0, 0.0),
+            flags: 0,
+            special: 0,
+            tag: 0,
+            bbox: BBox::default(),
+            slopetype: SlopeType::Horizontal,
+            sides: [0, 1],
+            front_sidedef: MapPtr::new(&mut front[0]),
+            back_sidedef: back.first_mut().map(MapPtr::new),
+            frontsector: MapPtr::new(sector),
+            backsector: None,
+            valid_count: 0,
+            translucent: false,
+        }
+    }
+
+    fn side(sector: &mut Sector) -> SideDef {
+        SideDef {
+            textureoffset: 0.0,
+            rowoffset: 0.0,
+            toptexture: None,
+            bottomtexture: None,
+            midtexture: Some(1),
+            sector: MapPtr::new(sector),
+        }
+    }
+
+    #[test]
+    fn vanilla_world() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+        let mut sector = Sector::new(0, 0.0, 128.0, 0, 0, 160, 0, 0);
+        let mut sides = [side(&mut sector), side(&mut sector)];
+        let two_sided = line(&mut sides, &mut sector);
+        let mut one_side = [side(&mut sector)];
+        let one_sided = line(&mut one_side, &mut sector);
+        level.map_data.linedefs = vec![two_sided, one_sided];
+
+        let mut data = Vec::new();
+        // floor, ceiling, floorpic, ceilingpic, light, special, tag
+        shorts(&mut data, &[-16, 72, 2, 0, 144, 9, 5]);
+        shorts(&mut data, &[0, 128, 1, 1, 160, 0, 0]);
+        shorts(&mut data, &[8, 8, 0, 2, -1, 0, 0]);
+        // flags, special, tag, then offsets and top, bottom, middle per side
+        shorts(&mut data, &[0x0c, 1, 0, 16, -8, 3, 0, 4, 0, 0, 0, 0, 9]);
+        shorts(&mut data, &[0x01, 97, 7, 0, 0, 0, 0, 0]);
+        let flats = Flats(vec![Some(5), None, Some(7)]);
+        let mut inp = SaveReader::new(&data);
+        unarchive_world(&mut inp, &mut level, &flats, 5).unwrap();
+        assert_eq!(inp.pos, data.len());
+
+        let sectors = level.map_data.sectors();
+        assert_eq!(sectors[0].floorheight, -16.0);
+        assert_eq!(sectors[0].ceilingheight, 72.0);
+        assert_eq!((sectors[0].floorpic, sectors[0].ceilingpic), (7, 5));
+        assert_eq!(sectors[0].lightlevel, 144);
+        assert_eq!((sectors[0].special, sectors[0].tag), (9, 5));
+        // flats that aren't in `PicData` are left as they were
+        assert_eq!((sectors[1].floorpic, sectors[1].ceilingpic), (0, 0));
+        assert_eq!((sectors[2].floorpic, sectors[2].ceilingpic), (5, 7));
+        assert_eq!(sectors[2].lightlevel, 0);
+
+        let lines = &level.map_data.linedefs;
+        assert_eq!(
+            (lines[0].flags, lines[0].special, lines[0].tag),
+            (0x0c, 1, 0)
+        );
+        assert_eq!(
+            (lines[1].flags, lines[1].special, lines[1].tag),
+            (0x01, 97, 7)
+        );
+        let front = &lines[0].front_sidedef;
+        assert_eq!((front.textureoffset, front.rowoffset), (16.0, -8.0));
+        assert_eq!(front.toptexture, Some(3));
+        // 0 is no texture
+        assert_eq!(front.bottomtexture, None);
+        assert_eq!(front.midtexture, Some(4));
+        let back = lines[0].back_sidedef.as_ref().unwrap();
+        // textures past the end of `PicData` are dropped
+        assert_eq!(back.midtexture, None);
+        assert_eq!(lines[1].front_sidedef.midtexture, None);
+    }
+
+    #[test]
+    fn vanilla_mobj() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+
+        let state = StateNum::POSS_RUN1 as i32;
+        let mut data = Vec::new();
+        record(
+            &mut data,
+            TC_MOBJ,
+            MOBJ_SIZE,
+            &[
+                (12, 96 << 16),
+                (16, -32 << 16 | 0x8000),
+                (20, 24 << 16),
+                (32, 0x4000_0000),
+                (40, 3),
+                (56, 8 << 16),
+                (60, 120 << 16),
+                (64, 20 << 16),
+                (68, 56 << 16),
+                (72, 1 << 16),
+                (76, -2 << 16),
+                (80, 1 << 15),
+                (88, MapObjKind::MT_POSSESSED as i32),
+                (96, 7),
+                (100, state),
+                (104, 0x0040_0006),
+                (108, 15),
+                (112, MoveDir::North as i32),
+                (116, 4),
+                (124, 2),
+                (128, 9),
+                (132, 0),
+                (136, 1),
+                (140, -32 << 16 | 96),
+                (144, 90 | 3004 << 16),
+                (148, 7),
+            ],
+        );
+        data.push(TC_END);
+        let mut inp = SaveReader::new(&data);
+        unarchive_thinkers(&mut inp, &mut level).unwrap();
+        assert_eq!(inp.pos, data.len());
+
+        let mobjs: Vec<_> = level.thinkers.iter().filter(|t| t.is_mobj()).collect();
+        assert_eq!(mobjs.len(), 1);
+        let mobj = mobjs[0].mobj();
+        assert!(matches!(mobj.kind, MapObjKind::MT_POSSESSED));
+        assert_eq!(mobj.xyz, Vec3::new(96.0, -31.5, 24.0));
+        assert_eq!((mobj.fixed.x, mobj.fixed.y), (96 << 16, -32 << 16 | 0x8000));
+        assert_eq!(mobj.fixed.angle, 0x4000_0000);
+        assert!((mobj.angle.rad() - FRAC_PI_2).abs() < 1e-6);
+        assert_eq!((mobj.floorz, mobj.ceilingz), (8.0, 120.0));
+        assert_eq!((mobj.radius, mobj.height), (20.0, 56.0));
+        assert_eq!(mobj.momxyz, Vec3::new(1.0, -2.0, 0.5));
+        assert_eq!(mobj.frame, 3);
+        assert_eq!(mobj.tics, 7);
+        assert!(std::ptr::eq(mobj.state, &STATES[state as usize]));
+        assert_eq!(mobj.flags, 0x0040_0006);
+        assert_eq!(mobj.health, 15);
+        assert!(matches!(mobj.movedir, MoveDir::North));
+        assert_eq!((mobj.movecount, mobj.reactiontime), (4, 2));
+        assert_eq!((mobj.threshold, mobj.lastlook), (9, 1));
+        let spawn = mobj.spawnpoint;
+        assert_eq!((spawn.x, spawn.y, spawn.angle), (96, -32, 90));
+        assert_eq!((spawn.kind, spawn.flags), (3004, 7));
+        assert!(mobj.player.is_none());
+    }
+
+    #[test]
+    fn vanilla_mobj_errors() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+        let kind = MapObjKind::MT_POSSESSED as i32;
+
+        for (fields, error) in [
+            (vec![(88, -1)], "MapObject kind out of range"),
+            (vec![(88, kind), (100, i32::MAX)], "state out of range"),
+            (vec![(88, kind), (112, 9)], "move direction out of range"),
+            (vec![(88, kind), (132, 5)], "player out of range"),
+        ] {
+            let mut data = Vec::new();
+            record(&mut data, TC_MOBJ, MOBJ_SIZE, &fields);
+            data.push(TC_END);
+            let mut inp = SaveReader::new(&data);
+            assert!(matches!(
+                unarchive_thinkers(&mut inp, &mut level),
+                Err(SaveError::Corrupt(e)) if e == error
+            ));
+        }
+    }
+
+    #[test]
+    fn vanilla_specials() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+
+        let mut data = Vec::new();
+        // ceiling: active, kind, sector, bottom, top, speed, crush, direction,
+        // tag, olddirection
+        #[rustfmt::skip]
+        record(&mut data, TC_CEILING, CEILING_SIZE, &[
+            (8, 1), (12, 3), (16, 0), (20, 8 << 16), (24, 128 << 16), (28, 1 << 16),
+            (32, 1), (36, -1), (40, 12), (44, 1),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_DOOR, DOOR_SIZE, &[
+            (12, 5), (16, 1), (20, 124 << 16), (24, 8 << 16), (28, 1), (32, 150), (36, 35),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_FLOOR, FLOOR_SIZE, &[
+            (12, 6), (16, 0), (20, 2), (24, -1), (28, 11), (32, 2), (36, -8 << 16),
+            (40, 1 << 16),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_PLAT, PLAT_SIZE, &[
+            (12, 0), (16, 4 << 16), (20, -24 << 16), (24, 16 << 16), (28, 105), (32, 30),
+            (36, 2), (40, 0), (44, 0), (48, 3), (52, 1),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_FLASH, FLASH_SIZE, &[
+            (12, 1), (16, 4), (20, 200), (24, 96), (28, 64), (32, 7),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_STROBE, STROBE_SIZE, &[
+            (12, 2), (16, 3), (20, 80), (24, 192), (28, 35), (32, 5),
+        ]);
+        #[rustfmt::skip]
+        record(&mut data, TC_GLOW, GLOW_SIZE, &[
+            (12, 2), (16, 48), (20, 176), (24, -1),
+        ]);
+        data.push(TC_ENDSPECIALS);
+        let flats = Flats(vec![Some(5), None, Some(7)]);
+        let mut inp = SaveReader::new(&data);
+        unarchive_specials(&mut inp, &mut level, &flats).unwrap();
+        assert_eq!(inp.pos, data.len());
+
+        let mut classes = Vec::new();
+        for thinker in level.thinkers.iter() {
+            match thinker.data() {
+                ThinkerData::CeilingMove(c) => {
+                    classes.push(TC_CEILING);
+                    assert_eq!(c.sector.num, 0);
+                    assert!(matches!(c.kind, CeilKind::CrushAndRaise));
+                    assert_eq!((c.bottomheight, c.topheight, c.speed), (8.0, 128.0, 1.0));
+                    assert!(c.crush);
+                    assert_eq!((c.direction, c.tag, c.olddirection), (-1, 12, 1));
+                }
+                ThinkerData::VerticalDoor(d) => {
+                    classes.push(TC_DOOR);
+                    assert_eq!(d.sector.num, 1);
+                    assert!(matches!(d.kind, DoorKind::BlazeRaise));
+                    assert_eq!((d.topheight, d.speed), (124.0, 8.0));
+                    assert_eq!((d.direction, d.topwait, d.topcountdown), (1, 150, 35));
+                }
+                ThinkerData::FloorMove(f) => {
+                    classes.push(TC_FLOOR);
+                    assert_eq!(f.sector.num, 2);
+                    assert!(matches!(f.kind, FloorKind::LowerAndChange));
+                    assert!(!f.crush);
+                    assert_eq!((f.direction, f.newspecial, f.texture), (-1, 11, 7));
+                    assert_eq!((f.destheight, f.speed), (-8.0, 1.0));
+                }
+                ThinkerData::Platform(p) => {
+                    classes.push(TC_PLAT);
+                    assert_eq!(p.sector.num, 0);
+                    assert_eq!((p.speed, p.low, p.high), (4.0, -24.0, 16.0));
+                    assert_eq!((p.wait, p.count), (105, 30));
+                    assert_eq!(
+                        (p.status, p.old_status),
+                        (PlatStatus::Waiting, PlatStatus::Up)
+                    );
+                    assert!(!p.crush);
+                    assert_eq!(p.tag, 3);
+                    assert!(matches!(p.kind, PlatKind::DownWaitUpStay));
+                }
+                ThinkerData::LightFlash(l) => {
+                    classes.push(TC_FLASH);
+                    assert_eq!((l.sector.num, l.count), (1, 4));
+                    assert_eq!((l.max_light, l.min_light), (200, 96));
+                    assert_eq!((l.max_time, l.min_time), (64, 7));
+                }
+                ThinkerData::StrobeFlash(l) => {
+                    classes.push(TC_STROBE);
+                    assert_eq!((l.sector.num, l.count), (2, 3));
+                    assert_eq!((l.min_light, l.max_light), (80, 192));
+                    assert_eq!((l.dark_time, l.bright_time), (35, 5));
+                }
+                ThinkerData::Glow(l) => {
+                    classes.push(TC_GLOW);
+                    assert_eq!(l.sector.num, 2);
+                    assert_eq!((l.min_light, l.max_light, l.direction), (48, 176, -1));
+                }
+                _ => panic!("unexpected thinker"),
+            }
+        }
+        assert_eq!(
+            classes,
+            [TC_CEILING, TC_DOOR, TC_FLOOR, TC_PLAT, TC_FLASH, TC_STROBE, TC_GLOW]
+        );
+
+        // The movers are the special of their sector
+        let sectors = level.map_data.sectors();
+        assert!(sectors.iter().all(|s| s.specialdata.is_some()));
+    }
+
+    #[test]
+    fn vanilla_special_errors() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+        let flats = Flats(Vec::new());
+
+        for (class, size, fields, error) in [
+            (TC_CEILING, CEILING_SIZE, vec![(12, 6)], "ceiling kind"),
+            (TC_DOOR, DOOR_SIZE, vec![(16, 3)], "sector out of range"),
+            (TC_PLAT, PLAT_SIZE, vec![(36, 4)], "platform status"),
+            (TC_GLOW + 2, 4, vec![], "unknown special class"),
+        ] {
+            let mut data = Vec::new();
+            record(&mut data, class, size, &fields);
+            data.push(TC_ENDSPECIALS);
+            let mut inp = SaveReader::new(&data);
+            assert!(matches!(
+                unarchive_specials(&mut inp, &mut level, &flats),
+                Err(SaveError::Corrupt(e)) if e == error
+            ));
+        }
+    }
+}
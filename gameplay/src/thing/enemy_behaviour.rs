@@ -11,20 +11,26 @@ use std::ptr;
 
 use log::trace;
 use sound_traits::SfxName;
+use wad::umapinfo::BossAction;
 
 use crate::doom_def::{MISSILERANGE, SKULLSPEED};
 use crate::env::doors::{ev_do_door, DoorKind};
 use crate::env::floor::{ev_do_floor, FloorKind};
+use crate::env::specials::cross_special_line;
+use crate::env::switch::p_use_special_line;
+use crate::fixed::{ANG180, ANG270, ANG90};
 use crate::info::{StateNum, MOBJINFO};
 use crate::level::map_defs::{LineDef, SlopeType};
+use crate::level::Level;
 use crate::thing::{MapObjFlag, MapObject};
 use crate::thinker::{Thinker, ThinkerData};
-use crate::utilities::{p_random, point_to_angle_2, PortalZ};
+use crate::utilities::{point_to_angle_2, PortalZ};
 use crate::{
     teleport_move, Angle, GameMode, LineDefFlags, MapObjKind, MapPtr, Sector, Skill, MAXPLAYERS
 };
 
 use super::enemy_movement::MoveDir;
+use super::fixed_enemy::FATSPREAD;
 use super::movement::SubSectorMinMax;
 
 /// This was only ever called with the player as the target, so it never follows
@@ -82,13 +88,18 @@ fn sound_flood(
 /// A_FaceTarget
 pub(crate) fn a_facetarget(actor: &mut MapObject) {
     actor.flags &= !(MapObjFlag::Ambush as u32);
+    if actor.level().options.fixed_point {
+        actor.fixed_face_target();
+        return;
+    }
 
     let xyz = actor.xyz;
     let mut angle = actor.angle;
     if let Some(target) = actor.target_mut() {
         angle = point_to_angle_2(target.xyz, xyz);
         if target.flags & MapObjFlag::Shadow as u32 == MapObjFlag::Shadow as u32 {
-            actor.angle += (((p_random() - p_random()) >> 4) as f32).to_radians();
+            let random = unsafe { &mut (*actor.level).random };
+            actor.angle += (((random.p_random() - random.p_random()) >> 4) as f32).to_radians();
         }
     }
     actor.angle = angle;
@@ -97,6 +108,11 @@ pub(crate) fn a_facetarget(actor: &mut MapObject) {
 /// Actor has a melee attack,
 /// so it tries to close as fast as possible
 pub(crate) fn a_chase(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_chase();
+        return;
+    }
+
     if actor.reactiontime > 0 {
         actor.reactiontime -= 1;
     }
@@ -192,7 +208,7 @@ pub(crate) fn a_chase(actor: &mut MapObject) {
     }
 
     // make active sound
-    if actor.info.activesound != SfxName::None && p_random() < 3 {
+    if actor.info.activesound != SfxName::None && actor.p_random() < 3 {
         actor.start_sound(actor.info.activesound);
     }
 }
@@ -210,7 +226,11 @@ pub(crate) fn a_look(actor: &mut MapObject) {
     // }
 
     let ss = actor.subsector.clone();
-    if let Some(target) = ss.sector.sound_target() {
+    if actor.level().options.fixed_point {
+        if !actor.fixed_look() {
+            return;
+        }
+    } else if let Some(target) = ss.sector.sound_target() {
         if target.flags & MapObjFlag::Shootable as u32 != 0 {
             actor.target = actor.subsector.sector.sound_target_raw();
 
@@ -227,10 +247,10 @@ pub(crate) fn a_look(actor: &mut MapObject) {
     if actor.info.seesound != SfxName::None {
         let sound = match actor.info.seesound {
             SfxName::Posit1 | SfxName::Posit2 | SfxName::Posit3 => {
-                SfxName::from((SfxName::Posit1 as i32 + p_random() % 3) as u8)
+                SfxName::from((SfxName::Posit1 as i32 + actor.p_random() % 3) as u8)
             }
             SfxName::Bgsit1 | SfxName::Bgsit2 => {
-                SfxName::from((SfxName::Bgsit1 as i32 + p_random() % 3) as u8)
+                SfxName::from((SfxName::Bgsit1 as i32 + actor.p_random() % 3) as u8)
             }
             _ => actor.info.seesound,
         };
@@ -248,6 +268,10 @@ pub(crate) fn a_look(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_fire(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_fire();
+        return;
+    }
     if let Some(dest) = actor.tracer {
         let dest = unsafe { (*dest).mobj() };
         if let Some(targ) = actor.target_mut() {
@@ -269,10 +293,10 @@ pub(crate) fn a_scream(actor: &mut MapObject) {
     let sound = match actor.info.deathsound {
         SfxName::None => return,
         SfxName::Podth1 | SfxName::Podth2 | SfxName::Podth3 => {
-            SfxName::from(SfxName::Podth1 as u8 + (p_random() % 3) as u8)
+            SfxName::from(SfxName::Podth1 as u8 + (actor.p_random() % 3) as u8)
         }
         SfxName::Bgdth1 | SfxName::Bgdth2 => {
-            SfxName::from(SfxName::Bgdth1 as u8 + (p_random() % 2) as u8)
+            SfxName::from(SfxName::Bgdth1 as u8 + (actor.p_random() % 2) as u8)
         }
         _ => SfxName::from(actor.info.deathsound as u8),
     };
@@ -335,6 +359,7 @@ pub(crate) fn a_keendie(actor: &mut MapObject) {
         frontsector: sector,
         backsector: None,
         valid_count: 0,
+        translucent: false,
         sides: [0, 0],
     };
     ev_do_door(MapPtr::new(&mut junk), DoorKind::BlazeOpen, level);
@@ -378,8 +403,13 @@ pub(crate) fn a_braindie(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_brainspit(actor: &mut MapObject) {
-    let skill = unsafe { (*actor.level).options.skill };
-    if skill == Skill::Baby {
+    let level = unsafe { &mut *actor.level };
+    if level.options.fixed_point {
+        level.brain_spit_easy = !level.brain_spit_easy;
+        if level.options.skill <= Skill::Easy && !level.brain_spit_easy {
+            return;
+        }
+    } else if level.options.skill == Skill::Baby {
         return;
     }
     // spooge a cube at the thing
@@ -387,7 +417,7 @@ pub(crate) fn a_brainspit(actor: &mut MapObject) {
     actor.boss_target_on = (actor.boss_target_on + 1) % actor.boss_targets.len();
 
     let target_xy = target_thinker.mobj_mut().xyz;
-    let level = unsafe { &mut *actor.level };
+    let (_, target_y, _) = target_thinker.mobj().fixed_pos();
     let m = MapObject::spawn_missile(
         actor,
         target_thinker.mobj_mut(),
@@ -395,7 +425,12 @@ pub(crate) fn a_brainspit(actor: &mut MapObject) {
         level,
     );
     m.target = Some(target_thinker);
-    m.reactiontime = ((target_xy.y - actor.xyz.y) / m.momxyz.y) as i32 / m.state.tics;
+    m.reactiontime = if level.options.fixed_point {
+        let (_, y, _) = actor.fixed_pos();
+        (target_y - y).checked_div(m.fixed.momy).unwrap_or(0) / m.state.tics
+    } else {
+        ((target_xy.y - actor.xyz.y) / m.momxyz.y) as i32 / m.state.tics
+    };
 
     actor.start_sound(SfxName::Bospit);
 }
@@ -405,17 +440,21 @@ pub(crate) fn a_brainpain(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_brainscream(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_brain_scream();
+        return;
+    }
     let mut x = actor.xyz.x as i32 - 196;
     while x < actor.xyz.x as i32 + 320 {
         let y = actor.xyz.y - 320.0;
-        let z = 128 + p_random();
+        let z = 128 + actor.p_random();
         let level = unsafe { &mut *actor.level };
         let th = MapObject::spawn_map_object(x as f32, y, z, MapObjKind::MT_ROCKET, level);
         unsafe {
             let th = &mut (*th);
-            th.momxyz.z = (p_random() as f32 / 64.0).ceil();
+            th.momxyz.z = (actor.p_random() as f32 / 64.0).ceil();
             th.set_state(StateNum::BRAINEXPLODE1);
-            th.tics -= p_random() & 7;
+            th.tics -= actor.p_random() & 7;
             if th.tics < 1 {
                 th.tics = 1;
             }
@@ -426,16 +465,20 @@ pub(crate) fn a_brainscream(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_brainexplode(actor: &mut MapObject) {
-    let x = actor.xyz.x + (p_random() - p_random()) as f32 * 2.0;
+    if actor.level().options.fixed_point {
+        actor.fixed_brain_explode();
+        return;
+    }
+    let x = actor.xyz.x + (actor.p_random() - actor.p_random()) as f32 * 2.0;
     let y = actor.xyz.y;
-    let z = 128 + p_random();
+    let z = 128 + actor.p_random();
     let level = unsafe { &mut *actor.level };
     let th = MapObject::spawn_map_object(x, y, z, MapObjKind::MT_ROCKET, level);
     unsafe {
         let th = &mut (*th);
-        th.momxyz.z = (p_random() as f32 / 64.0).ceil();
+        th.momxyz.z = (actor.p_random() as f32 / 64.0).ceil();
         th.set_state(StateNum::BRAINEXPLODE1);
-        th.tics -= p_random() & 7;
+        th.tics -= actor.p_random() & 7;
         if th.tics < 1 {
             th.tics = 1;
         }
@@ -443,26 +486,27 @@ pub(crate) fn a_brainexplode(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_spawnfly(actor: &mut MapObject) {
+    let level = unsafe { &mut *actor.level };
+    let fixed = level.options.fixed_point;
     actor.reactiontime -= 1;
-    if actor.reactiontime > 0 {
-        return;
+    if actor.reactiontime > 0 || fixed && actor.reactiontime != 0 {
+        return; // still flying
     }
 
-    let level = unsafe { &mut *actor.level };
     if let Some(target) = actor.target() {
         let xyz = target.xyz;
-        let fog = unsafe {
-            &mut *MapObject::spawn_map_object(
-                xyz.x,
-                xyz.y,
-                target.xyz.z as i32,
-                MapObjKind::MT_SPAWNFIRE,
-                level,
-            )
+        let (x, y, z) = target.fixed_pos();
+        let spawn = |kind, level: &mut Level| {
+            if fixed {
+                MapObject::spawn_map_object_fixed(x, y, z, kind, level)
+            } else {
+                MapObject::spawn_map_object(xyz.x, xyz.y, xyz.z as i32, kind, level)
+            }
         };
+        let fog = unsafe { &mut *spawn(MapObjKind::MT_SPAWNFIRE, level) };
         fog.start_sound(SfxName::Telept);
 
-        let r = p_random();
+        let r = level.random.p_random();
         let t = if r < 50 {
             MapObjKind::MT_TROOP
         } else if r < 90 {
@@ -487,13 +531,15 @@ pub(crate) fn a_spawnfly(actor: &mut MapObject) {
             MapObjKind::MT_BRUISER
         };
 
-        let new_critter = unsafe {
-            &mut *MapObject::spawn_map_object(xyz.x, xyz.y, target.xyz.z as i32, t, level)
-        };
+        let new_critter = unsafe { &mut *spawn(t, level) };
         if new_critter.look_for_players(true) {
             new_critter.set_state(new_critter.info.seestate);
         }
-        teleport_move(xyz, new_critter, level);
+        if fixed {
+            new_critter.fixed_teleport_move(x, y);
+        } else {
+            teleport_move(xyz, new_critter, level);
+        }
         actor.remove();
     }
 }
@@ -541,6 +587,13 @@ fn vile_raise_check(actor: &mut MapObject, obj: &mut MapObject) -> bool {
 }
 
 pub(crate) fn a_vilechase(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        if !actor.fixed_vile_raise() {
+            a_chase(actor);
+        }
+        return;
+    }
+
     if actor.movedir != MoveDir::None {
         // look for corpses
         let mut ss = actor.subsector.clone();
@@ -577,6 +630,10 @@ pub(crate) fn a_vilechase(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_viletarget(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_vile_target();
+        return;
+    }
     if let Some(target) = actor.target {
         let targ = unsafe { (*target).mobj_mut() };
         a_facetarget(actor);
@@ -598,6 +655,10 @@ pub(crate) fn a_viletarget(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_vileattack(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_vile_attack();
+        return;
+    }
     if let Some(targ) = actor.target {
         let targ = unsafe { (*targ).mobj_mut() };
         a_facetarget(actor);
@@ -630,9 +691,8 @@ pub(crate) fn a_posattack(actor: &mut MapObject) {
 
     actor.start_sound(SfxName::Pistol);
 
-    let mut angle = actor.angle;
-    angle += (((p_random() - p_random()) >> 4) as f32).to_radians();
-    let damage = ((p_random() % 5) + 1) * 3;
+    let angle = actor.spread_angle(20, 4);
+    let damage = ((actor.p_random() % 5) + 1) * 3;
     actor.line_attack(damage as f32, MISSILERANGE, angle, slope, &mut bsp_trace);
 }
 
@@ -649,8 +709,8 @@ pub(crate) fn a_sposattack(actor: &mut MapObject) {
 
     let mut angle;
     for _ in 0..3 {
-        angle = actor.angle + (((p_random() - p_random()) >> 4) as f32).to_radians();
-        let damage = ((p_random() % 5) + 1) * 3;
+        angle = actor.spread_angle(20, 4);
+        let damage = ((actor.p_random() % 5) + 1) * 3;
         actor.line_attack(
             damage as f32,
             MISSILERANGE,
@@ -672,9 +732,8 @@ pub(crate) fn a_cposattack(actor: &mut MapObject) {
 
     actor.start_sound(SfxName::Shotgn);
 
-    let mut angle = actor.angle;
-    angle += (((p_random() - p_random()) >> 4) as f32).to_radians();
-    let damage = ((p_random() % 5) + 1) * 3;
+    let angle = actor.spread_angle(20, 4);
+    let damage = ((actor.p_random() % 5) + 1) * 3;
     actor.line_attack(damage as f32, MISSILERANGE, angle, slope, &mut bsp_trace);
 }
 
@@ -689,6 +748,10 @@ pub(crate) fn a_bspiattack(actor: &mut MapObject) {
 }
 
 pub(crate) fn a_skullattack(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_skull_attack();
+        return;
+    }
     if let Some(target) = actor.target {
         let target = unsafe { (*target).mobj() };
 
@@ -715,7 +778,7 @@ pub(crate) fn a_headattack(actor: &mut MapObject) {
 
         if actor.check_melee_range() {
             actor.start_sound(SfxName::Claw);
-            let damage = ((p_random() % 8) + 1) * 10;
+            let damage = ((actor.p_random() % 8) + 1) * 10;
             target.p_take_damage(Some(actor), None, true, damage);
             return;
         }
@@ -731,7 +794,7 @@ pub(crate) fn a_sargattack(actor: &mut MapObject) {
 
         a_facetarget(actor);
         if actor.check_melee_range() {
-            let damage = ((p_random() % 10) + 1) * 4;
+            let damage = ((actor.p_random() % 10) + 1) * 4;
             target.p_take_damage(Some(actor), None, true, damage);
         }
     }
@@ -743,7 +806,7 @@ pub(crate) fn a_bruisattack(actor: &mut MapObject) {
         a_facetarget(actor);
 
         if actor.check_melee_range() {
-            let damage = ((p_random() % 6) + 1) * 10;
+            let damage = ((actor.p_random() % 6) + 1) * 10;
             target.p_take_damage(Some(actor), None, true, damage);
             return;
         }
@@ -756,7 +819,7 @@ pub(crate) fn a_bruisattack(actor: &mut MapObject) {
 pub(crate) fn a_cposrefire(actor: &mut MapObject) {
     if let Some(target) = actor.target {
         a_facetarget(actor);
-        if p_random() < 40 {
+        if actor.p_random() < 40 {
             return;
         }
 
@@ -785,7 +848,7 @@ pub(crate) fn a_troopattack(actor: &mut MapObject) {
 
         if actor.check_melee_range() {
             actor.start_sound(SfxName::Claw);
-            let damage = ((p_random() % 8) + 1) * 3;
+            let damage = ((actor.p_random() % 8) + 1) * 3;
             target.p_take_damage(Some(actor), None, true, damage);
             return;
         }
@@ -828,12 +891,26 @@ fn a_painshootskull(actor: &mut MapObject, angle: Angle) {
 }
 
 pub(crate) fn a_painattack(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        if actor.target.is_some() {
+            a_facetarget(actor);
+            actor.fixed_pain_shoot_skull(actor.fixed.angle);
+        }
+        return;
+    }
     a_facetarget(actor);
     a_painshootskull(actor, actor.angle);
 }
 
 pub(crate) fn a_paindie(actor: &mut MapObject) {
     a_fall(actor);
+    if actor.level().options.fixed_point {
+        let angle = actor.fixed_angle();
+        for turn in [ANG90, ANG180, ANG270] {
+            actor.fixed_pain_shoot_skull(angle.wrapping_add(turn));
+        }
+        return;
+    }
     a_painshootskull(actor, actor.angle + FRAC_PI_2);
     a_painshootskull(actor, actor.angle + PI);
     a_painshootskull(actor, actor.angle + PI + FRAC_PI_2);
@@ -842,6 +919,10 @@ pub(crate) fn a_paindie(actor: &mut MapObject) {
 const FAT_SPREAD: f32 = FRAC_PI_2 / 8.0;
 
 pub(crate) fn a_fatattack1(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_fat_attack(FATSPREAD, [0, FATSPREAD]);
+        return;
+    }
     if let Some(target) = actor.target {
         let level = unsafe { &mut *actor.level };
         let target = unsafe { (*target).mobj_mut() };
@@ -863,6 +944,13 @@ pub(crate) fn a_fatattack1(actor: &mut MapObject) {
     }
 }
 pub(crate) fn a_fatattack2(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_fat_attack(
+            FATSPREAD.wrapping_neg(),
+            [0, (FATSPREAD * 2).wrapping_neg()],
+        );
+        return;
+    }
     if let Some(target) = actor.target {
         let level = unsafe { &mut *actor.level };
         let target = unsafe { (*target).mobj_mut() };
@@ -884,6 +972,10 @@ pub(crate) fn a_fatattack2(actor: &mut MapObject) {
     }
 }
 pub(crate) fn a_fatattack3(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_fat_attack(0, [(FATSPREAD / 2).wrapping_neg(), FATSPREAD / 2]);
+        return;
+    }
     if let Some(target) = actor.target {
         let level = unsafe { &mut *actor.level };
         let target = unsafe { (*target).mobj_mut() };
@@ -913,7 +1005,7 @@ pub(crate) fn a_fatraise(actor: &mut MapObject) {
 pub(crate) fn a_spidrefire(actor: &mut MapObject) {
     if let Some(target) = actor.target {
         a_facetarget(actor);
-        if p_random() < 10 {
+        if actor.p_random() < 10 {
             return;
         }
 
@@ -922,11 +1014,227 @@ pub(crate) fn a_spidrefire(actor: &mut MapObject) {
             actor.set_state(actor.info.seestate);
         }
     }
-    actor.set_state(actor.info.seestate);
+}
+
+/// ZDoom class names in `MapObjKind` order, as used by `UMAPINFO` boss actions
+const THING_CLASS_NAMES: [&str; MapObjKind::Count as usize] = [
+    "DoomPlayer",
+    "ZombieMan",
+    "ShotgunGuy",
+    "Archvile",
+    "ArchvileFire",
+    "Revenant",
+    "RevenantTracer",
+    "RevenantTracerSmoke",
+    "Fatso",
+    "FatShot",
+    "ChaingunGuy",
+    "DoomImp",
+    "Demon",
+    "Spectre",
+    "Cacodemon",
+    "BaronOfHell",
+    "BaronBall",
+    "HellKnight",
+    "LostSoul",
+    "SpiderMastermind",
+    "Arachnotron",
+    "Cyberdemon",
+    "PainElemental",
+    "WolfensteinSS",
+    "CommanderKeen",
+    "BossBrain",
+    "BossEye",
+    "BossTarget",
+    "SpawnShot",
+    "SpawnFire",
+    "ExplosiveBarrel",
+    "DoomImpBall",
+    "CacodemonBall",
+    "Rocket",
+    "PlasmaBall",
+    "BFGBall",
+    "ArachnotronPlasma",
+    "BulletPuff",
+    "Blood",
+    "TeleportFog",
+    "ItemFog",
+    "TeleportDest",
+    "BFGExtra",
+    "GreenArmor",
+    "BlueArmor",
+    "HealthBonus",
+    "ArmorBonus",
+    "BlueCard",
+    "RedCard",
+    "YellowCard",
+    "YellowSkull",
+    "RedSkull",
+    "BlueSkull",
+    "Stimpack",
+    "Medikit",
+    "Soulsphere",
+    "InvulnerabilitySphere",
+    "Berserk",
+    "BlurSphere",
+    "RadSuit",
+    "Allmap",
+    "Infrared",
+    "Megasphere",
+    "Clip",
+    "ClipBox",
+    "RocketAmmo",
+    "RocketBox",
+    "Cell",
+    "CellPack",
+    "Shell",
+    "ShellBox",
+    "Backpack",
+    "BFG9000",
+    "Chaingun",
+    "Chainsaw",
+    "RocketLauncher",
+    "PlasmaRifle",
+    "Shotgun",
+    "SuperShotgun",
+    "TechLamp",
+    "TechLamp2",
+    "Column",
+    "TallGreenColumn",
+    "ShortGreenColumn",
+    "TallRedColumn",
+    "ShortRedColumn",
+    "SkullColumn",
+    "HeartColumn",
+    "EvilEye",
+    "FloatingSkull",
+    "TorchTree",
+    "BlueTorch",
+    "GreenTorch",
+    "RedTorch",
+    "ShortBlueTorch",
+    "ShortGreenTorch",
+    "ShortRedTorch",
+    "Stalagtite",
+    "TechPillar",
+    "CandleStick",
+    "Candelabra",
+    "BloodyTwitch",
+    "Meat2",
+    "Meat3",
+    "Meat4",
+    "Meat5",
+    "NonsolidMeat2",
+    "NonsolidMeat4",
+    "NonsolidMeat3",
+    "NonsolidMeat5",
+    "NonsolidTwitch",
+    "DeadCacodemon",
+    "DeadMarine",
+    "DeadZombieMan",
+    "DeadDemon",
+    "DeadLostSoul",
+    "DeadDoomImp",
+    "DeadShotgunGuy",
+    "GibbedMarine",
+    "GibbedMarineExtra",
+    "HeadsOnAStick",
+    "Gibs",
+    "HeadOnAStick",
+    "HeadCandles",
+    "DeadStick",
+    "LiveStick",
+    "BigTree",
+    "BurningBarrel",
+    "HangNoGuts",
+    "HangBNoBrain",
+    "HangTLookingDown",
+    "HangTSkull",
+    "HangTLookingUp",
+    "HangTNoBrain",
+    "ColonGibs",
+    "SmallBloodPool",
+    "BrainStem",
+];
+
+/// True if all other things of the actors kind are dead
+fn boss_kind_dead(actor: &MapObject, level: &mut Level) -> bool {
+    let mut dead = true;
+    level.thinkers.run_fn_on_things(|thinker| {
+        if let ThinkerData::MapObject(ref mobj) = thinker.data() {
+            if !ptr::eq(mobj, actor) && mobj.kind == actor.kind && mobj.health > 0 {
+                dead = false;
+            }
+        }
+        true
+    });
+    dead
+}
+
+/// A junk line copied from the first line of the actors sector, used to
+/// trigger boss death specials
+fn boss_junk_line(actor: &MapObject, special: i16, tag: i16) -> LineDef {
+    LineDef {
+        v1: Default::default(),
+        v2: Default::default(),
+        delta: Default::default(),
+        flags: 0,
+        special,
+        tag,
+        bbox: Default::default(),
+        slopetype: SlopeType::Horizontal,
+        front_sidedef: actor.subsector.sector.lines[0].front_sidedef.clone(),
+        back_sidedef: None,
+        frontsector: actor.subsector.sector.clone(),
+        backsector: None,
+        valid_count: 0,
+        translucent: false,
+        sides: [0, 0],
+    }
+}
+
+/// Run the `UMAPINFO` boss actions for the actors kind once all of that kind
+/// are dead. Each action is tried as a use special, then as a cross special.
+fn umapinfo_boss_death(actor: &mut MapObject, actions: &[BossAction]) {
+    let level = unsafe { &mut *actor.level };
+    let class_name = THING_CLASS_NAMES[actor.kind as usize];
+    if !actions
+        .iter()
+        .any(|a| a.thing_type.eq_ignore_ascii_case(class_name))
+    {
+        return;
+    }
+    // There needs to be at least one player alive
+    if !level.players().iter().any(|p| p.status.health > 0) {
+        return;
+    }
+    if !boss_kind_dead(actor, level) {
+        return;
+    }
+
+    for action in actions
+        .iter()
+        .filter(|a| a.thing_type.eq_ignore_ascii_case(class_name))
+    {
+        // Specials such as switches may keep a pointer to the line
+        level.boss_lines.push(Box::new(boss_junk_line(
+            actor,
+            action.line_special,
+            action.tag,
+        )));
+        let junk = level.boss_lines.last_mut().unwrap();
+        if !p_use_special_line(0, MapPtr::new(junk), actor, true) {
+            cross_special_line(0, MapPtr::new(junk), actor, true);
+        }
+    }
 }
 
 pub(crate) fn a_bossdeath(actor: &mut MapObject) {
     let level = unsafe { &mut *actor.level };
+    if let Some(actions) = level.boss_actions.clone() {
+        umapinfo_boss_death(actor, &actions);
+        return;
+    }
     let map = level.options.map;
     let episode = level.options.episode;
     let mode = level.game_mode;
@@ -982,38 +1290,11 @@ pub(crate) fn a_bossdeath(actor: &mut MapObject) {
     }
 
     // Check bosses are all dead
-    let mut dead = true;
-    level.thinkers.run_fn_on_things(|thinker| {
-        if let ThinkerData::MapObject(ref mobj) = thinker.data() {
-            if !ptr::eq(mobj, actor) && mobj.kind == actor.kind && mobj.health > 0 {
-                dead = false;
-            }
-        }
-        true
-    });
-    if !dead {
+    if !boss_kind_dead(actor, level) {
         return;
     };
 
-    let sidedef = actor.subsector.sector.lines[0].front_sidedef.clone();
-    let sector = actor.subsector.sector.clone();
-
-    let mut junk = LineDef {
-        v1: Default::default(),
-        v2: Default::default(),
-        delta: Default::default(),
-        flags: 0,
-        special: 0,
-        tag: 666,
-        bbox: Default::default(),
-        slopetype: SlopeType::Horizontal,
-        front_sidedef: sidedef,
-        back_sidedef: None,
-        frontsector: sector,
-        backsector: None,
-        valid_count: 0,
-        sides: [0, 0],
-    };
+    let mut junk = boss_junk_line(actor, 0, 666);
 
     if mode == GameMode::Commercial && map == 7 {
         if actor.kind == MapObjKind::MT_FATSO {
@@ -1059,13 +1340,17 @@ pub(crate) fn a_skelfist(actor: &mut MapObject) {
 
         if actor.check_melee_range() {
             actor.start_sound(SfxName::Skepch);
-            let damage = ((p_random() % 10) + 1) * 6;
+            let damage = ((actor.p_random() % 10) + 1) * 6;
             target.p_take_damage(Some(actor), None, true, damage);
         }
     }
 }
 
 pub(crate) fn a_skelmissile(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_skel_missile();
+        return;
+    }
     if let Some(target) = actor.target {
         let target = unsafe { (*target).mobj_mut() };
         a_facetarget(actor);
@@ -1082,6 +1367,10 @@ pub(crate) fn a_skelmissile(actor: &mut MapObject) {
 
 /// Skelly missile that tracks the player/target
 pub(crate) fn a_tracer(actor: &mut MapObject) {
+    if actor.level().options.fixed_point {
+        actor.fixed_tracer();
+        return;
+    }
     let level = unsafe { &mut *actor.level };
     // spawn a puff of smoke behind the rocket
     MapObject::spawn_puff(actor.xyz.x, actor.xyz.y, actor.xyz.z as i32, 0.0, level);
@@ -1094,7 +1383,7 @@ pub(crate) fn a_tracer(actor: &mut MapObject) {
     );
     let smoke = unsafe { &mut *thing };
     smoke.momxyz.z = 1.0;
-    smoke.tics -= p_random() & 3;
+    smoke.tics -= actor.p_random() & 3;
     if smoke.tics < 1 {
         smoke.tics = 1;
     }
@@ -4,7 +4,8 @@ use log::error;
 
 use crate::doom_def::FLOATSPEED;
 use crate::env::switch::p_use_special_line;
-use crate::{p_random, Angle, MapObjFlag, MapObject};
+use crate::fixed::FRACUNIT;
+use crate::{Angle, MapObjFlag, MapObject};
 
 use super::movement::SubSectorMinMax;
 
@@ -78,7 +79,7 @@ impl MapObject {
         if !self.do_enemy_move() {
             return false;
         }
-        self.movecount = p_random() & 15;
+        self.movecount = self.p_random() & 15;
         true
     }
 
@@ -87,11 +88,14 @@ impl MapObject {
             return false;
         }
 
+        if self.level().options.fixed_point {
+            return self.fixed_enemy_move();
+        }
+
+        let mut specs = SubSectorMinMax::default();
         let mut try_move = self.xyz;
         try_move.x += self.info.speed * DIR_XSPEED[self.movedir as usize];
         try_move.y += self.info.speed * DIR_YSPEED[self.movedir as usize];
-
-        let mut specs = SubSectorMinMax::default();
         if !self.p_try_move(try_move, &mut specs) {
             // open any specials
             // TODO: if (actor->flags & MF_FLOAT && floatok)
@@ -113,7 +117,7 @@ impl MapObject {
             self.movedir = MoveDir::None;
             let mut good = false;
             for ld in &specs.spec_hits {
-                if p_use_special_line(0, ld.clone(), self) || ld.special == 0 {
+                if p_use_special_line(0, ld.clone(), self, false) || ld.special == 0 {
                     good = true;
                 }
             }
@@ -141,8 +145,21 @@ impl MapObject {
 
         let target = unsafe { (**self.target.as_mut().unwrap()).mobj() };
 
-        let dx = target.xyz.x - self.xyz.x;
-        let dy = target.xyz.y - self.xyz.y;
+        let (dx, dy) = if self.level().options.fixed_point {
+            // Exact in an f64, so the thresholds and comparisons below match
+            // the original's on the fixed-point deltas
+            let (x, y, _) = self.fixed_pos();
+            let (tx, ty, _) = target.fixed_pos();
+            (
+                tx.wrapping_sub(x) as f64 / FRACUNIT as f64,
+                ty.wrapping_sub(y) as f64 / FRACUNIT as f64,
+            )
+        } else {
+            (
+                (target.xyz.x - self.xyz.x) as f64,
+                (target.xyz.y - self.xyz.y) as f64,
+            )
+        };
         // Select a cardinal angle based on delta
         if dx > 10.0 {
             dirs[1] = MoveDir::East;
@@ -169,7 +186,7 @@ impl MapObject {
         }
 
         // try other directions
-        if p_random() > 200 || dy.abs() > dx.abs() {
+        if self.p_random() > 200 || dy.abs() > dx.abs() {
             dirs.swap(1, 2);
         }
         if dirs[1] == turnaround {
@@ -204,7 +221,7 @@ impl MapObject {
         }
 
         // randomly determine direction of search
-        if p_random() & 1 != 0 {
+        if self.p_random() & 1 != 0 {
             for t in MoveDir::East as usize..=MoveDir::SouthEast as usize {
                 let tdir = MoveDir::from(t);
                 if tdir != turnaround {
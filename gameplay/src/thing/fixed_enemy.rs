@@ -0,0 +1,660 @@
+//! Monster thinking for the fixed-point mode (`GameOptions::fixed_point`).
+//!
+//! The target searches, attack range checks and the actions that aim or place
+//! things are done in the original engine's 16.16 maths and in its order, so
+//! the random numbers drawn and what they decide match it. Sight is the
+//! `REJECT` and BSP check in `fixed_sight`.
+//!
+//! Doom source name `p_enemy`
+
+use sound_traits::SfxName;
+
+use crate::fixed::{
+    aprox_distance, finecosine, finesine, fixed_mul, float_to_fixed, point_to_angle2, Bam, Fixed, ANG270, ANG90, ANGLETOFINESHIFT, FRACUNIT
+};
+use crate::info::{MapObjKind, StateNum, MOBJINFO};
+use crate::level::blockmap::MAXRADIUS;
+use crate::thinker::Thinker;
+use crate::{MapObjFlag, MapObject, Skill};
+
+use super::enemy_behaviour::{a_facetarget, a_fire};
+use super::enemy_movement::MoveDir;
+use super::fixed_map::{block_range, block_things_iterator, FixedCheck};
+use super::fixed_movement::{DIR_XSPEED, DIR_YSPEED};
+
+const MELEERANGE: Fixed = 64 * FRACUNIT;
+const SKULLSPEED: Fixed = 20 * FRACUNIT;
+/// The turn between each of the Mancubus' shots
+pub(super) const FATSPREAD: Bam = ANG90 / 8;
+/// The most a revenant tracer turns towards its target per adjustment
+const TRACEANGLE: Bam = 0xc000000;
+
+impl MapObject {
+    /// Look for a live player in sight, checking at most two players. Unless
+    /// `all_around` a player behind is only seen within melee range. The
+    /// player found becomes the target.
+    ///
+    /// Doom function name `P_LookForPlayers`
+    pub(super) fn fixed_look_for_players(&mut self, all_around: bool) -> bool {
+        let in_game = *self.level().players_in_game();
+        if !in_game.contains(&true) {
+            return false; // the original would search forever
+        }
+
+        let mut count = 0;
+        let stop = (self.lastlook - 1) & 3;
+        loop {
+            let look = self.lastlook as usize;
+            if in_game[look] {
+                if count == 2 || self.lastlook == stop {
+                    // done looking
+                    return false;
+                }
+                count += 1;
+
+                if let Some(target) = self.fixed_seen_player(look, all_around) {
+                    self.target = Some(target);
+                    return true;
+                }
+            }
+            self.lastlook = (self.lastlook + 1) & 3;
+        }
+    }
+
+    /// The thinker of player `num` if it is alive and can be seen
+    fn fixed_seen_player(&self, num: usize, all_around: bool) -> Option<*mut Thinker> {
+        let player = &self.level().players()[num];
+        if player.status.health <= 0 {
+            return None; // dead
+        }
+        let mobj = player.mobj()?;
+        if !self.fixed_check_sight(mobj) {
+            return None; // out of sight
+        }
+
+        if !all_around {
+            let (x, y, _) = self.fixed_pos();
+            let (px, py, _) = mobj.fixed_pos();
+            let an = point_to_angle2(x, y, px, py).wrapping_sub(self.fixed_angle());
+            if an > ANG90
+                && an < ANG270
+                && aprox_distance(px.wrapping_sub(x), py.wrapping_sub(y)) > MELEERANGE
+            {
+                return None; // behind back
+            }
+        }
+        Some(mobj.thinker)
+    }
+
+    /// The target search of `A_Look`: the thing that last made a noise in the
+    /// sector, unless in ambush and it can't be seen, else any player in
+    /// front.
+    ///
+    /// Doom function name `A_Look`
+    pub(super) fn fixed_look(&mut self) -> bool {
+        let sector = self.subsector.sector.clone();
+        if let Some(target) = sector.sound_target() {
+            if target.flags & MapObjFlag::Shootable as u32 != 0 {
+                self.target = Some(target.thinker);
+                if self.flags & MapObjFlag::Ambush as u32 == 0 || self.fixed_check_sight(target) {
+                    return true;
+                }
+            }
+        }
+        self.fixed_look_for_players(false)
+    }
+
+    /// Doom function name `P_CheckMeleeRange`
+    pub(super) fn fixed_check_melee_range(&self) -> bool {
+        let Some(target) = self.target else {
+            return false;
+        };
+        let target = unsafe { (*target).mobj() };
+        let (x, y, _) = self.fixed_pos();
+        let (tx, ty, _) = target.fixed_pos();
+        let dist = aprox_distance(tx.wrapping_sub(x), ty.wrapping_sub(y));
+        if dist >= MELEERANGE - 20 * FRACUNIT + float_to_fixed(target.info.radius) {
+            return false;
+        }
+        self.fixed_check_sight(target)
+    }
+
+    /// The closer the target the more likely a shot is
+    ///
+    /// Doom function name `P_CheckMissileRange`
+    pub(super) fn fixed_check_missile_range(&mut self) -> bool {
+        let Some(target) = self.target else {
+            return false;
+        };
+        let target = unsafe { (*target).mobj() };
+        if !self.fixed_check_sight(target) {
+            return false;
+        }
+
+        if self.flags & MapObjFlag::Justhit as u32 != 0 {
+            // the target just hit the enemy, so fight back!
+            self.flags &= !(MapObjFlag::Justhit as u32);
+            return true;
+        }
+
+        if self.reactiontime != 0 {
+            return false; // do not attack yet
+        }
+
+        let (x, y, _) = self.fixed_pos();
+        let (tx, ty, _) = target.fixed_pos();
+        let mut dist = aprox_distance(x.wrapping_sub(tx), y.wrapping_sub(ty)) - 64 * FRACUNIT;
+        if self.info.meleestate == StateNum::None {
+            dist -= 128 * FRACUNIT; // no melee attack, so fire more
+        }
+        dist >>= 16;
+
+        if self.kind == MapObjKind::MT_VILE && dist > 14 * 64 {
+            return false; // too far away
+        }
+        if self.kind == MapObjKind::MT_UNDEAD {
+            if dist < 196 {
+                return false; // close for fist attack
+            }
+            dist >>= 1;
+        }
+        if matches!(
+            self.kind,
+            MapObjKind::MT_CYBORG | MapObjKind::MT_SPIDER | MapObjKind::MT_SKULL
+        ) {
+            dist >>= 1;
+        }
+        dist = dist.min(200);
+        if self.kind == MapObjKind::MT_CYBORG && dist > 160 {
+            dist = 160;
+        }
+
+        self.p_random() >= dist
+    }
+
+    /// Actor has a melee attack, so it tries to close as fast as possible
+    ///
+    /// Doom function name `A_Chase`
+    pub(super) fn fixed_chase(&mut self) {
+        if self.reactiontime != 0 {
+            self.reactiontime -= 1;
+        }
+
+        // modify target threshold
+        if self.threshold != 0 {
+            if self.target().is_none_or(|t| t.health <= 0) {
+                self.threshold = 0;
+            } else {
+                self.threshold -= 1;
+            }
+        }
+
+        // turn towards movement direction if not there yet
+        self.fixed_chase_turn();
+
+        if self
+            .target()
+            .is_none_or(|t| t.flags & MapObjFlag::Shootable as u32 == 0)
+        {
+            // look for a new target
+            if self.look_for_players(true) {
+                return; // got a new target
+            }
+            self.set_state(self.info.spawnstate);
+            return;
+        }
+
+        let options = &self.level().options;
+        let fast = options.skill == Skill::Nightmare || options.fast_parm;
+        let netgame = options.netgame;
+
+        // do not attack twice in a row
+        if self.flags & MapObjFlag::Justattacked as u32 != 0 {
+            self.flags &= !(MapObjFlag::Justattacked as u32);
+            if !fast {
+                self.new_chase_dir();
+            }
+            return;
+        }
+
+        // check for melee attack
+        if self.info.meleestate != StateNum::None && self.fixed_check_melee_range() {
+            if self.info.attacksound != SfxName::None {
+                self.start_sound(self.info.attacksound);
+            }
+            self.set_state(self.info.meleestate);
+            return;
+        }
+
+        // check for missile attack
+        if self.info.missilestate != StateNum::None
+            && (fast || self.movecount == 0)
+            && self.fixed_check_missile_range()
+        {
+            self.set_state(self.info.missilestate);
+            self.flags |= MapObjFlag::Justattacked as u32;
+            return;
+        }
+
+        // possibly choose another target
+        if netgame
+            && self.threshold == 0
+            && !self.target().is_some_and(|t| self.fixed_check_sight(t))
+            && self.look_for_players(true)
+        {
+            return; // got a new target
+        }
+
+        // chase towards player
+        self.movecount -= 1;
+        if self.movecount < 0 || !self.do_enemy_move() {
+            self.new_chase_dir();
+        }
+
+        // make active sound
+        if self.info.activesound != SfxName::None && self.p_random() < 3 {
+            self.start_sound(self.info.activesound);
+        }
+    }
+
+    /// Fly at the target
+    ///
+    /// Doom function name `A_SkullAttack`
+    pub(super) fn fixed_skull_attack(&mut self) {
+        let Some(dest) = self.target else {
+            return;
+        };
+        let dest = unsafe { (*dest).mobj() };
+
+        self.flags |= MapObjFlag::Skullfly as u32;
+        self.start_sound(self.info.attacksound);
+        a_facetarget(self);
+
+        let an = (self.fixed.angle >> ANGLETOFINESHIFT) as usize;
+        self.fixed.momx = fixed_mul(SKULLSPEED, finecosine(an));
+        self.fixed.momy = fixed_mul(SKULLSPEED, finesine(an));
+        let (dx, dy, dz) = dest.fixed_pos();
+        let dist = aprox_distance(dx.wrapping_sub(self.fixed.x), dy.wrapping_sub(self.fixed.y));
+        let dist = (dist / SKULLSPEED).max(1);
+        self.fixed.momz = (dz + (float_to_fixed(dest.height) >> 1) - self.fixed.z) / dist;
+        self.store_fixed();
+    }
+
+    /// Spawn a lost soul in front of a pain elemental at `angle` and launch it
+    /// at the target
+    ///
+    /// Doom function name `A_PainShootSkull`
+    pub(super) fn fixed_pain_shoot_skull(&mut self, angle: Bam) {
+        let level = unsafe { &mut *self.level };
+        // count total number of skulls currently on the level
+        let count = level
+            .thinkers
+            .iter()
+            .filter(|t| t.is_mobj() && t.mobj().kind == MapObjKind::MT_SKULL)
+            .count();
+        // if there are already 20 skulls on the level, don't spit another one
+        if count > 20 {
+            return;
+        }
+
+        // okay, there's room for another one
+        let an = (angle >> ANGLETOFINESHIFT) as usize;
+        let radius = float_to_fixed(self.info.radius)
+            + float_to_fixed(MOBJINFO[MapObjKind::MT_SKULL as usize].radius);
+        let prestep = 4 * FRACUNIT + 3 * radius / 2;
+        let (x, y, z) = self.fixed_pos();
+        let skull = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(
+                x + fixed_mul(prestep, finecosine(an)),
+                y + fixed_mul(prestep, finesine(an)),
+                z + 8 * FRACUNIT,
+                MapObjKind::MT_SKULL,
+                level,
+            )
+        };
+
+        // check for movements
+        let (sx, sy) = (skull.fixed.x, skull.fixed.y);
+        if !skull.fixed_try_move(sx, sy, &mut FixedCheck::default()) {
+            // kill it immediately
+            skull.p_take_damage(Some(self), None, true, 10000);
+            return;
+        }
+        skull.target = self.target;
+        skull.fixed_skull_attack();
+    }
+
+    /// Fire two Mancubus shots, first turning by `turn`, with each shot turned
+    /// by its `spread` from the line to the target
+    ///
+    /// Doom function names `A_FatAttack1`, `A_FatAttack2` and `A_FatAttack3`
+    pub(super) fn fixed_fat_attack(&mut self, turn: Bam, spread: [Bam; 2]) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let target = unsafe { (*target).mobj_mut() };
+        a_facetarget(self);
+        // change direction to ...
+        self.fixed.angle = self.fixed.angle.wrapping_add(turn);
+        self.store_fixed();
+
+        let level = unsafe { &mut *self.level };
+        for spread in spread {
+            let missile = MapObject::spawn_missile(self, target, MapObjKind::MT_FATSHOT, level);
+            missile.fixed.angle = missile.fixed.angle.wrapping_add(spread);
+            let an = (missile.fixed.angle >> ANGLETOFINESHIFT) as usize;
+            let speed = float_to_fixed(missile.info.speed);
+            missile.fixed.momx = fixed_mul(speed, finecosine(an));
+            missile.fixed.momy = fixed_mul(speed, finesine(an));
+            missile.store_fixed();
+        }
+    }
+
+    /// Fire a revenant tracer from above the shoulder
+    ///
+    /// Doom function name `A_SkelMissile`
+    pub(super) fn fixed_skel_missile(&mut self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let target = unsafe { (*target).mobj_mut() };
+        a_facetarget(self);
+
+        let level = unsafe { &mut *self.level };
+        self.fixed.z += 16 * FRACUNIT; // so missile spawns higher
+        self.store_fixed();
+        let missile = MapObject::spawn_missile(self, target, MapObjKind::MT_TRACER, level);
+        self.fixed.z -= 16 * FRACUNIT; // back to normal
+        self.store_fixed();
+
+        missile.fixed.x += missile.fixed.momx;
+        missile.fixed.y += missile.fixed.momy;
+        missile.store_fixed();
+        missile.tracer = self.target;
+    }
+
+    /// Steer a revenant tracer towards its target every fourth tic, leaving
+    /// smoke behind
+    ///
+    /// Doom function name `A_Tracer`
+    pub(super) fn fixed_tracer(&mut self) {
+        let level = unsafe { &mut *self.level };
+        if level.game_tic & 3 != 0 {
+            return;
+        }
+
+        // spawn a puff of smoke behind the rocket
+        self.sync_fixed();
+        let (x, y, z) = (self.fixed.x, self.fixed.y, self.fixed.z);
+        MapObject::fixed_spawn_puff(x, y, z, 0, level);
+        let smoke = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(
+                x.wrapping_sub(self.fixed.momx),
+                y.wrapping_sub(self.fixed.momy),
+                z,
+                MapObjKind::MT_SMOKE,
+                level,
+            )
+        };
+        smoke.fixed.momz = FRACUNIT;
+        smoke.store_fixed();
+        smoke.tics -= self.p_random() & 3;
+        if smoke.tics < 1 {
+            smoke.tics = 1;
+        }
+
+        // adjust direction
+        let Some(dest) = self.tracer else {
+            return;
+        };
+        let dest = unsafe { (*dest).mobj() };
+        if dest.health <= 0 {
+            return;
+        }
+
+        // change angle
+        let (dx, dy, dz) = dest.fixed_pos();
+        let exact = point_to_angle2(x, y, dx, dy);
+        if exact != self.fixed.angle {
+            if exact.wrapping_sub(self.fixed.angle) > 0x80000000 {
+                self.fixed.angle = self.fixed.angle.wrapping_sub(TRACEANGLE);
+                if exact.wrapping_sub(self.fixed.angle) < 0x80000000 {
+                    self.fixed.angle = exact;
+                }
+            } else {
+                self.fixed.angle = self.fixed.angle.wrapping_add(TRACEANGLE);
+                if exact.wrapping_sub(self.fixed.angle) > 0x80000000 {
+                    self.fixed.angle = exact;
+                }
+            }
+        }
+
+        let an = (self.fixed.angle >> ANGLETOFINESHIFT) as usize;
+        let speed = float_to_fixed(self.info.speed);
+        self.fixed.momx = fixed_mul(speed, finecosine(an));
+        self.fixed.momy = fixed_mul(speed, finesine(an));
+
+        // change slope
+        let dist = aprox_distance(dx.wrapping_sub(x), dy.wrapping_sub(y));
+        let dist = (dist / speed).max(1);
+        let slope = (dz + 40 * FRACUNIT - z) / dist;
+        if slope < self.fixed.momz {
+            self.fixed.momz -= FRACUNIT / 8;
+        } else {
+            self.fixed.momz += FRACUNIT / 8;
+        }
+        self.store_fixed();
+    }
+
+    /// Look for a corpse to raise just ahead of an arch-vile, and raise it.
+    /// Returns false if there was none.
+    ///
+    /// Doom function name `A_VileChase`
+    pub(super) fn fixed_vile_raise(&mut self) -> bool {
+        if self.movedir == MoveDir::None {
+            return false;
+        }
+
+        // check for corpses to raise
+        let (x, y, _) = self.fixed_pos();
+        let speed = self.info.speed as Fixed;
+        let tryx = x + speed * DIR_XSPEED[self.movedir as usize];
+        let tryy = y + speed * DIR_YSPEED[self.movedir as usize];
+        let level = unsafe { &mut *self.level };
+        let ([xl, xh], [yl, yh]) = block_range(
+            level,
+            tryx - MAXRADIUS * 2,
+            tryx + MAXRADIUS * 2,
+            tryy - MAXRADIUS * 2,
+            tryy + MAXRADIUS * 2,
+        );
+
+        let vile_radius = float_to_fixed(self.info.radius);
+        for bx in xl..=xh {
+            for by in yl..=yh {
+                let mut corpse = None;
+                block_things_iterator(level, bx, by, |thing| {
+                    if !thing.fixed_raisable(tryx, tryy, vile_radius) {
+                        return true;
+                    }
+                    corpse = Some(thing as *mut MapObject);
+                    false
+                });
+                let Some(corpse) = corpse else {
+                    continue;
+                };
+
+                // got one!
+                let corpse = unsafe { &mut *corpse };
+                let temp = self.target.replace(corpse.thinker);
+                a_facetarget(self);
+                self.target = temp;
+                self.set_state(StateNum::VILE_HEAL1);
+                corpse.start_sound(SfxName::Slop);
+
+                corpse.set_state(corpse.info.raisestate);
+                corpse.height *= 4.0;
+                corpse.flags = corpse.info.flags;
+                corpse.health = corpse.info.spawnhealth;
+                corpse.target = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// True if `self` is a corpse touching an arch-vile stepping to `tryx`,
+    /// `tryy` with room to stand back up
+    ///
+    /// Doom function name `PIT_VileCheck`
+    fn fixed_raisable(&mut self, tryx: Fixed, tryy: Fixed, vile_radius: Fixed) -> bool {
+        if self.flags & MapObjFlag::Corpse as u32 == 0 {
+            return false; // not a monster
+        }
+        if self.tics != -1 {
+            return false; // not lying still yet
+        }
+        if self.info.raisestate == StateNum::None {
+            return false; // monster doesn't have a raise state
+        }
+
+        let maxdist = float_to_fixed(self.info.radius) + vile_radius;
+        self.sync_fixed();
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        if x.wrapping_sub(tryx).wrapping_abs() > maxdist
+            || y.wrapping_sub(tryy).wrapping_abs() > maxdist
+        {
+            return false; // not actually touching
+        }
+
+        self.fixed.momx = 0;
+        self.fixed.momy = 0;
+        self.store_fixed();
+        self.height *= 4.0;
+        let check = self.fixed_check_position(x, y, &mut FixedCheck::default());
+        self.height /= 4.0;
+        check
+    }
+
+    /// Start the arch-vile's fire on the target. The fire is spawned at the
+    /// target's `x` for both `x` and `y` as in the original, `A_Fire` puts it
+    /// in front of the target straight after.
+    ///
+    /// Doom function name `A_VileTarget`
+    pub(super) fn fixed_vile_target(&mut self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let target = unsafe { (*target).mobj() };
+        a_facetarget(self);
+
+        let (tx, _, tz) = target.fixed_pos();
+        let level = unsafe { &mut *self.level };
+        let fog = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(tx, tx, tz, MapObjKind::MT_FIRE, level)
+        };
+        self.tracer = Some(fog.thinker);
+        fog.target = Some(self.thinker);
+        fog.tracer = self.target;
+        a_fire(fog);
+    }
+
+    /// Keep the arch-vile's fire in front of its victim while the vile can see
+    /// it
+    ///
+    /// Doom function name `A_Fire`
+    pub(super) fn fixed_fire(&mut self) {
+        let Some(dest) = self.tracer else {
+            return;
+        };
+        let dest = unsafe { (*dest).mobj() };
+        let Some(vile) = self.target() else {
+            return;
+        };
+        // don't move it if the vile lost sight
+        if !vile.fixed_check_sight(dest) {
+            return;
+        }
+
+        let an = (dest.fixed_angle() >> ANGLETOFINESHIFT) as usize;
+        let (x, y, z) = dest.fixed_pos();
+        self.sync_fixed();
+        unsafe { self.unset_thing_position() };
+        self.fixed.x = x + fixed_mul(24 * FRACUNIT, finecosine(an));
+        self.fixed.y = y + fixed_mul(24 * FRACUNIT, finesine(an));
+        self.fixed.z = z;
+        self.store_fixed();
+        unsafe { self.set_thing_position() };
+    }
+
+    /// Blast the target if still in sight, throwing it up, and explode the
+    /// fire in front of it
+    ///
+    /// Doom function name `A_VileAttack`
+    pub(super) fn fixed_vile_attack(&mut self) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let target = unsafe { (*target).mobj_mut() };
+        a_facetarget(self);
+        if !self.fixed_check_sight(target) {
+            return;
+        }
+
+        self.start_sound(SfxName::Barexp);
+        target.p_take_damage(Some(self), None, true, 20);
+        target.sync_fixed();
+        target.fixed.momz = 1000 * FRACUNIT / target.info.mass;
+        target.store_fixed();
+
+        let an = (self.fixed.angle >> ANGLETOFINESHIFT) as usize;
+        let Some(fire) = self.tracer else {
+            return;
+        };
+        let fire = unsafe { (*fire).mobj_mut() };
+        // move the fire between the vile and the player
+        let (tx, ty, _) = target.fixed_pos();
+        fire.sync_fixed();
+        fire.fixed.x = tx - fixed_mul(24 * FRACUNIT, finecosine(an));
+        fire.fixed.y = ty - fixed_mul(24 * FRACUNIT, finesine(an));
+        fire.store_fixed();
+        fire.fixed_radius_attack(70);
+    }
+
+    /// Set off a row of explosions in front of the boss brain
+    ///
+    /// Doom function name `A_BrainScream`
+    pub(super) fn fixed_brain_scream(&mut self) {
+        let (x, y, _) = self.fixed_pos();
+        let mut rx = x - 196 * FRACUNIT;
+        while rx < x + 320 * FRACUNIT {
+            let z = 128 + self.p_random() * 2 * FRACUNIT;
+            self.fixed_brain_rocket(rx, y - 320 * FRACUNIT, z);
+            rx += FRACUNIT * 8;
+        }
+        self.start_sound(SfxName::Bosdth);
+    }
+
+    /// Doom function name `A_BrainExplode`
+    pub(super) fn fixed_brain_explode(&mut self) {
+        let (x, y, _) = self.fixed_pos();
+        let x = x + (self.p_random() - self.p_random()) * 2048;
+        let z = 128 + self.p_random() * 2 * FRACUNIT;
+        self.fixed_brain_rocket(x, y, z);
+    }
+
+    /// A rising explosion of the boss brain's death
+    fn fixed_brain_rocket(&mut self, x: Fixed, y: Fixed, z: Fixed) {
+        let level = unsafe { &mut *self.level };
+        let th = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(x, y, z, MapObjKind::MT_ROCKET, level)
+        };
+        th.fixed.momz = self.p_random() * 512;
+        th.store_fixed();
+        th.set_state(StateNum::BRAINEXPLODE1);
+        th.tics -= self.p_random() & 7;
+        if th.tics < 1 {
+            th.tics = 1;
+        }
+    }
+}
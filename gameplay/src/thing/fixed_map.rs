@@ -0,0 +1,1583 @@
+//! Collision, hitscans and line use for the fixed-point mode
+//! (`GameOptions::fixed_point`), done as the original engine did them: the
+//! lines and things near a move are found through the `BlockMap` in the same
+//! order, and every check rounds in 16.16 fixed-point.
+//!
+//! Doom source name `p_maputl`, `p_map`
+
+use std::ptr;
+
+use glam::Vec3;
+use sound_traits::SfxName;
+
+use crate::doom_def::{ONCEILINGZ, ONFLOORZ};
+use crate::env::specials::{cross_special_line, shoot_special_line};
+use crate::env::switch::p_use_special_line;
+use crate::fixed::{
+    aprox_distance, finecosine, finesine, fixed_div, fixed_mul, float_to_fixed, point_to_angle2,
+    Bam, Fixed, ANG180, ANGLETOFINESHIFT, FRACBITS, FRACUNIT,
+};
+use crate::info::{MapObjKind, StateNum, MOBJINFO};
+use crate::level::blockmap::{
+    BOXBOTTOM, BOXLEFT, BOXRIGHT, BOXTOP, MAPBLOCKSHIFT, MAPBLOCKSIZE, MAPBTOFRAC, MAXRADIUS,
+};
+use crate::level::flags::LineDefFlags;
+use crate::level::map_defs::{LineDef, Sector, SlopeType};
+use crate::level::Level;
+use crate::utilities::fixed_to_float;
+use crate::{MapObject, MapPtr};
+
+use super::movement::{SubSectorMinMax, PT_ADDLINES, PT_ADDTHINGS, PT_EARLYOUT};
+use super::shooting::AimResult;
+use super::MapObjFlag;
+
+const MELEERANGE: Fixed = 64 * FRACUNIT;
+const USERANGE: Fixed = 64 * FRACUNIT;
+/// Aiming is limited to the slopes the original view could see
+const VIEW_SLOPE: Fixed = 100 * FRACUNIT / 160;
+
+/// A line as a start point and a delta
+///
+/// Doom type name `divline_t`
+#[derive(Debug, Clone, Copy)]
+struct DivLine {
+    x: Fixed,
+    y: Fixed,
+    dx: Fixed,
+    dy: Fixed,
+}
+
+impl DivLine {
+    /// Doom function name `P_MakeDivline`
+    fn from_line(line: &LineDef) -> Self {
+        Self {
+            x: float_to_fixed(line.v1.x),
+            y: float_to_fixed(line.v1.y),
+            dx: float_to_fixed(line.delta.x),
+            dy: float_to_fixed(line.delta.y),
+        }
+    }
+
+    /// The side of the line that the point is on, `0` is the front
+    ///
+    /// Doom function name `P_PointOnLineSide`
+    fn line_side(&self, x: Fixed, y: Fixed) -> usize {
+        if self.dx == 0 {
+            return if x <= self.x {
+                (self.dy > 0) as usize
+            } else {
+                (self.dy < 0) as usize
+            };
+        }
+        if self.dy == 0 {
+            return if y <= self.y {
+                (self.dx < 0) as usize
+            } else {
+                (self.dx > 0) as usize
+            };
+        }
+
+        let dx = x.wrapping_sub(self.x);
+        let dy = y.wrapping_sub(self.y);
+        let left = fixed_mul(self.dy >> FRACBITS, dx);
+        let right = fixed_mul(dy, self.dx >> FRACBITS);
+        if right < left {
+            return 0; // front side
+        }
+        1 // back side
+    }
+
+    /// `line_side()` with less precision but a much larger range, for traces
+    ///
+    /// Doom function name `P_PointOnDivlineSide`
+    fn divline_side(&self, x: Fixed, y: Fixed) -> usize {
+        if self.dx == 0 {
+            return if x <= self.x {
+                (self.dy > 0) as usize
+            } else {
+                (self.dy < 0) as usize
+            };
+        }
+        if self.dy == 0 {
+            return if y <= self.y {
+                (self.dx < 0) as usize
+            } else {
+                (self.dx > 0) as usize
+            };
+        }
+
+        let dx = x.wrapping_sub(self.x);
+        let dy = y.wrapping_sub(self.y);
+        // try to quickly decide by looking at sign bits
+        if (self.dy ^ self.dx ^ dx ^ dy) < 0 {
+            // left is negative
+            return ((self.dy ^ dx) < 0) as usize;
+        }
+
+        let left = fixed_mul(self.dy >> 8, dx >> 8);
+        let right = fixed_mul(dy >> 8, self.dx >> 8);
+        if right < left {
+            return 0; // front side
+        }
+        1 // back side
+    }
+
+    /// The fraction along `self` where `line` crosses it, `0` if parallel
+    ///
+    /// Doom function name `P_InterceptVector`
+    fn intercept(&self, line: &DivLine) -> Fixed {
+        let den = fixed_mul(line.dy >> 8, self.dx).wrapping_sub(fixed_mul(line.dx >> 8, self.dy));
+        if den == 0 {
+            return 0;
+        }
+        let num = fixed_mul(line.x.wrapping_sub(self.x) >> 8, line.dy)
+            .wrapping_add(fixed_mul(self.y.wrapping_sub(line.y) >> 8, line.dx));
+        fixed_div(num, den)
+    }
+}
+
+/// The side of `line` a box is on, or `-1` if it crosses the line
+///
+/// Doom function name `P_BoxOnLineSide`
+fn box_on_line_side(bbox: &[Fixed; 4], line: &LineDef) -> i32 {
+    let dl = DivLine::from_line(line);
+    let (p1, p2) = match line.slopetype {
+        SlopeType::Horizontal => {
+            let p = (bbox[BOXTOP] > dl.y, bbox[BOXBOTTOM] > dl.y);
+            if dl.dx < 0 {
+                (!p.0, !p.1)
+            } else {
+                p
+            }
+        }
+        SlopeType::Vertical => {
+            let p = (bbox[BOXRIGHT] < dl.x, bbox[BOXLEFT] < dl.x);
+            if dl.dy < 0 {
+                (!p.0, !p.1)
+            } else {
+                p
+            }
+        }
+        SlopeType::Positive => (
+            dl.line_side(bbox[BOXLEFT], bbox[BOXTOP]) == 1,
+            dl.line_side(bbox[BOXRIGHT], bbox[BOXBOTTOM]) == 1,
+        ),
+        SlopeType::Negative => (
+            dl.line_side(bbox[BOXRIGHT], bbox[BOXTOP]) == 1,
+            dl.line_side(bbox[BOXLEFT], bbox[BOXBOTTOM]) == 1,
+        ),
+    };
+    if p1 == p2 {
+        return p1 as i32;
+    }
+    -1
+}
+
+/// The gap through a two sided line
+#[derive(Debug, Default)]
+struct Opening {
+    top: Fixed,
+    bottom: Fixed,
+    range: Fixed,
+    lowfloor: Fixed,
+}
+
+/// Doom function name `P_LineOpening`
+fn line_opening(line: &LineDef) -> Opening {
+    let Some(back) = line.backsector.as_ref() else {
+        // single sided line
+        return Opening::default();
+    };
+    let front = &line.frontsector;
+    let top = float_to_fixed(front.ceilingheight.min(back.ceilingheight));
+    let bottom = float_to_fixed(front.floorheight.max(back.floorheight));
+    Opening {
+        top,
+        bottom,
+        range: top - bottom,
+        lowfloor: float_to_fixed(front.floorheight.min(back.floorheight)),
+    }
+}
+
+#[derive(Clone)]
+enum Intercepted {
+    Line(MapPtr<LineDef>),
+    Thing(*mut MapObject),
+}
+
+/// A line or thing crossed by a trace, `frac` along it
+///
+/// Doom type name `intercept_t`
+struct FixedIntercept {
+    frac: Fixed,
+    hit: Intercepted,
+}
+
+/// Doom function name `P_BlockLinesIterator`
+fn block_lines_iterator(
+    level: &mut Level,
+    x: i32,
+    y: i32,
+    mut func: impl FnMut(&mut LineDef) -> bool,
+) -> bool {
+    let Some(block) = level.map_data.blockmap.block(x, y) else {
+        return true;
+    };
+    let valid_count = level.valid_count;
+    let map_data = &mut level.map_data;
+    for &num in map_data.blockmap.block_lines(block) {
+        let Some(line) = map_data.linedefs.get_mut(num as usize) else {
+            continue;
+        };
+        if line.valid_count == valid_count {
+            continue; // line has already been checked
+        }
+        line.valid_count = valid_count;
+        if !func(line) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Doom function name `P_BlockThingsIterator`
+pub(super) fn block_things_iterator(
+    level: &mut Level,
+    x: i32,
+    y: i32,
+    mut func: impl FnMut(&mut MapObject) -> bool,
+) -> bool {
+    let Some(block) = level.map_data.blockmap.block(x, y) else {
+        return true;
+    };
+    let mut link = level.map_data.blockmap.links[block];
+    while let Some(thinker) = link {
+        let thing = unsafe { (*thinker).mobj_mut() };
+        if !func(thing) {
+            return false;
+        }
+        // Read after, the thing may have unlinked itself but it is not freed
+        // until the thinkers next run
+        link = thing.b_next;
+    }
+    true
+}
+
+/// The blocks covering `left..=right` and `bottom..=top`, as `(x, y)` ranges
+pub(super) fn block_range(
+    level: &Level,
+    left: Fixed,
+    right: Fixed,
+    bottom: Fixed,
+    top: Fixed,
+) -> ([i32; 2], [i32; 2]) {
+    let blockmap = &level.map_data.blockmap;
+    let block = |v: Fixed, org: Fixed| v.wrapping_sub(org) >> MAPBLOCKSHIFT;
+    (
+        [block(left, blockmap.orgx), block(right, blockmap.orgx)],
+        [block(bottom, blockmap.orgy), block(top, blockmap.orgy)],
+    )
+}
+
+/// Doom function name `PIT_AddLineIntercepts`
+fn add_line_intercepts(
+    trace: &DivLine,
+    line: &mut LineDef,
+    earlyout: bool,
+    intercepts: &mut Vec<FixedIntercept>,
+) -> bool {
+    let dl = DivLine::from_line(line);
+    // avoid precision problems with two routines
+    let (s1, s2) = if trace.dx > FRACUNIT * 16
+        || trace.dy > FRACUNIT * 16
+        || trace.dx < -FRACUNIT * 16
+        || trace.dy < -FRACUNIT * 16
+    {
+        (
+            trace.divline_side(dl.x, dl.y),
+            trace.divline_side(dl.x.wrapping_add(dl.dx), dl.y.wrapping_add(dl.dy)),
+        )
+    } else {
+        (
+            dl.line_side(trace.x, trace.y),
+            dl.line_side(
+                trace.x.wrapping_add(trace.dx),
+                trace.y.wrapping_add(trace.dy),
+            ),
+        )
+    };
+    if s1 == s2 {
+        return true; // line isn't crossed
+    }
+
+    // hit the line
+    let frac = trace.intercept(&dl);
+    if frac < 0 {
+        return true; // behind source
+    }
+    // try to early out the check
+    if earlyout && frac < FRACUNIT && line.backsector.is_none() {
+        return false; // stop checking
+    }
+
+    intercepts.push(FixedIntercept {
+        frac,
+        hit: Intercepted::Line(MapPtr::new(line)),
+    });
+    true
+}
+
+/// Doom function name `PIT_AddThingIntercepts`
+fn add_thing_intercepts(
+    trace: &DivLine,
+    thing: &mut MapObject,
+    intercepts: &mut Vec<FixedIntercept>,
+) -> bool {
+    let (x, y, _) = thing.fixed_pos();
+    let radius = float_to_fixed(thing.radius);
+    // check a corner to corner crossection for hit
+    let (x1, y1, x2, y2) = if (trace.dx ^ trace.dy) > 0 {
+        (x - radius, y + radius, x + radius, y - radius)
+    } else {
+        (x - radius, y - radius, x + radius, y + radius)
+    };
+    let s1 = trace.divline_side(x1, y1);
+    let s2 = trace.divline_side(x2, y2);
+    if s1 == s2 {
+        return true; // line isn't crossed
+    }
+
+    let dl = DivLine {
+        x: x1,
+        y: y1,
+        dx: x2 - x1,
+        dy: y2 - y1,
+    };
+    let frac = trace.intercept(&dl);
+    if frac < 0 {
+        return true; // behind source
+    }
+
+    intercepts.push(FixedIntercept {
+        frac,
+        hit: Intercepted::Thing(thing),
+    });
+    true
+}
+
+/// Call `func` on each intercept from nearest to furthest, stopping if it
+/// returns false
+///
+/// Doom function name `P_TraverseIntercepts`
+fn traverse_intercepts(
+    trace: &DivLine,
+    intercepts: &mut [FixedIntercept],
+    maxfrac: Fixed,
+    mut func: impl FnMut(&DivLine, &FixedIntercept) -> bool,
+) -> bool {
+    for _ in 0..intercepts.len() {
+        let mut dist = Fixed::MAX;
+        let mut next = 0;
+        for (i, scan) in intercepts.iter().enumerate() {
+            if scan.frac < dist {
+                dist = scan.frac;
+                next = i;
+            }
+        }
+        if dist > maxfrac {
+            return true; // checked everything in range
+        }
+        if !func(trace, &intercepts[next]) {
+            return false; // don't bother going farther
+        }
+        intercepts[next].frac = Fixed::MAX;
+    }
+    true
+}
+
+/// Trace a line from `(x1, y1)` to `(x2, y2)`, calling `trav` for each line
+/// and/or thing crossed, nearest first. Returns true if the traverser went all
+/// the way.
+///
+/// Doom function name `P_PathTraverse`
+fn path_traverse(
+    level: &mut Level,
+    (mut x1, mut y1): (Fixed, Fixed),
+    (mut x2, mut y2): (Fixed, Fixed),
+    flags: i32,
+    trav: impl FnMut(&DivLine, &FixedIntercept) -> bool,
+) -> bool {
+    let earlyout = flags & PT_EARLYOUT != 0;
+    level.valid_count = level.valid_count.wrapping_add(1);
+    let mut intercepts = Vec::new();
+
+    let (orgx, orgy) = (level.map_data.blockmap.orgx, level.map_data.blockmap.orgy);
+    if x1.wrapping_sub(orgx) & (MAPBLOCKSIZE - 1) == 0 {
+        x1 += FRACUNIT; // don't side exactly on a line
+    }
+    if y1.wrapping_sub(orgy) & (MAPBLOCKSIZE - 1) == 0 {
+        y1 += FRACUNIT; // don't side exactly on a line
+    }
+    let trace = DivLine {
+        x: x1,
+        y: y1,
+        dx: x2.wrapping_sub(x1),
+        dy: y2.wrapping_sub(y1),
+    };
+
+    x1 = x1.wrapping_sub(orgx);
+    y1 = y1.wrapping_sub(orgy);
+    let (xt1, yt1) = (x1 >> MAPBLOCKSHIFT, y1 >> MAPBLOCKSHIFT);
+    x2 = x2.wrapping_sub(orgx);
+    y2 = y2.wrapping_sub(orgy);
+    let (xt2, yt2) = (x2 >> MAPBLOCKSHIFT, y2 >> MAPBLOCKSHIFT);
+
+    let (mapxstep, partial, ystep) = if xt2 > xt1 {
+        (
+            1,
+            FRACUNIT - ((x1 >> MAPBTOFRAC) & (FRACUNIT - 1)),
+            fixed_div(y2.wrapping_sub(y1), x2.wrapping_sub(x1).wrapping_abs()),
+        )
+    } else if xt2 < xt1 {
+        (
+            -1,
+            (x1 >> MAPBTOFRAC) & (FRACUNIT - 1),
+            fixed_div(y2.wrapping_sub(y1), x2.wrapping_sub(x1).wrapping_abs()),
+        )
+    } else {
+        (0, FRACUNIT, 256 * FRACUNIT)
+    };
+    let mut yintercept = (y1 >> MAPBTOFRAC).wrapping_add(fixed_mul(partial, ystep));
+
+    let (mapystep, partial, xstep) = if yt2 > yt1 {
+        (
+            1,
+            FRACUNIT - ((y1 >> MAPBTOFRAC) & (FRACUNIT - 1)),
+            fixed_div(x2.wrapping_sub(x1), y2.wrapping_sub(y1).wrapping_abs()),
+        )
+    } else if yt2 < yt1 {
+        (
+            -1,
+            (y1 >> MAPBTOFRAC) & (FRACUNIT - 1),
+            fixed_div(x2.wrapping_sub(x1), y2.wrapping_sub(y1).wrapping_abs()),
+        )
+    } else {
+        (0, FRACUNIT, 256 * FRACUNIT)
+    };
+    let mut xintercept = (x1 >> MAPBTOFRAC).wrapping_add(fixed_mul(partial, xstep));
+
+    // Step through map blocks.
+    // Count is present to prevent a round off error
+    // from skipping the break.
+    let (mut mapx, mut mapy) = (xt1, yt1);
+    for _ in 0..64 {
+        if flags & PT_ADDLINES != 0
+            && !block_lines_iterator(level, mapx, mapy, |line| {
+                add_line_intercepts(&trace, line, earlyout, &mut intercepts)
+            })
+        {
+            return false; // early out
+        }
+        if flags & PT_ADDTHINGS != 0
+            && !block_things_iterator(level, mapx, mapy, |thing| {
+                add_thing_intercepts(&trace, thing, &mut intercepts)
+            })
+        {
+            return false; // early out
+        }
+
+        if mapx == xt2 && mapy == yt2 {
+            break;
+        }
+        if yintercept >> FRACBITS == mapy {
+            yintercept = yintercept.wrapping_add(ystep);
+            mapx += mapxstep;
+        } else if xintercept >> FRACBITS == mapx {
+            xintercept = xintercept.wrapping_add(xstep);
+            mapy += mapystep;
+        }
+    }
+
+    // go through the sorted list
+    traverse_intercepts(&trace, &mut intercepts, FRACUNIT, trav)
+}
+
+/// What a position check found, the original's `tm*` globals
+#[derive(Default)]
+pub(crate) struct FixedCheck {
+    /// If true the move would be ok if within `floorz - ceilingz`
+    pub floatok: bool,
+    pub floorz: Fixed,
+    pub ceilingz: Fixed,
+    pub dropoffz: Fixed,
+    /// The line that lowered the ceiling, a missile hitting it is removed if
+    /// the back of it is sky
+    pub ceilingline: Option<MapPtr<LineDef>>,
+    x: Fixed,
+    y: Fixed,
+    bbox: [Fixed; 4],
+    spechit: Vec<MapPtr<LineDef>>,
+}
+
+impl FixedCheck {
+    /// Copy to the `SubSectorMinMax` used by the `f32` checks
+    pub(super) fn fill(&self, ctrl: &mut SubSectorMinMax, level: &Level) {
+        ctrl.floatok = self.floatok;
+        ctrl.min_floor_z = fixed_to_float(self.floorz);
+        ctrl.max_ceil_z = fixed_to_float(self.ceilingz);
+        ctrl.max_dropoff = fixed_to_float(self.dropoffz);
+        ctrl.sky_line.clone_from(&self.ceilingline);
+        ctrl.spec_hits.clone_from(&level.spechit);
+    }
+}
+
+/// The blocks a change in height of `sector` reaches
+///
+/// Doom function name `P_ChangeSector`
+pub(crate) fn fixed_change_sector(level: &mut Level, sector: &Sector, crunch: bool) -> bool {
+    let mut nofit = false;
+    let [top, bottom, left, right] = level.map_data.blockmap.sector_blockbox(sector);
+    // re-check heights for all things near the moving sector
+    for x in left..=right {
+        for y in bottom..=top {
+            block_things_iterator(level, x, y, |thing| {
+                thing.fixed_pit_change_sector(&mut nofit, crunch)
+            });
+        }
+    }
+    nofit
+}
+
+impl MapObject {
+    /// The fixed-point position of a point given as `f32`, keeping the exact
+    /// position where it is this thing's own
+    pub(super) fn fixed_xy(&self, point: Vec3) -> (Fixed, Fixed) {
+        let (x, y, _) = self.fixed_pos();
+        (
+            if point.x == self.xyz.x {
+                x
+            } else {
+                float_to_fixed(point.x)
+            },
+            if point.y == self.xyz.y {
+                y
+            } else {
+                float_to_fixed(point.y)
+            },
+        )
+    }
+
+    /// Link in to the sector and the `BlockMap` at the fixed-point position
+    ///
+    /// Doom function name `P_SetThingPosition`
+    pub(super) unsafe fn set_thing_position_fixed(&mut self) {
+        let level = &mut *self.level;
+        let (x, y, _) = self.fixed_pos();
+        let mut subsector = level.map_data.point_in_subsector_fixed(x, y);
+        if MOBJINFO[self.kind as usize].flags & MapObjFlag::Nosector as u32 == 0 {
+            subsector.sector.add_to_thinglist(self.thinker)
+        }
+        self.subsector = subsector;
+
+        if self.flags & MapObjFlag::Noblockmap as u32 != 0 {
+            return;
+        }
+        let blockmap = &mut level.map_data.blockmap;
+        if let Some(block) = blockmap.block_at(x, y) {
+            self.b_prev = None;
+            self.b_next = blockmap.links[block];
+            if let Some(next) = self.b_next {
+                (*next).mobj_mut().b_prev = Some(self.thinker);
+            }
+            blockmap.links[block] = Some(self.thinker);
+            self.block = Some(block);
+        } else {
+            // thing is off the map
+            self.b_next = None;
+            self.b_prev = None;
+        }
+    }
+
+    /// `spawn_map_object()` at a fixed-point position. `z` may be `ONFLOORZ`
+    /// or `ONCEILINGZ`.
+    ///
+    /// Doom function name `P_SpawnMobj`
+    pub(crate) fn spawn_map_object_fixed(
+        x: Fixed,
+        y: Fixed,
+        z: Fixed,
+        kind: MapObjKind,
+        level: &mut Level,
+    ) -> *mut MapObject {
+        let mut mobj =
+            MapObject::new_for_spawn(fixed_to_float(x), fixed_to_float(y), 0, kind, level);
+        mobj.fixed.x = x;
+        mobj.fixed.y = y;
+        let thing = MapObject::push_spawned(mobj, level);
+
+        // set subsector and/or block links
+        thing.floorz = thing.subsector.sector.floorheight;
+        thing.ceilingz = thing.subsector.sector.ceilingheight;
+        thing.fixed.z = if z == ONFLOORZ {
+            float_to_fixed(thing.floorz)
+        } else if z == ONCEILINGZ {
+            float_to_fixed(thing.ceilingz) - float_to_fixed(thing.info.height)
+        } else {
+            z
+        };
+        thing.xyz.z = fixed_to_float(thing.fixed.z);
+        thing
+    }
+
+    /// Check for things and lines contacts at the position, filling `check`
+    /// with the heights found
+    ///
+    /// Doom function name `P_CheckPosition`
+    pub(crate) fn fixed_check_position(
+        &mut self,
+        x: Fixed,
+        y: Fixed,
+        check: &mut FixedCheck,
+    ) -> bool {
+        let level = unsafe { &mut *self.level };
+        let fits = self.fixed_check_blocks(x, y, check);
+        level.spechit = std::mem::take(&mut check.spechit);
+        fits
+    }
+
+    fn fixed_check_blocks(&mut self, x: Fixed, y: Fixed, check: &mut FixedCheck) -> bool {
+        let level = unsafe { &mut *self.level };
+        let radius = float_to_fixed(self.radius);
+        check.x = x;
+        check.y = y;
+        check.bbox[BOXTOP] = y + radius;
+        check.bbox[BOXBOTTOM] = y - radius;
+        check.bbox[BOXRIGHT] = x + radius;
+        check.bbox[BOXLEFT] = x - radius;
+
+        let subsector = level.map_data.point_in_subsector_fixed(x, y);
+        check.ceilingline = None;
+
+        // The base floor / ceiling is from the subsector
+        // that contains the point.
+        // Any contacted lines the step closer together
+        // will adjust them.
+        check.floorz = float_to_fixed(subsector.sector.floorheight);
+        check.dropoffz = check.floorz;
+        check.ceilingz = float_to_fixed(subsector.sector.ceilingheight);
+
+        level.valid_count = level.valid_count.wrapping_add(1);
+        check.spechit.clear();
+
+        if self.flags & MapObjFlag::Noclip as u32 != 0 {
+            return true;
+        }
+
+        // Check things first, possibly picking things up.
+        // The bounding box is extended by MAXRADIUS
+        // because mobj_ts are grouped into mapblocks
+        // based on their origin point, and can overlap
+        // into adjacent blocks by up to MAXRADIUS units.
+        let bbox = check.bbox;
+        let ([xl, xh], [yl, yh]) = block_range(
+            level,
+            bbox[BOXLEFT] - MAXRADIUS,
+            bbox[BOXRIGHT] + MAXRADIUS,
+            bbox[BOXBOTTOM] - MAXRADIUS,
+            bbox[BOXTOP] + MAXRADIUS,
+        );
+        for bx in xl..=xh {
+            for by in yl..=yh {
+                if !block_things_iterator(level, bx, by, |thing| {
+                    self.fixed_pit_check_thing(thing, check)
+                }) {
+                    return false;
+                }
+            }
+        }
+
+        // check lines
+        let ([xl, xh], [yl, yh]) = block_range(
+            level,
+            bbox[BOXLEFT],
+            bbox[BOXRIGHT],
+            bbox[BOXBOTTOM],
+            bbox[BOXTOP],
+        );
+        for bx in xl..=xh {
+            for by in yl..=yh {
+                if !block_lines_iterator(level, bx, by, |line| {
+                    self.fixed_pit_check_line(line, check)
+                }) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Doom function name `PIT_CheckThing`
+    fn fixed_pit_check_thing(&mut self, thing: &mut MapObject, check: &FixedCheck) -> bool {
+        if thing.flags
+            & (MapObjFlag::Solid as u32 | MapObjFlag::Special as u32 | MapObjFlag::Shootable as u32)
+            == 0
+        {
+            return true;
+        }
+
+        let (tx, ty, tz) = thing.fixed_pos();
+        let blockdist = float_to_fixed(thing.radius) + float_to_fixed(self.radius);
+        if tx.wrapping_sub(check.x).wrapping_abs() >= blockdist
+            || ty.wrapping_sub(check.y).wrapping_abs() >= blockdist
+        {
+            return true; // didn't hit it
+        }
+
+        // don't clip against self
+        if ptr::eq(self, thing) {
+            return true;
+        }
+
+        // check for skulls slamming into things
+        if self.flags & MapObjFlag::Skullfly as u32 != 0 {
+            let damage = ((self.p_random() % 8) + 1) * self.info.damage;
+            thing.p_take_damage(None, Some(self), true, damage);
+
+            self.flags &= !(MapObjFlag::Skullfly as u32);
+            self.fixed.momx = 0;
+            self.fixed.momy = 0;
+            self.fixed.momz = 0;
+            self.momxyz = Vec3::default();
+            self.set_state(self.info.spawnstate);
+            return false; // stop moving
+        }
+
+        // missiles can hit other things
+        if self.flags & MapObjFlag::Missile as u32 != 0 {
+            // see if it went over / under
+            if self.fixed.z > tz + float_to_fixed(thing.height) {
+                return true; // overhead
+            }
+            if self.fixed.z + float_to_fixed(self.height) < tz {
+                return true; // underneath
+            }
+
+            let target = self.target.map(|t| unsafe { (*t).mobj_mut() });
+            if let Some(target) = target.as_ref() {
+                if target.kind == thing.kind
+                    || (target.kind == MapObjKind::MT_KNIGHT
+                        && thing.kind == MapObjKind::MT_BRUISER)
+                    || (target.kind == MapObjKind::MT_BRUISER
+                        && thing.kind == MapObjKind::MT_KNIGHT)
+                {
+                    // Don't hit same species as originator.
+                    if ptr::eq(thing, *target) {
+                        return true;
+                    }
+                    if thing.kind != MapObjKind::MT_PLAYER {
+                        // Explode, but do no damage.
+                        // Let players missile other players.
+                        return false;
+                    }
+                }
+            }
+
+            if thing.flags & MapObjFlag::Shootable as u32 == 0 {
+                // didn't do any damage
+                return thing.flags & MapObjFlag::Solid as u32 == 0;
+            }
+
+            // damage / explode
+            let damage = ((self.p_random() % 8) + 1) * self.info.damage;
+            thing.p_take_damage(Some(self), target, false, damage);
+            return false; // don't traverse any more
+        }
+
+        // check for special pickup
+        if thing.flags & MapObjFlag::Special as u32 != 0 {
+            let solid = thing.flags & MapObjFlag::Solid as u32 != 0;
+            if self.flags & MapObjFlag::Pickup as u32 != 0 {
+                // can remove thing
+                self.touch_special(thing);
+            }
+            return !solid;
+        }
+
+        thing.flags & MapObjFlag::Solid as u32 == 0
+    }
+
+    /// Adjusts the heights in `check` as lines are contacted
+    ///
+    /// Doom function name `PIT_CheckLine`
+    fn fixed_pit_check_line(&mut self, line: &mut LineDef, check: &mut FixedCheck) -> bool {
+        let bbox = &check.bbox;
+        if bbox[BOXRIGHT] <= float_to_fixed(line.bbox.left)
+            || bbox[BOXLEFT] >= float_to_fixed(line.bbox.right)
+            || bbox[BOXTOP] <= float_to_fixed(line.bbox.bottom)
+            || bbox[BOXBOTTOM] >= float_to_fixed(line.bbox.top)
+        {
+            return true;
+        }
+        if box_on_line_side(bbox, line) != -1 {
+            return true;
+        }
+
+        // A line has been hit
+        if line.backsector.is_none() {
+            return false; // one sided line
+        }
+        if self.flags & MapObjFlag::Missile as u32 == 0 {
+            if line.flags & LineDefFlags::Blocking as u32 != 0 {
+                return false; // explicitly blocking everything
+            }
+            if self.player.is_none() && line.flags & LineDefFlags::BlockMonsters as u32 != 0 {
+                return false; // block monsters only
+            }
+        }
+
+        // set openrange, opentop, openbottom
+        let opening = line_opening(line);
+        // adjust floor / ceiling heights
+        if opening.top < check.ceilingz {
+            check.ceilingz = opening.top;
+            check.ceilingline = Some(MapPtr::new(line));
+        }
+        if opening.bottom > check.floorz {
+            check.floorz = opening.bottom;
+        }
+        if opening.lowfloor < check.dropoffz {
+            check.dropoffz = opening.lowfloor;
+        }
+
+        // if contacted a special line, add it to the list
+        if line.special != 0 {
+            check.spechit.push(MapPtr::new(line));
+        }
+        true
+    }
+
+    /// Attempt to move to a new position, crossing special lines unless
+    /// `Teleport` or `Noclip` is set
+    ///
+    /// Doom function name `P_TryMove`
+    pub(crate) fn fixed_try_move(&mut self, x: Fixed, y: Fixed, check: &mut FixedCheck) -> bool {
+        check.floatok = false;
+        if !self.fixed_check_position(x, y, check) {
+            return false; // solid wall or thing
+        }
+
+        if self.flags & MapObjFlag::Noclip as u32 == 0 {
+            let height = float_to_fixed(self.height);
+            if check.ceilingz - check.floorz < height {
+                return false; // doesn't fit
+            }
+            check.floatok = true;
+
+            if self.flags & MapObjFlag::Teleport as u32 == 0
+                && check.ceilingz - self.fixed.z < height
+            {
+                return false; // mobj must lower itself to fit
+            }
+            if self.flags & MapObjFlag::Teleport as u32 == 0
+                && check.floorz - self.fixed.z > 24 * FRACUNIT
+            {
+                return false; // too big a step up
+            }
+            if self.flags & (MapObjFlag::Dropoff as u32 | MapObjFlag::Float as u32) == 0
+                && check.floorz - check.dropoffz > 24 * FRACUNIT
+            {
+                return false; // don't stand over a dropoff
+            }
+        }
+
+        // the move is ok,
+        // so link the thing into its new position
+        unsafe {
+            self.unset_thing_position();
+        }
+        let (oldx, oldy) = (self.fixed.x, self.fixed.y);
+        self.floorz = fixed_to_float(check.floorz);
+        self.ceilingz = fixed_to_float(check.ceilingz);
+        self.fixed.x = x;
+        self.fixed.y = y;
+        self.xyz.x = fixed_to_float(x);
+        self.xyz.y = fixed_to_float(y);
+        unsafe {
+            self.set_thing_position();
+        }
+
+        // if any special lines were hit, do the effect
+        if self.flags & (MapObjFlag::Teleport as u32 | MapObjFlag::Noclip as u32) == 0 {
+            let level = unsafe { &mut *self.level };
+            // A teleport empties the list, ending this
+            while let Some(line) = level.spechit.pop() {
+                // see if the line was crossed
+                let dl = DivLine::from_line(&line);
+                let side = dl.line_side(self.fixed.x, self.fixed.y);
+                let old_side = dl.line_side(oldx, oldy);
+                if side != old_side && line.special != 0 {
+                    cross_special_line(old_side, line, self, false);
+                }
+            }
+        }
+        true
+    }
+
+    /// Takes a valid thing and adjusts the floorz, ceilingz, and possibly z.
+    /// Returns false if the thing doesn't fit.
+    ///
+    /// Doom function name `P_ThingHeightClip`
+    fn fixed_height_clip(&mut self) -> bool {
+        let onfloor = self.fixed.z == float_to_fixed(self.floorz);
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        let mut check = FixedCheck::default();
+        self.fixed_check_position(x, y, &mut check);
+        // what about stranding a monster partially off an edge?
+        self.floorz = fixed_to_float(check.floorz);
+        self.ceilingz = fixed_to_float(check.ceilingz);
+
+        let height = float_to_fixed(self.height);
+        if onfloor {
+            // walking monsters rise and fall with the floor
+            self.fixed.z = check.floorz;
+        } else if self.fixed.z + height > check.ceilingz {
+            // don't adjust a floating monster unless forced to
+            self.fixed.z = check.ceilingz - height;
+        }
+        self.xyz.z = fixed_to_float(self.fixed.z);
+
+        check.ceilingz - check.floorz >= height
+    }
+
+    /// Clip a thing near a sector that moved, crushing it if it doesn't fit
+    ///
+    /// Doom function name `PIT_ChangeSector`
+    fn fixed_pit_change_sector(&mut self, nofit: &mut bool, crunch: bool) -> bool {
+        self.sync_fixed();
+        if self.fixed_height_clip() {
+            return true; // keep checking
+        }
+
+        // crunch bodies to giblets
+        if self.health <= 0 {
+            self.set_state(StateNum::GIBS);
+            self.flags &= !(MapObjFlag::Solid as u32);
+            self.height = 0.0;
+            self.radius = 0.0;
+            return true;
+        }
+
+        // crunch dropped items
+        if self.flags & MapObjFlag::Dropped as u32 != 0 {
+            self.remove();
+            return true;
+        }
+
+        if self.flags & MapObjFlag::Shootable as u32 == 0 {
+            // assume it is bloody gibs or something
+            return true;
+        }
+
+        *nofit = true;
+        if crunch && self.level().level_time & 3 == 0 {
+            self.p_take_damage(None, None, false, 10);
+            // spray blood in a random direction
+            let z = self.fixed.z + float_to_fixed(self.height) / 2;
+            let level = unsafe { &mut *self.level };
+            let mobj = unsafe {
+                &mut *MapObject::spawn_map_object_fixed(
+                    self.fixed.x,
+                    self.fixed.y,
+                    z,
+                    MapObjKind::MT_BLOOD,
+                    level,
+                )
+            };
+            mobj.fixed.momx = (level.random.p_random() - level.random.p_random()) << 12;
+            mobj.fixed.momy = (level.random.p_random() - level.random.p_random()) << 12;
+            mobj.store_fixed();
+        }
+        true // keep checking (crush other things)
+    }
+
+    /// Move to a position, killing anything in the way
+    ///
+    /// Doom function name `P_TeleportMove`
+    pub(crate) fn fixed_teleport_move(&mut self, x: Fixed, y: Fixed) -> bool {
+        let level = unsafe { &mut *self.level };
+        let radius = float_to_fixed(self.radius);
+        let subsector = level.map_data.point_in_subsector_fixed(x, y);
+        // The base floor/ceiling is from the subsector that contains the point.
+        let floorz = subsector.sector.floorheight;
+        let ceilingz = subsector.sector.ceilingheight;
+        level.valid_count = level.valid_count.wrapping_add(1);
+        level.spechit.clear();
+
+        // stomp on any things contacted
+        let ([xl, xh], [yl, yh]) = block_range(
+            level,
+            x - radius - MAXRADIUS,
+            x + radius + MAXRADIUS,
+            y - radius - MAXRADIUS,
+            y + radius + MAXRADIUS,
+        );
+        let game_map = level.options.map;
+        for bx in xl..=xh {
+            for by in yl..=yh {
+                if !block_things_iterator(level, bx, by, |thing| {
+                    self.fixed_stomp_thing(thing, x, y, game_map)
+                }) {
+                    return false;
+                }
+            }
+        }
+
+        // the move is ok, so link the thing into its new position
+        unsafe {
+            self.unset_thing_position();
+        }
+        self.floorz = floorz;
+        self.ceilingz = ceilingz;
+        self.fixed.x = x;
+        self.fixed.y = y;
+        self.xyz.x = fixed_to_float(x);
+        self.xyz.y = fixed_to_float(y);
+        unsafe {
+            self.set_thing_position();
+        }
+        true
+    }
+
+    /// Doom function name `PIT_StompThing`
+    fn fixed_stomp_thing(
+        &mut self,
+        thing: &mut MapObject,
+        x: Fixed,
+        y: Fixed,
+        game_map: usize,
+    ) -> bool {
+        if thing.flags & MapObjFlag::Shootable as u32 == 0 {
+            return true;
+        }
+        let (tx, ty, _) = thing.fixed_pos();
+        let blockdist = float_to_fixed(thing.radius) + float_to_fixed(self.radius);
+        if tx.wrapping_sub(x).wrapping_abs() >= blockdist
+            || ty.wrapping_sub(y).wrapping_abs() >= blockdist
+        {
+            return true; // didn't hit it
+        }
+        // don't clip against self
+        if ptr::eq(self, thing) {
+            return true;
+        }
+        // monsters don't stomp things except on boss level
+        if self.player.is_none() && game_map != 30 {
+            return false;
+        }
+        thing.p_take_damage(None, Some(self), true, 10000);
+        true
+    }
+
+    /// The momentum is blocked, slide along the wall hit or step around it.
+    /// Only players slide.
+    ///
+    /// Doom function name `P_SlideMove`
+    pub(super) fn fixed_slide_move(&mut self) {
+        let radius = float_to_fixed(self.radius);
+        let mut hitcount = 0;
+        loop {
+            hitcount += 1;
+            if hitcount == 3 {
+                self.fixed_stair_step();
+                return; // don't loop forever
+            }
+
+            // trace along the three leading corners
+            let f = self.fixed;
+            let (leadx, trailx) = if f.momx > 0 {
+                (f.x + radius, f.x - radius)
+            } else {
+                (f.x - radius, f.x + radius)
+            };
+            let (leady, traily) = if f.momy > 0 {
+                (f.y + radius, f.y - radius)
+            } else {
+                (f.y - radius, f.y + radius)
+            };
+
+            let mut best = (FRACUNIT + 1, None);
+            for (x, y) in [(leadx, leady), (trailx, leady), (leadx, traily)] {
+                self.fixed_slide_traverse((x, y), (x + f.momx, y + f.momy), &mut best);
+            }
+            let (mut bestfrac, bestline) = best;
+
+            // move up to the wall
+            if bestfrac == FRACUNIT + 1 {
+                // the move most have hit the middle, so stairstep
+                self.fixed_stair_step();
+                return;
+            }
+
+            // fudge a bit to make sure it doesn't hit
+            bestfrac -= 0x800;
+            if bestfrac > 0 {
+                let newx = fixed_mul(f.momx, bestfrac);
+                let newy = fixed_mul(f.momy, bestfrac);
+                if !self.fixed_try_move(f.x + newx, f.y + newy, &mut FixedCheck::default()) {
+                    self.fixed_stair_step();
+                    return;
+                }
+            }
+
+            // Now continue along the wall.
+            // First calculate remainder.
+            bestfrac = (FRACUNIT - (bestfrac + 0x800)).min(FRACUNIT);
+            if bestfrac <= 0 {
+                return;
+            }
+
+            let mut tmmove = (fixed_mul(f.momx, bestfrac), fixed_mul(f.momy, bestfrac));
+            if let Some(line) = bestline {
+                self.fixed_hit_slide_line(&line, &mut tmmove);
+            }
+            self.fixed.momx = tmmove.0;
+            self.fixed.momy = tmmove.1;
+            self.store_fixed();
+
+            let (x, y) = (self.fixed.x + tmmove.0, self.fixed.y + tmmove.1);
+            if self.fixed_try_move(x, y, &mut FixedCheck::default()) {
+                return;
+            }
+        }
+    }
+
+    fn fixed_stair_step(&mut self) {
+        let f = self.fixed;
+        if !self.fixed_try_move(f.x, f.y + f.momy, &mut FixedCheck::default()) {
+            self.fixed_try_move(f.x + f.momx, f.y, &mut FixedCheck::default());
+        }
+    }
+
+    /// Find the nearest line blocking a trace, as `best` (fraction, line)
+    ///
+    /// Doom function name `PTR_SlideTraverse`
+    fn fixed_slide_traverse(
+        &mut self,
+        from: (Fixed, Fixed),
+        to: (Fixed, Fixed),
+        best: &mut (Fixed, Option<MapPtr<LineDef>>),
+    ) {
+        let (x, y, z) = (self.fixed.x, self.fixed.y, self.fixed.z);
+        let height = float_to_fixed(self.height);
+        let level = unsafe { &mut *self.level };
+        path_traverse(level, from, to, PT_ADDLINES, |_, intercept| {
+            let Intercepted::Line(line) = &intercept.hit else {
+                return true;
+            };
+            let blocking = if line.flags & LineDefFlags::TwoSided as u32 == 0 {
+                if DivLine::from_line(line).line_side(x, y) != 0 {
+                    return true; // don't hit the back side
+                }
+                true
+            } else {
+                // set openrange, opentop, openbottom
+                let opening = line_opening(line);
+                opening.range < height // doesn't fit
+                    || opening.top - z < height // mobj is too high
+                    || opening.bottom - z > 24 * FRACUNIT // too big a step up
+            };
+            if !blocking {
+                return true; // this line doesn't block movement
+            }
+
+            // the line does block movement,
+            // see if it is closer than best so far
+            if intercept.frac < best.0 {
+                *best = (intercept.frac, Some(line.clone()));
+            }
+            false // stop
+        });
+    }
+
+    /// Adjusts the move to slide along `line`
+    ///
+    /// Doom function name `P_HitSlideLine`
+    fn fixed_hit_slide_line(&self, line: &LineDef, tmmove: &mut (Fixed, Fixed)) {
+        if matches!(line.slopetype, SlopeType::Horizontal) {
+            tmmove.1 = 0;
+            return;
+        }
+        if matches!(line.slopetype, SlopeType::Vertical) {
+            tmmove.0 = 0;
+            return;
+        }
+
+        let dl = DivLine::from_line(line);
+        let side = dl.line_side(self.fixed.x, self.fixed.y);
+        let mut lineangle = point_to_angle2(0, 0, dl.dx, dl.dy);
+        if side == 1 {
+            lineangle = lineangle.wrapping_add(ANG180);
+        }
+        let moveangle = point_to_angle2(0, 0, tmmove.0, tmmove.1);
+        let mut deltaangle = moveangle.wrapping_sub(lineangle);
+        if deltaangle > ANG180 {
+            deltaangle = deltaangle.wrapping_add(ANG180);
+        }
+
+        let lineangle = (lineangle >> ANGLETOFINESHIFT) as usize;
+        let deltaangle = (deltaangle >> ANGLETOFINESHIFT) as usize;
+        let movelen = aprox_distance(tmmove.0, tmmove.1);
+        let newlen = fixed_mul(movelen, finecosine(deltaangle));
+        *tmmove = (
+            fixed_mul(newlen, finecosine(lineangle)),
+            fixed_mul(newlen, finesine(lineangle)),
+        );
+    }
+
+    /// Looks for special lines in front of the player to activate
+    ///
+    /// Doom function name `P_UseLines`
+    pub(super) fn fixed_use_lines(&mut self) {
+        let fine = (self.fixed.angle >> ANGLETOFINESHIFT) as usize;
+        let (x1, y1) = (self.fixed.x, self.fixed.y);
+        let x2 = x1 + (USERANGE >> FRACBITS) * finecosine(fine);
+        let y2 = y1 + (USERANGE >> FRACBITS) * finesine(fine);
+
+        let level = unsafe { &mut *self.level };
+        path_traverse(level, (x1, y1), (x2, y2), PT_ADDLINES, |_, intercept| {
+            let Intercepted::Line(line) = &intercept.hit else {
+                return true;
+            };
+            if line.special == 0 {
+                if line_opening(line).range <= 0 {
+                    self.start_sound(SfxName::Noway);
+                    // can't use through a wall
+                    return false;
+                }
+                // not a special line, but keep checking
+                return true;
+            }
+
+            let side = DivLine::from_line(line).line_side(self.fixed.x, self.fixed.y);
+            p_use_special_line(side as i32, line.clone(), self, false);
+            // can't use more than one special line in a row
+            false
+        });
+    }
+
+    /// Find a target along `angle`, returning the slope to it. The view can
+    /// be tilted up or down by `view_slope`.
+    ///
+    /// Doom function name `P_AimLineAttack`
+    pub(crate) fn fixed_aim_line_attack(
+        &mut self,
+        angle: Bam,
+        distance: Fixed,
+        view_slope: Fixed,
+    ) -> Option<(Fixed, MapPtr<MapObject>)> {
+        let fine = (angle >> ANGLETOFINESHIFT) as usize;
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        let x2 = x + (distance >> FRACBITS) * finecosine(fine);
+        let y2 = y + (distance >> FRACBITS) * finesine(fine);
+        let shootz = self.fixed.z + (float_to_fixed(self.height) >> 1) + 8 * FRACUNIT;
+
+        // can't shoot outside view angles
+        let mut topslope = view_slope + VIEW_SLOPE;
+        let mut bottomslope = view_slope - VIEW_SLOPE;
+        let mut result = None;
+
+        let level = unsafe { &mut *self.level };
+        path_traverse(
+            level,
+            (x, y),
+            (x2, y2),
+            PT_ADDLINES | PT_ADDTHINGS,
+            |_, intercept| {
+                let dist = fixed_mul(distance, intercept.frac);
+                match &intercept.hit {
+                    Intercepted::Line(line) => {
+                        if line.flags & LineDefFlags::TwoSided as u32 == 0 {
+                            return false; // stop
+                        }
+                        // Crosses a two sided line.
+                        // A two sided line will restrict
+                        // the possible target ranges.
+                        let opening = line_opening(line);
+                        if opening.bottom >= opening.top {
+                            return false; // stop
+                        }
+                        if let Some(back) = line.backsector.as_ref() {
+                            if line.frontsector.floorheight != back.floorheight {
+                                let slope = fixed_div(opening.bottom - shootz, dist);
+                                if slope > bottomslope {
+                                    bottomslope = slope;
+                                }
+                            }
+                            if line.frontsector.ceilingheight != back.ceilingheight {
+                                let slope = fixed_div(opening.top - shootz, dist);
+                                if slope < topslope {
+                                    topslope = slope;
+                                }
+                            }
+                        }
+                        // shot continues if the slopes still leave a gap
+                        topslope > bottomslope
+                    }
+                    Intercepted::Thing(thing) => {
+                        let thing = unsafe { &mut **thing };
+                        if ptr::eq(self, thing) {
+                            return true; // can't shoot self
+                        }
+                        if thing.flags & MapObjFlag::Shootable as u32 == 0 {
+                            return true; // corpse or something
+                        }
+
+                        // check angles to see if the thing can be aimed at
+                        let (_, _, tz) = thing.fixed_pos();
+                        let mut thingtopslope =
+                            fixed_div(tz + float_to_fixed(thing.height) - shootz, dist);
+                        if thingtopslope < bottomslope {
+                            return true; // shot over the thing
+                        }
+                        let mut thingbottomslope = fixed_div(tz - shootz, dist);
+                        if thingbottomslope > topslope {
+                            return true; // shot under the thing
+                        }
+
+                        // this thing can be hit!
+                        thingtopslope = thingtopslope.min(topslope);
+                        thingbottomslope = thingbottomslope.max(bottomslope);
+                        result = Some(((thingtopslope + thingbottomslope) / 2, MapPtr::new(thing)));
+                        false // don't go any farther
+                    }
+                }
+            },
+        );
+        result
+    }
+
+    /// `fixed_aim_line_attack()` as the `AimResult` of the `f32` code
+    pub(crate) fn fixed_aim_result(
+        &mut self,
+        angle: Bam,
+        distance: f32,
+        view_slope: f32,
+    ) -> Option<AimResult> {
+        self.sync_fixed();
+        self.fixed_aim_line_attack(angle, float_to_fixed(distance), float_to_fixed(view_slope))
+            .map(|(slope, line_target)| AimResult {
+                aimslope: fixed_to_float(slope),
+                line_target,
+            })
+    }
+
+    /// Fire a hitscan along `angle` and `slope`, damaging the first thing hit
+    /// or leaving a puff on a wall
+    ///
+    /// Doom function name `P_LineAttack`
+    pub(crate) fn fixed_line_attack(
+        &mut self,
+        angle: Bam,
+        distance: Fixed,
+        slope: Fixed,
+        damage: i32,
+    ) {
+        let fine = (angle >> ANGLETOFINESHIFT) as usize;
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        let x2 = x + (distance >> FRACBITS) * finecosine(fine);
+        let y2 = y + (distance >> FRACBITS) * finesine(fine);
+        let shootz = self.fixed.z + (float_to_fixed(self.height) >> 1) + 8 * FRACUNIT;
+
+        let level = unsafe { &mut *self.level };
+        let sky_num = level.sky_num;
+        // The point `back` along the trace before the intercept
+        let point = |trace: &DivLine, intercept: &FixedIntercept, back: Fixed| {
+            let frac = intercept.frac - fixed_div(back, distance);
+            (
+                trace.x + fixed_mul(trace.dx, frac),
+                trace.y + fixed_mul(trace.dy, frac),
+                shootz + fixed_mul(slope, fixed_mul(frac, distance)),
+            )
+        };
+
+        path_traverse(
+            level,
+            (x, y),
+            (x2, y2),
+            PT_ADDLINES | PT_ADDTHINGS,
+            |trace, intercept| {
+                let level = unsafe { &mut *self.level };
+                match &intercept.hit {
+                    Intercepted::Line(line) => {
+                        if line.special != 0 {
+                            shoot_special_line(line.clone(), self);
+                        }
+
+                        let mut hit = line.flags & LineDefFlags::TwoSided as u32 == 0;
+                        if !hit {
+                            // crosses a two sided line
+                            let opening = line_opening(line);
+                            let dist = fixed_mul(distance, intercept.frac);
+                            if let Some(back) = line.backsector.as_ref() {
+                                if line.frontsector.floorheight != back.floorheight
+                                    && fixed_div(opening.bottom - shootz, dist) > slope
+                                {
+                                    hit = true;
+                                }
+                                if line.frontsector.ceilingheight != back.ceilingheight
+                                    && fixed_div(opening.top - shootz, dist) < slope
+                                {
+                                    hit = true;
+                                }
+                            }
+                        }
+                        if !hit {
+                            return true; // shot continues
+                        }
+
+                        // hit line, position a bit closer
+                        let (x, y, z) = point(trace, intercept, 4 * FRACUNIT);
+                        if line.frontsector.ceilingpic == sky_num {
+                            // don't shoot the sky!
+                            if z > float_to_fixed(line.frontsector.ceilingheight) {
+                                return false;
+                            }
+                            // it's a sky hack wall
+                            if line
+                                .backsector
+                                .as_ref()
+                                .is_some_and(|back| back.ceilingpic == sky_num)
+                            {
+                                return false;
+                            }
+                        }
+                        // Spawn bullet puffs.
+                        MapObject::fixed_spawn_puff(x, y, z, distance, level);
+                        false // don't go any farther
+                    }
+                    Intercepted::Thing(thing) => {
+                        let thing = unsafe { &mut **thing };
+                        if ptr::eq(self, thing) {
+                            return true; // can't shoot self
+                        }
+                        if thing.flags & MapObjFlag::Shootable as u32 == 0 {
+                            return true; // corpse or something
+                        }
+
+                        // check angles to see if the thing can be aimed at
+                        let dist = fixed_mul(distance, intercept.frac);
+                        let (_, _, tz) = thing.fixed_pos();
+                        if fixed_div(tz + float_to_fixed(thing.height) - shootz, dist) < slope {
+                            return true; // shot over the thing
+                        }
+                        if fixed_div(tz - shootz, dist) > slope {
+                            return true; // shot under the thing
+                        }
+
+                        // hit thing, position a bit closer
+                        let (x, y, z) = point(trace, intercept, 10 * FRACUNIT);
+                        // Spawn bullet puffs or blood spots,
+                        // depending on target type.
+                        if thing.flags & MapObjFlag::Noblood as u32 != 0 {
+                            MapObject::fixed_spawn_puff(x, y, z, distance, level);
+                        } else {
+                            MapObject::fixed_spawn_blood(x, y, z, damage, level);
+                        }
+                        if damage != 0 {
+                            thing.p_take_damage(None, Some(self), true, damage);
+                        }
+                        false // don't go any farther
+                    }
+                }
+            },
+        );
+    }
+
+    /// Doom function name `P_SpawnPuff`
+    pub(super) fn fixed_spawn_puff(
+        x: Fixed,
+        y: Fixed,
+        z: Fixed,
+        distance: Fixed,
+        level: &mut Level,
+    ) {
+        let z = z + ((level.random.p_random() - level.random.p_random()) << 10);
+        let mobj =
+            unsafe { &mut *MapObject::spawn_map_object_fixed(x, y, z, MapObjKind::MT_PUFF, level) };
+        mobj.fixed.momz = FRACUNIT;
+        mobj.momxyz.z = 1.0;
+        mobj.tics -= level.random.p_random() & 3;
+        if mobj.tics < 1 {
+            mobj.tics = 1;
+        }
+        // don't make punches spark on the wall
+        if distance == MELEERANGE {
+            mobj.set_state(StateNum::PUFF3);
+        }
+    }
+
+    /// Doom function name `P_SpawnBlood`
+    fn fixed_spawn_blood(x: Fixed, y: Fixed, z: Fixed, damage: i32, level: &mut Level) {
+        let z = z + ((level.random.p_random() - level.random.p_random()) << 10);
+        let mobj = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(x, y, z, MapObjKind::MT_BLOOD, level)
+        };
+        mobj.fixed.momz = FRACUNIT * 2;
+        mobj.momxyz.z = 2.0;
+        mobj.tics -= level.random.p_random() & 3;
+        if mobj.tics < 1 {
+            mobj.tics = 1;
+        }
+        if (9..=12).contains(&damage) {
+            mobj.set_state(StateNum::BLOOD2);
+        } else if damage < 9 {
+            mobj.set_state(StateNum::BLOOD3);
+        }
+    }
+
+    /// Damage everything in sight within `damage` of this explosion. The
+    /// thing that caused it is its `target`.
+    ///
+    /// Doom function name `P_RadiusAttack`
+    pub(super) fn fixed_radius_attack(&mut self, damage: i32) {
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        // `MAXRADIUS` is added as fixed-point before the shift, and overflows
+        // away
+        let dist = damage << FRACBITS;
+        let level = unsafe { &mut *self.level };
+        let ([xl, xh], [yl, yh]) = block_range(level, x - dist, x + dist, y - dist, y + dist);
+        for by in yl..=yh {
+            for bx in xl..=xh {
+                block_things_iterator(level, bx, by, |thing| {
+                    self.fixed_radius_damage(thing, damage)
+                });
+            }
+        }
+    }
+
+    /// Doom function name `PIT_RadiusAttack`
+    fn fixed_radius_damage(&mut self, thing: &mut MapObject, damage: i32) -> bool {
+        if thing.flags & MapObjFlag::Shootable as u32 == 0 {
+            return true;
+        }
+        // Boss spider and cyborg take no damage from concussion.
+        if matches!(thing.kind, MapObjKind::MT_CYBORG | MapObjKind::MT_SPIDER) {
+            return true;
+        }
+
+        let (tx, ty, _) = thing.fixed_pos();
+        let dx = tx.wrapping_sub(self.fixed.x).wrapping_abs();
+        let dy = ty.wrapping_sub(self.fixed.y).wrapping_abs();
+        let dist = ((dx.max(dy) - float_to_fixed(thing.radius)) >> FRACBITS).max(0);
+        if dist >= damage {
+            return true; // out of range
+        }
+
+        if thing.check_sight_target(self) {
+            // must be in direct path
+            let source = self.target.map(|t| unsafe { (*t).mobj_mut() });
+            thing.p_take_damage(Some(self), source, false, damage - dist);
+        }
+        true
+    }
+}
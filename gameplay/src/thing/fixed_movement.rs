@@ -0,0 +1,638 @@
+//! Movement for the optional vanilla fixed-point simulation mode
+//! (`GameOptions::fixed_point`).
+//!
+//! In this mode a `MapObject` moves by the 16.16 fixed-point position,
+//! momentum and BAM angle in its `FixedState`, rounding as the original
+//! engine did, and the `f32` fields are kept as mirrors of it for the rest of
+//! the engine. Anything that still writes the `f32` fields directly is picked
+//! up by `sync_fixed()` before the next fixed-point move.
+//!
+//! The collision checks, hitscans and line uses these moves make are in
+//! `fixed_map`, sight checks in `fixed_sight` and the monster decisions in
+//! `fixed_enemy`.
+
+use glam::Vec3;
+use sound_traits::SfxName;
+
+use crate::angle::Angle;
+use crate::doom_def::{MTF_AMBUSH, ONCEILINGZ, ONFLOORZ};
+use crate::env::pushers::ORIG_FRICTION_FACTOR;
+use crate::env::switch::p_use_special_line;
+use crate::fixed::{
+    angle_to_bam, aprox_distance, bam_to_angle, finecosine, finesine, fixed_mul, float_to_fixed, point_to_angle2, Bam, Fixed, ANG180, ANG45, ANG90, ANGLETOFINESHIFT, FRACBITS, FRACUNIT
+};
+use crate::info::{MapObjKind, StateNum, STATES};
+use crate::level::Level;
+use crate::player::PlayerCheat;
+use crate::tic_cmd::TicCmd;
+use crate::utilities::fixed_to_float;
+use crate::MapObject;
+
+use super::enemy_movement::MoveDir;
+use super::fixed_map::FixedCheck;
+use super::movement::FRICTION;
+use super::MapObjFlag;
+
+const MAXMOVE: Fixed = 30 * FRACUNIT;
+const STOPSPEED: Fixed = 0x1000;
+const GRAVITY: Fixed = FRACUNIT;
+const FLOATSPEED: Fixed = 4 * FRACUNIT;
+const VIEWHEIGHT: Fixed = 41 * FRACUNIT;
+/// The range autoaim looks for a target in
+const AIMRANGE: f32 = 16.0 * 64.0;
+
+pub(super) const DIR_XSPEED: [Fixed; 8] = [FRACUNIT, 47000, 0, -47000, -FRACUNIT, -47000, 0, 47000];
+pub(super) const DIR_YSPEED: [Fixed; 8] = [0, 47000, FRACUNIT, 47000, 0, -47000, -FRACUNIT, -47000];
+
+/// The vanilla fixed-point position, momentum and angle of a `MapObject`
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct FixedState {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub z: Fixed,
+    pub momx: Fixed,
+    pub momy: Fixed,
+    pub momz: Fixed,
+    pub angle: Bam,
+}
+
+/// Take `value` unless its mirror no longer matches `mirror`
+fn synced(value: Fixed, mirror: f32) -> Fixed {
+    if fixed_to_float(value) == mirror {
+        value
+    } else {
+        float_to_fixed(mirror)
+    }
+}
+
+impl MapObject {
+    /// The fixed-point position, converted from the `f32` position for any
+    /// part that was changed outside of fixed-point code
+    pub fn fixed_pos(&self) -> (Fixed, Fixed, Fixed) {
+        (
+            synced(self.fixed.x, self.xyz.x),
+            synced(self.fixed.y, self.xyz.y),
+            synced(self.fixed.z, self.xyz.z),
+        )
+    }
+
+    /// The BAM angle, converted from the `Angle` if that was changed outside of
+    /// fixed-point code
+    pub(crate) fn fixed_angle(&self) -> Bam {
+        if bam_to_angle(self.fixed.angle) == self.angle {
+            self.fixed.angle
+        } else {
+            angle_to_bam(self.angle)
+        }
+    }
+
+    /// Reload any part of the fixed-point state whose `f32` mirror was changed
+    /// outside of fixed-point code
+    pub(crate) fn sync_fixed(&mut self) {
+        (self.fixed.x, self.fixed.y, self.fixed.z) = self.fixed_pos();
+        self.fixed.momx = synced(self.fixed.momx, self.momxyz.x);
+        self.fixed.momy = synced(self.fixed.momy, self.momxyz.y);
+        self.fixed.momz = synced(self.fixed.momz, self.momxyz.z);
+        self.fixed.angle = self.fixed_angle();
+    }
+
+    /// Write the fixed-point state to the `f32` mirrors
+    pub(crate) fn store_fixed(&mut self) {
+        let f = self.fixed;
+        self.xyz = Vec3::new(
+            fixed_to_float(f.x),
+            fixed_to_float(f.y),
+            fixed_to_float(f.z),
+        );
+        self.momxyz = Vec3::new(
+            fixed_to_float(f.momx),
+            fixed_to_float(f.momy),
+            fixed_to_float(f.momz),
+        );
+        self.angle = bam_to_angle(f.angle);
+    }
+
+    /// The BAM of `angle`, turned from the exact fixed-point angle so the low
+    /// bits are kept through a small turn made to the `Angle`
+    pub(crate) fn bam_of(&self, angle: Angle) -> Bam {
+        self.fixed_angle()
+            .wrapping_add(angle_to_bam(angle - self.angle))
+    }
+
+    /// Doom function name `P_XYMovement`
+    pub(super) fn fixed_xy_movement(&mut self) {
+        if self.fixed.momx == 0 && self.fixed.momy == 0 {
+            if self.flags & MapObjFlag::Skullfly as u32 != 0 {
+                self.flags &= !(MapObjFlag::Skullfly as u32);
+                self.fixed.momz = 0;
+                self.store_fixed();
+                self.set_state(self.info.spawnstate);
+            }
+            return;
+        }
+
+        self.fixed.momx = self.fixed.momx.clamp(-MAXMOVE, MAXMOVE);
+        self.fixed.momy = self.fixed.momy.clamp(-MAXMOVE, MAXMOVE);
+        self.store_fixed();
+        let mut xmove = self.fixed.momx;
+        let mut ymove = self.fixed.momy;
+        loop {
+            let (ptryx, ptryy);
+            if xmove > MAXMOVE / 2 || ymove > MAXMOVE / 2 {
+                ptryx = self.fixed.x + xmove / 2;
+                ptryy = self.fixed.y + ymove / 2;
+                xmove >>= 1;
+                ymove >>= 1;
+            } else {
+                ptryx = self.fixed.x + xmove;
+                ptryy = self.fixed.y + ymove;
+                xmove = 0;
+                ymove = 0;
+            }
+
+            let mut check = FixedCheck::default();
+            if !self.fixed_try_move(ptryx, ptryy, &mut check) {
+                if self.player.is_some() {
+                    self.fixed_slide_move();
+                } else if self.flags & MapObjFlag::Missile as u32 != 0 {
+                    // explode a missile, unless it hit the sky
+                    let sky_num = self.level().sky_num;
+                    if check
+                        .ceilingline
+                        .as_ref()
+                        .and_then(|line| line.backsector.as_ref())
+                        .is_some_and(|back| back.ceilingpic == sky_num)
+                    {
+                        self.remove();
+                        return;
+                    }
+                    self.p_explode_missile();
+                    self.sync_fixed();
+                } else {
+                    self.fixed.momx = 0;
+                    self.fixed.momy = 0;
+                    self.store_fixed();
+                }
+            }
+
+            if xmove == 0 && ymove == 0 {
+                break;
+            }
+        }
+
+        self.store_fixed();
+        if let Some(player) = self.player_mut() {
+            if player.status.cheats & PlayerCheat::NoMomentum as u32 != 0 {
+                self.momxyz.x = 0.0;
+                self.momxyz.y = 0.0;
+                self.sync_fixed();
+                return;
+            }
+        }
+
+        if self.flags & (MapObjFlag::Missile as u32 | MapObjFlag::Skullfly as u32) != 0 {
+            return; // no friction for missiles ever
+        }
+        if self.fixed.z > float_to_fixed(self.floorz) {
+            return; // no friction when airborne
+        }
+
+        let f = self.fixed;
+        if self.flags & MapObjFlag::Corpse as u32 != 0 {
+            // do not stop sliding
+            //  if halfway off a step with some momentum
+            if (f.momx > FRACUNIT / 4
+                || f.momx < -FRACUNIT / 4
+                || f.momy > FRACUNIT / 4
+                || f.momy < -FRACUNIT / 4)
+                && self.floorz != self.subsector.sector.floorheight
+            {
+                return;
+            }
+        }
+
+        let no_cmd = self
+            .player_mut()
+            .is_none_or(|p| p.cmd.forwardmove == 0 && p.cmd.sidemove == 0);
+        let f = &mut self.fixed;
+        if f.momx > -STOPSPEED
+            && f.momx < STOPSPEED
+            && f.momy > -STOPSPEED
+            && f.momy < STOPSPEED
+            && no_cmd
+        {
+            f.momx = 0;
+            f.momy = 0;
+            self.store_fixed();
+            let running = &STATES[StateNum::PLAY_RUN1 as usize..=StateNum::PLAY_RUN4 as usize];
+            if self.player.is_some() && running.iter().any(|s| std::ptr::eq(s, self.state)) {
+                self.set_state(StateNum::PLAY);
+            }
+        } else {
+            let friction = float_to_fixed(self.friction);
+            f.momx = fixed_mul(f.momx, friction);
+            f.momy = fixed_mul(f.momy, friction);
+            self.store_fixed();
+        }
+        // Friction sectors set these again each tic
+        self.friction = FRICTION;
+        self.movefactor = ORIG_FRICTION_FACTOR;
+    }
+
+    /// Doom function name `P_ZMovement`
+    pub(super) fn fixed_z_movement(&mut self) {
+        let floorz = float_to_fixed(self.floorz);
+        let ceilingz = float_to_fixed(self.ceilingz);
+        let height = float_to_fixed(self.height);
+
+        if let Some(player) = self.player {
+            if self.fixed.z < floorz {
+                let player = unsafe { &mut *player };
+                let viewheight = float_to_fixed(player.viewheight) - (floorz - self.fixed.z);
+                player.viewheight = fixed_to_float(viewheight);
+                player.deltaviewheight = fixed_to_float((VIEWHEIGHT - viewheight) >> 3);
+            }
+        }
+
+        self.fixed.z += self.fixed.momz;
+
+        if self.flags & MapObjFlag::Float as u32 != 0
+            && self.flags & (MapObjFlag::Skullfly as u32 | MapObjFlag::Infloat as u32) == 0
+        {
+            if let Some(target) = self.target {
+                let target = unsafe { (*target).mobj() };
+                // float down towards target if too close
+                let (tx, ty, tz) = target.fixed_pos();
+                let dist = aprox_distance(self.fixed.x - tx, self.fixed.y - ty);
+                let delta = (tz + (height >> 1)) - self.fixed.z;
+                if delta < 0 && dist < -(delta * 3) {
+                    self.fixed.z -= FLOATSPEED;
+                } else if delta > 0 && dist < delta * 3 {
+                    self.fixed.z += FLOATSPEED;
+                }
+            }
+        }
+
+        // clip movement
+        if self.fixed.z <= floorz {
+            // hit the floor
+            if self.flags & MapObjFlag::Skullfly as u32 != 0 {
+                // the skull slammed into something
+                self.fixed.momz = -self.fixed.momz;
+            }
+
+            if self.fixed.momz < 0 {
+                if let Some(player) = self.player {
+                    if self.fixed.momz < -GRAVITY * 8 {
+                        // Squat down.
+                        // Decrease viewheight for a moment
+                        // after hitting the ground (hard),
+                        // and utter appropriate sound.
+                        let player = unsafe { &mut *player };
+                        player.deltaviewheight = fixed_to_float(self.fixed.momz >> 3);
+                        player.start_sound(SfxName::Oof);
+                    }
+                }
+                self.fixed.momz = 0;
+            }
+            self.fixed.z = floorz;
+
+            if self.flags & MapObjFlag::Missile as u32 != 0
+                && self.flags & MapObjFlag::Noclip as u32 == 0
+            {
+                self.store_fixed();
+                self.p_explode_missile();
+                self.sync_fixed();
+                return;
+            }
+        } else if self.flags & MapObjFlag::Nogravity as u32 == 0 {
+            if self.fixed.momz == 0 {
+                self.fixed.momz = -GRAVITY * 2;
+            } else {
+                self.fixed.momz -= GRAVITY;
+            }
+        }
+
+        if self.fixed.z + height > ceilingz {
+            // hit the ceiling
+            if self.fixed.momz > 0 {
+                self.fixed.momz = 0;
+            }
+            self.fixed.z = ceilingz - height;
+
+            if self.flags & MapObjFlag::Skullfly as u32 != 0 {
+                // the skull slammed into something
+                self.fixed.momz = -self.fixed.momz;
+            }
+
+            if self.flags & MapObjFlag::Missile as u32 != 0
+                && self.flags & MapObjFlag::Noclip as u32 == 0
+            {
+                self.store_fixed();
+                self.p_explode_missile();
+                self.sync_fixed();
+                return;
+            }
+        }
+        self.store_fixed();
+    }
+
+    /// Doom function name `P_Thrust`
+    fn fixed_thrust(&mut self, angle: Bam, mv: Fixed) {
+        let fine = (angle >> ANGLETOFINESHIFT) as usize;
+        self.fixed.momx += fixed_mul(mv, finecosine(fine));
+        self.fixed.momy += fixed_mul(mv, finesine(fine));
+    }
+
+    /// Turn and thrust a player from `cmd`. Returns true if on the ground.
+    ///
+    /// Doom function name `P_MovePlayer`
+    pub(crate) fn fixed_move_player(&mut self, cmd: &TicCmd) -> bool {
+        self.sync_fixed();
+        self.fixed.angle = self.fixed.angle.wrapping_add((cmd.angleturn as u32) << 16);
+
+        let onground = self.fixed.z <= float_to_fixed(self.floorz);
+        if cmd.forwardmove != 0 && onground {
+            self.fixed_thrust(self.fixed.angle, cmd.forwardmove as i32 * self.movefactor);
+        }
+        if cmd.sidemove != 0 && onground {
+            self.fixed_thrust(
+                self.fixed.angle.wrapping_sub(ANG90),
+                cmd.sidemove as i32 * self.movefactor,
+            );
+        }
+        self.store_fixed();
+        onground
+    }
+
+    /// Step an enemy one move along `movedir`, opening any door in the way.
+    /// Returns false if blocked.
+    ///
+    /// Doom function name `P_Move`
+    pub(super) fn fixed_enemy_move(&mut self) -> bool {
+        self.sync_fixed();
+        let speed = self.info.speed as Fixed;
+        let dir = self.movedir as usize;
+        let x = self.fixed.x + speed * DIR_XSPEED[dir];
+        let y = self.fixed.y + speed * DIR_YSPEED[dir];
+
+        let mut check = FixedCheck::default();
+        if !self.fixed_try_move(x, y, &mut check) {
+            // open any specials
+            if self.flags & MapObjFlag::Float as u32 != 0 && check.floatok {
+                // must adjust height
+                if self.fixed.z < check.floorz {
+                    self.fixed.z += FLOATSPEED;
+                } else {
+                    self.fixed.z -= FLOATSPEED;
+                }
+                self.store_fixed();
+                self.flags |= MapObjFlag::Infloat as u32;
+                return true;
+            }
+
+            let level = unsafe { &mut *self.level };
+            if level.spechit.is_empty() {
+                return false;
+            }
+            self.movedir = MoveDir::None;
+            let mut good = false;
+            while let Some(line) = level.spechit.pop() {
+                // if the special is not a door that can be opened, return false
+                if p_use_special_line(0, line, self, false) {
+                    good = true;
+                }
+            }
+            return good;
+        }
+
+        self.flags &= !(MapObjFlag::Infloat as u32);
+        if self.flags & MapObjFlag::Float as u32 == 0 {
+            self.fixed.z = float_to_fixed(self.floorz);
+            self.store_fixed();
+        }
+        true
+    }
+
+    /// Turn an enemy 45 degrees towards `movedir`
+    pub(super) fn fixed_chase_turn(&mut self) {
+        if self.movedir >= MoveDir::None {
+            return;
+        }
+        self.sync_fixed();
+        self.fixed.angle &= 7 << 29;
+        let delta = self.fixed.angle.wrapping_sub((self.movedir as u32) << 29) as i32;
+        if delta > 0 {
+            self.fixed.angle = self.fixed.angle.wrapping_sub(ANG45);
+        } else if delta < 0 {
+            self.fixed.angle = self.fixed.angle.wrapping_add(ANG45);
+        }
+        self.store_fixed();
+    }
+
+    /// Doom function name `A_FaceTarget`
+    pub(super) fn fixed_face_target(&mut self) {
+        self.sync_fixed();
+        if let Some(target) = self.target {
+            let target = unsafe { (*target).mobj() };
+            let (tx, ty, _) = target.fixed_pos();
+            self.fixed.angle = point_to_angle2(self.fixed.x, self.fixed.y, tx, ty);
+            if target.flags & MapObjFlag::Shadow as u32 != 0 {
+                let fuzz = ((self.p_random() - self.p_random()) << 21) as u32;
+                self.fixed.angle = self.fixed.angle.wrapping_add(fuzz);
+            }
+        }
+        self.store_fixed();
+    }
+
+    /// Turn to face `target`, as a punch that hit does
+    pub(crate) fn fixed_face_thing(&mut self, target: &MapObject) {
+        self.sync_fixed();
+        let (tx, ty, _) = target.fixed_pos();
+        self.fixed.angle = point_to_angle2(self.fixed.x, self.fixed.y, tx, ty);
+        self.store_fixed();
+    }
+
+    /// Pull a chainsaw towards `target`
+    ///
+    /// Doom function name `A_Saw`
+    pub(crate) fn fixed_saw_turn(&mut self, target: &MapObject) {
+        self.sync_fixed();
+        let (tx, ty, _) = target.fixed_pos();
+        let angle = point_to_angle2(self.fixed.x, self.fixed.y, tx, ty);
+        let delta = angle.wrapping_sub(self.fixed.angle);
+        // The original compared against an unsigned `-ANG90 / 20`, so only
+        // the pull to the right ever snaps to the target
+        self.fixed.angle = if delta > ANG180 {
+            if delta < ANG90.wrapping_neg() / 20 {
+                angle.wrapping_add(ANG90 / 21)
+            } else {
+                self.fixed.angle.wrapping_sub(ANG90 / 20)
+            }
+        } else if delta > ANG90 / 20 {
+            angle.wrapping_sub(ANG90 / 21)
+        } else {
+            self.fixed.angle.wrapping_add(ANG90 / 20)
+        };
+        self.store_fixed();
+        self.flags |= MapObjFlag::Justattacked as u32;
+    }
+
+    /// Knock back from `damage` dealt by `inflictor`
+    ///
+    /// Doom function name `P_DamageMobj`
+    pub(crate) fn fixed_damage_thrust(&mut self, inflictor: &MapObject, damage: i32) {
+        self.sync_fixed();
+        let (ix, iy, iz) = inflictor.fixed_pos();
+        let mut angle = point_to_angle2(ix, iy, self.fixed.x, self.fixed.y);
+        let mut thrust = damage.wrapping_mul(FRACUNIT >> 3).wrapping_mul(100) / self.info.mass;
+        // make fall forwards sometimes
+        if damage < 40
+            && damage > self.health
+            && self.fixed.z - iz > 64 * FRACUNIT
+            && self.p_random() & 1 != 0
+        {
+            angle = angle.wrapping_add(ANG180);
+            thrust = thrust.wrapping_mul(4);
+        }
+        self.fixed_thrust(angle, thrust);
+        self.store_fixed();
+    }
+
+    /// Set the angle and momentum of a missile just spawned by `source` and
+    /// aimed at `target`
+    ///
+    /// Doom function name `P_SpawnMissile`
+    pub(super) fn fixed_aim_missile(&mut self, source: &MapObject, target: &MapObject) {
+        let (sx, sy, sz) = source.fixed_pos();
+        let (tx, ty, tz) = target.fixed_pos();
+        let mut angle = point_to_angle2(sx, sy, tx, ty);
+        if target.flags & MapObjFlag::Shadow as u32 != 0 {
+            angle = angle.wrapping_add(((self.p_random() - self.p_random()) << 20) as u32);
+        }
+
+        let speed = float_to_fixed(self.info.speed);
+        let dist = (aprox_distance(tx - sx, ty - sy) / speed).max(1);
+        self.fixed.angle = angle;
+        self.fixed.momx = 0;
+        self.fixed.momy = 0;
+        self.fixed.momz = (tz - sz) / dist;
+        self.fixed_thrust(angle, speed);
+        self.store_fixed();
+    }
+
+    /// Fire a missile from player `source`, turning up to 5.625 degrees to
+    /// either side to autoaim at a target
+    ///
+    /// Doom function name `P_SpawnPlayerMissile`
+    pub(super) fn fixed_spawn_player_missile(
+        source: &mut MapObject,
+        kind: MapObjKind,
+        level: &mut Level,
+    ) {
+        source.sync_fixed();
+        let view_slope = source.view_slope();
+        let look_slope = source.look_slope();
+        let mut angle = source.fixed.angle;
+        let mut aim = source.fixed_aim_result(angle, AIMRANGE, view_slope);
+        if aim.is_none() && look_slope.is_none() {
+            angle = angle.wrapping_add(1 << 26);
+            aim = source.fixed_aim_result(angle, AIMRANGE, view_slope);
+            if aim.is_none() {
+                angle = angle.wrapping_sub(2 << 26);
+                aim = source.fixed_aim_result(angle, AIMRANGE, view_slope);
+            }
+            if aim.is_none() {
+                angle = source.fixed.angle;
+            }
+        }
+        let slope = look_slope
+            .or(aim.map(|aim| aim.aimslope))
+            .map_or(0, float_to_fixed);
+
+        let (x, y, z) = (source.fixed.x, source.fixed.y, source.fixed.z);
+        let mobj = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(x, y, z + 32 * FRACUNIT, kind, level)
+        };
+        if !matches!(mobj.info.seesound, SfxName::None | SfxName::NumSfx) {
+            mobj.start_sound(mobj.info.seesound);
+        }
+
+        mobj.target = Some(source.thinker);
+        let speed = float_to_fixed(mobj.info.speed);
+        mobj.fixed.angle = angle;
+        mobj.fixed_thrust(angle, speed);
+        mobj.fixed.momz = fixed_mul(speed, slope);
+        mobj.store_fixed();
+        mobj.check_missile_spawn();
+    }
+
+    /// Doom function name `P_CheckMissileSpawn`
+    pub(super) fn fixed_check_missile_spawn(&mut self) {
+        self.tics -= self.p_random() & 3;
+        if self.tics < 1 {
+            self.tics = 1;
+        }
+
+        // move a little forward so an angle can be computed if it immediately
+        // explodes
+        self.fixed.x += self.fixed.momx >> 1;
+        self.fixed.y += self.fixed.momy >> 1;
+        self.fixed.z += self.fixed.momz >> 1;
+        self.store_fixed();
+        let (x, y) = (self.fixed.x, self.fixed.y);
+        if !self.fixed_try_move(x, y, &mut FixedCheck::default()) {
+            self.p_explode_missile();
+            self.sync_fixed();
+        }
+    }
+
+    /// Bring a dead monster back at its map spot, with teleport fog where the
+    /// body was and where it appears
+    ///
+    /// Doom function name `P_NightmareRespawn`
+    pub(super) fn fixed_nightmare_respawn(&mut self) {
+        let x = (self.spawnpoint.x as Fixed) << FRACBITS;
+        let y = (self.spawnpoint.y as Fixed) << FRACBITS;
+        // something is occupying its position?
+        if !self.fixed_check_position(x, y, &mut FixedCheck::default()) {
+            return; // no respawn
+        }
+
+        let level = unsafe { &mut *self.level };
+        // spawn a teleport fog at old spot because of removal of the body?
+        let (ox, oy, _) = self.fixed_pos();
+        let floor = float_to_fixed(self.subsector.sector.floorheight);
+        let fog = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(ox, oy, floor, MapObjKind::MT_TFOG, level)
+        };
+        fog.start_sound(SfxName::Telept);
+
+        // spawn a teleport fog at the new spot
+        let ss = level.map_data.point_in_subsector_fixed(x, y);
+        let floor = float_to_fixed(ss.sector.floorheight);
+        let fog = unsafe {
+            &mut *MapObject::spawn_map_object_fixed(x, y, floor, MapObjKind::MT_TFOG, level)
+        };
+        fog.start_sound(SfxName::Telept);
+
+        // spawn the new monster
+        let mthing = self.spawnpoint;
+        let z = if self.info.flags & MapObjFlag::Spawnceiling as u32 != 0 {
+            ONCEILINGZ
+        } else {
+            ONFLOORZ
+        };
+        let mobj = unsafe { &mut *MapObject::spawn_map_object_fixed(x, y, z, self.kind, level) };
+        mobj.spawnpoint = mthing;
+        mobj.fixed.angle = ANG45.wrapping_mul((mthing.angle as i32 / 45) as u32);
+        mobj.store_fixed();
+        if mthing.flags & MTF_AMBUSH != 0 {
+            mobj.flags |= MapObjFlag::Ambush as u32;
+        }
+        mobj.reactiontime = 18;
+
+        // remove the old monster
+        self.remove();
+    }
+}
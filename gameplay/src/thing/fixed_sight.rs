@@ -0,0 +1,268 @@
+//! Line of sight for the fixed-point mode (`GameOptions::fixed_point`), done
+//! as the original engine did it: the `REJECT` table is checked first, then the
+//! sight line is walked down the BSP, narrowing the vertical slopes at each
+//! two-sided line it crosses.
+//!
+//! Doom source name `p_sight`
+
+use crate::fixed::{fixed_div, fixed_mul, float_to_fixed, Fixed, FRACBITS};
+use crate::level::flags::LineDefFlags;
+use crate::level::map_data::IS_SSECTOR_MASK;
+use crate::level::Level;
+use crate::MapObject;
+
+/// A line as a start point and a delta
+///
+/// Doom type name `divline_t`
+#[derive(Debug, Clone, Copy)]
+struct DivLine {
+    x: Fixed,
+    y: Fixed,
+    dx: Fixed,
+    dy: Fixed,
+}
+
+impl DivLine {
+    /// The side of the line the point is on, `0` is the front, `2` on the
+    /// line. A horizontal line compares `x` to its `y`, as the original did.
+    ///
+    /// Doom function name `P_DivlineSide`
+    fn side(&self, x: Fixed, y: Fixed) -> usize {
+        if self.dx == 0 {
+            if x == self.x {
+                return 2;
+            }
+            return if x <= self.x {
+                (self.dy > 0) as usize
+            } else {
+                (self.dy < 0) as usize
+            };
+        }
+        if self.dy == 0 {
+            if x == self.y {
+                return 2;
+            }
+            return if y <= self.y {
+                (self.dx < 0) as usize
+            } else {
+                (self.dx > 0) as usize
+            };
+        }
+
+        let dx = x.wrapping_sub(self.x);
+        let dy = y.wrapping_sub(self.y);
+        let left = (self.dy >> FRACBITS).wrapping_mul(dx >> FRACBITS);
+        let right = (dy >> FRACBITS).wrapping_mul(self.dx >> FRACBITS);
+        if right < left {
+            return 0; // front side
+        }
+        if left == right {
+            return 2;
+        }
+        1 // back side
+    }
+
+    /// The fraction along `self` where `line` crosses it, `0` if parallel
+    ///
+    /// Doom function name `P_InterceptVector2`
+    fn intercept(&self, line: &DivLine) -> Fixed {
+        let den = fixed_mul(line.dy >> 8, self.dx).wrapping_sub(fixed_mul(line.dx >> 8, self.dy));
+        if den == 0 {
+            return 0;
+        }
+        let num = fixed_mul(line.x.wrapping_sub(self.x) >> 8, line.dy)
+            .wrapping_add(fixed_mul(self.y.wrapping_sub(line.y) >> 8, line.dx));
+        fixed_div(num, den)
+    }
+}
+
+/// The sight line being checked
+struct SightTrace {
+    strace: DivLine,
+    t2x: Fixed,
+    t2y: Fixed,
+    /// Eye height of the looker
+    zstart: Fixed,
+    /// Slopes to the top and bottom of the target, narrowed by each opening
+    topslope: Fixed,
+    bottomslope: Fixed,
+    valid_count: usize,
+}
+
+impl SightTrace {
+    /// True if the sight line gets through every line of the subsector
+    ///
+    /// Doom function name `P_CrossSubsector`
+    fn cross_subsector(&mut self, level: &mut Level, num: usize) -> bool {
+        let map = &mut level.map_data;
+        let Some(subsector) = map.subsectors().get(num) else {
+            return true;
+        };
+        let start = subsector.start_seg as usize;
+        let end = start + subsector.seg_count as usize;
+        for seg in map.segments_mut()[start..end].iter_mut() {
+            let line = &mut seg.linedef;
+            if line.valid_count == self.valid_count {
+                continue; // already checked other side
+            }
+            line.valid_count = self.valid_count;
+
+            let (v1x, v1y) = (float_to_fixed(line.v1.x), float_to_fixed(line.v1.y));
+            let (v2x, v2y) = (float_to_fixed(line.v2.x), float_to_fixed(line.v2.y));
+            let s1 = self.strace.side(v1x, v1y);
+            let s2 = self.strace.side(v2x, v2y);
+            // line isn't crossed?
+            if s1 == s2 {
+                continue;
+            }
+
+            let divl = DivLine {
+                x: v1x,
+                y: v1y,
+                dx: v2x.wrapping_sub(v1x),
+                dy: v2y.wrapping_sub(v1y),
+            };
+            let s1 = divl.side(self.strace.x, self.strace.y);
+            let s2 = divl.side(self.t2x, self.t2y);
+            // line isn't crossed?
+            if s1 == s2 {
+                continue;
+            }
+
+            // stop because it is not two sided anyway
+            if line.flags & LineDefFlags::TwoSided as u32 == 0 {
+                return false;
+            }
+            let Some(back) = seg.backsector.as_ref() else {
+                return false;
+            };
+            let front = &seg.frontsector;
+            let (front_floor, front_ceil) = (
+                float_to_fixed(front.floorheight),
+                float_to_fixed(front.ceilingheight),
+            );
+            let (back_floor, back_ceil) = (
+                float_to_fixed(back.floorheight),
+                float_to_fixed(back.ceilingheight),
+            );
+
+            // no wall to block sight with?
+            if front_floor == back_floor && front_ceil == back_ceil {
+                continue;
+            }
+
+            // possible occluder because of ceiling height differences
+            let opentop = front_ceil.min(back_ceil);
+            // because of floor height differences
+            let openbottom = front_floor.max(back_floor);
+            // quick test for totally closed doors
+            if openbottom >= opentop {
+                return false; // stop
+            }
+
+            let frac = self.strace.intercept(&divl);
+            if front_floor != back_floor {
+                let slope = fixed_div(openbottom.wrapping_sub(self.zstart), frac);
+                if slope > self.bottomslope {
+                    self.bottomslope = slope;
+                }
+            }
+            if front_ceil != back_ceil {
+                let slope = fixed_div(opentop.wrapping_sub(self.zstart), frac);
+                if slope < self.topslope {
+                    self.topslope = slope;
+                }
+            }
+            if self.topslope <= self.bottomslope {
+                return false; // stop
+            }
+        }
+        // passed the subsector ok
+        true
+    }
+
+    /// True if the sight line gets through the node's children
+    ///
+    /// Doom function name `P_CrossBSPNode`
+    fn cross_bsp_node(&mut self, level: &mut Level, node_id: u32) -> bool {
+        if node_id & IS_SSECTOR_MASK != 0 {
+            return self.cross_subsector(level, (node_id & !IS_SSECTOR_MASK) as usize);
+        }
+
+        let node = &level.map_data.get_nodes()[node_id as usize];
+        let children = node.children;
+        let bsp = DivLine {
+            x: float_to_fixed(node.xyz.x),
+            y: float_to_fixed(node.xyz.y),
+            dx: float_to_fixed(node.delta.x),
+            dy: float_to_fixed(node.delta.y),
+        };
+        // decide which side the start point is on
+        let mut side = bsp.side(self.strace.x, self.strace.y);
+        if side == 2 {
+            side = 0; // an "on" should cross both sides
+        }
+
+        // cross the starting side
+        if !self.cross_bsp_node(level, children[side]) {
+            return false;
+        }
+        // the partition plane is crossed here
+        if side == bsp.side(self.t2x, self.t2y) {
+            return true; // the line doesn't touch the other side
+        }
+        // cross the ending side
+        self.cross_bsp_node(level, children[side ^ 1])
+    }
+}
+
+impl MapObject {
+    /// True if a straight line between the eyes of `self` and any part of
+    /// `target` is not blocked by a wall, floor or ceiling
+    ///
+    /// Doom function name `P_CheckSight`
+    pub(crate) fn fixed_check_sight(&self, target: &MapObject) -> bool {
+        let level = unsafe { &mut *self.level };
+
+        // First check for trivial rejection
+        let s1 = self.subsector.sector.num as usize;
+        let s2 = target.subsector.sector.num as usize;
+        let pnum = s1 * level.map_data.sectors().len() + s2;
+        let rejected = level
+            .map_data
+            .reject
+            .get(pnum >> 3)
+            .is_some_and(|byte| byte & (1 << (pnum & 7)) != 0);
+        if rejected {
+            return false; // can't possibly be connected
+        }
+
+        // An unobstructed LOS is possible, now do the expensive checking
+        level.valid_count += 1;
+        let (x1, y1, z1) = self.fixed_pos();
+        let (x2, y2, z2) = target.fixed_pos();
+        let height = float_to_fixed(self.height);
+        let zstart = z1 + height - (height >> 2);
+        let mut trace = SightTrace {
+            strace: DivLine {
+                x: x1,
+                y: y1,
+                dx: x2.wrapping_sub(x1),
+                dy: y2.wrapping_sub(y1),
+            },
+            t2x: x2,
+            t2y: y2,
+            zstart,
+            topslope: z2 + float_to_fixed(target.height) - zstart,
+            bottomslope: z2 - zstart,
+            valid_count: level.valid_count,
+        };
+
+        // the head node is the last node output
+        if level.map_data.get_nodes().is_empty() {
+            return trace.cross_subsector(level, 0);
+        }
+        let start = level.map_data.start_node();
+        trace.cross_bsp_node(level, start)
+    }
+}
@@ -6,12 +6,12 @@ use glam::Vec3;
 use log::{debug, error, info};
 use sound_traits::SfxName;
 
-use crate::doom_def::{AmmoType, Card, PowerType, WeaponType};
+use crate::doom_def::{AmmoType, Card, PowerType, WeaponType, ONFLOORZ};
 use crate::info::{MapObjKind, SpriteNum, StateNum, STATES};
 use crate::lang::english::*;
-use crate::player::{PlayerCheat, PlayerState};
+use crate::player::{Player, PlayerCheat, PlayerState};
 use crate::thing::MapObjFlag;
-use crate::utilities::{p_random, point_to_angle_2};
+use crate::utilities::point_to_angle_2;
 use crate::{MapObject, Skill};
 
 pub const BONUSADD: i32 = 6;
@@ -87,14 +87,19 @@ impl MapObject {
                 source.as_mut().unwrap()
             };
 
-            if self.flags & MapObjFlag::Noclip as u32 == 0 && do_push {
+            if self.flags & MapObjFlag::Noclip as u32 == 0
+                && do_push
+                && self.level().options.fixed_point
+            {
+                self.fixed_damage_thrust(inflict, damage);
+            } else if self.flags & MapObjFlag::Noclip as u32 == 0 && do_push {
                 let angle = point_to_angle_2(self.xyz, inflict.xyz);
                 let mut thrust = damage as f32 * 16.66 / self.info.mass as f32;
                 // make fall forwards sometimes
                 if damage < 40
                     && damage > self.health
                     && self.xyz.z - inflict.xyz.z > 64.0
-                    && p_random() & 1 != 0
+                    && self.p_random() & 1 != 0
                 {
                     thrust *= 4.0;
                 }
@@ -163,7 +168,7 @@ impl MapObject {
             return;
         }
 
-        if p_random() < self.info.painchance && self.flags & MapObjFlag::Skullfly as u32 == 0 {
+        if self.p_random() < self.info.painchance && self.flags & MapObjFlag::Skullfly as u32 == 0 {
             self.flags |= MapObjFlag::Justhit as u32; // FIGHT!!!
             self.set_state(self.info.painstate);
         }
@@ -200,36 +205,30 @@ impl MapObject {
         self.flags |= MapObjFlag::Corpse as u32 | MapObjFlag::Dropoff as u32;
         self.height /= 4.0;
 
-        if let Some(source) = source.as_mut() {
-            if let Some(player) = source.player_mut() {
-                if self.flags & MapObjFlag::Countkill as u32 != 0 {
-                    player.total_kills += 1;
-                }
-
-                if self.player.is_some() {
-                    // TODO: set correct player for frags
-                    player.frags[0] += 1;
-                }
-            }
-        } else {
-            // TODO: Need to increment killcount for first player
-            if let Some(player) = self.player_mut() {
-                // TODO: players[0].killcount++; ??
+        let victim = self.player().and_then(|p| self.level().player_num(p));
+        if let Some(player) = source.as_mut().and_then(|s| s.player_mut()) {
+            if self.flags & MapObjFlag::Countkill as u32 != 0 {
                 player.total_kills += 1;
             }
+            if let Some(victim) = victim {
+                player.frags[victim] += 1;
+                self.check_frag_limit(player);
+            }
+        } else if !self.level().options.netgame && self.flags & MapObjFlag::Countkill as u32 != 0 {
+            // count all monster deaths, even those caused by other monsters
+            self.level_mut().players_mut()[0].total_kills += 1;
         }
 
         if let Some(player) = self.player_mut() {
             info!("Killing player");
             // Environment kills count against you
-            if source.is_none() {
-                // TODO: set correct player for frags
-                player.frags[0] += 1;
+            if let (None, Some(victim)) = (source.as_ref(), victim) {
+                player.frags[victim] += 1;
             }
 
             player.player_state = PlayerState::Dead;
             player.drop_weapon();
-            // TODO: stop automap
+            // The automap closes itself when the console player dies
         }
 
         if self.player().is_some() {
@@ -242,7 +241,7 @@ impl MapObject {
             self.set_state(self.info.deathstate);
         }
 
-        self.tics -= p_random() & 3;
+        self.tics -= self.p_random() & 3;
         if self.tics < 1 {
             self.tics = 1;
         }
@@ -255,17 +254,37 @@ impl MapObject {
         };
 
         unsafe {
-            let mobj = MapObject::spawn_map_object(
-                self.xyz.x,
-                self.xyz.y,
-                self.floorz as i32,
-                item,
-                &mut *self.level,
-            );
+            let mobj = if self.level().options.fixed_point {
+                let (x, y, _) = self.fixed_pos();
+                MapObject::spawn_map_object_fixed(x, y, ONFLOORZ, item, &mut *self.level)
+            } else {
+                MapObject::spawn_map_object(
+                    self.xyz.x,
+                    self.xyz.y,
+                    self.floorz as i32,
+                    item,
+                    &mut *self.level,
+                )
+            };
             (*mobj).flags |= MapObjFlag::Dropped as u32;
         }
     }
 
+    /// End a deathmatch level once `player` reaches the `-fraglimit`
+    fn check_frag_limit(&mut self, player: &Player) {
+        let options = &self.level().options;
+        let (deathmatch, Some(limit)) = (options.deathmatch, options.frag_limit) else {
+            return;
+        };
+        let Some(num) = self.level().player_num(player) else {
+            return;
+        };
+        if deathmatch != 0 && player.frag_count(num) >= limit {
+            info!("Player {} reached the frag limit", num + 1);
+            self.level_mut().do_exit_level();
+        }
+    }
+
     /// Interact with special pickups
     ///
     /// Doom function name `P_TouchSpecialThing`
@@ -288,6 +307,7 @@ impl MapObject {
             }
 
             let skill = unsafe { (*self.level).options.skill };
+            let netgame = unsafe { (*self.level).options.netgame };
             match special.sprite {
                 SpriteNum::ARM1 => {
                     if !player.give_armour(1) {
@@ -336,46 +356,34 @@ impl MapObject {
 
                 // Keycards
                 SpriteNum::BKEY => {
-                    if !player.status.cards[Card::Bluecard as usize] {
-                        player.message = Some(GOTBLUECARD);
+                    if !player.give_key(Card::Bluecard, GOTBLUECARD, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Bluecard);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::YKEY => {
-                    if !player.status.cards[Card::Yellowcard as usize] {
-                        player.message = Some(GOTYELWCARD);
+                    if !player.give_key(Card::Yellowcard, GOTYELWCARD, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Yellowcard);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::RKEY => {
-                    if !player.status.cards[Card::Redcard as usize] {
-                        player.message = Some(GOTREDCARD);
+                    if !player.give_key(Card::Redcard, GOTREDCARD, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Redcard);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::BSKU => {
-                    if !player.status.cards[Card::Blueskull as usize] {
-                        player.message = Some(GOTBLUESKUL);
+                    if !player.give_key(Card::Blueskull, GOTBLUESKUL, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Blueskull);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::YSKU => {
-                    if !player.status.cards[Card::Yellowskull as usize] {
-                        player.message = Some(GOTYELWSKUL);
+                    if !player.give_key(Card::Yellowskull, GOTYELWSKUL, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Yellowskull);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::RSKU => {
-                    if !player.status.cards[Card::Redskull as usize] {
-                        player.message = Some(GOTREDSKULL);
+                    if !player.give_key(Card::Redskull, GOTREDSKULL, netgame) {
+                        return;
                     }
-                    player.give_key(Card::Redskull);
-                    // TODO: if (netgame) return;
                 }
                 SpriteNum::STIM => {
                     if !player.give_body(10) {
@@ -3,11 +3,16 @@
 //!
 //! `MapObject` is also used for moving doors, platforms, floors, and ceilings.
 
+mod fixed_enemy;
+mod fixed_map;
+mod fixed_movement;
+mod fixed_sight;
 mod interact;
 use enemy_movement::MoveDir;
 pub use interact::*;
 mod movement;
 pub use movement::*;
+pub(crate) use fixed_map::fixed_change_sector;
 use sound_traits::SfxName;
 pub mod enemy_behaviour;
 pub mod enemy_movement;
@@ -16,10 +21,17 @@ mod shooting;
 use std::fmt::Debug;
 use std::ptr::null_mut;
 
+pub(crate) use self::fixed_movement::FixedState;
 use self::movement::SubSectorMinMax;
 
-use crate::doom_def::{MELEERANGE, MISSILERANGE, MTF_SINGLE_PLAYER};
+use crate::doom_def::{
+    Card, MELEERANGE, MISSILERANGE, MTF_NOT_COOP, MTF_NOT_DEATHMATCH, MTF_RESERVED, MTF_SINGLE_PLAYER, MTF_VANILLA
+};
+use crate::env::pushers::{ORIG_FRICTION_FACTOR, PULL_DOOMEDNUM, PUSH_DOOMEDNUM};
 use crate::level::Level;
+use crate::fixed::{
+    bam_to_angle, finecosine, finesine, float_to_fixed, Fixed, ANG45, ANGLETOFINESHIFT, FRACBITS, FRACUNIT
+};
 use crate::thinker::{Think, Thinker, ThinkerData};
 use crate::{MapPtr, Skill};
 use glam::Vec3;
@@ -31,10 +43,11 @@ use crate::doom_def::{ActFn, MAXPLAYERS, MTF_AMBUSH, ONCEILINGZ, ONFLOORZ, TICRA
 use crate::info::{MapObjInfo, MapObjKind, SpriteNum, State, StateNum, MOBJINFO, STATES};
 use crate::level::map_defs::SubSector;
 use crate::player::{Player, PlayerState};
-use crate::utilities::{p_random, p_subrandom, point_to_angle_2, BestSlide};
+use crate::utilities::{point_to_angle_2, BestSlide};
 
 //static MOBJ_CYCLE_LIMIT: u32 = 1000000;
 #[derive(Debug, PartialEq)]
+#[repr(u32)]
 pub enum MapObjFlag {
     /// Call P_SpecialThing when touched.
     Special = 1,
@@ -104,6 +117,9 @@ pub enum MapObjFlag {
     Translation = 0xC000000,
     /// Hmm ???.
     Transshift = 26,
+    /// BOOM/MBF: draw the sprite using the translucency table. Set in `MOBJINFO`
+    /// for BOOM's default translucent fireballs, fogs and powerups.
+    Translucent = 0x80000000,
 }
 
 pub struct MapObject {
@@ -135,6 +151,12 @@ pub struct MapObject {
     pub(super) s_next: Option<*mut Thinker>,
     /// Link to the previous `Thinker` in this sector
     pub(super) s_prev: Option<*mut Thinker>,
+    /// Links to the next and previous `Thinker` in the same `BlockMap` block,
+    /// kept only in the fixed-point mode
+    pub(super) b_next: Option<*mut Thinker>,
+    pub(super) b_prev: Option<*mut Thinker>,
+    /// The block this is linked in, if any
+    pub(super) block: Option<usize>,
     /// The subsector this object is currently in. When a map object is spawned
     /// `set_thing_position()` is called which then sets this to a valid
     /// subsector, making this safe in 99% of cases.
@@ -147,6 +169,14 @@ pub struct MapObject {
     pub(crate) height: f32,
     /// Momentum, used to update position.
     pub(crate) momxyz: Vec3,
+    /// Friction applied to momentum, changed by BOOM friction sectors and
+    /// reset each tic in `p_xy_movement()`
+    pub(crate) friction: f32,
+    /// Inertia factor applied to player thrust
+    pub(crate) movefactor: i32,
+    /// Vanilla position, momentum and angle for `GameOptions::fixed_point`.
+    /// `xyz`, `momxyz` and `angle` mirror this when that is enabled.
+    pub(crate) fixed: FixedState,
     /// If == validcount, already checked.
     pub(crate) valid_count: usize,
     /// The type of object
@@ -180,9 +210,9 @@ pub struct MapObject {
     /// Additional info record for player avatars only. Only valid if type ==
     /// MT_PLAYER. RUST: If this is not `None` then the pointer is
     /// guaranteed to point to a player
-    player: Option<*mut Player>,
+    pub(crate) player: Option<*mut Player>,
     /// Player number last looked for, 1-4 (does not start at 0)
-    lastlook: i32,
+    pub(crate) lastlook: i32,
     /// For nightmare respawn.
     pub(crate) spawnpoint: WadThing,
     // Thing being chased/attacked for tracers.
@@ -244,6 +274,9 @@ impl MapObject {
             radius: info.radius,
             height: info.height,
             momxyz: Vec3::default(),
+            friction: FRICTION,
+            movefactor: ORIG_FRICTION_FACTOR,
+            fixed: FixedState::default(),
             valid_count: 0,
             flags: info.flags,
             health: info.spawnhealth,
@@ -253,12 +286,15 @@ impl MapObject {
             best_slide: BestSlide::default(),
             reactiontime,
             threshold: 0,
-            lastlook: p_random() % MAXPLAYERS as i32,
+            lastlook: unsafe { (*level).random.p_random() } % MAXPLAYERS as i32,
             spawnpoint: WadThing::default(),
             target: None,
             tracer: None,
             s_next: None,
             s_prev: None,
+            b_next: None,
+            b_prev: None,
+            block: None,
             subsector: unsafe { MapPtr::new_null() },
             state,
             info,
@@ -283,6 +319,15 @@ impl MapObject {
         unsafe { &mut *self.level }
     }
 
+    /// Get the next gameplay random number from the level
+    pub(crate) fn p_random(&mut self) -> i32 {
+        self.level_mut().random.p_random()
+    }
+
+    pub(crate) fn p_subrandom(&mut self) -> i32 {
+        self.level_mut().random.p_subrandom()
+    }
+
     pub fn player(&self) -> Option<&Player> {
         self.player.map(|p| unsafe {
             #[cfg(feature = "null_check")]
@@ -329,7 +374,7 @@ impl MapObject {
     ///  between levels.
     ///
     /// Called in game-exe.c
-    fn p_spawn_player(
+    pub fn p_spawn_player(
         mthing: &WadThing,
         level: &mut Level,
         players: &mut [Player],
@@ -345,9 +390,9 @@ impl MapObject {
             return;
         }
 
-        // TODO: Properly sort this out
-        let player = &mut players[0];
-        trace!("Spawing player 1");
+        let player_num = (mthing.kind - 1) as usize;
+        let player = &mut players[player_num];
+        trace!("Spawing player {}", player_num + 1);
 
         if player.player_state == PlayerState::Reborn {
             player.reborn();
@@ -384,16 +429,16 @@ impl MapObject {
         player.extralight = 0;
         player.fixedcolormap = 0;
         player.viewheight = VIEWHEIGHT;
+        player.pitch = 0.0;
+        player.crouch = 0.0;
 
         // // setup gun psprite
         // TODO: P_SetupPsprites(p);
 
-        // // give all cards in death match mode
-        // if deathmatch {
-        //     for i in 0..Card::NUMCARDS as usize {
-        //         p.cards[i] = true;
-        //     }
-        // }
+        // give all cards in death match mode
+        if level.options.deathmatch != 0 {
+            player.status.cards = [true; Card::NumCards as usize];
+        }
 
         // if mthing.kind - 1 == consoleplayer {
         //     // wake up the status bar
@@ -403,14 +448,171 @@ impl MapObject {
         // }
     }
 
+    /// Check a player start spot is clear for `player_num` to spawn at, and
+    /// spawn a teleport fog there if it is. On the first spawn of a level the
+    /// player has no `MapObject` yet, and only the spots of other players are
+    /// checked.
+    ///
+    /// Doom function name `G_CheckSpot`
+    pub fn check_spot(
+        player_num: usize,
+        mthing: &WadThing,
+        level: &mut Level,
+        players: &mut [Player],
+    ) -> bool {
+        let x = mthing.x as f32;
+        let y = mthing.y as f32;
+
+        let Some(mobj) = players[player_num].mobj_mut() else {
+            // first spawn of level, before corpses
+            return !players[..player_num]
+                .iter()
+                .filter_map(|p| p.mobj())
+                .any(|mobj| mobj.xyz.x == x && mobj.xyz.y == y);
+        };
+        let mut ctrl = SubSectorMinMax::default();
+        if !mobj.p_check_position(Vec3::new(x, y, 0.0), &mut ctrl) {
+            return false;
+        }
+
+        // spawn a teleport fog, the angle snapped to 45 degrees as the original
+        // did
+        let floor = level
+            .map_data
+            .point_in_subsector(Vec3::new(x, y, 0.0))
+            .sector
+            .floorheight;
+        let an = ANG45.wrapping_mul(mthing.angle as u32 / 45);
+        let fog = if level.options.fixed_point {
+            let fine = (an >> ANGLETOFINESHIFT) as usize;
+            MapObject::spawn_map_object_fixed(
+                ((mthing.x as Fixed) << FRACBITS) + 20 * finecosine(fine),
+                ((mthing.y as Fixed) << FRACBITS) + 20 * finesine(fine),
+                float_to_fixed(floor),
+                MapObjKind::MT_TFOG,
+                level,
+            )
+        } else {
+            let angle = bam_to_angle(an);
+            MapObject::spawn_map_object(
+                x + 20.0 * angle.cos(),
+                y + 20.0 * angle.sin(),
+                floor as i32,
+                MapObjKind::MT_TFOG,
+                level,
+            )
+        };
+        unsafe {
+            (*fog).start_sound(SfxName::Telept);
+        }
+        true
+    }
+
+    /// Spawn a player at a random deathmatch start that is clear. If none is
+    /// found after 20 tries the player start is used, and the player will
+    /// probably get stuck.
+    ///
+    /// Doom function name `G_DeathMatchSpawnPlayer`
+    pub fn deathmatch_spawn_player(
+        player_num: usize,
+        level: &mut Level,
+        players: &mut [Player],
+        active_players: &[bool; MAXPLAYERS],
+    ) {
+        let spot = MapObject::deathmatch_spot(player_num, level, |mthing, level| {
+            MapObject::check_spot(player_num, mthing, level, players)
+        });
+        if let Some(mthing) = spot {
+            MapObject::p_spawn_player(&mthing, level, players, active_players);
+        }
+    }
+
+    /// Pick the deathmatch start for `player_num`, using `clear` to check
+    /// each one tried
+    fn deathmatch_spot(
+        player_num: usize,
+        level: &mut Level,
+        mut clear: impl FnMut(&WadThing, &mut Level) -> bool,
+    ) -> Option<WadThing> {
+        let selections = level.deathmatch_p.len();
+        if selections < 4 {
+            error!("Only {selections} deathmatch spots, 4 required");
+        }
+        if selections > 0 {
+            for _ in 0..20 {
+                let mut mthing = level.deathmatch_p[level.random.p_random() as usize % selections];
+                if clear(&mthing, level) {
+                    mthing.kind = player_num as i16 + 1;
+                    return Some(mthing);
+                }
+            }
+        }
+        level.player_starts[player_num]
+    }
+
+    /// Respawn a co-op player at their own start, or the start of another
+    /// player if it is blocked. If every start is blocked the spot they died
+    /// at is tried before giving up and spawning them into whatever is at
+    /// their start.
+    ///
+    /// Doom function name `G_DoReborn` (netgame part)
+    pub fn coop_spawn_player(
+        player_num: usize,
+        level: &mut Level,
+        players: &mut [Player],
+        active_players: &[bool; MAXPLAYERS],
+    ) {
+        let kind = player_num as i16 + 1;
+        let corpse = players[player_num].mobj().map(|corpse| {
+            let angle = corpse.angle.rad().to_degrees() as i16;
+            WadThing::new(corpse.xyz.x as i16, corpse.xyz.y as i16, angle, kind, 0)
+        });
+        let spot = MapObject::coop_spot(player_num, corpse, level, |mthing, level| {
+            MapObject::check_spot(player_num, mthing, level, players)
+        });
+        if let Some(mthing) = spot {
+            MapObject::p_spawn_player(&mthing, level, players, active_players);
+        }
+    }
+
+    /// Pick the co-op start for `player_num`, using `clear` to check each
+    /// one tried
+    fn coop_spot(
+        player_num: usize,
+        corpse: Option<WadThing>,
+        level: &mut Level,
+        mut clear: impl FnMut(&WadThing, &mut Level) -> bool,
+    ) -> Option<WadThing> {
+        let starts: Vec<WadThing> = level.player_starts[player_num]
+            .into_iter()
+            .chain(level.player_starts.iter().flatten().copied())
+            .chain(corpse)
+            .collect();
+        let mut mthing = starts
+            .into_iter()
+            .find(|mthing| clear(mthing, level))
+            .or(level.player_starts[player_num])?;
+        mthing.kind = player_num as i16 + 1;
+        Some(mthing)
+    }
+
     /// P_SpawnMapThing
     pub fn p_spawn_map_thing(
-        mthing: WadThing,
+        mut mthing: WadThing,
         no_monsters: bool,
         level: &mut Level,
         players: &mut [Player],
         active_players: &[bool; MAXPLAYERS],
     ) {
+        // BOOM: a broken editor set the bits above the vanilla ones
+        if mthing.flags & MTF_RESERVED != 0 {
+            warn!(
+                "P_SpawnMapThing: Correcting bad flags ({}) at ({}, {})",
+                mthing.flags, mthing.x, mthing.y
+            );
+            mthing.flags &= MTF_VANILLA;
+        }
+
         // count deathmatch start positions
         if mthing.kind == 11 {
             if level.deathmatch_p.len() < level.deathmatch_starts.len() {
@@ -429,10 +631,26 @@ impl MapObject {
             return;
         }
 
-        // check for appropriate skill level
-        if level.options.deathmatch == 0 && mthing.flags & MTF_SINGLE_PLAYER != 0 {
+        // BOOM push/pull sources are only read by `spawn_pushers()`
+        if mthing.kind == PUSH_DOOMEDNUM || mthing.kind == PULL_DOOMEDNUM {
+            return;
+        }
+
+        // check for appropriate game mode
+        if !level.options.netgame && mthing.flags & MTF_SINGLE_PLAYER != 0 {
+            return;
+        }
+        if level.options.deathmatch != 0 && mthing.flags & MTF_NOT_DEATHMATCH != 0 {
+            return;
+        }
+        if level.options.netgame
+            && level.options.deathmatch == 0
+            && mthing.flags & MTF_NOT_COOP != 0
+        {
             return;
         }
+
+        // check for appropriate skill level
         let bit: i16;
         if level.options.skill == Skill::Baby {
             bit = 1;
@@ -489,7 +707,7 @@ impl MapObject {
         let mobj = MapObject::spawn_map_object(x, y, z, MapObjKind::from(i), level);
         let mobj = unsafe { &mut *mobj };
         if mobj.tics > 0 {
-            mobj.tics = 1 + (p_random() % mobj.tics);
+            mobj.tics = 1 + (level.random.p_random() % mobj.tics);
         }
         if mobj.flags & MapObjFlag::Countkill as u32 != 0 {
             level.total_level_kills += 1;
@@ -513,7 +731,7 @@ impl MapObject {
         let mobj = MapObject::spawn_map_object(x, y, z, MapObjKind::MT_PUFF, level);
         let mobj = unsafe { &mut *mobj };
         mobj.momxyz.z = 1.0;
-        mobj.tics -= p_random() & 3;
+        mobj.tics -= level.random.p_random() & 3;
 
         if mobj.tics < 1 {
             mobj.tics = 1;
@@ -526,11 +744,11 @@ impl MapObject {
 
     /// Blood! In a game-exe!
     pub(crate) fn spawn_blood(x: f32, y: f32, mut z: i32, damage: f32, level: &mut Level) {
-        z += (p_random() - p_random()) / 64;
+        z += (level.random.p_random() - level.random.p_random()) / 64;
         let mobj = MapObject::spawn_map_object(x, y, z, MapObjKind::MT_BLOOD, level);
         let mobj = unsafe { &mut *mobj };
         mobj.momxyz.z = 2.0;
-        mobj.tics -= p_random() & 3;
+        mobj.tics -= level.random.p_random() & 3;
 
         if mobj.tics < 1 {
             mobj.tics = 1;
@@ -551,6 +769,10 @@ impl MapObject {
         kind: MapObjKind,
         level: &mut Level,
     ) {
+        if level.options.fixed_point {
+            MapObject::fixed_spawn_player_missile(source, kind, level);
+            return;
+        }
         let x = source.xyz.x;
         let y = source.xyz.y;
         let z = source.xyz.z + 32.0;
@@ -559,15 +781,19 @@ impl MapObject {
         let mobj = unsafe { &mut *mobj };
         mobj.angle = source.angle;
 
+        // Aim along the view of the player firing, or with free aim fire
+        // straight along it
+        let view_slope = source.view_slope();
+        let look_slope = source.look_slope();
         let mut bsp_trace = mobj.get_shoot_bsp_trace(MISSILERANGE);
-        let mut slope = mobj.aim_line_attack(MISSILERANGE, &mut bsp_trace);
+        let mut slope = mobj.aim_line_attack_at(MISSILERANGE, view_slope, &mut bsp_trace);
 
-        if slope.is_none() {
+        if slope.is_none() && look_slope.is_none() {
             mobj.angle += 5.625f32.to_radians();
-            slope = mobj.aim_line_attack(MISSILERANGE, &mut bsp_trace);
+            slope = mobj.aim_line_attack_at(MISSILERANGE, view_slope, &mut bsp_trace);
             if slope.is_none() {
                 mobj.angle -= 11.25f32.to_radians();
-                slope = mobj.aim_line_attack(MISSILERANGE, &mut bsp_trace);
+                slope = mobj.aim_line_attack_at(MISSILERANGE, view_slope, &mut bsp_trace);
             }
             if slope.is_none() {
                 mobj.angle = source.angle;
@@ -578,9 +804,10 @@ impl MapObject {
             mobj.start_sound(mobj.info.seesound);
         }
 
+        let slope = look_slope.or(slope.map(|s| s.aimslope));
         mobj.target = Some(source.thinker);
         mobj.momxyz = mobj.angle.unit_vec3() * mobj.info.speed;
-        mobj.momxyz.z = slope.map(|s| s.aimslope * mobj.info.speed).unwrap_or(0.0);
+        mobj.momxyz.z = slope.map(|s| s * mobj.info.speed).unwrap_or(0.0);
         mobj.check_missile_spawn();
     }
 
@@ -597,20 +824,32 @@ impl MapObject {
         let y = source.xyz.y;
         let z = source.xyz.z + 32.0;
 
-        let mobj = MapObject::spawn_map_object(x, y, z as i32, kind, level);
+        let mobj = if level.options.fixed_point {
+            let (x, y, z) = source.fixed_pos();
+            MapObject::spawn_map_object_fixed(x, y, z + 32 * FRACUNIT, kind, level)
+        } else {
+            MapObject::spawn_map_object(x, y, z as i32, kind, level)
+        };
         let mobj = unsafe { &mut *mobj };
 
         if !matches!(mobj.info.seesound, SfxName::None | SfxName::NumSfx) {
             mobj.start_sound(mobj.info.seesound);
         }
 
+        mobj.target = Some(source.thinker);
+        if level.options.fixed_point {
+            mobj.fixed_aim_missile(source, target);
+            mobj.check_missile_spawn();
+            return mobj;
+        }
+
         mobj.angle = point_to_angle_2(target.xyz, source.xyz);
         // fuzzy player
         if target.flags & MapObjFlag::Shadow as u32 != 0 {
-            mobj.angle += (((p_random() - p_random()) >> 4) as f32).to_radians();
+            let fuzz = mobj.p_random() - mobj.p_random();
+            mobj.angle += ((fuzz >> 4) as f32).to_radians();
         }
 
-        mobj.target = Some(source.thinker);
         mobj.momxyz = mobj.angle.unit_vec3() * mobj.info.speed;
         //thing.momz = slope.map(|s| s.aimslope * thing.info.speed).unwrap_or(0.0);
         let mut dist = mobj.xyz.distance(target.xyz) / mobj.info.speed;
@@ -624,7 +863,11 @@ impl MapObject {
     }
 
     fn check_missile_spawn(&mut self) {
-        self.tics -= p_random() & 3;
+        if self.level().options.fixed_point {
+            self.fixed_check_missile_spawn();
+            return;
+        }
+        self.tics -= self.p_random() & 3;
 
         if self.tics < 1 {
             self.tics = 1;
@@ -651,6 +894,41 @@ impl MapObject {
         kind: MapObjKind,
         level: &mut Level,
     ) -> *mut MapObject {
+        if level.options.fixed_point {
+            let z = if z == ONFLOORZ || z == ONCEILINGZ {
+                z
+            } else {
+                z.wrapping_shl(FRACBITS as u32)
+            };
+            return MapObject::spawn_map_object_fixed(
+                float_to_fixed(x),
+                float_to_fixed(y),
+                z,
+                kind,
+                level,
+            );
+        }
+
+        let mobj = MapObject::new_for_spawn(x, y, z, kind, level);
+        let thing = MapObject::push_spawned(mobj, level);
+        if !thing.subsector.is_null() {
+            // Now that we have a subsector this is safe
+            thing.floorz = thing.subsector.sector.floorheight;
+            thing.ceilingz = thing.subsector.sector.ceilingheight;
+
+            if z == ONFLOORZ {
+                thing.xyz.z = thing.floorz;
+            } else if z == ONCEILINGZ {
+                thing.xyz.z = thing.ceilingz - thing.info.height;
+            }
+        } else {
+            warn!("Thing {:?} didn't get a subsector", kind);
+        }
+        thing
+    }
+
+    /// A `MapObject` of `kind` in its spawn state
+    fn new_for_spawn(x: f32, y: f32, z: i32, kind: MapObjKind, level: &mut Level) -> MapObject {
         let info = MOBJINFO[kind as usize];
         let reactiontime = if level.options.skill != Skill::Nightmare {
             info.reactiontime
@@ -662,30 +940,22 @@ impl MapObject {
         // because action routines can not be called yet
         let state = &STATES[info.spawnstate as usize];
 
-        let mobj = MapObject::new(x, y, z, reactiontime, kind, info, state, level);
+        MapObject::new(x, y, z, reactiontime, kind, info, state, level)
+    }
 
+    /// Add a new `MapObject` to the thinkers and link it in to the level
+    fn push_spawned<'a>(mobj: MapObject, level: &mut Level) -> &'a mut MapObject {
+        let kind = mobj.kind;
+        let (x, y) = (mobj.xyz.x, mobj.xyz.y);
         let thinker = MapObject::create_thinker(ThinkerData::MapObject(mobj), MapObject::think);
 
         // P_AddThinker(&thing->thinker);
         if let Some(ptr) = level.thinkers.push::<MapObject>(thinker) {
-            let thing = ptr.mobj_mut();
+            let thing = ptr.mobj_mut() as *mut MapObject;
             unsafe {
                 // Sets the subsector link and links in sector
-                thing.set_thing_position();
-                if !thing.subsector.is_null() {
-                    // Now that we have a subsector this is safe
-                    thing.floorz = thing.subsector.sector.floorheight;
-                    thing.ceilingz = thing.subsector.sector.ceilingheight;
-
-                    if z == ONFLOORZ {
-                        thing.xyz.z = thing.floorz;
-                    } else if z == ONCEILINGZ {
-                        thing.xyz.z = thing.ceilingz - info.height;
-                    }
-                } else {
-                    warn!("Thing {:?} didn't get a subsector", kind);
-                }
-                return thing;
+                (*thing).set_thing_position();
+                return &mut *thing;
             }
         }
 
@@ -745,6 +1015,20 @@ impl MapObject {
             let mut ss = self.subsector.clone();
             ss.sector.remove_from_thinglist(self.thinker_mut());
         }
+        // `b_next` is left as is, a block iterator reads it after the thing
+        // removes itself
+        if let Some(block) = self.block.take() {
+            if let Some(next) = self.b_next {
+                (*next).mobj_mut().b_prev = self.b_prev;
+            }
+            match self.b_prev {
+                Some(prev) => (*prev).mobj_mut().b_next = self.b_next,
+                None => {
+                    let level = &mut *self.level;
+                    level.map_data.blockmap.links[block] = self.b_next;
+                }
+            }
+        }
     }
 
     /// P_SetThingPosition, unlink the thing from the sector
@@ -753,6 +1037,10 @@ impl MapObject {
     /// Thing must have had a SubSector set on creation.
     pub(crate) unsafe fn set_thing_position(&mut self) {
         let level = &mut *self.level;
+        if level.options.fixed_point {
+            self.set_thing_position_fixed();
+            return;
+        }
         let mut subsector = level.map_data.point_in_subsector_raw(self.xyz);
         if MOBJINFO[self.kind as usize].flags & MapObjFlag::Nosector as u32 == 0 {
             subsector.sector.add_to_thinglist(self.thinker)
@@ -762,11 +1050,11 @@ impl MapObject {
 
     /// P_RemoveMobj
     pub(crate) fn remove(&mut self) {
-        // Respawn specials for nightmare/deathmatch
+        // Respawn specials for altdeath
         if (self.flags & MapObjFlag::Special as u32 != 0
             && self.flags & MapObjFlag::Dropped as u32 == 0)
             && (self.kind != MapObjKind::MT_INV && self.kind != MapObjKind::MT_INS)
-            && (self.level().options.respawn_monsters || self.level().options.deathmatch != 0)
+            && self.level().options.deathmatch == 2
         {
             let time = self.level().level_time;
             let respawn = self.spawnpoint;
@@ -847,8 +1135,8 @@ impl MapObject {
                 unsafe { &mut *self.level },
             );
             unsafe {
-                (*mobj).momxyz.x = p_subrandom() as f32 * 0.6; // P_SubRandom() << 12;
-                (*mobj).momxyz.y = p_subrandom() as f32 * 0.6;
+                (*mobj).momxyz.x = (*mobj).p_subrandom() as f32 * 0.6; // P_SubRandom() << 12;
+                (*mobj).momxyz.y = (*mobj).p_subrandom() as f32 * 0.6;
             }
         }
 
@@ -868,6 +1156,10 @@ impl MapObject {
 
     /// P_NightmareRespawn
     pub fn nightmare_respawn(&mut self) {
+        if self.level().options.fixed_point {
+            self.fixed_nightmare_respawn();
+            return;
+        }
         let xyz = Vec3::new(self.spawnpoint.x as f32, self.spawnpoint.y as f32, 0.0);
         let mut ctrl = SubSectorMinMax::default();
         if !self.p_check_position(xyz, &mut ctrl) {
@@ -918,17 +1210,36 @@ impl Think for MapObject {
             std::panic!("MapObject thinker was null");
         }
 
-        // TODO: combine movement for XY and Z
+        if level.options.fixed_point {
+            this.sync_fixed();
+            if this.fixed.momx != 0
+                || this.fixed.momy != 0
+                || this.flags & MapObjFlag::Skullfly as u32 != 0
+            {
+                this.fixed_xy_movement();
+                if this.thinker_mut().should_remove() {
+                    return true; // thing was removed
+                }
+            }
+            if this.fixed.z != float_to_fixed(this.floorz) || this.fixed.momz != 0 {
+                this.fixed_z_movement();
+                if this.thinker_mut().should_remove() {
+                    return true; // thing was removed
+                }
+            }
+        } else {
+            // TODO: combine movement for XY and Z
 
-        if this.momxyz.x != 0.0 || this.momxyz.y != 0.0 || MapObjFlag::Skullfly as u32 != 0 {
-            this.p_xy_movement();
+            if this.momxyz.x != 0.0 || this.momxyz.y != 0.0 || MapObjFlag::Skullfly as u32 != 0 {
+                this.p_xy_movement();
 
-            if this.thinker_mut().should_remove() {
-                return true; // thing was removed
+                if this.thinker_mut().should_remove() {
+                    return true; // thing was removed
+                }
+            }
+            if (this.xyz.z - this.floorz).abs() > f32::EPSILON || this.momxyz.z != 0.0 {
+                this.p_z_movement();
             }
-        }
-        if (this.xyz.z - this.floorz).abs() > f32::EPSILON || this.momxyz.z != 0.0 {
-            this.p_z_movement();
         }
 
         // cycle through states,
@@ -958,7 +1269,7 @@ impl Think for MapObject {
             if (level.level_time & 31) != 0 {
                 return false;
             }
-            if p_random() > 4 {
+            if level.random.p_random() > 4 {
                 return false;
             }
             this.nightmare_respawn();
@@ -1006,3 +1317,127 @@ impl Think for MapObject {
 
 //     MapObjKind::from(i)
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use wad::types::WadThing;
+
+    use super::MapObject;
+    use crate::doom_def::GameMode;
+    use crate::info::MapObjKind;
+    use crate::level::Level;
+    use crate::{GameOptions, Player, Random, MAXPLAYERS};
+
+    fn level(players: &mut [Player; MAXPLAYERS]) -> Level {
+        let (tx, _rx) = channel();
+        let mut level = unsafe {
+            Level::new_empty(
+                GameOptions::default(),
+                GameMode::Shareware,
+                tx,
+                &[true; MAXPLAYERS],
+                players,
+                Random::new(),
+            )
+        };
+        for (i, start) in level.player_starts.iter_mut().enumerate() {
+            *start = Some(WadThing::new(i as i16 * 64, 0, 90, i as i16 + 1, 7));
+        }
+        for i in 0..4 {
+            let spot = WadThing::new(0, i * 128, 0, 11, 7);
+            level.deathmatch_p.push(spot);
+        }
+        level
+    }
+
+    fn spot(mthing: Option<WadThing>) -> Option<(i16, i16, i16)> {
+        mthing.map(|m| (m.x, m.y, m.kind))
+    }
+
+    #[test]
+    fn deathmatch_spot_is_a_clear_start() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+
+        let mut tried = 0;
+        let mthing = MapObject::deathmatch_spot(2, &mut level, |mthing, _| {
+            tried += 1;
+            mthing.y == 256
+        });
+        // kind is changed to the player spawning there
+        assert_eq!(spot(mthing), Some((0, 256, 3)));
+        // one random number per start tried
+        assert_eq!(level.random.indexes().1, tried);
+    }
+
+    #[test]
+    fn deathmatch_spot_falls_back_to_the_player_start() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+
+        let mut tried = 0;
+        let mthing = MapObject::deathmatch_spot(1, &mut level, |_, _| {
+            tried += 1;
+            false
+        });
+        assert_eq!(tried, 20);
+        assert_eq!(spot(mthing), Some((64, 0, 2)));
+
+        // no deathmatch starts at all
+        level.deathmatch_p.clear();
+        let mthing = MapObject::deathmatch_spot(1, &mut level, |_, _| unreachable!());
+        assert_eq!(spot(mthing), Some((64, 0, 2)));
+    }
+
+    #[test]
+    fn coop_spot_order() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+        let corpse = Some(WadThing::new(500, 600, 45, 2, 0));
+
+        // own start, every start in player order, then the corpse
+        let mut tried = Vec::new();
+        let mthing = MapObject::coop_spot(1, corpse, &mut level, |mthing, _| {
+            tried.push(mthing.x);
+            false
+        });
+        assert_eq!(tried, [64, 0, 64, 128, 192, 500]);
+        // blocked everywhere, spawn at their own start anyway
+        assert_eq!(spot(mthing), Some((64, 0, 2)));
+
+        let mthing = MapObject::coop_spot(1, corpse, &mut level, |mthing, _| mthing.x == 128);
+        assert_eq!(spot(mthing), Some((128, 0, 2)));
+        let mthing = MapObject::coop_spot(1, corpse, &mut level, |mthing, _| mthing.x == 500);
+        assert_eq!(spot(mthing), Some((500, 600, 2)));
+
+        // no start of their own
+        level.player_starts[1] = None;
+        let mut tried = Vec::new();
+        let mthing = MapObject::coop_spot(1, None, &mut level, |mthing, _| {
+            tried.push(mthing.x);
+            false
+        });
+        assert_eq!(tried, [0, 128, 192]);
+        assert!(mthing.is_none());
+    }
+
+    #[test]
+    fn first_spawn_check_spot() {
+        let mut players: [Player; MAXPLAYERS] = Default::default();
+        let mut level = level(&mut players);
+        // player 1 spawned at their own start
+        let mut mobj = MapObject::new_for_spawn(0.0, 0.0, 0, MapObjKind::MT_PLAYER, &mut level);
+        players[0].set_mobj(&mut mobj);
+
+        // only the players spawned before are checked
+        let start = level.player_starts[1].unwrap();
+        assert!(MapObject::check_spot(1, &start, &mut level, &mut players));
+        let taken = level.player_starts[0].unwrap();
+        assert!(!MapObject::check_spot(1, &taken, &mut level, &mut players));
+        assert!(!MapObject::check_spot(3, &taken, &mut level, &mut players));
+        players[0].clear_mobj();
+        assert!(MapObject::check_spot(1, &taken, &mut level, &mut players));
+    }
+}
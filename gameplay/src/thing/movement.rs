@@ -10,6 +10,7 @@ use log::debug;
 
 use crate::angle::Angle;
 use crate::doom_def::{FLOATSPEED, USERANGE, VIEWHEIGHT};
+use crate::env::pushers::ORIG_FRICTION_FACTOR;
 use crate::env::specials::cross_special_line;
 use crate::env::switch::p_use_special_line;
 use crate::info::StateNum;
@@ -17,10 +18,11 @@ use crate::level::flags::LineDefFlags;
 use crate::level::map_data::BSPTrace;
 use crate::level::map_defs::{BBox, LineDef, SlopeType};
 use crate::utilities::{
-    box_on_line_side, p_random, path_traverse, BestSlide, Intercept, PortalZ, FRACUNIT_DIV4
+    box_on_line_side, path_traverse, BestSlide, Intercept, PortalZ, FRACUNIT_DIV4
 };
 use crate::{MapObjKind, MapObject, MapPtr};
 
+use super::fixed_map::FixedCheck;
 use super::MapObjFlag;
 
 pub const GRAVITY: f32 = 1.0;
@@ -242,9 +244,12 @@ impl MapObject {
                 self.momxyz = Vec3::default();
             }
         } else {
-            self.momxyz.x *= FRICTION;
-            self.momxyz.y *= FRICTION;
+            self.momxyz.x *= self.friction;
+            self.momxyz.y *= self.friction;
         }
+        // Friction sectors set these again each tic
+        self.friction = FRICTION;
+        self.movefactor = ORIG_FRICTION_FACTOR;
     }
 
     /// P_TryMove, merged with P_CheckPosition and using a more verbose/modern
@@ -252,6 +257,14 @@ impl MapObject {
     ///
     /// If `try_move` is allowed it is then set as the current position
     pub(crate) fn p_try_move(&mut self, try_move: Vec3, ctrl: &mut SubSectorMinMax) -> bool {
+        if self.level().options.fixed_point {
+            self.sync_fixed();
+            let (x, y) = self.fixed_xy(try_move);
+            let mut check = FixedCheck::default();
+            let moved = self.fixed_try_move(x, y, &mut check);
+            check.fill(ctrl, self.level());
+            return moved;
+        }
         // P_CrossSpecialLine
 
         ctrl.floatok = false;
@@ -305,7 +318,7 @@ impl MapObject {
                 let side = ld.point_on_side(self.xyz);
                 let old_side = ld.point_on_side(old_xyz);
                 if side != old_side && ld.special != 0 {
-                    cross_special_line(old_side, ld.clone(), self)
+                    cross_special_line(old_side, ld.clone(), self, false)
                 }
             }
         }
@@ -316,6 +329,14 @@ impl MapObject {
     ///
     /// Doom function name `P_CheckPosition`
     pub(crate) fn p_check_position(&mut self, endpoint: Vec3, ctrl: &mut SubSectorMinMax) -> bool {
+        if self.level().options.fixed_point {
+            self.sync_fixed();
+            let (x, y) = self.fixed_xy(endpoint);
+            let mut check = FixedCheck::default();
+            let fits = self.fixed_check_position(x, y, &mut check);
+            check.fill(ctrl, self.level());
+            return fits;
+        }
         let left = endpoint.x - self.radius;
         let right = endpoint.x + self.radius;
         let top = endpoint.y + self.radius;
@@ -411,7 +432,7 @@ impl MapObject {
         }
 
         if self.flags & MapObjFlag::Skullfly as u32 != 0 {
-            let damage = ((p_random() % 8) + 1) * self.info.damage;
+            let damage = ((self.p_random() % 8) + 1) * self.info.damage;
             thing.p_take_damage(Some(self), None, true, damage);
 
             self.momxyz = Vec3::default();
@@ -455,7 +476,7 @@ impl MapObject {
                     return thing.flags & MapObjFlag::Solid as u32 != MapObjFlag::Solid as u32;
                 }
 
-                let damage = ((p_random() % 8) + 1) * self.info.damage;
+                let damage = ((self.p_random() % 8) + 1) * self.info.damage;
                 thing.p_take_damage(Some(self), Some(target), false, damage);
             }
         }
@@ -594,7 +615,12 @@ impl MapObject {
     /// Loop until get a good move or stopped
     ///
     /// Doom function name `P_SlideMove`
-    fn p_slide_move(&mut self) {
+    pub(super) fn p_slide_move(&mut self) {
+        if self.level().options.fixed_point {
+            self.sync_fixed();
+            self.fixed_slide_move();
+            return;
+        }
         // let ctrl = &mut level.mobj_ctrl;
         let mut hitcount = 0;
         self.best_slide = BestSlide::new();
@@ -786,6 +812,11 @@ impl MapObject {
     /// P_UseLines
     /// Looks for special lines in front of the player to activate.
     pub(crate) fn use_lines(&mut self) {
+        if self.level().options.fixed_point {
+            self.sync_fixed();
+            self.fixed_use_lines();
+            return;
+        }
         let angle = self.angle.unit_vec3();
 
         let origin = self.xyz;
@@ -837,7 +868,7 @@ impl MapObject {
             }
 
             let side = line.point_on_side(self.xyz);
-            p_use_special_line(side as i32, line.clone(), self);
+            p_use_special_line(side as i32, line.clone(), self, false);
         }
         // can't use for than one special line in a row
         false
@@ -7,10 +7,11 @@ use sound_traits::SfxName;
 
 use crate::doom_def::{MAXRADIUS, MELEERANGE};
 use crate::env::specials::shoot_special_line;
+use crate::fixed::{bam_to_angle, float_to_fixed};
 use crate::info::{StateNum, MOBJINFO};
 use crate::level::map_data::BSPTrace;
 use crate::level::map_defs::LineDef;
-use crate::utilities::{p_random, path_traverse, point_to_angle_2, Intercept, PortalZ};
+use crate::utilities::{path_traverse, point_to_angle_2, Intercept, PortalZ};
 use crate::{Angle, LineDefFlags, MapObjKind, MapObject, MapPtr};
 
 use super::{MapObjFlag, PT_ADDLINES, PT_ADDTHINGS};
@@ -21,7 +22,7 @@ impl MapObject {
         self.momxyz = Vec3::default();
         self.set_state(MOBJINFO[self.kind as usize].deathstate);
 
-        self.tics -= p_random() & 3;
+        self.tics -= self.p_random() & 3;
 
         if self.tics < 1 {
             self.tics = 1;
@@ -47,18 +48,46 @@ impl MapObject {
         bsp_trace
     }
 
+    /// Slope of the view of a player, level for everything else
+    pub(crate) fn view_slope(&self) -> f32 {
+        self.player
+            .map_or(0.0, |player| unsafe { (*player).pitch }.tan())
+    }
+
+    /// The slope a player shoots along with free aim, instead of the one
+    /// autoaim finds
+    pub(crate) fn look_slope(&self) -> Option<f32> {
+        (self.player.is_some() && self.level().options.controls.free_aim())
+            .then(|| self.view_slope())
+    }
+
     pub(crate) fn aim_line_attack(
         &mut self,
         distance: f32,
         bsp_trace: &mut BSPTrace,
     ) -> Option<AimResult> {
+        let view_slope = self.view_slope();
+        self.aim_line_attack_at(distance, view_slope, bsp_trace)
+    }
+
+    /// `aim_line_attack` for a view looking along `view_slope`
+    pub(crate) fn aim_line_attack_at(
+        &mut self,
+        distance: f32,
+        view_slope: f32,
+        bsp_trace: &mut BSPTrace,
+    ) -> Option<AimResult> {
+        if self.level().options.fixed_point {
+            let angle = self.fixed_angle();
+            return self.fixed_aim_result(angle, distance, view_slope);
+        }
         let xy2 = self.xyz + self.angle.unit_vec3() * distance;
 
         // set up traverser
         let mut aim_traverse = SubSectTraverse::new(
             // can't shoot outside view angles
-            100.0 / 160.0,
-            -100.0 / 160.0,
+            view_slope + 100.0 / 160.0,
+            view_slope - 100.0 / 160.0,
             //
             distance,
             self.xyz.z + (self.height as i32 >> 1) as f32 + 8.0,
@@ -77,6 +106,25 @@ impl MapObject {
         aim_traverse.result()
     }
 
+    /// `aim_line_attack` turned to `angle`
+    pub(crate) fn aim_line_attack_to(
+        &mut self,
+        angle: Angle,
+        distance: f32,
+        bsp_trace: &mut BSPTrace,
+    ) -> Option<AimResult> {
+        let view_slope = self.view_slope();
+        if self.level().options.fixed_point {
+            let angle = self.bam_of(angle);
+            return self.fixed_aim_result(angle, distance, view_slope);
+        }
+        let old_angle = self.angle;
+        self.angle = angle;
+        let aim = self.aim_line_attack_at(distance, view_slope, bsp_trace);
+        self.angle = old_angle;
+        aim
+    }
+
     /// `shoot_line_attack` is preceeded by `aim_line_attack` in many cases, so
     /// the `BSPTrace` can be shared between the two.
     pub(crate) fn shoot_line_attack(
@@ -87,6 +135,17 @@ impl MapObject {
         damage: f32,
         bsp_trace: &mut BSPTrace,
     ) {
+        if self.level().options.fixed_point {
+            let angle = self.bam_of(angle);
+            self.sync_fixed();
+            self.fixed_line_attack(
+                angle,
+                float_to_fixed(attack_range),
+                float_to_fixed(aim_slope),
+                damage as i32,
+            );
+            return;
+        }
         let mut shoot_traverse = ShootTraverse::new(
             aim_slope,
             attack_range,
@@ -118,6 +177,11 @@ impl MapObject {
     ///
     /// Doom function name `P_RadiusAttack`
     pub fn radius_attack(&mut self, damage: f32) {
+        if self.level().options.fixed_point {
+            self.sync_fixed();
+            self.fixed_radius_attack(damage as i32);
+            return;
+        }
         // source is self.target
         // bsp_count is just for debugging BSP descent depth/width
         let mut bsp_count = 0;
@@ -184,9 +248,12 @@ impl MapObject {
         distance: f32,
         bsp_trace: &mut BSPTrace,
     ) -> Option<AimResult> {
+        if self.level().options.fixed_point {
+            return self.fixed_bullet_slope();
+        }
         let mut bullet_slope = self.aim_line_attack(distance, bsp_trace);
         let old_angle = self.angle;
-        if bullet_slope.is_none() {
+        if bullet_slope.is_none() && self.look_slope().is_none() {
             self.angle += 5.625f32.to_radians();
             bullet_slope = self.aim_line_attack(distance, bsp_trace);
             if bullet_slope.is_none() {
@@ -206,18 +273,50 @@ impl MapObject {
         bullet_slope: Option<AimResult>,
         bsp_trace: &mut BSPTrace,
     ) {
-        let damage = 5.0 * (p_random() % 3 + 1) as f32;
+        let damage = 5.0 * (self.p_random() % 3 + 1) as f32;
         let mut angle = self.angle;
 
         if !accurate {
-            angle += (((p_random() - p_random()) >> 5) as f32).to_radians();
+            angle = self.spread_angle(18, 5);
         }
 
-        if let Some(res) = bullet_slope {
-            self.shoot_line_attack(distance, angle, res.aimslope, damage, bsp_trace);
-        } else {
-            self.shoot_line_attack(distance, angle, 0.0, damage, bsp_trace);
+        let slope = self.shot_slope(bullet_slope);
+        self.shoot_line_attack(distance, angle, slope, damage, bsp_trace);
+    }
+
+    /// `self.angle` turned by a random spread of `P_Random - P_Random`. The
+    /// fixed-point mode turns by that shifted up by `bam_shift` in BAM, as the
+    /// original did, otherwise by it shifted down by `degree_shift` in degrees.
+    pub(crate) fn spread_angle(&mut self, bam_shift: u32, degree_shift: u32) -> Angle {
+        let spread = self.p_random() - self.p_random();
+        if self.level().options.fixed_point {
+            return self.angle + bam_to_angle((spread << bam_shift) as u32);
+        }
+        self.angle + ((spread >> degree_shift) as f32).to_radians()
+    }
+
+    /// Aim straight ahead, or if nothing is there 5.625 degrees to either
+    /// side, in the range the original autoaim had
+    ///
+    /// Doom function name `P_BulletSlope`
+    fn fixed_bullet_slope(&mut self) -> Option<AimResult> {
+        let angle = self.fixed_angle();
+        let view_slope = self.view_slope();
+        let mut aim = self.fixed_aim_result(angle, 16.0 * 64.0, view_slope);
+        if aim.is_none() && self.look_slope().is_none() {
+            aim = self.fixed_aim_result(angle.wrapping_add(1 << 26), 16.0 * 64.0, view_slope);
+            if aim.is_none() {
+                aim = self.fixed_aim_result(angle.wrapping_sub(1 << 26), 16.0 * 64.0, view_slope);
+            }
         }
+        aim
+    }
+
+    /// The slope to shoot along after aiming
+    pub(crate) fn shot_slope(&self, aim: Option<AimResult>) -> f32 {
+        self.look_slope()
+            .or(aim.map(|res| res.aimslope))
+            .unwrap_or(0.0)
     }
 
     /// Try to attack along a line using the previous `AimResult` and
@@ -230,11 +329,8 @@ impl MapObject {
         bullet_slope: Option<AimResult>,
         bsp_trace: &mut BSPTrace,
     ) {
-        if let Some(res) = bullet_slope {
-            self.shoot_line_attack(distance, angle, res.aimslope, damage, bsp_trace);
-        } else {
-            self.shoot_line_attack(distance, angle, 0.0, damage, bsp_trace);
-        }
+        let slope = self.shot_slope(bullet_slope);
+        self.shoot_line_attack(distance, angle, slope, damage, bsp_trace);
     }
 
     /// Get a `BSPTrace` for the selected point. It uses the shooters radius to
@@ -290,6 +386,9 @@ impl MapObject {
     /// Iterate through the available live players and check if there is a LOS
     /// to one.
     pub(crate) fn look_for_players(&mut self, all_around: bool) -> bool {
+        if self.level().options.fixed_point {
+            return self.fixed_look_for_players(all_around);
+        }
         let mut see = 0;
         let stop = (self.lastlook - 1) & 3;
 
@@ -347,6 +446,9 @@ impl MapObject {
     /// This checks teh '2D top-down' nature of Doom, followed by the Z
     /// (height) axis.
     pub(crate) fn check_sight_target(&mut self, target: &MapObject) -> bool {
+        if self.level().options.fixed_point {
+            return self.fixed_check_sight(target);
+        }
         // skip the BSP trace if too far away
         if !self.target_within_min_dist(target) {
             return false;
@@ -356,6 +458,9 @@ impl MapObject {
     }
 
     pub(crate) fn check_melee_range(&mut self) -> bool {
+        if self.level().options.fixed_point {
+            return self.fixed_check_melee_range();
+        }
         if let Some(target) = self.target {
             let target = unsafe { (*target).mobj() };
 
@@ -374,6 +479,9 @@ impl MapObject {
 
     /// The closer the Actor gets to the Target the more they shoot
     pub(crate) fn check_missile_range(&mut self) -> bool {
+        if self.level().options.fixed_point {
+            return self.fixed_check_missile_range();
+        }
         if let Some(target) = self.target {
             let target = unsafe { (*target).mobj() };
 
@@ -430,7 +538,7 @@ impl MapObject {
             }
 
             // All down to chance now
-            if p_random() >= dist as i32 {
+            if self.p_random() >= dist as i32 {
                 return true;
             }
         }
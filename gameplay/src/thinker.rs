@@ -1,5 +1,6 @@
 use std::alloc::{alloc, dealloc, Layout};
 use std::fmt::{self, Debug};
+use std::marker::PhantomData;
 use std::mem::{align_of, size_of};
 use std::ptr::{self, null_mut};
 
@@ -10,6 +11,8 @@ use crate::env::doors::VerticalDoor;
 use crate::env::floor::FloorMove;
 use crate::env::lights::{FireFlicker, Glow, LightFlash, StrobeFlash};
 use crate::env::platforms::Platform;
+use crate::env::pushers::{Friction, Pusher};
+use crate::env::scroll::Scroll;
 use crate::level::Level;
 use crate::thing::MapObject;
 
@@ -146,6 +149,21 @@ impl ThinkerAlloc {
         true
     }
 
+    /// Iterate over the live thinkers in the order they are run, skipping any
+    /// marked for removal.
+    pub fn iter(&self) -> ThinkerIter<'_> {
+        ThinkerIter {
+            head: self.head,
+            current: self.head,
+            _alloc: PhantomData,
+        }
+    }
+
+    /// The number of live map objects
+    pub fn mobj_count(&self) -> usize {
+        self.iter().filter(|t| t.is_mobj()).count()
+    }
+
     /// Iterates through the list of thinkers until either the closure returns
     /// true or the end is reached.
     pub(crate) fn find_thinker<F>(&self, finder: F) -> Option<&mut Thinker>
@@ -312,6 +330,31 @@ impl ThinkerAlloc {
     }
 }
 
+pub struct ThinkerIter<'a> {
+    head: *mut Thinker,
+    current: *mut Thinker,
+    _alloc: PhantomData<&'a ThinkerAlloc>,
+}
+
+impl<'a> Iterator for ThinkerIter<'a> {
+    type Item = &'a Thinker;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.current.is_null() {
+            let thinker = unsafe { &*self.current };
+            self.current = if ptr::eq(thinker.next, self.head) {
+                null_mut()
+            } else {
+                thinker.next
+            };
+            if !thinker.should_remove() {
+                return Some(thinker);
+            }
+        }
+        None
+    }
+}
+
 /// All map object thinkers need to be registered here. If the object has
 /// pointees then these must be dealt with before setting `ObjectType::Remove`.
 #[repr(C)]
@@ -327,6 +370,9 @@ pub enum ThinkerData {
     StrobeFlash(StrobeFlash),
     FireFlicker(FireFlicker),
     Glow(Glow),
+    Scroll(Scroll),
+    Friction(Friction),
+    Pusher(Pusher),
     /// The thinker function should set to this when the linked-list node
     /// and memory is no-longer required. On thinker run it will be set to
     /// `Free` and unlinked.
@@ -348,6 +394,9 @@ impl Debug for ThinkerData {
             Self::StrobeFlash(_) => f.debug_tuple("StrobeFlash").finish(),
             Self::FireFlicker(_) => f.debug_tuple("FireFlicker").finish(),
             Self::Glow(_) => f.debug_tuple("Glow").finish(),
+            Self::Scroll(_) => f.debug_tuple("Scroll").finish(),
+            Self::Friction(_) => f.debug_tuple("Friction").finish(),
+            Self::Pusher(_) => f.debug_tuple("Pusher").finish(),
             Self::Remove => f.debug_tuple("Remove").finish(),
             Self::Free => f.debug_tuple("Free - this shouldn't ever be seen").finish(),
         }
@@ -409,6 +458,9 @@ impl Thinker {
             ThinkerData::StrobeFlash(obj) => obj.set_thinker_ptr(ptr),
             ThinkerData::FireFlicker(obj) => obj.set_thinker_ptr(ptr),
             ThinkerData::Glow(obj) => obj.set_thinker_ptr(ptr),
+            ThinkerData::Scroll(obj) => obj.set_thinker_ptr(ptr),
+            ThinkerData::Friction(obj) => obj.set_thinker_ptr(ptr),
+            ThinkerData::Pusher(obj) => obj.set_thinker_ptr(ptr),
             ThinkerData::Remove => {
                 error!("Tried to set the Thinker pointer for an Object that was 'Remove'");
             }
@@ -546,6 +598,36 @@ impl Thinker {
             panic!("ObjectType is not Glow");
         }
     }
+
+    /// Get inner `Scroll` data as mut. Panics if the inner is not actually
+    /// `Scroll`
+    pub fn scroll_mut(&mut self) -> &mut Scroll {
+        if let ThinkerData::Scroll(obj) = &mut self.data {
+            obj
+        } else {
+            panic!("ObjectType is not Scroll");
+        }
+    }
+
+    /// Get inner `Friction` data as mut. Panics if the inner is not actually
+    /// `Friction`
+    pub fn friction_mut(&mut self) -> &mut Friction {
+        if let ThinkerData::Friction(obj) = &mut self.data {
+            obj
+        } else {
+            panic!("ObjectType is not Friction");
+        }
+    }
+
+    /// Get inner `Pusher` data as mut. Panics if the inner is not actually
+    /// `Pusher`
+    pub fn pusher_mut(&mut self) -> &mut Pusher {
+        if let ThinkerData::Pusher(obj) = &mut self.data {
+            obj
+        } else {
+            panic!("ObjectType is not Pusher");
+        }
+    }
 }
 
 impl Debug for Thinker {
@@ -621,7 +703,7 @@ mod tests {
     use crate::level::map_data::MapData;
     use crate::level::Level;
     use crate::thinker::{Think, Thinker};
-    use crate::{PicData, Player, MAXPLAYERS};
+    use crate::{PicData, Player, Random, MAXPLAYERS};
 
     use super::{TestObject, ThinkerAlloc, ThinkerData};
     use std::ptr::null_mut;
@@ -666,6 +748,7 @@ mod tests {
                     Player::default(),
                     Player::default(),
                 ],
+                Random::new(),
             )
         };
         let mut x = Thinker {
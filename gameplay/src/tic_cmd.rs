@@ -62,6 +62,11 @@ pub const TIC_CMD_BUTTONS: ButtonCode = ButtonCode {
     bts_saveshift: 2,
 };
 
+/// `TicCmd::actions` flag to jump
+pub const ACTION_JUMP: u8 = 1;
+/// `TicCmd::actions` flag to crouch
+pub const ACTION_CROUCH: u8 = 2;
+
 /// The data sampled per tick (single player)
 /// and transmitted to other peers (multiplayer).
 /// Mainly movements/button commands per game-exe tick,
@@ -79,6 +84,11 @@ pub struct TicCmd {
     pub consistancy: i16,
     pub chatchar: u8,
     pub buttons: u8,
+    /// Look up or down by this, PI per 32768. Only used with free-look and
+    /// never recorded in demos
+    pub pitch: i16,
+    /// `ACTION_JUMP` and `ACTION_CROUCH`. Never recorded in demos
+    pub actions: u8,
 }
 
 impl TicCmd {
@@ -90,6 +100,70 @@ impl TicCmd {
             consistancy: 0,
             chatchar: 0,
             buttons: 0,
+            pitch: 0,
+            actions: 0,
         }
     }
+
+    /// Pack for sending to other peers. Multi-byte fields are little endian
+    pub fn to_bytes(&self) -> [u8; 11] {
+        let angleturn = self.angleturn.to_le_bytes();
+        let consistancy = self.consistancy.to_le_bytes();
+        let pitch = self.pitch.to_le_bytes();
+        [
+            self.forwardmove as u8,
+            self.sidemove as u8,
+            angleturn[0],
+            angleturn[1],
+            consistancy[0],
+            consistancy[1],
+            self.chatchar,
+            self.buttons,
+            pitch[0],
+            pitch[1],
+            self.actions,
+        ]
+    }
+
+    /// Unpack a cmd packed by `to_bytes`
+    pub fn from_bytes(b: [u8; 11]) -> Self {
+        TicCmd {
+            forwardmove: b[0] as i8,
+            sidemove: b[1] as i8,
+            angleturn: i16::from_le_bytes([b[2], b[3]]),
+            consistancy: i16::from_le_bytes([b[4], b[5]]),
+            chatchar: b[6],
+            buttons: b[7],
+            pitch: i16::from_le_bytes([b[8], b[9]]),
+            actions: b[10],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TicCmd;
+
+    #[test]
+    fn bytes_round_trip() {
+        let cmd = TicCmd {
+            forwardmove: -50,
+            sidemove: 40,
+            angleturn: -1280,
+            consistancy: 0x1234,
+            chatchar: b'a',
+            buttons: 0x83,
+            pitch: -300,
+            actions: 2,
+        };
+        let back = TicCmd::from_bytes(cmd.to_bytes());
+        assert_eq!(back.forwardmove, -50);
+        assert_eq!(back.sidemove, 40);
+        assert_eq!(back.angleturn, -1280);
+        assert_eq!(back.consistancy, 0x1234);
+        assert_eq!(back.chatchar, b'a');
+        assert_eq!(back.buttons, 0x83);
+        assert_eq!(back.pitch, -300);
+        assert_eq!(back.actions, 2);
+    }
 }
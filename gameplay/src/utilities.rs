@@ -21,6 +21,18 @@ pub fn fixed_to_float(value: i32) -> f32 {
     value as f32 / FRACUNIT
 }
 
+/// Gives an estimation of distance (not exact)
+///
+/// Doom function name `P_AproxDistance`
+pub fn p_aprox_distance(mut dx: f32, mut dy: f32) -> f32 {
+    dx = dx.abs();
+    dy = dy.abs();
+    if dx < dy {
+        return dx + dy - dx / 2.0;
+    }
+    dx + dy - dy / 2.0
+}
+
 const DEG_TO_RAD: f32 = PI / 180.0;
 
 /// Convert a BAM (Binary Angle Measure) to radians
@@ -28,9 +40,6 @@ pub fn bam_to_radian(value: u32) -> f32 {
     (value as f32 * 8.381_903e-8) * DEG_TO_RAD
 }
 
-static mut RNDINDEX: usize = 0;
-static mut PRNDINDEX: usize = 0;
-
 pub const RNDTABLE: [i32; 256] = [
     0, 8, 109, 220, 222, 241, 149, 107, 75, 248, 254, 140, 16, 66, 74, 21, 211, 47, 80, 242, 154,
     27, 205, 128, 161, 89, 77, 36, 95, 110, 85, 48, 212, 140, 211, 249, 22, 79, 200, 50, 28, 188,
@@ -47,31 +56,62 @@ pub const RNDTABLE: [i32; 256] = [
     84, 118, 222, 187, 136, 120, 163, 236, 249,
 ];
 
-pub fn p_random() -> i32 {
-    unsafe {
-        PRNDINDEX = (PRNDINDEX + 1) & 0xFF;
-        RNDTABLE[PRNDINDEX]
-    }
+/// The positions of the two random number generators in `RNDTABLE`. The
+/// gameplay generator (`p_random`) must only be used by things that affect the
+/// game, so that demos and network games stay in sync. The other (`m_random`)
+/// is for menus, screens and effects.
+///
+/// A `Game` owns one, which is handed to the `Level` while one is loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Random {
+    rnd_index: usize,
+    prnd_index: usize,
 }
 
-pub fn m_random() -> i32 {
-    unsafe {
-        RNDINDEX = (RNDINDEX + 1) & 0xFF;
-        RNDTABLE[RNDINDEX]
+impl Random {
+    pub const fn new() -> Self {
+        Self {
+            rnd_index: 0,
+            prnd_index: 0,
+        }
     }
-}
 
-pub fn m_clear_random() {
-    unsafe {
+    /// Doom function name `P_Random`
+    pub fn p_random(&mut self) -> i32 {
+        self.prnd_index = (self.prnd_index + 1) & 0xFF;
+        RNDTABLE[self.prnd_index]
+    }
+
+    /// Doom function name `M_Random`
+    pub fn m_random(&mut self) -> i32 {
+        self.rnd_index = (self.rnd_index + 1) & 0xFF;
+        RNDTABLE[self.rnd_index]
+    }
+
+    /// Doom function name `P_SubRandom`
+    pub fn p_subrandom(&mut self) -> i32 {
+        let r = self.p_random();
+        r - self.p_random()
+    }
+
+    /// Doom function name `M_ClearRandom`
+    pub fn clear(&mut self) {
         // Not clearing this random as it's used only by screen wipe so far
-        //RNDINDEX = 0;
-        PRNDINDEX = 0;
+        //self.rnd_index = 0;
+        self.prnd_index = 0;
+    }
+
+    /// The current indexes of `m_random()` and `p_random()` in to `RNDTABLE`
+    pub fn indexes(&self) -> (usize, usize) {
+        (self.rnd_index, self.prnd_index)
     }
-}
 
-pub fn p_subrandom() -> i32 {
-    let r = p_random();
-    r - p_random()
+    /// Restore the indexes of `m_random()` and `p_random()`, such as when
+    /// loading a saved game
+    pub fn set_indexes(&mut self, rnd: usize, prnd: usize) {
+        self.rnd_index = rnd & 0xFF;
+        self.prnd_index = prnd & 0xFF;
+    }
 }
 
 /// Used in path tracing for intercepts
@@ -2,18 +2,19 @@
 //! intermission screens to get certain information they require or cause a
 //! gamestate change.
 
+mod scancode;
 pub mod util;
 
 use gameplay::MAXPLAYERS;
 pub use gameplay::{
-    m_random, AmmoType, Card, GameMode, PlayerCheat, PlayerStatus, PowerType, Skill, WeaponType, WorldEndPlayerInfo, TICRATE, WEAPON_INFO
+    glam, AmmoType, Card, GameMode, Level, LineDefFlags, MapObjInfo, MapObjKind, Player, PlayerCheat, PlayerState, PlayerStatus, PowerType, Random, Sector, Skill, StateNum, WeaponType, WorldEndPlayerInfo, MOBJINFO, SPRNAMES, STATES, TICRATE, WEAPON_INFO
 };
 pub use render_target::{PixelBuffer, RenderType};
-pub use sdl2::keyboard::Scancode;
-pub use sdl2::{self};
+pub use scancode::Scancode;
 pub use sound_traits::{MusTrack, SfxName};
 
 use wad::types::{WadPalette, WadPatch};
+use wad::umapinfo::MapInfo;
 use wad::WadData;
 
 /// The current state of the game-exe: whether we are playing, gazing at the
@@ -42,10 +43,22 @@ pub struct WorldInfo {
     /// previous and next levels, origin 0
     pub last: usize,
     pub next: usize,
+    /// episode of the next level, origin 0. `UMAPINFO` may cross episodes
+    pub next_episode: usize,
+    /// if true, the level was left by the secret exit
+    pub secret_exit: bool,
+    /// `UMAPINFO` entry of the previous level, if any
+    pub last_info: Option<MapInfo>,
+    /// `UMAPINFO` entry of the next level, if any
+    pub next_info: Option<MapInfo>,
     pub maxkills: i32,
     pub maxitems: i32,
     pub maxsecret: i32,
     pub maxfrags: i32,
+    /// 0 for co-op or single player, 1 or 2 for deathmatch
+    pub deathmatch: u8,
+    /// More than one player was in the game
+    pub netgame: bool,
     /// the par time
     pub partime: i32,
     /// index of this player in game-exe
@@ -53,6 +66,29 @@ pub struct WorldInfo {
     pub plyr: [WorldEndPlayerInfo; MAXPLAYERS],
 }
 
+/// Demo playback position and controls, for display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DemoStatus {
+    /// Tics played so far
+    pub tic: u32,
+    /// Length of the demo in tics
+    pub total_tics: u32,
+    pub paused: bool,
+    /// Playback speed multiplier
+    pub speed: f32,
+}
+
+/// How the automap is shown
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum AutomapState {
+    #[default]
+    Off,
+    /// The map is drawn instead of the player's view
+    FullScreen,
+    /// The map is drawn over the player's view
+    Overlay,
+}
+
 /// Universal game traits. To be implemented by the Game
 pub trait GameTraits {
     /// Helper to start a new game, e.g, from menus
@@ -64,12 +100,19 @@ pub trait GameTraits {
     /// screens that Doom II doesn't have (for example).
     fn get_mode(&self) -> GameMode;
 
-    /// Ask the game to load this save
-    fn load_game(&mut self, name: String);
+    /// Ask the game to load the save in this slot
+    fn load_game(&mut self, slot: usize);
 
     /// Ask the game to save to this slot with this name
     fn save_game(&mut self, name: String, slot: usize);
 
+    /// The name of the save in this slot, `None` if the slot is empty or the
+    /// save can't be read
+    fn save_description(&self, slot: usize) -> Option<String>;
+
+    /// If the game can be saved right now, which is only while playing a level
+    fn can_save(&self) -> bool;
+
     /// Pauses the game-loop (generally stops gameplay input and thinkers
     /// running)
     fn toggle_pause_game(&mut self);
@@ -83,6 +126,10 @@ pub trait GameTraits {
     /// Change to or play this music track
     fn change_music(&self, music: MusTrack);
 
+    /// Change to or play music from a lump by name, e.g, music set by
+    /// `UMAPINFO`. Returns false if the lump doesn't exist.
+    fn change_music_lump(&self, name: &str) -> bool;
+
     /// Tell the game that the level is completed and the next level or state
     /// should begin
     fn level_done(&mut self);
@@ -101,8 +148,24 @@ pub trait GameTraits {
     /// Takes the player message waiting and replaces with None
     fn player_msg_take(&mut self) -> Option<String>;
 
+    /// The playback position if a demo is being played
+    fn demo_status(&self) -> Option<DemoStatus>;
+
     fn get_wad_data(&self) -> &WadData;
 
+    /// The level being played, `None` outside of a level
+    fn level(&self) -> Option<&Level>;
+
+    /// The player with this number, if they are in the game
+    fn player(&self, num: usize) -> Option<&Player>;
+
+    /// The number of the player at this console
+    fn console_player(&self) -> usize;
+
+    /// Show or hide the automap. The player's view isn't drawn while the
+    /// automap is full screen
+    fn set_automap(&mut self, state: AutomapState);
+
     // TODO: get and set settings Struct
 }
 
@@ -122,20 +185,37 @@ pub trait SubsystemTrait {
     /// Draw this Machination to the `PixelBuf`.
     fn draw(&mut self, buffer: &mut dyn PixelBuffer);
 
-    /// Free method, requires `get_palette()` to be implemented
+    /// Clip what `draw_patch_pixels()` draws to the 320 wide area of the
+    /// screen instead of the whole buffer, so patches scrolled in from the
+    /// side don't show past it on wide buffers
+    fn clip_patches_to_320(&self) -> bool {
+        false
+    }
+
+    /// Free method, requires `get_palette()` to be implemented. `x` and `y`
+    /// are in pixels of the buffer, and anything outside of it, or outside
+    /// the 320 wide area with `clip_patches_to_320()`, is clipped.
     fn draw_patch_pixels(&self, patch: &WadPatch, x: i32, y: i32, pixels: &mut dyn PixelBuffer) {
         let mut xtmp = 0;
         let mut ytmp = 0;
 
         let f = pixels.size().height() / 200;
+        let width = if self.clip_patches_to_320() {
+            pixels.size().width().min(320 * f)
+        } else {
+            pixels.size().width()
+        };
+        let height = pixels.size().height();
         for column in patch.columns.iter() {
             for n in 0..f {
                 for p in column.pixels.iter() {
                     let colour = self.get_palette().0[*p];
                     for _ in 0..f {
-                        let x = (x + xtmp - n - patch.left_offset as i32).unsigned_abs() as usize;
-                        let y = (y + ytmp + column.y_offset * f).unsigned_abs() as usize;
-                        pixels.set_pixel(x, y, &colour.0);
+                        let x = x + xtmp - n - patch.left_offset as i32;
+                        let y = y + ytmp + column.y_offset * f;
+                        if x >= 0 && y >= 0 && x < width && y < height {
+                            pixels.set_pixel(x as usize, y as usize, &colour.0);
+                        }
                         ytmp += 1;
                     }
                 }
@@ -148,3 +228,100 @@ pub trait SubsystemTrait {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use render_target::RenderTarget;
+    use wad::types::{WadColour, WadPalette, WadPatch, WadPatchCol};
+
+    use super::{GameTraits, PixelBuffer, Scancode, SubsystemTrait};
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const EMPTY: [u8; 4] = [0; 4];
+
+    struct Patches(WadPalette, bool);
+
+    impl SubsystemTrait for Patches {
+        fn init(&mut self, _: &impl GameTraits) {}
+
+        fn responder(&mut self, _: Scancode, _: &mut impl GameTraits) -> bool {
+            false
+        }
+
+        fn ticker(&mut self, _: &mut impl GameTraits) -> bool {
+            false
+        }
+
+        fn get_palette(&self) -> &WadPalette {
+            &self.0
+        }
+
+        fn draw(&mut self, _: &mut dyn PixelBuffer) {}
+
+        fn clip_patches_to_320(&self) -> bool {
+            self.1
+        }
+    }
+
+    /// A red column then a green one, each a single pixel
+    fn patches(clip_to_320: bool) -> (Patches, WadPatch) {
+        let mut palette = WadPalette::new();
+        palette.0[1] = WadColour(RED);
+        palette.0[2] = WadColour(GREEN);
+        let post = |y_offset, pixels| WadPatchCol { y_offset, pixels };
+        let patch = WadPatch {
+            name: "TEST".to_string(),
+            width: 2,
+            height: 1,
+            left_offset: 0,
+            top_offset: 0,
+            columns: vec![
+                post(0, vec![1]),
+                post(255, vec![]),
+                post(0, vec![2]),
+                post(255, vec![]),
+            ],
+        };
+        (Patches(palette, clip_to_320), patch)
+    }
+
+    #[test]
+    fn patch_pixels_clip_left() {
+        let (patches, patch) = patches(false);
+        let mut target = RenderTarget::new(640, 400);
+        let pixels = target.pixel_buffer();
+        patches.draw_patch_pixels(&patch, -2, 0, pixels);
+        assert_eq!(pixels.read_pixel(0, 0), GREEN);
+        // Not wrapped around to the other side of 0
+        for x in 1..4 {
+            assert_eq!(pixels.read_pixel(x, 0), EMPTY);
+        }
+    }
+
+    #[test]
+    fn patch_pixels_clip_right_and_bottom() {
+        let (patches, patch) = patches(false);
+        let mut target = RenderTarget::new(640, 400);
+        let pixels = target.pixel_buffer();
+        patches.draw_patch_pixels(&patch, 639, 399, pixels);
+        assert_eq!(pixels.read_pixel(639, 399), RED);
+        assert_eq!(pixels.read_pixel(638, 399), RED);
+    }
+
+    #[test]
+    fn patch_pixels_clip_to_320() {
+        let (clipped, patch) = patches(true);
+        let mut target = RenderTarget::new(800, 400);
+        let pixels = target.pixel_buffer();
+        clipped.draw_patch_pixels(&patch, 639, 0, pixels);
+        assert_eq!(pixels.read_pixel(639, 0), RED);
+        assert_eq!(pixels.read_pixel(640, 0), EMPTY);
+        assert_eq!(pixels.read_pixel(641, 0), EMPTY);
+
+        // Without the option the buffer is the limit
+        let (unclipped, patch) = patches(false);
+        unclipped.draw_patch_pixels(&patch, 639, 0, pixels);
+        assert_eq!(pixels.read_pixel(641, 0), GREEN);
+    }
+}
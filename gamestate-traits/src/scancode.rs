@@ -0,0 +1,255 @@
+//! Keyboard scancodes given to the `SubsystemTrait::responder()` of each
+//! subsystem. The values are the USB HID usage IDs, the same as SDL uses, so a
+//! frontend can convert with `Scancode::from_i32()`. Only the keys the game
+//! has a use for are here.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scancode {
+    A = 4,
+    B = 5,
+    C = 6,
+    D = 7,
+    E = 8,
+    F = 9,
+    G = 10,
+    H = 11,
+    I = 12,
+    J = 13,
+    K = 14,
+    L = 15,
+    M = 16,
+    N = 17,
+    O = 18,
+    P = 19,
+    Q = 20,
+    R = 21,
+    S = 22,
+    T = 23,
+    U = 24,
+    V = 25,
+    W = 26,
+    X = 27,
+    Y = 28,
+    Z = 29,
+    Num1 = 30,
+    Num2 = 31,
+    Num3 = 32,
+    Num4 = 33,
+    Num5 = 34,
+    Num6 = 35,
+    Num7 = 36,
+    Num8 = 37,
+    Num9 = 38,
+    Num0 = 39,
+    Return = 40,
+    Escape = 41,
+    Backspace = 42,
+    Tab = 43,
+    Space = 44,
+    Minus = 45,
+    Equals = 46,
+    LeftBracket = 47,
+    RightBracket = 48,
+    Backslash = 49,
+    Semicolon = 51,
+    Apostrophe = 52,
+    Grave = 53,
+    Comma = 54,
+    Period = 55,
+    Slash = 56,
+    F1 = 58,
+    F2 = 59,
+    F3 = 60,
+    F4 = 61,
+    F5 = 62,
+    F6 = 63,
+    F7 = 64,
+    F8 = 65,
+    F9 = 66,
+    F10 = 67,
+    F11 = 68,
+    F12 = 69,
+    Pause = 72,
+    Insert = 73,
+    Home = 74,
+    PageUp = 75,
+    Delete = 76,
+    End = 77,
+    PageDown = 78,
+    Right = 79,
+    Left = 80,
+    Down = 81,
+    Up = 82,
+    KpMinus = 86,
+    KpPlus = 87,
+    KpEnter = 88,
+    Kp1 = 89,
+    Kp2 = 90,
+    Kp3 = 91,
+    Kp4 = 92,
+    Kp5 = 93,
+    Kp6 = 94,
+    Kp7 = 95,
+    Kp8 = 96,
+    Kp9 = 97,
+    Kp0 = 98,
+    LCtrl = 224,
+    LShift = 225,
+    LAlt = 226,
+    RCtrl = 228,
+    RShift = 229,
+    RAlt = 230,
+}
+
+const ALL: [Scancode; 94] = [
+    Scancode::A,
+    Scancode::B,
+    Scancode::C,
+    Scancode::D,
+    Scancode::E,
+    Scancode::F,
+    Scancode::G,
+    Scancode::H,
+    Scancode::I,
+    Scancode::J,
+    Scancode::K,
+    Scancode::L,
+    Scancode::M,
+    Scancode::N,
+    Scancode::O,
+    Scancode::P,
+    Scancode::Q,
+    Scancode::R,
+    Scancode::S,
+    Scancode::T,
+    Scancode::U,
+    Scancode::V,
+    Scancode::W,
+    Scancode::X,
+    Scancode::Y,
+    Scancode::Z,
+    Scancode::Num1,
+    Scancode::Num2,
+    Scancode::Num3,
+    Scancode::Num4,
+    Scancode::Num5,
+    Scancode::Num6,
+    Scancode::Num7,
+    Scancode::Num8,
+    Scancode::Num9,
+    Scancode::Num0,
+    Scancode::Return,
+    Scancode::Escape,
+    Scancode::Backspace,
+    Scancode::Tab,
+    Scancode::Space,
+    Scancode::Minus,
+    Scancode::Equals,
+    Scancode::LeftBracket,
+    Scancode::RightBracket,
+    Scancode::Backslash,
+    Scancode::Semicolon,
+    Scancode::Apostrophe,
+    Scancode::Grave,
+    Scancode::Comma,
+    Scancode::Period,
+    Scancode::Slash,
+    Scancode::F1,
+    Scancode::F2,
+    Scancode::F3,
+    Scancode::F4,
+    Scancode::F5,
+    Scancode::F6,
+    Scancode::F7,
+    Scancode::F8,
+    Scancode::F9,
+    Scancode::F10,
+    Scancode::F11,
+    Scancode::F12,
+    Scancode::Pause,
+    Scancode::Insert,
+    Scancode::Home,
+    Scancode::PageUp,
+    Scancode::Delete,
+    Scancode::End,
+    Scancode::PageDown,
+    Scancode::Right,
+    Scancode::Left,
+    Scancode::Down,
+    Scancode::Up,
+    Scancode::KpMinus,
+    Scancode::KpPlus,
+    Scancode::KpEnter,
+    Scancode::Kp1,
+    Scancode::Kp2,
+    Scancode::Kp3,
+    Scancode::Kp4,
+    Scancode::Kp5,
+    Scancode::Kp6,
+    Scancode::Kp7,
+    Scancode::Kp8,
+    Scancode::Kp9,
+    Scancode::Kp0,
+    Scancode::LCtrl,
+    Scancode::LShift,
+    Scancode::LAlt,
+    Scancode::RCtrl,
+    Scancode::RShift,
+    Scancode::RAlt,
+];
+
+impl Scancode {
+    /// The key with USB HID usage ID `n`, if it is one the game uses
+    pub fn from_i32(n: i32) -> Option<Self> {
+        ALL.iter().copied().find(|sc| *sc as i32 == n)
+    }
+}
+
+/// Printable keys show as the character they type, the same as SDL names them
+impl fmt::Display for Scancode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scancode::Num1 => "1",
+            Scancode::Num2 => "2",
+            Scancode::Num3 => "3",
+            Scancode::Num4 => "4",
+            Scancode::Num5 => "5",
+            Scancode::Num6 => "6",
+            Scancode::Num7 => "7",
+            Scancode::Num8 => "8",
+            Scancode::Num9 => "9",
+            Scancode::Num0 => "0",
+            Scancode::Minus => "-",
+            Scancode::Equals => "=",
+            Scancode::LeftBracket => "[",
+            Scancode::RightBracket => "]",
+            Scancode::Backslash => "\\",
+            Scancode::Semicolon => ";",
+            Scancode::Apostrophe => "'",
+            Scancode::Grave => "`",
+            Scancode::Comma => ",",
+            Scancode::Period => ".",
+            Scancode::Slash => "/",
+            _ => return write!(f, "{self:?}"),
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scancode;
+
+    #[test]
+    fn from_i32_and_names() {
+        assert_eq!(Scancode::from_i32(4), Some(Scancode::A));
+        assert_eq!(Scancode::from_i32(230), Some(Scancode::RAlt));
+        assert_eq!(Scancode::from_i32(50), None);
+        assert_eq!(Scancode::Q.to_string(), "Q");
+        assert_eq!(Scancode::Num7.to_string(), "7");
+        assert_eq!(Scancode::Slash.to_string(), "/");
+        assert_eq!(Scancode::Space.to_string(), "Space");
+    }
+}
@@ -0,0 +1,324 @@
+//! Per-tic consistency checks. A small snapshot of the game state is taken
+//! each tic and hashed in to `TicCmd::consistancy` so that peers, or two runs
+//! of the same demo, can find the first tic where they stop agreeing.
+//!
+//! Snapshots can be dumped to a text file, one line per tic, and a later run
+//! can check itself against that file. The lines are plain enough that two
+//! dumps can also be compared with `diff`.
+//!
+//! Doom source name `g_game` (consistancy section)
+
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use gameplay::log::error;
+use gameplay::{Player, MAXPLAYERS};
+
+#[derive(Debug)]
+pub enum ConsistencyError {
+    Io(io::Error),
+    /// A line of a dump couldn't be read
+    Parse(usize),
+}
+
+impl Error for ConsistencyError {}
+
+impl fmt::Display for ConsistencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyError::Io(e) => write!(f, "Consistency dump IO error: {e}"),
+            ConsistencyError::Parse(line) => write!(f, "Consistency dump line {line} is invalid"),
+        }
+    }
+}
+
+impl From<io::Error> for ConsistencyError {
+    fn from(e: io::Error) -> Self {
+        ConsistencyError::Io(e)
+    }
+}
+
+/// The compared state of a single player. Positions are 16.16 fixed-point
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerSnapshot {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub health: i32,
+}
+
+impl PlayerSnapshot {
+    /// `None` if the player has no map object
+    pub fn new(player: &Player) -> Option<Self> {
+        let to_fixed = |v: f32| (v as f64 * 65536.0) as i32;
+        let mobj = player.mobj()?;
+        Some(Self {
+            x: to_fixed(mobj.xyz.x),
+            y: to_fixed(mobj.xyz.y),
+            z: to_fixed(mobj.xyz.z),
+            health: player.status.health,
+        })
+    }
+}
+
+/// The state compared each tic
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TicSnapshot {
+    pub tic: u32,
+    /// Index of `p_random()` in to the random table
+    pub rng_index: usize,
+    pub mobj_count: usize,
+    /// `None` for players not in the game, or without a map object
+    pub players: [Option<PlayerSnapshot>; MAXPLAYERS],
+}
+
+impl TicSnapshot {
+    /// FNV-1a of the compared fields. The tic number isn't included
+    pub fn hash(&self) -> u32 {
+        let mut hash: u32 = 0x811C9DC5;
+        let mut add = |value: i32| {
+            for b in value.to_le_bytes() {
+                hash ^= b as u32;
+                hash = hash.wrapping_mul(0x01000193);
+            }
+        };
+        add(self.rng_index as i32);
+        add(self.mobj_count as i32);
+        for player in self.players.iter() {
+            match player {
+                Some(p) => {
+                    add(p.x);
+                    add(p.y);
+                    add(p.z);
+                    add(p.health);
+                }
+                None => add(-1),
+            }
+        }
+        hash
+    }
+
+    /// The hash folded to the 16 bits of `TicCmd::consistancy`
+    pub fn consistancy(&self) -> i16 {
+        let hash = self.hash();
+        ((hash >> 16) ^ hash) as i16
+    }
+
+    /// Describe each field that differs from `expected`
+    pub fn diff(&self, expected: &TicSnapshot) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.rng_index != expected.rng_index {
+            diffs.push(format!(
+                "rng index: expected {}, got {}",
+                expected.rng_index, self.rng_index
+            ));
+        }
+        if self.mobj_count != expected.mobj_count {
+            diffs.push(format!(
+                "mobj count: expected {}, got {}",
+                expected.mobj_count, self.mobj_count
+            ));
+        }
+        for (i, (got, want)) in self.players.iter().zip(expected.players.iter()).enumerate() {
+            match (got, want) {
+                (Some(got), Some(want)) => {
+                    for (name, g, w) in [
+                        ("x", got.x, want.x),
+                        ("y", got.y, want.y),
+                        ("z", got.z, want.z),
+                        ("health", got.health, want.health),
+                    ] {
+                        if g != w {
+                            diffs.push(format!("player {i} {name}: expected {w}, got {g}"));
+                        }
+                    }
+                }
+                (None, Some(_)) => diffs.push(format!("player {i}: expected in game, got none")),
+                (Some(_), None) => diffs.push(format!("player {i}: expected none, got in game")),
+                (None, None) => {}
+            }
+        }
+        diffs
+    }
+
+    /// Format as a dump line, without the newline
+    fn to_line(self) -> String {
+        let mut line = format!(
+            "{} {:08X} rng={} mobjs={}",
+            self.tic,
+            self.hash(),
+            self.rng_index,
+            self.mobj_count
+        );
+        for (i, player) in self.players.iter().enumerate() {
+            match player {
+                Some(p) => write!(line, " p{i}={},{},{},{}", p.x, p.y, p.z, p.health),
+                None => write!(line, " p{i}=-"),
+            }
+            .ok();
+        }
+        line
+    }
+
+    /// Read a line written by `to_line`
+    fn from_line(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let tic = parts.next()?.parse().ok()?;
+        let _hash = parts.next()?;
+        let rng_index = parts.next()?.strip_prefix("rng=")?.parse().ok()?;
+        let mobj_count = parts.next()?.strip_prefix("mobjs=")?.parse().ok()?;
+        let mut players = [None; MAXPLAYERS];
+        for (i, player) in players.iter_mut().enumerate() {
+            let value = parts.next()?.strip_prefix(&format!("p{i}="))?;
+            if value == "-" {
+                continue;
+            }
+            let mut v = value.split(',').map(|n| n.parse::<i32>().ok());
+            *player = Some(PlayerSnapshot {
+                x: v.next()??,
+                y: v.next()??,
+                z: v.next()??,
+                health: v.next()??,
+            });
+        }
+        Some(Self {
+            tic,
+            rng_index,
+            mobj_count,
+            players,
+        })
+    }
+}
+
+/// Dumps snapshots to a file and/or checks them against an earlier dump
+#[derive(Default)]
+pub struct ConsistencyLog {
+    dump: Option<BufWriter<File>>,
+    /// Snapshots from an earlier run, checked against in order
+    reference: Vec<TicSnapshot>,
+    next_reference: usize,
+    /// Set when the first divergence has been reported
+    diverged: bool,
+}
+
+impl ConsistencyLog {
+    /// Write every snapshot to `path`
+    pub fn dump_to(&mut self, path: &Path) -> Result<(), ConsistencyError> {
+        self.dump = Some(BufWriter::new(File::create(path)?));
+        Ok(())
+    }
+
+    /// Check every snapshot against those in the dump at `path`
+    pub fn check_against(&mut self, path: &Path) -> Result<(), ConsistencyError> {
+        let data = fs::read_to_string(path)?;
+        self.reference = data
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| TicSnapshot::from_line(l).ok_or(ConsistencyError::Parse(i + 1)))
+            .collect::<Result<_, _>>()?;
+        self.next_reference = 0;
+        self.diverged = false;
+        Ok(())
+    }
+
+    /// Dump and check a snapshot. Returns the differences if this is the first
+    /// snapshot that doesn't match the reference.
+    pub fn record(&mut self, snapshot: &TicSnapshot) -> Option<Vec<String>> {
+        if let Some(dump) = self.dump.as_mut() {
+            if let Err(e) = writeln!(dump, "{}", snapshot.to_line()) {
+                self.dump = None;
+                error!("Stopped consistency dump: {e}");
+            }
+        }
+
+        if self.diverged {
+            return None;
+        }
+        // Find the reference for this tic, skipping any the reference has that
+        // this run doesn't
+        while let Some(expected) = self.reference.get(self.next_reference) {
+            if expected.tic > snapshot.tic {
+                return None;
+            }
+            self.next_reference += 1;
+            if expected.tic == snapshot.tic {
+                if expected.hash() == snapshot.hash() {
+                    return None;
+                }
+                self.diverged = true;
+                return Some(snapshot.diff(expected));
+            }
+        }
+        None
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(dump) = self.dump.as_mut() {
+            dump.flush().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> TicSnapshot {
+        TicSnapshot {
+            tic: 12,
+            rng_index: 34,
+            mobj_count: 120,
+            players: [
+                Some(PlayerSnapshot {
+                    x: 1024 << 16,
+                    y: -(3 << 15),
+                    z: 0,
+                    health: 100,
+                }),
+                None,
+                None,
+                None,
+            ],
+        }
+    }
+
+    #[test]
+    fn line_round_trip() {
+        let snap = snapshot();
+        let line = snap.to_line();
+        assert!(line.starts_with(&format!("12 {:08X} rng=34 mobjs=120 p0=", snap.hash())));
+        assert_eq!(TicSnapshot::from_line(&line), Some(snap));
+        assert_eq!(TicSnapshot::from_line("12 0 rng=x"), None);
+    }
+
+    #[test]
+    fn divergence() {
+        let expected = snapshot();
+        let mut got = expected;
+        assert_eq!(got.hash(), expected.hash());
+        assert!(got.diff(&expected).is_empty());
+
+        got.rng_index = 35;
+        got.players[0].as_mut().unwrap().health = 90;
+        assert_ne!(got.hash(), expected.hash());
+        assert_eq!(
+            got.diff(&expected),
+            vec![
+                "rng index: expected 34, got 35".to_string(),
+                "player 0 health: expected 100, got 90".to_string(),
+            ]
+        );
+
+        let mut log = ConsistencyLog {
+            reference: vec![expected],
+            ..Default::default()
+        };
+        assert_eq!(log.record(&got).map(|d| d.len()), Some(2));
+        // Only the first divergence is reported
+        assert_eq!(log.record(&got), None);
+    }
+}
@@ -0,0 +1,429 @@
+//! Demo (`.lmp`) headers and tic cmds. Reads the vanilla v1.04-v1.10 and
+//! v1.91 (longtics) headers, the headerless pre-v1.4 demos, and the BOOM, MBF
+//! and PrBoom complevel headers. Recording writes v1.9 or longtics demos.
+//!
+//! Only the options the engine uses are taken from the BOOM style option
+//! block, the rest are skipped.
+//!
+//! Doom source name `g_game` (demo section)
+
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+
+use gameplay::tic_cmd::TicCmd;
+use gameplay::{Skill, MAXPLAYERS};
+
+use crate::{DEMO_MARKER, DEMO_VERSION, DEMO_VERSION_LONGTICS};
+
+/// Size of the option block in BOOM style headers
+const GAME_OPTION_SIZE: usize = 64;
+/// BOOM v2.00 pads the option block to this
+const BOOM_200_OPTION_SIZE: usize = 256;
+/// BOOM style headers always have this many player in-game flags
+const BOOM_MAXPLAYERS: usize = 32;
+
+/// Slowest demo playback speed multiplier
+pub const DEMO_SPEED_MIN: f32 = 0.25;
+/// Fastest demo playback speed multiplier
+pub const DEMO_SPEED_MAX: f32 = 16.0;
+
+/// Where to run demo playback to without rendering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemoSkip {
+    /// Until this many tics of the demo have been played
+    Tic(u32),
+    /// Until the next level is loaded
+    NextLevel,
+}
+
+#[derive(Debug)]
+pub enum DemoError {
+    /// The demo ended inside the header
+    Truncated,
+    /// The version byte isn't one that can be played
+    Version(u8),
+    /// The header has a skill level greater than nightmare
+    Skill(u8),
+    /// The recording player is out of range
+    ConsolePlayer(usize),
+}
+
+impl Error for DemoError {}
+
+impl fmt::Display for DemoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DemoError::Truncated => write!(f, "Demo ends before the end of its header"),
+            DemoError::Version(v) => write!(f, "Demo is from an unsupported version ({v})"),
+            DemoError::Skill(s) => write!(f, "Demo has an invalid skill ({s})"),
+            DemoError::ConsolePlayer(p) => write!(f, "Demo has an invalid console player ({p})"),
+        }
+    }
+}
+
+/// The game a demo was recorded with
+#[derive(Debug, Clone)]
+pub struct DemoHeader {
+    /// The version byte, or 0 for pre-v1.4 demos that have none
+    pub version: u8,
+    pub skill: Skill,
+    pub episode: usize,
+    pub map: usize,
+    pub deathmatch: u8,
+    pub respawn_parm: bool,
+    pub fast_parm: bool,
+    pub no_monsters: bool,
+    pub consoleplayer: usize,
+    pub players_in_game: [bool; MAXPLAYERS],
+    /// `angleturn` is recorded as two bytes instead of one
+    pub long_tics: bool,
+    /// Length of the header. The tic cmds start here
+    pub len: usize,
+}
+
+impl DemoHeader {
+    /// Read the header at the start of `data`
+    ///
+    /// Doom function name `G_DoPlayDemo`
+    pub fn read(data: &[u8]) -> Result<Self, DemoError> {
+        let byte = |i: usize| data.get(i).copied().ok_or(DemoError::Truncated);
+        let skill = |s: u8| {
+            if s > Skill::Nightmare as u8 {
+                return Err(DemoError::Skill(s));
+            }
+            Ok(Skill::from(s))
+        };
+        let players = |start: usize| -> Result<[bool; MAXPLAYERS], DemoError> {
+            let mut players = [false; MAXPLAYERS];
+            for (i, p) in players.iter_mut().enumerate() {
+                *p = byte(start + i)? != 0;
+            }
+            Ok(players)
+        };
+
+        let version = byte(0)?;
+        match version {
+            // Pre-v1.4 demos start straight in to skill, episode and map
+            0..=4 => Ok(Self {
+                version: 0,
+                skill: skill(version)?,
+                episode: byte(1)? as usize,
+                map: byte(2)? as usize,
+                deathmatch: 0,
+                respawn_parm: false,
+                fast_parm: false,
+                no_monsters: false,
+                consoleplayer: 0,
+                players_in_game: players(3)?,
+                long_tics: false,
+                len: 3 + MAXPLAYERS,
+            }),
+            104..=DEMO_VERSION_LONGTICS => Ok(Self {
+                version,
+                skill: skill(byte(1)?)?,
+                episode: byte(2)? as usize,
+                map: byte(3)? as usize,
+                deathmatch: byte(4)?,
+                respawn_parm: byte(5)? != 0,
+                fast_parm: byte(6)? != 0,
+                no_monsters: byte(7)? != 0,
+                consoleplayer: byte(8)? as usize,
+                players_in_game: players(9)?,
+                long_tics: version == DEMO_VERSION_LONGTICS,
+                len: 9 + MAXPLAYERS,
+            }),
+            // BOOM 2.00-2.02, MBF, PrBoom 2.1-2.5 (complevels 7-17)
+            200..=203 | 210..=214 => {
+                // 6 byte signature then a compatibility byte
+                let options = 13;
+                let option_size = if version == 200 {
+                    BOOM_200_OPTION_SIZE
+                } else {
+                    GAME_OPTION_SIZE
+                };
+                let players_start = options + option_size;
+                Ok(Self {
+                    version,
+                    skill: skill(byte(8)?)?,
+                    episode: byte(9)? as usize,
+                    map: byte(10)? as usize,
+                    deathmatch: byte(11)?,
+                    consoleplayer: byte(12)? as usize,
+                    respawn_parm: byte(options + 6)? != 0,
+                    fast_parm: byte(options + 7)? != 0,
+                    no_monsters: byte(options + 8)? != 0,
+                    players_in_game: players(players_start)?,
+                    long_tics: version == 214,
+                    len: players_start + BOOM_MAXPLAYERS,
+                })
+            }
+            v => Err(DemoError::Version(v)),
+        }
+        .and_then(|header| {
+            // Make sure the tic cmds can start where the header says
+            byte(header.len - 1)?;
+            if header.consoleplayer >= MAXPLAYERS {
+                return Err(DemoError::ConsolePlayer(header.consoleplayer));
+            }
+            Ok(header)
+        })
+    }
+
+    /// Append the header to a demo being recorded. Only the v1.9 and longtics
+    /// versions can be written.
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.push(if self.long_tics {
+            DEMO_VERSION_LONGTICS
+        } else {
+            DEMO_VERSION
+        });
+        buf.push(self.skill as u8);
+        buf.push(self.episode as u8);
+        buf.push(self.map as u8);
+        buf.push(self.deathmatch);
+        buf.push(self.respawn_parm as u8);
+        buf.push(self.fast_parm as u8);
+        buf.push(self.no_monsters as u8);
+        buf.push(self.consoleplayer as u8);
+        for in_game in self.players_in_game {
+            buf.push(in_game as u8);
+        }
+    }
+
+    /// Doom v1.9, the version demos are recorded as without longtics
+    pub fn is_vanilla(&self) -> bool {
+        self.version == DEMO_VERSION
+    }
+
+    /// How many players the demo has tic cmds for
+    pub fn player_count(&self) -> usize {
+        self.players_in_game.iter().filter(|p| **p).count()
+    }
+
+    /// How many tics a demo of `data_len` bytes with this header runs for
+    pub fn tic_count(&self, data_len: usize) -> u32 {
+        let cmd_size = if self.long_tics { 5 } else { 4 };
+        let tic_size = cmd_size * self.player_count().max(1);
+        // The cmds are followed by the end marker
+        (data_len.saturating_sub(self.len + 1) / tic_size) as u32
+    }
+}
+
+/// Append `cmd` to a demo being recorded, then change it to what playback
+/// will read back
+pub fn write_tic_cmd(buf: &mut Vec<u8>, cmd: &mut TicCmd, long_tics: bool) {
+    buf.push(cmd.forwardmove as u8);
+    buf.push(cmd.sidemove as u8);
+    if long_tics {
+        buf.extend_from_slice(&cmd.angleturn.to_le_bytes());
+    } else {
+        let turn = ((cmd.angleturn as i32 + 128) >> 8) as u8;
+        buf.push(turn);
+        cmd.angleturn = (turn as i8 as i16) << 8;
+    }
+    buf.push(cmd.buttons);
+    cmd.pitch = 0;
+    cmd.actions = 0;
+}
+
+/// Read the next tic cmd of a demo in to `cmd`. Returns `false` without
+/// touching `cmd` at the end marker or the end of the data.
+pub fn read_tic_cmd(
+    demo: &mut Peekable<impl Iterator<Item = u8>>,
+    cmd: &mut TicCmd,
+    long_tics: bool,
+) -> bool {
+    if demo.peek().is_none_or(|byte| *byte == DEMO_MARKER) {
+        return false;
+    }
+
+    if let Some(byte) = demo.next() {
+        cmd.forwardmove = byte as i8;
+    }
+    if let Some(byte) = demo.next() {
+        cmd.sidemove = byte as i8;
+    }
+    if long_tics {
+        let lo = demo.next().unwrap_or_default();
+        let hi = demo.next().unwrap_or_default();
+        cmd.angleturn = i16::from_le_bytes([lo, hi]);
+    } else if let Some(byte) = demo.next() {
+        cmd.angleturn = (byte as i16) << 8;
+    }
+    if let Some(byte) = demo.next() {
+        cmd.buttons = byte;
+    }
+    cmd.pitch = 0;
+    cmd.actions = 0;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vanilla_header() {
+        let data = [109, 3, 1, 2, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0x80];
+        let header = DemoHeader::read(&data).unwrap();
+        assert!(header.is_vanilla());
+        assert_eq!(header.skill, Skill::Hard);
+        assert_eq!((header.episode, header.map), (1, 2));
+        assert!(header.fast_parm);
+        assert_eq!(header.players_in_game, [true, true, false, false]);
+        assert_eq!(header.player_count(), 2);
+        assert!(!header.long_tics);
+        assert_eq!(header.len, 13);
+        // Two tics for two players then the marker
+        assert_eq!(header.tic_count(13 + 2 * 2 * 4 + 1), 2);
+    }
+
+    #[test]
+    fn old_and_longtics_headers() {
+        let header = DemoHeader::read(&[2, 1, 3, 1, 0, 0, 0]).unwrap();
+        assert_eq!(header.version, 0);
+        assert_eq!(header.skill, Skill::Medium);
+        assert_eq!(header.len, 7);
+
+        let data = [111, 4, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+        assert!(DemoHeader::read(&data).unwrap().long_tics);
+    }
+
+    #[test]
+    fn boom_header() {
+        let mut data = vec![203, 0x1d, b'M', b'B', b'F', 0xe6, 0, 0, 2, 1, 7, 0, 0];
+        let mut options = [0; GAME_OPTION_SIZE];
+        options[8] = 1; // nomonsters
+        data.extend_from_slice(&options);
+        let mut players = [0; BOOM_MAXPLAYERS];
+        players[0] = 1;
+        data.extend_from_slice(&players);
+
+        let header = DemoHeader::read(&data).unwrap();
+        assert_eq!(header.map, 7);
+        assert!(header.no_monsters);
+        assert_eq!(header.players_in_game, [true, false, false, false]);
+        assert_eq!(header.len, data.len());
+    }
+
+    #[test]
+    fn bad_headers() {
+        assert!(matches!(
+            DemoHeader::read(&[150, 0, 0]),
+            Err(DemoError::Version(150))
+        ));
+        assert!(matches!(
+            DemoHeader::read(&[109, 2, 1]),
+            Err(DemoError::Truncated)
+        ));
+        assert!(matches!(
+            DemoHeader::read(&[109, 9, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0]),
+            Err(DemoError::Skill(9))
+        ));
+        assert!(matches!(
+            DemoHeader::read(&[109, 2, 1, 1, 0, 0, 0, 0, 4, 1, 0, 0, 0]),
+            Err(DemoError::ConsolePlayer(4))
+        ));
+    }
+
+    fn fields(cmd: &TicCmd) -> (i8, i8, i16, u8, i16, u8) {
+        (
+            cmd.forwardmove,
+            cmd.sidemove,
+            cmd.angleturn,
+            cmd.buttons,
+            cmd.pitch,
+            cmd.actions,
+        )
+    }
+
+    /// Record two tics of a two player game, returning the demo and the cmds
+    /// as changed by recording
+    fn record(long_tics: bool) -> (Vec<u8>, Vec<TicCmd>) {
+        let header = DemoHeader {
+            version: if long_tics {
+                DEMO_VERSION_LONGTICS
+            } else {
+                DEMO_VERSION
+            },
+            skill: Skill::Nightmare,
+            episode: 2,
+            map: 5,
+            deathmatch: 1,
+            respawn_parm: true,
+            fast_parm: false,
+            no_monsters: true,
+            consoleplayer: 1,
+            players_in_game: [true, true, false, false],
+            long_tics,
+            len: 9 + MAXPLAYERS,
+        };
+        let mut cmds = vec![TicCmd::default(); 4];
+        for (cmd, (fwd, side, turn, buttons)) in cmds.iter_mut().zip([
+            (50, -24, 0x1234, 3),
+            (-25, 40, -300, 0),
+            (0, 0, 127, 1),
+            // the turn byte is the same as the end marker
+            (0, 0, i16::MIN, 0),
+        ]) {
+            cmd.forwardmove = fwd;
+            cmd.sidemove = side;
+            cmd.angleturn = turn;
+            cmd.buttons = buttons;
+            cmd.pitch = 5;
+            cmd.actions = 1;
+        }
+
+        let mut demo = Vec::new();
+        header.write(&mut demo);
+        for cmd in cmds.iter_mut() {
+            write_tic_cmd(&mut demo, cmd, long_tics);
+        }
+        demo.push(DEMO_MARKER);
+        (demo, cmds)
+    }
+
+    fn play_back(demo: &[u8], recorded: &[TicCmd], long_tics: bool) {
+        let header = DemoHeader::read(demo).unwrap();
+        assert_eq!(header.long_tics, long_tics);
+        assert_eq!(header.skill, Skill::Nightmare);
+        assert_eq!((header.episode, header.map), (2, 5));
+        assert_eq!(header.deathmatch, 1);
+        assert!(header.respawn_parm && !header.fast_parm && header.no_monsters);
+        assert_eq!(header.consoleplayer, 1);
+        assert_eq!(header.players_in_game, [true, true, false, false]);
+        assert_eq!(header.tic_count(demo.len()), 2);
+
+        let mut cmds = demo[header.len..].iter().copied().peekable();
+        for expected in recorded {
+            let mut cmd = TicCmd::default();
+            assert!(read_tic_cmd(&mut cmds, &mut cmd, long_tics));
+            assert_eq!(fields(&cmd), fields(expected));
+        }
+        let mut cmd = TicCmd::default();
+        assert!(!read_tic_cmd(&mut cmds, &mut cmd, long_tics));
+        assert_eq!(cmds.next(), Some(DEMO_MARKER));
+    }
+
+    #[test]
+    fn record_and_play_back() {
+        let (demo, cmds) = record(false);
+        assert_eq!(demo.len(), 13 + 4 * 4 + 1);
+        // shorttics round the turn to the nearest 256
+        let turns: Vec<i16> = cmds.iter().map(|c| c.angleturn).collect();
+        assert_eq!(turns, [0x1200, -256, 0, i16::MIN]);
+        assert!(cmds.iter().all(|c| c.pitch == 0 && c.actions == 0));
+        play_back(&demo, &cmds, false);
+    }
+
+    #[test]
+    fn record_and_play_back_longtics() {
+        let (demo, cmds) = record(true);
+        assert_eq!(demo[0], DEMO_VERSION_LONGTICS);
+        assert_eq!(demo.len(), 13 + 4 * 5 + 1);
+        let turns: Vec<i16> = cmds.iter().map(|c| c.angleturn).collect();
+        assert_eq!(turns, [0x1234, -300, 127, i16::MIN]);
+        play_back(&demo, &cmds, true);
+    }
+}
@@ -1,6 +1,10 @@
+use std::fs;
+
 use crate::Game;
-use gameplay::{GameAction, GameMode, Skill, WorldEndPlayerInfo};
-use gamestate_traits::{GameTraits, PlayerStatus, WorldInfo};
+use gameplay::log::warn;
+use gameplay::save::{vanilla, SaveHeader, SaveReader};
+use gameplay::{GameAction, GameMode, Level, Player, Skill, WorldEndPlayerInfo};
+use gamestate_traits::{AutomapState, DemoStatus, GameState, GameTraits, PlayerStatus, WorldInfo};
 use sound_traits::{MusTrack, SfxName, SoundAction, EPISODE4_MUS};
 use wad::WadData;
 
@@ -25,12 +29,31 @@ impl GameTraits for Game {
         self.game_type.mode
     }
 
-    fn load_game(&mut self, _name: String) {
-        todo!()
+    fn load_game(&mut self, slot: usize) {
+        self.save_slot = slot;
+        self.pending_action = GameAction::LoadGame;
+    }
+
+    fn save_game(&mut self, name: String, slot: usize) {
+        self.save_slot = slot;
+        self.save_description = name;
+        self.pending_action = GameAction::SaveGame;
+    }
+
+    fn save_description(&self, slot: usize) -> Option<String> {
+        let (path, is_vanilla) = self.load_path(slot)?;
+        let data = fs::read(path).ok()?;
+        let mut save = SaveReader::new(&data);
+        let header = if is_vanilla {
+            vanilla::read_header(&mut save)
+        } else {
+            SaveHeader::read(&mut save)
+        };
+        header.ok().map(|h| h.description)
     }
 
-    fn save_game(&mut self, _name: String, _slot: usize) {
-        todo!()
+    fn can_save(&self) -> bool {
+        self.usergame && self.gamestate == GameState::Level && !self.demo.playback
     }
 
     fn toggle_pause_game(&mut self) {
@@ -69,6 +92,17 @@ impl GameTraits for Game {
             .unwrap();
     }
 
+    fn change_music_lump(&self, name: &str) -> bool {
+        if let Some(lump) = self.wad_data.get_lump(name) {
+            self.sound_cmd
+                .send(SoundAction::ChangeMusicData(lump.data.clone(), true))
+                .unwrap();
+            return true;
+        }
+        warn!("Music lump {name} is missing");
+        false
+    }
+
     /// Doom function name `G_WorldDone`
     fn level_done(&mut self) {
         self.pending_action = GameAction::WorldDone;
@@ -77,6 +111,19 @@ impl GameTraits for Game {
                 p.didsecret = true;
             }
         }
+        if let Some(info) = &self.world_info.last_info {
+            // An empty text clears the default text
+            if let Some(text) = info.inter_text(self.world_info.secret_exit) {
+                if !text.is_empty() || info.ends_game() == Some(true) {
+                    self.pending_action = GameAction::Victory;
+                }
+                return;
+            }
+            if info.ends_game() == Some(true) {
+                self.pending_action = GameAction::Victory;
+                return;
+            }
+        }
         if matches!(self.game_type.mode, GameMode::Commercial) {
             match self.world_info.last {
                 6 | 11 | 15 | 20 | 30 | 31 => {
@@ -116,7 +163,35 @@ impl GameTraits for Game {
             .map(|s| s.to_string())
     }
 
+    fn demo_status(&self) -> Option<DemoStatus> {
+        if !self.demo_playing() {
+            return None;
+        }
+        Some(DemoStatus {
+            tic: self.demo_tic(),
+            total_tics: self.demo_total_tics(),
+            paused: self.demo_paused(),
+            speed: self.demo_speed(),
+        })
+    }
+
     fn get_wad_data(&self) -> &WadData {
         &self.wad_data
     }
+
+    fn level(&self) -> Option<&Level> {
+        self.level.as_ref()
+    }
+
+    fn player(&self, num: usize) -> Option<&Player> {
+        self.players_in_game[num].then(|| &self.players[num])
+    }
+
+    fn console_player(&self) -> usize {
+        self.consoleplayer
+    }
+
+    fn set_automap(&mut self, state: AutomapState) {
+        self.automap = state;
+    }
 }
@@ -20,29 +20,42 @@
 //! only during the `GameState::Level` state, and draws to the buffer after the
 //! player view is drawn.
 
+pub mod consistency;
+pub mod demo;
 pub mod game_impl;
 pub mod subsystems;
 
+use crate::consistency::{ConsistencyError, ConsistencyLog, PlayerSnapshot, TicSnapshot};
+use crate::demo::{read_tic_cmd, write_tic_cmd, DemoHeader, DemoSkip, DEMO_SPEED_MAX, DEMO_SPEED_MIN};
 use crate::subsystems::GameSubsystem;
+use gameplay::bot::Bots;
+use gameplay::english::{GGSAVED, PLAYER_LEFT};
 use gameplay::log::{debug, error, info, trace, warn};
+use gameplay::save::{archive_level, unarchive_level, vanilla, SaveHeader, SaveOptions, SaveReader, SaveWriter};
 use gameplay::tic_cmd::{TicCmd, TIC_CMD_BUTTONS};
 use gameplay::{
-    m_clear_random, respawn_specials, spawn_specials, update_specials, GameAction, GameMission, GameMode, GameOptions, Level, MapObject, PicData, Player, PlayerState, Skill, MAXPLAYERS
+    respawn_specials, spawn_specials, update_specials, GameAction, GameMission, GameMode, GameOptions, Level, MapObject, ModernControls, PicData, Player, PlayerState, Random, Skill, MAXPLAYERS, TICRATE
 };
-use gamestate_traits::sdl2::AudioSubsystem;
-use gamestate_traits::{GameState, GameTraits, SubsystemTrait, WorldInfo};
-use sound_nosnd::SndServerTx;
+use gamestate_traits::{AutomapState, GameState, GameTraits, SubsystemTrait, WorldInfo};
+use net::{NetGame, NetSetup};
+use std::fs;
 use std::iter::Peekable;
+use std::path::{Path, PathBuf};
 use std::thread::JoinHandle;
 use std::time::Duration;
 use std::vec::IntoIter;
-// use sound_sdl2::SndServerTx;
-use sound_traits::{MusTrack, SoundAction, SoundServer, SoundServerTic};
+use sound_traits::{MusTrack, SfxName, SndServerTx, SoundAction, SoundServerTic};
 use wad::types::WadPatch;
+use wad::umapinfo::{map_name_to_num, MapInfo, UMapInfo};
 use wad::WadData;
 
 pub const DEMO_MARKER: u8 = 0x80;
-pub const BACKUPTICS: usize = 12;
+/// Demo version byte of Doom v1.9
+pub const DEMO_VERSION: u8 = 109;
+/// Demo version byte of Doom v1.91, which records `angleturn` at the full 16
+/// bits (longtics)
+pub const DEMO_VERSION_LONGTICS: u8 = 111;
+pub use net::BACKUPTICS;
 /// Description of the unregistered shareware release
 pub const DESC_SHAREWARE: &str = "DOOM Shareware";
 /// Description of registered shareware release
@@ -61,6 +74,30 @@ pub struct DemoData {
     sequence: i8,
     buffer: Peekable<IntoIter<u8>>,
     name: String,
+    /// Demo being recorded?
+    recording: bool,
+    /// Record `angleturn` at the full 16 bits
+    long_tics: bool,
+    /// The file the recording is written to when it ends
+    record_path: PathBuf,
+    /// The recording so far
+    record_buffer: Vec<u8>,
+    /// Playing a single demo from the command line, quit when it ends
+    single: bool,
+    /// The demo being played records `angleturn` at the full 16 bits
+    play_long_tics: bool,
+    /// Tics played of the current demo
+    tics: u32,
+    /// Length of the current demo in tics
+    total_tics: u32,
+    /// Playback is paused
+    paused: bool,
+    /// Run a single tic while paused
+    step: bool,
+    /// Playback speed multiplier
+    speed: f32,
+    /// Tics are being run without rendering until this is reached
+    skip: Option<DemoSkip>,
 }
 
 /// Details used for the demo screens (title, help, ordering)
@@ -120,6 +157,8 @@ pub struct Game {
     /// Contains the full wad file. Wads are tiny in terms of today's memory use
     /// so it doesn't hurt to store the full file in ram. May change later.
     pub wad_data: WadData,
+    /// Map definitions from any `UMAPINFO` lumps, empty if there are none
+    pub umapinfo: UMapInfo,
     /// gametic at level start
     level_start_tic: u32,
     /// The complete `Level` data encompassing the everything everywhere all at
@@ -128,13 +167,15 @@ pub struct Game {
 
     /// Data related to demo play and state
     pub demo: DemoData,
+    /// Per-tic state dumps and checks for finding desyncs
+    consistency: ConsistencyLog,
     /// The page currently shown during demo state
     pub page: PageData,
     /// Is the game running? Used as main loop control
     running: bool,
 
-    /// Showing automap?
-    automap: bool,
+    /// How the automap is shown, set by the automap subsystem
+    automap: AutomapState,
     /// only if started as net death
 
     /// player taking events and displaying
@@ -160,8 +201,6 @@ pub struct Game {
     /// The state is picked up in `d_main`.
     pub wipe_game_state: GameState,
 
-    /// If non-zero, exit the level after this number of minutes.
-    _time_limit: Option<i32>,
     /// Intermission and world/map end data, used to show map and world stats,
     /// and queue up the next map or episode.
     world_info: WorldInfo,
@@ -169,18 +208,68 @@ pub struct Game {
     pub netcmds: [[TicCmd; BACKUPTICS]; MAXPLAYERS],
     /// d_net.c
     _localcmds: [TicCmd; BACKUPTICS],
+    /// The peers of a network game
+    net: Option<NetGame>,
+    /// The tic last handed over by the network game
+    net_tic: u32,
+    /// Consistancy of the last `BACKUPTICS` tics run. Cmds are sent with the
+    /// value from `BACKUPTICS` tics earlier, so it can be checked when the tic
+    /// they are for is run.
+    consistancy_history: [i16; BACKUPTICS],
+    /// A consistency failure has been reported
+    net_desynced: bool,
+    /// Computer controlled players of a local game
+    bots: Bots,
     usergame: bool,
     pub paused: bool,
+    /// The random number generator while no level is loaded. The `Level` owns
+    /// it otherwise
+    random: Random,
 
     /// The options the game-exe exe was started with
     pub options: GameOptions,
+    /// `no_monsters`, `respawn_parm` and `fast_parm` as started with. Demos
+    /// set their own, so these are restored for each new game
+    start_parms: (bool, bool, bool),
+    /// `deathmatch` as started with, for games with bots
+    start_deathmatch: u8,
+    /// `controls` as started with. Demos are played and recorded without them
+    start_controls: ModernControls,
+    /// Where the save slots are stored
+    save_dir: PathBuf,
+    /// The slot to save to or load from on the next `SaveGame`/`LoadGame`
+    save_slot: usize,
+    /// The name to give the next save
+    save_description: String,
     /// Sound tx
     pub sound_cmd: SndServerTx,
     snd_thread: Option<JoinHandle<()>>,
 }
 
+/// Run a sound server on its own thread until it is sent
+/// `SoundAction::Shutdown`. The `Sender` and thread are given to `Game::new()`.
+pub fn spawn_sound_server<S, E>(mut server: S) -> Result<(SndServerTx, JoinHandle<()>), E>
+where
+    S: SoundServerTic<SfxName, usize, E> + Send + 'static,
+    E: std::error::Error,
+{
+    let tx = server.init()?;
+    let thread = std::thread::spawn(move || loop {
+        if !server.tic() {
+            break;
+        }
+    });
+    Ok((tx, thread))
+}
+
 impl Drop for Game {
     fn drop(&mut self) {
+        // Doom finishes a recording in `I_Quit`
+        self.end_demo_recording();
+        self.consistency.flush();
+        if let Some(net) = self.net.as_mut() {
+            net.quit();
+        }
         self.sound_cmd.send(SoundAction::Shutdown).unwrap();
         let thread = self.snd_thread.take();
         thread.unwrap().join().unwrap();
@@ -189,12 +278,17 @@ impl Drop for Game {
 }
 
 impl Game {
+    /// `snd_server` is a sound server started with `spawn_sound_server()`.
+    /// Without one the game runs silently, using the `sound_nosnd` server.
+    /// Tables generated at startup are kept in `cache_dir` if given.
     pub fn new(
         mut options: GameOptions,
         mut wad: WadData,
-        snd_ctx: AudioSubsystem,
+        snd_server: Option<(SndServerTx, JoinHandle<()>)>,
         sfx_vol: i32,
         mus_vol: i32,
+        save_dir: PathBuf,
+        cache_dir: Option<&Path>,
     ) -> Game {
         let game_type = GameType::identify_version(&wad);
 
@@ -284,30 +378,13 @@ impl Game {
 
         info!("Init playloop state.");
 
-        let snd_thread;
-        let snd_tx = match sound_sdl2::Snd::new(snd_ctx, &wad) {
-            Ok(mut s) => {
-                let tx = s.init().unwrap();
-                snd_thread = std::thread::spawn(move || loop {
-                    if !s.tic() {
-                        break;
-                    }
-                });
+        let (snd_tx, snd_thread) = match snd_server {
+            Some((tx, thread)) => {
                 tx.send(SoundAction::SfxVolume(sfx_vol)).unwrap();
                 tx.send(SoundAction::MusicVolume(mus_vol)).unwrap();
-                tx
-            }
-            Err(e) => {
-                warn!("Could not set up sound server: {e}");
-                let mut s = sound_nosnd::Snd::new(&wad).unwrap();
-                let tx = s.init().unwrap();
-                snd_thread = std::thread::spawn(move || loop {
-                    if !s.tic() {
-                        break;
-                    }
-                });
-                tx
+                (tx, thread)
             }
+            None => spawn_sound_server(sound_nosnd::Snd::new(&wad).unwrap()).unwrap(),
         };
 
         // TODO: D_CheckNetGame ();
@@ -321,18 +398,35 @@ impl Game {
 
         let lump = wad.get_lump("TITLEPIC").expect("TITLEPIC missing");
         let page_cache = WadPatch::from_lump(lump);
-        let pic_data = PicData::init(false, &wad);
+        let pic_data = PicData::init(false, &wad, cache_dir);
+        let umapinfo = wad.umapinfo();
 
+        // TODO: should be set in d_net.c
+        let consoleplayer = 0;
         Game {
             wad_data: wad,
+            umapinfo,
             level_start_tic: 0,
             level: None,
+            consistency: ConsistencyLog::default(),
             demo: DemoData {
                 playback: false,
                 buffer: Vec::new().into_iter().peekable(),
                 name: String::new(),
                 advance: false,
                 sequence: 0,
+                recording: false,
+                long_tics: false,
+                record_path: PathBuf::new(),
+                record_buffer: Vec::new(),
+                single: false,
+                play_long_tics: false,
+                tics: 0,
+                total_tics: 0,
+                paused: false,
+                step: false,
+                speed: 1.0,
+                skip: None,
             },
             page: PageData {
                 name: "TITLEPIC",
@@ -342,8 +436,8 @@ impl Game {
             pic_data,
             running: true,
 
-            automap: false,
-            consoleplayer: 0, // TODO: should be set in d_net.c
+            automap: AutomapState::Off,
+            consoleplayer,
 
             displayplayer: 0,
             players_in_game: [false, false, false, false],
@@ -363,15 +457,26 @@ impl Game {
             gamestate: GameState::ForceWipe,
             // Initial state is changed later, here doesn't matter
             wipe_game_state: GameState::DemoScreen,
-            _time_limit: None,
             world_info: WorldInfo::default(),
 
             netcmds: [[TicCmd::new(); BACKUPTICS]; MAXPLAYERS],
             _localcmds: [TicCmd::new(); BACKUPTICS],
+            net: None,
+            net_tic: 0,
+            consistancy_history: [0; BACKUPTICS],
+            net_desynced: false,
+            bots: Bots::new(&options, consoleplayer),
 
             usergame: false,
             paused: false,
+            random: Random::new(),
+            start_parms: (options.no_monsters, options.respawn_parm, options.fast_parm),
+            start_deathmatch: options.deathmatch,
+            start_controls: options.controls,
             options,
+            save_dir,
+            save_slot: 0,
+            save_description: String::new(),
             sound_cmd: snd_tx,
             snd_thread: Some(snd_thread),
         }
@@ -385,10 +490,19 @@ impl Game {
         self.running = run;
     }
 
+    pub fn automap(&self) -> AutomapState {
+        self.automap
+    }
+
     pub fn is_netgame(&self) -> bool {
         self.options.netgame
     }
 
+    /// Playing a network game with other peers
+    pub fn net_playing(&self) -> bool {
+        self.net.is_some()
+    }
+
     pub fn game_skill(&self) -> Skill {
         self.options.skill
     }
@@ -397,17 +511,54 @@ impl Game {
         self.game_type.mission
     }
 
+    /// The random number generator of the game, owned by the `Level` while one
+    /// is loaded
+    pub fn random_mut(&mut self) -> &mut Random {
+        match self.level.as_mut() {
+            Some(level) => &mut level.random,
+            None => &mut self.random,
+        }
+    }
+
+    /// Drop the level, keeping its random number generator for the next
+    fn unload_level(&mut self) {
+        if let Some(level) = self.level.take() {
+            self.random = level.random;
+        }
+    }
+
+    /// Undo the options a demo was played with
+    fn restore_start_parms(&mut self) {
+        let (no_monsters, respawn, fast) = self.start_parms;
+        self.options.no_monsters = no_monsters;
+        self.options.respawn_parm = respawn;
+        self.options.fast_parm = fast;
+        self.options.controls = self.start_controls;
+    }
+
     fn do_new_game(&mut self) {
         debug!("Entered do_new_game");
 
-        self.options.respawn_monsters = matches!(self.options.skill, Skill::Nightmare);
-        self.options.netgame = false;
-        self.options.deathmatch = 0;
-        self.players_in_game.fill(false);
-        self.players_in_game[self.consoleplayer] = true;
+        // A network game keeps the players and options it was started with
+        if self.net.is_none() {
+            self.restore_start_parms();
+            if self.demo.recording {
+                self.options.controls = ModernControls::VANILLA;
+            }
+            // Bots make a local game a netgame
+            let bots = !self.bots.is_empty();
+            self.options.netgame = bots;
+            self.options.deathmatch = if bots { self.start_deathmatch } else { 0 };
+            for (i, in_game) in self.players_in_game.iter_mut().enumerate() {
+                *in_game = i == self.consoleplayer || self.bots.controls(i);
+            }
+        }
 
         self.init_new();
         self.pending_action = GameAction::None;
+        if self.demo.recording && self.demo.record_buffer.is_empty() {
+            self.begin_recording();
+        }
     }
 
     fn init_new(&mut self) {
@@ -458,7 +609,7 @@ impl Game {
             self.options.map = 9;
         }
 
-        m_clear_random();
+        self.random_mut().clear();
 
         self.options.respawn_monsters =
             self.options.skill == Skill::Nightmare || self.options.respawn_parm;
@@ -488,7 +639,7 @@ impl Game {
 
         self.paused = false;
         self.demo.playback = false;
-        self.automap = false;
+        self.automap = AutomapState::Off;
         self.usergame = true; // will be set false if a demo
 
         info!("Begin new game!");
@@ -498,6 +649,9 @@ impl Game {
     /// Doom function name `G_DoLoadLevel`
     fn do_load_level(&mut self) {
         debug!("Entered do_load_level");
+        if self.demo.skip == Some(DemoSkip::NextLevel) {
+            self.demo.skip = None;
+        }
         if self.wipe_game_state == GameState::Level {
             self.wipe_game_state = GameState::ForceWipe;
         }
@@ -506,10 +660,8 @@ impl Game {
         for player in self.players.iter_mut() {
             if player.player_state == PlayerState::Dead {
                 player.player_state = PlayerState::Reborn;
-                for i in 0..player.frags.len() {
-                    player.frags[i] = 0;
-                }
             }
+            player.frags = [0; MAXPLAYERS];
             // Player setup from P_SetupLevel
             player.total_kills = 0;
             player.secrets_found = 0;
@@ -522,16 +674,10 @@ impl Game {
         self.pending_action = GameAction::None;
 
         // Verify and set the map number + name
-        let map_name = if self.game_type.mode == GameMode::Commercial {
-            if self.options.map < 10 {
-                format!("MAP0{}", self.options.map)
-            } else {
-                format!("MAP{}", self.options.map)
-            }
-        } else {
-            format!("E{}M{}", self.options.episode, self.options.map)
-        };
+        let map_name = self.map_name(self.options.episode, self.options.map);
+        let map_info = self.umapinfo.get(&map_name).cloned();
 
+        let random = *self.random_mut();
         let level = unsafe {
             Level::new_empty(
                 self.options.clone(),
@@ -539,6 +685,7 @@ impl Game {
                 self.sound_cmd.clone(),
                 &self.players_in_game,
                 &mut self.players,
+                random,
             )
         };
 
@@ -552,6 +699,7 @@ impl Game {
             level.load(
                 &map_name,
                 self.game_type.mode,
+                map_info.as_ref(),
                 &mut self.pic_data,
                 &self.wad_data,
             );
@@ -569,7 +717,24 @@ impl Game {
                     &self.players_in_game,
                 );
             }
+            // if deathmatch, randomly spawn the active players
+            if self.options.deathmatch != 0 {
+                for player in self.players.iter_mut() {
+                    player.clear_mobj();
+                }
+                for i in 0..MAXPLAYERS {
+                    if self.players_in_game[i] {
+                        MapObject::deathmatch_spawn_player(
+                            i,
+                            level,
+                            &mut self.players,
+                            &self.players_in_game,
+                        );
+                    }
+                }
+            }
             spawn_specials(level);
+            self.bots.load_level(level);
 
             debug!("Level: skill = {:?}", &level.options.skill);
             debug!("Level: episode = {}", &level.options.episode);
@@ -581,28 +746,170 @@ impl Game {
 
         // Player setup from P_SetupLevel
         self.world_info.maxfrags = 0;
-        self.world_info.partime = 180;
+        self.world_info.partime = map_info.as_ref().and_then(|i| i.par_time).unwrap_or(180);
         self.players[self.consoleplayer].viewz = 1.0;
         // TODO: remove after new-game-exe stuff done
 
-        self.change_music(MusTrack::None);
+        let music = map_info.as_ref().and_then(|i| i.music.as_deref());
+        if !music.is_some_and(|m| self.change_music_lump(m)) {
+            self.change_music(MusTrack::None);
+        }
+    }
+
+    /// The lump name of a map, `MAPxx` for commercial or `ExMy`
+    fn map_name(&self, episode: usize, map: usize) -> String {
+        if self.game_type.mode == GameMode::Commercial {
+            format!("MAP{map:0>2}")
+        } else {
+            format!("E{episode}M{map}")
+        }
     }
 
-    fn do_reborn(&mut self, _player_num: usize) {
-        info!("Player respawned");
-        self.pending_action = GameAction::LoadLevel;
-        // self.players[player_num].
-        // TODO: deathmatch spawns
+    /// The `UMAPINFO` entry of the map being played, if there is one
+    pub fn map_info(&self) -> Option<&MapInfo> {
+        self.umapinfo
+            .get(&self.map_name(self.options.episode, self.options.map))
+    }
+
+    /// Doom function name `G_DoReborn`
+    fn do_reborn(&mut self, player_num: usize) {
+        if !self.options.netgame {
+            // reload the level from scratch
+            info!("Player respawned");
+            self.pending_action = GameAction::LoadLevel;
+            return;
+        }
+        let Some(level) = self.level.as_mut() else {
+            return;
+        };
+        info!("Player {} respawned", player_num + 1);
+        self.players[player_num].disassociate_corpse();
+        if self.options.deathmatch != 0 {
+            MapObject::deathmatch_spawn_player(
+                player_num,
+                level,
+                &mut self.players,
+                &self.players_in_game,
+            );
+            return;
+        }
+        MapObject::coop_spawn_player(player_num, level, &mut self.players, &self.players_in_game);
+    }
+
+    /// The file a save slot is stored in
+    fn save_path(&self, slot: usize) -> PathBuf {
+        self.save_dir.join(format!("save{slot}.r4d"))
+    }
+
+    /// The file to load a save slot from, and if it is a vanilla save. A
+    /// vanilla `doomsav<slot>.dsg` copied in to the save dir is used if the
+    /// slot has no save of its own.
+    fn load_path(&self, slot: usize) -> Option<(PathBuf, bool)> {
+        let path = self.save_path(slot);
+        if path.exists() {
+            return Some((path, false));
+        }
+        [format!("doomsav{slot}.dsg"), format!("DOOMSAV{slot}.DSG")]
+            .into_iter()
+            .map(|name| self.save_dir.join(name))
+            .find(|path| path.exists())
+            .map(|path| (path, true))
     }
 
     /// G_DoLoadGame
     fn do_load_game(&mut self) {
-        todo!("do_load_game");
+        self.pending_action = GameAction::None;
+
+        let Some((path, is_vanilla)) = self.load_path(self.save_slot) else {
+            warn!("No save in slot {}", self.save_slot);
+            return;
+        };
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Could not read {path:?}: {e}");
+                return;
+            }
+        };
+        let mut save = SaveReader::new(&data);
+        let header = if is_vanilla {
+            vanilla::read_header(&mut save)
+        } else {
+            SaveHeader::read(&mut save)
+        };
+        let header = match header {
+            Ok(header) => header,
+            Err(e) => {
+                warn!("Could not load {path:?}: {e}");
+                return;
+            }
+        };
+
+        // Peers and demos can't follow a change of how the game plays
+        let locked = self.net.is_some() || self.demo.recording;
+        if let Some(options) = header.options {
+            if locked && options != SaveOptions::new(&self.options) {
+                warn!("Could not load {path:?}: it was saved with other game options");
+                return;
+            }
+            options.apply(&mut self.options);
+        } else if !locked {
+            // Playing a demo turned the modern controls off
+            self.options.controls = self.start_controls;
+        }
+        self.options.skill = header.skill;
+        self.options.episode = header.episode;
+        self.options.map = header.map;
+        self.players_in_game = header.players_in_game;
+
+        // Load the level as new, then replace everything in it with the save
+        self.init_new();
+        if let Some(level) = self.level.as_mut() {
+            let loaded = if is_vanilla {
+                vanilla::unarchive_level(&mut save, level, &self.wad_data, &self.pic_data)
+            } else {
+                unarchive_level(&mut save, level)
+            };
+            if let Err(e) = loaded {
+                error!("Could not load {path:?}, restarting the level: {e}");
+                for player in self.players.iter_mut() {
+                    player.player_state = PlayerState::Reborn;
+                }
+                self.do_load_level();
+                return;
+            }
+            self.level_start_tic = self.game_tic.saturating_sub(level.level_time);
+        }
+        info!("Loaded {:?} from {path:?}", header.description);
     }
 
     /// G_DoSaveGame
     fn do_save_game(&mut self) {
-        todo!("do_save_game");
+        self.pending_action = GameAction::None;
+
+        let Some(level) = self.level.as_ref() else {
+            return;
+        };
+        let mut save = SaveWriter::new();
+        SaveHeader {
+            description: self.save_description.clone(),
+            skill: self.options.skill,
+            episode: self.options.episode,
+            map: self.options.map,
+            players_in_game: self.players_in_game,
+            options: Some(SaveOptions::new(&self.options)),
+        }
+        .write(&mut save);
+        archive_level(&mut save, level);
+
+        let path = self.save_path(self.save_slot);
+        match fs::write(&path, save.into_inner()) {
+            Ok(_) => {
+                info!("Saved {:?} to {path:?}", self.save_description);
+                self.players[self.consoleplayer].message = Some(GGSAVED);
+            }
+            Err(e) => warn!("Could not save to {path:?}: {e}"),
+        }
     }
 
     pub fn start_title(&mut self) {
@@ -611,17 +918,48 @@ impl Game {
         self.advance_demo();
     }
 
+    /// Print how far the demo got, and how it went for each player
+    fn print_demo_summary(&self) {
+        let secs = self.demo.tics / TICRATE as u32;
+        println!(
+            "Demo {} ended after {} tics ({}:{:0>2})",
+            self.demo.name,
+            self.demo.tics,
+            secs / 60,
+            secs % 60
+        );
+        let max_kills = self
+            .level
+            .as_ref()
+            .map_or(self.world_info.maxkills, |l| l.total_level_kills);
+        for (i, player) in self.players.iter().enumerate() {
+            if self.players_in_game[i] {
+                println!(
+                    "  Player {}: kills {}/{max_kills}",
+                    i + 1,
+                    player.total_kills
+                );
+            }
+        }
+    }
+
     fn check_demo_status(&mut self) -> bool {
         if self.demo.playback {
+            if self.demo.single {
+                self.print_demo_summary();
+                self.set_running(false);
+            }
             self.demo.playback = false;
+            self.demo.paused = false;
+            self.demo.step = false;
+            self.demo.speed = 1.0;
+            self.demo.skip = None;
             self.options.netgame = false;
             self.options.deathmatch = 0;
             for p in self.players_in_game.iter_mut() {
                 *p = false;
             }
-            self.options.respawn_parm = false;
-            self.options.fast_parm = false;
-            self.options.no_monsters = false;
+            self.restore_start_parms();
             self.consoleplayer = 0;
 
             self.advance_demo();
@@ -633,27 +971,81 @@ impl Game {
 
     /// G_ReadDemoTicCmd
     fn read_demo_tic_cmd(&mut self, cmd: &mut TicCmd) {
-        if let Some(byte) = self.demo.buffer.peek() {
-            if *byte == DEMO_MARKER {
-                self.check_demo_status();
-                return;
-            }
-        } else {
+        if !read_tic_cmd(&mut self.demo.buffer, cmd, self.demo.play_long_tics) {
             self.check_demo_status();
-            return;
         }
+    }
 
-        if let Some(byte) = self.demo.buffer.next() {
-            cmd.forwardmove = byte as i8;
-        }
-        if let Some(byte) = self.demo.buffer.next() {
-            cmd.sidemove = byte as i8;
+    /// Start recording a demo to `name`, with `.lmp` appended if it doesn't
+    /// have it. Recording begins with the next new game and the file is
+    /// written when the game quits.
+    ///
+    /// Doom function name `G_RecordDemo`
+    pub fn record_demo(&mut self, name: &str, long_tics: bool) {
+        self.usergame = false;
+        self.demo.record_path = if name.to_lowercase().ends_with(".lmp") {
+            PathBuf::from(name)
+        } else {
+            PathBuf::from(format!("{name}.lmp"))
+        };
+        self.demo.long_tics = long_tics;
+        self.demo.record_buffer.clear();
+        self.demo.recording = true;
+    }
+
+    /// Write the demo header for the game just started
+    ///
+    /// Doom function name `G_BeginRecording`
+    fn begin_recording(&mut self) {
+        let header = DemoHeader {
+            version: if self.demo.long_tics {
+                DEMO_VERSION_LONGTICS
+            } else {
+                DEMO_VERSION
+            },
+            skill: self.options.skill,
+            episode: self.options.episode,
+            map: self.options.map,
+            deathmatch: self.options.deathmatch,
+            respawn_parm: self.options.respawn_parm,
+            fast_parm: self.options.fast_parm,
+            no_monsters: self.options.no_monsters,
+            consoleplayer: self.consoleplayer,
+            players_in_game: self.players_in_game,
+            long_tics: self.demo.long_tics,
+            len: 9 + MAXPLAYERS,
+        };
+        header.write(&mut self.demo.record_buffer);
+        info!("Recording demo to {:?}", self.demo.record_path);
+    }
+
+    /// Record `cmd`, then read it back so that the game plays exactly what was
+    /// recorded
+    ///
+    /// Doom function name `G_WriteDemoTiccmd`
+    fn write_demo_tic_cmd(&mut self, cmd: &mut TicCmd) {
+        write_tic_cmd(&mut self.demo.record_buffer, cmd, self.demo.long_tics);
+    }
+
+    /// Finish the demo being recorded, if any, and write it out
+    ///
+    /// Doom function name `G_CheckDemoStatus`
+    pub fn end_demo_recording(&mut self) {
+        if !self.demo.recording {
+            return;
         }
-        if let Some(byte) = self.demo.buffer.next() {
-            cmd.angleturn = (byte as i16) << 8;
+        self.demo.recording = false;
+        if self.demo.record_buffer.is_empty() {
+            warn!(
+                "Demo {:?} not written, no game was started",
+                self.demo.record_path
+            );
+            return;
         }
-        if let Some(byte) = self.demo.buffer.next() {
-            cmd.buttons = byte;
+        self.demo.record_buffer.push(DEMO_MARKER);
+        match fs::write(&self.demo.record_path, &self.demo.record_buffer) {
+            Ok(_) => info!("Demo {:?} recorded", self.demo.record_path),
+            Err(e) => warn!("Could not write demo {:?}: {e}", self.demo.record_path),
         }
     }
 
@@ -747,59 +1139,306 @@ impl Game {
         self.pending_action = GameAction::PlayDemo;
     }
 
-    /// G_DoPlayDemo
-    fn do_play_demo(&mut self) {
-        self.pending_action = GameAction::None;
+    /// Play a single demo from a file or lump, then quit when it ends
+    pub fn play_demo(&mut self, name: &str) {
+        self.demo.single = true;
+        self.defered_play_demo(name.into());
+    }
+
+    /// A demo is being played back
+    pub fn demo_playing(&self) -> bool {
+        self.demo.playback
+    }
 
-        if let Some(demo) = self.wad_data.get_lump(&self.demo.name) {
-            self.demo.buffer = demo.data.clone().into_iter().peekable();
+    /// A demo given with `-playdemo` is being played back, not an attract demo
+    pub fn single_demo_playing(&self) -> bool {
+        self.demo.playback && self.demo.single
+    }
+
+    /// Tics played of the current demo
+    pub fn demo_tic(&self) -> u32 {
+        self.demo.tics
+    }
+
+    /// Length of the current demo in tics
+    pub fn demo_total_tics(&self) -> u32 {
+        self.demo.total_tics
+    }
+
+    pub fn demo_paused(&self) -> bool {
+        self.demo.paused
+    }
 
-            if let Some(byte) = self.demo.buffer.next() {
-                if byte != 109 {
-                    self.pending_action = GameAction::None;
-                    return;
+    /// Pause or resume demo playback. Menus and input still run while paused
+    pub fn set_demo_paused(&mut self, paused: bool) {
+        self.demo.paused = paused;
+        self.demo.step = false;
+    }
+
+    pub fn toggle_demo_pause(&mut self) {
+        self.set_demo_paused(!self.demo.paused);
+    }
+
+    /// Pause demo playback if it isn't already, and run a single tic
+    pub fn demo_step(&mut self) {
+        self.demo.paused = true;
+        self.demo.step = true;
+    }
+
+    /// The playback speed multiplier. The caller runs tics at this rate
+    pub fn demo_speed(&self) -> f32 {
+        self.demo.speed
+    }
+
+    /// Set the playback speed multiplier, clamped to 0.25x-16x
+    pub fn set_demo_speed(&mut self, speed: f32) {
+        self.demo.speed = speed.clamp(DEMO_SPEED_MIN, DEMO_SPEED_MAX);
+    }
+
+    /// Run demo playback without rendering until `tic` tics have been played.
+    /// Skipping backwards restarts the demo.
+    pub fn demo_skip_to_tic(&mut self, tic: u32) {
+        if !self.demo.playback {
+            return;
+        }
+        if tic < self.demo.tics {
+            self.defered_play_demo(self.demo.name.clone());
+        }
+        self.demo.skip = Some(DemoSkip::Tic(tic.min(self.demo.total_tics)));
+    }
+
+    /// Run demo playback without rendering until the next level is loaded
+    pub fn demo_skip_to_next_level(&mut self) {
+        if self.demo.playback {
+            self.demo.skip = Some(DemoSkip::NextLevel);
+        }
+    }
+
+    /// Tics are being run without rendering for a skip. Drawing should be
+    /// skipped while this is true.
+    pub fn demo_skipping(&self) -> bool {
+        self.demo.skip.is_some()
+    }
+
+    /// Playback is paused and no single step is waiting. Takes the step.
+    fn demo_held(&mut self) -> bool {
+        if !self.demo.playback || !self.demo.paused || self.demo.skip.is_some() {
+            return false;
+        }
+        !std::mem::take(&mut self.demo.step)
+    }
+
+    /// Dump the consistency snapshot of every tic to `path`
+    pub fn dump_consistency(&mut self, path: &str) -> Result<(), ConsistencyError> {
+        self.consistency.dump_to(path.as_ref())
+    }
+
+    /// Check each tic against a dump from `dump_consistency` and report the
+    /// first tic that differs
+    pub fn check_consistency(&mut self, path: &str) -> Result<(), ConsistencyError> {
+        self.consistency.check_against(path.as_ref())
+    }
+
+    /// Start a network game with the setup of player 0. Every player joins
+    /// at the start of the first level.
+    ///
+    /// Doom function name `D_CheckNetGame`
+    pub fn start_netgame(&mut self, net: NetGame) {
+        let NetSetup {
+            skill,
+            episode,
+            map,
+            deathmatch,
+            no_monsters,
+            respawn,
+            fast,
+            players,
+            time_limit,
+            frag_limit,
+            free_look,
+            autoaim,
+            jump_crouch,
+            fixed_point,
+        } = net.setup();
+        self.options.netgame = true;
+        self.options.skill = Skill::from((skill as i8 as i32).clamp(-1, Skill::Nightmare as i32));
+        self.options.episode = episode.max(1) as usize;
+        self.options.map = map.max(1) as usize;
+        self.options.deathmatch = deathmatch;
+        self.options.no_monsters = no_monsters;
+        self.options.respawn_parm = respawn;
+        self.options.fast_parm = fast;
+        self.options.time_limit = (time_limit != 0).then_some(time_limit as u32);
+        self.options.frag_limit = (frag_limit != 0).then_some(frag_limit as i32);
+        self.options.controls = ModernControls {
+            free_look,
+            autoaim,
+            jump_crouch,
+        };
+        self.options.fixed_point = fixed_point;
+        self.consoleplayer = net.player();
+        self.displayplayer = net.player();
+        for (i, in_game) in self.players_in_game.iter_mut().enumerate() {
+            *in_game = i < players as usize;
+        }
+        info!(
+            "Network game as player {} of {}",
+            self.consoleplayer + 1,
+            players
+        );
+        self.net = Some(net);
+        self.pending_action = GameAction::NewGame;
+    }
+
+    /// Send the local cmd in `netcmds` for the next tic, if the other players
+    /// aren't too far behind. Called once per tic.
+    ///
+    /// Doom function name `NetUpdate`
+    pub fn net_build_tic(&mut self) {
+        let Some(net) = self.net.as_mut() else {
+            return;
+        };
+        let mut cmd = self.netcmds[self.consoleplayer][0];
+        cmd.consistancy = self.consistancy_history[net.maketic() as usize % BACKUPTICS];
+        net.make_tic(cmd.to_bytes());
+    }
+
+    /// Load the cmds of every player for the next tic in to `netcmds` if they
+    /// have all arrived. `ticker` should be run each time this returns true.
+    ///
+    /// Doom function name `TryRunTics`
+    pub fn net_tic_ready(&mut self) -> bool {
+        let Some(tic) = self.net.as_mut().and_then(|net| net.next_tic()) else {
+            return false;
+        };
+        for (i, cmd) in tic.cmds.iter().enumerate() {
+            match cmd {
+                Some(cmd) => self.netcmds[i][0] = TicCmd::from_bytes(*cmd),
+                None if self.players_in_game[i] => {
+                    self.players_in_game[i] = false;
+                    self.players[self.consoleplayer].message = Some(PLAYER_LEFT[i]);
                 }
+                None => {}
             }
+        }
+        self.net_tic = tic.tic;
+        true
+    }
 
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.skill = Skill::from(byte);
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.episode = byte as usize;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.map = byte as usize;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.deathmatch = byte;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.respawn_parm = byte == 1;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.fast_parm = byte == 1;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.options.no_monsters = byte == 1;
-            }
-            if let Some(byte) = self.demo.buffer.next() {
-                self.consoleplayer = byte as usize;
+    /// Load the cmds of the bots' players for the next tic in to `netcmds`.
+    /// Demos are played back with the cmds they recorded instead.
+    fn bot_tic_cmds(&mut self) {
+        if self.demo.playback || self.net.is_some() {
+            return;
+        }
+        let Some(level) = self.level.as_mut() else {
+            return;
+        };
+        for i in 0..MAXPLAYERS {
+            if self.players_in_game[i] && self.bots.controls(i) {
+                self.netcmds[i][0] = self.bots.tic_cmd(i, level);
             }
-            for player in self.players_in_game.iter_mut() {
-                if let Some(byte) = self.demo.buffer.next() {
-                    *player = byte == 1;
-                }
+        }
+    }
+
+    /// Snapshot this tic, dumping or checking it if asked to, and return the
+    /// value for `TicCmd::consistancy`. The tic is counted from the start of
+    /// the demo during playback.
+    ///
+    /// Doom function name `G_Ticker` (consistancy section)
+    fn consistency_tic(&mut self) -> i16 {
+        let Some(level) = self.level.as_ref() else {
+            return 0;
+        };
+        let mut players = [None; MAXPLAYERS];
+        for (i, p) in players.iter_mut().enumerate() {
+            if self.players_in_game[i] {
+                *p = PlayerSnapshot::new(&self.players[i]);
             }
-            if self.players_in_game[1] {
-                // TODO: netgame stuff
+        }
+        let snapshot = TicSnapshot {
+            tic: if self.demo.playback {
+                self.demo.tics
+            } else {
+                self.game_tic
+            },
+            rng_index: level.random.indexes().1,
+            mobj_count: level.thinkers.mobj_count(),
+            players,
+        };
+        if let Some(diffs) = self.consistency.record(&snapshot) {
+            error!("Desync at tic {}:", snapshot.tic);
+            for diff in diffs {
+                error!("  {diff}");
             }
+        }
+        snapshot.consistancy()
+    }
 
-            self.init_new();
-            self.usergame = false;
-            self.demo.playback = true;
-        } else {
+    /// The demo named `name`. A path to a file is used first, then a lump, then
+    /// `name.lmp`.
+    fn demo_data(&self, name: &str) -> Option<Vec<u8>> {
+        if let Ok(data) = fs::read(name) {
+            return Some(data);
+        }
+        if let Some(lump) = self.wad_data.get_lump(name) {
+            return Some(lump.data.clone());
+        }
+        fs::read(format!("{name}.lmp")).ok()
+    }
+
+    /// G_DoPlayDemo
+    fn do_play_demo(&mut self) {
+        self.pending_action = GameAction::None;
+
+        let Some(mut data) = self.demo_data(&self.demo.name) else {
             error!("Demo {} does not exist", self.demo.name);
-            self.pending_action = GameAction::None;
+            self.end_single_demo();
+            return;
+        };
+        let header = match DemoHeader::read(&data) {
+            Ok(header) => header,
+            Err(e) => {
+                error!("Can't play demo {}: {e}", self.demo.name);
+                self.end_single_demo();
+                return;
+            }
+        };
+        if !header.is_vanilla() && !self.options.fixed_point {
+            debug!(
+                "Demo {} is version {}, it may desync",
+                self.demo.name, header.version
+            );
+        }
+
+        self.options.skill = header.skill;
+        self.options.episode = header.episode;
+        self.options.map = header.map;
+        self.options.deathmatch = header.deathmatch;
+        self.options.respawn_parm = header.respawn_parm;
+        self.options.fast_parm = header.fast_parm;
+        self.options.no_monsters = header.no_monsters;
+        self.options.controls = ModernControls::VANILLA;
+        self.consoleplayer = header.consoleplayer;
+        self.players_in_game = header.players_in_game;
+        self.demo.play_long_tics = header.long_tics;
+        self.demo.tics = 0;
+        self.demo.total_tics = header.tic_count(data.len());
+        data.drain(..header.len);
+        self.demo.buffer = data.into_iter().peekable();
+        // A demo with more than one player is a net demo
+        self.options.netgame = header.player_count() > 1;
+
+        self.init_new();
+        self.usergame = false;
+        self.demo.playback = true;
+    }
+
+    /// Go to the title screen if a single demo couldn't be played
+    fn end_single_demo(&mut self) {
+        if self.demo.single {
+            self.demo.single = false;
+            self.start_title();
         }
     }
 
@@ -807,6 +1446,7 @@ impl Game {
     ///
     /// Doom function name `G_DoWorldDone`
     fn do_world_done(&mut self) {
+        self.options.episode = self.world_info.next_episode + 1;
         self.options.map = self.world_info.next + 1;
         self.do_load_level();
         self.gamestate = GameState::Level;
@@ -828,9 +1468,37 @@ impl Game {
 
         self.world_info.didsecret = self.players[self.consoleplayer].didsecret;
         self.world_info.episode = self.options.episode - 1;
+        self.world_info.next_episode = self.world_info.episode;
         self.world_info.last = self.options.map;
+        let secret_exit = self.level.as_ref().unwrap().secret_exit;
+        self.world_info.secret_exit = secret_exit;
+        self.world_info.last_info = self.map_info().cloned();
+        self.world_info.next_info = None;
+
+        let last_info = self.world_info.last_info.as_ref();
+        let ends_game = last_info.and_then(|i| i.ends_game());
+        if ends_game == Some(true) && last_info.is_some_and(|i| i.no_intermission) {
+            self.pending_action = GameAction::Victory;
+            return;
+        }
 
-        if !matches!(self.game_type.mode, GameMode::Commercial) {
+        // The next map set by UMAPINFO. The secret exit falls back to the
+        // normal exit if there is an entry
+        let umapinfo_next = last_info
+            .and_then(|i| {
+                if secret_exit {
+                    i.next_secret.as_deref().or(i.next.as_deref())
+                } else {
+                    i.next.as_deref()
+                }
+            })
+            .and_then(map_name_to_num)
+            .filter(|(episode, map)| *episode > 0 && *map > 0);
+
+        if !matches!(self.game_type.mode, GameMode::Commercial)
+            && ends_game.is_none()
+            && umapinfo_next.is_none()
+        {
             if self.options.map == 8 {
                 self.pending_action = GameAction::Victory;
                 return;
@@ -843,8 +1511,11 @@ impl Game {
         }
 
         // wminfo.next is 0 biased, unlike gamemap, which is just bloody confusing...
-        if matches!(self.game_type.mode, GameMode::Commercial) {
-            if self.level.as_ref().unwrap().secret_exit {
+        if let Some((episode, map)) = umapinfo_next {
+            self.world_info.next_episode = episode - 1;
+            self.world_info.next = map - 1;
+        } else if matches!(self.game_type.mode, GameMode::Commercial) {
+            if secret_exit {
                 if self.options.map == 15 {
                     self.world_info.next = 30;
                 } else if self.options.map == 31 {
@@ -855,7 +1526,7 @@ impl Game {
             } else {
                 self.world_info.next = self.options.map;
             }
-        } else if self.level.as_ref().unwrap().secret_exit {
+        } else if secret_exit {
             // go to secret level
             self.world_info.next = 8;
         } else if self.options.map == 9 {
@@ -874,6 +1545,11 @@ impl Game {
         self.world_info.maxitems = self.level.as_ref().unwrap().total_level_items;
         self.world_info.maxsecret = self.level.as_ref().unwrap().total_level_secrets;
         self.world_info.maxfrags = 0;
+        self.world_info.deathmatch = self.options.deathmatch;
+        self.world_info.netgame = self.options.netgame;
+        self.world_info.pnum = self.consoleplayer;
+        let next_name = self.map_name(self.world_info.next_episode + 1, self.world_info.next + 1);
+        self.world_info.next_info = self.umapinfo.get(&next_name).cloned();
 
         // TODO: par times
 
@@ -892,7 +1568,7 @@ impl Game {
                 .copy_from_slice(&self.players[i].frags);
         }
 
-        self.level = None; // Drop level data
+        self.unload_level();
         self.gamestate = GameState::Intermission;
     }
 
@@ -902,7 +1578,7 @@ impl Game {
         self.world_info.last = self.options.map;
 
         self.gamestate = GameState::Finale;
-        self.level = None; // drop the level
+        self.unload_level();
         self.pending_action = GameAction::None;
     }
 
@@ -912,12 +1588,13 @@ impl Game {
     /// change the game-exe state or cause an action through `GameAction`.
     ///
     /// Doom function name `G_Ticker`
-    pub fn ticker<I, S, H, F>(&mut self, machinations: &mut GameSubsystem<I, S, H, F>)
+    pub fn ticker<I, S, H, F, A>(&mut self, machinations: &mut GameSubsystem<I, S, H, F, A>)
     where
         I: SubsystemTrait,
         S: SubsystemTrait,
         H: SubsystemTrait,
         F: SubsystemTrait,
+        A: SubsystemTrait,
     {
         trace!("Entered ticker");
         // do player reborns if needed
@@ -935,6 +1612,7 @@ impl Game {
         }
 
         // do things to change the game-exe state
+        let action = self.pending_action;
         match self.pending_action {
             GameAction::LoadLevel => {
                 machinations.hud_msgs.init(self);
@@ -958,11 +1636,32 @@ impl Game {
             GameAction::WorldDone => self.do_world_done(),
             GameAction::Screenshot => todo!("M_ScreenShot(); gameaction = ga_nothing"),
         }
+        if matches!(
+            action,
+            GameAction::LoadLevel
+                | GameAction::NewGame
+                | GameAction::LoadGame
+                | GameAction::PlayDemo
+                | GameAction::WorldDone
+        ) {
+            // A new level, or none if it failed to load
+            self.automap = AutomapState::Off;
+            machinations.automap.init(self);
+        }
 
-        // TODO: get commands, check consistancy,
-        // and build new consistancy check
         // buf = (gametic / ticdup) % BACKUPTICS;
 
+        if self.demo_held() {
+            return;
+        }
+        if self.demo.playback {
+            self.demo.tics += 1;
+            if matches!(self.demo.skip, Some(DemoSkip::Tic(tic)) if self.demo.tics >= tic) {
+                self.demo.skip = None;
+            }
+        }
+        self.bot_tic_cmds();
+        let consistancy = self.consistency_tic();
         // Checks ticcmd consistency and turbo cheat
         for i in 0..MAXPLAYERS {
             if self.players_in_game[i] {
@@ -974,11 +1673,32 @@ impl Game {
                     self.read_demo_tic_cmd(&mut cmd);
                     self.players[i].cmd = cmd;
                 }
-                // if (demorecording)
-                //     TODO: G_WriteDemoTiccmd(cmd);
-                // TODO: Netgame stuff here
+                if self.demo.recording {
+                    let mut cmd = self.players[i].cmd;
+                    self.write_demo_tic_cmd(&mut cmd);
+                    self.players[i].cmd = cmd;
+                }
+                // Peers send the consistancy from `BACKUPTICS` tics before
+                if self.net.is_some()
+                    && !self.net_desynced
+                    && i != self.consoleplayer
+                    && self.net_tic >= BACKUPTICS as u32
+                    && self.players[i].cmd.consistancy
+                        != self.consistancy_history[self.net_tic as usize % BACKUPTICS]
+                {
+                    error!(
+                        "Consistency failure with player {} at tic {}",
+                        i + 1,
+                        self.net_tic
+                    );
+                    self.net_desynced = true;
+                }
+                self.players[i].cmd.consistancy = consistancy;
             }
         }
+        if self.net.is_some() {
+            self.consistancy_history[self.net_tic as usize % BACKUPTICS] = consistancy;
+        }
 
         // check for special buttons
         for i in 0..MAXPLAYERS {
@@ -1012,7 +1732,7 @@ impl Game {
                 // update statusbar information
                 machinations.statusbar.ticker(self);
                 // update the automap display info
-                // AM_Ticker();
+                machinations.automap.ticker(self);
                 // update the HUD statuses (things like timeout displayed messages)
                 machinations.hud_msgs.ticker(self);
             }
@@ -1053,6 +1773,7 @@ impl Game {
         // Only run thinkers if a level is loaded
 
         if let Some(ref mut level) = self.level {
+            level.game_tic = self.game_tic;
             for (i, player) in self.players.iter_mut().enumerate() {
                 if self.players_in_game[i] && !player.think(level) {
                     // TODO: what to do with dead player?
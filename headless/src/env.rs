@@ -0,0 +1,340 @@
+//! An environment for training agents, in the style of reinforcement learning
+//! libraries. An `Env` plays a single map: `reset()` starts it, and each
+//! `step()` runs one tic with the agent's `TicCmd` and returns an
+//! `Observation` of the player and what happened to them on that tic.
+//!
+//! Each `Env` owns its own `Game`, and with it the `Random` of its level, so
+//! several can be used side by side in one process or on separate threads.
+
+use gameplay::tic_cmd::TicCmd;
+use gameplay::{AmmoType, GameOptions, PlayerState, Skill, WeaponType};
+use gamestate_traits::GameTraits;
+
+use crate::Headless;
+
+/// What to play and how to observe it
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub iwad: String,
+    pub pwad: Vec<String>,
+    pub skill: Skill,
+    /// Episode and map, origin 1. The episode is ignored by Doom II
+    pub episode: usize,
+    pub map: usize,
+    pub no_monsters: bool,
+    /// Width and height of the rendered frame. No frame is rendered if `None`
+    pub frame_size: Option<(usize, usize)>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            iwad: "doom1.wad".into(),
+            pwad: Vec::new(),
+            skill: Skill::default(),
+            episode: 1,
+            map: 1,
+            no_monsters: false,
+            frame_size: Some((320, 200)),
+        }
+    }
+}
+
+/// Something that happened to the player during a step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// Health lost
+    DamageTaken(i32),
+    /// Armour lost
+    ArmourLost(i32),
+    /// Monsters killed
+    Kill(i32),
+    /// Items picked up that count towards the level total
+    ItemPickedUp(i32),
+    SecretFound(i32),
+    WeaponPickedUp(WeaponType),
+    Died,
+    /// The player left the map. The episode is over
+    LevelFinished,
+}
+
+/// The state of the player after a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// RGBA bytes of the rendered frame, row by row, if rendering
+    pub frame: Option<Vec<u8>>,
+    pub frame_width: usize,
+    pub frame_height: usize,
+    pub health: i32,
+    pub armour: i32,
+    /// 0 for none, 1 for green and 2 for blue armour
+    pub armour_type: i32,
+    pub ammo: [u32; AmmoType::NumAmmo as usize],
+    pub weapon: WeaponType,
+    pub weapons_owned: [bool; WeaponType::NumWeapons as usize],
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+    /// Totals of the map
+    pub max_kills: i32,
+    pub max_items: i32,
+    pub max_secrets: i32,
+    /// Position in map units, and facing angle in radians
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub angle: f32,
+    /// Tics since the map started
+    pub tic: u32,
+    /// The player was killed
+    pub dead: bool,
+    pub events: Vec<Event>,
+    /// The player died or left the map, and the env must be `reset()`
+    pub done: bool,
+}
+
+/// A single map played by one agent
+pub struct Env {
+    headless: Headless,
+    config: EnvConfig,
+    last: Observation,
+}
+
+impl Env {
+    pub fn new(config: EnvConfig) -> Self {
+        let options = GameOptions {
+            iwad: config.iwad.clone(),
+            pwad: config.pwad.clone(),
+            skill: config.skill,
+            episode: config.episode,
+            map: config.map,
+            no_monsters: config.no_monsters,
+            ..Default::default()
+        };
+        let headless = Headless::new(options, config.frame_size);
+        let (frame_width, frame_height) = config.frame_size.unwrap_or_default();
+        Self {
+            headless,
+            config,
+            last: Observation {
+                frame: None,
+                frame_width,
+                frame_height,
+                health: 0,
+                armour: 0,
+                armour_type: 0,
+                ammo: Default::default(),
+                weapon: WeaponType::Pistol,
+                weapons_owned: Default::default(),
+                kills: 0,
+                items: 0,
+                secrets: 0,
+                max_kills: 0,
+                max_items: 0,
+                max_secrets: 0,
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                angle: 0.0,
+                tic: 0,
+                dead: false,
+                events: Vec::new(),
+                done: true,
+            },
+        }
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// Start the map again with a new player
+    pub fn reset(&mut self) -> Observation {
+        let EnvConfig {
+            skill,
+            episode,
+            map,
+            ..
+        } = self.config;
+        self.headless
+            .game_mut()
+            .defered_init_new(skill, episode, map);
+        self.run(TicCmd::new());
+        self.last.events.clear();
+        self.last.done = false;
+        self.last.clone()
+    }
+
+    /// Run one tic with `cmd` as the player's input. Once the observation is
+    /// `done` the map must be `reset()`, further steps do nothing.
+    pub fn step(&mut self, cmd: TicCmd) -> Observation {
+        if self.last.done {
+            let mut obs = self.last.clone();
+            obs.events.clear();
+            return obs;
+        }
+        let prev = self.last.clone();
+        let finished = self.run(cmd);
+        let obs = &mut self.last;
+        obs.events = events(&prev, obs);
+        if finished {
+            obs.events.push(Event::LevelFinished);
+        }
+        obs.done = finished || obs.events.contains(&Event::Died);
+        obs.clone()
+    }
+
+    /// Run a tic and update the last observation. Returns true if the level
+    /// was finished.
+    fn run(&mut self, cmd: TicCmd) -> bool {
+        let finished = self.headless.run_tic(Some(cmd)).is_some();
+        self.observe();
+        finished
+    }
+
+    fn observe(&mut self) {
+        let obs = &mut self.last;
+        let game = self.headless.game();
+        let player = &game.players[game.consoleplayer];
+        let status = &player.status;
+
+        obs.armour = status.armorpoints;
+        obs.armour_type = status.armortype;
+        obs.ammo = status.ammo;
+        obs.weapon = status.readyweapon;
+        obs.weapons_owned = status.weaponowned;
+        obs.kills = player.total_kills;
+        obs.items = player.items_collected;
+        obs.secrets = player.secrets_found;
+        obs.dead = player.player_state == PlayerState::Dead;
+        if let Some(level) = game.level.as_ref() {
+            obs.max_kills = level.total_level_kills;
+            obs.max_items = level.total_level_items;
+            obs.max_secrets = level.total_level_secrets;
+            obs.tic = level.level_time;
+        }
+        // The player's `MapObject` is gone once the level is finished
+        if let Some(mobj) = player.mobj() {
+            obs.health = mobj.health;
+            obs.x = mobj.xyz.x;
+            obs.y = mobj.xyz.y;
+            obs.z = mobj.xyz.z;
+            obs.angle = mobj.angle.rad();
+        }
+
+        obs.frame = self
+            .headless
+            .frame()
+            .map(|frame| frame.read_pixels().to_vec());
+    }
+}
+
+/// What changed between two observations
+fn events(prev: &Observation, now: &Observation) -> Vec<Event> {
+    let mut events = Vec::new();
+    if now.health < prev.health {
+        events.push(Event::DamageTaken(prev.health - now.health));
+    }
+    if now.armour < prev.armour {
+        events.push(Event::ArmourLost(prev.armour - now.armour));
+    }
+    if now.kills > prev.kills {
+        events.push(Event::Kill(now.kills - prev.kills));
+    }
+    if now.items > prev.items {
+        events.push(Event::ItemPickedUp(now.items - prev.items));
+    }
+    if now.secrets > prev.secrets {
+        events.push(Event::SecretFound(now.secrets - prev.secrets));
+    }
+    for (i, owned) in now.weapons_owned.iter().enumerate() {
+        if *owned && !prev.weapons_owned[i] {
+            events.push(Event::WeaponPickedUp(WeaponType::from(i as u8)));
+        }
+    }
+    if now.dead && !prev.dead {
+        events.push(Event::Died);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{events, Env, EnvConfig, Event, Observation};
+    use gameplay::tic_cmd::TicCmd;
+    use gameplay::WeaponType;
+
+    fn observation() -> Observation {
+        Observation {
+            frame: None,
+            frame_width: 0,
+            frame_height: 0,
+            health: 100,
+            armour: 0,
+            armour_type: 0,
+            ammo: Default::default(),
+            weapon: WeaponType::Pistol,
+            weapons_owned: Default::default(),
+            kills: 0,
+            items: 0,
+            secrets: 0,
+            max_kills: 0,
+            max_items: 0,
+            max_secrets: 0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            angle: 0.0,
+            tic: 0,
+            dead: false,
+            events: Vec::new(),
+            done: false,
+        }
+    }
+
+    #[test]
+    fn events_between_observations() {
+        let prev = observation();
+        assert!(events(&prev, &prev).is_empty());
+
+        let mut now = observation();
+        now.health = 0;
+        now.kills = 2;
+        now.weapons_owned[WeaponType::Shotgun as usize] = true;
+        now.dead = true;
+        assert_eq!(
+            events(&prev, &now),
+            vec![
+                Event::DamageTaken(100),
+                Event::Kill(2),
+                Event::WeaponPickedUp(WeaponType::Shotgun),
+                Event::Died
+            ]
+        );
+    }
+
+    #[test]
+    fn envs_on_threads_are_independent() {
+        let run = || {
+            let mut env = Env::new(EnvConfig {
+                iwad: "../doom1.wad".into(),
+                frame_size: None,
+                ..Default::default()
+            });
+            env.reset();
+            let mut cmd = TicCmd::new();
+            cmd.forwardmove = 25;
+            cmd.angleturn = 256;
+            (0..350).map(|_| env.step(cmd)).collect::<Vec<_>>()
+        };
+        // The same inputs must give the same game on every thread, which
+        // they wouldn't if the envs shared any random state
+        let a = thread::spawn(run);
+        let b = thread::spawn(run);
+        let (a, b) = (a.join().unwrap(), b.join().unwrap());
+        assert_eq!(a, b);
+        assert_eq!(a, run());
+    }
+}
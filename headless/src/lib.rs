@@ -0,0 +1,345 @@
+//! Run the game without a window, input devices or audio. `Headless` owns a
+//! `Game` and its subsystems, runs one tic at a time with the cmd given by the
+//! caller, and can render each tic in to an in-memory `PixelBuffer`.
+//!
+//! Intermissions are skipped as soon as they start, and the stats of every
+//! finished level are kept as `LevelStats`. Sound goes to `sound_nosnd`.
+
+use std::fmt;
+
+use automap_doom::AutoMap;
+use finale_doom::Finale;
+use gameplay::tic_cmd::{TicCmd, TIC_CMD_BUTTONS};
+use gameplay::{GameOptions, PlayerState, TICRATE};
+use gamestate::subsystems::GameSubsystem;
+use gamestate::Game;
+use gamestate_traits::{AutomapState, GameState, GameTraits, Scancode, SubsystemTrait};
+use hud_doom::Messages;
+use intermission_doom::Intermission;
+use render_soft::SoftwareRenderer;
+use render_target::{PixelBuffer, PlayRenderer, RenderTarget};
+use statusbar_doom::Statusbar;
+use wad::WadData;
+
+pub mod env;
+pub mod script;
+
+/// Horizontal field of view of the rendered frames
+const FOV: f32 = 90.0;
+
+/// Kills, items and secrets of one player for a finished level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerStats {
+    pub player: usize,
+    pub kills: i32,
+    pub items: i32,
+    pub secrets: i32,
+    pub frags: i32,
+}
+
+/// The end-of-level stats of every player in the game
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelStats {
+    /// Episode and map, origin 1
+    pub episode: usize,
+    pub map: usize,
+    pub max_kills: i32,
+    pub max_items: i32,
+    pub max_secrets: i32,
+    /// Level time in tics
+    pub time: u32,
+    pub players: Vec<PlayerStats>,
+}
+
+impl fmt::Display for LevelStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.time / TICRATE as u32;
+        writeln!(
+            f,
+            "E{}M{} time {}:{:02} ({} tics)",
+            self.episode,
+            self.map,
+            secs / 60,
+            secs % 60,
+            self.time
+        )?;
+        for p in self.players.iter() {
+            writeln!(
+                f,
+                "  player {}: kills {}/{} items {}/{} secrets {}/{} frags {}",
+                p.player + 1,
+                p.kills,
+                self.max_kills,
+                p.items,
+                self.max_items,
+                p.secrets,
+                self.max_secrets,
+                p.frags
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A `Game` with no window or audio
+pub struct Headless {
+    /// Boxed as the `Level` keeps pointers in to the `Game`, so that a
+    /// `Headless` can be moved once a level is loaded
+    game: Box<Game>,
+    machines: GameSubsystem<Intermission, Statusbar, Messages, Finale, AutoMap>,
+    /// Only set up if frames are rendered
+    renderer: Option<(SoftwareRenderer, RenderTarget)>,
+    gamestate: GameState,
+    /// Stats of each level finished, in order
+    levels: Vec<LevelStats>,
+    /// Times the console player has died
+    deaths: u32,
+}
+
+impl Headless {
+    /// Load the IWAD and set up a game with `options`. If `render` is a
+    /// width and height every tic is drawn in to a buffer of that size.
+    pub fn new(options: GameOptions, render: Option<(usize, usize)>) -> Self {
+        let wad = WadData::new(options.iwad.clone().into());
+        let game = Box::new(Game::new(
+            options,
+            wad,
+            None,
+            0,
+            0,
+            std::env::temp_dir().join("room4doom-headless"),
+            None,
+        ));
+        let machines = GameSubsystem {
+            statusbar: Statusbar::new(game.game_type.mode, &game.wad_data),
+            automap: AutoMap::new(&game.wad_data),
+            intermission: Intermission::new(game.game_type.mode, &game.wad_data),
+            hud_msgs: Messages::new(&game.wad_data),
+            finale: Finale::new(&game.wad_data),
+        };
+        let renderer = render.map(|(width, height)| {
+            (
+                SoftwareRenderer::new(FOV.to_radians(), width, height, false),
+                RenderTarget::new(width, height),
+            )
+        });
+        Self {
+            gamestate: game.gamestate,
+            game,
+            machines,
+            renderer,
+            levels: Vec::new(),
+            deaths: 0,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    /// Stats of each level finished so far
+    pub fn levels(&self) -> &[LevelStats] {
+        &self.levels
+    }
+
+    pub fn deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    /// The console player's 16.16 fixed-point position and the `p_random()`
+    /// index, to check a demo against the original engine tic by tic
+    pub fn console_player_trace(&self) -> Option<(i32, i32, i32, usize)> {
+        let game = &*self.game;
+        let (x, y, z) = game.players[game.consoleplayer].mobj()?.fixed_pos();
+        let (_, prnd) = game.level.as_ref()?.random.indexes();
+        Some((x, y, z, prnd))
+    }
+
+    /// Play a demo lump or `.lmp` file. The game stops when it ends
+    pub fn play_demo(&mut self, name: &str) {
+        self.game.play_demo(name);
+    }
+
+    /// Run a single tic. `cmd` is used for the console player unless a demo
+    /// is playing. Returns the stats of the level if it was finished on this
+    /// tic.
+    pub fn run_tic(&mut self, cmd: Option<TicCmd>) -> Option<&LevelStats> {
+        let game = &mut *self.game;
+        if game.demo.advance {
+            game.do_advance_demo();
+        }
+        if let Some(cmd) = cmd {
+            game.netcmds[game.consoleplayer][0] = cmd;
+        }
+        // Skip the intermission, it would wait for a key press, or in a
+        // netgame for a player to press use
+        if game.gamestate == GameState::Intermission {
+            self.machines.intermission.responder(Scancode::Return, game);
+            game.netcmds[game.consoleplayer][0].buttons = if game.game_tic.is_multiple_of(2) {
+                TIC_CMD_BUTTONS.bt_use
+            } else {
+                0
+            };
+        }
+
+        let was_alive = game.players[game.consoleplayer].player_state != PlayerState::Dead;
+        game.ticker(&mut self.machines);
+        game.game_tic += 1;
+        if was_alive && game.players[game.consoleplayer].player_state == PlayerState::Dead {
+            self.deaths += 1;
+        }
+
+        if let Some((renderer, target)) = self.renderer.as_mut() {
+            draw(game, &mut self.machines, renderer, target);
+        }
+
+        let finished =
+            self.gamestate == GameState::Level && game.gamestate == GameState::Intermission;
+        self.gamestate = game.gamestate;
+        if finished {
+            self.levels.push(level_stats(game));
+            return self.levels.last();
+        }
+        None
+    }
+
+    /// The last frame drawn, if rendering
+    pub fn frame(&mut self) -> Option<&mut dyn PixelBuffer> {
+        self.renderer
+            .as_mut()
+            .map(|(_, target)| target.pixel_buffer())
+    }
+}
+
+fn level_stats(game: &Game) -> LevelStats {
+    let info = game.level_end_info();
+    let players = info
+        .plyr
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.inn)
+        .map(|(i, p)| PlayerStats {
+            player: i,
+            kills: p.total_kills,
+            items: p.items_collected,
+            secrets: p.secrets_found,
+            frags: game.players[i].frag_count(i),
+        })
+        .collect();
+    LevelStats {
+        episode: info.episode + 1,
+        map: info.last,
+        max_kills: info.maxkills,
+        max_items: info.maxitems,
+        max_secrets: info.maxsecret,
+        time: info.plyr[game.consoleplayer].level_time,
+        players,
+    }
+}
+
+/// Draw the console player's view or the current screen, without wipes or
+/// menus
+///
+/// Doom function name `D_Display`
+fn draw(
+    game: &mut Game,
+    machines: &mut GameSubsystem<Intermission, Statusbar, Messages, Finale, AutoMap>,
+    renderer: &mut SoftwareRenderer,
+    target: &mut RenderTarget,
+) {
+    match game.gamestate {
+        GameState::Level => {
+            if let Some(level) = game.level.as_ref() {
+                let player = &game.players[game.consoleplayer];
+                if player.mobj().is_some() && game.automap() != AutomapState::FullScreen {
+                    renderer.render_player_view(player, level, &mut game.pic_data, target);
+                }
+            }
+            machines.automap.draw(target.pixel_buffer());
+            machines.statusbar.draw(target.pixel_buffer());
+            machines.hud_msgs.draw(target.pixel_buffer());
+        }
+        GameState::Intermission => machines.intermission.draw(target.pixel_buffer()),
+        GameState::Finale => machines.finale.draw(target.pixel_buffer()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gameplay::GameOptions;
+    use wad::WadData;
+
+    use super::Headless;
+
+    /// Play each demo in the fixed-point mode and check the console player's
+    /// position and the `P_Random` index against a trace of the original
+    /// engine. The traces are `../demo-traces/demoN.txt` beside the IWAD, with
+    /// a `tic x y z prndindex` line for each tic to check, the position as raw
+    /// 16.16 values. Each line holds the values after that tic ran, counting
+    /// from 0 at the start of the demo. Chocolate Doom makes them by printing
+    /// `players[consoleplayer].mo->x/y/z` and `prndindex` at the end of
+    /// `G_Ticker`, see `demo-traces/make-traces.sh`. Lines starting with `#`
+    /// are skipped, as is a demo the IWAD doesn't have (`DEMO4` is only in The
+    /// Ultimate Doom). A demo in the IWAD without a trace fails.
+    #[test]
+    #[ignore = "the Chocolate Doom traces aren't in git, make them with demo-traces/make-traces.sh"]
+    fn demos_fixed_point_match_vanilla_traces() {
+        let wad = WadData::new("../doom1.wad".into());
+        for demo in ["demo1", "demo2", "demo3", "demo4"] {
+            if !wad.lump_exists(&demo.to_ascii_uppercase()) {
+                continue;
+            }
+            let path = format!("../demo-traces/{demo}.txt");
+            let trace = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("{path}: {e}, see demo-traces/make-traces.sh"));
+
+            let options = GameOptions {
+                iwad: "../doom1.wad".into(),
+                enable_demos: true,
+                fixed_point: true,
+                ..Default::default()
+            };
+            let mut headless = Headless::new(options, None);
+            headless.play_demo(demo);
+
+            let mut tic = 0;
+            let mut checked = 0;
+            for line in trace.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let values: Vec<i64> = line
+                    .split_whitespace()
+                    .map(|v| {
+                        v.parse()
+                            .unwrap_or_else(|_| panic!("{path}: bad line {line}"))
+                    })
+                    .collect();
+                let [at, x, y, z, prnd] = values[..] else {
+                    panic!("{path}: bad line {line}");
+                };
+
+                while tic <= at {
+                    assert!(headless.game().running(), "{demo} ended before tic {at}");
+                    headless.run_tic(None);
+                    tic += 1;
+                }
+                let (px, py, pz, pr) = headless
+                    .console_player_trace()
+                    .unwrap_or_else(|| panic!("{demo}: no player in a level at tic {at}"));
+                assert_eq!(
+                    (px as i64, py as i64, pz as i64, pr as i64),
+                    (x, y, z, prnd),
+                    "{demo} desynced at tic {at}"
+                );
+                checked += 1;
+            }
+            assert!(checked > 0, "{path} has no tics to check");
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Run the game without a window or audio, for CI and scripted regression
+//! checks. A new game is started on the selected skill/episode/map and driven
+//! by a script, or a demo is played. The stats of each finished level are
+//! printed.
+//!
+//! Exit codes:
+//! - 0: the run finished
+//! - 1: the game could not be started, or the script could not be read
+//! - 2: `--expect-exit` was given and no level was finished
+//! - 3: `--expect-alive` was given and the console player died
+
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::process::ExitCode;
+
+use argh::FromArgs;
+use gameplay::{log, GameOptions, Skill, MAXPLAYERS};
+use headless::script::Script;
+use headless::Headless;
+use render_target::PixelBuffer;
+
+/// Run room4doom without a window or audio
+#[derive(Debug, Clone, FromArgs)]
+struct CLIOptions {
+    /// verbose level: off, error, warn, info, debug
+    #[argh(option, short = 'v')]
+    verbose: Option<log::LevelFilter>,
+    /// path to game WAD
+    #[argh(option, short = 'i')]
+    iwad: String,
+    /// path to patch WAD
+    #[argh(option, short = 'p')]
+    pwad: Vec<String>,
+    /// set the game skill, 0-4 (0: easiest, 4: hardest)
+    #[argh(option, short = 's')]
+    skill: Option<Skill>,
+    /// select episode
+    #[argh(option, short = 'e')]
+    episode: Option<usize>,
+    /// select level in episode. If Doom II the episode is ignored
+    #[argh(option, short = 'm')]
+    map: Option<usize>,
+    /// disable monsters
+    #[argh(switch, short = 'n')]
+    no_monsters: bool,
+    /// fill this many other player slots with computer controlled players
+    #[argh(option)]
+    bots: Option<usize>,
+    /// set how well bots play, 0-4. Defaults to the game skill
+    #[argh(option)]
+    bot_skill: Option<Skill>,
+    /// move things with vanilla fixed-point math and BAM angles
+    #[argh(switch)]
+    fixed_point: bool,
+    /// play a demo from a .lmp file or a lump, and stop when it ends
+    #[argh(option)]
+    playdemo: Option<String>,
+    /// drive the console player with the tic cmds in this file, and stop
+    /// when it ends
+    #[argh(option)]
+    script: Option<String>,
+    /// stop after this many tics
+    #[argh(option)]
+    tics: Option<u32>,
+    /// render every tic in to a buffer of this width
+    #[argh(option)]
+    width: Option<usize>,
+    /// render every tic in to a buffer of this height
+    #[argh(option)]
+    height: Option<usize>,
+    /// write the last rendered frame to this file as a PPM image. Renders at
+    /// 320x200 unless --width/--height are given
+    #[argh(option)]
+    screenshot: Option<String>,
+    /// exit with code 2 if no level was finished
+    #[argh(switch)]
+    expect_exit: bool,
+    /// exit with code 3 if the console player died
+    #[argh(switch)]
+    expect_alive: bool,
+}
+
+impl From<&CLIOptions> for GameOptions {
+    fn from(g: &CLIOptions) -> Self {
+        GameOptions {
+            iwad: g.iwad.clone(),
+            pwad: g.pwad.clone(),
+            no_monsters: g.no_monsters,
+            skill: g.skill.unwrap_or_default(),
+            episode: g.episode.unwrap_or_default(),
+            map: g.map.unwrap_or_default(),
+            // Always start a game unless a demo is played
+            warp: g.playdemo.is_none(),
+            verbose: g.verbose.unwrap_or(log::LevelFilter::Warn),
+            enable_demos: g.playdemo.is_some(),
+            fixed_point: g.fixed_point,
+            bots: g.bots.unwrap_or_default().min(MAXPLAYERS - 1),
+            bot_skill: g.bot_skill.or(g.skill).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options: CLIOptions = argh::from_env();
+    env_logger::Builder::new()
+        .target(env_logger::Target::Stderr)
+        .filter(None, options.verbose.unwrap_or(log::LevelFilter::Warn))
+        .init();
+
+    match run(&options) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn run(options: &CLIOptions) -> Result<ExitCode, Box<dyn Error>> {
+    let mut script = match options.script.as_deref() {
+        Some(path) => Some(Script::parse(&fs::read_to_string(path)?)?),
+        None => None,
+    };
+    if script.is_none() && options.playdemo.is_none() && options.tics.is_none() {
+        return Err("nothing to run, give --script, --playdemo or --tics".into());
+    }
+    if !fs::metadata(&options.iwad).is_ok_and(|m| m.is_file()) {
+        return Err(format!("IWAD {} not found", options.iwad).into());
+    }
+
+    let render = if options.width.is_some() || options.height.is_some() {
+        Some((options.width.unwrap_or(320), options.height.unwrap_or(200)))
+    } else {
+        options.screenshot.as_ref().map(|_| (320, 200))
+    };
+    let mut headless = Headless::new(options.into(), render);
+    if let Some(name) = options.playdemo.as_deref() {
+        headless.play_demo(name);
+    }
+
+    let mut tics = 0;
+    while headless.game().running() && options.tics.is_none_or(|max| tics < max) {
+        let cmd = match script.as_mut() {
+            Some(script) => match script.next() {
+                Some(cmd) => Some(cmd),
+                None => break,
+            },
+            None => None,
+        };
+        if let Some(stats) = headless.run_tic(cmd) {
+            print!("{stats}");
+        }
+        tics += 1;
+    }
+    println!(
+        "Ran {tics} tics, finished {} levels, died {} times",
+        headless.levels().len(),
+        headless.deaths()
+    );
+
+    if let Some(path) = options.screenshot.as_deref() {
+        if let Some(frame) = headless.frame() {
+            write_ppm(path, frame)?;
+        }
+    }
+
+    Ok(if options.expect_exit && headless.levels().is_empty() {
+        ExitCode::from(2)
+    } else if options.expect_alive && headless.deaths() > 0 {
+        ExitCode::from(3)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Write the frame as a binary PPM, dropping the alpha channel
+fn write_ppm(path: &str, frame: &mut dyn PixelBuffer) -> Result<(), Box<dyn Error>> {
+    let (width, height) = (frame.size().width_usize(), frame.size().height_usize());
+    let mut file = fs::File::create(path)?;
+    write!(file, "P6\n{width} {height}\n255\n")?;
+    let rgb: Vec<u8> = frame
+        .read_pixels()
+        .chunks_exact(4)
+        .flat_map(|p| [p[0], p[1], p[2]])
+        .collect();
+    file.write_all(&rgb)?;
+    Ok(())
+}
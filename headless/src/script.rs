@@ -0,0 +1,166 @@
+//! Scripted input for a headless game. Each line is a number of tics followed
+//! by the cmd to repeat for those tics, for example:
+//!
+//! ```text
+//! # walk forward for a second, then open the door ahead
+//! 35 forward 50
+//! 1 use
+//! 70 forward 25 turn 320 attack
+//! 1 weapon 3
+//! ```
+//!
+//! `forward` and `side` are the move amounts of a `TicCmd` (50 is running),
+//! `turn` is the angle turn (1280 is a fast turn, positive is left), and
+//! `weapon` selects a weapon slot 1-8. Empty lines and lines starting with `#`
+//! are skipped.
+
+use std::error::Error;
+use std::fmt;
+
+use gameplay::tic_cmd::{TicCmd, TIC_CMD_BUTTONS};
+
+#[derive(Debug, PartialEq)]
+pub struct ScriptError {
+    /// Line number, origin 1
+    pub line: usize,
+    pub msg: String,
+}
+
+impl Error for ScriptError {}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "script line {}: {}", self.line, self.msg)
+    }
+}
+
+/// The cmds of a script, run in order
+#[derive(Default)]
+pub struct Script {
+    /// Each cmd and the tics to run it for
+    steps: Vec<(u32, TicCmd)>,
+    /// Index of the current step
+    step: usize,
+    /// Tics run of the current step
+    tic: u32,
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut steps = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let err = |msg: String| ScriptError { line: i + 1, msg };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let tics = words.next().unwrap_or_default();
+            let tics: u32 = tics
+                .parse()
+                .map_err(|_| err(format!("expected a tic count, found `{tics}`")))?;
+
+            let mut cmd = TicCmd::new();
+            while let Some(word) = words.next() {
+                let mut value = |min: i32, max: i32| -> Result<i32, ScriptError> {
+                    let v = words
+                        .next()
+                        .and_then(|v| v.parse::<i32>().ok())
+                        .ok_or_else(|| err(format!("`{word}` needs a number")))?;
+                    if !(min..=max).contains(&v) {
+                        return Err(err(format!("`{word}` must be {min} to {max}")));
+                    }
+                    Ok(v)
+                };
+                match word {
+                    "forward" => cmd.forwardmove = value(i8::MIN as i32, i8::MAX as i32)? as i8,
+                    "side" => cmd.sidemove = value(i8::MIN as i32, i8::MAX as i32)? as i8,
+                    "turn" => cmd.angleturn = value(i16::MIN as i32, i16::MAX as i32)? as i16,
+                    "weapon" => {
+                        let slot = value(1, 8)? as u8 - 1;
+                        cmd.buttons |=
+                            TIC_CMD_BUTTONS.bt_change | (slot << TIC_CMD_BUTTONS.bt_weaponshift);
+                    }
+                    "attack" => cmd.buttons |= TIC_CMD_BUTTONS.bt_attack,
+                    "use" => cmd.buttons |= TIC_CMD_BUTTONS.bt_use,
+                    _ => return Err(err(format!("unknown command `{word}`"))),
+                }
+            }
+            steps.push((tics, cmd));
+        }
+        Ok(Self {
+            steps,
+            ..Default::default()
+        })
+    }
+
+    /// Total number of tics in the script
+    pub fn len(&self) -> u32 {
+        self.steps.iter().map(|(tics, _)| tics).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Iterator for Script {
+    type Item = TicCmd;
+
+    fn next(&mut self) -> Option<TicCmd> {
+        loop {
+            let (tics, cmd) = self.steps.get(self.step)?;
+            if self.tic < *tics {
+                self.tic += 1;
+                return Some(*cmd);
+            }
+            self.step += 1;
+            self.tic = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Script, ScriptError};
+    use gameplay::tic_cmd::TIC_CMD_BUTTONS;
+
+    #[test]
+    fn parse_and_run() {
+        let script = Script::parse(
+            "# comment\n\n2 forward 50 turn -640\n0 use\n1 side -40 attack use\n1 weapon 3\n",
+        )
+        .unwrap();
+        assert_eq!(script.len(), 4);
+
+        let cmds: Vec<_> = script.collect();
+        assert_eq!(cmds.len(), 4);
+        assert_eq!(cmds[0].forwardmove, 50);
+        assert_eq!(cmds[1].angleturn, -640);
+        assert_eq!(cmds[2].sidemove, -40);
+        assert_eq!(
+            cmds[2].buttons,
+            TIC_CMD_BUTTONS.bt_attack | TIC_CMD_BUTTONS.bt_use
+        );
+        assert_eq!(
+            cmds[3].buttons,
+            TIC_CMD_BUTTONS.bt_change | (2 << TIC_CMD_BUTTONS.bt_weaponshift)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |text: &str| Script::parse(text).err().unwrap();
+        assert_eq!(
+            err("1 forward 50\njump"),
+            ScriptError {
+                line: 2,
+                msg: "expected a tic count, found `jump`".into()
+            }
+        );
+        assert_eq!(err("1 forward 200").msg, "`forward` must be -128 to 127");
+        assert_eq!(err("1 weapon").msg, "`weapon` needs a number");
+        assert_eq!(err("1 crouch").msg, "unknown command `crouch`");
+    }
+}
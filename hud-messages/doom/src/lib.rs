@@ -1,5 +1,5 @@
-use gamestate_traits::{GameTraits, PixelBuffer, Scancode, SubsystemTrait, TICRATE};
-use hud_util::{load_char_patches, HUDString, HUD_STRING};
+use gamestate_traits::{DemoStatus, GameTraits, PixelBuffer, Scancode, SubsystemTrait, TICRATE};
+use hud_util::{HUDFont, HUDString, HUD_STRING};
 use wad::types::WadPalette;
 use wad::WadData;
 
@@ -7,6 +7,7 @@ const COUNT_DOWN: i32 = 2 * TICRATE;
 
 pub struct Messages {
     palette: WadPalette,
+    font: HUDFont,
     screen_width: i32,
     screen_height: i32,
     lines: [HUDString; 4],
@@ -15,23 +16,54 @@ pub struct Messages {
     /// End of lines (wraps around)
     current: usize,
     count_down: i32,
+    /// Demo playback position, shown at the top right while a demo plays
+    demo_line: HUDString,
+}
+
+/// Tics as `m:ss`
+fn tics_to_time(tics: u32) -> String {
+    let secs = tics / TICRATE as u32;
+    format!("{}:{:0>2}", secs / 60, secs % 60)
 }
 
 impl Messages {
     pub fn new(wad: &WadData) -> Self {
-        // initialise
-        load_char_patches(wad);
         let palette = wad.playpal_iter().next().unwrap();
 
         Self {
             palette,
+            font: HUDFont::new(wad),
             screen_width: 0,
             screen_height: 0,
             lines: [HUD_STRING; 4],
             start: 0,
             current: 0,
             count_down: COUNT_DOWN,
+            demo_line: HUD_STRING,
+        }
+    }
+
+    /// Set the demo playback position to show, or `None` to hide it
+    pub fn set_demo_status(&mut self, status: Option<DemoStatus>) {
+        let Some(demo) = status else {
+            self.demo_line.clear();
+            return;
+        };
+        let percent = (demo.tic * 100).checked_div(demo.total_tics).unwrap_or(0);
+        let mut line = format!(
+            "{} / {} TIC {} ({percent}%)",
+            tics_to_time(demo.tic),
+            tics_to_time(demo.total_tics),
+            demo.tic
+        );
+        if demo.speed != 1.0 {
+            line.push_str(&format!(" {}X", demo.speed));
         }
+        if demo.paused {
+            line.push_str(" PAUSED");
+        }
+        self.demo_line.replace(line);
+        self.demo_line.set_draw_all();
     }
 
     pub fn add_line(&mut self, line: String) {
@@ -81,7 +113,7 @@ impl Messages {
                 continue;
             }
 
-            self.lines[pos].draw(x, y, machination, buffer);
+            self.lines[pos].draw(x, y, &self.font, machination, buffer);
             y += self.lines[pos].line_height() * f + 1;
 
             if pos == self.current {
@@ -132,6 +164,11 @@ impl SubsystemTrait for Messages {
         self.screen_width = buffer.size().width();
         self.screen_height = buffer.size().height();
         self.draw_wrapped(self, buffer);
+        if !self.demo_line.line().is_empty() {
+            let f = buffer.size().height() / 200;
+            let x = self.screen_width - (self.demo_line.width(&self.font) + 2) * f;
+            self.demo_line.draw(x, 2, &self.font, self, buffer);
+        }
     }
 }
 
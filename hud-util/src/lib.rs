@@ -1,5 +1,3 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-
 use gamestate_traits::{PixelBuffer, SubsystemTrait};
 use log::warn;
 use wad::types::{WadPatch, WAD_PATCH};
@@ -9,41 +7,39 @@ const FONT_START: u8 = b'!';
 const FONT_END: u8 = b'_';
 const FONT_COUNT: u8 = FONT_END - FONT_START + 1;
 
-static mut CHARS: [WadPatch; FONT_COUNT as usize] = [WAD_PATCH; FONT_COUNT as usize];
-static mut CHARS_INITIALISED: AtomicBool = AtomicBool::new(false);
+/// The patches of the HUD font, `!` to `_`. Each subsystem that draws a
+/// `HUDString` loads its own.
+pub struct HUDFont {
+    chars: [WadPatch; FONT_COUNT as usize],
+}
 
-pub fn load_char_patches(wad: &WadData) {
-    unsafe {
-        if CHARS_INITIALISED.load(Ordering::Relaxed) {
-            return;
-        }
-        for i in 0..FONT_COUNT {
-            let f = i + FONT_START;
+impl HUDFont {
+    /// Doom function name `HU_Init`
+    pub fn new(wad: &WadData) -> Self {
+        let mut chars = [WAD_PATCH; FONT_COUNT as usize];
+        for (i, patch) in chars.iter_mut().enumerate() {
+            let f = i as u8 + FONT_START;
             if let Some(lump) = wad.get_lump(&format!("STCFN{f:0>3}")) {
-                CHARS[i as usize] = WadPatch::from_lump(lump);
+                *patch = WadPatch::from_lump(lump);
             } else {
                 warn!("Missing STCFN{f:0>3}");
             }
         }
-        CHARS_INITIALISED.store(true, Ordering::Relaxed);
+        Self { chars }
     }
-}
 
-fn get_patch_for_char(c: char) -> Option<&'static WadPatch> {
-    unsafe {
-        if !CHARS_INITIALISED.load(Ordering::Relaxed) {
-            warn!("Character patches not initialised");
-            return None;
-        }
+    /// The patch for `c`, or `None` for a space or a character not in the font
+    pub fn get(&self, c: char) -> Option<&WadPatch> {
         if c == ' ' {
             return None;
         }
-        CHARS.get((c as u8 - FONT_START) as usize)
+        self.chars
+            .get((c as u32).checked_sub(FONT_START as u32)? as usize)
     }
 }
 
 /// Specifically to help create static arrays of `WadPatch`
-pub const HUD_STRING: HUDString = HUDString::default();
+pub const HUD_STRING: HUDString = HUDString::new();
 
 #[derive(Debug, Clone)]
 pub struct HUDString {
@@ -53,19 +49,14 @@ pub struct HUDString {
     space_width: i32,
 }
 
-impl HUDString {
-    const fn default() -> Self {
-        Self {
-            data: String::new(),
-            line_height: 10,
-            current_char: 0,
-            space_width: 4,
-        }
+impl Default for HUDString {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn new(wad: &WadData) -> Self {
-        load_char_patches(wad);
-
+impl HUDString {
+    pub const fn new() -> Self {
         Self {
             data: String::new(),
             line_height: 10,
@@ -82,13 +73,21 @@ impl HUDString {
         &self.data
     }
 
+    /// Width of the string in unscaled pixels, assuming a single line
+    pub fn width(&self, font: &HUDFont) -> i32 {
+        self.data
+            .chars()
+            .map(|c| font.get(c).map_or(self.space_width, |p| p.width as i32))
+            .sum()
+    }
+
     pub fn replace(&mut self, string: String) {
         self.data = string;
     }
 
-    pub fn add_char(&mut self, c: char) {
+    pub fn add_char(&mut self, c: char, font: &HUDFont) {
         self.data.push(c);
-        if let Some(p) = get_patch_for_char(c) {
+        if let Some(p) = font.get(c) {
             if p.height as i32 > self.line_height {
                 self.line_height = p.height as i32;
             }
@@ -118,6 +117,7 @@ impl HUDString {
         &self,
         mut x: i32,
         mut y: i32,
+        font: &HUDFont,
         machination: &impl SubsystemTrait,
         pixels: &mut dyn PixelBuffer,
     ) -> Option<()> {
@@ -156,7 +156,7 @@ impl HUDString {
                 continue;
             }
 
-            let patch = get_patch_for_char(ch).unwrap_or_else(|| panic!("Did not find {ch}"));
+            let patch = font.get(ch).unwrap_or_else(|| panic!("Did not find {ch}"));
             if y + self.line_height * f >= height {
                 warn!("HUD String to long for screen size");
                 return None;
@@ -177,40 +177,41 @@ impl HUDString {
         &self,
         x: i32,
         y: i32,
+        font: &HUDFont,
         machination: &impl SubsystemTrait,
         pixels: &mut dyn PixelBuffer,
     ) -> Option<()> {
-        self.draw_pixels(x, y, machination, pixels);
+        self.draw_pixels(x, y, font, machination, pixels);
         Some(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{get_patch_for_char, load_char_patches};
+    use crate::HUDFont;
     use wad::WadData;
 
     #[test]
     fn load_and_check_chars() {
         let wad = WadData::new("../doom1.wad".into());
-        load_char_patches(&wad);
+        let font = HUDFont::new(&wad);
 
-        let l = get_patch_for_char('!').unwrap();
+        let l = font.get('!').unwrap();
         assert_eq!(l.name.as_str(), "STCFN033");
 
-        let l = get_patch_for_char('$').unwrap();
+        let l = font.get('$').unwrap();
         assert_eq!(l.name.as_str(), "STCFN036");
 
-        let d = get_patch_for_char('D').unwrap();
+        let d = font.get('D').unwrap();
         assert_eq!(d.name.as_str(), "STCFN068");
-        let o = get_patch_for_char('O').unwrap();
+        let o = font.get('O').unwrap();
         assert_eq!(o.name.as_str(), "STCFN079");
-        let o = get_patch_for_char('O').unwrap();
+        let o = font.get('O').unwrap();
         assert_eq!(o.name.as_str(), "STCFN079");
-        let m = get_patch_for_char('M').unwrap();
+        let m = font.get('M').unwrap();
         assert_eq!(m.name.as_str(), "STCFN077");
 
-        let l = get_patch_for_char('_').unwrap();
+        let l = font.get('_').unwrap();
         assert_eq!(l.name.as_str(), "STCFN095");
     }
 }
@@ -14,6 +14,11 @@ pub struct InputConfig {
     pub(crate) key_use: i32,
     pub(crate) key_strafe: i32,
     pub(crate) key_speed: i32,
+    /// Added after the other keys, so configs written before have none
+    #[nserde(default = "Scancode::E as i32")]
+    pub(crate) key_jump: i32,
+    #[nserde(default = "Scancode::Q as i32")]
+    pub(crate) key_crouch: i32,
     pub(crate) mousebfire: u8,
     pub(crate) mousebstrafe: u8,
     pub(crate) mousebforward: u8,
@@ -33,6 +38,8 @@ impl Default for InputConfig {
             key_use: Scancode::Space as i32,
             key_strafe: Scancode::RAlt as i32,
             key_speed: Scancode::LShift as i32,
+            key_jump: Scancode::E as i32,
+            key_crouch: Scancode::Q as i32,
 
             mousebfire: MouseButton::Left as u8,
             mousebstrafe: MouseButton::Middle as u8,
@@ -52,6 +59,8 @@ pub struct InputConfigSdl {
     pub(crate) key_use: Scancode,
     pub(crate) key_strafe: Scancode,
     pub(crate) key_speed: Scancode,
+    pub(crate) key_jump: Scancode,
+    pub(crate) key_crouch: Scancode,
     pub(crate) mousebfire: MouseButton,
     pub(crate) mousebstrafe: MouseButton,
     pub(crate) mousebforward: MouseButton,
@@ -70,6 +79,8 @@ impl From<&InputConfig> for InputConfigSdl {
             key_use: Scancode::from_i32(i.key_use).unwrap(),
             key_strafe: Scancode::from_i32(i.key_strafe).unwrap(),
             key_speed: Scancode::from_i32(i.key_speed).unwrap(),
+            key_jump: Scancode::from_i32(i.key_jump).unwrap(),
+            key_crouch: Scancode::from_i32(i.key_crouch).unwrap(),
             mousebfire: MouseButton::from_ll(i.mousebfire),
             mousebstrafe: MouseButton::from_ll(i.mousebstrafe),
             mousebforward: MouseButton::from_ll(i.mousebforward),
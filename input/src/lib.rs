@@ -21,6 +21,10 @@ pub struct InputEvents {
     mouse_state: HashSet<Mb>,
     mouse_delta: (i32, i32),
     mouse_scale: (i32, i32),
+    /// Vertical mouse movement for free-look, scaled as for turning
+    mouse_look: i32,
+    /// Vertical mouse movement looks up and down instead of moving
+    free_look: bool,
     turn_held: u32,
 }
 impl InputEvents {
@@ -62,12 +66,18 @@ impl InputEvents {
         self.mouse_scale = scale;
     }
 
+    pub fn set_free_look(&mut self, free_look: bool) {
+        self.free_look = free_look;
+    }
+
     fn reset_mouse_delta(&mut self) {
         self.mouse_delta = (0, 0);
+        self.mouse_look = 0;
     }
 
     fn set_mouse_pos(&mut self, state: (i32, i32)) {
         self.mouse_delta = (state.0 * self.mouse_scale.0, state.1 * self.mouse_scale.1);
+        self.mouse_look = state.1 * self.mouse_scale.0;
     }
 
     pub fn build_tic_cmd(&mut self, cfg: &InputConfigSdl) -> TicCmd {
@@ -136,6 +146,13 @@ impl InputEvents {
             cmd.buttons |= TIC_CMD_BUTTONS.bt_use;
         }
 
+        if self.is_kb_pressed(cfg.key_jump) {
+            cmd.actions |= ACTION_JUMP;
+        }
+        if self.is_kb_pressed(cfg.key_crouch) {
+            cmd.actions |= ACTION_CROUCH;
+        }
+
         for i in 0..WeaponType::NumWeapons as u8 {
             if let Some(key) = Sc::from_i32(30 + i as i32) {
                 if self.is_kb_pressed(key) {
@@ -156,7 +173,11 @@ impl InputEvents {
 
         let mousex = self.mouse_delta.0;
 
-        forward += self.mouse_delta.1;
+        if self.free_look {
+            cmd.pitch = (-self.mouse_look * 0x8).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        } else {
+            forward += self.mouse_delta.1;
+        }
         if strafe {
             side += mousex * 2;
         } else {
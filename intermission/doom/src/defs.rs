@@ -5,10 +5,12 @@ use wad::types::WadPatch;
 use wad::WadData;
 
 pub(crate) const SHOW_NEXT_LOC_DELAY: i32 = 4;
+/// Vertical spacing of the rows of the multiplayer stats
+pub(crate) const WI_SPACINGY: i32 = 33;
 
 pub(crate) struct Patches {
     pub nums: [WadPatch; 10],
-    // pub minus: WadPatch,
+    pub minus: WadPatch,
     pub percent: WadPatch,
     pub kills: WadPatch,
     pub secret: WadPatch,
@@ -56,6 +58,7 @@ impl Patches {
 
         Self {
             nums: get_num_sprites("WINUM", 0, wad),
+            minus: WadPatch::from_lump(wad.get_lump("WIMINUS").unwrap()),
             percent: WadPatch::from_lump(wad.get_lump("WIPCNT").unwrap()),
             kills: WadPatch::from_lump(wad.get_lump("WIOSTK").unwrap()),
             secret: WadPatch::from_lump(wad.get_lump("WIOSTS").unwrap()),
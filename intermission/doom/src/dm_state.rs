@@ -0,0 +1,213 @@
+use crate::defs::WI_SPACINGY;
+use crate::{Intermission, TICRATE};
+use gameplay::MAXPLAYERS;
+use gamestate_traits::util::draw_num_pixels;
+use gamestate_traits::{GameTraits, PixelBuffer, SfxName, SubsystemTrait};
+
+const DM_MATRIXX: i32 = 42;
+const DM_MATRIXY: i32 = 68;
+const DM_SPACINGX: i32 = 40;
+const DM_TOTALSX: i32 = 269;
+const DM_KILLERSX: i32 = 10;
+const DM_KILLERSY: i32 = 100;
+const DM_VICTIMSX: i32 = 5;
+const DM_VICTIMSY: i32 = 50;
+
+/// The counts shown while the frag matrix ticks up
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DmCount {
+    /// 1 pauses, 2 counts and 4 waits to move on
+    stage: i32,
+    pause: i32,
+    frags: [[i32; MAXPLAYERS]; MAXPLAYERS],
+    totals: [i32; MAXPLAYERS],
+}
+
+impl Intermission {
+    /// Doom function name `WI_initDeathmatchStats`
+    pub(super) fn init_dm_stats(&mut self) {
+        self.dm_count = DmCount {
+            stage: 1,
+            pause: TICRATE,
+            ..Default::default()
+        };
+    }
+
+    /// Count every frag towards its total, one a tic.
+    ///
+    /// Doom function name `WI_updateDeathmatchStats`
+    pub(super) fn update_dm_stats(&mut self, game: &mut impl GameTraits) {
+        self.check_for_accelerate(game);
+        let in_game: Vec<usize> = (0..MAXPLAYERS)
+            .filter(|&i| self.level_info.plyr[i].inn)
+            .collect();
+        let totals: Vec<i32> = (0..MAXPLAYERS)
+            .map(|i| self.frag_total(i).clamp(-99, 99))
+            .collect();
+        let tick = self.bg_count % 4 == 0;
+        let plyr = &self.level_info.plyr;
+        let count = &mut self.dm_count;
+
+        if self.accelerate && count.stage != 4 {
+            self.accelerate = false;
+            for &i in in_game.iter() {
+                for &j in in_game.iter() {
+                    count.frags[i][j] = plyr[i].frags[j];
+                }
+                count.totals[i] = totals[i];
+            }
+            game.start_sound(SfxName::Barexp);
+            count.stage = 4;
+        }
+
+        match count.stage {
+            2 => {
+                if tick {
+                    game.start_sound(SfxName::Pistol);
+                }
+                let mut still_ticking = false;
+                for &i in in_game.iter() {
+                    for &j in in_game.iter() {
+                        let target = plyr[i].frags[j].clamp(-99, 99);
+                        let frags = &mut count.frags[i][j];
+                        if *frags != target {
+                            *frags += target.signum();
+                            still_ticking = true;
+                        }
+                    }
+                    count.totals[i] = totals[i];
+                }
+                if !still_ticking {
+                    game.start_sound(SfxName::Barexp);
+                    count.stage += 1;
+                }
+            }
+            4 => {
+                if self.accelerate {
+                    game.start_sound(SfxName::Slop);
+                    self.end_stats();
+                }
+            }
+            _ => {
+                count.pause -= 1;
+                if count.pause <= 0 {
+                    count.stage += 1;
+                    count.pause = TICRATE;
+                }
+            }
+        }
+    }
+
+    /// Frags of a player less their suicides
+    pub(super) fn frag_total(&self, player: usize) -> i32 {
+        let frags = &self.level_info.plyr[player].frags;
+        (0..MAXPLAYERS)
+            .map(|i| if i == player { -frags[i] } else { frags[i] })
+            .sum()
+    }
+
+    /// Draw a number right aligned to `x`, with a minus if negative
+    pub(super) fn draw_frags(
+        &self,
+        n: i32,
+        x: i32,
+        y: i32,
+        pad: usize,
+        scale: i32,
+        pixels: &mut dyn PixelBuffer,
+    ) {
+        let x = draw_num_pixels(
+            n.unsigned_abs(),
+            x,
+            y,
+            pad,
+            &self.patches.nums,
+            self,
+            pixels,
+        );
+        if n < 0 {
+            self.draw_patch_pixels(
+                &self.patches.minus,
+                x - self.patches.minus.width as i32 * scale,
+                y,
+                pixels,
+            );
+        }
+    }
+
+    /// The frags every player got on every other player, with the killers
+    /// down the side and the victims along the top.
+    ///
+    /// Doom function name `WI_drawDeathmatchStats`
+    pub(super) fn draw_dm_stats_pixels(&self, scale: i32, pixels: &mut dyn PixelBuffer) {
+        let in_game: Vec<bool> = self.level_info.plyr.iter().map(|p| p.inn).collect();
+        let me = self.level_info.pnum;
+
+        self.draw_patch_pixels(
+            &self.patches.total,
+            (DM_MATRIXX * scale) - self.patches.total.width as i32 * scale / 2,
+            (DM_MATRIXY - WI_SPACINGY + 10) * scale,
+            pixels,
+        );
+        self.draw_patch_pixels(
+            &self.patches.killers,
+            DM_KILLERSX * scale,
+            DM_KILLERSY * scale,
+            pixels,
+        );
+        self.draw_patch_pixels(
+            &self.patches.victims,
+            DM_VICTIMSX * scale,
+            DM_VICTIMSY * scale,
+            pixels,
+        );
+
+        // The player faces heading each column and row
+        let mut x = DM_MATRIXX + DM_SPACINGX;
+        let mut y = DM_MATRIXY;
+        for (i, patch) in self.patches.players.iter().enumerate() {
+            if in_game[i] {
+                let half = patch.width as i32 * scale / 2;
+                self.draw_patch_pixels(
+                    patch,
+                    x * scale - half,
+                    (DM_MATRIXY - WI_SPACINGY) * scale,
+                    pixels,
+                );
+                self.draw_patch_pixels(patch, DM_MATRIXX * scale - half, y * scale, pixels);
+                if i == me {
+                    self.draw_patch_pixels(
+                        &self.patches.bstar,
+                        x * scale - half,
+                        (DM_MATRIXY - WI_SPACINGY) * scale,
+                        pixels,
+                    );
+                    self.draw_patch_pixels(
+                        &self.patches.star,
+                        DM_MATRIXX * scale - half,
+                        y * scale,
+                        pixels,
+                    );
+                }
+            }
+            x += DM_SPACINGX;
+            y += WI_SPACINGY;
+        }
+
+        // The frags
+        let w = self.patches.nums[0].width as i32;
+        let mut y = DM_MATRIXY + 10;
+        for i in 0..MAXPLAYERS {
+            if in_game[i] {
+                for (j, _) in in_game.iter().enumerate().filter(|(_, &p)| p) {
+                    let x = DM_MATRIXX + DM_SPACINGX * (j as i32 + 1);
+                    let frags = self.dm_count.frags[i][j];
+                    self.draw_frags(frags, (x + w) * scale, y * scale, 1, scale, pixels);
+                }
+                let total = self.dm_count.totals[i];
+                self.draw_frags(total, (DM_TOTALSX + w) * scale, y * scale, 1, scale, pixels);
+            }
+            y += WI_SPACINGY;
+        }
+    }
+}
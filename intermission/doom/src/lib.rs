@@ -3,16 +3,22 @@
 use crate::defs::{
     animations, AnimType, Animation, Patches, State, MAP_POINTS, SHOW_NEXT_LOC_DELAY
 };
-use gameplay::{m_random, TICRATE};
+use crate::dm_state::DmCount;
+use crate::net_state::NetCount;
+use gameplay::tic_cmd::TIC_CMD_BUTTONS;
+use gameplay::{Random, MAXPLAYERS, TICRATE};
 use gamestate_traits::{
     GameMode, GameTraits, MusTrack, PixelBuffer, Scancode, SubsystemTrait, WorldEndPlayerInfo, WorldInfo
 };
+use hud_util::{HUDFont, HUDString, HUD_STRING};
 use log::warn;
 use wad::types::{WadPalette, WadPatch};
 use wad::WadData;
 
 mod defs;
+mod dm_state;
 mod loc_state;
+mod net_state;
 mod no_state;
 mod stat_state;
 
@@ -31,6 +37,8 @@ pub struct Intermission {
     current_bg: usize,
     /// General counter for animated BG
     bg_count: i32,
+    /// Times the animated BG
+    random: Random,
     mode: GameMode,
     // info updated by ticker
     player_info: WorldEndPlayerInfo,
@@ -38,9 +46,34 @@ pub struct Intermission {
 
     pointer_on: bool,
     count: i32,
+    /// A player pressed fire or use to skip the counting, or move on once it
+    /// is done
+    accelerate: bool,
+    /// Fire and use held by each player last tic, so a button held down from
+    /// the level doesn't skip the counting
+    attack_down: [bool; MAXPLAYERS],
+    use_down: [bool; MAXPLAYERS],
     state: State,
+    net_count: NetCount,
+    dm_count: DmCount,
     /// General patches not specific to retail/commercial/registered
     patches: Patches,
+
+    /// `UMAPINFO` background of the level finished screen
+    exit_pic: Option<WadPatch>,
+    /// `UMAPINFO` background of the entering level screen
+    enter_pic: Option<WadPatch>,
+    /// `UMAPINFO` name patches of the finished and next level
+    last_name_pic: Option<WadPatch>,
+    next_name_pic: Option<WadPatch>,
+    /// Font of the `UMAPINFO` level names
+    font: HUDFont,
+    /// `UMAPINFO` level names, drawn if there is no name patch
+    last_name: Option<HUDString>,
+    next_name: Option<HUDString>,
+    last_author: Option<HUDString>,
+    /// The finished level ends the game, so there is no entering screen
+    ends_game: bool,
 }
 
 impl Intermission {
@@ -139,13 +172,28 @@ impl Intermission {
             yah_idx: 0,
             current_bg: 0,
             bg_count: 0,
+            random: Random::new(),
             mode,
             player_info: WorldEndPlayerInfo::default(),
             level_info: WorldInfo::default(),
             pointer_on: true,
             count: SHOW_NEXT_LOC_DELAY * TICRATE,
+            accelerate: false,
+            attack_down: [false; MAXPLAYERS],
+            use_down: [false; MAXPLAYERS],
             state: State::None,
+            net_count: NetCount::default(),
+            dm_count: DmCount::default(),
             patches: Patches::new(wad),
+            exit_pic: None,
+            enter_pic: None,
+            last_name_pic: None,
+            next_name_pic: None,
+            font: HUDFont::new(wad),
+            last_name: None,
+            next_name: None,
+            last_author: None,
+            ends_game: false,
         }
     }
 
@@ -155,22 +203,128 @@ impl Intermission {
             .unwrap_or(&self.bg_patches[self.bg_patches.len() - 1])
     }
 
-    pub(crate) fn get_this_level_name(&self) -> &WadPatch {
-        let ep = if self.level_info.episode >= self.level_names.len() {
-            self.level_names.len() - 1
-        } else {
-            self.level_info.episode
-        };
-        &self.level_names[ep][self.level_info.last - 1]
+    /// The level name patch for this episode and level (origin 0). Maps
+    /// beyond the IWAD maps have no name patch.
+    fn get_level_name(&self, episode: usize, level: usize) -> Option<&WadPatch> {
+        let ep = episode.min(self.level_names.len() - 1);
+        self.level_names[ep].get(level)
+    }
+
+    pub(crate) fn get_this_level_name(&self) -> Option<&WadPatch> {
+        self.last_name_pic.as_ref().or_else(|| {
+            self.get_level_name(
+                self.level_info.episode,
+                self.level_info.last.checked_sub(1)?,
+            )
+        })
+    }
+
+    pub(crate) fn get_enter_level_name(&self) -> Option<&WadPatch> {
+        self.next_name_pic
+            .as_ref()
+            .or_else(|| self.get_level_name(self.level_info.next_episode, self.level_info.next))
     }
 
-    pub(crate) fn get_enter_level_name(&self) -> &WadPatch {
-        let ep = if self.level_info.episode >= self.level_names.len() {
-            self.level_names.len() - 1
+    /// Draw a level name patch, or the `UMAPINFO` level name if there is one
+    /// and no patch was given for it. Returns the height drawn.
+    pub(crate) fn draw_level_name_pixels(
+        &self,
+        name: Option<&HUDString>,
+        patch: Option<&WadPatch>,
+        y: i32,
+        scale: i32,
+        pixels: &mut dyn PixelBuffer,
+    ) -> i32 {
+        let half = pixels.size().width() / 2;
+        if let Some(name) = name {
+            name.draw_pixels(
+                half - name.width(&self.font) * scale / 2,
+                y,
+                &self.font,
+                self,
+                pixels,
+            );
+            name.line_height() * scale
+        } else if let Some(patch) = patch {
+            self.draw_patch_pixels(patch, half - patch.width as i32 * scale / 2, y, pixels);
+            patch.height as i32 * scale
         } else {
-            self.level_info.episode
+            0
+        }
+    }
+
+    /// Set up the `UMAPINFO` pics and names for the finished and next level
+    fn init_map_info(&mut self, wad: &WadData) {
+        let patch = |name: Option<&String>| {
+            let name = name?;
+            let lump = wad.get_lump(name);
+            if lump.is_none() {
+                warn!("Missing {name}");
+            }
+            lump.map(WadPatch::from_lump)
         };
-        &self.level_names[ep][self.level_info.next]
+        let last = self.level_info.last_info.as_ref();
+        let next = self.level_info.next_info.as_ref();
+
+        self.exit_pic = patch(last.and_then(|i| i.exit_pic.as_ref()));
+        // The exit pic is used for both screens if the next level has no enter pic
+        self.enter_pic = patch(next.and_then(|i| i.enter_pic.as_ref())).or(self.exit_pic.clone());
+        self.last_name_pic = patch(last.and_then(|i| i.level_pic.as_ref()));
+        self.next_name_pic = patch(next.and_then(|i| i.level_pic.as_ref()));
+
+        self.last_name = last
+            .filter(|i| i.level_pic.is_none())
+            .and_then(|i| i.level_name.as_deref())
+            .map(hud_string);
+        self.next_name = next
+            .filter(|i| i.level_pic.is_none())
+            .and_then(|i| i.level_name.as_deref())
+            .map(hud_string);
+        self.last_author = last
+            .filter(|i| i.level_pic.is_none())
+            .and_then(|i| i.author.as_deref())
+            .map(hud_string);
+
+        self.ends_game = last.and_then(|i| i.ends_game()) == Some(true);
+    }
+
+    /// Level locations are only shown for episodes 1-3 on the default
+    /// background, and for the maps of the episode
+    pub(crate) fn show_locations(&self) -> bool {
+        self.enter_pic.is_none()
+            && self.mode != GameMode::Commercial
+            && self.level_info.episode <= 2
+            && self.level_info.episode == self.level_info.next_episode
+            && self.level_info.last <= 9
+            && self.level_info.next < 9
+    }
+
+    /// Accelerate when any player presses fire or use.
+    ///
+    /// Doom function name `WI_checkForAccelerate`
+    pub(crate) fn check_for_accelerate(&mut self, game: &impl GameTraits) {
+        for i in 0..MAXPLAYERS {
+            let Some(player) = game.player(i) else {
+                continue;
+            };
+            let buttons = player.cmd.buttons;
+            if buttons & TIC_CMD_BUTTONS.bt_attack != 0 {
+                if !self.attack_down[i] {
+                    self.accelerate = true;
+                }
+                self.attack_down[i] = true;
+            } else {
+                self.attack_down[i] = false;
+            }
+            if buttons & TIC_CMD_BUTTONS.bt_use != 0 {
+                if !self.use_down[i] {
+                    self.accelerate = true;
+                }
+                self.use_down[i] = true;
+            } else {
+                self.use_down[i] = false;
+            }
+        }
     }
 
     fn init_animated_bg(&mut self) {
@@ -183,10 +337,11 @@ impl Intermission {
             // Next time to draw?
             match anim.kind {
                 AnimType::Always => {
-                    anim.next_tic = self.bg_count + 1 + (m_random() % anim.period);
+                    anim.next_tic = self.bg_count + 1 + (self.random.m_random() % anim.period);
                 }
                 AnimType::Random => {
-                    anim.next_tic = self.bg_count + 1 + anim.data2 + (m_random() % anim.data1);
+                    anim.next_tic =
+                        self.bg_count + 1 + anim.data2 + (self.random.m_random() % anim.data1);
                 }
                 AnimType::Level => {
                     anim.next_tic = self.bg_count + 1;
@@ -217,7 +372,8 @@ impl Intermission {
                         anim.counter += 1;
                         if anim.counter >= anim.num_of {
                             anim.counter = -1;
-                            anim.next_tic = self.bg_count + anim.data2 + (m_random() % anim.data1);
+                            anim.next_tic =
+                                self.bg_count + anim.data2 + (self.random.m_random() % anim.data1);
                         } else {
                             anim.next_tic = self.bg_count + anim.period;
                         }
@@ -275,6 +431,19 @@ impl Intermission {
     // }
 }
 
+/// A `HUDString` of text, with any characters the HUD font lacks removed
+fn hud_string(text: &str) -> HUDString {
+    let mut string = HUD_STRING;
+    string.replace(
+        text.to_ascii_uppercase()
+            .chars()
+            .map(|c| if ('!'..='_').contains(&c) { c } else { ' ' })
+            .collect(),
+    );
+    string.set_draw_all();
+    string
+}
+
 impl SubsystemTrait for Intermission {
     fn init(&mut self, game: &impl GameTraits) {
         self.bg_count = 0;
@@ -283,11 +452,17 @@ impl SubsystemTrait for Intermission {
         self.pointer_on = true;
         self.state = State::None;
 
+        for i in 0..MAXPLAYERS {
+            let status = game.player(i).map(|p| &p.status);
+            self.attack_down[i] = status.is_some_and(|s| s.attackdown);
+            self.use_down[i] = status.is_some_and(|s| s.usedown);
+        }
+
         self.player_info = game.player_end_info().clone();
         self.level_info = game.level_end_info().clone();
         self.current_bg = self.level_info.episode;
+        self.init_map_info(game.get_wad_data());
 
-        // TODO: deathmatch stuff
         self.init_stats();
     }
 
@@ -328,7 +503,7 @@ impl SubsystemTrait for Intermission {
 
         match self.state {
             State::StatCount => {
-                self.update_stats();
+                self.update_stats(game);
             }
             State::NextLoc => {
                 self.update_show_next_loc();
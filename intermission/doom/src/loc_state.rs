@@ -47,19 +47,27 @@ impl Intermission {
             buffer,
         );
         y += (5 * self.patches.enter.height as i32 * scale) / 4;
-        let patch = self.get_enter_level_name();
-        self.draw_patch_pixels(patch, half - patch.width as i32 * scale / 2, y, buffer);
+        self.draw_level_name_pixels(
+            self.next_name.as_ref(),
+            self.get_enter_level_name(),
+            y,
+            scale,
+            buffer,
+        );
     }
 
     pub(super) fn draw_next_loc_pixels(&self, scale: i32, buffer: &mut dyn PixelBuffer) {
         // Background
-        self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
-        self.draw_animated_bg_pixels(scale, buffer);
+        if let Some(bg) = &self.enter_pic {
+            self.draw_patch_pixels(bg, 0, 0, buffer);
+        } else {
+            self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
+            self.draw_animated_bg_pixels(scale, buffer);
+        }
 
-        // Location stuff only for episodes 1-3
-        if self.mode != GameMode::Commercial && self.level_info.episode <= 2 {
+        if self.show_locations() {
             let last = if self.level_info.last == 8 {
-                self.level_info.next - 1
+                self.level_info.next.saturating_sub(1)
             } else {
                 self.level_info.next
             };
@@ -78,7 +86,7 @@ impl Intermission {
             }
         }
 
-        if self.mode != GameMode::Commercial || self.level_info.next != 30 {
+        if !self.ends_game && (self.mode != GameMode::Commercial || self.level_info.next != 30) {
             self.draw_enter_level_pixels(scale, buffer);
         }
     }
@@ -0,0 +1,187 @@
+use crate::defs::WI_SPACINGY;
+use crate::{Intermission, TICRATE};
+use gameplay::MAXPLAYERS;
+use gamestate_traits::{GameTraits, PixelBuffer, SfxName, SubsystemTrait};
+
+const NG_STATSY: i32 = 50;
+const NG_SPACINGX: i32 = 64;
+
+/// `n` as a percentage of `max`, or 0 if there was nothing to get
+fn percent(n: i32, max: i32) -> u32 {
+    if max > 0 {
+        (n.max(0) * 100 / max) as u32
+    } else {
+        0
+    }
+}
+
+/// The counts shown while the co-op stats tick up
+#[derive(Debug, Default, Clone)]
+pub(crate) struct NetCount {
+    /// Odd stages pause, even stages count kills, items, secrets and frags
+    /// in turn, and 10 waits to move on
+    stage: i32,
+    pause: i32,
+    kills: [i32; MAXPLAYERS],
+    items: [i32; MAXPLAYERS],
+    secrets: [i32; MAXPLAYERS],
+    frags: [i32; MAXPLAYERS],
+    do_frags: bool,
+}
+
+impl Intermission {
+    /// Doom function name `WI_initNetgameStats`
+    pub(super) fn init_net_stats(&mut self) {
+        let info = &self.level_info;
+        self.net_count = NetCount {
+            stage: 1,
+            pause: TICRATE,
+            do_frags: (0..info.plyr.len()).any(|i| info.plyr[i].inn && self.frag_total(i) != 0),
+            ..Default::default()
+        };
+    }
+
+    /// Count up each column in turn, two percent a tic.
+    ///
+    /// Doom function name `WI_updateNetgameStats`
+    pub(super) fn update_net_stats(&mut self, game: &mut impl GameTraits) {
+        self.check_for_accelerate(game);
+        let info = &self.level_info;
+        let in_game: Vec<usize> = (0..MAXPLAYERS).filter(|&i| info.plyr[i].inn).collect();
+        let targets: Vec<[i32; 4]> = (0..MAXPLAYERS)
+            .map(|i| {
+                let p = &info.plyr[i];
+                [
+                    percent(p.total_kills, info.maxkills) as i32,
+                    percent(p.items_collected, info.maxitems) as i32,
+                    percent(p.secrets_found, info.maxsecret) as i32,
+                    self.frag_total(i),
+                ]
+            })
+            .collect();
+        let tick = self.bg_count % 4 == 0;
+        let count = &mut self.net_count;
+
+        if self.accelerate && count.stage != 10 {
+            self.accelerate = false;
+            for &i in in_game.iter() {
+                count.kills[i] = targets[i][0];
+                count.items[i] = targets[i][1];
+                count.secrets[i] = targets[i][2];
+                if count.do_frags {
+                    count.frags[i] = targets[i][3];
+                }
+            }
+            game.start_sound(SfxName::Barexp);
+            count.stage = 10;
+        }
+
+        match count.stage {
+            2 | 4 | 6 | 8 => {
+                if tick {
+                    game.start_sound(SfxName::Pistol);
+                }
+                let column = (count.stage / 2 - 1) as usize;
+                let (counts, step) = match column {
+                    0 => (&mut count.kills, 2),
+                    1 => (&mut count.items, 2),
+                    2 => (&mut count.secrets, 2),
+                    _ => (&mut count.frags, 1),
+                };
+                let mut still_ticking = false;
+                for &i in in_game.iter() {
+                    counts[i] += step;
+                    if counts[i] >= targets[i][column] {
+                        counts[i] = targets[i][column];
+                    } else {
+                        still_ticking = true;
+                    }
+                }
+                if !still_ticking {
+                    if column == 3 {
+                        game.start_sound(SfxName::Pldeth);
+                    } else {
+                        game.start_sound(SfxName::Barexp);
+                    }
+                    count.stage += 1;
+                    // Skip the frags column if nobody has any
+                    if column == 2 && !count.do_frags {
+                        count.stage += 2;
+                    }
+                }
+            }
+            10 => {
+                if self.accelerate {
+                    game.start_sound(SfxName::Sgcock);
+                    self.end_stats();
+                }
+            }
+            _ => {
+                count.pause -= 1;
+                if count.pause <= 0 {
+                    count.stage += 1;
+                    count.pause = TICRATE;
+                }
+            }
+        }
+    }
+
+    /// The kills, items, secrets and, if anyone got any, frags of every
+    /// player in a co-op game.
+    ///
+    /// Doom function name `WI_drawNetgameStats`
+    pub(super) fn draw_net_stats_pixels(&self, scale: i32, pixels: &mut dyn PixelBuffer) {
+        let info = &self.level_info;
+        let count = &self.net_count;
+        let do_frags = count.do_frags;
+        let stats_x = 32 + self.patches.star.width as i32 / 2 + if do_frags { 0 } else { 32 };
+        let pwidth = self.patches.percent.width as i32;
+
+        // the column titles
+        let titles = [
+            &self.patches.kills,
+            &self.patches.items,
+            &self.patches.secret,
+            &self.patches.frags,
+        ];
+        let columns = if do_frags { 4 } else { 3 };
+        for (i, patch) in titles.into_iter().take(columns).enumerate() {
+            self.draw_patch_pixels(
+                patch,
+                (stats_x + (i as i32 + 1) * NG_SPACINGX - patch.width as i32) * scale,
+                NG_STATSY * scale,
+                pixels,
+            );
+        }
+
+        let mut y = NG_STATSY + self.patches.kills.height as i32;
+        for (i, player) in info.plyr.iter().enumerate() {
+            if !player.inn {
+                continue;
+            }
+            let mut x = stats_x;
+            let face = &self.patches.players[i];
+            self.draw_patch_pixels(face, (x - face.width as i32) * scale, y * scale, pixels);
+            if i == info.pnum {
+                self.draw_patch_pixels(
+                    &self.patches.star,
+                    (x - face.width as i32) * scale,
+                    y * scale,
+                    pixels,
+                );
+            }
+
+            for n in [count.kills[i], count.items[i], count.secrets[i]] {
+                x += NG_SPACINGX;
+                self.draw_percent(n as u32, (x - pwidth) * scale, (y + 10) * scale, pixels);
+            }
+            x += NG_SPACINGX;
+
+            if do_frags {
+                let frags = count.frags[i];
+                self.draw_frags(frags, x * scale, (y + 10) * scale, 0, scale, pixels);
+            }
+            y += WI_SPACINGY;
+        }
+    }
+}
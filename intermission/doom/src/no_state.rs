@@ -5,7 +5,11 @@ use log::info;
 impl Intermission {
     pub(super) fn draw_no_state(&mut self, scale: i32, pixels: &mut dyn PixelBuffer) {
         self.pointer_on = true;
-        self.draw_next_loc_pixels(scale, pixels);
+        if self.ends_game {
+            self.draw_stats_pixels(scale, pixels);
+        } else {
+            self.draw_next_loc_pixels(scale, pixels);
+        }
     }
 
     pub(super) fn init_no_state(&mut self) {
@@ -1,6 +1,6 @@
 use crate::{Intermission, State, SHOW_NEXT_LOC_DELAY, TICRATE, TITLE_Y};
 use gamestate_traits::util::draw_num_pixels;
-use gamestate_traits::{GameMode, PixelBuffer, SubsystemTrait};
+use gamestate_traits::{GameMode, GameTraits, PixelBuffer, SubsystemTrait};
 
 const SCREEN_HEIGHT: i32 = 200;
 
@@ -14,20 +14,38 @@ impl Intermission {
         self.pointer_on = false;
         self.state = State::StatCount;
         self.count = SHOW_NEXT_LOC_DELAY * TICRATE;
+        self.accelerate = false;
+        if self.level_info.deathmatch != 0 {
+            self.init_dm_stats();
+        } else if self.level_info.netgame {
+            self.init_net_stats();
+        }
 
         self.init_animated_bg();
     }
 
-    pub(super) fn update_stats(&mut self) {
+    pub(super) fn update_stats(&mut self, game: &mut impl GameTraits) {
         self.update_animated_bg();
+        if self.level_info.deathmatch != 0 {
+            self.update_dm_stats(game);
+            return;
+        } else if self.level_info.netgame {
+            self.update_net_stats(game);
+            return;
+        }
 
         // self.count -= 1;
         if self.count <= 0 {
-            if self.mode == GameMode::Commercial {
-                self.init_no_state();
-            } else {
-                self.init_next_loc();
-            }
+            self.end_stats();
+        }
+    }
+
+    /// Move on from the stats to the next screen
+    pub(super) fn end_stats(&mut self) {
+        if self.mode == GameMode::Commercial || self.ends_game {
+            self.init_no_state();
+        } else {
+            self.init_next_loc();
         }
     }
 
@@ -41,11 +59,25 @@ impl Intermission {
             pixels,
         );
         y += (5 * self.patches.finish.height as i32) / 4 * scale;
-        let patch = self.get_this_level_name();
-        self.draw_patch_pixels(patch, half - patch.width as i32 * scale / 2, y, pixels);
+        y += self.draw_level_name_pixels(
+            self.last_name.as_ref(),
+            self.get_this_level_name(),
+            y,
+            scale,
+            pixels,
+        );
+        if let Some(author) = &self.last_author {
+            author.draw_pixels(
+                half - author.width(&self.font) * scale / 2,
+                y,
+                &self.font,
+                self,
+                pixels,
+            );
+        }
     }
 
-    fn draw_percent(&self, p: u32, x: i32, y: i32, pixels: &mut dyn PixelBuffer) {
+    pub(super) fn draw_percent(&self, p: u32, x: i32, y: i32, pixels: &mut dyn PixelBuffer) {
         self.draw_patch_pixels(&self.patches.percent, x, y, pixels);
         draw_num_pixels(p, x, y, 0, &self.patches.nums, self, pixels);
     }
@@ -78,9 +110,20 @@ impl Intermission {
         let time_y = SP_TIMEY * scale;
 
         // Background
-        self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
-        self.draw_animated_bg_pixels(scale, buffer);
+        if let Some(bg) = &self.exit_pic {
+            self.draw_patch_pixels(bg, 0, 0, buffer);
+        } else {
+            self.draw_patch_pixels(self.get_bg(), 0, 0, buffer);
+            self.draw_animated_bg_pixels(scale, buffer);
+        }
         self.draw_level_finish_pixels(scale, buffer);
+        if self.level_info.deathmatch != 0 {
+            self.draw_dm_stats_pixels(scale, buffer);
+            return;
+        } else if self.level_info.netgame {
+            self.draw_net_stats_pixels(scale, buffer);
+            return;
+        }
 
         let mut lh = (3 * self.patches.nums[0].height / 2) as i32;
         self.draw_patch_pixels(&self.patches.kills, stats_x, stats_y, buffer);
@@ -118,7 +161,12 @@ impl Intermission {
             buffer,
         );
 
-        if self.level_info.episode < 3 {
+        let umapinfo_par = self
+            .level_info
+            .last_info
+            .as_ref()
+            .is_some_and(|i| i.par_time.is_some());
+        if self.level_info.episode < 3 || umapinfo_par {
             self.draw_patch_pixels(&self.patches.par, width / 2 + time_x, time_y, buffer);
             self.draw_time(
                 self.level_info.partime as u32,
@@ -5,15 +5,23 @@
 //! a different menu.
 
 use gamestate_traits::{GameMode, GameTraits, PixelBuffer, Scancode, Skill, SubsystemTrait};
+use hud_util::{HUDFont, HUDString};
 use sound_traits::SfxName;
 use std::collections::HashMap;
 use wad::types::{WadPalette, WadPatch};
+use wad::umapinfo::map_name_to_num;
 use wad::WadData;
 
 const SAVESTRINGSIZE: i32 = 24;
+/// The number of save slots shown in the load and save menus
+const SAVE_SLOTS: usize = 6;
+const EMPTYSTRING: &str = "EMPTY SLOT";
+const SAVE_BORDER: [&str; 3] = ["M_LSLEFT", "M_LSCNTR", "M_LSRGHT"];
 const SKULLXOFF: i32 = -32;
 const LINEHEIGHT: i32 = 16;
 const SKULLS: [&str; 2] = ["M_SKULL1", "M_SKULL2"];
+/// The most episodes `UMAPINFO` can show in the menu
+const MAX_EPISODES: usize = 8;
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 enum Status {
@@ -125,6 +133,8 @@ enum MenuIndex {
     Skill,
     ReadThis1,
     ReadThis2,
+    LoadGame,
+    SaveGame,
 }
 
 fn place_holder(_: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {}
@@ -142,6 +152,13 @@ pub struct MenuDoom {
     save_old: String,
     /// Which char of the buffer to edit
     save_char_idx: usize,
+    /// The description of each save slot, `None` if the slot is empty
+    save_strings: [Option<String>; SAVE_SLOTS],
+    /// The slot quick-save and quick-load use, set by the last save made
+    quick_save_slot: Option<usize>,
+    /// Used to draw the save descriptions
+    font: HUDFont,
+    text: HUDString,
     //
     /// Main menu def
     menus: Vec<MenuSet>,
@@ -151,12 +168,17 @@ pub struct MenuDoom {
     palette: WadPalette,
     /// Track the episode selected by episode menu
     episode: usize,
+    /// Track the map the episode selected by episode menu starts on
+    map: usize,
+    /// The episode and map each item of the episode menu starts on
+    episode_starts: Vec<(usize, usize)>,
     which_skull: usize,
     skull_anim_counter: i32,
 }
 
 impl MenuDoom {
     pub fn new(mode: GameMode, wad: &WadData) -> Self {
+        let (episode_items, episode_starts) = episode_items(wad);
         let menus = vec![
             MenuSet::new(
                 MenuIndex::TopLevel,
@@ -168,8 +190,8 @@ impl MenuDoom {
                 vec![
                     MenuItem::new(Status::Ok, "M_NGAME", sel_new_game, 'N'),
                     MenuItem::new(Status::Ok, "M_OPTION", place_holder, 'O'),
-                    MenuItem::new(Status::Ok, "M_LOADG", place_holder, 'L'),
-                    MenuItem::new(Status::Ok, "M_SAVEG", place_holder, 'S'),
+                    MenuItem::new(Status::Ok, "M_LOADG", sel_load, 'L'),
+                    MenuItem::new(Status::Ok, "M_SAVEG", sel_save, 'S'),
                     MenuItem::new(Status::Ok, "M_RDTHIS", sel_readthis, 'R'),
                     MenuItem::new(Status::Ok, "M_QUITG", sel_quit_game, 'Q'),
                 ],
@@ -180,23 +202,11 @@ impl MenuDoom {
                 vec![Title::new("M_EPISOD", 54, 38)],
                 48,
                 63,
-                (1..=9)
-                    .filter_map(|e| {
-                        if wad.lump_exists(&format!("M_EPI{e}")) {
-                            return Some(MenuItem::new(
-                                Status::Ok,
-                                format!("M_EPI{e}"),
-                                sel_episode,
-                                char::from_digit(e, 10).unwrap(),
-                            ));
-                        }
-                        None
-                    })
-                    .collect(),
+                episode_items,
             ),
             MenuSet::new(
                 MenuIndex::Skill,
-                if mode == GameMode::Commercial {
+                if episode_starts.is_empty() {
                     MenuIndex::TopLevel
                 } else {
                     MenuIndex::Episodes
@@ -258,6 +268,22 @@ impl MenuDoom {
                     }
                 },
             ),
+            MenuSet::new(
+                MenuIndex::LoadGame,
+                MenuIndex::TopLevel,
+                vec![Title::new("M_LOADG", 72, 28)],
+                80,
+                54,
+                save_slot_items(sel_load_game),
+            ),
+            MenuSet::new(
+                MenuIndex::SaveGame,
+                MenuIndex::TopLevel,
+                vec![Title::new("M_SAVEG", 72, 28)],
+                80,
+                54,
+                save_slot_items(sel_save_game),
+            ),
         ];
 
         let mut patches = HashMap::new();
@@ -274,7 +300,7 @@ impl MenuDoom {
             }
         }
 
-        for patch in SKULLS {
+        for patch in SKULLS.iter().chain(SAVE_BORDER.iter()) {
             if let Some(lump) = wad.get_lump(patch) {
                 patches.insert(patch.to_string(), WadPatch::from_lump(lump));
             }
@@ -289,12 +315,18 @@ impl MenuDoom {
             save_slot: 0,
             save_old: String::new(),
             save_char_idx: 0,
+            save_strings: Default::default(),
+            quick_save_slot: None,
+            font: HUDFont::new(wad),
+            text: HUDString::new(),
             //
             menus,
             current_menu: MenuIndex::TopLevel,
             patches,
             palette,
             episode: 0,
+            map: 1,
+            episode_starts,
             which_skull: 0,
             skull_anim_counter: 10,
         }
@@ -330,6 +362,89 @@ impl MenuDoom {
         &mut self.menus[idx]
     }
 
+    /// Open the load or save menu directly, such as by the F-keys
+    fn open_menu(&mut self, menu: MenuIndex, game: &mut dyn GameTraits) {
+        self.read_save_strings(game);
+        self.active = true;
+        self.current_menu = menu;
+        game.start_sound(SfxName::Swtchn);
+    }
+
+    /// Refresh the slot descriptions. Only slots with a save can be loaded.
+    ///
+    /// Doom function name `M_ReadSaveStrings`
+    fn read_save_strings(&mut self, game: &dyn GameTraits) {
+        for (slot, string) in self.save_strings.iter_mut().enumerate() {
+            // Imported saves may have characters the font doesn't have
+            *string = game.save_description(slot).map(|s| {
+                s.to_ascii_uppercase()
+                    .chars()
+                    .filter(|c| *c == ' ' || ('!'..='_').contains(c))
+                    .collect()
+            });
+        }
+        let strings = self.save_strings.clone();
+        for menu in self.menus.iter_mut() {
+            if menu.this == MenuIndex::LoadGame {
+                for (item, string) in menu.items.iter_mut().zip(strings.iter()) {
+                    item.status = if string.is_some() {
+                        Status::Ok
+                    } else {
+                        Status::NoCursor
+                    };
+                }
+            }
+        }
+    }
+
+    /// Handle keys while the player types a save description
+    fn save_responder(&mut self, sc: Scancode, game: &mut dyn GameTraits) -> bool {
+        let string = self.save_strings[self.save_slot].get_or_insert_with(String::new);
+        match sc {
+            Scancode::Escape => {
+                self.save_enter = false;
+                self.save_strings[self.save_slot] = if self.save_old.is_empty() {
+                    None
+                } else {
+                    Some(self.save_old.clone())
+                };
+            }
+            Scancode::Return => {
+                if !string.is_empty() {
+                    self.save_enter = false;
+                    game.save_game(string.clone(), self.save_slot);
+                    self.quick_save_slot = Some(self.save_slot);
+                    self.exit_menu(game);
+                }
+            }
+            Scancode::Backspace => {
+                if self.save_char_idx > 0 {
+                    self.save_char_idx -= 1;
+                    string.truncate(self.save_char_idx);
+                }
+            }
+            _ => {
+                let name = sc.to_string();
+                let c = match name.as_str() {
+                    "Space" => ' ',
+                    _ if name.len() == 1 => name.chars().next().unwrap().to_ascii_uppercase(),
+                    _ => return true,
+                };
+                if !(c == ' ' || ('!'..='_').contains(&c)) {
+                    return true;
+                }
+                self.text.replace(format!("{string}{c}"));
+                if self.save_char_idx < SAVESTRINGSIZE as usize - 1
+                    && self.text.width(&self.font) < (SAVESTRINGSIZE - 2) * 8
+                {
+                    string.push(c);
+                    self.save_char_idx += 1;
+                }
+            }
+        }
+        true
+    }
+
     fn get_patch(&self, name: &str) -> &WadPatch {
         self.patches
             .get(name)
@@ -349,7 +464,9 @@ impl MenuDoom {
             let x = active.x * f;
             let mut y = active.y * f;
             for item in active.items.iter() {
-                self.draw_patch_pixels(self.get_patch(&item.patch), x, y, pixels);
+                if !item.patch.is_empty() {
+                    self.draw_patch_pixels(self.get_patch(&item.patch), x, y, pixels);
+                }
                 y += LINEHEIGHT * f;
             }
 
@@ -361,12 +478,111 @@ impl MenuDoom {
                 y,
                 pixels,
             );
+
+            if matches!(self.current_menu, MenuIndex::LoadGame | MenuIndex::SaveGame) {
+                self.draw_save_slots(pixels);
+            }
         }
     }
 }
 
-fn sel_new_game(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
-    if game.get_mode() == GameMode::Commercial {
+impl MenuDoom {
+    /// Doom function name `M_DrawLoad` and `M_DrawSave`
+    fn draw_save_slots(&mut self, pixels: &mut dyn PixelBuffer) {
+        let f = pixels.size().height() / 200;
+        let active = &self.menus[self.current_menu as usize];
+        let (x, y) = (active.x, active.y);
+
+        for slot in 0..SAVE_SLOTS {
+            let y = y + LINEHEIGHT * slot as i32;
+            self.draw_save_border(x, y, pixels);
+
+            let mut string = self.save_strings[slot]
+                .clone()
+                .unwrap_or_else(|| EMPTYSTRING.to_string());
+            if self.save_enter && slot == self.save_slot {
+                if self.save_strings[slot].is_none() {
+                    string.clear();
+                }
+                string.push('_');
+            }
+            self.text.replace(string);
+            self.text.set_draw_all();
+            self.text.draw(x * f, y * f, &self.font, self, pixels);
+        }
+    }
+
+    /// Doom function name `M_DrawSaveLoadBorder`
+    fn draw_save_border(&self, x: i32, y: i32, pixels: &mut dyn PixelBuffer) {
+        let f = pixels.size().height() / 200;
+        let [left, centre, right] = SAVE_BORDER.map(|name| self.get_patch(name));
+
+        self.draw_patch_pixels(left, (x - 8) * f, (y + 7) * f, pixels);
+        let mut x = x;
+        for _ in 0..SAVESTRINGSIZE {
+            self.draw_patch_pixels(centre, x * f, (y + 7) * f, pixels);
+            x += 8;
+        }
+        self.draw_patch_pixels(right, x * f, (y + 7) * f, pixels);
+    }
+}
+
+/// The items of the load or save menu, one per slot. These have no patch as the
+/// slot descriptions are drawn as text.
+fn save_slot_items(logic: fn(&mut MenuDoom, usize, &mut dyn GameTraits)) -> Vec<MenuItem> {
+    (0..SAVE_SLOTS)
+        .map(|slot| {
+            MenuItem::new(
+                Status::Ok,
+                "",
+                logic,
+                char::from_digit(slot as u32 + 1, 10).unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// The episode menu items and the episode and map each starts on. These are
+/// the `M_EPIx` patches of the IWAD, which `UMAPINFO` may clear or add to.
+fn episode_items(wad: &WadData) -> (Vec<MenuItem>, Vec<(usize, usize)>) {
+    let umapinfo = wad.umapinfo();
+    let mut items = Vec::new();
+    let mut starts = Vec::new();
+
+    if !umapinfo.episodes_cleared {
+        for e in 1..=9 {
+            if wad.lump_exists(&format!("M_EPI{e}")) {
+                items.push(MenuItem::new(
+                    Status::Ok,
+                    format!("M_EPI{e}"),
+                    sel_episode,
+                    char::from_digit(e, 10).unwrap(),
+                ));
+                starts.push((e as usize, 1));
+            }
+        }
+    }
+
+    for episode in umapinfo.episodes.iter() {
+        if let Some(start) = map_name_to_num(&episode.map_name) {
+            items.push(MenuItem::new(
+                Status::Ok,
+                &episode.patch,
+                sel_episode,
+                episode.key,
+            ));
+            starts.push(start);
+        }
+    }
+    if !umapinfo.episodes.is_empty() {
+        items.truncate(MAX_EPISODES);
+        starts.truncate(MAX_EPISODES);
+    }
+    (items, starts)
+}
+
+fn sel_new_game(menu: &mut MenuDoom, _: usize, _game: &mut dyn GameTraits) {
+    if menu.episode_starts.is_empty() {
         menu.current_menu = MenuIndex::Skill;
         return;
     }
@@ -385,27 +601,47 @@ fn sel_readthis2(menu: &mut MenuDoom, _: usize, _: &mut dyn GameTraits) {
     menu.current_menu = MenuIndex::TopLevel;
 }
 
+fn sel_load(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    menu.read_save_strings(game);
+    menu.current_menu = MenuIndex::LoadGame;
+}
+
+fn sel_save(menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
+    if !game.can_save() {
+        game.start_sound(SfxName::Oof);
+        return;
+    }
+    menu.read_save_strings(game);
+    menu.current_menu = MenuIndex::SaveGame;
+}
+
+fn sel_load_game(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
+    menu.exit_menu(game);
+    game.load_game(choice);
+}
+
+fn sel_save_game(menu: &mut MenuDoom, choice: usize, _: &mut dyn GameTraits) {
+    menu.save_enter = true;
+    menu.save_slot = choice;
+    menu.save_old = menu.save_strings[choice].clone().unwrap_or_default();
+    menu.save_char_idx = menu.save_old.len();
+}
+
 fn sel_quit_game(_menu: &mut MenuDoom, _: usize, game: &mut dyn GameTraits) {
     game.quit_game();
 }
 
-// TODO: kind of bad, should make a better method to set episode even if not
-// sequential
-fn sel_episode(menu: &mut MenuDoom, _choice: usize, _game: &mut dyn GameTraits) {
-    menu.episode = menu
-        .get_current_menu()
-        .on()
-        .hotkey
-        .to_digit(10)
-        .unwrap_or_default() as usize
-        - 1;
+fn sel_episode(menu: &mut MenuDoom, choice: usize, _game: &mut dyn GameTraits) {
+    let (episode, map) = menu.episode_starts[choice];
+    menu.episode = episode.saturating_sub(1);
+    menu.map = map;
     menu.current_menu = MenuIndex::Skill;
 }
 
 fn sel_skill(menu: &mut MenuDoom, choice: usize, game: &mut dyn GameTraits) {
     menu.exit_menu(game);
     let skill = Skill::from(choice);
-    game.defered_init_new(skill, menu.episode + 1, 1);
+    game.defered_init_new(skill, menu.episode + 1, menu.map);
 }
 
 impl SubsystemTrait for MenuDoom {
@@ -418,6 +654,9 @@ impl SubsystemTrait for MenuDoom {
     }
 
     fn responder(&mut self, mut sc: Scancode, game: &mut impl GameTraits) -> bool {
+        if self.save_enter {
+            return self.save_responder(sc, game);
+        }
         if !self.active {
             // F-keys
             match sc {
@@ -435,15 +674,44 @@ impl SubsystemTrait for MenuDoom {
                 }
                 Scancode::F2 => {
                     // SAVE
+                    if game.can_save() {
+                        self.open_menu(MenuIndex::SaveGame, game);
+                    } else {
+                        game.start_sound(SfxName::Oof);
+                    }
+                    return true;
                 }
                 Scancode::F3 => {
                     // LOAD
+                    self.open_menu(MenuIndex::LoadGame, game);
+                    return true;
                 }
                 Scancode::F6 => {
                     // QUICKSAVE
+                    if !game.can_save() {
+                        game.start_sound(SfxName::Oof);
+                        return true;
+                    }
+                    let description = self
+                        .quick_save_slot
+                        .and_then(|slot| game.save_description(slot).map(|d| (slot, d)));
+                    if let Some((slot, description)) = description {
+                        game.save_game(description, slot);
+                        game.start_sound(SfxName::Swtchx);
+                    } else {
+                        self.open_menu(MenuIndex::SaveGame, game);
+                    }
+                    return true;
                 }
                 Scancode::F9 => {
                     // QUICKLOAD
+                    if let Some(slot) = self.quick_save_slot {
+                        game.load_game(slot);
+                        game.start_sound(SfxName::Swtchx);
+                    } else {
+                        self.open_menu(MenuIndex::LoadGame, game);
+                    }
+                    return true;
                 }
                 Scancode::Pause => {
                     game.toggle_pause_game();
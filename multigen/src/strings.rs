@@ -112,7 +112,9 @@ pub struct State {
 }
 
 const NUM_STATES: usize = StateNum::Count as usize;
-pub const STATES: [State; NUM_STATES] = [
+/// A `static` so each state has a single address that can be turned back in
+/// to an index, e.g, by savegames
+pub static STATES: [State; NUM_STATES] = [
     // State::new(sprite, frame, tics, action, next_state, misc1, misc2)"#;
 
 pub const MKIND_ENUM_HEADER: &str = r#"
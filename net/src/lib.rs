@@ -0,0 +1,793 @@
+//! Peer-to-peer lockstep networking over UDP.
+//!
+//! Every peer sends its tic cmds to every other peer, and a tic is only run
+//! once the cmds of every player in the game have arrived for it. Cmds are
+//! resent until they are acknowledged, and no peer can get more than
+//! `BACKUPTICS` tics ahead of what the others have acknowledged or of the
+//! tics it has run.
+//!
+//! Startup has every peer say hello to every other until all of them have
+//! heard from each other. The WAD checksums must match, and the game setup
+//! (skill, map etc) is taken from player 0.
+//!
+//! This crate knows nothing of the game, tic cmds are sent as `Cmd` bytes.
+//!
+//! Doom source name `d_net`
+
+mod packet;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use crate::packet::{CmdsPacket, Packet};
+
+/// Most players in a game
+pub const MAXPLAYERS: usize = 4;
+/// Number of tic cmds buffered. Also the most tics a peer can run ahead
+pub const BACKUPTICS: usize = 12;
+/// Size of a packed tic cmd
+pub const CMD_SIZE: usize = 11;
+
+/// A packed tic cmd
+pub type Cmd = [u8; CMD_SIZE];
+
+/// Hellos and unacknowledged cmds are resent this often
+const RESEND_TIME: Duration = Duration::from_millis(50);
+/// A peer that hasn't been heard from in this long has left
+const PEER_TIMEOUT: Duration = Duration::from_secs(20);
+/// Cmds buffered from each peer. A peer can make cmds up to `BACKUPTICS`
+/// past the tics it has run, which can need this peer's cmds that are up to
+/// `BACKUPTICS` past this peer's run tics.
+const PEER_BUFFER: usize = 2 * BACKUPTICS;
+/// The final packet is sent this many times as it isn't acknowledged
+const QUIT_SENDS: usize = 4;
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    /// Not every peer was heard from in time
+    Timeout,
+    /// This player number isn't one of the addresses
+    BadPlayer(usize),
+    /// A player has a different WAD
+    WadMismatch(usize),
+    /// A player was started with a different number of players
+    PlayerCount(usize),
+}
+
+impl Error for NetError {}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(e) => write!(f, "Network error: {e}"),
+            NetError::Timeout => write!(f, "Timed out waiting for other players"),
+            NetError::BadPlayer(p) => write!(f, "Player {} has no address", p + 1),
+            NetError::WadMismatch(p) => write!(f, "Player {} is using different WADs", p + 1),
+            NetError::PlayerCount(p) => {
+                write!(f, "Player {} has a different number of players", p + 1)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(e: io::Error) -> Self {
+        NetError::Io(e)
+    }
+}
+
+/// The game options every peer must agree on. Sent by player 0
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NetSetup {
+    pub skill: u8,
+    pub episode: u8,
+    pub map: u8,
+    pub deathmatch: u8,
+    pub no_monsters: bool,
+    pub respawn: bool,
+    pub fast: bool,
+    pub players: u8,
+    /// Deathmatch level time limit in minutes, 0 for none
+    pub time_limit: u8,
+    /// Deathmatch frag limit, 0 for none
+    pub frag_limit: u8,
+    /// Look up and down with the mouse
+    pub free_look: bool,
+    /// Aim shots up and down at monsters, kept with free-look unless off
+    pub autoaim: bool,
+    /// Jumping and crouching allowed
+    pub jump_crouch: bool,
+    /// Things move with vanilla fixed-point math
+    pub fixed_point: bool,
+}
+
+impl NetSetup {
+    const SIZE: usize = 14;
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        [
+            self.skill,
+            self.episode,
+            self.map,
+            self.deathmatch,
+            self.no_monsters as u8,
+            self.respawn as u8,
+            self.fast as u8,
+            self.players,
+            self.time_limit,
+            self.frag_limit,
+            self.free_look as u8,
+            self.autoaim as u8,
+            self.jump_crouch as u8,
+            self.fixed_point as u8,
+        ]
+    }
+
+    fn from_bytes(b: &[u8]) -> Option<Self> {
+        Some(Self {
+            skill: *b.first()?,
+            episode: *b.get(1)?,
+            map: *b.get(2)?,
+            deathmatch: *b.get(3)?,
+            no_monsters: *b.get(4)? != 0,
+            respawn: *b.get(5)? != 0,
+            fast: *b.get(6)? != 0,
+            players: *b.get(7)?,
+            time_limit: *b.get(8)?,
+            frag_limit: *b.get(9)?,
+            free_look: *b.get(10)? != 0,
+            autoaim: *b.get(11)? != 0,
+            jump_crouch: *b.get(12)? != 0,
+            fixed_point: *b.get(13)? != 0,
+        })
+    }
+}
+
+/// How to reach the other players
+#[derive(Debug, Clone)]
+pub struct NetConfig {
+    /// This peer's player number, 0 based
+    pub player: usize,
+    /// Address of every player, in player order. This peer's own is unused
+    pub addrs: Vec<SocketAddr>,
+    /// Checksum of the WADs in use, see `checksum()`
+    pub wad_checksum: u32,
+    /// The game setup. Only player 0's is used
+    pub setup: NetSetup,
+}
+
+/// The cmds of every player for a tic. `None` for players not in the game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetTic {
+    pub tic: u32,
+    pub cmds: [Option<Cmd>; MAXPLAYERS],
+}
+
+/// FNV-1a checksum, used to check all peers have the same WADs
+pub fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811C9DC5_u32, |hash, b| {
+        (hash ^ *b as u32).wrapping_mul(0x01000193)
+    })
+}
+
+struct Peer {
+    addr: SocketAddr,
+    /// Still in the game
+    in_game: bool,
+    /// Cmds received from the peer, indexed by `tic % PEER_BUFFER`
+    cmds: [Cmd; PEER_BUFFER],
+    /// Every cmd below this tic has been received from the peer
+    recv_tic: u32,
+    /// The peer has every local cmd below this tic
+    acked: u32,
+    /// The peer left after sending cmds up to this tic
+    quit_tic: Option<u32>,
+    last_heard: Instant,
+    last_sent: Instant,
+    /// Something was received that should be acknowledged soon
+    needs_ack: bool,
+}
+
+/// A running network game
+pub struct NetGame {
+    socket: UdpSocket,
+    player: usize,
+    players: usize,
+    peers: [Option<Peer>; MAXPLAYERS],
+    wad_checksum: u32,
+    setup: NetSetup,
+    /// Local cmds, indexed by `tic % BACKUPTICS`
+    local: [Cmd; BACKUPTICS],
+    /// Number of local cmds made
+    maketic: u32,
+    /// Number of tics run
+    gametic: u32,
+    /// Percentage of sent packets to drop, for testing retransmission
+    #[cfg(test)]
+    loss: u32,
+    /// Random state for `loss`
+    #[cfg(test)]
+    loss_rng: u32,
+}
+
+impl NetGame {
+    /// Say hello to every peer and wait until all of them have heard from
+    /// each other. `socket` should be bound to this player's address.
+    ///
+    /// Doom function name `D_ArbitrateNetStart`
+    pub fn connect(
+        socket: UdpSocket,
+        config: NetConfig,
+        timeout: Duration,
+    ) -> Result<Self, NetError> {
+        let players = config.addrs.len().min(MAXPLAYERS);
+        if config.player >= players {
+            return Err(NetError::BadPlayer(config.player));
+        }
+        socket.set_nonblocking(true)?;
+
+        let now = Instant::now();
+        let mut peers: [Option<Peer>; MAXPLAYERS] = Default::default();
+        for (i, addr) in config.addrs.iter().take(players).enumerate() {
+            if i != config.player {
+                peers[i] = Some(Peer {
+                    addr: *addr,
+                    in_game: true,
+                    cmds: [[0; CMD_SIZE]; PEER_BUFFER],
+                    recv_tic: 0,
+                    acked: 0,
+                    quit_tic: None,
+                    last_heard: now,
+                    last_sent: now - RESEND_TIME,
+                    needs_ack: false,
+                });
+            }
+        }
+
+        let mut net = Self {
+            socket,
+            player: config.player,
+            players,
+            peers,
+            wad_checksum: config.wad_checksum,
+            setup: NetSetup {
+                players: players as u8,
+                ..config.setup
+            },
+            local: [[0; CMD_SIZE]; BACKUPTICS],
+            maketic: 0,
+            gametic: 0,
+            #[cfg(test)]
+            loss: 0,
+            #[cfg(test)]
+            loss_rng: config.player as u32 + 1,
+        };
+
+        info!("Waiting for {} other players", players - 1);
+        let all = (1_u8 << players) - 1;
+        let mut heard = 1_u8 << net.player;
+        // Bit set for each player known to have heard from everyone
+        let mut ready = 0_u8;
+        let mut have_setup = net.player == 0;
+        let mut last_hello = now - RESEND_TIME;
+        loop {
+            if heard == all && ready | (1 << net.player) == all && have_setup {
+                break;
+            }
+            if now.elapsed() > timeout {
+                return Err(NetError::Timeout);
+            }
+            if last_hello.elapsed() >= RESEND_TIME {
+                net.send_hello(heard);
+                last_hello = Instant::now();
+            }
+
+            let Some((packet, from)) = net.recv()? else {
+                sleep(Duration::from_millis(1));
+                continue;
+            };
+            match packet {
+                Packet::Hello {
+                    player,
+                    players,
+                    wad_checksum,
+                    heard: their_heard,
+                    setup,
+                } => {
+                    if players as usize != net.players {
+                        return Err(NetError::PlayerCount(from));
+                    }
+                    if wad_checksum != net.wad_checksum {
+                        return Err(NetError::WadMismatch(from));
+                    }
+                    heard |= 1 << player;
+                    if their_heard == all {
+                        ready |= 1 << player;
+                    }
+                    if let (0, Some(setup)) = (player, setup) {
+                        net.setup = setup;
+                        have_setup = true;
+                    }
+                }
+                // A peer only sends cmds once it has started
+                Packet::Cmds(cmds) | Packet::Quit(cmds) => {
+                    heard |= 1 << from;
+                    ready |= 1 << from;
+                    net.receive_cmds(&cmds);
+                }
+            }
+        }
+        // Make sure everyone hears that this peer is ready
+        net.send_hello(all);
+        info!("All {} players are connected", net.players);
+        Ok(net)
+    }
+
+    /// This peer's player number, 0 based
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Number of players the game started with
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    /// The game options from player 0
+    pub fn setup(&self) -> NetSetup {
+        self.setup
+    }
+
+    /// The tic the next local cmd is for
+    pub fn maketic(&self) -> u32 {
+        self.maketic
+    }
+
+    /// Number of tics run so far, also the next tic to run
+    pub fn gametic(&self) -> u32 {
+        self.gametic
+    }
+
+    /// A local cmd can be made without overrunning any buffer
+    pub fn can_make_tic(&self) -> bool {
+        let limit = self.peers().map(|p| p.acked).min().unwrap_or(self.maketic);
+        self.maketic < self.gametic + BACKUPTICS as u32 && self.maketic < limit + BACKUPTICS as u32
+    }
+
+    /// Send the local cmd for `maketic()`. Returns false, and doesn't send, if
+    /// the other peers are too far behind.
+    ///
+    /// Doom function name `NetUpdate`
+    pub fn make_tic(&mut self, cmd: Cmd) -> bool {
+        self.update();
+        if !self.can_make_tic() {
+            return false;
+        }
+        self.local[self.maketic as usize % BACKUPTICS] = cmd;
+        self.maketic += 1;
+        for i in 0..MAXPLAYERS {
+            self.send_cmds(i, false);
+        }
+        true
+    }
+
+    /// Read any waiting packets, resend anything unacknowledged, and drop
+    /// peers that have gone silent.
+    pub fn update(&mut self) {
+        loop {
+            match self.recv() {
+                Ok(Some((packet, from))) => self.handle(packet, from),
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Network receive failed: {e}");
+                    break;
+                }
+            }
+        }
+
+        for i in 0..MAXPLAYERS {
+            let Some(peer) = self.peers[i].as_mut() else {
+                continue;
+            };
+            if !peer.in_game {
+                continue;
+            }
+            if peer.quit_tic.is_none() && peer.last_heard.elapsed() > PEER_TIMEOUT {
+                warn!("Player {} timed out", i + 1);
+                peer.quit_tic = Some(peer.recv_tic);
+            }
+            let resend = peer.needs_ack || peer.last_sent.elapsed() >= RESEND_TIME;
+            if resend && peer.quit_tic.is_none() {
+                self.send_cmds(i, false);
+            }
+        }
+    }
+
+    /// The cmds for the next tic if every player's has arrived. Advances to
+    /// the next tic when it returns `Some`. Players that have left are `None`
+    /// from the tic after their last cmd.
+    ///
+    /// Doom function name `TryRunTics`
+    pub fn next_tic(&mut self) -> Option<NetTic> {
+        self.update();
+        let tic = self.gametic;
+        if tic >= self.maketic {
+            return None;
+        }
+        let mut cmds = [None; MAXPLAYERS];
+        cmds[self.player] = Some(self.local[tic as usize % BACKUPTICS]);
+        for (i, peer) in self.peers.iter_mut().enumerate() {
+            let Some(peer) = peer else {
+                continue;
+            };
+            if !peer.in_game {
+                continue;
+            }
+            if matches!(peer.quit_tic, Some(quit) if tic >= quit) {
+                info!("Player {} left the game", i + 1);
+                peer.in_game = false;
+                continue;
+            }
+            if peer.recv_tic <= tic {
+                return None;
+            }
+            cmds[i] = Some(peer.cmds[tic as usize % PEER_BUFFER]);
+        }
+        self.gametic += 1;
+        Some(NetTic { tic, cmds })
+    }
+
+    /// Tell the other peers this one is leaving, along with any cmds they
+    /// don't have yet
+    ///
+    /// Doom function name `D_QuitNetGame`
+    pub fn quit(&mut self) {
+        for _ in 0..QUIT_SENDS {
+            for i in 0..MAXPLAYERS {
+                self.send_cmds(i, true);
+            }
+        }
+    }
+
+    fn peers(&self) -> impl Iterator<Item = &Peer> {
+        self.peers
+            .iter()
+            .flatten()
+            .filter(|p| p.in_game && p.quit_tic.is_none())
+    }
+
+    fn handle(&mut self, packet: Packet, from: usize) {
+        match packet {
+            // A peer still starting up needs to know this one is ready. Only
+            // answer those, or two started peers would answer each other forever
+            Packet::Hello { heard, .. } => {
+                let all = (1_u8 << self.players) - 1;
+                if heard != all {
+                    self.send_hello_to(from, all);
+                }
+            }
+            Packet::Cmds(cmds) => self.receive_cmds(&cmds),
+            Packet::Quit(cmds) => {
+                self.receive_cmds(&cmds);
+                if let Some(peer) = self.peers[from].as_mut() {
+                    if peer.quit_tic.is_none() {
+                        peer.quit_tic = Some(cmds.start + cmds.cmds.len() as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    fn receive_cmds(&mut self, packet: &CmdsPacket) {
+        let gametic = self.gametic;
+        let Some(peer) = self.peers[packet.player as usize].as_mut() else {
+            return;
+        };
+        peer.acked = peer.acked.max(packet.ack.min(self.maketic));
+        for (i, cmd) in packet.cmds.iter().enumerate() {
+            let tic = packet.start + i as u32;
+            // Cmds are taken in order, and only if there is room for them
+            if tic == peer.recv_tic && tic < gametic + PEER_BUFFER as u32 {
+                peer.cmds[tic as usize % PEER_BUFFER] = *cmd;
+                peer.recv_tic += 1;
+            }
+        }
+        if !packet.cmds.is_empty() {
+            peer.needs_ack = true;
+        }
+    }
+
+    /// Send every local cmd `player` hasn't acknowledged, and acknowledge
+    /// theirs
+    fn send_cmds(&mut self, player: usize, quit: bool) {
+        let Some(peer) = self.peers[player].as_ref() else {
+            return;
+        };
+        if !peer.in_game || peer.quit_tic.is_some() {
+            return;
+        }
+        let start = peer.acked;
+        let cmds = (start..self.maketic)
+            .map(|tic| self.local[tic as usize % BACKUPTICS])
+            .collect();
+        let packet = CmdsPacket {
+            player: self.player as u8,
+            ack: peer.recv_tic,
+            start,
+            cmds,
+        };
+        let addr = peer.addr;
+        self.send(
+            &if quit {
+                Packet::Quit(packet)
+            } else {
+                Packet::Cmds(packet)
+            },
+            addr,
+        );
+        if let Some(peer) = self.peers[player].as_mut() {
+            peer.last_sent = Instant::now();
+            peer.needs_ack = false;
+        }
+    }
+
+    fn hello(&self, heard: u8) -> Packet {
+        Packet::Hello {
+            player: self.player as u8,
+            players: self.players as u8,
+            wad_checksum: self.wad_checksum,
+            heard,
+            setup: (self.player == 0).then_some(self.setup),
+        }
+    }
+
+    fn send_hello(&mut self, heard: u8) {
+        let hello = self.hello(heard);
+        let addrs: Vec<SocketAddr> = self.peers.iter().flatten().map(|p| p.addr).collect();
+        for addr in addrs {
+            self.send(&hello, addr);
+        }
+    }
+
+    fn send_hello_to(&mut self, player: usize, heard: u8) {
+        if let Some(addr) = self.peers[player].as_ref().map(|p| p.addr) {
+            let hello = self.hello(heard);
+            self.send(&hello, addr);
+        }
+    }
+
+    fn send(&mut self, packet: &Packet, addr: SocketAddr) {
+        #[cfg(test)]
+        {
+            // xorshift
+            self.loss_rng ^= self.loss_rng << 13;
+            self.loss_rng ^= self.loss_rng >> 17;
+            self.loss_rng ^= self.loss_rng << 5;
+            if self.loss_rng % 100 < self.loss {
+                return;
+            }
+        }
+        if let Err(e) = self.socket.send_to(&packet.encode(), addr) {
+            warn!("Network send to {addr} failed: {e}");
+        }
+    }
+
+    /// The next valid packet from a peer, and which player it is from
+    fn recv(&mut self) -> io::Result<Option<(Packet, usize)>> {
+        let mut buf = [0; 512];
+        loop {
+            let (len, addr) = match self.socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                // Windows reports an unreachable peer on the next receive
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(packet) = Packet::decode(&buf[..len]) else {
+                continue;
+            };
+            let from = match &packet {
+                Packet::Hello { player, .. } => *player,
+                Packet::Cmds(c) | Packet::Quit(c) => c.player,
+            } as usize;
+            let Some(peer) = self.peers.get_mut(from).and_then(|p| p.as_mut()) else {
+                continue;
+            };
+            // Anyone can claim to be a player
+            if peer.addr != addr {
+                continue;
+            }
+            peer.last_heard = Instant::now();
+            return Ok(Some((packet, from)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Connect `n` peers on localhost, one thread each
+    fn run_peers(
+        n: usize,
+        loss: u32,
+        body: impl Fn(NetGame) -> Vec<NetTic> + Send + Sync + Copy + 'static,
+    ) -> Vec<Vec<NetTic>> {
+        let sockets: Vec<UdpSocket> = (0..n)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let addrs: Vec<SocketAddr> = sockets.iter().map(|s| s.local_addr().unwrap()).collect();
+        let threads: Vec<_> = sockets
+            .into_iter()
+            .enumerate()
+            .map(|(player, socket)| {
+                let config = NetConfig {
+                    player,
+                    addrs: addrs.clone(),
+                    wad_checksum: checksum(b"doom1.wad"),
+                    setup: NetSetup {
+                        skill: player as u8,
+                        map: 3,
+                        ..Default::default()
+                    },
+                };
+                thread::spawn(move || {
+                    let mut net =
+                        NetGame::connect(socket, config, Duration::from_secs(10)).unwrap();
+                    net.loss = loss;
+                    assert_eq!(net.setup().skill, 0);
+                    assert_eq!(net.setup().players, n as u8);
+                    body(net)
+                })
+            })
+            .collect();
+        threads.into_iter().map(|t| t.join().unwrap()).collect()
+    }
+
+    /// Run `tics` tics, each player's cmd being its player number and the tic
+    fn lockstep(mut net: NetGame, tics: u32) -> Vec<NetTic> {
+        let mut run = Vec::new();
+        let start = Instant::now();
+        while run.len() < tics as usize {
+            assert!(
+                start.elapsed() < Duration::from_secs(20),
+                "lockstep stalled"
+            );
+            if net.maketic() < tics {
+                let mut cmd = [net.player() as u8; CMD_SIZE];
+                cmd[4..8].copy_from_slice(&net.maketic().to_le_bytes());
+                net.make_tic(cmd);
+            }
+            while let Some(tic) = net.next_tic() {
+                run.push(tic);
+            }
+            sleep(Duration::from_millis(1));
+        }
+        // The quit isn't resent, so don't lose it
+        net.loss = 0;
+        net.quit();
+        run
+    }
+
+    fn check_runs(runs: &[Vec<NetTic>], players: usize, tics: u32) {
+        for run in runs {
+            assert_eq!(run, &runs[0]);
+            for (t, tic) in run.iter().enumerate() {
+                assert_eq!(tic.tic, t as u32);
+                for p in 0..players {
+                    let cmd = tic.cmds[p].unwrap();
+                    assert_eq!(cmd[0], p as u8);
+                    assert_eq!(u32::from_le_bytes(cmd[4..8].try_into().unwrap()), t as u32);
+                }
+            }
+            assert_eq!(run.len(), tics as usize);
+        }
+    }
+
+    #[test]
+    fn two_peers() {
+        let runs = run_peers(2, 0, |net| lockstep(net, 100));
+        check_runs(&runs, 2, 100);
+    }
+
+    #[test]
+    fn four_peers_with_loss() {
+        let runs = run_peers(4, 20, |net| lockstep(net, 100));
+        check_runs(&runs, 4, 100);
+    }
+
+    #[test]
+    fn peer_quits() {
+        let runs = run_peers(3, 0, |mut net| {
+            if net.player() == 2 {
+                return lockstep(net, 20);
+            }
+            let mut run = Vec::new();
+            let start = Instant::now();
+            while run.len() < 60 {
+                assert!(
+                    start.elapsed() < Duration::from_secs(20),
+                    "lockstep stalled"
+                );
+                let cmd = [net.player() as u8; CMD_SIZE];
+                net.make_tic(cmd);
+                while let Some(tic) = net.next_tic() {
+                    run.push(tic);
+                }
+                sleep(Duration::from_millis(1));
+            }
+            net.quit();
+            run.truncate(60);
+            run
+        });
+        for run in &runs[..2] {
+            assert_eq!(run, &runs[0]);
+            assert!(run[19].cmds[2].is_some());
+            assert!(run[20].cmds[2].is_none());
+            assert!(run[59].cmds[1].is_some());
+        }
+    }
+
+    #[test]
+    fn wad_mismatch() {
+        let sockets: Vec<UdpSocket> = (0..2)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let addrs: Vec<SocketAddr> = sockets.iter().map(|s| s.local_addr().unwrap()).collect();
+        let threads: Vec<_> = sockets
+            .into_iter()
+            .enumerate()
+            .map(|(player, socket)| {
+                let config = NetConfig {
+                    player,
+                    addrs: addrs.clone(),
+                    wad_checksum: player as u32,
+                    setup: NetSetup::default(),
+                };
+                thread::spawn(move || NetGame::connect(socket, config, Duration::from_secs(2)))
+            })
+            .collect();
+        for t in threads {
+            assert!(matches!(t.join().unwrap(), Err(NetError::WadMismatch(_))));
+        }
+    }
+
+    #[test]
+    fn packets_from_other_addresses_are_ignored() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let config = NetConfig {
+            player: 0,
+            addrs: vec![socket.local_addr().unwrap(), peer.local_addr().unwrap()],
+            wad_checksum: 1,
+            setup: NetSetup::default(),
+        };
+        let addr = config.addrs[0];
+        let thread =
+            thread::spawn(move || NetGame::connect(socket, config, Duration::from_millis(500)));
+
+        // Player 1's hello with other WADs, but not from player 1
+        let spoof = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let hello = Packet::Hello {
+            player: 1,
+            players: 2,
+            wad_checksum: 2,
+            heard: 0b11,
+            setup: None,
+        };
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(400) {
+            spoof.send_to(&hello.encode(), addr).unwrap();
+            sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(thread.join().unwrap(), Err(NetError::Timeout)));
+    }
+}
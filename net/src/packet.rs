@@ -0,0 +1,188 @@
+//! The packets peers send each other. All numbers are little endian.
+//!
+//! Every packet starts with `MAGIC`, a kind byte and the sender's player
+//! number. `Hello` is sent until every peer has heard from every other, and
+//! carries the WAD checksum and, from player 0, the game setup. `Cmds` carries
+//! the sender's unacknowledged tic cmds and acknowledges the receiver's. `Quit`
+//! is a final `Cmds`, after which the sender has left the game.
+//!
+//! Doom source name `d_net` (doomdata_t)
+
+use crate::{Cmd, NetSetup, BACKUPTICS, CMD_SIZE};
+
+const MAGIC: [u8; 4] = *b"R4DN";
+
+const KIND_HELLO: u8 = 0;
+const KIND_CMDS: u8 = 1;
+const KIND_QUIT: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Packet {
+    Hello {
+        player: u8,
+        /// Number of players the sender was started with
+        players: u8,
+        wad_checksum: u32,
+        /// Bit set for each player the sender has heard from
+        heard: u8,
+        /// Only sent by player 0
+        setup: Option<NetSetup>,
+    },
+    Cmds(CmdsPacket),
+    Quit(CmdsPacket),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CmdsPacket {
+    pub player: u8,
+    /// The sender has every cmd of the receiver below this tic
+    pub ack: u32,
+    /// Tic of the first cmd
+    pub start: u32,
+    pub cmds: Vec<Cmd>,
+}
+
+impl Packet {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = MAGIC.to_vec();
+        match self {
+            Packet::Hello {
+                player,
+                players,
+                wad_checksum,
+                heard,
+                setup,
+            } => {
+                buf.extend_from_slice(&[KIND_HELLO, *player, *players]);
+                buf.extend_from_slice(&wad_checksum.to_le_bytes());
+                buf.push(*heard);
+                match setup {
+                    Some(s) => {
+                        buf.push(1);
+                        buf.extend_from_slice(&s.to_bytes());
+                    }
+                    None => buf.push(0),
+                }
+            }
+            Packet::Cmds(c) => c.encode(KIND_CMDS, &mut buf),
+            Packet::Quit(c) => c.encode(KIND_QUIT, &mut buf),
+        }
+        buf
+    }
+
+    /// `None` if the packet isn't one of ours or is malformed
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.get(..4)? != MAGIC {
+            return None;
+        }
+        let kind = *data.get(4)?;
+        let player = *data.get(5)?;
+        let u32_at = |i: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(data.get(i..i + 4)?.try_into().ok()?))
+        };
+        match kind {
+            KIND_HELLO => {
+                let setup = match data.get(12)? {
+                    0 => None,
+                    _ => Some(NetSetup::from_bytes(data.get(13..13 + NetSetup::SIZE)?)?),
+                };
+                Some(Packet::Hello {
+                    player,
+                    players: *data.get(6)?,
+                    wad_checksum: u32_at(7)?,
+                    heard: *data.get(11)?,
+                    setup,
+                })
+            }
+            KIND_CMDS | KIND_QUIT => {
+                let count = *data.get(14)? as usize;
+                if count > BACKUPTICS {
+                    return None;
+                }
+                let cmds = data
+                    .get(15..15 + count * CMD_SIZE)?
+                    .chunks_exact(CMD_SIZE)
+                    .map(|c| c.try_into().unwrap())
+                    .collect();
+                let cmds = CmdsPacket {
+                    player,
+                    ack: u32_at(6)?,
+                    start: u32_at(10)?,
+                    cmds,
+                };
+                Some(if kind == KIND_CMDS {
+                    Packet::Cmds(cmds)
+                } else {
+                    Packet::Quit(cmds)
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl CmdsPacket {
+    fn encode(&self, kind: u8, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[kind, self.player]);
+        buf.extend_from_slice(&self.ack.to_le_bytes());
+        buf.extend_from_slice(&self.start.to_le_bytes());
+        buf.push(self.cmds.len() as u8);
+        for cmd in self.cmds.iter() {
+            buf.extend_from_slice(cmd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let hello = Packet::Hello {
+            player: 0,
+            players: 3,
+            wad_checksum: 0xDEADBEEF,
+            heard: 0b101,
+            setup: Some(NetSetup {
+                skill: 3,
+                episode: 1,
+                map: 4,
+                deathmatch: 2,
+                no_monsters: true,
+                respawn: false,
+                fast: true,
+                players: 3,
+                time_limit: 20,
+                frag_limit: 10,
+                free_look: true,
+                autoaim: false,
+                jump_crouch: true,
+                fixed_point: true,
+            }),
+        };
+        assert_eq!(Packet::decode(&hello.encode()), Some(hello));
+
+        let cmds = Packet::Quit(CmdsPacket {
+            player: 2,
+            ack: 40,
+            start: 38,
+            cmds: vec![[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], [9; CMD_SIZE]],
+        });
+        assert_eq!(Packet::decode(&cmds.encode()), Some(cmds));
+    }
+
+    #[test]
+    fn reject_bad_packets() {
+        assert_eq!(Packet::decode(b"XXXX\x01\x00"), None);
+        let mut data = Packet::Cmds(CmdsPacket {
+            player: 1,
+            ack: 0,
+            start: 0,
+            cmds: vec![[0; CMD_SIZE]],
+        })
+        .encode();
+        data.pop();
+        assert_eq!(Packet::decode(&data), None);
+    }
+}
@@ -1,18 +1,31 @@
 //! A generic `PixelBuf` that can be drawn to and is blitted to screen by the
 //! game, and a generic `PlayRenderer` for rendering the players view of the
 //! level.
+//!
+//! Showing the buffer in an SDL2 window needs the `display` feature, without
+//! it a `RenderTarget` is only drawn to in memory.
 
+#[cfg(feature = "display")]
 pub mod shaders;
 
 use gameplay::{Level, PicData, Player};
+#[cfg(feature = "display")]
 use golem::{ColorFormat, Context, GolemError, Texture, TextureFilter};
+#[cfg(feature = "display")]
 use sdl2::rect::Rect;
+#[cfg(feature = "display")]
 use sdl2::render::{Canvas, TextureCreator};
+#[cfg(feature = "display")]
 use sdl2::video::{Window, WindowContext};
+#[cfg(feature = "display")]
 use sdl2::{pixels, surface};
+#[cfg(feature = "display")]
 use shaders::basic::Basic;
+#[cfg(feature = "display")]
 use shaders::cgwg_crt::Cgwgcrt;
+#[cfg(feature = "display")]
 use shaders::lottes_crt::LottesCRT;
+#[cfg(feature = "display")]
 use shaders::{ShaderDraw, Shaders};
 
 const CHANNELS: usize = 4;
@@ -39,6 +52,11 @@ pub trait PixelBuffer {
     fn set_pixel(&mut self, x: usize, y: usize, rgba: &[u8; 4]);
     fn read_pixel(&self, x: usize, y: usize) -> [u8; 4];
     fn read_pixels(&mut self) -> &mut [u8];
+    /// Set a pixel to `rgba`, the colour of palette entry `index`, and keep the
+    /// index for blending translucent things over it
+    fn set_palette_pixel(&mut self, x: usize, y: usize, index: u8, rgba: &[u8; 4]);
+    /// The palette index last drawn at X|Y by `set_palette_pixel`
+    fn read_palette_index(&self, x: usize, y: usize) -> u8;
 }
 
 pub struct BufferSize {
@@ -100,6 +118,10 @@ pub struct Buffer {
     size: BufferSize,
     /// Total length is width * height * CHANNELS, where CHANNELS is RGB bytes
     buffer: Vec<u8>,
+    /// The palette index of each pixel of the player view, width * height.
+    /// Colours can't be mapped back to the palette exactly as some are
+    /// repeated or differ only in the low bits
+    indexes: Vec<u8>,
     stride: usize,
 }
 
@@ -119,6 +141,7 @@ impl Buffer {
                 half_height_f32: height as f32 / 2.0,
             },
             buffer: vec![0; (width * height) * CHANNELS],
+            indexes: vec![0; width * height],
             stride: width * CHANNELS,
         }
     }
@@ -174,14 +197,32 @@ impl PixelBuffer for Buffer {
     fn read_pixels(&mut self) -> &mut [u8] {
         &mut self.buffer
     }
+
+    fn set_palette_pixel(&mut self, x: usize, y: usize, index: u8, rgba: &[u8; 4]) {
+        self.set_pixel(x, y, rgba);
+        #[cfg(not(feature = "safety_check"))]
+        unsafe {
+            *self.indexes.get_unchecked_mut(y * self.size.width + x) = index;
+        }
+        #[cfg(feature = "safety_check")]
+        {
+            self.indexes[y * self.size.width + x] = index;
+        }
+    }
+
+    fn read_palette_index(&self, x: usize, y: usize) -> u8 {
+        self.indexes[y * self.size.width + x]
+    }
 }
 
 /// A structure holding display data
+#[cfg(feature = "display")]
 pub struct SoftFramebuffer {
     crop_rect: Rect,
     tex_creator: TextureCreator<WindowContext>,
 }
 
+#[cfg(feature = "display")]
 impl SoftFramebuffer {
     fn new(canvas: &Canvas<Window>) -> Self {
         let wsize = canvas.window().drawable_size();
@@ -198,11 +239,13 @@ impl SoftFramebuffer {
 }
 
 /// A structure holding display data
+#[cfg(feature = "display")]
 pub struct SoftOpenGL {
     gl_texture: Texture,
     screen_shader: Box<dyn ShaderDraw>,
 }
 
+#[cfg(feature = "display")]
 impl SoftOpenGL {
     fn new(width: usize, height: usize, gl_ctx: &Context, screen_shader: Shaders) -> Self {
         let mut gl_texture = Texture::new(gl_ctx).unwrap();
@@ -245,7 +288,9 @@ pub struct RenderTarget {
     render_type: RenderType,
     buffer: Buffer,
     /// Total length is width * height * CHANNELS, where CHANNELS is RGB bytes
+    #[cfg(feature = "display")]
     software: Option<SoftFramebuffer>,
+    #[cfg(feature = "display")]
     soft_opengl: Option<SoftOpenGL>,
 }
 
@@ -256,7 +301,9 @@ impl RenderTarget {
             height,
             render_type: RenderType::Software,
             buffer: Buffer::new(width, height),
+            #[cfg(feature = "display")]
             software: None,
+            #[cfg(feature = "display")]
             soft_opengl: None,
         }
     }
@@ -266,6 +313,7 @@ impl RenderTarget {
         &mut self.buffer
     }
 
+    #[cfg(feature = "display")]
     pub fn with_software(mut self, canvas: &Canvas<Window>) -> Self {
         if self.soft_opengl.is_some() {
             panic!("Rendering already set up for software-opengl");
@@ -275,6 +323,7 @@ impl RenderTarget {
         self
     }
 
+    #[cfg(feature = "display")]
     pub fn with_gl(
         mut self,
         canvas: &Canvas<Window>,
@@ -312,6 +361,7 @@ impl RenderTarget {
         self.render_type
     }
 
+    #[cfg(feature = "display")]
     pub fn software(&mut self) -> Option<&mut SoftFramebuffer> {
         self.software.as_mut()
     }
@@ -320,11 +370,12 @@ impl RenderTarget {
     ///
     /// The software framebuffer must not be `None`. Only use if software is
     /// used.
-
+    #[cfg(feature = "display")]
     pub unsafe fn software_unchecked(&mut self) -> &mut SoftFramebuffer {
         self.software.as_mut().unwrap_unchecked()
     }
 
+    #[cfg(feature = "display")]
     pub fn soft_opengl(&mut self) -> Option<&mut SoftOpenGL> {
         self.soft_opengl.as_mut()
     }
@@ -332,11 +383,12 @@ impl RenderTarget {
     /// # Safety
     ///
     /// The opengl framebuffer must not be `None`. Only use if opengl is used.
-
+    #[cfg(feature = "display")]
     pub unsafe fn soft_opengl_unchecked(&mut self) -> &mut SoftOpenGL {
         self.soft_opengl.as_mut().unwrap_unchecked()
     }
 
+    #[cfg(feature = "display")]
     pub fn blit(&mut self, sdl_canvas: &mut Canvas<Window>) {
         match self.render_type {
             RenderType::SoftOpenGL => {
@@ -385,6 +437,25 @@ pub trait PlayRenderer {
     );
 }
 
+#[cfg(test)]
+mod buffer_tests {
+    use crate::{Buffer, PixelBuffer};
+
+    #[test]
+    fn palette_index_kept_for_same_colour() {
+        let mut pixels = Buffer::new(320, 200);
+        // Two palette entries of the same colour, as PLAYPAL has
+        let black = [0, 0, 0, 255];
+        pixels.set_palette_pixel(10, 10, 0, &black);
+        pixels.set_palette_pixel(319, 199, 247, &black);
+
+        assert_eq!(pixels.read_pixel(10, 10), black);
+        assert_eq!(pixels.read_pixel(319, 199), black);
+        assert_eq!(pixels.read_palette_index(10, 10), 0);
+        assert_eq!(pixels.read_palette_index(319, 199), 247);
+    }
+}
+
 // TODO: somehow test with gl context
 // #[cfg(test)]
 // mod tests {
@@ -1,4 +1,4 @@
-use super::defs::ClipRange;
+use super::defs::{ClipRange, RenderSector};
 use super::segs::SegRender;
 use super::things::VisSprite;
 use super::RenderData;
@@ -7,12 +7,13 @@ use crate::utilities::{
 };
 use gameplay::log::trace;
 use gameplay::{
-    Angle, Level, MapData, MapObject, Node, PicData, Player, Sector, Segment, SubSector
+    Angle, Level, MapData, MapObject, Node, PicData, Player, Random, Segment, SubSector
 };
 use glam::Vec3;
 use render_target::{PixelBuffer, PlayRenderer, RenderTarget};
 use std::f32::consts::PI;
 use std::mem;
+use std::time::{Duration, Instant};
 
 const MAX_SEGS: usize = 128;
 const MAX_VIS_SPRITES: usize = 1024;
@@ -68,6 +69,22 @@ pub struct SoftwareRenderer {
     pub y_scale: f32,
     /// Mostly used in thing drawing only
     pub projection: f32,
+    /// Time spent in each phase of rendering since last taken
+    timings: RenderTimings,
+    /// Picks the pixels skipped when drawing shadow (fuzz) sprites
+    pub(super) fuzz_random: Random,
+    /// Draw things flagged `Translucent` with the TRANMAP, set from the
+    /// level's `GameOptions::translucency` each frame
+    pub(super) translucency: bool,
+}
+
+/// Time spent in each phase of `render_player_view`, used by timedemo
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderTimings {
+    /// BSP traversal and drawing of walls and flats
+    pub bsp: Duration,
+    /// Sprites, masked mid-textures and the player weapon
+    pub masked: Duration,
 }
 
 impl PlayRenderer for SoftwareRenderer {
@@ -88,8 +105,16 @@ impl PlayRenderer for SoftwareRenderer {
 
         pic_data.set_fixed_lightscale(player.fixedcolormap as usize);
         pic_data.set_player_palette(player);
+        self.translucency = level.options.translucency;
+        // BOOM deep water: pick the colourmap for the side of the fake planes
+        // the view is on
+        let mobj = unsafe { player.mobj_unchecked() };
+        pic_data.set_view_colourmap(mobj.subsector.sector.colourmap_for_view(player.viewz));
 
         self.seg_renderer.clear();
+        self.seg_renderer
+            .set_view_pitch(player.pitch, buffer.pixel_buffer());
+        let start = Instant::now();
         self.render_bsp_node(
             map,
             player,
@@ -100,8 +125,11 @@ impl PlayRenderer for SoftwareRenderer {
         );
         trace!("BSP traversals for render: {count}");
         // TODO: netupdate again
+        let bsp_done = Instant::now();
         self.draw_masked(player, pic_data, buffer.pixel_buffer());
         // TODO: netupdate again
+        self.timings.bsp += bsp_done - start;
+        self.timings.masked += bsp_done.elapsed();
     }
 }
 
@@ -125,9 +153,17 @@ impl SoftwareRenderer {
             next_vissprite: 0,
             y_scale,
             projection,
+            timings: RenderTimings::default(),
+            fuzz_random: Random::new(),
+            translucency: false,
         }
     }
 
+    /// Take the time spent rendering since this was last called
+    pub fn take_timings(&mut self) -> RenderTimings {
+        mem::take(&mut self.timings)
+    }
+
     fn clear(&mut self, screen_width: f32) {
         for vis in self.vissprites.iter_mut() {
             *vis = unsafe { mem::zeroed::<VisSprite>() };
@@ -143,7 +179,7 @@ impl SoftwareRenderer {
         &'a mut self,
         player: &Player,
         seg: &'a Segment,
-        front_sector: &'a Sector,
+        front_sector: &RenderSector,
         pic_data: &PicData,
         pixels: &mut dyn PixelBuffer,
     ) {
@@ -207,7 +243,14 @@ impl SoftwareRenderer {
             return;
         }
 
-        if let Some(back_sector) = seg.backsector.clone() {
+        let back_sector = seg.backsector.as_ref().map(|back| {
+            let view_heightsec = mobj.subsector.sector.heightsec.as_deref();
+            RenderSector::fake_flat(back, view_heightsec, player.viewz, pic_data.sky_num(), true)
+        });
+        self.seg_renderer.frontsector = *front_sector;
+        self.seg_renderer.backsector = back_sector;
+
+        if let Some(back_sector) = back_sector {
             // Doors. Block view
             if back_sector.ceilingheight <= front_sector.floorheight
                 || back_sector.floorheight >= front_sector.ceilingheight
@@ -231,6 +274,7 @@ impl SoftwareRenderer {
             if back_sector.ceilingpic == front_sector.ceilingpic
                 && back_sector.floorpic == front_sector.floorpic
                 && back_sector.lightlevel == front_sector.lightlevel
+                && back_sector.colourmap == front_sector.colourmap
                 && seg.sidedef.midtexture.is_none()
             {
                 return;
@@ -251,13 +295,26 @@ impl SoftwareRenderer {
         pic_data: &PicData,
         pixels: &mut dyn PixelBuffer,
     ) {
-        let front_sector = &subsect.sector;
+        let mobj = unsafe { player.mobj_unchecked() };
+        let front_sector = RenderSector::fake_flat(
+            &subsect.sector,
+            mobj.subsector.sector.heightsec.as_deref(),
+            player.viewz,
+            pic_data.sky_num(),
+            false,
+        );
 
-        self.add_sprites(player, front_sector, pixels.size().width() as u32, pic_data);
+        self.add_sprites(
+            player,
+            &subsect.sector,
+            &front_sector,
+            pixels.size().width() as u32,
+            pic_data,
+        );
 
         for i in subsect.start_seg..subsect.start_seg + subsect.seg_count {
             let seg = &map.segments()[i as usize];
-            self.add_line(player, seg, front_sector, pic_data, pixels);
+            self.add_line(player, seg, &front_sector, pic_data, pixels);
         }
     }
 
@@ -1,7 +1,7 @@
 use std::fmt::Debug;
 use std::ptr::NonNull;
 
-use gameplay::Segment;
+use gameplay::{Sector, Segment};
 
 pub const SIL_NONE: i32 = 0;
 pub const SIL_BOTTOM: i32 = 1;
@@ -57,6 +57,125 @@ impl DrawSeg {
     }
 }
 
+/// The sector values used for drawing a subsector or seg. BOOM deep water
+/// sectors (linedef 242) substitute the heights, flats and light of their
+/// control sector depending on where the view is.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RenderSector {
+    pub floorheight: f32,
+    pub ceilingheight: f32,
+    pub floorpic: usize,
+    pub ceilingpic: usize,
+    pub lightlevel: usize,
+    pub floor_xoffs: f32,
+    pub floor_yoffs: f32,
+    pub ceil_xoffs: f32,
+    pub ceil_yoffs: f32,
+    /// BOOM colourmap set to light the sector with
+    pub colourmap: usize,
+}
+
+impl RenderSector {
+    pub fn new(sector: &Sector) -> Self {
+        Self {
+            floorheight: sector.floorheight,
+            ceilingheight: sector.ceilingheight,
+            floorpic: sector.floorpic,
+            ceilingpic: sector.ceilingpic,
+            lightlevel: sector.lightlevel,
+            floor_xoffs: sector.floor_xoffs,
+            floor_yoffs: sector.floor_yoffs,
+            ceil_xoffs: sector.ceil_xoffs,
+            ceil_yoffs: sector.ceil_yoffs,
+            colourmap: 0,
+        }
+    }
+
+    /// Substitute the fake planes of a deep water sector, as seen from
+    /// `viewz` which is inside a sector with the `view_heightsec` control
+    /// sector. `back` is true if this is the sector behind a seg. The
+    /// colourmap is the viewer's, every sector is drawn with it.
+    ///
+    /// Doom function name `R_FakeFlat` (BOOM)
+    pub fn fake_flat(
+        sector: &Sector,
+        view_heightsec: Option<&Sector>,
+        viewz: f32,
+        sky_num: usize,
+        back: bool,
+    ) -> Self {
+        let mut fake = Self::new(sector);
+        fake.colourmap = view_heightsec.map_or(0, |h| h.control_colourmap(viewz));
+        let Some(s) = sector.heightsec.as_ref() else {
+            return fake;
+        };
+        fake.fake_planes(sector, s, view_heightsec, viewz, sky_num, back);
+        fake
+    }
+
+    /// Substitute the planes of `sector` with those of its control sector `s`
+    fn fake_planes(
+        &mut self,
+        sector: &Sector,
+        s: &Sector,
+        view_heightsec: Option<&Sector>,
+        viewz: f32,
+        sky_num: usize,
+        back: bool,
+    ) {
+        let fake = self;
+        // Replace floor and ceiling height with other sector's heights.
+        fake.floorheight = s.floorheight;
+        fake.ceilingheight = s.ceilingheight;
+
+        let underwater = view_heightsec.is_some_and(|h| viewz <= h.floorheight);
+        if underwater {
+            // prevent sudden light changes from non-water sectors
+            fake.floorheight = sector.floorheight;
+            fake.ceilingheight = s.floorheight - 1.0;
+            if !back {
+                // head-below-floor hack
+                fake.floorpic = s.floorpic;
+                fake.floor_xoffs = s.floor_xoffs;
+                fake.floor_yoffs = s.floor_yoffs;
+
+                if s.ceilingpic == sky_num {
+                    fake.floorheight = fake.ceilingheight + 1.0;
+                    fake.ceilingpic = fake.floorpic;
+                    fake.ceil_xoffs = fake.floor_xoffs;
+                    fake.ceil_yoffs = fake.floor_yoffs;
+                } else {
+                    fake.ceilingpic = s.ceilingpic;
+                    fake.ceil_xoffs = s.ceil_xoffs;
+                    fake.ceil_yoffs = s.ceil_yoffs;
+                }
+                fake.lightlevel = s.lightlevel;
+            }
+        } else if view_heightsec.is_some_and(|h| viewz >= h.ceilingheight)
+            && sector.ceilingheight > s.ceilingheight
+        {
+            // above-ceiling hack
+            fake.ceilingheight = s.ceilingheight;
+            fake.floorheight = s.ceilingheight + 1.0;
+
+            fake.floorpic = s.ceilingpic;
+            fake.ceilingpic = s.ceilingpic;
+            fake.floor_xoffs = s.ceil_xoffs;
+            fake.ceil_xoffs = s.ceil_xoffs;
+            fake.floor_yoffs = s.ceil_yoffs;
+            fake.ceil_yoffs = s.ceil_yoffs;
+
+            if s.floorpic != sky_num {
+                fake.ceilingheight = sector.ceilingheight;
+                fake.floorpic = s.floorpic;
+                fake.floor_xoffs = s.floor_xoffs;
+                fake.floor_yoffs = s.floor_yoffs;
+            }
+            fake.lightlevel = s.lightlevel;
+        }
+    }
+}
+
 /// The range of columns on the screen clipped against
 #[derive(Copy, Clone)]
 pub struct ClipRange {
@@ -94,11 +213,108 @@ impl PortalClip {
 
 #[cfg(test)]
 mod tests {
-    use super::PortalClip;
+    use gameplay::Sector;
+
+    use super::{PortalClip, RenderSector};
+
+    const SKY: usize = 9;
 
     #[test]
     fn default_portal_clip() {
         let mut rd = PortalClip::new(640, 400);
         rd.clear();
     }
+
+    /// A sector 0-256 high with its control sector's water at 64 and a fake
+    /// ceiling at 192
+    fn deep_water() -> (Sector, Sector) {
+        let sector = Sector::new(0, 0.0, 256.0, 1, 2, 160, 0, 0);
+        let water = Sector::new(1, 64.0, 192.0, 3, 4, 100, 0, 0);
+        (sector, water)
+    }
+
+    fn planes(sector: &Sector, water: &Sector, viewz: f32, back: bool) -> RenderSector {
+        let mut fake = RenderSector::new(sector);
+        fake.fake_planes(sector, water, Some(water), viewz, SKY, back);
+        fake
+    }
+
+    #[test]
+    fn fake_flat_inside_the_water_band() {
+        let (sector, water) = deep_water();
+        let fake = planes(&sector, &water, 100.0, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (64.0, 192.0));
+        assert_eq!(
+            (fake.floorpic, fake.ceilingpic, fake.lightlevel),
+            (1, 2, 160)
+        );
+
+        // seen from a sector without deep water
+        let mut fake = RenderSector::new(&sector);
+        fake.fake_planes(&sector, &water, None, 32.0, SKY, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (64.0, 192.0));
+    }
+
+    #[test]
+    fn fake_flat_below_the_water() {
+        let (sector, mut water) = deep_water();
+        let fake = planes(&sector, &water, 32.0, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (0.0, 63.0));
+        assert_eq!(
+            (fake.floorpic, fake.ceilingpic, fake.lightlevel),
+            (3, 4, 100)
+        );
+
+        // the sector behind a seg keeps its own flats and light
+        let back = planes(&sector, &water, 32.0, true);
+        assert_eq!((back.floorheight, back.ceilingheight), (0.0, 63.0));
+        assert_eq!(
+            (back.floorpic, back.ceilingpic, back.lightlevel),
+            (1, 2, 160)
+        );
+
+        // a sky ceiling on the control sector closes the water over
+        water.ceilingpic = SKY;
+        let fake = planes(&sector, &water, 32.0, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (64.0, 63.0));
+        assert_eq!((fake.floorpic, fake.ceilingpic), (3, 3));
+    }
+
+    #[test]
+    fn fake_flat_colourmap_is_the_viewers() {
+        let (sector, mut water) = deep_water();
+        (water.bottommap, water.midmap, water.topmap) = (1, 2, 3);
+        let colourmap = |view_heightsec, viewz| {
+            RenderSector::fake_flat(&sector, view_heightsec, viewz, SKY, false).colourmap
+        };
+
+        // the sector drawn has no deep water, the viewer's control sector
+        // picks the map
+        assert_eq!(colourmap(Some(&water), 32.0), 1);
+        assert_eq!(colourmap(Some(&water), 100.0), 2);
+        assert_eq!(colourmap(Some(&water), 220.0), 3);
+        assert_eq!(colourmap(None, 32.0), 0);
+
+        // on the planes, as the fake planes are picked
+        assert_eq!(colourmap(Some(&water), 64.0), 1);
+        assert_eq!(colourmap(Some(&water), 192.0), 3);
+        assert_eq!(planes(&sector, &water, 64.0, false).ceilingheight, 63.0);
+    }
+
+    #[test]
+    fn fake_flat_above_the_fake_ceiling() {
+        let (sector, mut water) = deep_water();
+        let fake = planes(&sector, &water, 220.0, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (193.0, 256.0));
+        assert_eq!(
+            (fake.floorpic, fake.ceilingpic, fake.lightlevel),
+            (3, 4, 100)
+        );
+
+        // a sky floor on the control sector shows its ceiling both ways
+        water.floorpic = SKY;
+        let fake = planes(&sector, &water, 220.0, false);
+        assert_eq!((fake.floorheight, fake.ceilingheight), (193.0, 192.0));
+        assert_eq!((fake.floorpic, fake.ceilingpic), (4, 4));
+    }
 }
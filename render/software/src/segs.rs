@@ -8,7 +8,9 @@ use std::ptr::NonNull;
 
 use crate::utilities::{point_to_dist, scale_from_view_angle};
 
-use super::defs::{DrawSeg, MAXDRAWSEGS, SIL_BOTH, SIL_BOTTOM, SIL_NONE, SIL_TOP};
+use super::defs::{
+    DrawSeg, RenderSector, MAXDRAWSEGS, SIL_BOTH, SIL_BOTTOM, SIL_NONE, SIL_TOP
+};
 use super::RenderData;
 
 //const HEIGHTUNIT: f32 = 0.062485;
@@ -26,6 +28,11 @@ pub(crate) struct SegRender {
     /// Current segment, e.g, `curline` in Doom src. We can use this to get the
     /// `sector_t *frontsector;` `sector_t *backsector;` shared variables
     /// between `r_bsp.c` and `r_seg.c`.
+    ///
+    /// The front and back sectors of the current seg as they should be drawn,
+    /// set by the BSP traversal.
+    pub(crate) frontsector: RenderSector,
+    pub(crate) backsector: Option<RenderSector>,
 
     /// True if any of the segs textures might be visible.
     segtextured: bool,
@@ -74,6 +81,9 @@ pub(crate) struct SegRender {
     /// Light level for the wall
     wall_lights: usize,
     pub yslope: Vec<f32>,
+    /// Screen row of the horizon. Moved from the middle of the screen by the
+    /// view pitch to shear the view
+    pub centery: f32,
     pub screen_x: Vec<f32>,
     pub fov: f32,
     pub fov_half: f32,
@@ -86,6 +96,8 @@ pub(crate) struct SegRender {
 impl SegRender {
     pub fn new(fov: f32, screen_width: usize, screen_height: usize) -> Self {
         Self {
+            frontsector: RenderSector::default(),
+            backsector: None,
             segtextured: false,
             markfloor: false,
             markceiling: false,
@@ -120,12 +132,8 @@ impl SegRender {
             wall_lights: 0,
             openings: vec![f32::MAX; screen_width * screen_height],
             lastopening: 0.0,
-            yslope: (0..=screen_height + 1)
-                .map(|y| {
-                    let dy = y as f32 - screen_height as f32 / 2.0;
-                    screen_width as f32 / 2.0 / dy.abs()
-                })
-                .collect(),
+            yslope: y_slopes(screen_width, screen_height, screen_height as f32 / 2.0),
+            centery: screen_height as f32 / 2.0,
             screen_x: (0..=screen_width)
                 .map(|x| screen_to_angle(fov, x as f32, (screen_width / 2) as f32))
                 .collect(),
@@ -142,6 +150,18 @@ impl SegRender {
         self.lastopening = 0.0;
     }
 
+    /// Shear the view for a player looking up or down by `pitch` radians,
+    /// moving the horizon
+    pub fn set_view_pitch(&mut self, pitch: f32, pixels: &dyn PixelBuffer) {
+        let size = pixels.size();
+        let shear = pitch.tan() * size.half_width_f32() * self.wide_ratio;
+        let centery = size.half_height_f32() + shear.round();
+        if centery != self.centery {
+            self.centery = centery;
+            self.yslope = y_slopes(size.width_usize(), size.height_usize(), centery);
+        }
+    }
+
     /// R_StoreWallRange - r_segs
     /// This is called by the BSP clipping functions. The incoming `start` and
     /// `stop` have already been `.floor()`ed by `angle_to_screen()` function
@@ -231,7 +251,7 @@ impl SegRender {
         // calculate texture boundaries
         //  and decide if floor / ceiling marks are needed
         // `seg.sidedef.sector` is the front sector
-        let frontsector = &seg.frontsector;
+        let frontsector = self.frontsector;
         self.worldtop = frontsector.ceilingheight - player.viewz;
         self.worldbottom = frontsector.floorheight - player.viewz;
 
@@ -248,30 +268,7 @@ impl SegRender {
         //     dbg!(seg);
         // }
 
-        if seg.backsector.is_none() {
-            // single sided line
-            self.markfloor = true;
-            self.markceiling = true;
-            self.midtexture = sidedef.midtexture.is_some();
-            if linedef.flags & LineDefFlags::UnpegBottom as u32 != 0 {
-                if let Some(mid_tex) = sidedef.midtexture {
-                    let texture_column = pic_data.wall_pic_column(mid_tex, 0);
-                    let vtop = frontsector.floorheight + texture_column.len() as f32;
-                    self.rw_midtexturemid = vtop - player.viewz;
-                }
-            } else {
-                // top of texture at top
-                self.rw_midtexturemid = self.worldtop;
-            }
-            self.rw_midtexturemid += sidedef.rowoffset;
-
-            ds_p.silhouette = SIL_BOTH;
-            ds_p.sprtopclip = Some(0.0); // start of screenheightarray
-            ds_p.sprbottomclip = Some(0.0); // start of negonearray
-            ds_p.bsilheight = f32::MAX;
-            ds_p.tsilheight = f32::MIN;
-        } else {
-            let backsector = seg.backsector.as_ref().unwrap();
+        if let Some(backsector) = self.backsector {
             // two sided line
             // TODO: when thing render started
             ds_p.sprtopclip = None;
@@ -322,6 +319,8 @@ impl SegRender {
             if self.worldlow != self.worldbottom
                 || backsector.floorpic != frontsector.floorpic
                 || backsector.lightlevel != frontsector.lightlevel
+                || backsector.floor_xoffs != frontsector.floor_xoffs
+                || backsector.floor_yoffs != frontsector.floor_yoffs
             {
                 self.markfloor = true;
             } else {
@@ -332,6 +331,8 @@ impl SegRender {
             if self.worldhigh != self.worldtop
                 || backsector.ceilingpic != frontsector.ceilingpic
                 || backsector.lightlevel != frontsector.lightlevel
+                || backsector.ceil_xoffs != frontsector.ceil_xoffs
+                || backsector.ceil_yoffs != frontsector.ceil_yoffs
             {
                 self.markceiling = true;
             } else {
@@ -380,6 +381,28 @@ impl SegRender {
 
             self.lastopening += self.rw_stopx - self.rw_startx;
             // }
+        } else {
+            // single sided line
+            self.markfloor = true;
+            self.markceiling = true;
+            self.midtexture = sidedef.midtexture.is_some();
+            if linedef.flags & LineDefFlags::UnpegBottom as u32 != 0 {
+                if let Some(mid_tex) = sidedef.midtexture {
+                    let texture_column = pic_data.wall_pic_column(mid_tex, 0);
+                    let vtop = frontsector.floorheight + texture_column.len() as f32;
+                    self.rw_midtexturemid = vtop - player.viewz;
+                }
+            } else {
+                // top of texture at top
+                self.rw_midtexturemid = self.worldtop;
+            }
+            self.rw_midtexturemid += sidedef.rowoffset;
+
+            ds_p.silhouette = SIL_BOTH;
+            ds_p.sprtopclip = Some(0.0); // start of screenheightarray
+            ds_p.sprbottomclip = Some(0.0); // start of negonearray
+            ds_p.bsilheight = f32::MAX;
+            ds_p.tsilheight = f32::MIN;
         }
 
         // calculate rw_offset (only needed for textured lines)
@@ -394,7 +417,7 @@ impl SegRender {
             //  }
             self.rw_offset += sidedef.textureoffset + seg.offset;
             self.rw_centerangle = mobj.angle - self.rw_normalangle;
-            self.wall_lights = (sidedef.sector.lightlevel >> 4) + player.extralight;
+            self.wall_lights = (frontsector.lightlevel >> 4) + player.extralight;
             if (seg.angle.rad().abs() == PI || seg.angle.rad() == 0.0) && self.wall_lights > 0 {
                 self.wall_lights -= 1;
             }
@@ -414,14 +437,14 @@ impl SegRender {
             self.markceiling = false;
         }
 
-        let half_height = pixels.size().half_height_f32();
+        let half_height = self.centery;
         self.topstep = -(self.worldtop * self.rw_scalestep);
         self.topfrac = half_height - (self.worldtop * self.rw_scale) + 1.0;
 
         self.bottomstep = -(self.worldbottom * self.rw_scalestep);
         self.bottomfrac = half_height - (self.worldbottom * self.rw_scale);
 
-        if seg.backsector.is_some() {
+        if self.backsector.is_some() {
             if self.worldhigh < self.worldtop {
                 self.pixhigh = half_height - (self.worldhigh * self.rw_scale);
                 self.pixhighstep = -(self.worldhigh * self.rw_scalestep);
@@ -510,13 +533,35 @@ impl SegRender {
         let mut angle;
         let mut texture_column = 0;
 
-        let flats_total_light = (seg.frontsector.lightlevel >> 4) + player.extralight;
-        let ceil_height = (seg.frontsector.ceilingheight - player.viewz).abs();
-        let ceil_tex = pic_data.get_flat(seg.frontsector.ceilingpic);
-        let floor_height = (seg.frontsector.floorheight - player.viewz).abs();
-        let floor_tex = pic_data.get_flat(seg.frontsector.floorpic);
+        let front = self.frontsector;
+        let flats_total_light = (front.lightlevel >> 4) + player.extralight;
+        let ceil_height = (front.ceilingheight - player.viewz).abs();
+        let ceil_tex = pic_data.get_flat(front.ceilingpic);
+        let floor_height = (front.floorheight - player.viewz).abs();
+        let floor_tex = pic_data.get_flat(front.floorpic);
+        // Scrolled flats are drawn by shifting the view origin
+        let ceil_origin = mobj.xyz + Vec3::new(front.ceil_xoffs, -front.ceil_yoffs, 0.0);
+        let floor_origin = mobj.xyz + Vec3::new(front.floor_xoffs, -front.floor_yoffs, 0.0);
 
         let sky_colourmap = pic_data.colourmap(0);
+        // MBF sky transfer (linedef 271/272): the sky texture and offsets are
+        // taken from the line's front upper texture. 271 is mirrored.
+        let (sky_pic, sky_offset, sky_mid, sky_flip) = seg
+            .frontsector
+            .sky_line
+            .as_ref()
+            .and_then(|line| {
+                let side = &line.front_sidedef;
+                side.toptexture.map(|tex| {
+                    (
+                        tex,
+                        side.textureoffset,
+                        self.sky_mid + side.rowoffset,
+                        line.special == 271,
+                    )
+                })
+            })
+            .unwrap_or((pic_data.sky_pic(), 0.0, self.sky_mid, false));
 
         let sidedef = seg.sidedef.clone();
 
@@ -542,25 +587,31 @@ impl SegRender {
                     bottom = rdata.portal_clip.floorclip[clip_index] - 1.0;
                 }
                 if top <= bottom {
-                    if seg.frontsector.ceilingpic == pic_data.sky_num() {
+                    if front.ceilingpic == pic_data.sky_num() {
                         let screen_x_degrees = screen_to_angle(
                             self.fov,
                             self.rw_startx,
                             pixels.size().half_width_f32(),
                         );
-                        let sky_angle =
+                        let mut sky_angle =
                             (mobj.angle.rad() + screen_x_degrees + TAU * 2.).to_degrees() * 2.8444; // 2.8444 seems to give the corect skybox width
-                        let sky_column = pic_data
-                            .wall_pic_column(pic_data.sky_pic(), sky_angle.abs() as u32 as usize);
+                        sky_angle += sky_offset / 64.0; // 64 units of offset per column
+                        if sky_flip {
+                            // one full turn is 1024 columns
+                            sky_angle = 1024.0 - sky_angle.rem_euclid(1024.0);
+                        }
+                        let sky_column =
+                            pic_data.wall_pic_column(sky_pic, sky_angle.abs() as u32 as usize);
 
                         draw_wall_column(
                             sky_column,
                             sky_colourmap,
                             0.94,
                             self.rw_startx,
-                            self.sky_mid,
+                            sky_mid,
                             top,
                             bottom,
+                            self.centery,
                             pic_data,
                             self.sky_doubled,
                             pixels,
@@ -569,9 +620,10 @@ impl SegRender {
                         let x_start = self.rw_startx as u32 as usize;
                         draw_flat_column(
                             ceil_tex,
-                            mobj.xyz,
+                            ceil_origin,
                             ceil_height,
                             flats_total_light,
+                            front.colourmap,
                             x_start,
                             self.screen_x[x_start],
                             mobj.angle,
@@ -603,9 +655,10 @@ impl SegRender {
                     let x_start = self.rw_startx as u32 as usize;
                     draw_flat_column(
                         floor_tex,
-                        mobj.xyz,
+                        floor_origin,
                         floor_height,
                         flats_total_light,
+                        front.colourmap,
                         x_start,
                         self.screen_x[x_start],
                         mobj.angle,
@@ -638,12 +691,17 @@ impl SegRender {
                         let texture_column = pic_data.wall_pic_column(mid_tex, texture_column);
                         draw_wall_column(
                             texture_column,
-                            pic_data.vert_light_colourmap(self.wall_lights, self.rw_scale),
+                            pic_data.vert_light_colourmap(
+                                self.wall_lights,
+                                self.rw_scale,
+                                front.colourmap,
+                            ),
                             dc_iscale,
                             self.rw_startx,
                             self.rw_midtexturemid,
                             yl,
                             yh,
+                            self.centery,
                             pic_data,
                             false,
                             pixels,
@@ -666,12 +724,17 @@ impl SegRender {
                             let texture_column = pic_data.wall_pic_column(top_tex, texture_column);
                             draw_wall_column(
                                 texture_column,
-                                pic_data.vert_light_colourmap(self.wall_lights, self.rw_scale),
+                                pic_data.vert_light_colourmap(
+                                    self.wall_lights,
+                                    self.rw_scale,
+                                    front.colourmap,
+                                ),
                                 dc_iscale,
                                 self.rw_startx,
                                 self.rw_toptexturemid,
                                 yl,
                                 mid,
+                                self.centery,
                                 pic_data,
                                 false,
                                 pixels,
@@ -698,12 +761,17 @@ impl SegRender {
                             let texture_column = pic_data.wall_pic_column(bot_tex, texture_column);
                             draw_wall_column(
                                 texture_column,
-                                pic_data.vert_light_colourmap(self.wall_lights, self.rw_scale),
+                                pic_data.vert_light_colourmap(
+                                    self.wall_lights,
+                                    self.rw_scale,
+                                    front.colourmap,
+                                ),
                                 dc_iscale,
                                 self.rw_startx,
                                 self.rw_bottomtexturemid,
                                 mid,
                                 yh,
+                                self.centery,
                                 pic_data,
                                 false,
                                 pixels,
@@ -749,6 +817,7 @@ pub fn draw_wall_column(
     dc_texturemid: f32,
     yl: f32,
     mut yh: f32,
+    centery: f32,
     pic_data: &PicData,
     doubled: bool,
     pixels: &mut dyn PixelBuffer,
@@ -757,7 +826,7 @@ pub fn draw_wall_column(
 
     let dc_x = dc_x as u32 as usize;
     let pal = pic_data.palette();
-    let mut frac = dc_texturemid + (yl - pixels.size().half_height_f32()) * fracstep;
+    let mut frac = dc_texturemid + (yl - centery) * fracstep;
     for y in yl as u32 as usize..=yh as u32 as usize {
         let mut select = frac.abs() as u32 as usize;
         if doubled {
@@ -770,12 +839,13 @@ pub fn draw_wall_column(
         }
         #[cfg(not(feature = "safety_check"))]
         unsafe {
-            let c = pal.get_unchecked(*colourmap.get_unchecked(tc));
-            pixels.set_pixel(dc_x, y, &c.0);
+            let px = *colourmap.get_unchecked(tc);
+            pixels.set_palette_pixel(dc_x, y, px as u8, &pal.get_unchecked(px).0);
         }
         #[cfg(feature = "safety_check")]
         {
-            pixels.set_pixel(dc_x, y as u32 as usize, &pal[colourmap[tc]].0);
+            let px = colourmap[tc];
+            pixels.set_palette_pixel(dc_x, y as u32 as usize, px as u8, &pal[px].0);
         }
         frac += fracstep;
     }
@@ -786,6 +856,7 @@ pub fn draw_flat_column(
     viewxy: Vec3,
     plane_height: f32,
     total_light: usize,
+    colourmap_set: usize,
     dc_x: usize,
     screen_x: f32,
     angle: Angle,
@@ -817,19 +888,30 @@ pub fn draw_flat_column(
         let y_step = ds_yfrac.abs() as u32 as usize & tex_len;
 
         // changed from `distance` to `length` to provide a radius light
-        let colourmap = pic_data.flat_light_colourmap(total_light, distance as u32 as usize);
+        let colourmap =
+            pic_data.flat_light_colourmap(total_light, distance as u32 as usize, colourmap_set);
         #[cfg(not(feature = "safety_check"))]
         unsafe {
             let px =
                 *colourmap.get_unchecked(*texture.data.get_unchecked(x_step).get_unchecked(y_step));
-            let c = pal.get_unchecked(px);
-            pixels.set_pixel(dc_x, y, &c.0);
+            pixels.set_palette_pixel(dc_x, y, px as u8, &pal.get_unchecked(px).0);
         }
         #[cfg(feature = "safety_check")]
         {
             let px = colourmap[texture.data[x_step][y_step]];
-            pixels.set_pixel(dc_x, y, &pal[px].0);
+            pixels.set_palette_pixel(dc_x, y, px as u8, &pal[px].0);
         }
     }
     // panic!()
 }
+
+/// Distance to a flat, per unit of height above or below the view, for each
+/// screen row with the horizon at `centery`
+fn y_slopes(screen_width: usize, screen_height: usize, centery: f32) -> Vec<f32> {
+    (0..=screen_height + 1)
+        .map(|y| {
+            let dy = y as f32 - centery;
+            screen_width as f32 / 2.0 / dy.abs()
+        })
+        .collect()
+}
@@ -3,12 +3,12 @@ use std::f32::consts::{FRAC_PI_2, TAU};
 
 use gameplay::log::{error, warn};
 use gameplay::{
-    p_random, point_to_angle_2, LineDefFlags, MapObjFlag, MapObject, PicData, Player, PspDef, Sector
+    point_to_angle_2, LineDefFlags, MapObjFlag, MapObject, PicData, Player, PspDef, Random, Sector
 };
 use render_target::PixelBuffer;
 
 use super::bsp::SoftwareRenderer;
-use super::defs::DrawSeg;
+use super::defs::{DrawSeg, RenderSector};
 
 const FF_FULLBRIGHT: u32 = 0x8000;
 const FF_FRAMEMASK: u32 = 0x7FFF;
@@ -16,6 +16,29 @@ const FF_FRAMEMASK: u32 = 0x7FFF;
 const FRAME_ROT_OFFSET: f32 = FRAC_PI_2 / 4.0;
 /// Divisor for selecting which frame rotation to use
 const FRAME_ROT_SELECT: f32 = 8.0 / TAU;
+/// Players 2-4 have the green range of the palette translated to indigo,
+/// brown and red
+const TRANSLATIONS: [[usize; 256]; 3] = translation_tables();
+
+/// Doom function name `R_InitTranslationTables`
+const fn translation_tables() -> [[usize; 256]; 3] {
+    let mut tables = [[0; 256]; 3];
+    let mut i = 0;
+    while i < 256 {
+        if i >= 0x70 && i <= 0x7f {
+            // map green ramp to gray, brown, red
+            tables[0][i] = 0x60 + (i & 0xf);
+            tables[1][i] = 0x40 + (i & 0xf);
+            tables[2][i] = 0x20 + (i & 0xf);
+        } else {
+            tables[0][i] = i;
+            tables[1][i] = i;
+            tables[2][i] = i;
+        }
+        i += 1;
+    }
+    tables
+}
 
 #[derive(Clone, Copy, PartialEq, Default)]
 pub struct VisSprite {
@@ -37,6 +60,8 @@ pub struct VisSprite {
     patch: usize,
     /// The index used to fetch colourmap for drawing
     light_level: usize,
+    /// The BOOM colourmap set of the sector the sprite is in
+    colourmap_set: usize,
     mobj_flags: u32,
 }
 
@@ -75,6 +100,7 @@ impl VisSprite {
             texture_mid: 0.0,
             patch: 0,
             light_level: 0,
+            colourmap_set: 0,
             mobj_flags: 0,
         }
     }
@@ -85,6 +111,7 @@ impl SoftwareRenderer {
         &'a mut self,
         player: &Player,
         sector: &'a Sector,
+        render_sector: &RenderSector,
         screen_width: u32,
         pic_data: &PicData,
     ) {
@@ -96,9 +123,17 @@ impl SoftwareRenderer {
         }
         self.checked_sectors.push(sector.num);
 
-        let light_level = (sector.lightlevel >> 4) + player.extralight;
+        let light_level = (render_sector.lightlevel >> 4) + player.extralight;
+        let colourmap_set = render_sector.colourmap;
         sector.run_func_on_thinglist(|thing| {
-            self.project_sprite(player, thing, light_level, screen_width, pic_data)
+            self.project_sprite(
+                player,
+                thing,
+                light_level,
+                colourmap_set,
+                screen_width,
+                pic_data,
+            )
         });
     }
 
@@ -118,6 +153,7 @@ impl SoftwareRenderer {
         player: &Player,
         thing: &MapObject,
         light_level: usize,
+        colourmap_set: usize,
         screen_width: u32,
         pic_data: &PicData,
     ) -> bool {
@@ -195,8 +231,14 @@ impl SoftwareRenderer {
         }
 
         let y_scale = self.y_scale;
+        let mut mobj_flags = thing.flags;
+        if !self.translucency {
+            // BOOM's `general_translucency` off
+            mobj_flags &= !(MapObjFlag::Translucent as u32);
+        }
         let vis = self.new_vissprite();
-        vis.mobj_flags = thing.flags;
+        vis.mobj_flags = mobj_flags;
+        vis.colourmap_set = colourmap_set;
         vis.scale = x_scale * y_scale; // Note: increase Y
         vis.gx = thing.xyz.x;
         vis.gy = thing.xyz.y;
@@ -237,10 +279,11 @@ impl SoftwareRenderer {
 
     // R_DrawSprite
     fn draw_vissprite(
-        &self,
+        &mut self,
         vis: &VisSprite,
         clip_bottom: &[f32],
         clip_top: &[f32],
+        centery: f32,
         pic_data: &PicData,
         pixels: &mut dyn PixelBuffer,
     ) {
@@ -250,10 +293,22 @@ impl SoftwareRenderer {
         let dc_iscale = vis.x_iscale.abs();
         let dc_texmid = vis.texture_mid;
         let mut frac = vis.start_frac;
-        let colourmap = if vis.mobj_flags & MapObjFlag::Shadow as u32 != 0 {
+        let shadow = vis.mobj_flags & MapObjFlag::Shadow as u32 != 0;
+        let colourmap = if shadow {
             pic_data.colourmap(33)
         } else {
-            pic_data.vert_light_colourmap(vis.light_level, vis.scale)
+            pic_data.vert_light_colourmap(vis.light_level, vis.scale, vis.colourmap_set)
+        };
+        let tranmap = if !shadow && vis.mobj_flags & MapObjFlag::Translucent as u32 != 0 {
+            Some(pic_data.tranmap())
+        } else {
+            None
+        };
+        let translation = match (vis.mobj_flags & MapObjFlag::Translation as u32)
+            >> MapObjFlag::Transshift as u32
+        {
+            0 => None,
+            n => Some(&TRANSLATIONS[n as usize - 1]),
         };
 
         let xfrac = vis.x_iscale * self.y_scale; // proportional to x1..x2
@@ -264,7 +319,7 @@ impl SoftwareRenderer {
             }
 
             let texture_column = &patch.data[tex_column];
-            let mut top = ((pixels.size().half_height_f32() - dc_texmid * spryscale) + 1.0).round();
+            let mut top = ((centery - dc_texmid * spryscale) + 1.0).round();
             let mut bottom = top + (spryscale * texture_column.len() as f32).round();
 
             if bottom >= clip_bottom[x] {
@@ -279,12 +334,15 @@ impl SoftwareRenderer {
                 draw_masked_column(
                     texture_column,
                     colourmap,
-                    vis.mobj_flags & MapObjFlag::Shadow as u32 != 0,
+                    translation,
+                    tranmap,
+                    shadow.then_some(&mut self.fuzz_random),
                     dc_iscale,
                     x,
                     dc_texmid,
                     top,
                     bottom,
+                    centery,
                     pic_data,
                     pixels,
                 );
@@ -372,7 +430,8 @@ impl SoftwareRenderer {
             }
         }
 
-        self.draw_vissprite(vis, &clip_bottom, &clip_top, pic_data, pixels);
+        let centery = self.seg_renderer.centery;
+        self.draw_vissprite(vis, &clip_bottom, &clip_top, centery, pic_data, pixels);
     }
 
     fn draw_player_sprites(
@@ -384,10 +443,18 @@ impl SoftwareRenderer {
         if let Some(mobj) = player.mobj() {
             let light = mobj.subsector.sector.lightlevel;
             let light = (light >> 4) + player.extralight;
+            let colourmap_set = mobj.subsector.sector.colourmap_for_view(player.viewz);
 
             for sprite in player.psprites.iter() {
                 if sprite.state.is_some() {
-                    self.draw_player_sprite(sprite, light, mobj.flags, pic_data, pixels);
+                    self.draw_player_sprite(
+                        sprite,
+                        light,
+                        colourmap_set,
+                        mobj.flags,
+                        pic_data,
+                        pixels,
+                    );
                 }
             }
         }
@@ -397,6 +464,7 @@ impl SoftwareRenderer {
         &mut self,
         sprite: &PspDef,
         light: usize,
+        colourmap_set: usize,
         flags: u32,
         pic_data: &PicData,
         pixels: &mut dyn PixelBuffer,
@@ -432,7 +500,8 @@ impl SoftwareRenderer {
         }
 
         let mut vis = VisSprite::new();
-        vis.mobj_flags = flags;
+        // the weapon is not translated
+        vis.mobj_flags = flags & !(MapObjFlag::Translation as u32);
         vis.patch = frame.lump[0] as u32 as usize;
         // -(sprite.sy.floor() - patch.top_offset as f32);
         vis.texture_mid = 100.0 - (sprite.sy - patch.top_offset as f32);
@@ -444,6 +513,7 @@ impl SoftwareRenderer {
         };
         vis.scale = pspritescale;
         vis.light_level = light + 2;
+        vis.colourmap_set = colourmap_set;
 
         if flip != 0 {
             vis.x_iscale = -pspriteiscale;
@@ -459,7 +529,9 @@ impl SoftwareRenderer {
 
         let clip_bottom = vec![0.0; pixels.size().width_usize()];
         let clip_top = vec![pixels.size().height_f32(); pixels.size().width_usize()];
-        self.draw_vissprite(&vis, &clip_top, &clip_bottom, pic_data, pixels)
+        // The weapon doesn't move with the view pitch
+        let centery = pixels.size().half_height_f32();
+        self.draw_vissprite(&vis, &clip_top, &clip_bottom, centery, pic_data, pixels)
     }
 
     pub(crate) fn draw_masked(
@@ -506,6 +578,10 @@ impl SoftwareRenderer {
             let texnum = unsafe { seg.sidedef.midtexture.unwrap_unchecked() };
 
             let wall_lights = (seg.sidedef.sector.lightlevel >> 4) + player.extralight;
+            let colourmap_set = player
+                .mobj()
+                .map_or(0, |m| m.subsector.sector.colourmap_for_view(player.viewz));
+            let tranmap = seg.linedef.translucent.then(|| pic_data.tranmap());
 
             let rw_scalestep = ds.scalestep;
             // TODO: hmmmm 0.05
@@ -568,7 +644,7 @@ impl SoftwareRenderer {
                     }
 
                     // calculate unclipped screen coordinates for post
-                    let sprtopscreen = pixels.size().half_height_f32() - dc_texturemid * spryscale;
+                    let sprtopscreen = self.seg_renderer.centery - dc_texturemid * spryscale;
                     let mut top = sprtopscreen.round(); // TODO: possible glitch
                     let mut bottom = top + 1.0 + (spryscale * texture_column.len() as f32).round();
 
@@ -581,13 +657,16 @@ impl SoftwareRenderer {
 
                     draw_masked_column(
                         texture_column,
-                        pic_data.vert_light_colourmap(wall_lights, spryscale),
-                        false,
+                        pic_data.vert_light_colourmap(wall_lights, spryscale, colourmap_set),
+                        None,
+                        tranmap,
+                        None,
                         1.0 / spryscale,
                         x,
                         dc_texturemid,
                         top,
                         bottom,
+                        self.seg_renderer.centery,
                         pic_data,
                         pixels,
                     );
@@ -604,12 +683,15 @@ impl SoftwareRenderer {
 fn draw_masked_column(
     texture_column: &[usize],
     colourmap: &[usize],
-    fuzz: bool,
+    translation: Option<&[usize; 256]>,
+    tranmap: Option<&[u8]>,
+    mut fuzz: Option<&mut Random>,
     fracstep: f32,
     dc_x: usize,
     dc_texturemid: f32,
     yl: f32,
     mut yh: f32,
+    centery: f32,
     pic_data: &PicData,
     pixels: &mut dyn PixelBuffer,
 ) {
@@ -617,19 +699,31 @@ fn draw_masked_column(
         yh = pixels.size().height_f32() - 1.0;
     }
     let pal = pic_data.palette();
-    let mut frac = dc_texturemid + (yl - pixels.size().half_height_f32()) * fracstep;
+    let mut frac = dc_texturemid + (yl - centery) * fracstep;
     for y in yl as u32 as usize..=yh as u32 as usize {
         let select = frac as u32 as usize;
         if select >= texture_column.len() {
             return;
         }
         // Transparency
-        if texture_column[select] == usize::MAX || (fuzz && p_random() % 3 != 0) {
+        if texture_column[select] == usize::MAX
+            || fuzz.as_mut().is_some_and(|r| r.m_random() % 3 != 0)
+        {
             frac += fracstep;
             continue;
         }
-        let c = pal[colourmap[texture_column[select]]];
-        pixels.set_pixel(dc_x, y, &c.0);
+        let fg = match translation {
+            Some(translation) => colourmap[translation[texture_column[select]]],
+            None => colourmap[texture_column[select]],
+        };
+        let c = if let Some(tranmap) = tranmap {
+            // Blend with what is already drawn
+            let bg = pixels.read_palette_index(dc_x, y) as usize;
+            tranmap[(bg << 8) + fg] as usize
+        } else {
+            fg
+        };
+        pixels.set_palette_pixel(dc_x, y, c as u8, &pal[c].0);
         frac += fracstep;
     }
 }
@@ -8,7 +8,9 @@ use log::{debug, info};
 use sdl2::audio::{AudioCVT, AudioFormat};
 use sdl2::mixer::{Chunk, InitFlag, Music, Sdl2MixerContext, AUDIO_S16LSB, DEFAULT_CHANNELS};
 use sdl2::AudioSubsystem;
-use sound_traits::{InitResult, SfxName, SoundAction, SoundServer, SoundServerTic, MUS_DATA};
+use sound_traits::{
+    InitResult, MusData, SfxName, SoundAction, SoundServer, SoundServerTic, MUS_DATA
+};
 use wad::WadData;
 
 use crate::info::SFX_INFO_BASE;
@@ -123,7 +125,13 @@ pub struct Snd<'a> {
     rx: SndServerRx,
     tx: SndServerTx,
     chunks: Vec<SfxInfo>,
+    /// Must be declared before `mus_data` and `extra_music` so that it is
+    /// dropped first, as it may borrow from them
     music: Option<Music<'a>>,
+    /// The MIDI data of each `MusTrack`, set once by `new()`
+    mus_data: [MusData; 68],
+    /// Converted music that is not in `mus_data`, kept while it is playing
+    extra_music: Option<Box<[u8]>>,
     listener: SoundObject<SfxName>,
     sources: [SoundObject<SfxName>; MIXER_CHANNELS as usize],
     sfx_vol: i32,
@@ -164,23 +172,21 @@ impl<'a> Snd<'a> {
         info!("Initialised {} sfx", chunks.len());
 
         let mut mus_count = 0;
-        unsafe {
-            // TODO: make function unsafe to call instead to reflect the static mut
-            for mus in MUS_DATA.iter_mut() {
-                if let Some(lump) = wad.get_lump(mus.lump_name().as_str()) {
-                    if lump.data[..4] == MUS_ID {
-                        if let Some(res) = read_mus_to_midi(&lump.data) {
-                            mus.set_data(res);
-                            mus_count += 1;
-                        }
-                    } else if lump.data[..4] == MID_ID {
-                        // It's MIDI
-                        mus.set_data(lump.data.clone());
+        let mut mus_data = MUS_DATA;
+        for mus in mus_data.iter_mut() {
+            if let Some(lump) = wad.get_lump(mus.lump_name().as_str()) {
+                if lump.data[..4] == MUS_ID {
+                    if let Some(res) = read_mus_to_midi(&lump.data) {
+                        mus.set_data(res);
                         mus_count += 1;
                     }
-                } else {
-                    debug!("{} is missing", mus.lump_name().as_str());
+                } else if lump.data[..4] == MID_ID {
+                    // It's MIDI
+                    mus.set_data(lump.data.clone());
+                    mus_count += 1;
                 }
+            } else {
+                debug!("{} is missing", mus.lump_name().as_str());
             }
         }
         info!("Initialised {} midi songs", mus_count);
@@ -193,6 +199,8 @@ impl<'a> Snd<'a> {
             tx,
             chunks,
             music: None,
+            mus_data,
+            extra_music: None,
             listener: SoundObject::default(),
             sources: [SoundObject::default(); MIXER_CHANNELS as usize],
             sfx_vol: 64,
@@ -349,14 +357,16 @@ impl<'a> SoundServer<SfxName, usize, sdl2::Error> for Snd<'a> {
     }
 
     fn start_music(&mut self, music: usize, looping: bool) {
-        unsafe {
-            if let Ok(music) = Music::from_static_bytes(MUS_DATA[music].data())
-                .map_err(|e| log::error!("MUS: {}, error: {e}", MUS_DATA[music].lump_name()))
-            {
-                music.play(if looping { -1 } else { 0 }).unwrap();
-                self.music = Some(music);
-                Music::set_volume(self.mus_vol);
-            }
+        let mus = &self.mus_data[music];
+        // The data is never changed after `new()`, and `self.music` is dropped
+        // before `self.mus_data`
+        let data: &'static [u8] = unsafe { &*(mus.data() as *const [u8]) };
+        if let Ok(music) = Music::from_static_bytes(data)
+            .map_err(|e| log::error!("MUS: {}, error: {e}", mus.lump_name()))
+        {
+            music.play(if looping { -1 } else { 0 }).unwrap();
+            self.music = Some(music);
+            Music::set_volume(self.mus_vol);
         }
     }
 
@@ -371,9 +381,39 @@ impl<'a> SoundServer<SfxName, usize, sdl2::Error> for Snd<'a> {
     fn change_music(&mut self, music: usize, looping: bool) {
         Music::halt();
         self.music.take();
+        self.extra_music.take();
         self.start_music(music, looping)
     }
 
+    fn change_music_data(&mut self, data: Vec<u8>, looping: bool) {
+        Music::halt();
+        self.music.take();
+        self.extra_music.take();
+
+        let midi = if data.len() >= 4 && data[..4] == MUS_ID {
+            read_mus_to_midi(&data)
+        } else if data.len() >= 4 && data[..4] == MID_ID {
+            Some(data)
+        } else {
+            None
+        };
+        let Some(midi) = midi else {
+            log::error!("Music data is not MUS or MIDI");
+            return;
+        };
+        let midi = self.extra_music.insert(midi.into_boxed_slice());
+        // The buffer is only replaced after `self.music` is dropped, and
+        // `self.music` is dropped before `self.extra_music`
+        let midi: &'static [u8] = unsafe { &*(&**midi as *const [u8]) };
+
+        if let Ok(music) = Music::from_static_bytes(midi).map_err(|e| log::error!("MUS error: {e}"))
+        {
+            music.play(if looping { -1 } else { 0 }).unwrap();
+            self.music = Some(music);
+            Music::set_volume(self.mus_vol);
+        }
+    }
+
     fn stop_music(&mut self) {
         Music::halt();
     }
@@ -415,13 +455,12 @@ mod tests {
     fn write_map_mus_data() {
         let wad = WadData::new("../doom1.wad".into());
 
-        unsafe {
-            for mus in MUS_DATA.iter_mut() {
-                if let Some(lump) = wad.get_lump(mus.lump_name().as_str()) {
-                    dbg!(mus.lump_name());
-                    let res = read_mus_to_midi(&lump.data).unwrap();
-                    mus.set_data(res);
-                }
+        let mut mus_data = MUS_DATA;
+        for mus in mus_data.iter_mut() {
+            if let Some(lump) = wad.get_lump(mus.lump_name().as_str()) {
+                dbg!(mus.lump_name());
+                let res = read_mus_to_midi(&lump.data).unwrap();
+                mus.set_data(res);
             }
         }
 
@@ -439,7 +478,8 @@ mod tests {
         // simultaneously.
         sdl2::mixer::allocate_channels(16);
 
-        let music = unsafe { sdl2::mixer::Music::from_static_bytes(MUS_DATA[1].data()).unwrap() };
+        let data: &'static [u8] = Box::leak(mus_data[1].data().to_vec().into_boxed_slice());
+        let music = sdl2::mixer::Music::from_static_bytes(data).unwrap();
 
         println!("music => {:?}", music);
         println!("music type => {:?}", music.get_type());
@@ -13,6 +13,9 @@ pub use music::*;
 /// `S` is SFX enum, `M` is Music enum, `E` is Errors
 pub type InitResult<S, M, E> = Result<Sender<SoundAction<S, M>>, E>;
 
+/// The channel the game sends Doom's `SoundAction`s down to a sound server
+pub type SndServerTx = Sender<SoundAction<SfxName, usize>>;
+
 pub enum SoundAction<S: Debug + Copy, M: Debug> {
     StartSfx {
         /// Objects unique ID or hash. This should be used to track which
@@ -47,6 +50,9 @@ pub enum SoundAction<S: Debug + Copy, M: Debug> {
     PauseMusic,
     ResumeMusic,
     ChangeMusic(M, bool),
+    /// Raw MUS or MIDI lump data and looping/not. Used for music that is not
+    /// one of the `M` tracks, such as music set by `UMAPINFO`
+    ChangeMusicData(Vec<u8>, bool),
     StopMusic,
     Shutdown,
 }
@@ -88,6 +94,9 @@ where
 
     fn change_music(&mut self, music: M, looping: bool);
 
+    /// Change to music from MUS or MIDI lump data
+    fn change_music_data(&mut self, data: Vec<u8>, looping: bool);
+
     fn stop_music(&mut self);
 
     fn set_mus_volume(&mut self, volume: i32);
@@ -129,6 +138,9 @@ where
                 SoundAction::PauseMusic => self.pause_music(),
                 SoundAction::ResumeMusic => self.resume_music(),
                 SoundAction::ChangeMusic(music, looping) => self.change_music(music, looping),
+                SoundAction::ChangeMusicData(data, looping) => {
+                    self.change_music_data(data, looping)
+                }
                 SoundAction::StopMusic => self.stop_music(),
                 SoundAction::SfxVolume(v) => self.set_sfx_volume(v),
                 SoundAction::MusicVolume(v) => self.set_mus_volume(v),
@@ -217,6 +229,8 @@ mod tests {
 
         fn change_music(&mut self, _music: Music, _looping: bool) {}
 
+        fn change_music_data(&mut self, _data: Vec<u8>, _looping: bool) {}
+
         fn stop_music(&mut self) {}
 
         fn set_mus_volume(&mut self, _volume: i32) {}
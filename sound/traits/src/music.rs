@@ -25,9 +25,9 @@ impl MusData {
     }
 }
 
-/// Requires the user to initialise the data for each `MusData`. This is unsafe
-/// and should be done as part of the startup code.
-pub static mut MUS_DATA: [MusData; 68] = [
+/// The `MusData` of every `MusTrack`, in order and without data. Each sound
+/// server takes its own copy and fills in the data from the WAD.
+pub const MUS_DATA: [MusData; 68] = [
     MusData::new(""),
     MusData::new("e1m1"),
     MusData::new("e1m2"),
@@ -1,7 +1,7 @@
 use std::f32::consts::{FRAC_PI_4, PI};
 
 use crate::PlayerStatus;
-use gamestate_traits::{m_random, PlayerCheat, PowerType, WeaponType, TICRATE};
+use gamestate_traits::{PlayerCheat, PowerType, Random, WeaponType, TICRATE};
 use wad::types::{WadPatch, WAD_PATCH};
 use wad::WadData;
 
@@ -39,6 +39,7 @@ pub(crate) struct DoomguyFace {
     old_health: i32,
     last_pain_calc: i32,
     last_attack_down: i32,
+    random: Random,
     rand: i32,
     priority: i32,
 }
@@ -90,13 +91,14 @@ impl DoomguyFace {
             old_health: -1,
             last_pain_calc: 0,
             last_attack_down: -1,
+            random: Random::new(),
             rand: 0,
             priority: 0,
         }
     }
 
     pub(crate) fn tick(&mut self, status: &PlayerStatus) {
-        self.rand = m_random();
+        self.rand = self.random.m_random();
         self.update_face(status);
         //self.old_health = status.health;
         self.old_weapons_owned = status.weaponowned;
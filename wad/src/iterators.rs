@@ -241,6 +241,22 @@ impl WadData {
         }
     }
 
+    /// Iterate over the BOOM colourmap lumps found between `C_START` and
+    /// `C_END` markers. Each lump has the same layout as `COLORMAP`.
+    pub fn colourmap_lumps_iter(&self) -> impl Iterator<Item = &Lump> {
+        let mut inside = false;
+        self.lumps.iter().filter(move |lump| {
+            if lump.name == "C_START" {
+                inside = true;
+                return false;
+            } else if lump.name == "C_END" {
+                inside = false;
+                return false;
+            }
+            inside && !lump.data.is_empty()
+        })
+    }
+
     pub fn pnames_iter(&self) -> OffsetIter<String, impl Fn(usize) -> String + '_> {
         let info = self.find_lump_or_panic("PNAMES");
         let item_size = 8;
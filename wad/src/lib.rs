@@ -44,3 +44,6 @@ pub mod types;
 
 /// ZDoom BSP support (and maybe others in future)
 pub mod extended;
+
+/// Parsing of the `UMAPINFO` map definitions
+pub mod umapinfo;
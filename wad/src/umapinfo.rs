@@ -0,0 +1,545 @@
+//! Parsing of the `UMAPINFO` lump. The full specification is in
+//! `misc/umapinfo.md`.
+//!
+//! Each `MAP` block is parsed in to a `MapInfo`. Keys that are not given are
+//! left as `None` so that the engine falls back to its hard-coded defaults.
+//! Where a key accepts `clear`, this is stored as `Some` of an empty value.
+
+use std::error::Error;
+use std::fmt;
+
+use log::warn;
+
+use crate::WadData;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UMapInfoError {
+    /// Line number and a description
+    Syntax(usize, String),
+}
+
+impl Error for UMapInfoError {}
+
+impl fmt::Display for UMapInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UMapInfoError::Syntax(line, m) => write!(f, "UMAPINFO line {line}: {m}"),
+        }
+    }
+}
+
+/// An entry for the episode selection menu
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Episode {
+    /// The map the episode starts on
+    pub map_name: String,
+    pub patch: String,
+    pub name: String,
+    pub key: char,
+}
+
+/// A boss death action, run when all things of `thing_type` are dead
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BossAction {
+    /// The ZDoom class name of the thing, such as `BaronOfHell`
+    pub thing_type: String,
+    pub line_special: i16,
+    pub tag: i16,
+}
+
+/// The `UMAPINFO` settings for a single map
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MapInfo {
+    /// Name in the format of the IWAD, `MAPxx` or `ExMy`
+    pub map_name: String,
+    pub level_name: Option<String>,
+    /// `Some("")` if the label is cleared
+    pub label: Option<String>,
+    pub author: Option<String>,
+    pub level_pic: Option<String>,
+    pub next: Option<String>,
+    pub next_secret: Option<String>,
+    pub sky_texture: Option<String>,
+    pub music: Option<String>,
+    pub exit_pic: Option<String>,
+    pub enter_pic: Option<String>,
+    /// Par time in seconds
+    pub par_time: Option<i32>,
+    pub end_game: Option<bool>,
+    pub end_pic: Option<String>,
+    pub end_bunny: bool,
+    pub end_cast: bool,
+    pub no_intermission: bool,
+    /// Lines are joined with `\n`. `Some("")` if the text is cleared
+    pub inter_text: Option<String>,
+    /// Lines are joined with `\n`. `Some("")` if the text is cleared
+    pub inter_text_secret: Option<String>,
+    pub inter_backdrop: Option<String>,
+    pub inter_music: Option<String>,
+    /// `Some` if boss actions override the map defaults. Empty if cleared
+    pub boss_actions: Option<Vec<BossAction>>,
+}
+
+impl MapInfo {
+    /// `Some(true)` if this map ends the game, `Some(false)` if a default game
+    /// end is overridden, or `None` to use the default.
+    pub fn ends_game(&self) -> Option<bool> {
+        if self.end_pic.is_some() || self.end_bunny || self.end_cast {
+            return Some(true);
+        }
+        self.end_game
+    }
+
+    /// The intermission text to show after the map, if any. Secret exits never
+    /// fall back to the normal text. An empty string disables the default.
+    pub fn inter_text(&self, secret_exit: bool) -> Option<&str> {
+        if secret_exit {
+            self.inter_text_secret.as_deref()
+        } else {
+            self.inter_text.as_deref()
+        }
+    }
+}
+
+/// Convert a map name to the episode and map numbers. `MAPxx` names are
+/// returned as episode 1.
+pub fn map_name_to_num(name: &str) -> Option<(usize, usize)> {
+    let name = name.to_ascii_uppercase();
+    if let Some(num) = name.strip_prefix("MAP") {
+        return num.parse().ok().map(|m| (1, m));
+    }
+    let rest = name.strip_prefix('E')?;
+    let (ep, map) = rest.split_once('M')?;
+    Some((ep.parse().ok()?, map.parse().ok()?))
+}
+
+/// All the map entries of the `UMAPINFO` lumps in a wad
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UMapInfo {
+    pub maps: Vec<MapInfo>,
+    /// Set if any map cleared the default episode menu
+    pub episodes_cleared: bool,
+    /// Episodes added to the menu, in order of definition
+    pub episodes: Vec<Episode>,
+}
+
+impl UMapInfo {
+    /// Parse a `UMAPINFO` lump. Later entries for the same map replace earlier
+    /// ones.
+    pub fn parse(text: &str) -> Result<Self, UMapInfoError> {
+        let mut info = Self::default();
+        let mut tokens = Tokens::new(text);
+
+        while let Some(tok) = tokens.next()? {
+            match tok {
+                Token::Ident(ref s) if s.eq_ignore_ascii_case("MAP") => {}
+                t => return Err(tokens.error(format!("expected MAP, found {t:?}"))),
+            }
+            let map_name = match tokens.next()? {
+                Some(Token::Ident(s)) | Some(Token::Str(s)) => s.to_ascii_uppercase(),
+                t => return Err(tokens.error(format!("expected a map name, found {t:?}"))),
+            };
+            tokens.expect(Token::Symbol('{'))?;
+
+            let mut map = MapInfo {
+                map_name,
+                ..Default::default()
+            };
+            loop {
+                let key = match tokens.next()? {
+                    Some(Token::Symbol('}')) => break,
+                    Some(Token::Ident(s)) => s.to_ascii_lowercase(),
+                    t => return Err(tokens.error(format!("expected a key, found {t:?}"))),
+                };
+                tokens.expect(Token::Symbol('='))?;
+                let values = tokens.values()?;
+                info.set_key(&mut map, &key, values, tokens.line)?;
+            }
+
+            if let Some(old) = info.maps.iter_mut().find(|m| m.map_name == map.map_name) {
+                *old = map;
+            } else {
+                info.maps.push(map);
+            }
+        }
+        Ok(info)
+    }
+
+    fn set_key(
+        &mut self,
+        map: &mut MapInfo,
+        key: &str,
+        values: Vec<Token>,
+        line: usize,
+    ) -> Result<(), UMapInfoError> {
+        let err = |m: &str| UMapInfoError::Syntax(line, format!("{key}: {m}"));
+        let clear = values.len() == 1 && values[0].is_clear();
+        let string = || match values.first() {
+            Some(Token::Str(s)) | Some(Token::Ident(s)) => Ok(s.clone()),
+            _ => Err(err("expected a string")),
+        };
+        let lump = || string().map(|s| s.to_ascii_uppercase());
+        let boolean = || match values.first() {
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("true") => Ok(true),
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("false") => Ok(false),
+            _ => Err(err("expected true or false")),
+        };
+        let text = || {
+            if clear {
+                return Ok(String::new());
+            }
+            values
+                .iter()
+                .map(|v| match v {
+                    Token::Str(s) => Ok(s.as_str()),
+                    _ => Err(err("expected a string")),
+                })
+                .collect::<Result<Vec<&str>, _>>()
+                .map(|l| l.join("\n"))
+        };
+
+        match key {
+            "levelname" => map.level_name = Some(string()?),
+            "label" => map.label = Some(if clear { String::new() } else { string()? }),
+            "author" => map.author = Some(string()?),
+            "levelpic" => map.level_pic = Some(lump()?),
+            "next" => map.next = Some(lump()?),
+            "nextsecret" => map.next_secret = Some(lump()?),
+            "skytexture" => map.sky_texture = Some(lump()?),
+            "music" => map.music = Some(lump()?),
+            "exitpic" => map.exit_pic = Some(lump()?),
+            "enterpic" => map.enter_pic = Some(lump()?),
+            "partime" => match values.first() {
+                Some(Token::Num(n)) => map.par_time = Some(*n),
+                _ => return Err(err("expected a number")),
+            },
+            "endgame" => map.end_game = Some(boolean()?),
+            "endpic" => map.end_pic = Some(lump()?),
+            "endbunny" => map.end_bunny = boolean()?,
+            "endcast" => map.end_cast = boolean()?,
+            "nointermission" => map.no_intermission = boolean()?,
+            "intertext" => map.inter_text = Some(text()?),
+            "intertextsecret" => map.inter_text_secret = Some(text()?),
+            "interbackdrop" => map.inter_backdrop = Some(lump()?),
+            "intermusic" => map.inter_music = Some(lump()?),
+            "episode" => {
+                if clear {
+                    self.episodes_cleared = true;
+                    self.episodes.clear();
+                } else {
+                    let mut strings = values.iter().filter_map(|v| match v {
+                        Token::Str(s) => Some(s.clone()),
+                        _ => None,
+                    });
+                    let patch = strings.next().ok_or_else(|| err("missing patch"))?;
+                    let name = strings.next().unwrap_or_default();
+                    let key = strings
+                        .next()
+                        .and_then(|k| k.chars().next())
+                        .unwrap_or_default()
+                        .to_ascii_uppercase();
+                    self.episodes.push(Episode {
+                        map_name: map.map_name.clone(),
+                        patch: patch.to_ascii_uppercase(),
+                        name,
+                        key,
+                    });
+                }
+            }
+            "bossaction" => {
+                let actions = map.boss_actions.get_or_insert_with(Vec::new);
+                if clear {
+                    actions.clear();
+                } else {
+                    match values.as_slice() {
+                        [Token::Ident(thing), Token::Num(special), Token::Num(tag)] => {
+                            actions.push(BossAction {
+                                thing_type: thing.clone(),
+                                line_special: *special as i16,
+                                tag: *tag as i16,
+                            });
+                        }
+                        _ => return Err(err("expected thingtype, linespecial, tag")),
+                    }
+                }
+            }
+            _ => warn!("UMAPINFO: unknown key {key} in {}", map.map_name),
+        }
+        Ok(())
+    }
+
+    /// Find the entry for a map by name, e.g, `MAP01` or `E1M1`
+    pub fn get(&self, map_name: &str) -> Option<&MapInfo> {
+        self.maps
+            .iter()
+            .find(|m| m.map_name.eq_ignore_ascii_case(map_name))
+    }
+}
+
+impl WadData {
+    /// Parse all `UMAPINFO` lumps in load order. Entries from later wads
+    /// replace those of earlier ones.
+    pub fn umapinfo(&self) -> UMapInfo {
+        let mut info = UMapInfo::default();
+        for lump in self.lumps.iter().filter(|l| l.name == "UMAPINFO") {
+            let text = String::from_utf8_lossy(&lump.data);
+            match UMapInfo::parse(&text) {
+                Ok(other) => {
+                    for map in other.maps {
+                        if let Some(old) = info.maps.iter_mut().find(|m| m.map_name == map.map_name)
+                        {
+                            *old = map;
+                        } else {
+                            info.maps.push(map);
+                        }
+                    }
+                    if other.episodes_cleared {
+                        info.episodes_cleared = true;
+                        info.episodes.clear();
+                    }
+                    info.episodes.extend(other.episodes);
+                }
+                Err(e) => warn!("{e}"),
+            }
+        }
+        info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(i32),
+    Symbol(char),
+}
+
+impl Token {
+    fn is_clear(&self) -> bool {
+        matches!(self, Token::Ident(s) if s.eq_ignore_ascii_case("clear"))
+    }
+}
+
+struct Tokens<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    peeked: Option<Token>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            peeked: None,
+        }
+    }
+
+    fn error(&self, m: String) -> UMapInfoError {
+        UMapInfoError::Syntax(self.line, m)
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), UMapInfoError> {
+        match self.next()? {
+            Some(t) if t == token => Ok(()),
+            t => Err(self.error(format!("expected {token:?}, found {t:?}"))),
+        }
+    }
+
+    /// Read a comma separated list of values
+    fn values(&mut self) -> Result<Vec<Token>, UMapInfoError> {
+        let mut values = Vec::new();
+        loop {
+            match self.next()? {
+                Some(t @ Token::Symbol(_)) => {
+                    return Err(self.error(format!("expected a value, found {t:?}")))
+                }
+                Some(t) => values.push(t),
+                None => return Err(self.error("unexpected end of lump".to_string())),
+            }
+            match self.next()? {
+                Some(Token::Symbol(',')) => {}
+                t => {
+                    self.peeked = t;
+                    return Ok(values);
+                }
+            }
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                self.line += 1;
+                self.chars.next();
+            } else if c.is_whitespace() {
+                self.chars.next();
+            } else if c == '/' {
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                match ahead.next() {
+                    Some('/') => {
+                        for c in self.chars.by_ref() {
+                            if c == '\n' {
+                                self.line += 1;
+                                break;
+                            }
+                        }
+                    }
+                    Some('*') => {
+                        self.chars.next();
+                        self.chars.next();
+                        let mut last = ' ';
+                        for c in self.chars.by_ref() {
+                            if c == '\n' {
+                                self.line += 1;
+                            }
+                            if last == '*' && c == '/' {
+                                break;
+                            }
+                            last = c;
+                        }
+                    }
+                    _ => return,
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, UMapInfoError> {
+        if let Some(t) = self.peeked.take() {
+            return Ok(Some(t));
+        }
+        self.skip_whitespace_and_comments();
+        let Some(c) = self.chars.next() else {
+            return Ok(None);
+        };
+
+        if c == '"' {
+            let mut s = String::new();
+            loop {
+                match self.chars.next() {
+                    Some('"') => break,
+                    Some('\\') => {
+                        if let Some(c) = self.chars.next() {
+                            s.push(c);
+                        }
+                    }
+                    Some(c) => {
+                        if c == '\n' {
+                            self.line += 1;
+                        }
+                        s.push(c);
+                    }
+                    None => return Err(self.error("unterminated string".to_string())),
+                }
+            }
+            return Ok(Some(Token::Str(s)));
+        }
+
+        if c.is_ascii_digit() || c == '-' {
+            let mut s = c.to_string();
+            while let Some(&c) = self.chars.peek() {
+                if !c.is_ascii_digit() {
+                    break;
+                }
+                s.push(c);
+                self.chars.next();
+            }
+            return s
+                .parse()
+                .map(|n| Some(Token::Num(n)))
+                .map_err(|_| self.error(format!("invalid number {s}")));
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut s = c.to_string();
+            while let Some(&c) = self.chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                s.push(c);
+                self.chars.next();
+            }
+            return Ok(Some(Token::Ident(s)));
+        }
+
+        Ok(Some(Token::Symbol(c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_name_to_num, UMapInfo};
+
+    const LUMP: &str = r#"
+// A comment
+MAP E1M7
+{
+    levelname = "The Hidden Cave"
+    skytexture =  "sky2"
+    intertext = "You have beaten the shit",
+        "out of those big barons",
+        "and now must continue the fight."
+    partime = 120
+    bossaction = BaronOfHell, 23, 666
+}
+
+/* block
+   comment */
+map MAP01 {
+    next = "MAP05"
+    endgame = false
+    intertextsecret = clear
+    episode = clear
+    episode = "M_EPI1", "Knee-Deep", "k"
+}
+"#;
+
+    #[test]
+    fn parse_umapinfo() {
+        let info = UMapInfo::parse(LUMP).unwrap();
+        assert_eq!(info.maps.len(), 2);
+
+        let e1m7 = info.get("e1m7").unwrap();
+        assert_eq!(e1m7.level_name.as_deref(), Some("The Hidden Cave"));
+        assert_eq!(e1m7.sky_texture.as_deref(), Some("SKY2"));
+        assert_eq!(e1m7.par_time, Some(120));
+        assert_eq!(
+            e1m7.inter_text(false),
+            Some(
+                "You have beaten the shit\nout of those big barons\nand now must continue the \
+                 fight."
+            )
+        );
+        let boss = &e1m7.boss_actions.as_ref().unwrap()[0];
+        assert_eq!(boss.thing_type, "BaronOfHell");
+        assert_eq!((boss.line_special, boss.tag), (23, 666));
+
+        let map01 = info.get("MAP01").unwrap();
+        assert_eq!(map01.next.as_deref(), Some("MAP05"));
+        assert_eq!(map01.ends_game(), Some(false));
+        assert_eq!(map01.inter_text(true), Some(""));
+        assert_eq!(map01.inter_text(false), None);
+
+        assert!(info.episodes_cleared);
+        assert_eq!(info.episodes[0].map_name, "MAP01");
+        assert_eq!(info.episodes[0].key, 'K');
+    }
+
+    #[test]
+    fn parse_umapinfo_errors() {
+        assert!(UMapInfo::parse("MAP MAP01 { next = }").is_err());
+        assert!(UMapInfo::parse("MAP MAP01 { levelname \"x\" }").is_err());
+        assert!(UMapInfo::parse("MAP MAP01 { levelname = \"x").is_err());
+    }
+
+    #[test]
+    fn map_names() {
+        assert_eq!(map_name_to_num("MAP07"), Some((1, 7)));
+        assert_eq!(map_name_to_num("e2m10"), Some((2, 10)));
+        assert_eq!(map_name_to_num("START"), None);
+    }
+}